@@ -0,0 +1,25 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+use std::env;
+use std::path::Path;
+
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+
+    // the header is checked in; this only rewrites it if the API changed.
+    cbindgen::generate(&crate_dir)
+        .expect("cannot generate C header")
+        .write_to_file(Path::new(&crate_dir).join("include").join("graviola.h"));
+
+    // tests/c_harness.rs needs to know whether it can run compiled C.
+    for var in ["HOST", "TARGET"] {
+        println!(
+            "cargo:rustc-env=GRAVIOLA_CAPI_{var}={}",
+            env::var(var).unwrap()
+        );
+    }
+
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src");
+}
@@ -0,0 +1,358 @@
+/* Written for Graviola by Joe Birr-Pixton, 2024.
+ * SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+ *
+ * This file is generated by cbindgen from the graviola-capi crate.
+ * Do not edit it by hand.
+ */
+
+#ifndef GRAVIOLA_H
+#define GRAVIOLA_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Length of the nonce for all supported AEADs.
+#define GRAVIOLA_AEAD_NONCE_LEN 12
+
+// Length of the authentication tag for all supported AEADs.
+#define GRAVIOLA_AEAD_TAG_LEN 16
+
+// Largest output of any supported hash function (or HMAC).
+#define GRAVIOLA_HASH_MAX_OUTPUT_LEN 64
+
+// Largest public key of any supported key agreement algorithm.
+#define GRAVIOLA_KX_MAX_PUBLIC_KEY_LEN 97
+
+// Largest shared secret of any supported key agreement algorithm.
+#define GRAVIOLA_KX_MAX_SHARED_SECRET_LEN 48
+
+// Largest signature made by any supported key (an 8192-bit RSA key).
+#define GRAVIOLA_MAX_SIGNATURE_LEN 1024
+
+// A hash function.
+typedef enum graviola_hash_algorithm {
+  // SHA-256, with 32 bytes of output.
+  GRAVIOLA_HASH_ALGORITHM_SHA256 = 1,
+  // SHA-384, with 48 bytes of output.
+  GRAVIOLA_HASH_ALGORITHM_SHA384 = 2,
+  // SHA-512, with 64 bytes of output.
+  GRAVIOLA_HASH_ALGORITHM_SHA512 = 3,
+} graviola_hash_algorithm;
+
+// A key agreement algorithm.
+typedef enum graviola_kx_algorithm {
+  // X25519.  Public keys and shared secrets are 32 bytes.
+  GRAVIOLA_KX_ALGORITHM_X25519 = 1,
+  // ECDH on P-256.  Public keys are 65 bytes (X9.62 uncompressed),
+  // and shared secrets are 32 bytes.
+  GRAVIOLA_KX_ALGORITHM_P256 = 2,
+  // ECDH on P-384.  Public keys are 97 bytes (X9.62 uncompressed),
+  // and shared secrets are 48 bytes.
+  GRAVIOLA_KX_ALGORITHM_P384 = 3,
+} graviola_kx_algorithm;
+
+// A signature scheme.
+//
+// ECDSA signatures are DER-encoded ASN.1, as used in TLS and X.509.
+typedef enum graviola_signature_scheme {
+  // ECDSA on P-256, with SHA-256.
+  GRAVIOLA_SIGNATURE_SCHEME_ECDSA_P256_SHA256 = 1,
+  // ECDSA on P-384, with SHA-384.
+  GRAVIOLA_SIGNATURE_SCHEME_ECDSA_P384_SHA384 = 2,
+  // RSASSA-PKCS1-v1_5 with SHA-256.
+  GRAVIOLA_SIGNATURE_SCHEME_RSA_PKCS1_SHA256 = 3,
+  // RSASSA-PKCS1-v1_5 with SHA-384.
+  GRAVIOLA_SIGNATURE_SCHEME_RSA_PKCS1_SHA384 = 4,
+  // RSASSA-PKCS1-v1_5 with SHA-512.
+  GRAVIOLA_SIGNATURE_SCHEME_RSA_PKCS1_SHA512 = 5,
+  // RSASSA-PSS with SHA-256, MGF1-SHA-256 and a 32 byte salt.
+  GRAVIOLA_SIGNATURE_SCHEME_RSA_PSS_SHA256 = 6,
+  // RSASSA-PSS with SHA-384, MGF1-SHA-384 and a 48 byte salt.
+  GRAVIOLA_SIGNATURE_SCHEME_RSA_PSS_SHA384 = 7,
+  // RSASSA-PSS with SHA-512, MGF1-SHA-512 and a 64 byte salt.
+  GRAVIOLA_SIGNATURE_SCHEME_RSA_PSS_SHA512 = 8,
+} graviola_signature_scheme;
+
+// The result of every fallible function in this library.
+typedef enum graviola_status {
+  // Success.
+  GRAVIOLA_STATUS_OK = 0,
+  // Some input or output buffer was the wrong length.
+  GRAVIOLA_STATUS_WRONG_LENGTH = 1,
+  // A compressed elliptic curve point encoding was encountered.
+  GRAVIOLA_STATUS_NOT_UNCOMPRESSED = 2,
+  // A public key was invalid.
+  GRAVIOLA_STATUS_NOT_ON_CURVE = 3,
+  // A value was too small or large.
+  GRAVIOLA_STATUS_OUT_OF_RANGE = 4,
+  // The random number generator failed.
+  GRAVIOLA_STATUS_RNG_FAILED = 5,
+  // A deterministic random bit generator must be reseeded before further use.
+  GRAVIOLA_STATUS_RESEED_REQUIRED = 6,
+  // A signature or MAC was invalid.
+  GRAVIOLA_STATUS_BAD_SIGNATURE = 7,
+  // AEAD decryption failed: the tag, aad, ciphertext or nonce was wrong.
+  GRAVIOLA_STATUS_DECRYPT_FAILED = 8,
+  // An ASN.1 encoding was invalid.
+  GRAVIOLA_STATUS_ASN1_ERROR = 9,
+  // A key encoding was valid ASN.1, but not a supported key.
+  GRAVIOLA_STATUS_KEY_FORMAT_ERROR = 10,
+  // A required pointer argument was `NULL`.
+  GRAVIOLA_STATUS_NULL_POINTER = 100,
+  // The signature scheme is not usable with this key.
+  GRAVIOLA_STATUS_SCHEME_MISMATCH = 101,
+  // The library panicked.  This is a bug.
+  GRAVIOLA_STATUS_PANIC = 102,
+  // Any other error.
+  GRAVIOLA_STATUS_OTHER = 103,
+  // An algorithm or signature scheme argument was not a known value.
+  GRAVIOLA_STATUS_UNKNOWN_ALGORITHM = 104,
+} graviola_status;
+
+// An AES-GCM key.
+typedef struct graviola_aes_gcm graviola_aes_gcm;
+
+// A ChaCha20-Poly1305 key.
+typedef struct graviola_chacha20poly1305 graviola_chacha20poly1305;
+
+// An in-progress hash computation.
+typedef struct graviola_hash graviola_hash;
+
+// An in-progress HMAC computation.
+typedef struct graviola_hmac graviola_hmac;
+
+// An ephemeral private key for key agreement.
+typedef struct graviola_kx_private_key graviola_kx_private_key;
+
+// A private key for signing.
+typedef struct graviola_signing_key graviola_signing_key;
+
+// A public key for verifying signatures.
+typedef struct graviola_verifying_key graviola_verifying_key;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns a static, NUL-terminated description of `status`.
+//
+// `status` should be one of the values of [`graviola_status`].  Other
+// values give "unknown status".
+const char *graviola_status_message(uint32_t status);
+
+// Makes a new AES-GCM key from `key_len` bytes at `key`.
+//
+// `key_len` must be 16 (for AES-128) or 32 (for AES-256).
+enum graviola_status graviola_aes_gcm_new(const uint8_t *key,
+                                          size_t key_len,
+                                          struct graviola_aes_gcm **out);
+
+// Encrypts `inout_len` bytes at `inout` in place.
+//
+// `nonce` is [`GRAVIOLA_AEAD_NONCE_LEN`] bytes and must be unique for
+// this key.  `aad` is the additionally-authenticated data.  The tag
+// is written to `tag_out`, which is [`GRAVIOLA_AEAD_TAG_LEN`] bytes.
+enum graviola_status graviola_aes_gcm_encrypt(const struct graviola_aes_gcm *key,
+                                              const uint8_t *nonce,
+                                              const uint8_t *aad,
+                                              size_t aad_len,
+                                              uint8_t *inout,
+                                              size_t inout_len,
+                                              uint8_t *tag_out);
+
+// Decrypts and verifies `inout_len` bytes at `inout` in place.
+//
+// `nonce` and `aad` must match those used for encryption.  `tag` is
+// the purported authentication tag.  On failure,
+// [`graviola_status::DecryptFailed`] is returned and `inout` is
+// cleared.
+enum graviola_status graviola_aes_gcm_decrypt(const struct graviola_aes_gcm *key,
+                                              const uint8_t *nonce,
+                                              const uint8_t *aad,
+                                              size_t aad_len,
+                                              uint8_t *inout,
+                                              size_t inout_len,
+                                              const uint8_t *tag,
+                                              size_t tag_len);
+
+// Frees an AES-GCM key.
+void graviola_aes_gcm_free(struct graviola_aes_gcm *key);
+
+// Makes a new ChaCha20-Poly1305 key from `key_len` bytes at `key`.
+//
+// `key_len` must be 32.
+enum graviola_status graviola_chacha20poly1305_new(const uint8_t *key,
+                                                   size_t key_len,
+                                                   struct graviola_chacha20poly1305 **out);
+
+// Encrypts `inout_len` bytes at `inout` in place.
+//
+// See [`graviola_aes_gcm_encrypt()`] for details of the other arguments.
+enum graviola_status graviola_chacha20poly1305_encrypt(const struct graviola_chacha20poly1305 *key,
+                                                       const uint8_t *nonce,
+                                                       const uint8_t *aad,
+                                                       size_t aad_len,
+                                                       uint8_t *inout,
+                                                       size_t inout_len,
+                                                       uint8_t *tag_out);
+
+// Decrypts and verifies `inout_len` bytes at `inout` in place.
+//
+// See [`graviola_aes_gcm_decrypt()`] for details of the other arguments.
+enum graviola_status graviola_chacha20poly1305_decrypt(const struct graviola_chacha20poly1305 *key,
+                                                       const uint8_t *nonce,
+                                                       const uint8_t *aad,
+                                                       size_t aad_len,
+                                                       uint8_t *inout,
+                                                       size_t inout_len,
+                                                       const uint8_t *tag,
+                                                       size_t tag_len);
+
+// Frees a ChaCha20-Poly1305 key.
+void graviola_chacha20poly1305_free(struct graviola_chacha20poly1305 *key);
+
+// Starts a new hash computation using `algorithm`, which is a
+// [`graviola_hash_algorithm`] value.
+enum graviola_status graviola_hash_new(uint32_t algorithm, struct graviola_hash **out);
+
+// Adds `bytes_len` bytes at `bytes` to the computation.
+enum graviola_status graviola_hash_update(struct graviola_hash *hash,
+                                          const uint8_t *bytes,
+                                          size_t bytes_len);
+
+// Writes the hash of the input so far to `out`.
+//
+// `hash` is not changed, so further input may be added afterwards.
+enum graviola_status graviola_hash_finish(const struct graviola_hash *hash,
+                                          uint8_t *out,
+                                          size_t out_len,
+                                          size_t *written);
+
+// Frees a hash computation.
+void graviola_hash_free(struct graviola_hash *hash);
+
+// Starts a new HMAC computation using `algorithm`, which is a
+// [`graviola_hash_algorithm`] value, keyed with `key_len` bytes at `key`.
+enum graviola_status graviola_hmac_new(uint32_t algorithm,
+                                       const uint8_t *key,
+                                       size_t key_len,
+                                       struct graviola_hmac **out);
+
+// Adds `bytes_len` bytes at `bytes` to the computation.
+enum graviola_status graviola_hmac_update(struct graviola_hmac *hmac,
+                                          const uint8_t *bytes,
+                                          size_t bytes_len);
+
+// Writes the HMAC of the input so far to `out`.
+//
+// `hmac` is not changed, so further input may be added afterwards.
+enum graviola_status graviola_hmac_finish(const struct graviola_hmac *hmac,
+                                          uint8_t *out,
+                                          size_t out_len,
+                                          size_t *written);
+
+// Compares the HMAC of the input so far against `tag_len` bytes at
+// `tag`, in constant time.
+//
+// [`graviola_status::BadSignature`] is returned if they differ.
+// `tag` may not be truncated.
+enum graviola_status graviola_hmac_verify(const struct graviola_hmac *hmac,
+                                          const uint8_t *tag,
+                                          size_t tag_len);
+
+// Frees an HMAC computation.
+void graviola_hmac_free(struct graviola_hmac *hmac);
+
+// Generates a new random private key for `algorithm`, which is a
+// [`graviola_kx_algorithm`] value.
+enum graviola_status graviola_kx_private_key_generate(uint32_t algorithm,
+                                                      struct graviola_kx_private_key **out);
+
+// Writes the public key corresponding to `key` to `out`.
+enum graviola_status graviola_kx_private_key_public_key(const struct graviola_kx_private_key *key,
+                                                        uint8_t *out,
+                                                        size_t out_len,
+                                                        size_t *written);
+
+// Completes key agreement with the peer's public key, `peer_len` bytes
+// at `peer`, and writes the shared secret to `out`.
+//
+// `key` is consumed by this function (whether it succeeds or not), and
+// must not be used or freed afterwards.
+enum graviola_status graviola_kx_private_key_agree(struct graviola_kx_private_key *key,
+                                                   const uint8_t *peer,
+                                                   size_t peer_len,
+                                                   uint8_t *out,
+                                                   size_t out_len,
+                                                   size_t *written);
+
+// Frees a private key without using it.
+void graviola_kx_private_key_free(struct graviola_kx_private_key *key);
+
+// Loads a private key from `der_len` bytes of PKCS#8 DER at `der`.
+//
+// RSA, ECDSA P-256 and ECDSA P-384 keys are supported.
+enum graviola_status graviola_signing_key_from_pkcs8_der(const uint8_t *der,
+                                                         size_t der_len,
+                                                         struct graviola_signing_key **out);
+
+// Loads an RSA private key from `der_len` bytes of PKCS#1 DER at `der`.
+enum graviola_status graviola_signing_key_from_rsa_pkcs1_der(const uint8_t *der,
+                                                             size_t der_len,
+                                                             struct graviola_signing_key **out);
+
+// Makes a new verifying key for the public half of `key`.
+enum graviola_status graviola_signing_key_public_key(const struct graviola_signing_key *key,
+                                                     struct graviola_verifying_key **out);
+
+// Signs `message_len` bytes at `message` using `scheme`, which is a
+// [`graviola_signature_scheme`] value, and writes the signature to `out`.
+//
+// `out_len` of [`GRAVIOLA_MAX_SIGNATURE_LEN`] is always sufficient.
+// [`graviola_status::SchemeMismatch`] is returned if `scheme` cannot be
+// used with `key`.
+enum graviola_status graviola_signing_key_sign(const struct graviola_signing_key *key,
+                                               uint32_t scheme,
+                                               const uint8_t *message,
+                                               size_t message_len,
+                                               uint8_t *out,
+                                               size_t out_len,
+                                               size_t *written);
+
+// Frees a signing key.
+void graviola_signing_key_free(struct graviola_signing_key *key);
+
+// Loads an RSA public key from `der_len` bytes of PKCS#1 DER at `der`.
+enum graviola_status graviola_verifying_key_from_rsa_pkcs1_der(const uint8_t *der,
+                                                               size_t der_len,
+                                                               struct graviola_verifying_key **out);
+
+// Loads an ECDSA public key from `encoded_len` bytes of X9.62
+// uncompressed point encoding at `encoded`.
+//
+// The curve is P-256 if `encoded_len` is 65, or P-384 if it is 97.
+enum graviola_status graviola_verifying_key_from_ecdsa_x962(const uint8_t *encoded,
+                                                            size_t encoded_len,
+                                                            struct graviola_verifying_key **out);
+
+// Verifies `signature_len` bytes at `signature` over `message_len`
+// bytes at `message`, using `scheme`, which is a [`graviola_signature_scheme`]
+// value.
+//
+// [`graviola_status::BadSignature`] (or another error) is returned if
+// the signature is invalid.
+enum graviola_status graviola_verifying_key_verify(const struct graviola_verifying_key *key,
+                                                   uint32_t scheme,
+                                                   const uint8_t *message,
+                                                   size_t message_len,
+                                                   const uint8_t *signature,
+                                                   size_t signature_len);
+
+// Frees a verifying key.
+void graviola_verifying_key_free(struct graviola_verifying_key *key);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* GRAVIOLA_H */
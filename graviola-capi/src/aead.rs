@@ -0,0 +1,171 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+use graviola::aead::{AesGcm, ChaCha20Poly1305};
+
+use crate::{
+    array_in, array_out, clear_out, ffi_call, graviola_status, handle_free, handle_new, handle_ref,
+    slice_in, slice_out,
+};
+
+/// Length of the nonce for all supported AEADs.
+pub const GRAVIOLA_AEAD_NONCE_LEN: usize = 12;
+
+/// Length of the authentication tag for all supported AEADs.
+pub const GRAVIOLA_AEAD_TAG_LEN: usize = 16;
+
+/// An AES-GCM key.
+pub struct graviola_aes_gcm(AesGcm);
+
+/// Makes a new AES-GCM key from `key_len` bytes at `key`.
+///
+/// `key_len` must be 16 (for AES-128) or 32 (for AES-256).
+#[no_mangle]
+pub unsafe extern "C" fn graviola_aes_gcm_new(
+    key: *const u8,
+    key_len: usize,
+    out: *mut *mut graviola_aes_gcm,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let key = slice_in(key, key_len)?;
+        if !matches!(key.len(), 16 | 32) {
+            return Err(graviola_status::WrongLength);
+        }
+        handle_new(out, graviola_aes_gcm(AesGcm::new(key)))
+    })
+}
+
+/// Encrypts `inout_len` bytes at `inout` in place.
+///
+/// `nonce` is [`GRAVIOLA_AEAD_NONCE_LEN`] bytes and must be unique for
+/// this key.  `aad` is the additionally-authenticated data.  The tag
+/// is written to `tag_out`, which is [`GRAVIOLA_AEAD_TAG_LEN`] bytes.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_aes_gcm_encrypt(
+    key: *const graviola_aes_gcm,
+    nonce: *const u8,
+    aad: *const u8,
+    aad_len: usize,
+    inout: *mut u8,
+    inout_len: usize,
+    tag_out: *mut u8,
+) -> graviola_status {
+    ffi_call(|| {
+        handle_ref(key)?.0.encrypt(
+            array_in(nonce)?,
+            slice_in(aad, aad_len)?,
+            slice_out(inout, inout_len)?,
+            array_out(tag_out)?,
+        );
+        Ok(())
+    })
+}
+
+/// Decrypts and verifies `inout_len` bytes at `inout` in place.
+///
+/// `nonce` and `aad` must match those used for encryption.  `tag` is
+/// the purported authentication tag.  On failure,
+/// [`graviola_status::DecryptFailed`] is returned and `inout` is
+/// cleared.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_aes_gcm_decrypt(
+    key: *const graviola_aes_gcm,
+    nonce: *const u8,
+    aad: *const u8,
+    aad_len: usize,
+    inout: *mut u8,
+    inout_len: usize,
+    tag: *const u8,
+    tag_len: usize,
+) -> graviola_status {
+    ffi_call(|| {
+        Ok(handle_ref(key)?.0.decrypt(
+            array_in(nonce)?,
+            slice_in(aad, aad_len)?,
+            slice_out(inout, inout_len)?,
+            slice_in(tag, tag_len)?,
+        )?)
+    })
+}
+
+/// Frees an AES-GCM key.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_aes_gcm_free(key: *mut graviola_aes_gcm) {
+    handle_free(key)
+}
+
+/// A ChaCha20-Poly1305 key.
+pub struct graviola_chacha20poly1305(ChaCha20Poly1305);
+
+/// Makes a new ChaCha20-Poly1305 key from `key_len` bytes at `key`.
+///
+/// `key_len` must be 32.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_chacha20poly1305_new(
+    key: *const u8,
+    key_len: usize,
+    out: *mut *mut graviola_chacha20poly1305,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let key = slice_in(key, key_len)?
+            .try_into()
+            .map_err(|_| graviola_status::WrongLength)?;
+        handle_new(out, graviola_chacha20poly1305(ChaCha20Poly1305::new(key)))
+    })
+}
+
+/// Encrypts `inout_len` bytes at `inout` in place.
+///
+/// See [`graviola_aes_gcm_encrypt()`] for details of the other arguments.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_chacha20poly1305_encrypt(
+    key: *const graviola_chacha20poly1305,
+    nonce: *const u8,
+    aad: *const u8,
+    aad_len: usize,
+    inout: *mut u8,
+    inout_len: usize,
+    tag_out: *mut u8,
+) -> graviola_status {
+    ffi_call(|| {
+        handle_ref(key)?.0.encrypt(
+            array_in(nonce)?,
+            slice_in(aad, aad_len)?,
+            slice_out(inout, inout_len)?,
+            array_out(tag_out)?,
+        );
+        Ok(())
+    })
+}
+
+/// Decrypts and verifies `inout_len` bytes at `inout` in place.
+///
+/// See [`graviola_aes_gcm_decrypt()`] for details of the other arguments.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_chacha20poly1305_decrypt(
+    key: *const graviola_chacha20poly1305,
+    nonce: *const u8,
+    aad: *const u8,
+    aad_len: usize,
+    inout: *mut u8,
+    inout_len: usize,
+    tag: *const u8,
+    tag_len: usize,
+) -> graviola_status {
+    ffi_call(|| {
+        Ok(handle_ref(key)?.0.decrypt(
+            array_in(nonce)?,
+            slice_in(aad, aad_len)?,
+            slice_out(inout, inout_len)?,
+            slice_in(tag, tag_len)?,
+        )?)
+    })
+}
+
+/// Frees a ChaCha20-Poly1305 key.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_chacha20poly1305_free(key: *mut graviola_chacha20poly1305) {
+    handle_free(key)
+}
@@ -0,0 +1,233 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+use graviola::hashing::hmac::Hmac;
+use graviola::hashing::{Hash, HashContext, HashOutput, Sha256, Sha384, Sha512};
+
+use crate::{
+    clear_out, ffi_call, graviola_status, handle_free, handle_mut, handle_new, handle_ref,
+    slice_in, write_bytes,
+};
+
+/// Largest output of any supported hash function (or HMAC).
+pub const GRAVIOLA_HASH_MAX_OUTPUT_LEN: usize = 64;
+
+/// A hash function.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub enum graviola_hash_algorithm {
+    /// SHA-256, with 32 bytes of output.
+    Sha256 = 1,
+    /// SHA-384, with 48 bytes of output.
+    Sha384 = 2,
+    /// SHA-512, with 64 bytes of output.
+    Sha512 = 3,
+}
+
+impl TryFrom<u32> for graviola_hash_algorithm {
+    type Error = graviola_status;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Sha256),
+            2 => Ok(Self::Sha384),
+            3 => Ok(Self::Sha512),
+            _ => Err(graviola_status::UnknownAlgorithm),
+        }
+    }
+}
+
+/// An in-progress hash computation.
+pub struct graviola_hash(HashState);
+
+#[derive(Clone)]
+enum HashState {
+    Sha256(<Sha256 as Hash>::Context),
+    Sha384(<Sha384 as Hash>::Context),
+    Sha512(<Sha512 as Hash>::Context),
+}
+
+impl HashState {
+    fn new(algorithm: graviola_hash_algorithm) -> Self {
+        match algorithm {
+            graviola_hash_algorithm::Sha256 => Self::Sha256(Sha256::new()),
+            graviola_hash_algorithm::Sha384 => Self::Sha384(Sha384::new()),
+            graviola_hash_algorithm::Sha512 => Self::Sha512(Sha512::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Sha256(ctx) => HashContext::update(ctx, bytes),
+            Self::Sha384(ctx) => HashContext::update(ctx, bytes),
+            Self::Sha512(ctx) => HashContext::update(ctx, bytes),
+        }
+    }
+
+    fn finish(self) -> HashOutput {
+        match self {
+            Self::Sha256(ctx) => HashContext::finish(ctx),
+            Self::Sha384(ctx) => HashContext::finish(ctx),
+            Self::Sha512(ctx) => HashContext::finish(ctx),
+        }
+    }
+}
+
+/// Starts a new hash computation using `algorithm`, which is a
+/// [`graviola_hash_algorithm`] value.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_hash_new(
+    algorithm: u32,
+    out: *mut *mut graviola_hash,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let algorithm = graviola_hash_algorithm::try_from(algorithm)?;
+        handle_new(out, graviola_hash(HashState::new(algorithm)))
+    })
+}
+
+/// Adds `bytes_len` bytes at `bytes` to the computation.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_hash_update(
+    hash: *mut graviola_hash,
+    bytes: *const u8,
+    bytes_len: usize,
+) -> graviola_status {
+    ffi_call(|| {
+        let bytes = slice_in(bytes, bytes_len)?;
+        handle_mut(hash)?.0.update(bytes);
+        Ok(())
+    })
+}
+
+/// Writes the hash of the input so far to `out`.
+///
+/// `hash` is not changed, so further input may be added afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_hash_finish(
+    hash: *const graviola_hash,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> graviola_status {
+    ffi_call(|| {
+        let output = handle_ref(hash)?.0.clone().finish();
+        write_bytes(output.as_ref(), out, out_len, written)
+    })
+}
+
+/// Frees a hash computation.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_hash_free(hash: *mut graviola_hash) {
+    handle_free(hash)
+}
+
+/// An in-progress HMAC computation.
+pub struct graviola_hmac(HmacState);
+
+#[derive(Clone)]
+enum HmacState {
+    Sha256(Hmac<Sha256>),
+    Sha384(Hmac<Sha384>),
+    Sha512(Hmac<Sha512>),
+}
+
+impl HmacState {
+    fn new(algorithm: graviola_hash_algorithm, key: &[u8]) -> Self {
+        match algorithm {
+            graviola_hash_algorithm::Sha256 => Self::Sha256(Hmac::new(key)),
+            graviola_hash_algorithm::Sha384 => Self::Sha384(Hmac::new(key)),
+            graviola_hash_algorithm::Sha512 => Self::Sha512(Hmac::new(key)),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Sha256(ctx) => ctx.update(bytes),
+            Self::Sha384(ctx) => ctx.update(bytes),
+            Self::Sha512(ctx) => ctx.update(bytes),
+        }
+    }
+
+    fn finish(self) -> HashOutput {
+        match self {
+            Self::Sha256(ctx) => ctx.finish(),
+            Self::Sha384(ctx) => ctx.finish(),
+            Self::Sha512(ctx) => ctx.finish(),
+        }
+    }
+}
+
+/// Starts a new HMAC computation using `algorithm`, which is a
+/// [`graviola_hash_algorithm`] value, keyed with `key_len` bytes at `key`.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_hmac_new(
+    algorithm: u32,
+    key: *const u8,
+    key_len: usize,
+    out: *mut *mut graviola_hmac,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let algorithm = graviola_hash_algorithm::try_from(algorithm)?;
+        let key = slice_in(key, key_len)?;
+        handle_new(out, graviola_hmac(HmacState::new(algorithm, key)))
+    })
+}
+
+/// Adds `bytes_len` bytes at `bytes` to the computation.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_hmac_update(
+    hmac: *mut graviola_hmac,
+    bytes: *const u8,
+    bytes_len: usize,
+) -> graviola_status {
+    ffi_call(|| {
+        let bytes = slice_in(bytes, bytes_len)?;
+        handle_mut(hmac)?.0.update(bytes);
+        Ok(())
+    })
+}
+
+/// Writes the HMAC of the input so far to `out`.
+///
+/// `hmac` is not changed, so further input may be added afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_hmac_finish(
+    hmac: *const graviola_hmac,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> graviola_status {
+    ffi_call(|| {
+        let output = handle_ref(hmac)?.0.clone().finish();
+        write_bytes(output.as_ref(), out, out_len, written)
+    })
+}
+
+/// Compares the HMAC of the input so far against `tag_len` bytes at
+/// `tag`, in constant time.
+///
+/// [`graviola_status::BadSignature`] is returned if they differ.
+/// `tag` may not be truncated.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_hmac_verify(
+    hmac: *const graviola_hmac,
+    tag: *const u8,
+    tag_len: usize,
+) -> graviola_status {
+    ffi_call(|| {
+        let tag = slice_in(tag, tag_len)?;
+        match handle_ref(hmac)?.0.clone().finish().ct_equal(tag) {
+            true => Ok(()),
+            false => Err(graviola_status::BadSignature),
+        }
+    })
+}
+
+/// Frees an HMAC computation.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_hmac_free(hmac: *mut graviola_hmac) {
+    handle_free(hmac)
+}
@@ -0,0 +1,125 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+use graviola::key_agreement::{p256, p384, x25519};
+
+use crate::{
+    clear_out, ffi_call, graviola_status, handle_free, handle_new, handle_ref, handle_take,
+    slice_in, write_bytes,
+};
+
+/// Largest public key of any supported key agreement algorithm.
+pub const GRAVIOLA_KX_MAX_PUBLIC_KEY_LEN: usize = 97;
+
+/// Largest shared secret of any supported key agreement algorithm.
+pub const GRAVIOLA_KX_MAX_SHARED_SECRET_LEN: usize = 48;
+
+/// A key agreement algorithm.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub enum graviola_kx_algorithm {
+    /// X25519.  Public keys and shared secrets are 32 bytes.
+    X25519 = 1,
+    /// ECDH on P-256.  Public keys are 65 bytes (X9.62 uncompressed),
+    /// and shared secrets are 32 bytes.
+    P256 = 2,
+    /// ECDH on P-384.  Public keys are 97 bytes (X9.62 uncompressed),
+    /// and shared secrets are 48 bytes.
+    P384 = 3,
+}
+
+impl TryFrom<u32> for graviola_kx_algorithm {
+    type Error = graviola_status;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::X25519),
+            2 => Ok(Self::P256),
+            3 => Ok(Self::P384),
+            _ => Err(graviola_status::UnknownAlgorithm),
+        }
+    }
+}
+
+/// An ephemeral private key for key agreement.
+pub struct graviola_kx_private_key(PrivateKey);
+
+enum PrivateKey {
+    X25519(x25519::PrivateKey),
+    P256(p256::PrivateKey),
+    P384(p384::PrivateKey),
+}
+
+/// Generates a new random private key for `algorithm`, which is a
+/// [`graviola_kx_algorithm`] value.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_kx_private_key_generate(
+    algorithm: u32,
+    out: *mut *mut graviola_kx_private_key,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let key = match graviola_kx_algorithm::try_from(algorithm)? {
+            graviola_kx_algorithm::X25519 => PrivateKey::X25519(x25519::PrivateKey::new_random()?),
+            graviola_kx_algorithm::P256 => PrivateKey::P256(p256::PrivateKey::new_random()?),
+            graviola_kx_algorithm::P384 => PrivateKey::P384(p384::PrivateKey::new_random()?),
+        };
+        handle_new(out, graviola_kx_private_key(key))
+    })
+}
+
+/// Writes the public key corresponding to `key` to `out`.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_kx_private_key_public_key(
+    key: *const graviola_kx_private_key,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> graviola_status {
+    ffi_call(|| match &handle_ref(key)?.0 {
+        PrivateKey::X25519(key) => write_bytes(&key.public_key().as_bytes(), out, out_len, written),
+        PrivateKey::P256(key) => write_bytes(&key.public_key_uncompressed(), out, out_len, written),
+        PrivateKey::P384(key) => write_bytes(&key.public_key_uncompressed(), out, out_len, written),
+    })
+}
+
+/// Completes key agreement with the peer's public key, `peer_len` bytes
+/// at `peer`, and writes the shared secret to `out`.
+///
+/// `key` is consumed by this function (whether it succeeds or not), and
+/// must not be used or freed afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_kx_private_key_agree(
+    key: *mut graviola_kx_private_key,
+    peer: *const u8,
+    peer_len: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> graviola_status {
+    ffi_call(|| {
+        let key = handle_take(key)?;
+        let peer = slice_in(peer, peer_len)?;
+        match key.0 {
+            PrivateKey::X25519(key) => {
+                let peer = x25519::PublicKey::try_from_slice(peer)
+                    .map_err(|_| graviola_status::WrongLength)?;
+                write_bytes(&key.diffie_hellman(&peer).0, out, out_len, written)
+            }
+            PrivateKey::P256(key) => {
+                let peer = p256::PublicKey::from_x962_uncompressed(peer)?;
+                write_bytes(&key.diffie_hellman(&peer)?.0, out, out_len, written)
+            }
+            PrivateKey::P384(key) => {
+                let peer = p384::PublicKey::from_x962_uncompressed(peer)?;
+                write_bytes(&key.diffie_hellman(&peer)?.0, out, out_len, written)
+            }
+        }
+    })
+}
+
+/// Frees a private key without using it.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_kx_private_key_free(key: *mut graviola_kx_private_key) {
+    handle_free(key)
+}
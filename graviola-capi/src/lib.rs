@@ -0,0 +1,304 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! A C API for [Graviola](https://github.com/ctz/graviola/).
+//!
+//! This crate builds static and dynamic libraries exposing Graviola's
+//! AEADs, hash functions, HMAC, key agreement and signatures to C (and
+//! anything that can call C).  The header is `include/graviola.h`; it is
+//! generated by `cbindgen` when this crate is built.
+//!
+//! # Conventions
+//!
+//! - Every fallible function returns a [`graviola_status`].
+//!   [`graviola_status::Ok`] is zero.
+//! - Objects are opaque handles, created by a `graviola_*_new` (or similar)
+//!   function, and released with the matching `graviola_*_free` function.
+//!   Freeing `NULL` is a no-op.  Handles may be shared between threads
+//!   for concurrent use of functions taking a `const` handle.
+//! - Byte strings are passed as a pointer and length.  The pointer may be
+//!   `NULL` only if the length is zero.
+//! - Variable-length outputs are written to a caller-supplied buffer of
+//!   length `out_len`, and the used length is written to `*written`.
+//!   [`graviola_status::WrongLength`] is returned if the buffer is too small.
+//! - Algorithm and scheme arguments are passed as `uint32_t`, and should
+//!   be one of the values of the matching enum in the header.  Other
+//!   values are rejected with [`graviola_status::UnknownAlgorithm`].
+//!
+//! Passing invalid pointers (other than `NULL`, which is checked for)
+//! is undefined behaviour.  A panic inside this library is caught and
+//! returned as [`graviola_status::Panic`].
+
+#![allow(non_camel_case_types, clippy::missing_safety_doc)]
+#![warn(
+    clippy::undocumented_unsafe_blocks,
+    clippy::use_self,
+    elided_lifetimes_in_paths,
+    missing_docs,
+    trivial_numeric_casts,
+    unreachable_pub,
+    unused_import_braces,
+    unused_extern_crates,
+    unused_qualifications
+)]
+
+use core::ffi::c_char;
+use core::panic::AssertUnwindSafe;
+use core::{ptr, slice};
+use std::panic;
+
+use graviola::Error;
+
+mod aead;
+mod hash;
+mod kx;
+mod sign;
+
+pub use aead::*;
+pub use hash::*;
+pub use kx::*;
+pub use sign::*;
+
+/// The result of every fallible function in this library.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum graviola_status {
+    /// Success.
+    Ok = 0,
+    /// Some input or output buffer was the wrong length.
+    WrongLength = 1,
+    /// A compressed elliptic curve point encoding was encountered.
+    NotUncompressed = 2,
+    /// A public key was invalid.
+    NotOnCurve = 3,
+    /// A value was too small or large.
+    OutOfRange = 4,
+    /// The random number generator failed.
+    RngFailed = 5,
+    /// A deterministic random bit generator must be reseeded before further use.
+    ReseedRequired = 6,
+    /// A signature or MAC was invalid.
+    BadSignature = 7,
+    /// AEAD decryption failed: the tag, aad, ciphertext or nonce was wrong.
+    DecryptFailed = 8,
+    /// An ASN.1 encoding was invalid.
+    Asn1Error = 9,
+    /// A key encoding was valid ASN.1, but not a supported key.
+    KeyFormatError = 10,
+    /// A required pointer argument was `NULL`.
+    NullPointer = 100,
+    /// The signature scheme is not usable with this key.
+    SchemeMismatch = 101,
+    /// The library panicked.  This is a bug.
+    Panic = 102,
+    /// Any other error.
+    Other = 103,
+    /// An algorithm or signature scheme argument was not a known value.
+    UnknownAlgorithm = 104,
+}
+
+impl From<Error> for graviola_status {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::WrongLength => Self::WrongLength,
+            Error::NotUncompressed => Self::NotUncompressed,
+            Error::NotOnCurve => Self::NotOnCurve,
+            Error::OutOfRange => Self::OutOfRange,
+            Error::RngFailed => Self::RngFailed,
+            Error::ReseedRequired => Self::ReseedRequired,
+            Error::BadSignature => Self::BadSignature,
+            Error::DecryptFailed => Self::DecryptFailed,
+            Error::Asn1Error(_) => Self::Asn1Error,
+            Error::KeyFormatError(_) => Self::KeyFormatError,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl graviola_status {
+    fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Ok),
+            1 => Some(Self::WrongLength),
+            2 => Some(Self::NotUncompressed),
+            3 => Some(Self::NotOnCurve),
+            4 => Some(Self::OutOfRange),
+            5 => Some(Self::RngFailed),
+            6 => Some(Self::ReseedRequired),
+            7 => Some(Self::BadSignature),
+            8 => Some(Self::DecryptFailed),
+            9 => Some(Self::Asn1Error),
+            10 => Some(Self::KeyFormatError),
+            100 => Some(Self::NullPointer),
+            101 => Some(Self::SchemeMismatch),
+            102 => Some(Self::Panic),
+            103 => Some(Self::Other),
+            104 => Some(Self::UnknownAlgorithm),
+            _ => None,
+        }
+    }
+}
+
+/// Returns a static, NUL-terminated description of `status`.
+///
+/// `status` should be one of the values of [`graviola_status`].  Other
+/// values give "unknown status".
+#[no_mangle]
+pub extern "C" fn graviola_status_message(status: u32) -> *const c_char {
+    let msg: &'static [u8] = match graviola_status::from_u32(status) {
+        Some(graviola_status::Ok) => b"success\0",
+        Some(graviola_status::WrongLength) => b"wrong length\0",
+        Some(graviola_status::NotUncompressed) => b"elliptic curve point is not uncompressed\0",
+        Some(graviola_status::NotOnCurve) => b"public key is invalid\0",
+        Some(graviola_status::OutOfRange) => b"value out of range\0",
+        Some(graviola_status::RngFailed) => b"random number generator failed\0",
+        Some(graviola_status::ReseedRequired) => b"DRBG reseed required\0",
+        Some(graviola_status::BadSignature) => b"bad signature\0",
+        Some(graviola_status::DecryptFailed) => b"decryption failed\0",
+        Some(graviola_status::Asn1Error) => b"invalid ASN.1 encoding\0",
+        Some(graviola_status::KeyFormatError) => b"unsupported key format\0",
+        Some(graviola_status::NullPointer) => b"unexpected NULL pointer\0",
+        Some(graviola_status::SchemeMismatch) => b"signature scheme does not match key\0",
+        Some(graviola_status::Panic) => b"internal panic\0",
+        Some(graviola_status::Other) => b"other error\0",
+        Some(graviola_status::UnknownAlgorithm) => b"unknown algorithm or signature scheme\0",
+        None => b"unknown status\0",
+    };
+    msg.as_ptr().cast()
+}
+
+/// Runs `f` at the FFI boundary, converting its result (or a panic)
+/// into a [`graviola_status`].
+fn ffi_call(f: impl FnOnce() -> Result<(), graviola_status>) -> graviola_status {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => graviola_status::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => graviola_status::Panic,
+    }
+}
+
+/// Borrows `len` bytes from `ptr`.
+///
+/// The caller must ensure `ptr` is `NULL` or valid for `len` bytes.
+unsafe fn slice_in<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], graviola_status> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(graviola_status::NullPointer),
+        // SAFETY: non-NULL, and the caller guarantees validity for `len` bytes.
+        (false, _) => Ok(unsafe { slice::from_raw_parts(ptr, len) }),
+    }
+}
+
+/// Mutably borrows `len` bytes from `ptr`.
+///
+/// The caller must ensure `ptr` is `NULL` or valid for writing `len` bytes.
+unsafe fn slice_out<'a>(ptr: *mut u8, len: usize) -> Result<&'a mut [u8], graviola_status> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&mut []),
+        (true, _) => Err(graviola_status::NullPointer),
+        // SAFETY: non-NULL, and the caller guarantees validity for `len` bytes.
+        (false, _) => Ok(unsafe { slice::from_raw_parts_mut(ptr, len) }),
+    }
+}
+
+/// Borrows a fixed-length array from `ptr`.
+///
+/// The caller must ensure `ptr` is `NULL` or valid for `N` bytes.
+unsafe fn array_in<'a, const N: usize>(ptr: *const u8) -> Result<&'a [u8; N], graviola_status> {
+    // SAFETY: `[u8; N]` has the same alignment as `u8`, and the caller
+    // guarantees validity for `N` bytes.
+    unsafe { ptr.cast::<[u8; N]>().as_ref() }.ok_or(graviola_status::NullPointer)
+}
+
+/// Mutably borrows a fixed-length array from `ptr`.
+///
+/// The caller must ensure `ptr` is `NULL` or valid for writing `N` bytes.
+unsafe fn array_out<'a, const N: usize>(ptr: *mut u8) -> Result<&'a mut [u8; N], graviola_status> {
+    // SAFETY: as for `array_in`.
+    unsafe { ptr.cast::<[u8; N]>().as_mut() }.ok_or(graviola_status::NullPointer)
+}
+
+/// Writes `bytes` to the caller's buffer `out` of length `out_len`, and
+/// the length used to `written`.
+///
+/// The caller must ensure `out` is `NULL` or valid for writing `out_len`
+/// bytes, and `written` is `NULL` or valid.
+unsafe fn write_bytes(
+    bytes: &[u8],
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> Result<(), graviola_status> {
+    if written.is_null() {
+        return Err(graviola_status::NullPointer);
+    }
+    if out_len < bytes.len() {
+        return Err(graviola_status::WrongLength);
+    }
+    // SAFETY: guaranteed by caller.
+    unsafe { slice_out(out, bytes.len()) }?.copy_from_slice(bytes);
+    // SAFETY: non-NULL, and the caller guarantees validity.
+    unsafe { written.write(bytes.len()) };
+    Ok(())
+}
+
+/// Borrows the object behind a handle.
+///
+/// The caller must ensure `handle` is `NULL` or a live handle.
+unsafe fn handle_ref<'a, T>(handle: *const T) -> Result<&'a T, graviola_status> {
+    // SAFETY: guaranteed by caller.
+    unsafe { handle.as_ref() }.ok_or(graviola_status::NullPointer)
+}
+
+/// Mutably borrows the object behind a handle.
+///
+/// The caller must ensure `handle` is `NULL` or a live handle, not used
+/// elsewhere for the duration of the borrow.
+unsafe fn handle_mut<'a, T>(handle: *mut T) -> Result<&'a mut T, graviola_status> {
+    // SAFETY: guaranteed by caller.
+    unsafe { handle.as_mut() }.ok_or(graviola_status::NullPointer)
+}
+
+/// Takes back ownership of the object behind a handle.
+///
+/// The caller must ensure `handle` is `NULL` or a live handle, which
+/// is not used again.
+unsafe fn handle_take<T>(handle: *mut T) -> Result<Box<T>, graviola_status> {
+    if handle.is_null() {
+        return Err(graviola_status::NullPointer);
+    }
+    // SAFETY: handles are only made by `handle_new`, and the caller
+    // guarantees this one is live.
+    Ok(unsafe { Box::from_raw(handle) })
+}
+
+/// Makes a new handle for `value`, and writes it to `out`.
+///
+/// The caller must ensure `out` is `NULL` or valid for writing.
+unsafe fn handle_new<T>(out: *mut *mut T, value: T) -> Result<(), graviola_status> {
+    if out.is_null() {
+        return Err(graviola_status::NullPointer);
+    }
+    // SAFETY: non-NULL, and the caller guarantees validity.
+    unsafe { out.write(Box::into_raw(Box::new(value))) };
+    Ok(())
+}
+
+/// Releases a handle.  `NULL` is ignored.
+///
+/// The caller must ensure `handle` is `NULL` or a live handle, which
+/// is not used again.
+unsafe fn handle_free<T>(handle: *mut T) {
+    // SAFETY: guaranteed by caller.
+    drop(unsafe { handle_take(handle) });
+}
+
+/// Writes `NULL` to `out`, if it is non-`NULL`.
+///
+/// This is done first by constructors, so `*out` is defined on error.
+unsafe fn clear_out<T>(out: *mut *mut T) {
+    if !out.is_null() {
+        // SAFETY: non-NULL, and the caller guarantees validity.
+        unsafe { out.write(ptr::null_mut()) };
+    }
+}
@@ -0,0 +1,291 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+use graviola::hashing::{Sha256, Sha384};
+use graviola::signing::{ecdsa, rsa};
+use graviola::Error;
+
+use crate::{
+    clear_out, ffi_call, graviola_status, handle_free, handle_new, handle_ref, slice_in,
+    write_bytes,
+};
+
+/// Largest signature made by any supported key (an 8192-bit RSA key).
+pub const GRAVIOLA_MAX_SIGNATURE_LEN: usize = 1024;
+
+/// A signature scheme.
+///
+/// ECDSA signatures are DER-encoded ASN.1, as used in TLS and X.509.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub enum graviola_signature_scheme {
+    /// ECDSA on P-256, with SHA-256.
+    EcdsaP256Sha256 = 1,
+    /// ECDSA on P-384, with SHA-384.
+    EcdsaP384Sha384 = 2,
+    /// RSASSA-PKCS1-v1_5 with SHA-256.
+    RsaPkcs1Sha256 = 3,
+    /// RSASSA-PKCS1-v1_5 with SHA-384.
+    RsaPkcs1Sha384 = 4,
+    /// RSASSA-PKCS1-v1_5 with SHA-512.
+    RsaPkcs1Sha512 = 5,
+    /// RSASSA-PSS with SHA-256, MGF1-SHA-256 and a 32 byte salt.
+    RsaPssSha256 = 6,
+    /// RSASSA-PSS with SHA-384, MGF1-SHA-384 and a 48 byte salt.
+    RsaPssSha384 = 7,
+    /// RSASSA-PSS with SHA-512, MGF1-SHA-512 and a 64 byte salt.
+    RsaPssSha512 = 8,
+}
+
+impl TryFrom<u32> for graviola_signature_scheme {
+    type Error = graviola_status;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::EcdsaP256Sha256),
+            2 => Ok(Self::EcdsaP384Sha384),
+            3 => Ok(Self::RsaPkcs1Sha256),
+            4 => Ok(Self::RsaPkcs1Sha384),
+            5 => Ok(Self::RsaPkcs1Sha512),
+            6 => Ok(Self::RsaPssSha256),
+            7 => Ok(Self::RsaPssSha384),
+            8 => Ok(Self::RsaPssSha512),
+            _ => Err(graviola_status::UnknownAlgorithm),
+        }
+    }
+}
+
+/// A private key for signing.
+pub struct graviola_signing_key(SigningKey);
+
+#[allow(clippy::large_enum_variant)] // only used boxed, as a handle
+enum SigningKey {
+    EcdsaP256(ecdsa::SigningKey<ecdsa::P256>),
+    EcdsaP384(ecdsa::SigningKey<ecdsa::P384>),
+    Rsa(rsa::SigningKey),
+}
+
+impl SigningKey {
+    fn from_pkcs8_der(der: &[u8]) -> Result<Self, Error> {
+        let rsa_err = match rsa::SigningKey::from_pkcs8_der(der) {
+            Ok(key) => return Ok(Self::Rsa(key)),
+            Err(err) => err,
+        };
+        let p256_err = match ecdsa::SigningKey::from_pkcs8_der(der) {
+            Ok(key) => return Ok(Self::EcdsaP256(key)),
+            Err(err) => err,
+        };
+        let p384_err = match ecdsa::SigningKey::from_pkcs8_der(der) {
+            Ok(key) => return Ok(Self::EcdsaP384(key)),
+            Err(err) => err,
+        };
+
+        // each attempt rejects other algorithms' keys with a `KeyFormatError`,
+        // so any other error is the interesting one.
+        Err([rsa_err, p256_err, p384_err]
+            .into_iter()
+            .find(|err| !matches!(err, Error::KeyFormatError(_)))
+            .unwrap_or(rsa_err))
+    }
+}
+
+/// Loads a private key from `der_len` bytes of PKCS#8 DER at `der`.
+///
+/// RSA, ECDSA P-256 and ECDSA P-384 keys are supported.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_signing_key_from_pkcs8_der(
+    der: *const u8,
+    der_len: usize,
+    out: *mut *mut graviola_signing_key,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let key = SigningKey::from_pkcs8_der(slice_in(der, der_len)?)?;
+        handle_new(out, graviola_signing_key(key))
+    })
+}
+
+/// Loads an RSA private key from `der_len` bytes of PKCS#1 DER at `der`.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_signing_key_from_rsa_pkcs1_der(
+    der: *const u8,
+    der_len: usize,
+    out: *mut *mut graviola_signing_key,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let key = rsa::SigningKey::from_pkcs1_der(slice_in(der, der_len)?)?;
+        handle_new(out, graviola_signing_key(SigningKey::Rsa(key)))
+    })
+}
+
+/// Makes a new verifying key for the public half of `key`.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_signing_key_public_key(
+    key: *const graviola_signing_key,
+    out: *mut *mut graviola_verifying_key,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let public = match &handle_ref(key)?.0 {
+            SigningKey::EcdsaP256(key) => {
+                VerifyingKey::EcdsaP256(ecdsa::VerifyingKey::from_x962_uncompressed(
+                    &key.private_key.public_key_uncompressed(),
+                )?)
+            }
+            SigningKey::EcdsaP384(key) => {
+                VerifyingKey::EcdsaP384(ecdsa::VerifyingKey::from_x962_uncompressed(
+                    &key.private_key.public_key_uncompressed(),
+                )?)
+            }
+            SigningKey::Rsa(key) => VerifyingKey::Rsa(key.public_key()),
+        };
+        handle_new(out, graviola_verifying_key(public))
+    })
+}
+
+/// Signs `message_len` bytes at `message` using `scheme`, which is a
+/// [`graviola_signature_scheme`] value, and writes the signature to `out`.
+///
+/// `out_len` of [`GRAVIOLA_MAX_SIGNATURE_LEN`] is always sufficient.
+/// [`graviola_status::SchemeMismatch`] is returned if `scheme` cannot be
+/// used with `key`.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_signing_key_sign(
+    key: *const graviola_signing_key,
+    scheme: u32,
+    message: *const u8,
+    message_len: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> graviola_status {
+    ffi_call(|| {
+        let key = handle_ref(key)?;
+        let scheme = graviola_signature_scheme::try_from(scheme)?;
+        let message = slice_in(message, message_len)?;
+        let mut signature = [0u8; GRAVIOLA_MAX_SIGNATURE_LEN];
+
+        use graviola_signature_scheme::*;
+        let signature = match (&key.0, scheme) {
+            (SigningKey::EcdsaP256(key), EcdsaP256Sha256) => {
+                key.sign_asn1::<Sha256>(&[message], &mut signature)
+            }
+            (SigningKey::EcdsaP384(key), EcdsaP384Sha384) => {
+                key.sign_asn1::<Sha384>(&[message], &mut signature)
+            }
+            (SigningKey::Rsa(key), RsaPkcs1Sha256) => {
+                key.sign_pkcs1_sha256(&mut signature, message)
+            }
+            (SigningKey::Rsa(key), RsaPkcs1Sha384) => {
+                key.sign_pkcs1_sha384(&mut signature, message)
+            }
+            (SigningKey::Rsa(key), RsaPkcs1Sha512) => {
+                key.sign_pkcs1_sha512(&mut signature, message)
+            }
+            (SigningKey::Rsa(key), RsaPssSha256) => key.sign_pss_sha256(&mut signature, message),
+            (SigningKey::Rsa(key), RsaPssSha384) => key.sign_pss_sha384(&mut signature, message),
+            (SigningKey::Rsa(key), RsaPssSha512) => key.sign_pss_sha512(&mut signature, message),
+            _ => return Err(graviola_status::SchemeMismatch),
+        }?;
+        write_bytes(signature, out, out_len, written)
+    })
+}
+
+/// Frees a signing key.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_signing_key_free(key: *mut graviola_signing_key) {
+    handle_free(key)
+}
+
+/// A public key for verifying signatures.
+pub struct graviola_verifying_key(VerifyingKey);
+
+#[allow(clippy::large_enum_variant)] // only used boxed, as a handle
+enum VerifyingKey {
+    EcdsaP256(ecdsa::VerifyingKey<ecdsa::P256>),
+    EcdsaP384(ecdsa::VerifyingKey<ecdsa::P384>),
+    Rsa(rsa::VerifyingKey),
+}
+
+/// Loads an RSA public key from `der_len` bytes of PKCS#1 DER at `der`.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_verifying_key_from_rsa_pkcs1_der(
+    der: *const u8,
+    der_len: usize,
+    out: *mut *mut graviola_verifying_key,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let key = rsa::VerifyingKey::from_pkcs1_der(slice_in(der, der_len)?)?;
+        handle_new(out, graviola_verifying_key(VerifyingKey::Rsa(key)))
+    })
+}
+
+/// Loads an ECDSA public key from `encoded_len` bytes of X9.62
+/// uncompressed point encoding at `encoded`.
+///
+/// The curve is P-256 if `encoded_len` is 65, or P-384 if it is 97.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_verifying_key_from_ecdsa_x962(
+    encoded: *const u8,
+    encoded_len: usize,
+    out: *mut *mut graviola_verifying_key,
+) -> graviola_status {
+    ffi_call(|| {
+        clear_out(out);
+        let encoded = slice_in(encoded, encoded_len)?;
+        let key = match encoded.len() {
+            65 => VerifyingKey::EcdsaP256(ecdsa::VerifyingKey::from_x962_uncompressed(encoded)?),
+            97 => VerifyingKey::EcdsaP384(ecdsa::VerifyingKey::from_x962_uncompressed(encoded)?),
+            _ => return Err(graviola_status::WrongLength),
+        };
+        handle_new(out, graviola_verifying_key(key))
+    })
+}
+
+/// Verifies `signature_len` bytes at `signature` over `message_len`
+/// bytes at `message`, using `scheme`, which is a [`graviola_signature_scheme`]
+/// value.
+///
+/// [`graviola_status::BadSignature`] (or another error) is returned if
+/// the signature is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_verifying_key_verify(
+    key: *const graviola_verifying_key,
+    scheme: u32,
+    message: *const u8,
+    message_len: usize,
+    signature: *const u8,
+    signature_len: usize,
+) -> graviola_status {
+    ffi_call(|| {
+        let key = handle_ref(key)?;
+        let scheme = graviola_signature_scheme::try_from(scheme)?;
+        let message = slice_in(message, message_len)?;
+        let signature = slice_in(signature, signature_len)?;
+
+        use graviola_signature_scheme::*;
+        Ok(match (&key.0, scheme) {
+            (VerifyingKey::EcdsaP256(key), EcdsaP256Sha256) => {
+                key.verify_asn1::<Sha256>(&[message], signature)
+            }
+            (VerifyingKey::EcdsaP384(key), EcdsaP384Sha384) => {
+                key.verify_asn1::<Sha384>(&[message], signature)
+            }
+            (VerifyingKey::Rsa(key), RsaPkcs1Sha256) => key.verify_pkcs1_sha256(signature, message),
+            (VerifyingKey::Rsa(key), RsaPkcs1Sha384) => key.verify_pkcs1_sha384(signature, message),
+            (VerifyingKey::Rsa(key), RsaPkcs1Sha512) => key.verify_pkcs1_sha512(signature, message),
+            (VerifyingKey::Rsa(key), RsaPssSha256) => key.verify_pss_sha256(signature, message),
+            (VerifyingKey::Rsa(key), RsaPssSha384) => key.verify_pss_sha384(signature, message),
+            (VerifyingKey::Rsa(key), RsaPssSha512) => key.verify_pss_sha512(signature, message),
+            _ => return Err(graviola_status::SchemeMismatch),
+        }?)
+    })
+}
+
+/// Frees a verifying key.
+#[no_mangle]
+pub unsafe extern "C" fn graviola_verifying_key_free(key: *mut graviola_verifying_key) {
+    handle_free(key)
+}
@@ -0,0 +1,58 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! Compiles `tests/harness.c` against the generated header and the
+//! cdylib built alongside this test, and runs it.
+
+#![cfg(unix)]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[test]
+fn c_harness() {
+    if env!("GRAVIOLA_CAPI_HOST") != env!("GRAVIOLA_CAPI_TARGET") {
+        eprintln!("skipping C harness: cannot run a cross-compiled harness");
+        return;
+    }
+
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let keys_dir = crate_dir.join("../graviola/src/high");
+
+    // the cdylib is built next to this test binary, in `target/<profile>/deps`.
+    let lib_dir = env::current_exe()
+        .unwrap()
+        .parent()
+        .map(PathBuf::from)
+        .unwrap();
+    let lib = lib_dir.join(format!(
+        "{}graviola_capi{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    ));
+    let harness = lib_dir.join("graviola-capi-harness");
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&harness)
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg(crate_dir.join("tests/harness.c"))
+        .arg(&lib)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .status()
+        .expect("cannot run C compiler");
+    assert!(status.success(), "compiling harness.c failed");
+
+    let output = Command::new(&harness)
+        .arg(keys_dir.join("rsa/rsa2048.pkcs8.der"))
+        .arg(keys_dir.join("ecdsa/secp256r1.pkcs8.der"))
+        .arg(keys_dir.join("ecdsa/secp384r1.pkcs8.der"))
+        .output()
+        .unwrap();
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success(), "harness failed");
+}
@@ -0,0 +1,322 @@
+/* Written for Graviola by Joe Birr-Pixton, 2024.
+ * SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+ *
+ * Exercises the C API.  Run by tests/c_harness.rs, with the paths of
+ * an RSA-2048, P-256 and P-384 PKCS#8 key as arguments.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "graviola.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                  \
+  do {                                                               \
+    if (!(cond)) {                                                   \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,         \
+              __LINE__, #cond);                                      \
+      failures++;                                                    \
+    }                                                                \
+  } while (0)
+
+#define CHECK_STATUS(expr, expected)                                 \
+  do {                                                               \
+    graviola_status _st = (expr);                                    \
+    if (_st != (expected)) {                                         \
+      fprintf(stderr, "%s:%d: %s returned %d (%s)\n", __FILE__,      \
+              __LINE__, #expr, (int)_st, graviola_status_message(_st)); \
+      failures++;                                                    \
+    }                                                                \
+  } while (0)
+
+#define CHECK_OK(expr) CHECK_STATUS(expr, GRAVIOLA_STATUS_OK)
+
+static void from_hex(const char *hex, uint8_t *out, size_t out_len) {
+  for (size_t i = 0; i < out_len; i++) {
+    unsigned int byte;
+    sscanf(hex + 2 * i, "%2x", &byte);
+    out[i] = (uint8_t)byte;
+  }
+}
+
+static int read_file(const char *path, uint8_t **out, size_t *out_len) {
+  FILE *f = fopen(path, "rb");
+  if (!f) {
+    perror(path);
+    return 0;
+  }
+  fseek(f, 0, SEEK_END);
+  long len = ftell(f);
+  fseek(f, 0, SEEK_SET);
+  *out = malloc((size_t)len);
+  *out_len = fread(*out, 1, (size_t)len, f);
+  fclose(f);
+  return *out_len == (size_t)len;
+}
+
+static void test_status(void) {
+  CHECK(strcmp(graviola_status_message(GRAVIOLA_STATUS_OK), "success") == 0);
+  CHECK(strcmp(graviola_status_message(GRAVIOLA_STATUS_NULL_POINTER),
+               "unexpected NULL pointer") == 0);
+  CHECK(strcmp(graviola_status_message(12345), "unknown status") == 0);
+}
+
+static void test_hash(void) {
+  static const char *abc = "abc";
+  uint8_t expected[32], out[GRAVIOLA_HASH_MAX_OUTPUT_LEN];
+  size_t written = 0;
+  graviola_hash *hash = NULL;
+
+  from_hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+           expected, sizeof expected);
+
+  CHECK_OK(graviola_hash_new(GRAVIOLA_HASH_ALGORITHM_SHA256, &hash));
+  CHECK_OK(graviola_hash_update(hash, (const uint8_t *)abc, 1));
+  CHECK_OK(graviola_hash_update(hash, NULL, 0));
+  CHECK_OK(graviola_hash_update(hash, (const uint8_t *)abc + 1, 2));
+  CHECK_STATUS(graviola_hash_finish(hash, out, 31, &written),
+               GRAVIOLA_STATUS_WRONG_LENGTH);
+  CHECK_OK(graviola_hash_finish(hash, out, sizeof out, &written));
+  CHECK(written == 32);
+  CHECK(memcmp(out, expected, 32) == 0);
+  graviola_hash_free(hash);
+
+  CHECK_STATUS(graviola_hash_new(GRAVIOLA_HASH_ALGORITHM_SHA384, NULL),
+               GRAVIOLA_STATUS_NULL_POINTER);
+  hash = (graviola_hash *)1;
+  CHECK_STATUS(graviola_hash_new(0, &hash), GRAVIOLA_STATUS_UNKNOWN_ALGORITHM);
+  CHECK(hash == NULL);
+  CHECK_OK(graviola_hash_new(GRAVIOLA_HASH_ALGORITHM_SHA512, &hash));
+  CHECK_OK(graviola_hash_finish(hash, out, sizeof out, &written));
+  CHECK(written == 64);
+  graviola_hash_free(hash);
+  graviola_hash_free(NULL);
+}
+
+static void test_hmac(void) {
+  /* RFC4231 test case 2 */
+  static const char *key = "Jefe";
+  static const char *data = "what do ya want for nothing?";
+  uint8_t expected[32], out[GRAVIOLA_HASH_MAX_OUTPUT_LEN];
+  size_t written = 0;
+  graviola_hmac *hmac = NULL;
+
+  from_hex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+           expected, sizeof expected);
+
+  CHECK_OK(graviola_hmac_new(GRAVIOLA_HASH_ALGORITHM_SHA256,
+                             (const uint8_t *)key, strlen(key), &hmac));
+  CHECK_OK(graviola_hmac_update(hmac, (const uint8_t *)data, strlen(data)));
+  CHECK_OK(graviola_hmac_finish(hmac, out, sizeof out, &written));
+  CHECK(written == 32);
+  CHECK(memcmp(out, expected, 32) == 0);
+  CHECK_OK(graviola_hmac_verify(hmac, expected, sizeof expected));
+  expected[0] ^= 1;
+  CHECK_STATUS(graviola_hmac_verify(hmac, expected, sizeof expected),
+               GRAVIOLA_STATUS_BAD_SIGNATURE);
+  CHECK_STATUS(graviola_hmac_verify(hmac, expected, 16),
+               GRAVIOLA_STATUS_BAD_SIGNATURE);
+  graviola_hmac_free(hmac);
+}
+
+static void test_aes_gcm(void) {
+  /* "Test Case 2" from the original GCM specification */
+  uint8_t key[16] = {0}, nonce[GRAVIOLA_AEAD_NONCE_LEN] = {0};
+  uint8_t msg[16] = {0}, expected_ct[16], expected_tag[16];
+  uint8_t tag[GRAVIOLA_AEAD_TAG_LEN];
+  graviola_aes_gcm *aead = NULL;
+
+  from_hex("0388dace60b6a392f328c2b971b2fe78", expected_ct, 16);
+  from_hex("ab6e47d42cec13bdf53a67b21257bddf", expected_tag, 16);
+
+  CHECK_STATUS(graviola_aes_gcm_new(key, 24, &aead),
+               GRAVIOLA_STATUS_WRONG_LENGTH);
+  CHECK(aead == NULL);
+  CHECK_OK(graviola_aes_gcm_new(key, sizeof key, &aead));
+  CHECK_OK(graviola_aes_gcm_encrypt(aead, nonce, NULL, 0, msg, sizeof msg, tag));
+  CHECK(memcmp(msg, expected_ct, 16) == 0);
+  CHECK(memcmp(tag, expected_tag, 16) == 0);
+
+  CHECK_OK(graviola_aes_gcm_decrypt(aead, nonce, NULL, 0, msg, sizeof msg,
+                                    tag, sizeof tag));
+  CHECK(msg[0] == 0 && memcmp(msg, msg + 1, 15) == 0);
+
+  CHECK_OK(graviola_aes_gcm_encrypt(aead, nonce, NULL, 0, msg, sizeof msg, tag));
+  tag[0] ^= 1;
+  CHECK_STATUS(graviola_aes_gcm_decrypt(aead, nonce, NULL, 0, msg, sizeof msg,
+                                        tag, sizeof tag),
+               GRAVIOLA_STATUS_DECRYPT_FAILED);
+  CHECK_STATUS(graviola_aes_gcm_encrypt(aead, NULL, NULL, 0, msg, sizeof msg, tag),
+               GRAVIOLA_STATUS_NULL_POINTER);
+  graviola_aes_gcm_free(aead);
+}
+
+static void test_chacha20poly1305(void) {
+  uint8_t key[32], nonce[GRAVIOLA_AEAD_NONCE_LEN] = {1};
+  uint8_t msg[100], tag[GRAVIOLA_AEAD_TAG_LEN];
+  static const char *aad = "header";
+  graviola_chacha20poly1305 *aead = NULL;
+
+  memset(key, 0x42, sizeof key);
+  memset(msg, 0x11, sizeof msg);
+
+  CHECK_STATUS(graviola_chacha20poly1305_new(key, 16, &aead),
+               GRAVIOLA_STATUS_WRONG_LENGTH);
+  CHECK_OK(graviola_chacha20poly1305_new(key, sizeof key, &aead));
+  CHECK_OK(graviola_chacha20poly1305_encrypt(
+      aead, nonce, (const uint8_t *)aad, strlen(aad), msg, sizeof msg, tag));
+  CHECK(msg[0] != 0x11);
+  CHECK_STATUS(graviola_chacha20poly1305_decrypt(aead, nonce, NULL, 0, msg,
+                                                 sizeof msg, tag, sizeof tag),
+               GRAVIOLA_STATUS_DECRYPT_FAILED);
+  CHECK(msg[0] == 0 && msg[99] == 0);
+
+  memset(msg, 0x11, sizeof msg);
+  CHECK_OK(graviola_chacha20poly1305_encrypt(
+      aead, nonce, (const uint8_t *)aad, strlen(aad), msg, sizeof msg, tag));
+  CHECK_OK(graviola_chacha20poly1305_decrypt(aead, nonce, (const uint8_t *)aad,
+                                             strlen(aad), msg, sizeof msg, tag,
+                                             sizeof tag));
+  CHECK(msg[0] == 0x11 && msg[99] == 0x11);
+  graviola_chacha20poly1305_free(aead);
+}
+
+static void test_kx(graviola_kx_algorithm alg, size_t public_len,
+                    size_t secret_len) {
+  graviola_kx_private_key *a = NULL, *b = NULL;
+  uint8_t a_pub[GRAVIOLA_KX_MAX_PUBLIC_KEY_LEN], b_pub[GRAVIOLA_KX_MAX_PUBLIC_KEY_LEN];
+  uint8_t a_secret[GRAVIOLA_KX_MAX_SHARED_SECRET_LEN];
+  uint8_t b_secret[GRAVIOLA_KX_MAX_SHARED_SECRET_LEN];
+  size_t a_pub_len = 0, b_pub_len = 0, a_secret_len = 0, b_secret_len = 0;
+
+  CHECK_OK(graviola_kx_private_key_generate(alg, &a));
+  CHECK_OK(graviola_kx_private_key_generate(alg, &b));
+  CHECK_OK(graviola_kx_private_key_public_key(a, a_pub, sizeof a_pub, &a_pub_len));
+  CHECK_OK(graviola_kx_private_key_public_key(b, b_pub, sizeof b_pub, &b_pub_len));
+  CHECK(a_pub_len == public_len && b_pub_len == public_len);
+
+  CHECK_OK(graviola_kx_private_key_agree(a, b_pub, b_pub_len, a_secret,
+                                         sizeof a_secret, &a_secret_len));
+  CHECK_OK(graviola_kx_private_key_agree(b, a_pub, a_pub_len, b_secret,
+                                         sizeof b_secret, &b_secret_len));
+  CHECK(a_secret_len == secret_len && b_secret_len == secret_len);
+  CHECK(memcmp(a_secret, b_secret, secret_len) == 0);
+
+  /* a truncated peer key is rejected (and consumes the private key) */
+  CHECK_OK(graviola_kx_private_key_generate(alg, &a));
+  CHECK_STATUS(graviola_kx_private_key_agree(a, b_pub, b_pub_len - 1, a_secret,
+                                             sizeof a_secret, &a_secret_len),
+               GRAVIOLA_STATUS_WRONG_LENGTH);
+
+  CHECK_OK(graviola_kx_private_key_generate(alg, &a));
+  graviola_kx_private_key_free(a);
+}
+
+static void test_sign(const char *path, graviola_signature_scheme good,
+                      graviola_signature_scheme bad) {
+  uint8_t *der = NULL;
+  size_t der_len = 0;
+  graviola_signing_key *key = NULL;
+  graviola_verifying_key *public_key = NULL;
+  uint8_t sig[GRAVIOLA_MAX_SIGNATURE_LEN];
+  size_t sig_len = 0;
+  static const char *msg = "hello world";
+  const uint8_t *m = (const uint8_t *)msg;
+
+  if (!read_file(path, &der, &der_len)) {
+    failures++;
+    return;
+  }
+
+  CHECK_OK(graviola_signing_key_from_pkcs8_der(der, der_len, &key));
+  CHECK_OK(graviola_signing_key_public_key(key, &public_key));
+  CHECK_OK(graviola_signing_key_sign(key, good, m, strlen(msg), sig, sizeof sig,
+                                     &sig_len));
+  CHECK_OK(graviola_verifying_key_verify(public_key, good, m, strlen(msg), sig,
+                                         sig_len));
+  CHECK_STATUS(graviola_verifying_key_verify(public_key, good, m, strlen(msg) - 1,
+                                             sig, sig_len),
+               GRAVIOLA_STATUS_BAD_SIGNATURE);
+  CHECK_STATUS(graviola_signing_key_sign(key, bad, m, strlen(msg), sig,
+                                         sizeof sig, &sig_len),
+               GRAVIOLA_STATUS_SCHEME_MISMATCH);
+  CHECK_STATUS(graviola_verifying_key_verify(public_key, bad, m, strlen(msg),
+                                             sig, sig_len),
+               GRAVIOLA_STATUS_SCHEME_MISMATCH);
+  CHECK_STATUS(graviola_signing_key_sign(key, good, m, strlen(msg), sig, 8,
+                                         &sig_len),
+               GRAVIOLA_STATUS_WRONG_LENGTH);
+  CHECK_STATUS(graviola_signing_key_sign(key, 9, m, strlen(msg), sig, sizeof sig,
+                                         &sig_len),
+               GRAVIOLA_STATUS_UNKNOWN_ALGORITHM);
+  CHECK_STATUS(graviola_verifying_key_verify(public_key, 0xffffffff, m,
+                                             strlen(msg), sig, sig_len),
+               GRAVIOLA_STATUS_UNKNOWN_ALGORITHM);
+
+  graviola_verifying_key_free(public_key);
+  graviola_signing_key_free(key);
+
+  /* truncated keys are rejected */
+  key = (graviola_signing_key *)1;
+  CHECK(graviola_signing_key_from_pkcs8_der(der, der_len - 1, &key) !=
+        GRAVIOLA_STATUS_OK);
+  CHECK(key == NULL);
+  free(der);
+}
+
+static void test_verify_x962(void) {
+  graviola_kx_private_key *kx = NULL;
+  graviola_verifying_key *key = NULL;
+  uint8_t point[GRAVIOLA_KX_MAX_PUBLIC_KEY_LEN];
+  size_t point_len = 0;
+
+  CHECK_STATUS(graviola_kx_private_key_generate(4, &kx),
+               GRAVIOLA_STATUS_UNKNOWN_ALGORITHM);
+  CHECK_OK(graviola_kx_private_key_generate(GRAVIOLA_KX_ALGORITHM_P384, &kx));
+  CHECK_OK(graviola_kx_private_key_public_key(kx, point, sizeof point, &point_len));
+  graviola_kx_private_key_free(kx);
+
+  CHECK_OK(graviola_verifying_key_from_ecdsa_x962(point, point_len, &key));
+  graviola_verifying_key_free(key);
+  CHECK_STATUS(graviola_verifying_key_from_ecdsa_x962(point, 64, &key),
+               GRAVIOLA_STATUS_WRONG_LENGTH);
+  point[point_len - 1] ^= 1;
+  CHECK_STATUS(graviola_verifying_key_from_ecdsa_x962(point, point_len, &key),
+               GRAVIOLA_STATUS_NOT_ON_CURVE);
+}
+
+int main(int argc, char **argv) {
+  if (argc != 4) {
+    fprintf(stderr, "usage: %s <rsa.pkcs8.der> <p256.pkcs8.der> <p384.pkcs8.der>\n",
+            argv[0]);
+    return 2;
+  }
+
+  test_status();
+  test_hash();
+  test_hmac();
+  test_aes_gcm();
+  test_chacha20poly1305();
+  test_kx(GRAVIOLA_KX_ALGORITHM_X25519, 32, 32);
+  test_kx(GRAVIOLA_KX_ALGORITHM_P256, 65, 32);
+  test_kx(GRAVIOLA_KX_ALGORITHM_P384, 97, 48);
+  test_sign(argv[1], GRAVIOLA_SIGNATURE_SCHEME_RSA_PSS_SHA256,
+            GRAVIOLA_SIGNATURE_SCHEME_ECDSA_P256_SHA256);
+  test_sign(argv[1], GRAVIOLA_SIGNATURE_SCHEME_RSA_PKCS1_SHA512,
+            GRAVIOLA_SIGNATURE_SCHEME_ECDSA_P384_SHA384);
+  test_sign(argv[2], GRAVIOLA_SIGNATURE_SCHEME_ECDSA_P256_SHA256,
+            GRAVIOLA_SIGNATURE_SCHEME_ECDSA_P384_SHA384);
+  test_sign(argv[3], GRAVIOLA_SIGNATURE_SCHEME_ECDSA_P384_SHA384,
+            GRAVIOLA_SIGNATURE_SCHEME_RSA_PKCS1_SHA256);
+  test_verify_x962();
+
+  if (failures) {
+    fprintf(stderr, "%d checks failed\n", failures);
+    return 1;
+  }
+  printf("all checks passed\n");
+  return 0;
+}
@@ -20,26 +20,104 @@ pub enum Error {
     /// A random number generator returned an error or fixed values.
     RngFailed,
 
+    /// A deterministic random bit generator must be reseeded before further use.
+    ReseedRequired,
+
     /// Presented signature is invalid.
     BadSignature,
 
+    /// A key was used with an algorithm or parameters it is restricted from.
+    ///
+    /// For example, an RSA key with `RSASSA-PSS-params` cannot be used
+    /// for RSASSA-PKCS1-v1_5.
+    KeyRestricted,
+
     /// Presented AEAD tag/aad/ciphertext/nonce was wrong
     DecryptFailed,
 
     /// An ASN.1 encoding/decoding error.
+    #[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
     Asn1Error(crate::high::asn1::Error),
 
     /// A key formatting/validation error.
     KeyFormatError(KeyFormatError),
+
+    /// A JSON Web Signature was malformed or unacceptable.
+    JwsError(JwsError),
+
+    /// A known-answer or pairwise consistency self-test failed.
+    ///
+    /// See [`crate::self_test()`].
+    SelfTestFailed(SelfTestFailure),
 }
 
+/// Ways a key encoding may be unacceptable.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum KeyFormatError {
+    /// A PKCS#8 `version` other than `v1` was encountered.
     UnsupportedPkcs8Version,
+
+    /// A PKCS#8 `privateKeyAlgorithm` did not match the expected key type.
     MismatchedPkcs8Algorithm,
+
+    /// A PKCS#8 `privateKeyAlgorithm` had unexpected parameters (for
+    /// example, the wrong curve).
     MismatchedPkcs8Parameters,
+
+    /// A SEC1 `version` other than `ecPrivkeyVer1` was encountered.
     UnsupportedSec1Version,
+
+    /// A SEC1 `publicKey` was not the public key for the private key.
+    MismatchedSec1PublicKey,
+
+    /// An RSA `modulus` was not the product of `prime1` and `prime2`.
+    MismatchedRsaModulus,
+
+    /// An RSA `privateExponent` was not the inverse of `publicExponent`.
+    MismatchedRsaPrivateExponent,
+
+    /// An RSA `exponent1` or `exponent2` was not the inverse of
+    /// `publicExponent`, modulo `prime1 - 1` or `prime2 - 1` respectively.
+    MismatchedRsaCrtExponent,
+
+    /// A `SubjectPublicKeyInfo` `algorithm` did not match the expected key type.
+    MismatchedSpkiAlgorithm,
+
+    /// A `SubjectPublicKeyInfo` `algorithm` had unexpected parameters.
+    MismatchedSpkiParameters,
+
+    /// An `RSASSA-PSS-params` specified a hash function, mask generation
+    /// function or trailer field that is not supported.
+    ///
+    /// Only SHA-256, SHA-384 and SHA-512 are supported, and MGF1 must
+    /// use the same hash function.
+    UnsupportedRsaPssParameters,
+
+    /// An `EncryptedPrivateKeyInfo` used an encryption scheme, key
+    /// derivation function or parameters that are not supported.
+    ///
+    /// Only PBES2 is supported, using PBKDF2 with HMAC-SHA256, and
+    /// AES-128 or AES-256 in CBC or GCM mode.
+    UnsupportedPkcs8Encryption,
+
+    /// A PEM encoding was malformed: for example, it had missing or
+    /// mismatched boundary lines, or invalid base64.
+    InvalidPem,
+
+    /// A PEM label did not match the expected key type.
+    MismatchedPemLabel,
+
+    /// A JSON Web Key was malformed: for example, it was not valid
+    /// JSON, or a required member was missing or had invalid base64url.
+    InvalidJwk,
+
+    /// A JSON Web Key's `kty` or `crv` did not match the expected key type.
+    MismatchedJwkKeyType,
+
+    /// A JSON Web Key's public key members were not the public key
+    /// for its private key.
+    MismatchedJwkPublicKey,
 }
 
 impl From<KeyFormatError> for Error {
@@ -47,3 +125,80 @@ impl From<KeyFormatError> for Error {
         Self::KeyFormatError(kfe)
     }
 }
+
+/// Ways a JSON Web Signature may be unacceptable.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JwsError {
+    /// The compact serialisation or protected header was malformed: for
+    /// example, it did not have three parts, had invalid base64url, or
+    /// the header was not a valid JSON object.
+    Malformed,
+
+    /// The header's `alg` is not supported.
+    ///
+    /// Only `ES256`, `ES384`, `RS256` and `PS256` are supported.  In
+    /// particular, `none` is never accepted.
+    UnsupportedAlgorithm,
+
+    /// The header's `alg` did not match the key used for verification.
+    MismatchedAlgorithm,
+
+    /// The header contained a `crit` member.
+    ///
+    /// No extensions are supported, so any critical extension is
+    /// not understood.
+    UnsupportedCriticalHeader,
+}
+
+impl From<JwsError> for Error {
+    fn from(jwse: JwsError) -> Self {
+        Self::JwsError(jwse)
+    }
+}
+
+/// Which algorithm failed a self-test.
+///
+/// See [`crate::self_test()`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelfTestFailure {
+    /// SHA-1.
+    Sha1,
+    /// SHA-224 and SHA-256.
+    Sha256,
+    /// SHA-384, SHA-512 and SHA-512/256.
+    Sha512,
+    /// The SHA-3 family.
+    Sha3,
+    /// BLAKE2b.
+    Blake2b,
+    /// BLAKE2s.
+    Blake2s,
+    /// BLAKE3.
+    Blake3,
+    /// HMAC.
+    Hmac,
+    /// HMAC_DRBG.
+    HmacDrbg,
+    /// CTR_DRBG.
+    CtrDrbg,
+    /// AES-GCM.
+    AesGcm,
+    /// ChaCha20-Poly1305.
+    ChaCha20Poly1305,
+    /// X25519 key agreement.
+    X25519,
+    /// P-256 key agreement.
+    P256,
+    /// P-384 key agreement.
+    P384,
+    /// Finite field Diffie-Hellman key agreement.
+    Ffdhe,
+    /// ECDSA on P-256.
+    EcdsaP256,
+    /// ECDSA on P-384.
+    EcdsaP384,
+    /// RSA signatures.
+    Rsa,
+}
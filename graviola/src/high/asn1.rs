@@ -7,25 +7,40 @@ use core::mem;
 use core::mem::size_of;
 
 macro_rules! _asn1_struct_ty(
+    ([$context:literal] IMPLICIT SET OF $ty:ident) => { $crate::high::asn1::SequenceOf<'a, $ty<'a>, { $crate::high::asn1::Tag::CONTEXT_SPECIFIC | $crate::high::asn1::Tag::CONSTRUCTED | $context }> };
     ([$context:literal] $($itty:ident)+) => { $crate::high::asn1::ContextConstructed<'a, $context, $crate::high::asn1::_asn1_struct_ty!($($itty)+)> };
     (INTEGER) => { $crate::high::asn1::Integer<'a> };
+    (INTEGER OPTIONAL) => { Option<$crate::high::asn1::Integer<'a>> };
+    (BOOLEAN OPTIONAL) => { Option<$crate::high::asn1::Boolean> };
     (OBJECT IDENTIFIER) => { $crate::high::asn1::ObjectId };
+    (ANY) => { $crate::high::asn1::Any<'a> };
     (ANY OPTIONAL) => { Option<$crate::high::asn1::Any<'a>> };
+    (SEQUENCE OF OBJECT IDENTIFIER) => { $crate::high::asn1::SequenceOf<'a, $crate::high::asn1::ObjectId> };
+    (SEQUENCE OF $ty:ident) => { $crate::high::asn1::SequenceOf<'a, $ty<'a>> };
+    (SET OF $ty:ident) => { $crate::high::asn1::SetOf<'a, $ty<'a>> };
     (OCTET STRING) => { $crate::high::asn1::OctetString<'a> };
     (BIT STRING) => { $crate::high::asn1::BitString<'a> };
     ($ty:tt REF) => { $ty<'a> };
+    ($ty:tt REF OPTIONAL) => { Option<$ty<'a>> };
     ($ty:tt) => { $ty };
 );
 pub(crate) use _asn1_struct_ty;
 
 macro_rules! _asn1_struct_parse_ty(
+    ($p:ident, [$context:literal] IMPLICIT SET OF $ty:ident) => { $crate::high::asn1::Type::parse(&mut $p)? };
     ($p:ident, [$context:literal] $($itty:ident)+) => { $crate::high::asn1::ContextConstructed::parse(&mut $p)? };
     ($p:ident, INTEGER) => { $crate::high::asn1::Integer::parse(&mut $p)? };
+    ($p:ident, INTEGER OPTIONAL) => { $crate::high::asn1::parse_optional(&mut $p, $crate::high::asn1::Tag::INTEGER)? };
+    ($p:ident, BOOLEAN OPTIONAL) => { $crate::high::asn1::parse_optional(&mut $p, $crate::high::asn1::Tag::BOOLEAN)? };
     ($p:ident, OBJECT IDENTIFIER) => { $crate::high::asn1::ObjectId::parse(&mut $p)? };
+    ($p:ident, ANY) => { $crate::high::asn1::Any::parse(&mut $p)? };
     ($p:ident, ANY OPTIONAL) => { Option::<$crate::high::asn1::Any<'_>>::parse(&mut $p)? };
+    ($p:ident, SEQUENCE OF $($itty:ident)+) => { $crate::high::asn1::Type::parse(&mut $p)? };
+    ($p:ident, SET OF $ty:ident) => { $crate::high::asn1::Type::parse(&mut $p)? };
     ($p:ident, OCTET STRING) => { $crate::high::asn1::OctetString::parse(&mut $p)? };
     ($p:ident, BIT STRING) => { $crate::high::asn1::BitString::parse(&mut $p)? };
     ($p:ident, $ty:tt REF) => { $ty::parse(&mut $p)? };
+    ($p:ident, $ty:tt REF OPTIONAL) => { $crate::high::asn1::parse_optional::<$ty<'_>>(&mut $p, $crate::high::asn1::Tag::SEQUENCE)? };
     ($p:ident, $ty:tt) => { $ty::parse(&mut $p)? };
 );
 pub(crate) use _asn1_struct_parse_ty;
@@ -142,6 +157,9 @@ pub(crate) enum Any<'a> {
     OctetString(OctetString<'a>),
     BitString(BitString<'a>),
     ObjectId(ObjectId),
+    Sequence(Sequence<'a>),
+    Utf8String(Utf8String<'a>),
+    PrintableString(PrintableString<'a>),
 }
 
 impl<'a> Type<'a> for Any<'a> {
@@ -152,6 +170,9 @@ impl<'a> Type<'a> for Any<'a> {
             Tag::OCTET_STRING => Ok(Self::OctetString(OctetString::parse(p)?)),
             Tag::BIT_STRING => Ok(Self::BitString(BitString::parse(p)?)),
             Tag::OBJECT_ID => Ok(Self::ObjectId(ObjectId::parse(p)?)),
+            Tag::SEQUENCE => Ok(Self::Sequence(Sequence::parse(p)?)),
+            Tag::UTF8_STRING => Ok(Self::Utf8String(Utf8String::parse(p)?)),
+            Tag::PRINTABLE_STRING => Ok(Self::PrintableString(PrintableString::parse(p)?)),
             _ => Err(Error::UnexpectedTag),
         }
     }
@@ -163,6 +184,9 @@ impl<'a> Type<'a> for Any<'a> {
             Self::BitString(bs) => bs.encode(encoder),
             Self::OctetString(os) => os.encode(encoder),
             Self::ObjectId(obj) => obj.encode(encoder),
+            Self::Sequence(seq) => seq.encode(encoder),
+            Self::Utf8String(s) => s.encode(encoder),
+            Self::PrintableString(s) => s.encode(encoder),
         }
     }
 
@@ -173,6 +197,9 @@ impl<'a> Type<'a> for Any<'a> {
             Self::BitString(bs) => bs.encoded_len(),
             Self::OctetString(os) => os.encoded_len(),
             Self::ObjectId(obj) => obj.encoded_len(),
+            Self::Sequence(seq) => seq.encoded_len(),
+            Self::Utf8String(s) => s.encoded_len(),
+            Self::PrintableString(s) => s.encoded_len(),
         }
     }
 }
@@ -201,9 +228,32 @@ impl<'a, T: Type<'a>> Type<'a> for Option<T> {
     }
 }
 
+/// Parses an optional item, which is present if the next tag is `tag`.
+///
+/// Unlike `Option<T>`, this allows later items in the same `SEQUENCE`.
+pub(crate) fn parse_optional<'a, T: Type<'a>>(
+    p: &mut Parser<'a>,
+    tag: u8,
+) -> Result<Option<T>, Error> {
+    match p.peek_tag() {
+        Ok(tt) if tt.0 == tag => T::parse(p).map(Some),
+        _ => Ok(None),
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) struct ContextConstructed<'a, const ID: u8, T: Type<'a>>(Option<T>, PhantomData<&'a ()>);
 
+impl<'a, const ID: u8, T: Type<'a>> ContextConstructed<'a, ID, T> {
+    pub(crate) fn new(inner: Option<T>) -> Self {
+        Self(inner, PhantomData)
+    }
+
+    pub(crate) fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
 impl<'a, const ID: u8, T: Type<'a>> Type<'a> for ContextConstructed<'a, ID, T> {
     fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
         let tag = Tag::context_constructed(ID);
@@ -260,7 +310,9 @@ impl Type<'_> for Null {
     }
 }
 
+// Exported only via `Curve::oid()`, which needs an elliptic curve.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(not(any(feature = "p256", feature = "p384")), allow(unreachable_pub))]
 pub struct ObjectId {
     buf: [u8; Self::MAX_LEN],
     used: usize,
@@ -358,7 +410,11 @@ impl<'a> Integer<'a> {
         Self { twos_complement }
     }
 
-    fn from_bytes(mut bytes: &'a [u8]) -> Self {
+    /// Makes a minimal encoding of the two's complement integer `bytes`.
+    ///
+    /// Positive integers with the top bit set must be given a leading
+    /// zero byte.
+    pub(crate) fn from_bytes(mut bytes: &'a [u8]) -> Self {
         static ZERO: &[u8] = &[0];
 
         if bytes.is_empty() || bytes.iter().all(|b| *b == 0x00) {
@@ -396,10 +452,9 @@ impl<'a> Integer<'a> {
             return Err(Error::IntegerOutOfRange);
         }
 
-        let mut bytes = [0u8; 8];
-        for (i, b) in self.twos_complement.iter().enumerate() {
-            bytes[bytes.len() - 1 - i] = *b;
-        }
+        let mut bytes = [0u8; size_of::<usize>()];
+        bytes[size_of::<usize>() - self.twos_complement.len()..]
+            .copy_from_slice(self.twos_complement);
         Ok(usize::from_be_bytes(bytes))
     }
 }
@@ -433,7 +488,6 @@ pub(crate) struct OctetString<'a> {
 }
 
 impl<'a> OctetString<'a> {
-    #[cfg(test)]
     pub(crate) fn new(octets: &'a [u8]) -> Self {
         Self { octets }
     }
@@ -465,6 +519,16 @@ pub(crate) struct BitString<'a> {
     octets: &'a [u8],
 }
 
+impl<'a> BitString<'a> {
+    pub(crate) fn new(octets: &'a [u8]) -> Self {
+        Self { octets }
+    }
+
+    pub(crate) fn into_octets(self) -> &'a [u8] {
+        self.octets
+    }
+}
+
 impl<'a> Type<'a> for BitString<'a> {
     fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
         let (_, octets) = p.take(Tag::bit_string())?;
@@ -490,6 +554,387 @@ impl<'a> Type<'a> for BitString<'a> {
     }
 }
 
+/// A `SEQUENCE` whose contents are not interpreted.
+///
+/// This retains the whole encoding, so it may be later decoded
+/// as a specific type with [`Sequence::decode()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Sequence<'a> {
+    encoding: &'a [u8],
+}
+
+impl<'a> Sequence<'a> {
+    /// Wraps an existing encoding of a `SEQUENCE`.
+    #[cfg(any(feature = "pbes2", feature = "rsa"))]
+    pub(crate) fn new(encoding: &'a [u8]) -> Self {
+        Self { encoding }
+    }
+
+    #[cfg(any(test, feature = "pbes2", feature = "rsa"))]
+    pub(crate) fn decode<T: Type<'a>>(&self) -> Result<T, Error> {
+        T::from_bytes(self.encoding)
+    }
+}
+
+impl<'a> Type<'a> for Sequence<'a> {
+    fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
+        let start = p.input;
+        p.take(Tag::sequence())?;
+        let encoding = &start[..start.len() - p.left()];
+        Ok(Self { encoding })
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        encoder.append_slice(self.encoding)?;
+        Ok(encoder.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.encoding.len()
+    }
+}
+
+/// A `SEQUENCE OF` items of type `T`.
+///
+/// `TAG` may be changed to make a `SET OF`, or an implicitly-tagged
+/// `SET OF`.  For encoding, this is made from a slice of items.  Note
+/// that DER requires the items of a `SET OF` to be given in order of
+/// their encodings.
+///
+/// When parsed, the encoded items are retained.  They are checked
+/// when parsing, and decoded by [`SequenceOf::iter()`].
+#[cfg(feature = "x509")]
+#[derive(Clone, Debug)]
+pub(crate) struct SequenceOf<'a, T, const TAG: u8 = { Tag::SEQUENCE }> {
+    items: Items<'a, T>,
+}
+
+/// A `SET OF` items of type `T`.
+#[cfg(feature = "x509")]
+pub(crate) type SetOf<'a, T> = SequenceOf<'a, T, { Tag::SET }>;
+
+#[cfg(feature = "x509")]
+#[derive(Clone, Debug)]
+enum Items<'a, T> {
+    Slice(&'a [T]),
+    Encoded(&'a [u8]),
+}
+
+#[cfg(feature = "x509")]
+impl<'a, T: Type<'a> + Clone, const TAG: u8> SequenceOf<'a, T, TAG> {
+    pub(crate) fn new(items: &'a [T]) -> Self {
+        Self {
+            items: Items::Slice(items),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> SequenceOfIter<'a, T> {
+        SequenceOfIter {
+            items: self.items.clone(),
+        }
+    }
+
+    fn body_len(&self) -> usize {
+        match &self.items {
+            Items::Slice(items) => items.iter().map(|item| item.encoded_len()).sum(),
+            Items::Encoded(body) => body.len(),
+        }
+    }
+}
+
+#[cfg(feature = "x509")]
+impl<'a, T: Type<'a> + Clone, const TAG: u8> Type<'a> for SequenceOf<'a, T, TAG> {
+    fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
+        let (_, body) = p.take(Tag(TAG))?;
+        let r = Self {
+            items: Items::Encoded(body),
+        };
+
+        for item in r.iter() {
+            item?;
+        }
+        Ok(r)
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let mut body = encoder.begin(Tag(TAG), self.body_len())?;
+        match &self.items {
+            Items::Slice(items) => {
+                for item in items.iter() {
+                    item.encode(&mut body)?;
+                }
+            }
+            Items::Encoded(encoded) => body.append_slice(encoded)?,
+        }
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(self.body_len())
+    }
+}
+
+#[cfg(feature = "x509")]
+pub(crate) struct SequenceOfIter<'a, T> {
+    items: Items<'a, T>,
+}
+
+#[cfg(feature = "x509")]
+impl<'a, T: Type<'a> + Clone> Iterator for SequenceOfIter<'a, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.items {
+            Items::Slice(items) => {
+                let (first, rest) = items.split_first()?;
+                *items = rest;
+                Some(Ok(first.clone()))
+            }
+            Items::Encoded([]) => None,
+            Items::Encoded(encoded) => {
+                let mut p = Parser::new(encoded);
+                let item = T::parse(&mut p);
+                // stop after any error
+                *encoded = match item {
+                    Ok(_) => p.input,
+                    Err(_) => &[],
+                };
+                Some(item)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "x509")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Boolean(pub(crate) bool);
+
+#[cfg(feature = "x509")]
+impl Type<'_> for Boolean {
+    fn parse(p: &mut Parser<'_>) -> Result<Self, Error> {
+        let (_, body) = p.take(Tag::boolean())?;
+        match body {
+            [0x00] => Ok(Self(false)),
+            [0xff] => Ok(Self(true)),
+            _ => Err(Error::NonCanonicalEncoding),
+        }
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let mut body = encoder.begin(Tag::boolean(), 1)?;
+        body.push(if self.0 { 0xff } else { 0x00 })?;
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(1)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Utf8String<'a> {
+    value: &'a str,
+}
+
+#[cfg(feature = "x509")]
+impl<'a> Utf8String<'a> {
+    pub(crate) fn new(value: &'a str) -> Self {
+        Self { value }
+    }
+
+    pub(crate) fn as_str(&self) -> &'a str {
+        self.value
+    }
+}
+
+impl<'a> Type<'a> for Utf8String<'a> {
+    fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
+        let (_, body) = p.take(Tag::utf8_string())?;
+        let value = core::str::from_utf8(body).map_err(|_| Error::IllegalString)?;
+        Ok(Self { value })
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let mut body = encoder.begin(Tag::utf8_string(), self.value.len())?;
+        body.append_slice(self.value.as_bytes())?;
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(self.value.len())
+    }
+}
+
+/// A `PrintableString`: a restricted subset of ASCII.
+///
+/// The contents are checked when parsing, and by [`PrintableString::new()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct PrintableString<'a> {
+    value: &'a str,
+}
+
+impl<'a> PrintableString<'a> {
+    pub(crate) fn new(value: &'a str) -> Result<Self, Error> {
+        match value.bytes().all(Self::is_printable) {
+            true => Ok(Self { value }),
+            false => Err(Error::IllegalString),
+        }
+    }
+
+    #[cfg(feature = "x509")]
+    pub(crate) fn as_str(&self) -> &'a str {
+        self.value
+    }
+
+    fn is_printable(c: u8) -> bool {
+        matches!(c, b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b' ' | b'\'' | b'(' | b')' | b'+'..=b'/' | b':' | b'=' | b'?')
+    }
+}
+
+impl<'a> Type<'a> for PrintableString<'a> {
+    fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
+        let (_, body) = p.take(Tag::printable_string())?;
+        let value = core::str::from_utf8(body).map_err(|_| Error::IllegalString)?;
+        Self::new(value)
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let mut body = encoder.begin(Tag::printable_string(), self.value.len())?;
+        body.append_slice(self.value.as_bytes())?;
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(self.value.len())
+    }
+}
+
+/// A `Time`, which is a `UTCTime` or `GeneralizedTime`.
+///
+/// This holds the number of seconds since the UNIX epoch.  As
+/// required by RFC5280, years before 2050 are encoded as a
+/// `UTCTime`, and all times are in UTC with whole seconds.
+#[cfg(feature = "x509")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Time(pub(crate) u64);
+
+#[cfg(feature = "x509")]
+impl Time {
+    /// Returns the year, month, day, hour, minute and second.
+    fn to_civil(self) -> [u64; 6] {
+        // from <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+        let (days, secs) = (self.0 / 86_400, self.0 % 86_400);
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + (month <= 2) as u64;
+        [year, month, day, secs / 3_600, (secs / 60) % 60, secs % 60]
+    }
+
+    fn from_civil([year, month, day, hour, minute, second]: [u64; 6]) -> Result<Self, Error> {
+        if year < 1970 || !(1..=12).contains(&month) || hour > 23 || minute > 59 || second > 59 {
+            return Err(Error::IllegalTime);
+        }
+
+        // from <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+        let y = if month <= 2 { year - 1 } else { year };
+        let era = y / 400;
+        let yoe = y - era * 400;
+        let mp = if month > 2 { month - 3 } else { month + 9 };
+        let doy = (153 * mp + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146_097 + doe - 719_468;
+        let r = Self(days * 86_400 + hour * 3_600 + minute * 60 + second);
+
+        // reject days past the end of the month
+        match day >= 1 && r.to_civil()[2] == day {
+            true => Ok(r),
+            false => Err(Error::IllegalTime),
+        }
+    }
+
+    fn body_len(&self) -> usize {
+        match self.to_civil()[0] {
+            0..=2049 => Self::UTC_TIME_LEN,
+            _ => Self::GENERALIZED_TIME_LEN,
+        }
+    }
+
+    const UTC_TIME_LEN: usize = 13;
+    const GENERALIZED_TIME_LEN: usize = 15;
+}
+
+#[cfg(feature = "x509")]
+impl Type<'_> for Time {
+    fn parse(p: &mut Parser<'_>) -> Result<Self, Error> {
+        let (year_digits, body) = match p.peek_tag()?.0 {
+            Tag::UTC_TIME => (2, p.take(Tag::utc_time())?.1),
+            _ => (4, p.take(Tag::generalized_time())?.1),
+        };
+
+        // YYMMDDHHMMSSZ or YYYYMMDDHHMMSSZ
+        let digits = match body.split_last() {
+            Some((b'Z', digits)) if digits.len() == year_digits + 10 => digits,
+            _ => return Err(Error::IllegalTime),
+        };
+        let (year, rest) = digits.split_at(year_digits);
+        let mut civil = [decimal(year)?, 0, 0, 0, 0, 0];
+        for (out, pair) in civil[1..].iter_mut().zip(rest.chunks(2)) {
+            *out = decimal(pair)?;
+        }
+
+        if year_digits == 2 {
+            civil[0] += if civil[0] < 50 { 2000 } else { 1900 };
+        }
+
+        // DER requires `UTCTime` for years before 2050
+        let r = Self::from_civil(civil)?;
+        match r.body_len() == body.len() {
+            true => Ok(r),
+            false => Err(Error::NonCanonicalEncoding),
+        }
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let civil = self.to_civil();
+        if civil[0] > 9999 {
+            return Err(Error::IllegalTime);
+        }
+
+        let (tag, year_digits) = match self.body_len() {
+            Self::UTC_TIME_LEN => (Tag::utc_time(), 2),
+            _ => (Tag::generalized_time(), 4),
+        };
+
+        let mut body = encoder.begin(tag, self.body_len())?;
+        for (i, value) in civil.iter().enumerate() {
+            let digits = if i == 0 { year_digits } else { 2 };
+            for d in (0..digits).rev() {
+                body.push(b'0' + ((value / 10u64.pow(d)) % 10) as u8)?;
+            }
+        }
+        body.push(b'Z')?;
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(self.body_len())
+    }
+}
+
+#[cfg(feature = "x509")]
+fn decimal(digits: &[u8]) -> Result<u64, Error> {
+    digits.iter().try_fold(0, |acc, d| match d {
+        b'0'..=b'9' => Ok(acc * 10 + (d - b'0') as u64),
+        _ => Err(Error::IllegalTime),
+    })
+}
+
 pub(crate) struct Parser<'a> {
     input: &'a [u8],
 }
@@ -664,6 +1109,8 @@ pub enum Error {
     UnsupportedLargeObjectId,
     UnsupportedLargeObjectLength,
     UnhandledBitString,
+    IllegalString,
+    IllegalTime,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -675,7 +1122,7 @@ impl Tag {
     }
 
     fn sequence() -> Self {
-        Self(0x30)
+        Self(Self::SEQUENCE)
     }
 
     fn integer() -> Self {
@@ -694,6 +1141,29 @@ impl Tag {
         Self(Self::NULL)
     }
 
+    #[cfg(feature = "x509")]
+    fn boolean() -> Self {
+        Self(Self::BOOLEAN)
+    }
+
+    fn utf8_string() -> Self {
+        Self(Self::UTF8_STRING)
+    }
+
+    fn printable_string() -> Self {
+        Self(Self::PRINTABLE_STRING)
+    }
+
+    #[cfg(feature = "x509")]
+    fn utc_time() -> Self {
+        Self(Self::UTC_TIME)
+    }
+
+    #[cfg(feature = "x509")]
+    fn generalized_time() -> Self {
+        Self(Self::GENERALIZED_TIME)
+    }
+
     fn object_id() -> Self {
         Self(Self::OBJECT_ID)
     }
@@ -702,14 +1172,25 @@ impl Tag {
         Self(Self::CONTEXT_SPECIFIC | Self::CONSTRUCTED | id)
     }
 
-    const INTEGER: u8 = 0x02;
+    #[cfg(feature = "x509")]
+    pub(crate) const BOOLEAN: u8 = 0x01;
+    pub(crate) const INTEGER: u8 = 0x02;
     const BIT_STRING: u8 = 0x03;
     const OCTET_STRING: u8 = 0x04;
     const NULL: u8 = 0x05;
     const OBJECT_ID: u8 = 0x06;
-
-    const CONSTRUCTED: u8 = 0x20;
-    const CONTEXT_SPECIFIC: u8 = 0x80;
+    const UTF8_STRING: u8 = 0x0c;
+    const PRINTABLE_STRING: u8 = 0x13;
+    #[cfg(feature = "x509")]
+    const UTC_TIME: u8 = 0x17;
+    #[cfg(feature = "x509")]
+    const GENERALIZED_TIME: u8 = 0x18;
+    pub(crate) const SEQUENCE: u8 = 0x30;
+    #[cfg(feature = "x509")]
+    pub(crate) const SET: u8 = 0x31;
+
+    pub(crate) const CONSTRUCTED: u8 = 0x20;
+    pub(crate) const CONTEXT_SPECIFIC: u8 = 0x80;
 }
 
 impl From<u8> for Tag {
@@ -744,6 +1225,22 @@ mod tests {
         check_integer_from_isize(127, &[0x02, 0x01, 0x7f]);
     }
 
+    #[test]
+    fn test_integer_as_usize() {
+        assert_eq!(Integer::new(&[0x00]).as_usize(), Ok(0));
+        assert_eq!(Integer::new(&[0x7f]).as_usize(), Ok(0x7f));
+        assert_eq!(Integer::new(&[0x00, 0x80]).as_usize(), Ok(0x80));
+        assert_eq!(Integer::new(&[0x09, 0x27, 0xc0]).as_usize(), Ok(600_000));
+        assert_eq!(
+            Integer::new(&[0x80]).as_usize(),
+            Err(Error::IntegerOutOfRange)
+        );
+        assert_eq!(
+            Integer::new(&[0x01; 9]).as_usize(),
+            Err(Error::IntegerOutOfRange)
+        );
+    }
+
     fn check_integer_from_bytes(bytes: &[u8], encoding: &[u8]) {
         let mut buf = vec![0u8; encoding.len()];
         let len = Integer::from_bytes(bytes)
@@ -762,6 +1259,122 @@ mod tests {
         assert_eq!(&buf, encoding);
     }
 
+    #[cfg(feature = "x509")]
+    #[test]
+    fn test_boolean() {
+        assert_eq!(Boolean::from_bytes(&[0x01, 0x01, 0xff]), Ok(Boolean(true)));
+        assert_eq!(Boolean::from_bytes(&[0x01, 0x01, 0x00]), Ok(Boolean(false)));
+        assert_eq!(
+            Boolean::from_bytes(&[0x01, 0x01, 0x01]),
+            Err(Error::NonCanonicalEncoding)
+        );
+        assert_eq!(
+            Boolean::from_bytes(&[0x01, 0x00]),
+            Err(Error::NonCanonicalEncoding)
+        );
+    }
+
+    #[cfg(feature = "x509")]
+    #[test]
+    fn test_strings() {
+        assert_eq!(
+            PrintableString::from_bytes(b"\x13\x02GB").map(|s| s.as_str()),
+            Ok("GB")
+        );
+        assert_eq!(
+            PrintableString::from_bytes(b"\x13\x01*"),
+            Err(Error::IllegalString)
+        );
+        assert_eq!(
+            PrintableString::new("a@b").unwrap_err(),
+            Error::IllegalString
+        );
+        assert_eq!(
+            Utf8String::from_bytes(b"\x0c\x02\xc3\xbc").map(|s| s.as_str()),
+            Ok("\u{fc}")
+        );
+        assert_eq!(
+            Utf8String::from_bytes(b"\x0c\x01\xc3"),
+            Err(Error::IllegalString)
+        );
+    }
+
+    #[cfg(feature = "x509")]
+    #[test]
+    fn test_sequence_of() {
+        let items = [Boolean(true), Boolean(false)];
+        let mut buf = [0u8; 8];
+        let len = SetOf::new(&items)
+            .encode(&mut Encoder::new(&mut buf))
+            .unwrap();
+        assert_eq!(
+            &buf[..len],
+            &[0x31, 0x06, 0x01, 0x01, 0xff, 0x01, 0x01, 0x00]
+        );
+
+        let parsed = SetOf::<Boolean>::from_bytes(&buf[..len]).unwrap();
+        assert_eq!(parsed.encoded_len(), len);
+        assert_eq!(
+            parsed.iter().collect::<Result<Vec<_>, _>>(),
+            Ok(items.to_vec())
+        );
+
+        // items are checked when parsing
+        assert_eq!(
+            SetOf::<Boolean>::from_bytes(&[0x31, 0x03, 0x01, 0x01, 0x02]).unwrap_err(),
+            Error::NonCanonicalEncoding
+        );
+        assert_eq!(
+            SequenceOf::<Boolean>::from_bytes(&[0x31, 0x00]).unwrap_err(),
+            Error::UnexpectedTag
+        );
+    }
+
+    #[cfg(feature = "x509")]
+    #[test]
+    fn test_time() {
+        for (secs, encoding) in [
+            (0, &b"\x17\x0d700101000000Z"[..]),
+            (951_782_400, b"\x17\x0d000229000000Z"),
+            (1_700_000_000, b"\x17\x0d231114221320Z"),
+            (2_524_607_999, b"\x17\x0d491231235959Z"),
+            (2_524_608_000, b"\x18\x0f20500101000000Z"),
+            (253_402_300_799, b"\x18\x0f99991231235959Z"),
+        ] {
+            let mut buf = [0u8; 17];
+            let len = Time(secs).encode(&mut Encoder::new(&mut buf)).unwrap();
+            assert_eq!(&buf[..len], encoding);
+            assert_eq!(Time(secs).encoded_len(), len);
+            assert_eq!(Time::from_bytes(encoding), Ok(Time(secs)));
+        }
+
+        assert_eq!(
+            Time(253_402_300_800)
+                .encode(&mut Encoder::new(&mut [0u8; 17]))
+                .unwrap_err(),
+            Error::IllegalTime
+        );
+
+        for bad in [
+            &b"\x17\x0d690101000000Z"[..],
+            b"\x17\x0d010229000000Z",
+            b"\x17\x0d011301000000Z",
+            b"\x17\x0d010101240000Z",
+            b"\x17\x0d0101010000+0Z",
+            b"\x17\x0b0101010000Z",
+            b"\x17\x0d010101000000+",
+            b"\x18\x0e20500101000000",
+        ] {
+            assert_eq!(Time::from_bytes(bad), Err(Error::IllegalTime));
+        }
+
+        // years before 2050 must use UTCTime
+        assert_eq!(
+            Time::from_bytes(b"\x18\x0f20490101000000Z"),
+            Err(Error::NonCanonicalEncoding)
+        );
+    }
+
     #[test]
     fn test_encode_oid() {
         assert_eq!(
@@ -3,6 +3,7 @@
 
 #![allow(non_upper_case_globals, dead_code)]
 
+#[allow(unused_imports)]
 use super::asn1_oid;
 
 asn1_oid! {
@@ -50,3 +51,208 @@ asn1_oid! {
         csor(3) nistalgorithm(4) hashalgs(2) 3
     }
 }
+
+asn1_oid! {
+    id_mgf1 OBJECT IDENTIFIER ::= {
+        iso(1) member_body(2)
+        us(840) rsadsi(113549) pkcs(1) 1 8
+    }
+}
+
+asn1_oid! {
+    id_RSASSA_PSS OBJECT IDENTIFIER ::= {
+        iso(1) member_body(2)
+        us(840) rsadsi(113549) pkcs(1) 1 10
+    }
+}
+
+asn1_oid! {
+    id_PBKDF2 OBJECT IDENTIFIER ::= {
+        iso(1) member_body(2)
+        us(840) rsadsi(113549) pkcs(1) pkcs_5(5) 12
+    }
+}
+
+asn1_oid! {
+    id_PBES2 OBJECT IDENTIFIER ::= {
+        iso(1) member_body(2)
+        us(840) rsadsi(113549) pkcs(1) pkcs_5(5) 13
+    }
+}
+
+asn1_oid! {
+    id_hmacWithSHA256 OBJECT IDENTIFIER ::= {
+        iso(1) member_body(2)
+        us(840) rsadsi(113549) digestAlgorithm(2) 9
+    }
+}
+
+asn1_oid! {
+    aes128_CBC_PAD OBJECT IDENTIFIER ::= {
+        joint_iso_itu_t(2) country(16) us(840) organization(1) gov(101)
+        csor(3) nistAlgorithms(4) aes(1) 2
+    }
+}
+
+asn1_oid! {
+    aes128_GCM OBJECT IDENTIFIER ::= {
+        joint_iso_itu_t(2) country(16) us(840) organization(1) gov(101)
+        csor(3) nistAlgorithms(4) aes(1) 6
+    }
+}
+
+asn1_oid! {
+    aes256_CBC_PAD OBJECT IDENTIFIER ::= {
+        joint_iso_itu_t(2) country(16) us(840) organization(1) gov(101)
+        csor(3) nistAlgorithms(4) aes(1) 42
+    }
+}
+
+asn1_oid! {
+    aes256_GCM OBJECT IDENTIFIER ::= {
+        joint_iso_itu_t(2) country(16) us(840) organization(1) gov(101)
+        csor(3) nistAlgorithms(4) aes(1) 46
+    }
+}
+
+asn1_oid! {
+    ecdsa_with_SHA256 OBJECT IDENTIFIER ::= {
+        iso(1) member_body(2) us(840) ansi_X9_62(10045) signatures(4)
+        ecdsa_with_SHA2(3) 2
+    }
+}
+
+asn1_oid! {
+    ecdsa_with_SHA384 OBJECT IDENTIFIER ::= {
+        iso(1) member_body(2) us(840) ansi_X9_62(10045) signatures(4)
+        ecdsa_with_SHA2(3) 3
+    }
+}
+
+asn1_oid! {
+    sha256WithRSAEncryption OBJECT IDENTIFIER ::= {
+        iso(1) member_body(2)
+        us(840) rsadsi(113549) pkcs(1) 1 11
+    }
+}
+
+asn1_oid! {
+    pkcs_9_at_extensionRequest OBJECT IDENTIFIER ::= {
+        iso(1) member_body(2)
+        us(840) rsadsi(113549) pkcs(1) pkcs_9(9) 14
+    }
+}
+
+asn1_oid! {
+    id_at_commonName OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) attributeType(4) 3
+    }
+}
+
+asn1_oid! {
+    id_at_countryName OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) attributeType(4) 6
+    }
+}
+
+asn1_oid! {
+    id_at_localityName OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) attributeType(4) 7
+    }
+}
+
+asn1_oid! {
+    id_at_stateOrProvinceName OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) attributeType(4) 8
+    }
+}
+
+asn1_oid! {
+    id_at_organizationName OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) attributeType(4) 10
+    }
+}
+
+asn1_oid! {
+    id_at_organizationalUnitName OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) attributeType(4) 11
+    }
+}
+
+asn1_oid! {
+    id_ce_keyUsage OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) id_ce(29) 15
+    }
+}
+
+asn1_oid! {
+    id_ce_subjectAltName OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) id_ce(29) 17
+    }
+}
+
+asn1_oid! {
+    id_ce_subjectKeyIdentifier OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) id_ce(29) 14
+    }
+}
+
+asn1_oid! {
+    id_ce_basicConstraints OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) id_ce(29) 19
+    }
+}
+
+asn1_oid! {
+    id_ce_authorityKeyIdentifier OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) id_ce(29) 35
+    }
+}
+
+asn1_oid! {
+    id_ce_extKeyUsage OBJECT IDENTIFIER ::= {
+        joint_iso_ccitt(2) ds(5) id_ce(29) 37
+    }
+}
+
+asn1_oid! {
+    id_kp_serverAuth OBJECT IDENTIFIER ::= {
+        iso(1) identified_organization(3) dod(6) internet(1)
+        security(5) mechanisms(5) pkix(7) kp(3) 1
+    }
+}
+
+asn1_oid! {
+    id_kp_clientAuth OBJECT IDENTIFIER ::= {
+        iso(1) identified_organization(3) dod(6) internet(1)
+        security(5) mechanisms(5) pkix(7) kp(3) 2
+    }
+}
+
+asn1_oid! {
+    id_kp_codeSigning OBJECT IDENTIFIER ::= {
+        iso(1) identified_organization(3) dod(6) internet(1)
+        security(5) mechanisms(5) pkix(7) kp(3) 3
+    }
+}
+
+asn1_oid! {
+    id_kp_emailProtection OBJECT IDENTIFIER ::= {
+        iso(1) identified_organization(3) dod(6) internet(1)
+        security(5) mechanisms(5) pkix(7) kp(3) 4
+    }
+}
+
+asn1_oid! {
+    id_kp_timeStamping OBJECT IDENTIFIER ::= {
+        iso(1) identified_organization(3) dod(6) internet(1)
+        security(5) mechanisms(5) pkix(7) kp(3) 8
+    }
+}
+
+asn1_oid! {
+    id_kp_OCSPSigning OBJECT IDENTIFIER ::= {
+        iso(1) identified_organization(3) dod(6) internet(1)
+        security(5) mechanisms(5) pkix(7) kp(3) 9
+    }
+}
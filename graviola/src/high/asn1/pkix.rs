@@ -1,7 +1,13 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
+#[allow(unused_imports)]
 use super::{asn1_enum, asn1_struct};
+#[cfg(feature = "x509")]
+use super::{
+    encoded_length_for, oid, Any, Encoder, Error, ObjectId, OctetString, Parser, PrintableString,
+    SequenceOf, SetOf, Tag, Time, Type, Utf8String,
+};
 
 asn1_struct! {
     RSAPublicKey ::= SEQUENCE {
@@ -36,6 +42,36 @@ asn1_struct! {
     }
 }
 
+asn1_struct! {
+    EncryptedPrivateKeyInfo ::= SEQUENCE {
+        encryptionAlgorithm       AlgorithmIdentifier REF,
+        encryptedData             OCTET STRING
+    }
+}
+
+asn1_struct! {
+    Pbes2Params ::= SEQUENCE {
+        keyDerivationFunc         AlgorithmIdentifier REF,
+        encryptionScheme          AlgorithmIdentifier REF
+    }
+}
+
+asn1_struct! {
+    Pbkdf2Params ::= SEQUENCE {
+        salt                      OCTET STRING,
+        iterationCount            INTEGER,
+        keyLength                 INTEGER OPTIONAL,
+        prf                       AlgorithmIdentifier REF OPTIONAL
+    }
+}
+
+asn1_struct! {
+    GcmParameters ::= SEQUENCE {
+        nonce                     OCTET STRING,
+        icvLen                    INTEGER OPTIONAL
+    }
+}
+
 asn1_struct! {
     AlgorithmIdentifier ::= SEQUENCE {
         algorithm                 OBJECT IDENTIFIER,
@@ -43,6 +79,15 @@ asn1_struct! {
     }
 }
 
+asn1_struct! {
+    RsassaPssParams ::= SEQUENCE {
+        hashAlgorithm      [0]    AlgorithmIdentifier REF,
+        maskGenAlgorithm   [1]    AlgorithmIdentifier REF,
+        saltLength         [2]    INTEGER,
+        trailerField       [3]    INTEGER
+    }
+}
+
 asn1_struct! {
     EcPrivateKey ::= SEQUENCE {
         version                   EcPrivateKeyVer,
@@ -77,6 +122,637 @@ asn1_struct! {
     }
 }
 
+#[cfg(feature = "x509")]
+asn1_struct! {
+    Certificate ::= SEQUENCE {
+        tbsCertificate            TBSCertificate REF,
+        signatureAlgorithm        AlgorithmIdentifier REF,
+        signatureValue            BIT STRING
+    }
+}
+
+// nb. `issuerUniqueID` and `subjectUniqueID` are not supported.
+#[cfg(feature = "x509")]
+asn1_struct! {
+    TBSCertificate ::= SEQUENCE {
+        version              [0]  INTEGER,
+        serialNumber              INTEGER,
+        signature                 AlgorithmIdentifier REF,
+        issuer                    Name REF,
+        validity                  Validity,
+        subject                   Name REF,
+        subjectPublicKeyInfo      SubjectPublicKeyInfo REF,
+        extensions           [3]  Extensions REF
+    }
+}
+
+#[cfg(feature = "x509")]
+asn1_struct! {
+    BasicConstraintsSyntax ::= SEQUENCE {
+        cA                        BOOLEAN OPTIONAL,
+        pathLenConstraint         INTEGER OPTIONAL
+    }
+}
+
+#[cfg(feature = "x509")]
+asn1_struct! {
+    CertificationRequest ::= SEQUENCE {
+        certificationRequestInfo  CertificationRequestInfo REF,
+        signatureAlgorithm        AlgorithmIdentifier REF,
+        signature                 BIT STRING
+    }
+}
+
+#[cfg(feature = "x509")]
+asn1_struct! {
+    CertificationRequestInfo ::= SEQUENCE {
+        version                   INTEGER,
+        subject                   Name REF,
+        subjectPKInfo             SubjectPublicKeyInfo REF,
+        attributes         [0]    IMPLICIT SET OF Attribute
+    }
+}
+
+// nb. the only attribute we support is `extensionRequest`.
+#[cfg(feature = "x509")]
+asn1_struct! {
+    Attribute ::= SEQUENCE {
+        attrType                  OBJECT IDENTIFIER,
+        attrValues                SET OF Extensions
+    }
+}
+
+#[cfg(feature = "x509")]
+asn1_struct! {
+    Extension ::= SEQUENCE {
+        extnID                    OBJECT IDENTIFIER,
+        critical                  BOOLEAN OPTIONAL,
+        extnValue                 ExtensionValue REF
+    }
+}
+
+#[cfg(feature = "x509")]
+asn1_struct! {
+    AttributeTypeAndValue ::= SEQUENCE {
+        attrType                  OBJECT IDENTIFIER,
+        attrValue                 ANY
+    }
+}
+
+/// `Extensions ::= SEQUENCE SIZE (1..MAX) OF Extension`
+///
+/// Absent items are skipped when encoding.
+#[cfg(feature = "x509")]
+pub(crate) type Extensions<'a> = SequenceOf<'a, Option<Extension<'a>>>;
+
+/// `Name ::= RDNSequence`, where each `RelativeDistinguishedName`
+/// has exactly one `AttributeTypeAndValue`.
+#[cfg(feature = "x509")]
+pub(crate) type Name<'a> = SequenceOf<'a, NameAttribute<'a>>;
+
+/// `GeneralNames ::= SEQUENCE SIZE (1..MAX) OF GeneralName`
+#[cfg(feature = "x509")]
+pub(crate) type GeneralNames<'a> = SequenceOf<'a, SubjectAltName<'a>>;
+
+/// `ExtKeyUsageSyntax ::= SEQUENCE SIZE (1..MAX) OF KeyPurposeId`
+#[cfg(feature = "x509")]
+pub(crate) type ExtKeyUsageSyntax<'a> = SequenceOf<'a, ExtendedKeyUsage>;
+
+/// `Validity ::= SEQUENCE { notBefore Time, notAfter Time }`
+///
+/// (This is not an `asn1_struct!` as it does not borrow anything.)
+#[cfg(feature = "x509")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Validity {
+    pub(crate) not_before: Time,
+    pub(crate) not_after: Time,
+}
+
+#[cfg(feature = "x509")]
+impl Validity {
+    fn body_len(&self) -> usize {
+        self.not_before.encoded_len() + self.not_after.encoded_len()
+    }
+}
+
+#[cfg(feature = "x509")]
+impl Type<'_> for Validity {
+    fn parse(p: &mut Parser<'_>) -> Result<Self, Error> {
+        let (_, mut sub) = p.descend(Tag::sequence())?;
+        let r = Self {
+            not_before: Time::parse(&mut sub)?,
+            not_after: Time::parse(&mut sub)?,
+        };
+        sub.check_end()?;
+        Ok(r)
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let mut body = encoder.begin(Tag::sequence(), self.body_len())?;
+        self.not_before.encode(&mut body)?;
+        self.not_after.encode(&mut body)?;
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(self.body_len())
+    }
+}
+
+/// `AuthorityKeyIdentifier`, with only the `keyIdentifier` field.
+///
+/// ```asn1
+/// AuthorityKeyIdentifier ::= SEQUENCE {
+///    keyIdentifier             [0] KeyIdentifier           OPTIONAL,
+///    authorityCertIssuer       [1] GeneralNames            OPTIONAL,
+///    authorityCertSerialNumber [2] CertificateSerialNumber OPTIONAL  }
+/// ```
+#[cfg(feature = "x509")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct AuthorityKeyIdentifier<'a> {
+    pub(crate) key_identifier: &'a [u8],
+}
+
+#[cfg(feature = "x509")]
+impl AuthorityKeyIdentifier<'_> {
+    fn body_len(&self) -> usize {
+        encoded_length_for(self.key_identifier.len())
+    }
+}
+
+#[cfg(feature = "x509")]
+impl<'a> Type<'a> for AuthorityKeyIdentifier<'a> {
+    fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
+        let (_, mut sub) = p.descend(Tag::sequence())?;
+        let (_, key_identifier) = sub.take(Tag(Tag::CONTEXT_SPECIFIC))?;
+        sub.check_end()?;
+        Ok(Self { key_identifier })
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let mut body = encoder.begin(Tag::sequence(), self.body_len())?;
+        let mut key_id = body.begin(Tag(Tag::CONTEXT_SPECIFIC), self.key_identifier.len())?;
+        key_id.append_slice(self.key_identifier)?;
+        key_id.finish();
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(self.body_len())
+    }
+}
+
+/// The `extnValue` of an `Extension`.
+///
+/// This is an `OCTET STRING` containing the DER encoding of a
+/// type that depends on the `extnID`.  Extensions are encoded
+/// from one of the typed variants, and are parsed as
+/// [`ExtensionValue::Encoded`].
+#[cfg(feature = "x509")]
+#[derive(Clone, Debug)]
+pub(crate) enum ExtensionValue<'a> {
+    BasicConstraints(BasicConstraintsSyntax<'a>),
+    KeyUsage(KeyUsage),
+    ExtKeyUsage(ExtKeyUsageSyntax<'a>),
+    SubjectAltName(GeneralNames<'a>),
+    SubjectKeyIdentifier(OctetString<'a>),
+    AuthorityKeyIdentifier(AuthorityKeyIdentifier<'a>),
+    Encoded(&'a [u8]),
+}
+
+#[cfg(feature = "x509")]
+impl<'a> ExtensionValue<'a> {
+    /// Decode a parsed extension value as `T`.
+    #[cfg(all(
+        test,
+        feature = "std",
+        feature = "p256",
+        feature = "p384",
+        feature = "rsa"
+    ))]
+    pub(crate) fn decode<T: Type<'a>>(&self) -> Result<T, Error> {
+        match self {
+            Self::Encoded(octets) => T::from_bytes(octets),
+            _ => Err(Error::UnexpectedTag),
+        }
+    }
+
+    fn body_len(&self) -> usize {
+        match self {
+            Self::BasicConstraints(bc) => bc.encoded_len(),
+            Self::KeyUsage(usage) => usage.encoded_len(),
+            Self::ExtKeyUsage(usages) => usages.encoded_len(),
+            Self::SubjectAltName(names) => names.encoded_len(),
+            Self::SubjectKeyIdentifier(id) => id.encoded_len(),
+            Self::AuthorityKeyIdentifier(aki) => aki.encoded_len(),
+            Self::Encoded(octets) => octets.len(),
+        }
+    }
+}
+
+#[cfg(feature = "x509")]
+impl<'a> Type<'a> for ExtensionValue<'a> {
+    fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
+        OctetString::parse(p).map(|os| Self::Encoded(os.into_octets()))
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let mut body = encoder.begin(Tag::octet_string(), self.body_len())?;
+        match self {
+            Self::BasicConstraints(bc) => bc.encode(&mut body)?,
+            Self::KeyUsage(usage) => usage.encode(&mut body)?,
+            Self::ExtKeyUsage(usages) => usages.encode(&mut body)?,
+            Self::SubjectAltName(names) => names.encode(&mut body)?,
+            Self::SubjectKeyIdentifier(id) => id.encode(&mut body)?,
+            Self::AuthorityKeyIdentifier(aki) => aki.encode(&mut body)?,
+            Self::Encoded(octets) => {
+                body.append_slice(octets)?;
+                body.finish()
+            }
+        };
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(self.body_len())
+    }
+}
+
+/// An attribute of a distinguished name.
+///
+/// Names are a sequence of these.  For example, the name
+/// `CN=example.com, O=Example, C=GB` (in the usual reversed
+/// string form) is written:
+///
+/// ```
+/// use graviola::x509::NameAttribute;
+///
+/// let name = [
+///     NameAttribute::Country("GB"),
+///     NameAttribute::Organization("Example"),
+///     NameAttribute::CommonName("example.com"),
+/// ];
+/// ```
+///
+/// `Country` is encoded as a `PrintableString`, and the others
+/// as `UTF8String`.
+#[cfg(feature = "x509")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameAttribute<'a> {
+    /// `id-at-commonName`
+    CommonName(&'a str),
+
+    /// `id-at-countryName`.  This should be a two-letter ISO 3166 code.
+    Country(&'a str),
+
+    /// `id-at-localityName`
+    Locality(&'a str),
+
+    /// `id-at-stateOrProvinceName`
+    StateOrProvince(&'a str),
+
+    /// `id-at-organizationName`
+    Organization(&'a str),
+
+    /// `id-at-organizationalUnitName`
+    OrganizationalUnit(&'a str),
+}
+
+#[cfg(feature = "x509")]
+impl<'a> NameAttribute<'a> {
+    fn to_attribute(self) -> Result<AttributeTypeAndValue<'a>, Error> {
+        let value = match self {
+            Self::Country(v) => Any::PrintableString(PrintableString::new(v)?),
+            _ => Any::Utf8String(Utf8String::new(self.value())),
+        };
+
+        Ok(AttributeTypeAndValue {
+            attrType: self.oid().clone(),
+            attrValue: value,
+        })
+    }
+
+    fn from_attribute(attr: AttributeTypeAndValue<'a>) -> Result<Self, Error> {
+        let value = match attr.attrValue {
+            Any::Utf8String(s) => s.as_str(),
+            Any::PrintableString(s) => s.as_str(),
+            _ => return Err(Error::UnexpectedTag),
+        };
+
+        match &attr.attrType {
+            t if t == &oid::id_at_commonName => Ok(Self::CommonName(value)),
+            t if t == &oid::id_at_countryName => Ok(Self::Country(value)),
+            t if t == &oid::id_at_localityName => Ok(Self::Locality(value)),
+            t if t == &oid::id_at_stateOrProvinceName => Ok(Self::StateOrProvince(value)),
+            t if t == &oid::id_at_organizationName => Ok(Self::Organization(value)),
+            t if t == &oid::id_at_organizationalUnitName => Ok(Self::OrganizationalUnit(value)),
+            _ => Err(Error::UnhandledEnumValue),
+        }
+    }
+
+    fn value(&self) -> &'a str {
+        match self {
+            Self::CommonName(v)
+            | Self::Country(v)
+            | Self::Locality(v)
+            | Self::StateOrProvince(v)
+            | Self::Organization(v)
+            | Self::OrganizationalUnit(v) => v,
+        }
+    }
+
+    fn oid(&self) -> &'static ObjectId {
+        match self {
+            Self::CommonName(_) => &oid::id_at_commonName,
+            Self::Country(_) => &oid::id_at_countryName,
+            Self::Locality(_) => &oid::id_at_localityName,
+            Self::StateOrProvince(_) => &oid::id_at_stateOrProvinceName,
+            Self::Organization(_) => &oid::id_at_organizationName,
+            Self::OrganizationalUnit(_) => &oid::id_at_organizationalUnitName,
+        }
+    }
+}
+
+/// Each `NameAttribute` is encoded as a `RelativeDistinguishedName`,
+/// which is a `SET OF` one `AttributeTypeAndValue`.
+#[cfg(feature = "x509")]
+impl<'a> Type<'a> for NameAttribute<'a> {
+    fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
+        let rdn = SetOf::<AttributeTypeAndValue<'a>>::parse(p)?;
+        let mut attrs = rdn.iter();
+        let attr = attrs.next().ok_or(Error::UnexpectedEof)??;
+        if attrs.next().is_some() {
+            // multi-valued RDNs are not supported
+            return Err(Error::UnexpectedTrailingData);
+        }
+        Self::from_attribute(attr)
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let attr = self.to_attribute()?;
+        SetOf::new(core::slice::from_ref(&attr)).encode(encoder)
+    }
+
+    fn encoded_len(&self) -> usize {
+        let attr_len = self.oid().encoded_len() + encoded_length_for(self.value().len());
+        encoded_length_for(encoded_length_for(attr_len))
+    }
+}
+
+/// A name for the subject of a certificate, in addition to
+/// its distinguished name.
+///
+/// These are `GeneralName`s from
+/// [RFC5280](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2.1.6).
+#[cfg(feature = "x509")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubjectAltName<'a> {
+    /// A `dNSName`, such as `example.com`.  This must be ASCII.
+    DnsName(&'a str),
+
+    /// An `iPAddress`: four bytes for IPv4, or sixteen bytes for IPv6.
+    IpAddress(&'a [u8]),
+
+    /// An `rfc822Name`: an email address.  This must be ASCII.
+    Email(&'a str),
+
+    /// A `uniformResourceIdentifier`.  This must be ASCII.
+    Uri(&'a str),
+}
+
+#[cfg(feature = "x509")]
+impl<'a> SubjectAltName<'a> {
+    fn tag_and_value(&self) -> (u8, &'a [u8]) {
+        match self {
+            Self::Email(v) => (1, v.as_bytes()),
+            Self::DnsName(v) => (2, v.as_bytes()),
+            Self::Uri(v) => (6, v.as_bytes()),
+            Self::IpAddress(v) => (7, v),
+        }
+    }
+}
+
+/// `GeneralName`s use implicit context-specific tags.
+#[cfg(feature = "x509")]
+impl<'a> Type<'a> for SubjectAltName<'a> {
+    fn parse(p: &mut Parser<'a>) -> Result<Self, Error> {
+        let tag = p.peek_tag()?.0;
+        let (_, value) = p.take(Tag(tag))?;
+        let ascii = || {
+            core::str::from_utf8(value)
+                .ok()
+                .filter(|s| s.is_ascii())
+                .ok_or(Error::IllegalString)
+        };
+
+        match tag {
+            0x81 => Ok(Self::Email(ascii()?)),
+            0x82 => Ok(Self::DnsName(ascii()?)),
+            0x86 => Ok(Self::Uri(ascii()?)),
+            0x87 if value.len() == 4 || value.len() == 16 => Ok(Self::IpAddress(value)),
+            0x87 => Err(Error::UnexpectedEof),
+            _ => Err(Error::UnhandledEnumValue),
+        }
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let (tag, value) = self.tag_and_value();
+        match self {
+            Self::IpAddress(v) if v.len() != 4 && v.len() != 16 => {
+                return Err(Error::UnexpectedEof);
+            }
+            Self::IpAddress(_) => {}
+            _ if !value.is_ascii() => return Err(Error::IllegalString),
+            _ => {}
+        }
+
+        let mut body = encoder.begin(Tag(Tag::CONTEXT_SPECIFIC | tag), value.len())?;
+        body.append_slice(value)?;
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(self.tag_and_value().1.len())
+    }
+}
+
+/// The permitted uses of a certificate's key.
+///
+/// Values can be combined with `|`.  These are described in
+/// [RFC5280](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2.1.3).
+#[cfg(feature = "x509")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyUsage(u16);
+
+#[cfg(feature = "x509")]
+impl KeyUsage {
+    /// `digitalSignature`
+    pub const DIGITAL_SIGNATURE: Self = Self(1 << 0);
+    /// `nonRepudiation` (also known as `contentCommitment`)
+    pub const NON_REPUDIATION: Self = Self(1 << 1);
+    /// `keyEncipherment`
+    pub const KEY_ENCIPHERMENT: Self = Self(1 << 2);
+    /// `dataEncipherment`
+    pub const DATA_ENCIPHERMENT: Self = Self(1 << 3);
+    /// `keyAgreement`
+    pub const KEY_AGREEMENT: Self = Self(1 << 4);
+    /// `keyCertSign`
+    pub const KEY_CERT_SIGN: Self = Self(1 << 5);
+    /// `cRLSign`
+    pub const CRL_SIGN: Self = Self(1 << 6);
+    /// `encipherOnly`
+    pub const ENCIPHER_ONLY: Self = Self(1 << 7);
+    /// `decipherOnly`
+    pub const DECIPHER_ONLY: Self = Self(1 << 8);
+
+    /// Returns true if all the usages in `other` are in `self`.
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// `KeyUsage` is a named `BIT STRING`, where bit zero is the most
+    /// significant bit of the first byte.
+    ///
+    /// DER requires trailing zero bits are removed, so this returns
+    /// the number of used bytes and the number of unused bits
+    /// in the final byte.
+    fn encoding(&self) -> ([u8; 2], usize, u8) {
+        let bits = self.0.reverse_bits();
+        let bytes = bits.to_be_bytes();
+        match (bytes[0], bytes[1]) {
+            (0, 0) => (bytes, 0, 0),
+            (_, 0) => (bytes, 1, bytes[0].trailing_zeros() as u8),
+            (_, _) => (bytes, 2, bytes[1].trailing_zeros() as u8),
+        }
+    }
+}
+
+#[cfg(feature = "x509")]
+impl core::ops::BitOr for KeyUsage {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+#[cfg(feature = "x509")]
+impl Type<'_> for KeyUsage {
+    fn parse(p: &mut Parser<'_>) -> Result<Self, Error> {
+        let (_, body) = p.take(Tag::bit_string())?;
+        let (unused, bytes) = body.split_first().ok_or(Error::UnexpectedEof)?;
+        let mut value = [0u8; 2];
+        match bytes.len() {
+            0..=2 => value[..bytes.len()].copy_from_slice(bytes),
+            _ => return Err(Error::UnhandledBitString),
+        };
+
+        let r = Self(u16::from_be_bytes(value).reverse_bits());
+        match r.encoding() {
+            (_, len, u) if len == bytes.len() && u == *unused => Ok(r),
+            _ => Err(Error::NonCanonicalEncoding),
+        }
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        let (bytes, len, unused) = self.encoding();
+        let mut body = encoder.begin(Tag::bit_string(), len + 1)?;
+        body.push(unused)?;
+        body.append_slice(&bytes[..len])?;
+        Ok(body.finish())
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_length_for(self.encoding().1 + 1)
+    }
+}
+
+/// A purpose for which a certificate's key may be used.
+///
+/// These are `KeyPurposeId`s from
+/// [RFC5280](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2.1.12).
+#[cfg(feature = "x509")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtendedKeyUsage {
+    /// `id-kp-serverAuth`: TLS server authentication.
+    ServerAuth,
+
+    /// `id-kp-clientAuth`: TLS client authentication.
+    ClientAuth,
+
+    /// `id-kp-codeSigning`
+    CodeSigning,
+
+    /// `id-kp-emailProtection`
+    EmailProtection,
+
+    /// `id-kp-timeStamping`
+    TimeStamping,
+
+    /// `id-kp-OCSPSigning`
+    OcspSigning,
+}
+
+#[cfg(feature = "x509")]
+impl ExtendedKeyUsage {
+    fn oid(&self) -> &'static ObjectId {
+        match self {
+            Self::ServerAuth => &oid::id_kp_serverAuth,
+            Self::ClientAuth => &oid::id_kp_clientAuth,
+            Self::CodeSigning => &oid::id_kp_codeSigning,
+            Self::EmailProtection => &oid::id_kp_emailProtection,
+            Self::TimeStamping => &oid::id_kp_timeStamping,
+            Self::OcspSigning => &oid::id_kp_OCSPSigning,
+        }
+    }
+}
+
+#[cfg(feature = "x509")]
+impl Type<'_> for ExtendedKeyUsage {
+    fn parse(p: &mut Parser<'_>) -> Result<Self, Error> {
+        let id = ObjectId::parse(p)?;
+        [
+            Self::ServerAuth,
+            Self::ClientAuth,
+            Self::CodeSigning,
+            Self::EmailProtection,
+            Self::TimeStamping,
+            Self::OcspSigning,
+        ]
+        .into_iter()
+        .find(|usage| usage.oid() == &id)
+        .ok_or(Error::UnhandledEnumValue)
+    }
+
+    fn encode(&self, encoder: &mut Encoder<'_>) -> Result<usize, Error> {
+        self.oid().encode(encoder)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.oid().encoded_len()
+    }
+}
+
+/// Whether a certificate is for a certification authority.
+///
+/// This is the `basicConstraints` extension, described in
+/// [RFC5280](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2.1.9).
+#[cfg(feature = "x509")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BasicConstraints {
+    /// The certificate is not for a CA, and may not issue certificates.
+    EndEntity,
+
+    /// The certificate is for a CA.
+    ///
+    /// `path_len` limits the number of intermediate CA certificates
+    /// that may follow this one in a path.
+    Ca {
+        /// The `pathLenConstraint`, or `None` for no limit.
+        path_len: Option<u8>,
+    },
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,6 +854,92 @@ mod tests {
         roundtrip_check::<SubjectPublicKeyInfo<'_>>(data);
     }
 
+    #[test]
+    fn parse_rsassa_pss_pkcs8_key() {
+        let data = include_bytes!("testdata/rsassa-pss-p8.bin");
+        truncation_check::<PrivateKeyInfo<'_>>(data);
+        roundtrip_check::<PrivateKeyInfo<'_>>(data);
+
+        let key = PrivateKeyInfo::parse(&mut Parser::new(data)).unwrap();
+        assert_eq!(key.privateKeyAlgorithm.algorithm, oid::id_RSASSA_PSS);
+        let Some(Any::Sequence(params)) = key.privateKeyAlgorithm.parameters else {
+            panic!("missing RSASSA-PSS-params");
+        };
+        let params = params.decode::<RsassaPssParams<'_>>().unwrap();
+        dbg!(&params);
+
+        let hash = params.hashAlgorithm.into_inner().unwrap();
+        assert_eq!(hash.algorithm, oid::id_sha256);
+        assert_eq!(hash.parameters, Some(Any::Null(Null)));
+
+        let mgf = params.maskGenAlgorithm.into_inner().unwrap();
+        assert_eq!(mgf.algorithm, oid::id_mgf1);
+        let Some(Any::Sequence(mgf_hash)) = mgf.parameters else {
+            panic!("missing MGF1 hash");
+        };
+        let mgf_hash = mgf_hash.decode::<AlgorithmIdentifier<'_>>().unwrap();
+        assert_eq!(mgf_hash.algorithm, oid::id_sha256);
+
+        assert_eq!(
+            params.saltLength.into_inner().unwrap().as_usize().unwrap(),
+            32
+        );
+        assert!(params.trailerField.into_inner().is_none());
+    }
+
+    #[cfg(feature = "x509")]
+    #[test]
+    fn key_usage_encoding() {
+        for (usage, encoding) in [
+            (KeyUsage(0), &b"\x03\x01\x00"[..]),
+            (KeyUsage::DIGITAL_SIGNATURE, b"\x03\x02\x07\x80"),
+            (
+                KeyUsage::DIGITAL_SIGNATURE | KeyUsage::KEY_ENCIPHERMENT,
+                b"\x03\x02\x05\xa0",
+            ),
+            (
+                KeyUsage::KEY_CERT_SIGN | KeyUsage::CRL_SIGN,
+                b"\x03\x02\x01\x06",
+            ),
+            (KeyUsage::DECIPHER_ONLY, b"\x03\x03\x07\x00\x80"),
+        ] {
+            let mut buf = [0u8; 5];
+            let len = usage.encode(&mut Encoder::new(&mut buf)).unwrap();
+            assert_eq!(&buf[..len], encoding);
+            assert_eq!(usage.encoded_len(), len);
+            assert_eq!(KeyUsage::from_bytes(encoding), Ok(usage));
+        }
+
+        // trailing zero bits, and incorrect unused bit count
+        assert_eq!(
+            KeyUsage::from_bytes(b"\x03\x02\x00\x80"),
+            Err(Error::NonCanonicalEncoding)
+        );
+        assert_eq!(
+            KeyUsage::from_bytes(b"\x03\x03\x07\x80\x00"),
+            Err(Error::NonCanonicalEncoding)
+        );
+    }
+
+    #[cfg(feature = "x509")]
+    #[test]
+    fn name_roundtrip() {
+        for name in [
+            NameAttribute::CommonName("example.com"),
+            NameAttribute::Country("GB"),
+            NameAttribute::Locality("London"),
+            NameAttribute::StateOrProvince("England"),
+            NameAttribute::Organization("Graviola"),
+            NameAttribute::OrganizationalUnit("Tests"),
+        ] {
+            let mut buf = [0u8; 64];
+            let len = name.encode(&mut Encoder::new(&mut buf)).unwrap();
+            assert_eq!(name.encoded_len(), len);
+            assert_eq!(NameAttribute::from_bytes(&buf[..len]), Ok(name));
+            truncation_check::<NameAttribute<'_>>(&buf[..len]);
+        }
+    }
+
     fn truncation_check<'a, T: Type<'a>>(bytes: &'a [u8]) {
         // base case
         T::from_bytes(bytes).unwrap();
@@ -0,0 +1,266 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! Constant-time mapping between base64 characters and their values.
+//!
+//! Base64 is used for PEM and JOSE encodings, which frequently contain
+//! private keys.  So this avoids table lookups indexed by (or branches
+//! on) the encoded data.  Instead, each character is mapped to and from
+//! its value by arithmetic on the ranges of the alphabet.
+//!
+//! Decoding and encoding whole strings is shared too, parameterised by
+//! [`Alphabet`] and [`Padding`].
+
+use crate::error::Error;
+use crate::low::zeroise;
+
+/// A base64 alphabet.
+///
+/// These differ only in the characters used for the values 62 and 63.
+#[derive(Clone, Copy)]
+pub(crate) enum Alphabet {
+    /// The standard alphabet, from RFC4648 section 4.
+    Standard,
+
+    /// The URL- and filename-safe alphabet, from RFC4648 section 5.
+    #[cfg_attr(not(feature = "jose"), allow(dead_code))]
+    UrlSafe,
+}
+
+impl Alphabet {
+    /// The characters used for 62 and 63.
+    fn last_two(self) -> (u8, u8) {
+        match self {
+            Self::Standard => (b'+', b'/'),
+            Self::UrlSafe => (b'-', b'_'),
+        }
+    }
+}
+
+/// Whether encodings are padded with `=` to a multiple of four characters.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Padding {
+    /// Padding is required, as in PEM.
+    Required,
+
+    /// Padding is not used, and `=` is an invalid character, as in JOSE.
+    #[cfg_attr(not(feature = "jose"), allow(dead_code))]
+    Omitted,
+}
+
+/// Reasons [`decode()`] can fail.
+#[derive(Debug, PartialEq)]
+pub(crate) enum DecodeError {
+    /// The output buffer is too short.
+    WrongLength,
+
+    /// The encoding is invalid.
+    Invalid,
+}
+
+/// Decodes base64 `encoded` into `output`, returning the number of
+/// bytes written.
+///
+/// Bits beyond the end of the decoded data must be zero.  The data is
+/// not branched on; only its length (and the position of any padding)
+/// is considered public.
+///
+/// On error, `output` is zeroed.
+pub(crate) fn decode(
+    encoded: impl Iterator<Item = u8>,
+    alphabet: Alphabet,
+    padding: Padding,
+    output: &mut [u8],
+) -> Result<usize, DecodeError> {
+    let mut accum = 0u32;
+    let mut accum_bits = 0;
+    let mut chars = 0usize;
+    let mut pad_chars = 0usize;
+    let mut invalid = 0u8;
+    let mut used = 0;
+
+    for c in encoded {
+        // nb. secret data never contains padding, so these branches
+        // are only taken according to the layout.
+        if padding == Padding::Required && c == b'=' {
+            pad_chars += 1;
+            continue;
+        }
+
+        if pad_chars > 0 {
+            zeroise(output);
+            return Err(DecodeError::Invalid);
+        }
+
+        let value = decode_char(c, alphabet);
+        invalid |= value;
+        accum = (accum << 6) | (value as u32 & 0x3f);
+        accum_bits += 6;
+        chars += 1;
+
+        if accum_bits >= 8 {
+            accum_bits -= 8;
+            match output.get_mut(used) {
+                Some(out) => *out = (accum >> accum_bits) as u8,
+                None => {
+                    zeroise(output);
+                    return Err(DecodeError::WrongLength);
+                }
+            }
+            used += 1;
+        }
+    }
+
+    // any leftover bits must be zero, and are dealt with alongside
+    // invalid characters so as to not branch on them separately.
+    let leftover = (accum & ((1 << accum_bits) - 1)) as u8;
+    invalid |= (leftover | leftover.wrapping_neg()) & 0x80;
+
+    let valid_length = match padding {
+        Padding::Required => (chars + pad_chars) % 4 == 0 && pad_chars <= 2,
+        Padding::Omitted => chars % 4 != 1,
+    };
+
+    if invalid & 0x80 != 0 || !valid_length {
+        zeroise(output);
+        return Err(DecodeError::Invalid);
+    }
+
+    Ok(used)
+}
+
+/// Writes ASCII text into a caller-supplied buffer.
+pub(crate) struct Writer<'o> {
+    output: &'o mut [u8],
+    used: usize,
+}
+
+impl<'o> Writer<'o> {
+    pub(crate) fn new(output: &'o mut [u8]) -> Self {
+        Self { output, used: 0 }
+    }
+
+    /// Writes `data` encoded with `alphabet` and `padding`.
+    pub(crate) fn base64(
+        &mut self,
+        data: &[u8],
+        alphabet: Alphabet,
+        padding: Padding,
+    ) -> Result<(), Error> {
+        for group in data.chunks(3) {
+            let mut bytes = [0u8; 3];
+            bytes[..group.len()].copy_from_slice(group);
+            let accum = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+            let mut out = [b'='; 4];
+            for (i, out) in out.iter_mut().enumerate().take(group.len() + 1) {
+                *out = encode_value((accum >> (18 - 6 * i)) as u8 & 0x3f, alphabet);
+            }
+
+            let len = match padding {
+                Padding::Required => out.len(),
+                Padding::Omitted => group.len() + 1,
+            };
+            self.write(&out[..len])?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.output
+            .get_mut(self.used..self.used + bytes.len())
+            .ok_or(Error::WrongLength)?
+            .copy_from_slice(bytes);
+        self.used += bytes.len();
+        Ok(())
+    }
+
+    /// Returns the text written so far.
+    #[cfg(feature = "jose")]
+    pub(crate) fn written(&self) -> &[u8] {
+        &self.output[..self.used]
+    }
+
+    /// Returns the written text, or zeroes the output if `result`
+    /// is an error.
+    pub(crate) fn finish(self, result: Result<(), Error>) -> Result<&'o str, Error> {
+        let Self { output, used } = self;
+        match result {
+            // nb. only ASCII and the UTF-8 of `&str` arguments is written,
+            // so this cannot fail.
+            Ok(()) => core::str::from_utf8(&output[..used]).map_err(|_| Error::WrongLength),
+            Err(err) => {
+                zeroise(output);
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Maps a base64 character to its 6-bit value.
+///
+/// The top bit of the result is set for characters outside the
+/// alphabet.
+fn decode_char(c: u8, alphabet: Alphabet) -> u8 {
+    let (c62, c63) = alphabet.last_two();
+    let c = c as i16;
+
+    // each term is `value + 1` if `c` is inside the range, or 0 if not.
+    let mut ret = -1i16;
+    ret += in_range(c, b'A', b'Z') & (c - b'A' as i16 + 1);
+    ret += in_range(c, b'a', b'z') & (c - b'a' as i16 + 27);
+    ret += in_range(c, b'0', b'9') & (c - b'0' as i16 + 53);
+    ret += in_range(c, c62, c62) & 63;
+    ret += in_range(c, c63, c63) & 64;
+    ret as u8
+}
+
+/// Returns all-ones if `lo <= c <= hi`, or zero otherwise.
+fn in_range(c: i16, lo: u8, hi: u8) -> i16 {
+    ((lo as i16 - 1 - c) & (c - hi as i16 - 1)) >> 8
+}
+
+/// Maps a 6-bit value to its base64 character.
+fn encode_value(v: u8, alphabet: Alphabet) -> u8 {
+    let (c62, c63) = alphabet.last_two();
+    let v = v as i16;
+
+    // starting at 'A', add the offset to each subsequent range
+    // of the alphabet if `v` is inside or after it.
+    let mut diff = b'A' as i16;
+    diff += ((25 - v) >> 8) & (b'a' as i16 - 26 - b'A' as i16);
+    diff += ((51 - v) >> 8) & (b'0' as i16 - 52 - (b'a' as i16 - 26));
+    diff += ((61 - v) >> 8) & (c62 as i16 - 62 - (b'0' as i16 - 52));
+    diff += ((62 - v) >> 8) & (c63 as i16 - 63 - (c62 as i16 - 62));
+    (v + diff) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alphabets() {
+        for (alphabet, chars) in [
+            (
+                Alphabet::Standard,
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+            ),
+            (
+                Alphabet::UrlSafe,
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+            ),
+        ] {
+            for (v, c) in chars.iter().enumerate() {
+                assert_eq!(encode_value(v as u8, alphabet), *c);
+                assert_eq!(decode_char(*c, alphabet), v as u8);
+            }
+
+            for c in 0..=255u8 {
+                if !chars.contains(&c) {
+                    assert_eq!(decode_char(c, alphabet) & 0x80, 0x80, "{c:?}");
+                }
+            }
+        }
+    }
+}
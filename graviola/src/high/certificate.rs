@@ -0,0 +1,538 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! X.509 v3 certificates.
+//!
+//! See [RFC5280](https://datatracker.ietf.org/doc/html/rfc5280).
+
+use super::asn1::pkix::{
+    self, BasicConstraints, ExtendedKeyUsage, KeyUsage, NameAttribute, SubjectAltName,
+};
+use super::asn1::{self, oid, Type};
+use super::csr::{
+    subject_alt_name_critical, Signer, MAX_ALGORITHM_PARAMS_LEN, MAX_SIGNATURE_LEN, MAX_SPKI_LEN,
+};
+use super::hash::{Hash, Sha256};
+use crate::error::Error;
+use crate::low::Entry;
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
+
+/// The contents of an X.509 v3 certificate.
+///
+/// Fill in the fields of this structure, and then use
+/// [`Self::self_sign()`] or [`Self::sign()`] to produce a
+/// DER-encoded `Certificate`.
+///
+/// A `subjectKeyIdentifier` extension is always included.  Issued
+/// certificates also include an `authorityKeyIdentifier` extension.
+/// Key identifiers are the leftmost 160 bits of the SHA-256 hash of
+/// the `subjectPublicKey`, as described in
+/// [RFC7093](https://datatracker.ietf.org/doc/html/rfc7093#section-2).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Certificate<'a> {
+    /// The serial number, as a big-endian unsigned integer.
+    ///
+    /// This must be non-zero, and at most 20 bytes when encoded.
+    /// It should contain at least 64 bits of randomness.
+    pub serial_number: &'a [u8],
+
+    /// The start of the validity period, in seconds since the UNIX epoch.
+    pub not_before: u64,
+
+    /// The end of the validity period, in seconds since the UNIX epoch.
+    ///
+    /// This time is included in the validity period.
+    pub not_after: u64,
+
+    /// The subject's distinguished name.
+    pub subject: &'a [NameAttribute<'a>],
+
+    /// The subject's alternative names.
+    ///
+    /// No `subjectAltName` extension is included if this is empty.
+    pub subject_alt_names: &'a [SubjectAltName<'a>],
+
+    /// Whether the subject is a certification authority.
+    ///
+    /// No `basicConstraints` extension is included if this is `None`.
+    pub basic_constraints: Option<BasicConstraints>,
+
+    /// The permitted uses of the subject's key.
+    ///
+    /// No `keyUsage` extension is included if this is `None`.
+    pub key_usage: Option<KeyUsage>,
+
+    /// The permitted purposes of the subject's key.
+    ///
+    /// No `extKeyUsage` extension is included if this is empty.
+    pub extended_key_usage: &'a [ExtendedKeyUsage],
+}
+
+/// The issuer of a certificate.
+#[derive(Clone, Copy)]
+pub struct Issuer<'a> {
+    /// The issuer's distinguished name.
+    ///
+    /// This should be the `subject` of the issuer's certificate.
+    pub name: &'a [NameAttribute<'a>],
+
+    /// The issuer's private key, and the signature algorithm to use.
+    pub signer: Signer<'a>,
+}
+
+impl Certificate<'_> {
+    /// Make a self-signed certificate, writing the DER encoding
+    /// to `output`.
+    ///
+    /// The subject's public key is that of `signer`, and the issuer
+    /// is the subject.  The encoding is written to the front of `output`,
+    /// and that prefix is returned.
+    ///
+    /// Any randomness needed for signing comes from the default
+    /// [`RandomSource`].
+    #[cfg(feature = "std")]
+    pub fn self_sign<'o>(
+        &self,
+        signer: Signer<'_>,
+        output: &'o mut [u8],
+    ) -> Result<&'o [u8], Error> {
+        self.self_sign_with_rng(&mut DefaultRandom, signer, output)
+    }
+
+    /// Make a self-signed certificate, using randomness from `rng`.
+    ///
+    /// See [`Self::self_sign()`] for details of the other arguments.
+    pub fn self_sign_with_rng<'o>(
+        &self,
+        rng: &mut dyn RandomSource,
+        signer: Signer<'_>,
+        output: &'o mut [u8],
+    ) -> Result<&'o [u8], Error> {
+        let _ = Entry::new_secret();
+        let mut spki = [0u8; MAX_SPKI_LEN];
+        let spki = signer.public_key_spki(&mut spki)?;
+        self.encode(
+            rng,
+            spki,
+            &Issuer {
+                name: self.subject,
+                signer,
+            },
+            true,
+            output,
+        )
+    }
+
+    /// Make a certificate for `public_key`, issued by `issuer`, writing
+    /// the DER encoding to `output`.
+    ///
+    /// `public_key` is the subject's DER-encoded `SubjectPublicKeyInfo`.
+    /// See, for example, [`crate::signing::ecdsa::VerifyingKey::to_spki_der()`].
+    ///
+    /// The encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    ///
+    /// Any randomness needed for signing comes from the default
+    /// [`RandomSource`].
+    #[cfg(feature = "std")]
+    pub fn sign<'o>(
+        &self,
+        public_key: &[u8],
+        issuer: &Issuer<'_>,
+        output: &'o mut [u8],
+    ) -> Result<&'o [u8], Error> {
+        self.sign_with_rng(&mut DefaultRandom, public_key, issuer, output)
+    }
+
+    /// Make a certificate for `public_key`, issued by `issuer`, using
+    /// randomness from `rng`.
+    ///
+    /// See [`Self::sign()`] for details of the other arguments.
+    pub fn sign_with_rng<'o>(
+        &self,
+        rng: &mut dyn RandomSource,
+        public_key: &[u8],
+        issuer: &Issuer<'_>,
+        output: &'o mut [u8],
+    ) -> Result<&'o [u8], Error> {
+        let _ = Entry::new_secret();
+        self.encode(rng, public_key, issuer, false, output)
+    }
+
+    fn encode<'o>(
+        &self,
+        rng: &mut dyn RandomSource,
+        public_key: &[u8],
+        issuer: &Issuer<'_>,
+        self_signed: bool,
+        output: &'o mut [u8],
+    ) -> Result<&'o [u8], Error> {
+        let spki = pkix::SubjectPublicKeyInfo::from_bytes(public_key).map_err(Error::Asn1Error)?;
+        let subject_key_id = key_identifier(&spki);
+
+        let mut issuer_spki = [0u8; MAX_SPKI_LEN];
+        let issuer_spki = issuer.signer.public_key_spki(&mut issuer_spki)?;
+        let issuer_spki =
+            pkix::SubjectPublicKeyInfo::from_bytes(issuer_spki).map_err(Error::Asn1Error)?;
+        let authority_key_id = key_identifier(&issuer_spki);
+
+        let mut serial = [0u8; MAX_SERIAL_LEN + 1];
+        let serial = serial_number(self.serial_number, &mut serial)?;
+
+        let mut path_len = [0u8; 2];
+        let basic_constraints = self.basic_constraints.map(|bc| match bc {
+            BasicConstraints::EndEntity => pkix::BasicConstraintsSyntax {
+                // nb. DER requires that DEFAULT values are omitted.
+                cA: None,
+                pathLenConstraint: None,
+            },
+            BasicConstraints::Ca { path_len: limit } => {
+                path_len[1] = limit.unwrap_or_default();
+                pkix::BasicConstraintsSyntax {
+                    cA: Some(asn1::Boolean(true)),
+                    pathLenConstraint: limit.map(|_| asn1::Integer::from_bytes(&path_len)),
+                }
+            }
+        });
+
+        let extensions = [
+            basic_constraints.map(|bc| pkix::Extension {
+                extnID: oid::id_ce_basicConstraints.clone(),
+                critical: Some(asn1::Boolean(true)),
+                extnValue: pkix::ExtensionValue::BasicConstraints(bc),
+            }),
+            self.key_usage.map(|usage| pkix::Extension {
+                extnID: oid::id_ce_keyUsage.clone(),
+                critical: Some(asn1::Boolean(true)),
+                extnValue: pkix::ExtensionValue::KeyUsage(usage),
+            }),
+            (!self.extended_key_usage.is_empty()).then(|| pkix::Extension {
+                extnID: oid::id_ce_extKeyUsage.clone(),
+                critical: None,
+                extnValue: pkix::ExtensionValue::ExtKeyUsage(asn1::SequenceOf::new(
+                    self.extended_key_usage,
+                )),
+            }),
+            (!self.subject_alt_names.is_empty()).then(|| pkix::Extension {
+                extnID: oid::id_ce_subjectAltName.clone(),
+                critical: subject_alt_name_critical(self.subject),
+                extnValue: pkix::ExtensionValue::SubjectAltName(asn1::SequenceOf::new(
+                    self.subject_alt_names,
+                )),
+            }),
+            Some(pkix::Extension {
+                extnID: oid::id_ce_subjectKeyIdentifier.clone(),
+                critical: None,
+                extnValue: pkix::ExtensionValue::SubjectKeyIdentifier(asn1::OctetString::new(
+                    &subject_key_id,
+                )),
+            }),
+            (!self_signed).then(|| pkix::Extension {
+                extnID: oid::id_ce_authorityKeyIdentifier.clone(),
+                critical: None,
+                extnValue: pkix::ExtensionValue::AuthorityKeyIdentifier(
+                    pkix::AuthorityKeyIdentifier {
+                        key_identifier: &authority_key_id,
+                    },
+                ),
+            }),
+        ];
+
+        let mut algorithm_params = [0u8; MAX_ALGORITHM_PARAMS_LEN];
+        let signature_algorithm = issuer.signer.signature_algorithm(&mut algorithm_params)?;
+
+        let tbs = pkix::TBSCertificate {
+            version: asn1::ContextConstructed::new(Some(asn1::Integer::new(&[VERSION_3]))),
+            serialNumber: serial,
+            signature: signature_algorithm.clone(),
+            issuer: pkix::Name::new(issuer.name),
+            validity: pkix::Validity {
+                not_before: asn1::Time(self.not_before),
+                not_after: asn1::Time(self.not_after),
+            },
+            subject: pkix::Name::new(self.subject),
+            subjectPublicKeyInfo: spki,
+            extensions: asn1::ContextConstructed::new(Some(pkix::Extensions::new(&extensions))),
+        };
+
+        let len = tbs
+            .encode(&mut asn1::Encoder::new(output))
+            .map_err(Error::Asn1Error)?;
+
+        let mut signature = [0u8; MAX_SIGNATURE_LEN];
+        let signature = issuer.signer.sign(rng, &output[..len], &mut signature)?;
+
+        let len = pkix::Certificate {
+            tbsCertificate: tbs,
+            signatureAlgorithm: signature_algorithm,
+            signatureValue: asn1::BitString::new(signature),
+        }
+        .encode(&mut asn1::Encoder::new(output))
+        .map_err(Error::Asn1Error)?;
+        Ok(&output[..len])
+    }
+}
+
+/// Encodes `serial` as a positive `INTEGER`, using `buf`.
+fn serial_number<'b>(
+    serial: &[u8],
+    buf: &'b mut [u8; MAX_SERIAL_LEN + 1],
+) -> Result<asn1::Integer<'b>, Error> {
+    let out_of_range = Error::Asn1Error(asn1::Error::IntegerOutOfRange);
+
+    let start = serial.iter().position(|b| *b != 0x00).ok_or(out_of_range)?;
+    let serial = &serial[start..];
+    if serial.len() > MAX_SERIAL_LEN {
+        return Err(out_of_range);
+    }
+
+    // leading zero byte ensures the encoding is positive
+    buf[1..serial.len() + 1].copy_from_slice(serial);
+    let serial = asn1::Integer::from_bytes(&buf[..serial.len() + 1]);
+    match serial.as_ref().len() {
+        0..=MAX_SERIAL_LEN => Ok(serial),
+        _ => Err(out_of_range),
+    }
+}
+
+/// RFC7093 section 2, method 1.
+fn key_identifier(spki: &pkix::SubjectPublicKeyInfo<'_>) -> [u8; KEY_IDENTIFIER_LEN] {
+    let hash = Sha256::hash(spki.subjectPublicKey.clone().into_octets());
+    let mut id = [0u8; KEY_IDENTIFIER_LEN];
+    id.copy_from_slice(&hash.as_ref()[..KEY_IDENTIFIER_LEN]);
+    id
+}
+
+/// `Version ::= INTEGER { v1(0), v2(1), v3(2) }`
+const VERSION_3: u8 = 2;
+
+/// RFC5280 limits serial numbers to 20 octets.
+const MAX_SERIAL_LEN: usize = 20;
+
+const KEY_IDENTIFIER_LEN: usize = 20;
+
+#[cfg(all(
+    test,
+    feature = "std",
+    feature = "p256",
+    feature = "p384",
+    feature = "rsa"
+))]
+mod tests {
+    use super::*;
+    use crate::high::curve::{P256, P384};
+    use crate::high::{ecdsa, rsa};
+
+    #[test]
+    fn self_signed() {
+        let key =
+            ecdsa::SigningKey::<P256>::from_pkcs8_der(include_bytes!("ecdsa/secp256r1.pkcs8.der"))
+                .unwrap();
+        let subject = [NameAttribute::CommonName("Graviola Test CA")];
+        let mut output = [0u8; 1024];
+        let der = Certificate {
+            serial_number: &[0x80; 19],
+            not_before: 1_700_000_000,
+            not_after: 2_600_000_000,
+            subject: &subject,
+            basic_constraints: Some(BasicConstraints::Ca { path_len: Some(0) }),
+            key_usage: Some(KeyUsage::KEY_CERT_SIGN | KeyUsage::CRL_SIGN),
+            ..Default::default()
+        }
+        .self_sign(Signer::EcdsaP256Sha256(&key), &mut output)
+        .unwrap();
+
+        let cert = pkix::Certificate::from_bytes(der).unwrap();
+        let tbs = &cert.tbsCertificate;
+        assert_eq!(tbs.version.clone().into_inner().unwrap().as_usize(), Ok(2));
+        assert_eq!(tbs.serialNumber.as_ref().len(), 20);
+        assert!(!tbs.serialNumber.is_negative());
+        assert_eq!(tbs.signature.algorithm, oid::ecdsa_with_SHA256);
+        assert_eq!(cert.signatureAlgorithm.algorithm, oid::ecdsa_with_SHA256);
+        assert_eq!(tbs.validity.not_before, asn1::Time(1_700_000_000));
+        // 2052 requires GeneralizedTime
+        assert_eq!(tbs.validity.not_after, asn1::Time(2_600_000_000));
+        assert_eq!(tbs.issuer.iter().next(), Some(Ok(subject[0])));
+        assert_eq!(tbs.subject.iter().next(), Some(Ok(subject[0])));
+
+        let extensions = tbs
+            .extensions
+            .clone()
+            .into_inner()
+            .unwrap()
+            .iter()
+            .map(|ext| ext.unwrap().unwrap())
+            .collect::<Vec<_>>();
+        let ids = extensions
+            .iter()
+            .map(|ext| ext.extnID.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            [
+                oid::id_ce_basicConstraints.clone(),
+                oid::id_ce_keyUsage.clone(),
+                oid::id_ce_subjectKeyIdentifier.clone(),
+            ]
+        );
+
+        let bc = extensions[0]
+            .extnValue
+            .decode::<pkix::BasicConstraintsSyntax<'_>>()
+            .unwrap();
+        assert_eq!(bc.cA, Some(asn1::Boolean(true)));
+        assert_eq!(bc.pathLenConstraint.unwrap().as_usize(), Ok(0));
+
+        let ski = extensions[2]
+            .extnValue
+            .decode::<asn1::OctetString<'_>>()
+            .unwrap();
+        assert_eq!(
+            ski.into_octets(),
+            &key_identifier(&tbs.subjectPublicKeyInfo)
+        );
+
+        let mut message = [0u8; 1024];
+        let message_len = tbs.encode(&mut asn1::Encoder::new(&mut message)).unwrap();
+        let mut spki = [0u8; 128];
+        let vk = ecdsa::VerifyingKey::<P256>::from_spki_der(
+            Signer::EcdsaP256Sha256(&key)
+                .public_key_spki(&mut spki)
+                .unwrap(),
+        )
+        .unwrap();
+        vk.verify_asn1::<Sha256>(
+            &[&message[..message_len]],
+            cert.signatureValue.clone().into_octets(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn issued() {
+        let ca_key = rsa::SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa2048.der")).unwrap();
+        let ca_name = [
+            NameAttribute::Country("GB"),
+            NameAttribute::CommonName("Graviola Test CA"),
+        ];
+        let ee_key =
+            ecdsa::SigningKey::<P384>::from_pkcs8_der(include_bytes!("ecdsa/secp384r1.pkcs8.der"))
+                .unwrap();
+        let mut ee_spki = [0u8; 128];
+        let ee_spki = Signer::EcdsaP384Sha384(&ee_key)
+            .public_key_spki(&mut ee_spki)
+            .unwrap();
+
+        let mut output = [0u8; 2048];
+        let der = Certificate {
+            serial_number: &[0x00, 0x00, 0x01],
+            not_before: 0,
+            not_after: 1_000_000_000,
+            subject_alt_names: &[SubjectAltName::DnsName("localhost")],
+            basic_constraints: Some(BasicConstraints::EndEntity),
+            extended_key_usage: &[ExtendedKeyUsage::ServerAuth, ExtendedKeyUsage::ClientAuth],
+            ..Default::default()
+        }
+        .sign(
+            ee_spki,
+            &Issuer {
+                name: &ca_name,
+                signer: Signer::RsaPssSha256(&ca_key),
+            },
+            &mut output,
+        )
+        .unwrap();
+
+        let cert = pkix::Certificate::from_bytes(der).unwrap();
+        let tbs = &cert.tbsCertificate;
+        assert_eq!(tbs.serialNumber.as_ref(), &[0x01]);
+        assert_eq!(tbs.signature.algorithm, oid::id_RSASSA_PSS);
+        assert_eq!(
+            tbs.issuer.iter().collect::<Result<Vec<_>, _>>().unwrap(),
+            ca_name
+        );
+        assert!(tbs.subject.iter().next().is_none());
+
+        let extensions = tbs
+            .extensions
+            .clone()
+            .into_inner()
+            .unwrap()
+            .iter()
+            .map(|ext| ext.unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(extensions.len(), 5);
+
+        // empty SEQUENCE: cA is DEFAULT FALSE
+        assert_eq!(extensions[0].extnID, oid::id_ce_basicConstraints);
+        let bc = extensions[0]
+            .extnValue
+            .decode::<pkix::BasicConstraintsSyntax<'_>>()
+            .unwrap();
+        assert_eq!(bc.cA, None);
+        assert!(bc.pathLenConstraint.is_none());
+
+        assert_eq!(extensions[1].extnID, oid::id_ce_extKeyUsage);
+        assert_eq!(
+            extensions[1]
+                .extnValue
+                .decode::<pkix::ExtKeyUsageSyntax<'_>>()
+                .unwrap()
+                .iter()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            [ExtendedKeyUsage::ServerAuth, ExtendedKeyUsage::ClientAuth]
+        );
+
+        // critical, as the subject is empty
+        assert_eq!(extensions[2].extnID, oid::id_ce_subjectAltName);
+        assert_eq!(extensions[2].critical, Some(asn1::Boolean(true)));
+
+        let mut ca_spki = [0u8; 512];
+        let ca_spki = Signer::RsaPssSha256(&ca_key)
+            .public_key_spki(&mut ca_spki)
+            .unwrap();
+        let ca_spki = pkix::SubjectPublicKeyInfo::from_bytes(ca_spki).unwrap();
+        assert_eq!(extensions[4].extnID, oid::id_ce_authorityKeyIdentifier);
+        assert_eq!(
+            extensions[4]
+                .extnValue
+                .decode::<pkix::AuthorityKeyIdentifier<'_>>()
+                .unwrap()
+                .key_identifier,
+            &key_identifier(&ca_spki)
+        );
+
+        let mut message = [0u8; 1024];
+        let message_len = tbs.encode(&mut asn1::Encoder::new(&mut message)).unwrap();
+        ca_key
+            .public_key()
+            .verify_pss_sha256(
+                cert.signatureValue.clone().into_octets(),
+                &message[..message_len],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn invalid_serial_numbers() {
+        let key =
+            ecdsa::SigningKey::<P256>::from_pkcs8_der(include_bytes!("ecdsa/secp256r1.pkcs8.der"))
+                .unwrap();
+        let mut output = [0u8; 1024];
+
+        for serial_number in [&[][..], &[0x00; 4], &[0x80; 20], &[0x01; 21]] {
+            assert_eq!(
+                Certificate {
+                    serial_number,
+                    ..Default::default()
+                }
+                .self_sign(Signer::EcdsaP256Sha256(&key), &mut output)
+                .unwrap_err(),
+                Error::Asn1Error(asn1::Error::IntegerOutOfRange)
+            );
+        }
+    }
+}
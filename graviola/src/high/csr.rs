@@ -0,0 +1,451 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! PKCS#10 certification requests.
+//!
+//! See [RFC2986](https://datatracker.ietf.org/doc/html/rfc2986).
+
+use super::asn1::pkix::{self, KeyUsage, NameAttribute, SubjectAltName};
+use super::asn1::{self, oid, Type};
+#[cfg(any(feature = "p256", feature = "p384"))]
+use super::curve::PrivateKey;
+#[cfg(feature = "p256")]
+use super::curve::P256;
+#[cfg(feature = "p384")]
+use super::curve::P384;
+#[cfg(any(feature = "p256", feature = "p384"))]
+use super::ecdsa;
+#[cfg(any(feature = "p256", feature = "p384"))]
+use super::hash;
+#[cfg(feature = "rsa")]
+use super::rsa;
+use crate::error::Error;
+use crate::low::Entry;
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
+
+/// A private key, and the signature algorithm to use with it.
+#[derive(Clone, Copy)]
+pub enum Signer<'a> {
+    /// ECDSA on P-256, with SHA-256.
+    #[cfg(feature = "p256")]
+    EcdsaP256Sha256(&'a ecdsa::SigningKey<P256>),
+
+    /// ECDSA on P-384, with SHA-384.
+    #[cfg(feature = "p384")]
+    EcdsaP384Sha384(&'a ecdsa::SigningKey<P384>),
+
+    /// RSASSA-PKCS1-v1_5 with SHA-256.
+    #[cfg(feature = "rsa")]
+    RsaPkcs1Sha256(&'a rsa::SigningKey),
+
+    /// RSASSA-PSS with SHA-256, with a 32-byte salt.
+    #[cfg(feature = "rsa")]
+    RsaPssSha256(&'a rsa::SigningKey),
+}
+
+impl Signer<'_> {
+    /// Writes the `SubjectPublicKeyInfo` for the signing key.
+    pub(crate) fn public_key_spki<'o>(&self, output: &'o mut [u8]) -> Result<&'o [u8], Error> {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::EcdsaP256Sha256(key) => ecdsa_spki(*key, output),
+            #[cfg(feature = "p384")]
+            Self::EcdsaP384Sha384(key) => ecdsa_spki(*key, output),
+            #[cfg(feature = "rsa")]
+            Self::RsaPkcs1Sha256(key) | Self::RsaPssSha256(key) => {
+                key.public_key().to_spki_der(output)
+            }
+        }
+    }
+
+    /// Returns the `AlgorithmIdentifier` for signatures made by [`Self::sign()`].
+    ///
+    /// `buf` is used for encoding any parameters.
+    #[cfg_attr(not(feature = "rsa"), allow(unused_variables))]
+    pub(crate) fn signature_algorithm<'b>(
+        &self,
+        buf: &'b mut [u8],
+    ) -> Result<pkix::AlgorithmIdentifier<'b>, Error> {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::EcdsaP256Sha256(_) => Ok(ecdsa_signature_algorithm(&oid::ecdsa_with_SHA256)),
+            #[cfg(feature = "p384")]
+            Self::EcdsaP384Sha384(_) => Ok(ecdsa_signature_algorithm(&oid::ecdsa_with_SHA384)),
+            #[cfg(feature = "rsa")]
+            Self::RsaPkcs1Sha256(_) => Ok(pkix::AlgorithmIdentifier {
+                algorithm: oid::sha256WithRSAEncryption.clone(),
+                parameters: Some(asn1::Any::Null(asn1::Null)),
+            }),
+            #[cfg(feature = "rsa")]
+            Self::RsaPssSha256(_) => {
+                rsa::Restriction::PssWith(rsa::PssParameters::new(rsa::PssHash::Sha256))
+                    .to_algorithm_identifier(buf)
+            }
+        }
+    }
+
+    /// Signs `message`, writing the signature into `signature`.
+    ///
+    /// ECDSA signatures are DER-encoded `ECDSA-Sig-Value`s.
+    pub(crate) fn sign<'s>(
+        &self,
+        rng: &mut dyn RandomSource,
+        message: &[u8],
+        signature: &'s mut [u8],
+    ) -> Result<&'s [u8], Error> {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::EcdsaP256Sha256(key) => {
+                key.sign_asn1_with_rng::<hash::Sha256>(rng, &[message], signature)
+            }
+            #[cfg(feature = "p384")]
+            Self::EcdsaP384Sha384(key) => {
+                key.sign_asn1_with_rng::<hash::Sha384>(rng, &[message], signature)
+            }
+            #[cfg(feature = "rsa")]
+            Self::RsaPkcs1Sha256(key) => key.sign_pkcs1_sha256(signature, message),
+            #[cfg(feature = "rsa")]
+            Self::RsaPssSha256(key) => key.sign_pss_sha256_with_rng(rng, signature, message),
+        }
+    }
+}
+
+/// RFC5758 requires the parameters are absent for ECDSA.
+#[cfg(any(feature = "p256", feature = "p384"))]
+fn ecdsa_signature_algorithm(algorithm: &asn1::ObjectId) -> pkix::AlgorithmIdentifier<'static> {
+    pkix::AlgorithmIdentifier {
+        algorithm: algorithm.clone(),
+        parameters: None,
+    }
+}
+
+#[cfg(any(feature = "p256", feature = "p384"))]
+fn ecdsa_spki<'o, C: super::curve::Curve>(
+    key: &ecdsa::SigningKey<C>,
+    output: &'o mut [u8],
+) -> Result<&'o [u8], Error> {
+    let mut public_key = [0u8; MAX_X962_LEN];
+    let public_key = key
+        .private_key
+        .public_key_encode_uncompressed(&mut public_key)?;
+    ecdsa::VerifyingKey::<C>::from_x962_uncompressed(public_key)?.to_spki_der(output)
+}
+
+/// The contents of a PKCS#10 certification request.
+///
+/// Fill in the fields of this structure, and then use
+/// [`Self::sign()`] to produce a DER-encoded `CertificationRequest`.
+///
+/// The requested extensions are included in an `extensionRequest`
+/// attribute, which is omitted if there are none.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CertificationRequest<'a> {
+    /// The subject's distinguished name.
+    pub subject: &'a [NameAttribute<'a>],
+
+    /// Requested subject alternative names.
+    ///
+    /// No `subjectAltName` extension is requested if this is empty.
+    pub subject_alt_names: &'a [SubjectAltName<'a>],
+
+    /// Requested key usage.
+    ///
+    /// No `keyUsage` extension is requested if this is `None`.
+    pub key_usage: Option<KeyUsage>,
+}
+
+impl CertificationRequest<'_> {
+    /// Sign this request with `signer`, writing the DER encoding
+    /// to `output`.
+    ///
+    /// The public key in the request is that of `signer`.  The encoding
+    /// is written to the front of `output`, and that prefix is returned.
+    ///
+    /// Any randomness needed for signing comes from the default
+    /// [`RandomSource`].
+    #[cfg(feature = "std")]
+    pub fn sign<'o>(&self, signer: Signer<'_>, output: &'o mut [u8]) -> Result<&'o [u8], Error> {
+        self.sign_with_rng(&mut DefaultRandom, signer, output)
+    }
+
+    /// Sign this request with `signer`, using randomness from `rng`.
+    ///
+    /// See [`Self::sign()`] for details of the other arguments.
+    pub fn sign_with_rng<'o>(
+        &self,
+        rng: &mut dyn RandomSource,
+        signer: Signer<'_>,
+        output: &'o mut [u8],
+    ) -> Result<&'o [u8], Error> {
+        let _ = Entry::new_secret();
+
+        let mut spki = [0u8; MAX_SPKI_LEN];
+        let spki = signer.public_key_spki(&mut spki)?;
+        let spki = pkix::SubjectPublicKeyInfo::from_bytes(spki).map_err(Error::Asn1Error)?;
+
+        let extensions = [
+            (!self.subject_alt_names.is_empty()).then(|| pkix::Extension {
+                extnID: oid::id_ce_subjectAltName.clone(),
+                critical: subject_alt_name_critical(self.subject),
+                extnValue: pkix::ExtensionValue::SubjectAltName(asn1::SequenceOf::new(
+                    self.subject_alt_names,
+                )),
+            }),
+            self.key_usage.map(|usage| pkix::Extension {
+                extnID: oid::id_ce_keyUsage.clone(),
+                critical: Some(asn1::Boolean(true)),
+                extnValue: pkix::ExtensionValue::KeyUsage(usage),
+            }),
+        ];
+        let extensions = [pkix::Extensions::new(&extensions)];
+        let attributes = [pkix::Attribute {
+            attrType: oid::pkcs_9_at_extensionRequest.clone(),
+            attrValues: asn1::SetOf::new(&extensions),
+        }];
+        let any_extensions = self.key_usage.is_some() || !self.subject_alt_names.is_empty();
+
+        let info = pkix::CertificationRequestInfo {
+            version: asn1::Integer::new(&[0]),
+            subject: pkix::Name::new(self.subject),
+            subjectPKInfo: spki,
+            attributes: asn1::SequenceOf::new(match any_extensions {
+                true => &attributes,
+                false => &[],
+            }),
+        };
+
+        let len = info
+            .encode(&mut asn1::Encoder::new(output))
+            .map_err(Error::Asn1Error)?;
+
+        let mut signature = [0u8; MAX_SIGNATURE_LEN];
+        let signature = signer.sign(rng, &output[..len], &mut signature)?;
+        let mut algorithm_params = [0u8; MAX_ALGORITHM_PARAMS_LEN];
+
+        let len = pkix::CertificationRequest {
+            certificationRequestInfo: info,
+            signatureAlgorithm: signer.signature_algorithm(&mut algorithm_params)?,
+            signature: asn1::BitString::new(signature),
+        }
+        .encode(&mut asn1::Encoder::new(output))
+        .map_err(Error::Asn1Error)?;
+        Ok(&output[..len])
+    }
+}
+
+/// RFC5280 requires the `subjectAltName` extension is critical if
+/// the subject name is empty.  `critical` is `DEFAULT FALSE`, so DER
+/// requires it is omitted otherwise.
+pub(crate) fn subject_alt_name_critical(subject: &[NameAttribute<'_>]) -> Option<asn1::Boolean> {
+    subject.is_empty().then_some(asn1::Boolean(true))
+}
+
+/// Uncompressed P-384 public key.
+#[cfg(any(feature = "p256", feature = "p384"))]
+const MAX_X962_LEN: usize = 97;
+
+/// This is enough for an 8192-bit RSA key.
+pub(crate) const MAX_SPKI_LEN: usize = 1536;
+
+/// This is enough for an 8192-bit RSA signature.
+pub(crate) const MAX_SIGNATURE_LEN: usize = 1024;
+
+/// This is enough for `RSASSA-PSS-params`.
+pub(crate) const MAX_ALGORITHM_PARAMS_LEN: usize = 128;
+
+#[cfg(all(
+    test,
+    feature = "std",
+    feature = "p256",
+    feature = "p384",
+    feature = "rsa"
+))]
+mod tests {
+    use super::*;
+    use crate::high::asn1::pkix::Extension;
+    use crate::mid::rng::SliceRandomSource;
+
+    #[test]
+    fn ecdsa_request() {
+        let key =
+            ecdsa::SigningKey::<P256>::from_pkcs8_der(include_bytes!("ecdsa/secp256r1.pkcs8.der"))
+                .unwrap();
+        let subject = [
+            NameAttribute::Country("GB"),
+            NameAttribute::Organization("Graviola Tests"),
+            NameAttribute::CommonName("example.com"),
+        ];
+        let sans = [
+            SubjectAltName::DnsName("example.com"),
+            SubjectAltName::DnsName("*.example.com"),
+            SubjectAltName::IpAddress(&[192, 0, 2, 1]),
+            SubjectAltName::Email("admin@example.com"),
+        ];
+        let usage = KeyUsage::DIGITAL_SIGNATURE | KeyUsage::KEY_AGREEMENT;
+        let request = CertificationRequest {
+            subject: &subject,
+            subject_alt_names: &sans,
+            key_usage: Some(usage),
+        };
+
+        let mut output = [0u8; 1024];
+        let der = request
+            .sign_with_rng(
+                &mut SliceRandomSource(&[0xab; 16]),
+                Signer::EcdsaP256Sha256(&key),
+                &mut output,
+            )
+            .unwrap();
+
+        let parsed = pkix::CertificationRequest::from_bytes(der).unwrap();
+        assert_eq!(parsed.signatureAlgorithm.algorithm, oid::ecdsa_with_SHA256);
+        assert_eq!(parsed.signatureAlgorithm.parameters, None);
+
+        let info = &parsed.certificationRequestInfo;
+        assert_eq!(info.version.as_usize(), Ok(0));
+        let names = info.subject.iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(names, subject);
+
+        let mut spki = [0u8; 128];
+        let spki_len = info
+            .subjectPKInfo
+            .encode(&mut asn1::Encoder::new(&mut spki))
+            .unwrap();
+        let vk = ecdsa::VerifyingKey::<P256>::from_spki_der(&spki[..spki_len]).unwrap();
+
+        let mut attrs = info.attributes.iter();
+        let attr = attrs.next().unwrap().unwrap();
+        assert!(attrs.next().is_none());
+        assert_eq!(attr.attrType, oid::pkcs_9_at_extensionRequest);
+        let extensions = attr.attrValues.iter().next().unwrap().unwrap();
+        let extensions = extensions
+            .iter()
+            .map(|ext| ext.unwrap().unwrap())
+            .collect::<Vec<Extension<'_>>>();
+        assert_eq!(extensions.len(), 2);
+
+        assert_eq!(extensions[0].extnID, oid::id_ce_subjectAltName);
+        assert_eq!(extensions[0].critical, None);
+        let names = extensions[0]
+            .extnValue
+            .decode::<pkix::GeneralNames<'_>>()
+            .unwrap()
+            .iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(names, sans);
+
+        assert_eq!(extensions[1].extnID, oid::id_ce_keyUsage);
+        assert_eq!(extensions[1].critical, Some(asn1::Boolean(true)));
+        assert_eq!(extensions[1].extnValue.decode::<KeyUsage>().unwrap(), usage);
+
+        // the signature covers the encoding of `certificationRequestInfo`
+        let mut message = [0u8; 1024];
+        let message_len = info.encode(&mut asn1::Encoder::new(&mut message)).unwrap();
+        vk.verify_asn1::<hash::Sha256>(
+            &[&message[..message_len]],
+            parsed.signature.clone().into_octets(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn rsa_request() {
+        let key = rsa::SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa2048.der")).unwrap();
+        let subject = [NameAttribute::CommonName("rsa.example.com")];
+
+        for (signer, algorithm) in [
+            (Signer::RsaPkcs1Sha256(&key), &oid::sha256WithRSAEncryption),
+            (Signer::RsaPssSha256(&key), &oid::id_RSASSA_PSS),
+        ] {
+            let mut output = [0u8; 2048];
+            let der = CertificationRequest {
+                subject: &subject,
+                ..Default::default()
+            }
+            .sign(signer, &mut output)
+            .unwrap();
+
+            let parsed = pkix::CertificationRequest::from_bytes(der).unwrap();
+            assert_eq!(&parsed.signatureAlgorithm.algorithm, algorithm);
+            // no extensions requested, so no attributes
+            assert!(parsed
+                .certificationRequestInfo
+                .attributes
+                .iter()
+                .next()
+                .is_none());
+
+            let mut message = [0u8; 1024];
+            let message_len = parsed
+                .certificationRequestInfo
+                .encode(&mut asn1::Encoder::new(&mut message))
+                .unwrap();
+            let message = &message[..message_len];
+            let signature = parsed.signature.clone().into_octets();
+            let vk = key.public_key();
+            match signer {
+                Signer::RsaPkcs1Sha256(_) => vk.verify_pkcs1_sha256(signature, message).unwrap(),
+                _ => vk.verify_pss_sha256(signature, message).unwrap(),
+            }
+        }
+    }
+
+    #[test]
+    fn empty_subject() {
+        let key =
+            ecdsa::SigningKey::<P384>::from_pkcs8_der(include_bytes!("ecdsa/secp384r1.pkcs8.der"))
+                .unwrap();
+        let mut output = [0u8; 1024];
+        let der = CertificationRequest {
+            subject_alt_names: &[SubjectAltName::IpAddress(&[0u8; 16])],
+            ..Default::default()
+        }
+        .sign(Signer::EcdsaP384Sha384(&key), &mut output)
+        .unwrap();
+
+        let parsed = pkix::CertificationRequest::from_bytes(der).unwrap();
+        let info = parsed.certificationRequestInfo;
+        assert!(info.subject.iter().next().is_none());
+        let attr = info.attributes.iter().next().unwrap().unwrap();
+        let extensions = attr.attrValues.iter().next().unwrap().unwrap();
+        let san = extensions.iter().next().unwrap().unwrap().unwrap();
+        assert_eq!(san.critical, Some(asn1::Boolean(true)));
+    }
+
+    #[test]
+    fn invalid_names() {
+        let key =
+            ecdsa::SigningKey::<P256>::from_pkcs8_der(include_bytes!("ecdsa/secp256r1.pkcs8.der"))
+                .unwrap();
+        let mut output = [0u8; 1024];
+
+        for request in [
+            CertificationRequest {
+                subject: &[NameAttribute::Country("G*")],
+                ..Default::default()
+            },
+            CertificationRequest {
+                subject_alt_names: &[SubjectAltName::DnsName("bücher.example")],
+                ..Default::default()
+            },
+            CertificationRequest {
+                subject_alt_names: &[SubjectAltName::IpAddress(&[127, 0, 0])],
+                ..Default::default()
+            },
+        ] {
+            assert!(request
+                .sign(Signer::EcdsaP256Sha256(&key), &mut output)
+                .is_err());
+        }
+
+        assert_eq!(
+            CertificationRequest::default()
+                .sign(Signer::EcdsaP256Sha256(&key), &mut [0u8; 64])
+                .unwrap_err(),
+            Error::Asn1Error(asn1::Error::UnexpectedEof)
+        );
+    }
+}
@@ -0,0 +1,562 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! CTR_DRBG: a deterministic random bit generator built on a block cipher.
+//!
+//! CTR_DRBG is standardized in [SP800-90A](https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-90Ar1.pdf).
+
+use crate::low::{zeroise, AesKey, Entry};
+use crate::mid::rng::RandomSource;
+use crate::Error;
+
+/// An AES-256 CTR_DRBG instance, using the derivation function.
+///
+/// This implements the CTR_DRBG of SP800-90A section 10.2.1, including
+/// reseeding, additional input, reseed-interval enforcement and
+/// prediction resistance.  The block cipher derivation function
+/// (section 10.3.2) is always used, so entropy input, nonce,
+/// personalization strings and additional input may be any length.
+///
+/// It is the caller's responsibility to supply entropy input of at
+/// least 256 bits to [`CtrDrbgAes256::new()`], [`CtrDrbgAes256::reseed()`]
+/// and [`CtrDrbgAes256::generate_with_prediction_resistance()`].
+/// [`CtrDrbgAes256::new_from_source()`] and
+/// [`CtrDrbgAes256::reseed_from_source()`] obtain entropy input from a
+/// [`RandomSource`].
+///
+/// The [`RandomSource`] implementation generates output with no
+/// additional input.  It returns [`Error::ReseedRequired`] once the
+/// reseed interval is exhausted.
+pub struct CtrDrbgAes256 {
+    key: AesKey,
+    v: [u8; BLOCK_LEN],
+    reseed_counter: u64,
+    reseed_interval: u64,
+}
+
+impl CtrDrbgAes256 {
+    /// The largest number of bytes that may be requested from one call to
+    /// [`CtrDrbgAes256::generate()`].
+    ///
+    /// This is 2<sup>19</sup> bits.
+    pub const MAX_REQUEST_BYTES: usize = 1 << 16;
+
+    /// The largest (and default) permitted reseed interval.
+    ///
+    /// This is the number of calls to [`CtrDrbgAes256::generate()`] allowed
+    /// between reseeds.
+    pub const MAX_RESEED_INTERVAL: u64 = 1 << 48;
+
+    /// Instantiate a CTR_DRBG.
+    ///
+    /// `entropy_input`, `nonce` and `personalization_string` are as
+    /// described in SP800-90A.  `personalization_string` may be empty.
+    pub fn new(entropy_input: &[u8], nonce: &[u8], personalization_string: &[u8]) -> Self {
+        let _ = Entry::new_secret();
+
+        // 1. seed_material = entropy_input || nonce || personalization_string.
+        // 2. seed_material = df (seed_material, seedlen).
+        let mut seed_material =
+            derivation_function(&[entropy_input, nonce, personalization_string]);
+
+        // 3. Key = 0^keylen.
+        // 4. V = 0^blocklen.
+        let mut r = Self {
+            key: AesKey::new(&[0u8; KEY_LEN]),
+            v: [0u8; BLOCK_LEN],
+            reseed_counter: 0,
+            reseed_interval: Self::MAX_RESEED_INTERVAL,
+        };
+
+        // 5. (Key, V) = CTR_DRBG_Update (seed_material, Key, V).
+        r.update(&seed_material);
+        zeroise(&mut seed_material);
+
+        // 6. reseed_counter = 1.
+        r.reseed_counter = 1;
+        r
+    }
+
+    /// Instantiate a CTR_DRBG, taking the entropy input and nonce
+    /// from `source`.
+    ///
+    /// `personalization_string` may be empty.
+    pub fn new_from_source(
+        source: &mut dyn RandomSource,
+        personalization_string: &[u8],
+    ) -> Result<Self, Error> {
+        let mut seed = [0u8; KEY_LEN + BLOCK_LEN];
+        source.fill(&mut seed)?;
+        let (entropy_input, nonce) = seed.split_at(KEY_LEN);
+        let r = Self::new(entropy_input, nonce, personalization_string);
+        zeroise(&mut seed);
+        Ok(r)
+    }
+
+    /// Reseed this CTR_DRBG with fresh `entropy_input`.
+    ///
+    /// `additional_input` may be empty.
+    pub fn reseed(&mut self, entropy_input: &[u8], additional_input: &[u8]) {
+        let _ = Entry::new_secret();
+
+        // 1. seed_material = entropy_input || additional_input.
+        // 2. seed_material = df (seed_material, seedlen).
+        let mut seed_material = derivation_function(&[entropy_input, additional_input]);
+
+        // 3. (Key, V) = CTR_DRBG_Update (seed_material, Key, V).
+        self.update(&seed_material);
+        zeroise(&mut seed_material);
+
+        // 4. reseed_counter = 1.
+        self.reseed_counter = 1;
+    }
+
+    /// Reseed this CTR_DRBG, taking the entropy input from `source`.
+    ///
+    /// `additional_input` may be empty.
+    pub fn reseed_from_source(
+        &mut self,
+        source: &mut dyn RandomSource,
+        additional_input: &[u8],
+    ) -> Result<(), Error> {
+        let mut entropy_input = [0u8; KEY_LEN];
+        source.fill(&mut entropy_input)?;
+        self.reseed(&entropy_input, additional_input);
+        zeroise(&mut entropy_input);
+        Ok(())
+    }
+
+    /// Generate `out.len()` bytes of output, into `out`.
+    ///
+    /// `additional_input` may be empty.
+    ///
+    /// Returns [`Error::ReseedRequired`] if the reseed interval
+    /// is exhausted, or [`Error::WrongLength`] if `out` is longer
+    /// than [`CtrDrbgAes256::MAX_REQUEST_BYTES`].
+    pub fn generate(&mut self, additional_input: &[u8], out: &mut [u8]) -> Result<(), Error> {
+        let _ = Entry::new_secret();
+
+        if out.len() > Self::MAX_REQUEST_BYTES {
+            return Err(Error::WrongLength);
+        }
+
+        // 1. If reseed_counter > reseed_interval, then return an
+        //    indication that a reseed is required.
+        if self.reseed_counter > self.reseed_interval {
+            return Err(Error::ReseedRequired);
+        }
+
+        // 2. If (additional_input ≠ Null), then
+        //    2.1 additional_input = Block_Cipher_df (additional_input, seedlen).
+        //    2.2 (Key, V) = CTR_DRBG_Update (additional_input, Key, V).
+        //    Else additional_input = 0^seedlen.
+        let mut additional_input = match additional_input.is_empty() {
+            true => [0u8; SEED_LEN],
+            false => {
+                let additional_input = derivation_function(&[additional_input]);
+                self.update(&additional_input);
+                additional_input
+            }
+        };
+
+        // 3. temp = Null.
+        // 4. While (len (temp) < requested_number_of_bits) do:
+        //    4.1 V = (V+1) mod 2^blocklen.
+        //    4.2 output_block = Block_Encrypt (Key, V).
+        //    4.3 temp = temp || output_block.
+        // 5. returned_bits = leftmost (temp, requested_number_of_bits).
+        for chunk in out.chunks_mut(BLOCK_LEN) {
+            increment(&mut self.v);
+            let mut block = self.v;
+            self.key.encrypt_block(&mut block);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            zeroise(&mut block);
+        }
+
+        // 6. (Key, V) = CTR_DRBG_Update (additional_input, Key, V).
+        self.update(&additional_input);
+        zeroise(&mut additional_input);
+
+        // 7. reseed_counter = reseed_counter + 1.
+        self.reseed_counter += 1;
+
+        Ok(())
+    }
+
+    /// Generate `out.len()` bytes of output, into `out`, with
+    /// prediction resistance.
+    ///
+    /// This reseeds with `entropy_input` and `additional_input`
+    /// before generating output, as described in SP800-90A
+    /// section 9.3.1.
+    pub fn generate_with_prediction_resistance(
+        &mut self,
+        entropy_input: &[u8],
+        additional_input: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), Error> {
+        if out.len() > Self::MAX_REQUEST_BYTES {
+            return Err(Error::WrongLength);
+        }
+
+        self.reseed(entropy_input, additional_input);
+        self.generate(&[], out)
+    }
+
+    /// Set the reseed interval.
+    ///
+    /// This is the number of calls to [`CtrDrbgAes256::generate()`]
+    /// allowed between reseeds.  It must be between 1 and
+    /// [`CtrDrbgAes256::MAX_RESEED_INTERVAL`] inclusive, otherwise
+    /// [`Error::OutOfRange`] is returned.
+    pub fn set_reseed_interval(&mut self, reseed_interval: u64) -> Result<(), Error> {
+        if !(1..=Self::MAX_RESEED_INTERVAL).contains(&reseed_interval) {
+            return Err(Error::OutOfRange);
+        }
+        self.reseed_interval = reseed_interval;
+        Ok(())
+    }
+
+    fn update(&mut self, provided_data: &[u8; SEED_LEN]) {
+        // 1. temp = Null.
+        // 2. While (len (temp) < seedlen) do
+        //    2.1 V = (V+1) mod 2^blocklen.
+        //    2.2 output_block = Block_Encrypt (Key, V).
+        //    2.3 temp = temp || output_block.
+        let mut temp = [0u8; SEED_LEN];
+        for block in temp.chunks_exact_mut(BLOCK_LEN) {
+            increment(&mut self.v);
+            block.copy_from_slice(&self.v);
+            self.key.encrypt_block(block);
+        }
+
+        // 3. temp = leftmost (temp, seedlen).
+        // 4. temp = temp ⊕ provided_data.
+        for (t, p) in temp.iter_mut().zip(provided_data.iter()) {
+            *t ^= *p;
+        }
+
+        // 5. Key = leftmost (temp, keylen).
+        // 6. V = rightmost (temp, blocklen).
+        self.key = AesKey::new(&temp[..KEY_LEN]);
+        self.v.copy_from_slice(&temp[KEY_LEN..]);
+        zeroise(&mut temp);
+    }
+}
+
+impl RandomSource for CtrDrbgAes256 {
+    fn fill(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        for chunk in out.chunks_mut(Self::MAX_REQUEST_BYTES) {
+            self.generate(&[], chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for CtrDrbgAes256 {
+    fn drop(&mut self) {
+        // nb. `key` zeroises itself
+        zeroise(&mut self.v);
+    }
+}
+
+/// Block_Cipher_df, from SP800-90A section 10.3.2.
+///
+/// `input` is the concatenation of the given slices.  The
+/// output length is always `seedlen`.
+fn derivation_function(input: &[&[u8]]) -> [u8; SEED_LEN] {
+    let input_len: usize = input.iter().map(|i| i.len()).sum();
+
+    // 1. If (number_of_bits_to_return > max_number_of_bits), then return an
+    //    ERROR_FLAG and a Null string.
+    // nb. not applicable: number_of_bits_to_return is fixed.
+
+    // 2. L = len (input_string)/8.
+    // 3. N = number_of_bits_to_return/8.
+    let l = (input_len as u32).to_be_bytes();
+    let n = (SEED_LEN as u32).to_be_bytes();
+
+    // 4. S = L || N || input_string || 0x80.
+    // 5. While (len (S) mod outlen) ≠ 0, do S = S || 0x00.
+    // (nb. the padding is done by `Bcc::finish()`)
+
+    // 6. temp = the Null string.
+    // 7. i = 0.
+    // 8. K = leftmost (0x00010203...1D1E1F, keylen).
+    let k = AesKey::new(&DF_KEY);
+    let mut temp = [0u8; SEED_LEN];
+
+    // 9. While len (temp) < keylen + outlen, do
+    //    9.1 IV = i || 0^(outlen - len (i)).
+    //    9.2 temp = temp || BCC (K, (IV || S)).
+    //    9.3 i = i + 1.
+    for (i, chunk) in temp.chunks_exact_mut(BLOCK_LEN).enumerate() {
+        let mut iv = [0u8; BLOCK_LEN];
+        iv[..4].copy_from_slice(&(i as u32).to_be_bytes());
+
+        let mut bcc = Bcc::new(&k);
+        bcc.update(&iv);
+        bcc.update(&l);
+        bcc.update(&n);
+        for item in input {
+            bcc.update(item);
+        }
+        bcc.update(&[0x80]);
+        chunk.copy_from_slice(&bcc.finish());
+    }
+
+    // 10. K = leftmost (temp, keylen).
+    // 11. X = select (temp, keylen+1, keylen+outlen).
+    let k = AesKey::new(&temp[..KEY_LEN]);
+    let mut x = [0u8; BLOCK_LEN];
+    x.copy_from_slice(&temp[KEY_LEN..]);
+
+    // 12. temp = the Null string.
+    // 13. While len (temp) < number_of_bits_to_return, do
+    //     13.1 X = Block_Encrypt (K, X).
+    //     13.2 temp = temp || X.
+    // 14. requested_bits = leftmost (temp, number_of_bits_to_return).
+    for chunk in temp.chunks_exact_mut(BLOCK_LEN) {
+        k.encrypt_block(&mut x);
+        chunk.copy_from_slice(&x);
+    }
+    zeroise(&mut x);
+
+    temp
+}
+
+/// BCC, from SP800-90A section 10.3.3.
+///
+/// This is CBC-MAC with a zero IV, taking its input incrementally.
+/// Incomplete final blocks are padded with zeroes.
+struct Bcc<'a> {
+    key: &'a AesKey,
+    chaining_value: [u8; BLOCK_LEN],
+    used: usize,
+}
+
+impl<'a> Bcc<'a> {
+    fn new(key: &'a AesKey) -> Self {
+        Self {
+            key,
+            chaining_value: [0u8; BLOCK_LEN],
+            used: 0,
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.chaining_value[self.used] ^= *b;
+            self.used += 1;
+
+            if self.used == BLOCK_LEN {
+                self.key.encrypt_block(&mut self.chaining_value);
+                self.used = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; BLOCK_LEN] {
+        if self.used != 0 {
+            self.key.encrypt_block(&mut self.chaining_value);
+        }
+        let r = self.chaining_value;
+        zeroise(&mut self.chaining_value);
+        r
+    }
+}
+
+/// V = (V+1) mod 2^blocklen
+fn increment(v: &mut [u8; BLOCK_LEN]) {
+    *v = u128::from_be_bytes(*v).wrapping_add(1).to_be_bytes();
+}
+
+const BLOCK_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const SEED_LEN: usize = KEY_LEN + BLOCK_LEN;
+
+const DF_KEY: [u8; KEY_LEN] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+
+    #[test]
+    fn cavs_example() {
+        let mut ctx = CtrDrbgAes256::new(
+            b"\x2d\x4c\x9f\x46\xb9\x81\xc6\xa0\xb2\xb5\xd8\xc6\x93\x91\xe5\x69\xff\x13\x85\x14\x37\xeb\xc0\xfc\x00\xd6\x16\x34\x02\x52\xfe\xd5",
+            b"\x0b\xf8\x14\xb4\x11\xf6\x5e\xc4\x86\x6b\xe1\xab\xb5\x9d\x3c\x32",
+            &[],
+        );
+        ctx.reseed(b"\x93\x50\x0f\xae\x4f\xa3\x2b\x86\x03\x3b\x7a\x7b\xac\x9d\x37\xe7\x10\xdc\xc6\x7c\xa2\x66\xbc\x86\x07\xd6\x65\x93\x77\x66\xd2\x07", &[]);
+        let mut out = [0u8; 64];
+        ctx.generate(&[], &mut out).unwrap();
+        ctx.generate(&[], &mut out).unwrap();
+        assert_eq!(&out, b"\x32\x2d\xd2\x86\x70\xe7\x5c\x0e\xa6\x38\xf3\xcb\x68\xd6\xa9\xd6\xe5\x0d\xdf\xd0\x52\xb7\x72\xa7\xb1\xd7\x82\x63\xa7\xb8\x97\x8b\x67\x40\xc2\xb6\x5a\x95\x50\xc3\xa7\x63\x25\x86\x6f\xa9\x7e\x16\xd7\x40\x06\xbc\x96\xf2\x62\x49\xb9\xf0\xa9\x0d\x07\x6f\x08\xe5");
+    }
+
+    #[test]
+    fn cavs_example_personalization_additional_input() {
+        let mut ctx = CtrDrbgAes256::new(
+            b"\x17\x4b\x46\x25\x00\x51\xa9\xe3\xd8\x0c\x56\xae\x71\x63\xda\xfe\x7e\x54\x48\x1a\x56\xca\xfd\x3b\x86\x25\xf9\x9b\xbb\x29\xc4\x42",
+            b"\x98\xff\xd9\x9c\x46\x6e\x0e\x94\xa4\x5d\xa7\xe0\xe8\x2d\xbc\x6b",
+            b"\x70\x95\x26\x8e\x99\x93\x8b\x3e\x04\x27\x34\xb9\x17\x6c\x9a\xa0\x51\xf0\x0a\x5f\x8d\x2a\x89\xad\xa2\x14\xb8\x9b\xee\xf1\x8e\xbf",
+        );
+        ctx.reseed(
+            b"\xe8\x8b\xe1\x96\x7c\x55\x03\xf6\x5d\x23\x86\x7b\xbc\x89\x1b\xd6\x79\xdb\x03\xb4\x87\x86\x63\xf6\xc8\x77\x59\x2d\xf2\x5f\x0d\x9a",
+            b"\xcd\xf6\xad\x54\x9e\x45\xb6\xaa\x5c\xd6\x7d\x02\x49\x31\xc3\x3c\xd1\x33\xd5\x2d\x5a\xe5\x00\xc3\x01\x50\x20\xbe\xb3\x0d\xa0\x63",
+        );
+        let mut out = [0u8; 64];
+        ctx.generate(b"\xc7\x22\x8e\x90\xc6\x2f\x89\x6a\x09\xe1\x16\x84\x53\x01\x02\xf9\x26\xec\x90\xa3\x25\x5f\x6c\x21\xb8\x57\x88\x3c\x75\x80\x01\x43", &mut out).unwrap();
+        ctx.generate(b"\x76\xa9\x4f\x22\x41\x78\xfe\x4c\xbf\x9e\x2b\x8a\xcc\x53\xc9\xdc\x3e\x50\xbb\x61\x3a\xac\x89\x36\x60\x14\x53\xcd\xa3\x29\x3b\x17", &mut out).unwrap();
+        assert_eq!(&out, b"\x1a\x6d\x8d\xbd\x64\x20\x76\xd1\x39\x16\xe5\xe2\x30\x38\xb6\x0b\x26\x06\x1f\x13\xdd\x4e\x00\x62\x77\xe0\x26\x86\x98\xff\xb2\xc8\x7e\x45\x3b\xae\x12\x51\x63\x1a\xc9\x0c\x70\x1a\x98\x49\xd9\x33\x99\x5e\x8b\x02\x21\xfe\x9a\xca\x19\x85\xc5\x46\xc2\x07\x90\x27");
+    }
+
+    #[test]
+    fn reseed_interval() {
+        let mut ctx = CtrDrbgAes256::new(&[1; 32], &[2; 16], b"test");
+        assert_eq!(ctx.set_reseed_interval(0), Err(Error::OutOfRange));
+        assert_eq!(
+            ctx.set_reseed_interval(CtrDrbgAes256::MAX_RESEED_INTERVAL + 1),
+            Err(Error::OutOfRange)
+        );
+        ctx.set_reseed_interval(2).unwrap();
+
+        let mut out = [0u8; 16];
+        ctx.generate(&[], &mut out).unwrap();
+        ctx.fill(&mut out).unwrap();
+        assert_eq!(ctx.generate(&[], &mut out), Err(Error::ReseedRequired));
+        assert_eq!(ctx.fill(&mut out), Err(Error::ReseedRequired));
+
+        ctx.reseed(&[3; 32], &[]);
+        ctx.generate(&[], &mut out).unwrap();
+    }
+
+    #[test]
+    fn max_request() {
+        let mut ctx = CtrDrbgAes256::new(&[1; 32], &[2; 16], &[]);
+        let mut out = vec![0u8; CtrDrbgAes256::MAX_REQUEST_BYTES + 1];
+        assert_eq!(ctx.generate(&[], &mut out), Err(Error::WrongLength));
+        ctx.generate(&[], &mut out[1..]).unwrap();
+        // `fill` splits larger requests
+        ctx.fill(&mut out).unwrap();
+    }
+
+    #[test]
+    fn from_source() {
+        let mut source = crate::mid::rng::SliceRandomSource(&[0x11; 48 + 32]);
+        let mut ctx = CtrDrbgAes256::new_from_source(&mut source, b"pers").unwrap();
+        ctx.reseed_from_source(&mut source, b"add").unwrap();
+        assert_eq!(
+            ctx.reseed_from_source(&mut source, &[]),
+            Err(Error::RngFailed)
+        );
+
+        let mut expected = CtrDrbgAes256::new(&[0x11; 32], &[0x11; 16], b"pers");
+        expected.reseed(&[0x11; 32], b"add");
+
+        let (mut a, mut b) = ([0u8; 32], [0u8; 32]);
+        ctx.fill(&mut a).unwrap();
+        expected.fill(&mut b).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn cavp() {
+        #[derive(Default)]
+        struct State {
+            supported: bool,
+            prediction_resistance: bool,
+            returned_bits_len: usize,
+            entropy_input: Vec<u8>,
+            nonce: Vec<u8>,
+            entropy_input_reseed: Vec<u8>,
+            additional_input: Vec<u8>,
+            drbg: Option<CtrDrbgAes256>,
+            output: Vec<u8>,
+        }
+
+        impl CavpSink for State {
+            fn on_meta(&mut self, meta: &str) {
+                match meta.split_once(" = ") {
+                    Some(("PredictionResistance", value)) => {
+                        self.prediction_resistance = value == "True"
+                    }
+                    Some(("ReturnedBitsLen", value)) => {
+                        self.returned_bits_len = value.parse().unwrap()
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.supported = meta == "AES-256 use df";
+                        if !self.supported {
+                            println!("skipping unsupported {meta:?}");
+                        }
+                    }
+                }
+            }
+
+            fn on_value(&mut self, name: &str, value: Value<'_>) {
+                match name {
+                    "COUNT" => println!("  test {}", value.int()),
+                    "EntropyInput" => self.entropy_input = value.bytes(),
+                    "Nonce" => self.nonce = value.bytes(),
+                    "PersonalizationString" if self.supported => {
+                        self.drbg = Some(CtrDrbgAes256::new(
+                            &self.entropy_input,
+                            &self.nonce,
+                            &value.bytes(),
+                        ))
+                    }
+                    "PersonalizationString" => {}
+                    "EntropyInputReseed" => self.entropy_input_reseed = value.bytes(),
+                    "AdditionalInputReseed" => {
+                        if let Some(drbg) = self.drbg.as_mut() {
+                            drbg.reseed(&self.entropy_input_reseed, &value.bytes());
+                        }
+                    }
+                    "AdditionalInput" if self.prediction_resistance => {
+                        self.additional_input = value.bytes()
+                    }
+                    "AdditionalInput" => {
+                        if let Some(drbg) = self.drbg.as_mut() {
+                            self.output = vec![0u8; self.returned_bits_len / 8];
+                            drbg.generate(&value.bytes(), &mut self.output).unwrap();
+                        }
+                    }
+                    "EntropyInputPR" => {
+                        if let Some(drbg) = self.drbg.as_mut() {
+                            self.output = vec![0u8; self.returned_bits_len / 8];
+                            drbg.generate_with_prediction_resistance(
+                                &value.bytes(),
+                                &self.additional_input,
+                                &mut self.output,
+                            )
+                            .unwrap();
+                        }
+                    }
+                    "ReturnedBits" => {
+                        if self.drbg.take().is_some() {
+                            assert_eq!(self.output, value.bytes());
+                        }
+                    }
+                    _ => {
+                        todo!("unhandled value {name} = {value:?}");
+                    }
+                }
+            }
+        }
+
+        process_cavp(
+            "../thirdparty/cavp/drbg/pr_false/CTR_DRBG.rsp",
+            &mut State::default(),
+        );
+        process_cavp(
+            "../thirdparty/cavp/drbg/pr_true/CTR_DRBG.rsp",
+            &mut State::default(),
+        );
+    }
+}
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
 use crate::high::asn1;
+#[cfg(feature = "p256")]
 use crate::mid::p256;
+#[cfg(feature = "p384")]
 use crate::mid::p384;
 use crate::mid::rng::RandomSource;
 use crate::Error;
@@ -80,6 +82,11 @@ pub trait PublicKey<C: Curve + ?Sized> {
     where
         Self: Sized;
 
+    /// Output the uncompressed encoding of this point.
+    ///
+    /// The return value is the written prefix of `out`.
+    fn encode_uncompressed<'a>(&self, out: &'a mut [u8]) -> Result<&'a [u8], Error>;
+
     /// Raw ECDSA verification primitive.
     fn raw_ecdsa_verify(&self, r: &C::Scalar, s: &C::Scalar, e: &C::Scalar) -> Result<(), Error>;
 }
@@ -129,8 +136,10 @@ pub(crate) const MAX_SCALAR_LEN: usize = 66;
 /// P-256 is also known as "NISTP256", "prime256v1", or "secp256r1".
 ///
 /// See [SEC1](https://www.secg.org/sec1-v2.pdf) for one definition.
+#[cfg(feature = "p256")]
 pub struct P256;
 
+#[cfg(feature = "p256")]
 impl Curve for P256 {
     type PrivateKey = p256::PrivateKey;
     type PublicKey = p256::PublicKey;
@@ -141,12 +150,14 @@ impl Curve for P256 {
     }
 
     fn generate_random_key(rng: &mut dyn RandomSource) -> Result<p256::PrivateKey, Error> {
-        p256::PrivateKey::generate(rng)
+        p256::PrivateKey::new_random_with_rng(rng)
     }
 }
 
+#[cfg(feature = "p256")]
 impl private::Sealed for P256 {}
 
+#[cfg(feature = "p256")]
 impl PrivateKey<P256> for p256::PrivateKey {
     fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
         Self::from_bytes(bytes)
@@ -179,6 +190,7 @@ impl PrivateKey<P256> for p256::PrivateKey {
     }
 }
 
+#[cfg(feature = "p256")]
 impl PublicKey<P256> for p256::PublicKey {
     const LEN_BYTES: usize = 65;
 
@@ -186,6 +198,15 @@ impl PublicKey<P256> for p256::PublicKey {
         Self::from_x962_uncompressed(bytes)
     }
 
+    fn encode_uncompressed<'a>(&self, out: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        if let Some(out) = out.get_mut(0..65) {
+            out.copy_from_slice(&self.as_bytes_uncompressed());
+            Ok(out)
+        } else {
+            Err(Error::OutOfRange)
+        }
+    }
+
     fn raw_ecdsa_verify(
         &self,
         r: &p256::Scalar,
@@ -196,6 +217,7 @@ impl PublicKey<P256> for p256::PublicKey {
     }
 }
 
+#[cfg(feature = "p256")]
 impl Scalar<P256> for p256::Scalar {
     const LEN_BYTES: usize = 32;
 
@@ -221,8 +243,10 @@ impl Scalar<P256> for p256::Scalar {
 /// P-384 is also known as "NISTP384", or "secp384r1".
 ///
 /// See [SEC1](https://www.secg.org/sec1-v2.pdf) for one definition.
+#[cfg(feature = "p384")]
 pub struct P384;
 
+#[cfg(feature = "p384")]
 impl Curve for P384 {
     type PrivateKey = p384::PrivateKey;
     type PublicKey = p384::PublicKey;
@@ -233,12 +257,14 @@ impl Curve for P384 {
     }
 
     fn generate_random_key(rng: &mut dyn RandomSource) -> Result<p384::PrivateKey, Error> {
-        p384::PrivateKey::generate(rng)
+        p384::PrivateKey::new_random_with_rng(rng)
     }
 }
 
+#[cfg(feature = "p384")]
 impl private::Sealed for P384 {}
 
+#[cfg(feature = "p384")]
 impl PrivateKey<P384> for p384::PrivateKey {
     fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
         Self::from_bytes(bytes)
@@ -271,6 +297,7 @@ impl PrivateKey<P384> for p384::PrivateKey {
     }
 }
 
+#[cfg(feature = "p384")]
 impl PublicKey<P384> for p384::PublicKey {
     const LEN_BYTES: usize = 97;
 
@@ -278,6 +305,15 @@ impl PublicKey<P384> for p384::PublicKey {
         Self::from_x962_uncompressed(bytes)
     }
 
+    fn encode_uncompressed<'a>(&self, out: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        if let Some(out) = out.get_mut(0..97) {
+            out.copy_from_slice(&self.as_bytes_uncompressed());
+            Ok(out)
+        } else {
+            Err(Error::OutOfRange)
+        }
+    }
+
     fn raw_ecdsa_verify(
         &self,
         r: &p384::Scalar,
@@ -288,6 +324,7 @@ impl PublicKey<P384> for p384::PublicKey {
     }
 }
 
+#[cfg(feature = "p384")]
 impl Scalar<P384> for p384::Scalar {
     const LEN_BYTES: usize = 48;
 
@@ -308,7 +345,7 @@ impl Scalar<P384> for p384::Scalar {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "p256", feature = "p384"))]
 mod tests {
     use super::*;
     use crate::test::*;
@@ -3,18 +3,20 @@
 
 use super::asn1::{self, Type};
 use super::curve::{Curve, PrivateKey, PublicKey, Scalar, MAX_SCALAR_LEN};
-use super::hash::{Hash, HashContext};
+use super::hash::{Hash, HashContext, SigningHash};
 use super::hmac_drbg::HmacDrbg;
-use super::pkcs8;
+use super::{pem, pkcs8};
 use crate::error::{Error, KeyFormatError};
 use crate::low::{zeroise, Entry};
-use crate::mid::rng::{RandomSource, SystemRandom};
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
 
 /// An ECDSA signing key, on curve `C`.
 ///
 /// You can make one of these by loading a key from a file
-/// with [`Self::from_pkcs8_der()`] or [`Self::from_sec1_der()`],
-/// or by generating a random key using [`Curve::generate_random_key()`].
+/// with [`Self::from_pkcs8_der()`], [`Self::from_sec1_der()`] or
+/// [`Self::from_pem()`], or by generating a random key using [`Curve::generate_random_key()`].
 pub struct SigningKey<C: Curve> {
     /// The private key.
     pub private_key: C::PrivateKey,
@@ -33,6 +35,10 @@ impl<C: Curve> SigningKey<C> {
     }
 
     /// Load an ECDSA private key in SEC.1 format.
+    ///
+    /// If the optional `publicKey` is present, it must match the
+    /// private key, else [`crate::KeyFormatError::MismatchedSec1PublicKey`]
+    /// is returned.
     pub fn from_sec1_der(bytes: &[u8]) -> Result<Self, Error> {
         let _ = Entry::new_secret();
         let ecpk = asn1::pkix::EcPrivateKey::from_bytes(bytes).map_err(Error::Asn1Error)?;
@@ -41,9 +47,156 @@ impl<C: Curve> SigningKey<C> {
             return Err(KeyFormatError::UnsupportedSec1Version.into());
         }
 
-        Ok(Self {
-            private_key: C::PrivateKey::from_bytes(ecpk.privateKey.into_octets())?,
-        })
+        let private_key = C::PrivateKey::from_bytes(ecpk.privateKey.into_octets())?;
+
+        if let Some(public_key) = ecpk.publicKey.into_inner() {
+            check_public_key::<C>(&private_key, public_key.into_octets())?;
+        }
+
+        Ok(Self { private_key })
+    }
+
+    /// Load an ECDSA private key in encrypted PKCS#8 format.
+    ///
+    /// This format is the `EncryptedPrivateKeyInfo` defined in
+    /// [RFC5208](https://datatracker.ietf.org/doc/html/rfc5208#section-6).
+    /// It is decrypted using `password`, and the result is decoded as
+    /// for [`Self::from_pkcs8_der()`].
+    ///
+    /// Only PBES2 encryption is supported, with PBKDF2-HMAC-SHA256 and
+    /// AES-128 or AES-256 in CBC or GCM mode.  Otherwise,
+    /// [`crate::KeyFormatError::UnsupportedPkcs8Encryption`] is returned.
+    /// A wrong `password` typically results in [`Error::DecryptFailed`].
+    #[cfg(feature = "pbes2")]
+    pub fn from_encrypted_pkcs8_der(bytes: &[u8], password: &[u8]) -> Result<Self, Error> {
+        let _ = Entry::new_secret();
+        let mut plaintext = [0u8; MAX_PKCS8_LEN];
+        let result =
+            pkcs8::decrypt_pkcs8(bytes, password, &mut plaintext).and_then(Self::from_pkcs8_der);
+        zeroise(&mut plaintext);
+        result
+    }
+
+    /// Encode this private key in PKCS#8 format.
+    ///
+    /// The encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    pub fn to_pkcs8_der<'a>(&self, output: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let _ = Entry::new_secret();
+        let mut sec1 = [0u8; MAX_PKCS8_LEN];
+        let result = self.encode_sec1(&mut sec1).and_then(|sec1| {
+            pkcs8::encode_pkcs8(
+                asn1::pkix::AlgorithmIdentifier {
+                    algorithm: asn1::oid::id_ecPublicKey.clone(),
+                    parameters: Some(asn1::Any::ObjectId(C::oid())),
+                },
+                sec1,
+                output,
+            )
+        });
+        zeroise(&mut sec1);
+        result
+    }
+
+    /// Encode this private key in encrypted PKCS#8 format.
+    ///
+    /// The key is encoded as for [`Self::to_pkcs8_der()`], and then
+    /// encrypted using `password`.  This uses PBES2, with PBKDF2-HMAC-SHA256
+    /// and AES-256-CBC.  The salt and IV are generated using the default
+    /// [`RandomSource`].
+    ///
+    /// The encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    #[cfg(all(feature = "pbes2", feature = "std"))]
+    pub fn to_encrypted_pkcs8_der<'a>(
+        &self,
+        password: &[u8],
+        output: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.to_encrypted_pkcs8_der_with_rng(&mut DefaultRandom, password, output)
+    }
+
+    /// Encode this private key in encrypted PKCS#8 format, using randomness
+    /// from `rng`.
+    ///
+    /// See [`Self::to_encrypted_pkcs8_der()`] for details of the other arguments.
+    #[cfg(feature = "pbes2")]
+    pub fn to_encrypted_pkcs8_der_with_rng<'a>(
+        &self,
+        rng: &mut dyn RandomSource,
+        password: &[u8],
+        output: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        let _ = Entry::new_secret();
+        let mut plaintext = [0u8; MAX_PKCS8_LEN];
+        let result = self
+            .to_pkcs8_der(&mut plaintext)
+            .map(|pkcs8| pkcs8.len())
+            .and_then(|len| pkcs8::encrypt_pkcs8(rng, password, &mut plaintext, len, output));
+        zeroise(&mut plaintext);
+        result
+    }
+
+    /// Load an ECDSA private key in PEM format.
+    ///
+    /// The first PEM section in `pem` is used, skipping any `EC PARAMETERS`
+    /// sections (as written by `openssl ecparam -genkey`).  A `PRIVATE KEY`
+    /// section is decoded as for [`Self::from_pkcs8_der()`], and an
+    /// `EC PRIVATE KEY` section as for [`Self::from_sec1_der()`].  Other
+    /// labels result in [`crate::KeyFormatError::MismatchedPemLabel`].
+    pub fn from_pem(pem: &str) -> Result<Self, Error> {
+        let _ = Entry::new_secret();
+        let section = pem::Section::find_skipping(pem, &["EC PARAMETERS"])?;
+        let mut der = [0u8; MAX_PKCS8_LEN];
+        let result = match section.label {
+            "PRIVATE KEY" => section.decode(&mut der).and_then(Self::from_pkcs8_der),
+            "EC PRIVATE KEY" => section.decode(&mut der).and_then(Self::from_sec1_der),
+            _ => Err(KeyFormatError::MismatchedPemLabel.into()),
+        };
+        zeroise(&mut der);
+        result
+    }
+
+    /// Encode this private key in PEM format, with the label `PRIVATE KEY`.
+    ///
+    /// The key is encoded as for [`Self::to_pkcs8_der()`].  The PEM
+    /// encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    pub fn to_pem<'a>(&self, output: &'a mut [u8]) -> Result<&'a str, Error> {
+        let _ = Entry::new_secret();
+        let mut der = [0u8; MAX_PKCS8_LEN];
+        let result = self
+            .to_pkcs8_der(&mut der)
+            .and_then(|der| pem::encode("PRIVATE KEY", der, output));
+        zeroise(&mut der);
+        result
+    }
+
+    /// Encodes this key in SEC.1 format, omitting the (optional) `parameters`.
+    fn encode_sec1<'a>(&self, output: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let mut private_key = [0u8; MAX_SCALAR_LEN];
+        let mut public_key = [0u8; MAX_SCALAR_LEN * 2 + 1];
+        let public_key = self
+            .private_key
+            .public_key_encode_uncompressed(&mut public_key)?;
+
+        let result = self
+            .private_key
+            .encode(&mut private_key)
+            .and_then(|private_key| {
+                asn1::pkix::EcPrivateKey {
+                    version: asn1::pkix::EcPrivateKeyVer::ecPrivkeyVer1,
+                    privateKey: asn1::OctetString::new(private_key),
+                    parameters: asn1::ContextConstructed::new(None),
+                    publicKey: asn1::ContextConstructed::new(Some(asn1::BitString::new(
+                        public_key,
+                    ))),
+                }
+                .encode(&mut asn1::Encoder::new(output))
+                .map_err(Error::Asn1Error)
+            });
+        zeroise(&mut private_key);
+        Ok(&output[..result?])
     }
 
     /// ECDSA signing, returning a fixed-length signature.
@@ -54,14 +207,34 @@ impl<C: Curve> SigningKey<C> {
     /// `signature` is the output buffer; `Error::WrongLength` is returned
     /// if it is not long enough.  The used prefix of this buffer is returned
     /// on success.
-    pub fn sign<'a, H: Hash>(
+    ///
+    /// The signature is randomised using the default [`RandomSource`].
+    #[cfg(feature = "std")]
+    pub fn sign<'a, H: SigningHash>(
+        &self,
+        message: &[&[u8]],
+        signature: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.sign_with_rng::<H>(&mut DefaultRandom, message, signature)
+    }
+
+    /// ECDSA signing, returning a fixed-length signature, using randomness
+    /// from `rng`.
+    ///
+    /// See [`Self::sign()`] for details of the other arguments.
+    ///
+    /// Signatures remain secure if `rng` is predictable (or
+    /// fixed), as randomness is added to a deterministic RFC6979
+    /// signature -- see `rfc6979_sign_with_random()`.
+    pub fn sign_with_rng<'a, H: SigningHash>(
         &self,
+        rng: &mut dyn RandomSource,
         message: &[&[u8]],
         signature: &'a mut [u8],
     ) -> Result<&'a [u8], Error> {
         let _ = Entry::new_secret();
         let mut random = [0u8; 16];
-        SystemRandom.fill(&mut random)?;
+        rng.fill(&mut random)?;
         self.rfc6979_sign_with_random::<H>(message, &random, signature)
     }
 
@@ -69,14 +242,29 @@ impl<C: Curve> SigningKey<C> {
     ///
     /// This calls [`Self::sign()`] and then does a straightforward conversion
     /// from fixed length to ASN.1 -- see that function's documentation for more.
-    pub fn sign_asn1<'a, H: Hash>(
+    #[cfg(feature = "std")]
+    pub fn sign_asn1<'a, H: SigningHash>(
         &self,
         message: &[&[u8]],
         asn1_signature: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.sign_asn1_with_rng::<H>(&mut DefaultRandom, message, asn1_signature)
+    }
+
+    /// ECDSA signing, returning a DER-encoded ASN.1 signature, using randomness
+    /// from `rng`.
+    ///
+    /// This calls [`Self::sign_with_rng()`] and then does a straightforward
+    /// conversion from fixed length to ASN.1.
+    pub fn sign_asn1_with_rng<'a, H: SigningHash>(
+        &self,
+        rng: &mut dyn RandomSource,
+        message: &[&[u8]],
+        asn1_signature: &'a mut [u8],
     ) -> Result<&'a [u8], Error> {
         let _ = Entry::new_secret();
         let mut fixed_sig = [0u8; MAX_SCALAR_LEN * 2];
-        let fixed_sig = self.sign::<H>(message, &mut fixed_sig)?;
+        let fixed_sig = self.sign_with_rng::<H>(rng, message, &mut fixed_sig)?;
 
         Self::fixed_to_asn1(fixed_sig, asn1_signature)
     }
@@ -109,7 +297,7 @@ impl<C: Curve> SigningKey<C> {
     /// RFC6979 allows for this: see section 3.6:
     /// <https://datatracker.ietf.org/doc/html/rfc6979#section-3.6>.  And HMAC_DRBG
     /// also allows for it, it is the `personalization_string` input.
-    fn rfc6979_sign_with_random<'a, H: Hash>(
+    pub(super) fn rfc6979_sign_with_random<'a, H: SigningHash>(
         &self,
         message: &[&[u8]],
         random: &[u8],
@@ -153,6 +341,36 @@ impl<C: Curve> SigningKey<C> {
     }
 }
 
+/// An upper bound on the length of PKCS#8 encodings of supported
+/// private keys, plus space for encryption padding.
+const MAX_PKCS8_LEN: usize = 256;
+
+/// An upper bound on the length of `SubjectPublicKeyInfo` encodings of
+/// supported public keys.
+const MAX_SPKI_LEN: usize = 128;
+
+/// Checks that `encoded` (from a SEC1 `publicKey`) is the public key
+/// for `private_key`.
+///
+/// Compressed encodings are accepted here, though not elsewhere.
+fn check_public_key<C: Curve>(private_key: &C::PrivateKey, encoded: &[u8]) -> Result<(), Error> {
+    let mut expected = [0u8; MAX_SCALAR_LEN * 2 + 1];
+    let expected = private_key.public_key_encode_uncompressed(&mut expected)?;
+    let x = &expected[1..1 + C::Scalar::LEN_BYTES];
+    let y_is_odd = expected[expected.len() - 1] & 1;
+
+    let matches = match encoded.split_first() {
+        Some((0x04, _)) => encoded == expected,
+        Some((&form @ (0x02 | 0x03), encoded_x)) => encoded_x == x && form & 1 == y_is_odd,
+        _ => false,
+    };
+
+    match matches {
+        true => Ok(()),
+        false => Err(KeyFormatError::MismatchedSec1PublicKey.into()),
+    }
+}
+
 /// An ECDSA verification key, on curve `C`.
 pub struct VerifyingKey<C: Curve> {
     /// The public key.
@@ -166,6 +384,73 @@ impl<C: Curve> VerifyingKey<C> {
         C::PublicKey::from_x962_uncompressed(encoded).map(|public_key| Self { public_key })
     }
 
+    /// Create a `VerifyingKey` by decoding an X.509 `SubjectPublicKeyInfo`.
+    ///
+    /// The `algorithm` must be `id-ecPublicKey`, with the curve `C` as
+    /// its parameters.  The point must use the uncompressed encoding.
+    pub fn from_spki_der(bytes: &[u8]) -> Result<Self, Error> {
+        let _ = Entry::new_public();
+        let spki = asn1::pkix::SubjectPublicKeyInfo::from_bytes(bytes).map_err(Error::Asn1Error)?;
+
+        if spki.algorithm.algorithm != asn1::oid::id_ecPublicKey {
+            return Err(KeyFormatError::MismatchedSpkiAlgorithm.into());
+        }
+
+        if spki.algorithm.parameters != Some(asn1::Any::ObjectId(C::oid())) {
+            return Err(KeyFormatError::MismatchedSpkiParameters.into());
+        }
+
+        Self::from_x962_uncompressed(spki.subjectPublicKey.into_octets())
+    }
+
+    /// Create a `VerifyingKey` by decoding a PEM `PUBLIC KEY` section.
+    ///
+    /// The first PEM section in `pem` is used, and decoded as for
+    /// [`Self::from_spki_der()`].  Other labels result in
+    /// [`crate::KeyFormatError::MismatchedPemLabel`].
+    pub fn from_pem(pem: &str) -> Result<Self, Error> {
+        let _ = Entry::new_public();
+        let section = pem::Section::find(pem)?;
+        let mut der = [0u8; MAX_SPKI_LEN];
+        match section.label {
+            "PUBLIC KEY" => Self::from_spki_der(section.decode(&mut der)?),
+            _ => Err(KeyFormatError::MismatchedPemLabel.into()),
+        }
+    }
+
+    /// Encode this public key as an X.509 `SubjectPublicKeyInfo`.
+    ///
+    /// The encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    pub fn to_spki_der<'a>(&self, output: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let _ = Entry::new_public();
+        let mut public_key = [0u8; MAX_SCALAR_LEN * 2 + 1];
+        let public_key = self.public_key.encode_uncompressed(&mut public_key)?;
+
+        let len = asn1::pkix::SubjectPublicKeyInfo {
+            algorithm: asn1::pkix::AlgorithmIdentifier {
+                algorithm: asn1::oid::id_ecPublicKey.clone(),
+                parameters: Some(asn1::Any::ObjectId(C::oid())),
+            },
+            subjectPublicKey: asn1::BitString::new(public_key),
+        }
+        .encode(&mut asn1::Encoder::new(output))
+        .map_err(Error::Asn1Error)?;
+        Ok(&output[..len])
+    }
+
+    /// Encode this public key in PEM format, with the label `PUBLIC KEY`.
+    ///
+    /// The key is encoded as for [`Self::to_spki_der()`].  The PEM
+    /// encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    pub fn to_pem<'a>(&self, output: &'a mut [u8]) -> Result<&'a str, Error> {
+        let _ = Entry::new_public();
+        let mut der = [0u8; MAX_SPKI_LEN];
+        let der = self.to_spki_der(&mut der)?;
+        pem::encode("PUBLIC KEY", der, output)
+    }
+
     /// Verify an ECDSA fixed-length signature.
     ///
     /// The `message` is hashed with `H`.  The message is presented as a sequence of byte
@@ -262,12 +547,20 @@ fn write_positive_int<'a>(buf: &'a mut [u8], value: &[u8]) -> asn1::Integer<'a>
     asn1::Integer::new(&buf[..buf_len])
 }
 
-#[cfg(test)]
+#[cfg(all(
+    test,
+    feature = "p256",
+    feature = "p384",
+    feature = "sha1",
+    feature = "sha256",
+    feature = "sha512"
+))]
 mod tests {
     use super::*;
     use crate::high::curve::Curve;
     use crate::high::{curve, hash};
     use crate::mid::rng::SliceRandomSource;
+    #[cfg(feature = "std")]
     use crate::mid::rng::SystemRandom;
     use crate::test::*;
 
@@ -295,6 +588,206 @@ mod tests {
         );
     }
 
+    #[test]
+    fn sec1_public_key_must_match() {
+        let mut key = include_bytes!("ecdsa/secp256r1.der").to_vec();
+        *key.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            SigningKey::<curve::P256>::from_sec1_der(&key).err(),
+            Some(KeyFormatError::MismatchedSec1PublicKey.into())
+        );
+
+        let mut key = include_bytes!("ecdsa/secp256r1-compressed.der").to_vec();
+        SigningKey::<curve::P256>::from_sec1_der(&key).unwrap();
+        // flip the y parity in the point form
+        key[56] ^= 1;
+        assert_eq!(
+            SigningKey::<curve::P256>::from_sec1_der(&key).err(),
+            Some(KeyFormatError::MismatchedSec1PublicKey.into())
+        );
+    }
+
+    #[test]
+    fn pkcs8_encoding() {
+        let der = include_bytes!("ecdsa/secp256r1.pkcs8.der");
+        let key = SigningKey::<curve::P256>::from_pkcs8_der(der).unwrap();
+        let mut buf = [0u8; 256];
+        assert_eq!(key.to_pkcs8_der(&mut buf).unwrap(), der);
+        assert_eq!(
+            key.to_pkcs8_der(&mut buf[..der.len() - 1]).unwrap_err(),
+            Error::Asn1Error(asn1::Error::UnexpectedEof)
+        );
+
+        let der = include_bytes!("ecdsa/secp384r1.pkcs8.der");
+        let key = SigningKey::<curve::P384>::from_pkcs8_der(der).unwrap();
+        assert_eq!(key.to_pkcs8_der(&mut buf).unwrap(), der);
+    }
+
+    #[cfg(feature = "pbes2")]
+    #[test]
+    fn encrypted_pkcs8() {
+        let mut buf = [0u8; 256];
+
+        // aes-128-cbc, by openssl
+        let key = SigningKey::<curve::P256>::from_encrypted_pkcs8_der(
+            include_bytes!("ecdsa/secp256r1.encrypted-pkcs8.der"),
+            b"hunter2",
+        )
+        .unwrap();
+        assert_eq!(
+            key.to_pkcs8_der(&mut buf).unwrap(),
+            include_bytes!("ecdsa/secp256r1.pkcs8.der")
+        );
+        assert_eq!(
+            SigningKey::<curve::P256>::from_encrypted_pkcs8_der(
+                include_bytes!("ecdsa/secp256r1.encrypted-pkcs8.der"),
+                b"hunter3",
+            )
+            .err(),
+            Some(Error::DecryptFailed)
+        );
+
+        // aes-256-gcm
+        let key = SigningKey::<curve::P384>::from_encrypted_pkcs8_der(
+            include_bytes!("ecdsa/secp384r1-aes256gcm.encrypted-pkcs8.der"),
+            b"hunter2",
+        )
+        .unwrap();
+        assert_eq!(
+            key.to_pkcs8_der(&mut buf).unwrap(),
+            include_bytes!("ecdsa/secp384r1.pkcs8.der")
+        );
+        assert_eq!(
+            SigningKey::<curve::P384>::from_encrypted_pkcs8_der(
+                include_bytes!("ecdsa/secp384r1-aes256gcm.encrypted-pkcs8.der"),
+                b"hunter3",
+            )
+            .err(),
+            Some(Error::DecryptFailed)
+        );
+
+        // hmacWithSHA1 is not supported
+        assert_eq!(
+            SigningKey::<curve::P256>::from_encrypted_pkcs8_der(
+                include_bytes!("ecdsa/secp256r1-sha1.encrypted-pkcs8.der"),
+                b"hunter2",
+            )
+            .err(),
+            Some(KeyFormatError::UnsupportedPkcs8Encryption.into())
+        );
+
+        // unencrypted keys are not accepted
+        assert!(SigningKey::<curve::P256>::from_encrypted_pkcs8_der(
+            include_bytes!("ecdsa/secp256r1.pkcs8.der"),
+            b"hunter2",
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "pbes2")]
+    #[test]
+    fn encrypted_pkcs8_roundtrip() {
+        let key =
+            SigningKey::<curve::P256>::from_pkcs8_der(include_bytes!("ecdsa/secp256r1.pkcs8.der"))
+                .unwrap();
+
+        let mut encrypted = [0u8; 512];
+        let encrypted = key
+            .to_encrypted_pkcs8_der_with_rng(
+                &mut SliceRandomSource(&[0x11; 32]),
+                b"password",
+                &mut encrypted,
+            )
+            .unwrap();
+
+        let decrypted =
+            SigningKey::<curve::P256>::from_encrypted_pkcs8_der(encrypted, b"password").unwrap();
+        let mut buf = [0u8; 256];
+        assert_eq!(
+            decrypted.to_pkcs8_der(&mut buf).unwrap(),
+            include_bytes!("ecdsa/secp256r1.pkcs8.der")
+        );
+    }
+
+    #[test]
+    fn pem_encoding() {
+        let mut buf = [0u8; 512];
+
+        let pem = include_str!("ecdsa/secp256r1.pkcs8.pem");
+        let key = SigningKey::<curve::P256>::from_pem(pem).unwrap();
+        assert_eq!(key.to_pem(&mut buf).unwrap(), pem);
+
+        let key = SigningKey::<curve::P384>::from_pem(include_str!("ecdsa/secp384r1.pem")).unwrap();
+        let mut expect = [0u8; 256];
+        assert_eq!(
+            key.to_pkcs8_der(&mut buf).unwrap(),
+            SigningKey::<curve::P384>::from_sec1_der(include_bytes!("ecdsa/secp384r1.der"))
+                .unwrap()
+                .to_pkcs8_der(&mut expect)
+                .unwrap()
+        );
+
+        // `openssl ecparam -genkey` output has a leading `EC PARAMETERS` section
+        let pem = include_str!("ecdsa/secp256r1-ecparam.pem");
+        let p256_key = SigningKey::<curve::P256>::from_pem(pem).unwrap();
+        let (_, sec1) = pem.split_once("-----END EC PARAMETERS-----\n").unwrap();
+        assert_eq!(
+            p256_key.to_pkcs8_der(&mut buf).unwrap(),
+            SigningKey::<curve::P256>::from_pem(sec1)
+                .unwrap()
+                .to_pkcs8_der(&mut expect)
+                .unwrap()
+        );
+
+        let pem = include_str!("ecdsa/secp384r1.spki.pem");
+        let public_key = VerifyingKey::<curve::P384>::from_pem(pem).unwrap();
+        assert_eq!(public_key.to_pem(&mut buf).unwrap(), pem);
+        let mut encoded = [0u8; 97];
+        let encoded = key
+            .private_key
+            .public_key_encode_uncompressed(&mut encoded)
+            .unwrap();
+        let mut spki = [0u8; 128];
+        assert_eq!(
+            VerifyingKey::<curve::P384>::from_x962_uncompressed(encoded)
+                .unwrap()
+                .to_spki_der(&mut spki)
+                .unwrap(),
+            public_key.to_spki_der(&mut buf).unwrap()
+        );
+
+        let der = include_bytes!("asn1/testdata/spki-ec-nistp256.bin");
+        assert_eq!(
+            VerifyingKey::<curve::P256>::from_spki_der(der)
+                .unwrap()
+                .to_spki_der(&mut buf)
+                .unwrap(),
+            der
+        );
+
+        // wrong curves
+        assert!(SigningKey::<curve::P256>::from_pem(include_str!("ecdsa/secp384r1.pem")).is_err());
+        assert_eq!(
+            VerifyingKey::<curve::P256>::from_pem(pem).err(),
+            Some(KeyFormatError::MismatchedSpkiParameters.into())
+        );
+
+        // wrong labels
+        assert_eq!(
+            SigningKey::<curve::P384>::from_pem(pem).err(),
+            Some(KeyFormatError::MismatchedPemLabel.into())
+        );
+        assert_eq!(
+            VerifyingKey::<curve::P384>::from_pem(include_str!("ecdsa/secp384r1.pem")).err(),
+            Some(KeyFormatError::MismatchedPemLabel.into())
+        );
+        assert_eq!(
+            SigningKey::<curve::P384>::from_pem("").err(),
+            Some(KeyFormatError::InvalidPem.into())
+        );
+    }
+
+    #[cfg(feature = "std")]
     #[test]
     fn smoke_test_ecdsa_sign() {
         let k = curve::P256::generate_random_key(&mut SystemRandom).unwrap();
@@ -317,23 +810,89 @@ mod tests {
         let mut buffer = [0u8; 256];
         let message = [&b"hello"[..], &b"world"[..]];
 
-        let signature = sk.sign::<hash::Sha256>(&message, &mut buffer).unwrap();
-        vk.verify::<hash::Sha256>(&message, signature).unwrap();
+        #[cfg(feature = "std")]
+        {
+            let signature = sk.sign::<hash::Sha224>(&message, &mut buffer).unwrap();
+            vk.verify::<hash::Sha224>(&message, signature).unwrap();
+
+            let signature = sk.sign::<hash::Sha256>(&message, &mut buffer).unwrap();
+            vk.verify::<hash::Sha256>(&message, signature).unwrap();
 
-        let signature = sk.sign::<hash::Sha384>(&message, &mut buffer).unwrap();
-        vk.verify::<hash::Sha384>(&message, signature).unwrap();
+            let signature = sk.sign::<hash::Sha384>(&message, &mut buffer).unwrap();
+            vk.verify::<hash::Sha384>(&message, signature).unwrap();
 
-        let signature = sk.sign::<hash::Sha512>(&message, &mut buffer).unwrap();
-        vk.verify::<hash::Sha512>(&message, signature).unwrap();
+            let signature = sk.sign::<hash::Sha512>(&message, &mut buffer).unwrap();
+            vk.verify::<hash::Sha512>(&message, signature).unwrap();
 
-        let signature = sk.sign_asn1::<hash::Sha256>(&message, &mut buffer).unwrap();
-        vk.verify_asn1::<hash::Sha256>(&message, signature).unwrap();
+            let signature = sk.sign::<hash::Sha512_256>(&message, &mut buffer).unwrap();
+            vk.verify::<hash::Sha512_256>(&message, signature).unwrap();
 
-        let signature = sk.sign_asn1::<hash::Sha384>(&message, &mut buffer).unwrap();
+            let signature = sk.sign_asn1::<hash::Sha256>(&message, &mut buffer).unwrap();
+            vk.verify_asn1::<hash::Sha256>(&message, signature).unwrap();
+
+            let signature = sk.sign_asn1::<hash::Sha384>(&message, &mut buffer).unwrap();
+            vk.verify_asn1::<hash::Sha384>(&message, signature).unwrap();
+
+            let signature = sk.sign_asn1::<hash::Sha512>(&message, &mut buffer).unwrap();
+            vk.verify_asn1::<hash::Sha512>(&message, signature).unwrap();
+        }
+
+        // signatures with a given rng are deterministic
+        let mut other_buffer = [0u8; 256];
+        let signature = sk
+            .sign_with_rng::<hash::Sha256>(&mut SliceRandomSource(&[1; 16]), &message, &mut buffer)
+            .unwrap();
+        let other = sk
+            .sign_with_rng::<hash::Sha256>(
+                &mut SliceRandomSource(&[1; 16]),
+                &message,
+                &mut other_buffer,
+            )
+            .unwrap();
+        assert_eq!(signature, other);
+        vk.verify::<hash::Sha256>(&message, signature).unwrap();
+
+        let other = sk
+            .sign_with_rng::<hash::Sha256>(
+                &mut SliceRandomSource(&[2; 16]),
+                &message,
+                &mut other_buffer,
+            )
+            .unwrap();
+        assert_ne!(signature, other);
+
+        let signature = sk
+            .sign_asn1_with_rng::<hash::Sha384>(
+                &mut SliceRandomSource(&[1; 16]),
+                &message,
+                &mut buffer,
+            )
+            .unwrap();
         vk.verify_asn1::<hash::Sha384>(&message, signature).unwrap();
 
-        let signature = sk.sign_asn1::<hash::Sha512>(&message, &mut buffer).unwrap();
-        vk.verify_asn1::<hash::Sha512>(&message, signature).unwrap();
+        // and rng failures are reported
+        assert_eq!(
+            sk.sign_with_rng::<hash::Sha256>(&mut SliceRandomSource(&[]), &message, &mut buffer)
+                .unwrap_err(),
+            Error::RngFailed
+        );
+    }
+
+    #[test]
+    fn verify_sha1() {
+        let sk =
+            SigningKey::<curve::P256>::from_pkcs8_der(include_bytes!("ecdsa/secp256r1.pkcs8.der"))
+                .unwrap();
+        let mut public_key = [0u8; 65];
+        let public_key = sk
+            .private_key
+            .public_key_encode_uncompressed(&mut public_key)
+            .unwrap();
+        let vk = VerifyingKey::<curve::P256>::from_x962_uncompressed(public_key).unwrap();
+        vk.verify_asn1::<hash::Sha1>(&[b"hello"], b"\x30\x44\x02\x20\x24\x97\xdd\x8b\x7d\x38\xad\x08\x49\x9b\x8e\xaa\x3f\x32\x96\x7d\xd6\x8d\x23\x1e\x66\xdf\x88\xbe\xc3\x06\x9d\x7d\xa5\x46\xf1\xf9\x02\x20\x7e\x91\xf4\x45\x66\x10\xa2\xd0\x03\xdd\x44\x8d\x5e\xaf\x27\xa2\x19\x7b\xd6\xad\x53\x7c\x52\xa3\x4a\xd5\x51\x19\xe8\x6d\x9e\xa5")
+            .unwrap();
+        vk.verify_asn1::<hash::Sha1>(&[b"jello"], b"\x30\x44\x02\x20\x24\x97\xdd\x8b\x7d\x38\xad\x08\x49\x9b\x8e\xaa\x3f\x32\x96\x7d\xd6\x8d\x23\x1e\x66\xdf\x88\xbe\xc3\x06\x9d\x7d\xa5\x46\xf1\xf9\x02\x20\x7e\x91\xf4\x45\x66\x10\xa2\xd0\x03\xdd\x44\x8d\x5e\xaf\x27\xa2\x19\x7b\xd6\xad\x53\x7c\x52\xa3\x4a\xd5\x51\x19\xe8\x6d\x9e\xa5")
+            .unwrap_err();
     }
 
     #[test]
@@ -370,7 +929,7 @@ mod tests {
 
         let mut asn1_sig = [0u8; 128];
         let asn1_sig = SigningKey::<curve::P256>::fixed_to_asn1(&signature, &mut asn1_sig).unwrap();
-        v.verify_asn1::<hash::Sha256>(&[b"sample"], &asn1_sig)
+        v.verify_asn1::<hash::Sha256>(&[b"sample"], asn1_sig)
             .unwrap();
 
         k.rfc6979_sign_with_random::<hash::Sha256>(&[b"test"], &[], &mut signature)
@@ -389,8 +948,7 @@ mod tests {
 
         let mut asn1_sig = [0u8; 128];
         let asn1_sig = SigningKey::<curve::P256>::fixed_to_asn1(&signature, &mut asn1_sig).unwrap();
-        v.verify_asn1::<hash::Sha256>(&[b"test"], &asn1_sig)
-            .unwrap();
+        v.verify_asn1::<hash::Sha256>(&[b"test"], asn1_sig).unwrap();
 
         k.rfc6979_sign_with_random::<hash::Sha512>(&[b"sample"], &[], &mut signature)
             .unwrap();
@@ -408,7 +966,7 @@ mod tests {
 
         let mut asn1_sig = [0u8; 128];
         let asn1_sig = SigningKey::<curve::P256>::fixed_to_asn1(&signature, &mut asn1_sig).unwrap();
-        v.verify_asn1::<hash::Sha512>(&[b"sample"], &asn1_sig)
+        v.verify_asn1::<hash::Sha512>(&[b"sample"], asn1_sig)
             .unwrap();
 
         k.rfc6979_sign_with_random::<hash::Sha512>(&[b"test"], &[], &mut signature)
@@ -427,13 +985,26 @@ mod tests {
 
         let mut asn1_sig = [0u8; 128];
         let asn1_sig = SigningKey::<curve::P256>::fixed_to_asn1(&signature, &mut asn1_sig).unwrap();
-        v.verify_asn1::<hash::Sha512>(&[b"test"], &asn1_sig)
+        v.verify_asn1::<hash::Sha512>(&[b"test"], asn1_sig).unwrap();
+
+        k.rfc6979_sign_with_random::<hash::Sha224>(&[b"sample"], &[], &mut signature)
             .unwrap();
+        assert_eq!(
+            signature,
+            [
+                0x53, 0xb2, 0xff, 0xf5, 0xd1, 0x75, 0x2b, 0x2c, 0x68, 0x9d, 0xf2, 0x57, 0xc0, 0x4c,
+                0x40, 0xa5, 0x87, 0xfa, 0xba, 0xbb, 0x3f, 0x6f, 0xc2, 0x70, 0x2f, 0x13, 0x43, 0xaf,
+                0x7c, 0xa9, 0xaa, 0x3f, 0xb9, 0xaf, 0xb6, 0x4f, 0xdc, 0x03, 0xdc, 0x1a, 0x13, 0x1c,
+                0x7d, 0x23, 0x86, 0xd1, 0x1e, 0x34, 0x9f, 0x07, 0x0a, 0xa4, 0x32, 0xa4, 0xac, 0xc9,
+                0x18, 0xbe, 0xa9, 0x88, 0xbf, 0x75, 0xc7, 0x4c,
+            ]
+        );
+        v.verify::<hash::Sha224>(&[b"sample"], &signature).unwrap();
 
         // This is an extra test vector from
         // <https://github.com/C2SP/CCTV/tree/main/RFC6979>
         // that exercises the rejection sampling in
-        // `p256::PrivateKey::generate()`
+        // `p256::PrivateKey::new_random_with_rng()`
 
         k.rfc6979_sign_with_random::<hash::Sha256>(&[b"wv[vnX"], &[], &mut signature)
             .unwrap();
@@ -476,7 +1047,7 @@ mod tests {
                     "S" => self.s = value.bytes(),
                     "Result" => {
                         match self.param.as_ref() {
-                            "P-256,SHA-256" | "P-256,SHA-384" | "P-256,SHA-512" => {
+                            "P-256,SHA-1" | "P-256,SHA-256" | "P-256,SHA-384" | "P-256,SHA-512" => {
                                 pad(&mut self.px, 32);
                                 pad(&mut self.py, 32);
                                 pad(&mut self.r, 32);
@@ -495,6 +1066,7 @@ mod tests {
                                         .unwrap();
 
                                 let result = match self.param.as_ref() {
+                                    "P-256,SHA-1" => vkey.verify::<hash::Sha1>(&[&self.msg], &sig),
                                     "P-256,SHA-256" => {
                                         vkey.verify::<hash::Sha256>(&[&self.msg], &sig)
                                     }
@@ -518,7 +1090,7 @@ mod tests {
                                 };
                                 println!("PASS: {}", value.str());
                             }
-                            "P-384,SHA-256" | "P-384,SHA-384" | "P-384,SHA-512" => {
+                            "P-384,SHA-1" | "P-384,SHA-256" | "P-384,SHA-384" | "P-384,SHA-512" => {
                                 pad(&mut self.px, 48);
                                 pad(&mut self.py, 48);
                                 pad(&mut self.r, 48);
@@ -537,6 +1109,7 @@ mod tests {
                                         .unwrap();
 
                                 let result = match self.param.as_ref() {
+                                    "P-384,SHA-1" => vkey.verify::<hash::Sha1>(&[&self.msg], &sig),
                                     "P-384,SHA-256" => {
                                         vkey.verify::<hash::Sha256>(&[&self.msg], &sig)
                                     }
@@ -2,8 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
 use crate::low::ct_equal;
-use crate::mid::sha2::{Sha256Context, Sha384Context, Sha512Context};
+#[cfg(feature = "blake2")]
+use crate::mid::blake2::{Blake2bContext, Blake2sContext};
+#[cfg(feature = "blake3")]
+use crate::mid::blake3::Blake3Context;
+#[cfg(feature = "sha1")]
+use crate::mid::sha1::Sha1Context;
+#[cfg(feature = "sha256")]
+use crate::mid::sha2::{Sha224Context, Sha256Context};
+#[cfg(feature = "sha512")]
+use crate::mid::sha2::{Sha384Context, Sha512Context, Sha512_256Context};
+#[cfg(feature = "sha3")]
+use crate::mid::sha3::{Sha3_224Context, Sha3_256Context, Sha3_384Context, Sha3_512Context};
 
+use crate::Error;
 use core::ops::{Deref, DerefMut};
 
 /// Output from a hash function.
@@ -11,12 +23,32 @@ use core::ops::{Deref, DerefMut};
 /// This has one variant per supported hash function.
 #[derive(Clone, Debug)]
 pub enum HashOutput {
+    /// Output from SHA-1
+    Sha1([u8; 20]),
+    /// Output from SHA224
+    Sha224([u8; 28]),
     /// Output from SHA256
     Sha256([u8; 32]),
     /// Output from SHA384
     Sha384([u8; 48]),
     /// Output from SHA512
     Sha512([u8; 64]),
+    /// Output from SHA512/256
+    Sha512_256([u8; 32]),
+    /// Output from SHA3-224
+    Sha3_224([u8; 28]),
+    /// Output from SHA3-256
+    Sha3_256([u8; 32]),
+    /// Output from SHA3-384
+    Sha3_384([u8; 48]),
+    /// Output from SHA3-512
+    Sha3_512([u8; 64]),
+    /// Output from BLAKE2b, of any length
+    Blake2b(Blake2Output<64>),
+    /// Output from BLAKE2s, of any length
+    Blake2s(Blake2Output<32>),
+    /// Output from BLAKE3
+    Blake3([u8; 32]),
 }
 
 impl HashOutput {
@@ -43,9 +75,19 @@ impl HashOutput {
 impl PartialEq for HashOutput {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            (Self::Sha1(s), Self::Sha1(o)) => ct_equal(s, o),
+            (Self::Sha224(s), Self::Sha224(o)) => ct_equal(s, o),
             (Self::Sha256(s), Self::Sha256(o)) => ct_equal(s, o),
             (Self::Sha384(s), Self::Sha384(o)) => ct_equal(s, o),
             (Self::Sha512(s), Self::Sha512(o)) => ct_equal(s, o),
+            (Self::Sha512_256(s), Self::Sha512_256(o)) => ct_equal(s, o),
+            (Self::Sha3_224(s), Self::Sha3_224(o)) => ct_equal(s, o),
+            (Self::Sha3_256(s), Self::Sha3_256(o)) => ct_equal(s, o),
+            (Self::Sha3_384(s), Self::Sha3_384(o)) => ct_equal(s, o),
+            (Self::Sha3_512(s), Self::Sha3_512(o)) => ct_equal(s, o),
+            (Self::Blake2b(s), Self::Blake2b(o)) => ct_equal(s.as_ref(), o.as_ref()),
+            (Self::Blake2s(s), Self::Blake2s(o)) => ct_equal(s.as_ref(), o.as_ref()),
+            (Self::Blake3(s), Self::Blake3(o)) => ct_equal(s, o),
             _ => false,
         }
     }
@@ -54,9 +96,19 @@ impl PartialEq for HashOutput {
 impl AsRef<[u8]> for HashOutput {
     fn as_ref(&self) -> &[u8] {
         match self {
+            Self::Sha1(v) => v,
+            Self::Sha224(v) => v,
             Self::Sha256(v) => v,
             Self::Sha384(v) => v,
             Self::Sha512(v) => v,
+            Self::Sha512_256(v) => v,
+            Self::Sha3_224(v) => v,
+            Self::Sha3_256(v) => v,
+            Self::Sha3_384(v) => v,
+            Self::Sha3_512(v) => v,
+            Self::Blake2b(v) => v.as_ref(),
+            Self::Blake2s(v) => v.as_ref(),
+            Self::Blake3(v) => v,
         }
     }
 }
@@ -64,25 +116,87 @@ impl AsRef<[u8]> for HashOutput {
 impl AsMut<[u8]> for HashOutput {
     fn as_mut(&mut self) -> &mut [u8] {
         match self {
+            Self::Sha1(v) => v,
+            Self::Sha224(v) => v,
             Self::Sha256(v) => v,
             Self::Sha384(v) => v,
             Self::Sha512(v) => v,
+            Self::Sha512_256(v) => v,
+            Self::Sha3_224(v) => v,
+            Self::Sha3_256(v) => v,
+            Self::Sha3_384(v) => v,
+            Self::Sha3_512(v) => v,
+            Self::Blake2b(v) => v.as_mut(),
+            Self::Blake2s(v) => v.as_mut(),
+            Self::Blake3(v) => v,
         }
     }
 }
 
+/// Output from BLAKE2, whose length is chosen at runtime.
+///
+/// `N` is the maximum output length, in bytes.
+#[derive(Clone, Debug)]
+pub struct Blake2Output<const N: usize> {
+    output: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Blake2Output<N> {
+    /// Makes a `Blake2Output` containing a copy of `output`.
+    ///
+    /// `output` must be between 1 and `N` bytes in length, inclusive.
+    pub fn new(output: &[u8]) -> Result<Self, Error> {
+        if output.is_empty() || output.len() > N {
+            return Err(Error::WrongLength);
+        }
+
+        let mut r = Self::zeroed(output.len());
+        r.output[..output.len()].copy_from_slice(output);
+        Ok(r)
+    }
+
+    fn zeroed(len: usize) -> Self {
+        debug_assert!(len != 0 && len <= N);
+        Self {
+            output: [0u8; N],
+            len,
+        }
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for Blake2Output<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.output[..self.len]
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for Blake2Output<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.output[..self.len]
+    }
+}
+
 /// One block of hash function input.
 #[derive(Copy, Clone)]
 pub struct HashBlock {
-    buf: [u8; 128],
+    buf: [u8; 144],
     len: usize,
 }
 
 impl HashBlock {
     /// Creates a new `HashBlock`, containing `len` zeroed bytes.
+    #[cfg(any(
+        feature = "blake2",
+        feature = "blake3",
+        feature = "sha1",
+        feature = "sha256",
+        feature = "sha3",
+        feature = "sha512"
+    ))]
     fn new(len: usize) -> Self {
         Self {
-            buf: [0u8; 128],
+            buf: [0u8; 144],
             len,
         }
     }
@@ -111,6 +225,15 @@ pub trait Hash {
     /// Create a new hash context.
     fn new() -> Self::Context;
 
+    /// Create a new hash context, for hashing secret data.
+    ///
+    /// This is used by HMAC.  The default implementation is [`Hash::new()`];
+    /// [`Sha1`] overrides it to disable collision detection, whose timing
+    /// depends on the input.
+    fn new_for_secret_input() -> Self::Context {
+        Self::new()
+    }
+
     /// Hash the given bytes (one-shot style) and return the output.
     fn hash(bytes: &[u8]) -> HashOutput;
 
@@ -121,6 +244,19 @@ pub trait Hash {
     fn zeroed_output() -> HashOutput;
 }
 
+/// A marker trait for hash functions which may be used to make
+/// new signatures.
+///
+/// This is implemented for every [`Hash`] except [`Sha1`], which is
+/// only available for verifying existing signatures.
+///
+/// This trait is sealed: it cannot be implemented outside this crate.
+pub trait SigningHash: Hash + private::Sealed {}
+
+mod private {
+    pub trait Sealed {}
+}
+
 /// A generic trait over supported hash function contexts.
 ///
 /// These may be cloned: the semantics of that forks the
@@ -136,12 +272,111 @@ pub trait HashContext: Clone {
     fn finish(self) -> HashOutput;
 }
 
+/// This is SHA-1.
+///
+/// SHA-1 is standardized in [FIPS180](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+///
+/// SHA-1 is not collision-resistant, so this does not implement
+/// [`SigningHash`]: it may only be used for verifying signatures,
+/// and for HMAC.  Inputs containing known collision attacks are
+/// detected, and hash to a different value.  See [`crate::hashing::sha1`].
+#[cfg(feature = "sha1")]
+#[derive(Clone)]
+pub struct Sha1;
+
+#[cfg(feature = "sha1")]
+impl Hash for Sha1 {
+    type Context = Sha1Context;
+
+    fn new() -> Self::Context {
+        Sha1Context::new()
+    }
+
+    fn new_for_secret_input() -> Self::Context {
+        Sha1Context::new_without_collision_detection()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashOutput::Sha1(ctx.finish())
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Sha1Context::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Sha1([0u8; 20])
+    }
+}
+
+#[cfg(feature = "sha1")]
+impl HashContext for Sha1Context {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        HashOutput::Sha1(self.finish())
+    }
+}
+
+/// This is SHA224.
+///
+/// SHA224 is standardized in [FIPS180](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+#[cfg(feature = "sha256")]
+#[derive(Clone)]
+pub struct Sha224;
+
+#[cfg(feature = "sha256")]
+impl Hash for Sha224 {
+    type Context = Sha224Context;
+
+    fn new() -> Self::Context {
+        Sha224Context::new()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashOutput::Sha224(ctx.finish())
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Sha256Context::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Sha224([0u8; 28])
+    }
+}
+
+#[cfg(feature = "sha256")]
+impl SigningHash for Sha224 {}
+
+#[cfg(feature = "sha256")]
+impl private::Sealed for Sha224 {}
+
+#[cfg(feature = "sha256")]
+impl HashContext for Sha224Context {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        HashOutput::Sha224(self.finish())
+    }
+}
+
 /// This is SHA256.
 ///
 /// SHA256 is standardized in [FIPS180](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+#[cfg(feature = "sha256")]
 #[derive(Clone)]
 pub struct Sha256;
 
+#[cfg(feature = "sha256")]
 impl Hash for Sha256 {
     type Context = Sha256Context;
 
@@ -164,6 +399,13 @@ impl Hash for Sha256 {
     }
 }
 
+#[cfg(feature = "sha256")]
+impl SigningHash for Sha256 {}
+
+#[cfg(feature = "sha256")]
+impl private::Sealed for Sha256 {}
+
+#[cfg(feature = "sha256")]
 impl HashContext for Sha256Context {
     fn update(&mut self, bytes: &[u8]) {
         self.update(bytes)
@@ -177,9 +419,11 @@ impl HashContext for Sha256Context {
 /// This is SHA384.
 ///
 /// SHA384 is standardized in [FIPS180](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+#[cfg(feature = "sha512")]
 #[derive(Clone)]
 pub struct Sha384;
 
+#[cfg(feature = "sha512")]
 impl Hash for Sha384 {
     type Context = Sha384Context;
 
@@ -202,6 +446,13 @@ impl Hash for Sha384 {
     }
 }
 
+#[cfg(feature = "sha512")]
+impl SigningHash for Sha384 {}
+
+#[cfg(feature = "sha512")]
+impl private::Sealed for Sha384 {}
+
+#[cfg(feature = "sha512")]
 impl HashContext for Sha384Context {
     fn update(&mut self, bytes: &[u8]) {
         self.update(bytes)
@@ -215,8 +466,11 @@ impl HashContext for Sha384Context {
 /// This is SHA512.
 ///
 /// SHA512 is standardized in [FIPS180](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+#[cfg(feature = "sha512")]
+#[derive(Clone)]
 pub struct Sha512;
 
+#[cfg(feature = "sha512")]
 impl Hash for Sha512 {
     type Context = Sha512Context;
 
@@ -239,6 +493,13 @@ impl Hash for Sha512 {
     }
 }
 
+#[cfg(feature = "sha512")]
+impl SigningHash for Sha512 {}
+
+#[cfg(feature = "sha512")]
+impl private::Sealed for Sha512 {}
+
+#[cfg(feature = "sha512")]
 impl HashContext for Sha512Context {
     fn update(&mut self, bytes: &[u8]) {
         self.update(bytes)
@@ -249,7 +510,395 @@ impl HashContext for Sha512Context {
     }
 }
 
-#[cfg(test)]
+/// This is SHA512/256.
+///
+/// SHA512/256 is standardized in [FIPS180](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+#[cfg(feature = "sha512")]
+#[derive(Clone)]
+pub struct Sha512_256;
+
+#[cfg(feature = "sha512")]
+impl Hash for Sha512_256 {
+    type Context = Sha512_256Context;
+
+    fn new() -> Self::Context {
+        Sha512_256Context::new()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashOutput::Sha512_256(ctx.finish())
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Sha512Context::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Sha512_256([0u8; 32])
+    }
+}
+
+#[cfg(feature = "sha512")]
+impl SigningHash for Sha512_256 {}
+
+#[cfg(feature = "sha512")]
+impl private::Sealed for Sha512_256 {}
+
+#[cfg(feature = "sha512")]
+impl HashContext for Sha512_256Context {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        HashOutput::Sha512_256(self.finish())
+    }
+}
+
+/// This is SHA3-224.
+///
+/// SHA3-224 is standardized in [FIPS202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+#[cfg(feature = "sha3")]
+#[derive(Clone)]
+pub struct Sha3_224;
+
+#[cfg(feature = "sha3")]
+impl Hash for Sha3_224 {
+    type Context = Sha3_224Context;
+
+    fn new() -> Self::Context {
+        Sha3_224Context::new()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashOutput::Sha3_224(ctx.finish())
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Sha3_224Context::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Sha3_224([0u8; 28])
+    }
+}
+
+#[cfg(feature = "sha3")]
+impl SigningHash for Sha3_224 {}
+
+#[cfg(feature = "sha3")]
+impl private::Sealed for Sha3_224 {}
+
+#[cfg(feature = "sha3")]
+impl HashContext for Sha3_224Context {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        HashOutput::Sha3_224(self.finish())
+    }
+}
+
+/// This is SHA3-256.
+///
+/// SHA3-256 is standardized in [FIPS202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+#[cfg(feature = "sha3")]
+#[derive(Clone)]
+pub struct Sha3_256;
+
+#[cfg(feature = "sha3")]
+impl Hash for Sha3_256 {
+    type Context = Sha3_256Context;
+
+    fn new() -> Self::Context {
+        Sha3_256Context::new()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashOutput::Sha3_256(ctx.finish())
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Sha3_256Context::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Sha3_256([0u8; 32])
+    }
+}
+
+#[cfg(feature = "sha3")]
+impl SigningHash for Sha3_256 {}
+
+#[cfg(feature = "sha3")]
+impl private::Sealed for Sha3_256 {}
+
+#[cfg(feature = "sha3")]
+impl HashContext for Sha3_256Context {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        HashOutput::Sha3_256(self.finish())
+    }
+}
+
+/// This is SHA3-384.
+///
+/// SHA3-384 is standardized in [FIPS202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+#[cfg(feature = "sha3")]
+#[derive(Clone)]
+pub struct Sha3_384;
+
+#[cfg(feature = "sha3")]
+impl Hash for Sha3_384 {
+    type Context = Sha3_384Context;
+
+    fn new() -> Self::Context {
+        Sha3_384Context::new()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashOutput::Sha3_384(ctx.finish())
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Sha3_384Context::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Sha3_384([0u8; 48])
+    }
+}
+
+#[cfg(feature = "sha3")]
+impl SigningHash for Sha3_384 {}
+
+#[cfg(feature = "sha3")]
+impl private::Sealed for Sha3_384 {}
+
+#[cfg(feature = "sha3")]
+impl HashContext for Sha3_384Context {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        HashOutput::Sha3_384(self.finish())
+    }
+}
+
+/// This is SHA3-512.
+///
+/// SHA3-512 is standardized in [FIPS202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+#[cfg(feature = "sha3")]
+#[derive(Clone)]
+pub struct Sha3_512;
+
+#[cfg(feature = "sha3")]
+impl Hash for Sha3_512 {
+    type Context = Sha3_512Context;
+
+    fn new() -> Self::Context {
+        Sha3_512Context::new()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashOutput::Sha3_512(ctx.finish())
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Sha3_512Context::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Sha3_512([0u8; 64])
+    }
+}
+
+#[cfg(feature = "sha3")]
+impl SigningHash for Sha3_512 {}
+
+#[cfg(feature = "sha3")]
+impl private::Sealed for Sha3_512 {}
+
+#[cfg(feature = "sha3")]
+impl HashContext for Sha3_512Context {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        HashOutput::Sha3_512(self.finish())
+    }
+}
+
+/// This is BLAKE2b-512: BLAKE2b with a 64-byte output.
+///
+/// BLAKE2b is specified in [RFC7693](https://www.rfc-editor.org/rfc/rfc7693).
+///
+/// [`HashContext::finish()`] on a [`Blake2bContext`] gives an output of the
+/// length that context was created with (see [`Blake2bContext::new_keyed()`]).
+#[cfg(feature = "blake2")]
+#[derive(Clone)]
+pub struct Blake2b512;
+
+#[cfg(feature = "blake2")]
+impl Hash for Blake2b512 {
+    type Context = Blake2bContext;
+
+    fn new() -> Self::Context {
+        Blake2bContext::new()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashContext::finish(ctx)
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Blake2bContext::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Blake2b(Blake2Output::zeroed(64))
+    }
+}
+
+#[cfg(feature = "blake2")]
+impl SigningHash for Blake2b512 {}
+
+#[cfg(feature = "blake2")]
+impl private::Sealed for Blake2b512 {}
+
+#[cfg(feature = "blake2")]
+impl HashContext for Blake2bContext {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        let mut output = Blake2Output::zeroed(self.output_len());
+        // cannot fail: `output` has the length this context was created with
+        Self::finish(self, output.as_mut()).unwrap();
+        HashOutput::Blake2b(output)
+    }
+}
+
+/// This is BLAKE2s-256: BLAKE2s with a 32-byte output.
+///
+/// BLAKE2s is specified in [RFC7693](https://www.rfc-editor.org/rfc/rfc7693).
+///
+/// [`HashContext::finish()`] on a [`Blake2sContext`] gives an output of the
+/// length that context was created with (see [`Blake2sContext::new_keyed()`]).
+#[cfg(feature = "blake2")]
+#[derive(Clone)]
+pub struct Blake2s256;
+
+#[cfg(feature = "blake2")]
+impl Hash for Blake2s256 {
+    type Context = Blake2sContext;
+
+    fn new() -> Self::Context {
+        Blake2sContext::new()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashContext::finish(ctx)
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Blake2sContext::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Blake2s(Blake2Output::zeroed(32))
+    }
+}
+
+#[cfg(feature = "blake2")]
+impl SigningHash for Blake2s256 {}
+
+#[cfg(feature = "blake2")]
+impl private::Sealed for Blake2s256 {}
+
+#[cfg(feature = "blake2")]
+impl HashContext for Blake2sContext {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        let mut output = Blake2Output::zeroed(self.output_len());
+        // cannot fail: `output` has the length this context was created with
+        Self::finish(self, output.as_mut()).unwrap();
+        HashOutput::Blake2s(output)
+    }
+}
+
+/// This is BLAKE3, with a 32-byte output.
+///
+/// BLAKE3 is specified in [the BLAKE3 paper](https://github.com/BLAKE3-team/BLAKE3-specs/blob/master/blake3.pdf).
+#[cfg(feature = "blake3")]
+#[derive(Clone)]
+pub struct Blake3;
+
+#[cfg(feature = "blake3")]
+impl Hash for Blake3 {
+    type Context = Blake3Context;
+
+    fn new() -> Self::Context {
+        Blake3Context::new()
+    }
+
+    fn hash(bytes: &[u8]) -> HashOutput {
+        let mut ctx = Self::new();
+        ctx.update(bytes);
+        HashOutput::Blake3(ctx.finish())
+    }
+
+    fn zeroed_block() -> HashBlock {
+        HashBlock::new(Blake3Context::BLOCK_SZ)
+    }
+
+    fn zeroed_output() -> HashOutput {
+        HashOutput::Blake3([0u8; 32])
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl SigningHash for Blake3 {}
+
+#[cfg(feature = "blake3")]
+impl private::Sealed for Blake3 {}
+
+#[cfg(feature = "blake3")]
+impl HashContext for Blake3Context {
+    fn update(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(self) -> HashOutput {
+        HashOutput::Blake3(self.finish())
+    }
+}
+
+#[cfg(all(test, feature = "sha1", feature = "sha256", feature = "sha512"))]
 mod tests {
     use super::*;
     use crate::test::*;
@@ -259,14 +908,18 @@ mod tests {
         #[derive(Debug)]
         enum Kind {
             None,
+            Sha1,
+            Sha224,
             Sha256,
             Sha384,
             Sha512,
+            Sha512_256,
         }
 
         #[derive(Debug)]
         struct Cavp {
             kind: Kind,
+            sha512_256: bool,
             message: Vec<u8>,
             message_bytes: usize,
         }
@@ -275,6 +928,7 @@ mod tests {
             fn default() -> Self {
                 Self {
                     kind: Kind::None,
+                    sha512_256: false,
                     message: Vec::new(),
                     message_bytes: 0,
                 }
@@ -284,6 +938,10 @@ mod tests {
         impl CavpSink for Cavp {
             fn on_meta(&mut self, meta: &str) {
                 self.kind = match meta {
+                    "L = 20" => Kind::Sha1,
+                    "L = 28" => Kind::Sha224,
+                    // SHA512/256 is distinguished only by filename
+                    "L = 32" if self.sha512_256 => Kind::Sha512_256,
                     "L = 32" => Kind::Sha256,
                     "L = 48" => Kind::Sha384,
                     "L = 64" => Kind::Sha512,
@@ -300,6 +958,16 @@ mod tests {
                     }
                     "MD" => match self.kind {
                         Kind::None => {}
+                        Kind::Sha1 => {
+                            let result = Sha1::hash(&self.message);
+                            let wanted = value.bytes();
+                            assert!(result.ct_equal(&wanted));
+                        }
+                        Kind::Sha224 => {
+                            let result = Sha224::hash(&self.message);
+                            let wanted = value.bytes();
+                            assert!(result.ct_equal(&wanted));
+                        }
                         Kind::Sha256 => {
                             let result = Sha256::hash(&self.message);
                             let wanted = value.bytes();
@@ -315,6 +983,11 @@ mod tests {
                             let wanted = value.bytes();
                             assert!(result.ct_equal(&wanted));
                         }
+                        Kind::Sha512_256 => {
+                            let result = Sha512_256::hash(&self.message);
+                            let wanted = value.bytes();
+                            assert!(result.ct_equal(&wanted));
+                        }
                     },
                     _ => {
                         todo!("{self:?} value {name} = {value:?}");
@@ -323,6 +996,22 @@ mod tests {
             }
         }
 
+        process_cavp(
+            "../thirdparty/cavp/sha2/SHA1ShortMsg.rsp",
+            &mut Cavp::default(),
+        );
+        process_cavp(
+            "../thirdparty/cavp/sha2/SHA1LongMsg.rsp",
+            &mut Cavp::default(),
+        );
+        process_cavp(
+            "../thirdparty/cavp/sha2/SHA224ShortMsg.rsp",
+            &mut Cavp::default(),
+        );
+        process_cavp(
+            "../thirdparty/cavp/sha2/SHA224LongMsg.rsp",
+            &mut Cavp::default(),
+        );
         process_cavp(
             "../thirdparty/cavp/sha2/SHA256ShortMsg.rsp",
             &mut Cavp::default(),
@@ -347,6 +1036,54 @@ mod tests {
             "../thirdparty/cavp/sha2/SHA512LongMsg.rsp",
             &mut Cavp::default(),
         );
+        process_cavp(
+            "../thirdparty/cavp/sha2/SHA512_256ShortMsg.rsp",
+            &mut Cavp {
+                sha512_256: true,
+                ..Default::default()
+            },
+        );
+        process_cavp(
+            "../thirdparty/cavp/sha2/SHA512_256LongMsg.rsp",
+            &mut Cavp {
+                sha512_256: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn blake2_output_lengths() {
+        let mut ctx = Blake2bContext::new_keyed(b"key", 20).unwrap();
+        ctx.update(b"hello");
+        let output = HashContext::finish(ctx);
+        assert!(matches!(output, HashOutput::Blake2b(_)));
+        assert_eq!(
+            output.as_ref(),
+            b"\x9e\xdf\xa5\xd3\xeb\x10\xaa\x4a\xab\x45\x49\x92\x3e\x8c\x3b\xdd\xaf\xdb\xe0\x1d"
+        );
+
+        let mut ctx = Blake2sContext::new_keyed(b"", 16).unwrap();
+        ctx.update(b"hello");
+        let output = HashContext::finish(ctx);
+        assert!(matches!(output, HashOutput::Blake2s(_)));
+        assert_eq!(
+            output.as_ref(),
+            b"\x96\xd5\x39\x65\x3d\xbf\x84\x1c\x38\x4b\x53\xd5\xf0\x46\x58\xe5"
+        );
+
+        // outputs of different lengths are never equal, even if one
+        // is a prefix of the other.
+        let shorter = HashOutput::Blake2s(Blake2Output::new(&output.as_ref()[..8]).unwrap());
+        assert_ne!(output, shorter);
+        assert_eq!(Blake2s256::hash(b"hello").as_ref().len(), 32);
+
+        assert_eq!(Blake2Output::<32>::new(&[]).err(), Some(Error::WrongLength));
+        assert_eq!(
+            Blake2Output::<32>::new(&[0u8; 33]).err(),
+            Some(Error::WrongLength)
+        );
     }
 
     #[test]
@@ -354,14 +1091,18 @@ mod tests {
         #[derive(Debug)]
         enum Kind {
             None,
+            Sha1,
+            Sha224,
             Sha256,
             Sha384,
             Sha512,
+            Sha512_256,
         }
 
         #[derive(Debug)]
         struct Cavp {
             kind: Kind,
+            sha512_256: bool,
             seed: Vec<u8>,
         }
 
@@ -369,6 +1110,7 @@ mod tests {
             fn default() -> Self {
                 Self {
                     kind: Kind::None,
+                    sha512_256: false,
                     seed: Vec::new(),
                 }
             }
@@ -377,6 +1119,10 @@ mod tests {
         impl CavpSink for Cavp {
             fn on_meta(&mut self, meta: &str) {
                 self.kind = match meta {
+                    "L = 20" => Kind::Sha1,
+                    "L = 28" => Kind::Sha224,
+                    // SHA512/256 is distinguished only by filename
+                    "L = 32" if self.sha512_256 => Kind::Sha512_256,
                     "L = 32" => Kind::Sha256,
                     "L = 48" => Kind::Sha384,
                     "L = 64" => Kind::Sha512,
@@ -390,6 +1136,14 @@ mod tests {
                     "COUNT" => {}
                     "MD" => match self.kind {
                         Kind::None => {}
+                        Kind::Sha1 => {
+                            self.seed = monte_carlo_1000::<Sha1>(&self.seed);
+                            assert_eq!(self.seed, value.bytes());
+                        }
+                        Kind::Sha224 => {
+                            self.seed = monte_carlo_1000::<Sha224>(&self.seed);
+                            assert_eq!(self.seed, value.bytes());
+                        }
                         Kind::Sha256 => {
                             self.seed = monte_carlo_1000::<Sha256>(&self.seed);
                             assert_eq!(self.seed, value.bytes());
@@ -402,6 +1156,10 @@ mod tests {
                             self.seed = monte_carlo_1000::<Sha512>(&self.seed);
                             assert_eq!(self.seed, value.bytes());
                         }
+                        Kind::Sha512_256 => {
+                            self.seed = monte_carlo_1000::<Sha512_256>(&self.seed);
+                            assert_eq!(self.seed, value.bytes());
+                        }
                     },
                     _ => {
                         todo!("{self:?} value {name} = {value:?}");
@@ -424,6 +1182,14 @@ mod tests {
             window[2].clone()
         }
 
+        process_cavp(
+            "../thirdparty/cavp/sha2/SHA1Monte.rsp",
+            &mut Cavp::default(),
+        );
+        process_cavp(
+            "../thirdparty/cavp/sha2/SHA224Monte.rsp",
+            &mut Cavp::default(),
+        );
         process_cavp(
             "../thirdparty/cavp/sha2/SHA256Monte.rsp",
             &mut Cavp::default(),
@@ -436,5 +1202,12 @@ mod tests {
             "../thirdparty/cavp/sha2/SHA512Monte.rsp",
             &mut Cavp::default(),
         );
+        process_cavp(
+            "../thirdparty/cavp/sha2/SHA512_256Monte.rsp",
+            &mut Cavp {
+                sha512_256: true,
+                ..Default::default()
+            },
+        );
     }
 }
@@ -23,7 +23,9 @@ impl<H: Hash> Hmac<H> {
         let key = key.as_ref();
         // shorten long keys
         if key.len() > key_block.len() {
-            let h_key = H::hash(key);
+            let mut ctx = H::new_for_secret_input();
+            ctx.update(key);
+            let h_key = ctx.finish();
             key_block[..h_key.as_ref().len()].copy_from_slice(h_key.as_ref());
         } else {
             key_block[..key.len()].copy_from_slice(key);
@@ -34,7 +36,7 @@ impl<H: Hash> Hmac<H> {
         for byte in block.iter_mut() {
             *byte ^= 0x36;
         }
-        let mut inner = H::new();
+        let mut inner = H::new_for_secret_input();
         inner.update(&block);
 
         // and outer
@@ -42,7 +44,7 @@ impl<H: Hash> Hmac<H> {
         for byte in block.iter_mut() {
             *byte ^= 0x5c;
         }
-        let mut outer = H::new();
+        let mut outer = H::new_for_secret_input();
         outer.update(&block);
 
         Self { inner, outer }
@@ -74,10 +76,19 @@ impl<H: Hash> Hmac<H> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(
+    test,
+    feature = "blake2",
+    feature = "sha1",
+    feature = "sha256",
+    feature = "sha3",
+    feature = "sha512"
+))]
 mod tests {
     use super::*;
-    use crate::high::hash::{Sha256, Sha384, Sha512};
+    use crate::high::hash::{
+        Blake2Output, Blake2s256, Sha1, Sha224, Sha256, Sha384, Sha3_224, Sha3_256, Sha512,
+    };
     use crate::test::*;
 
     #[test]
@@ -88,11 +99,60 @@ mod tests {
                    HashOutput::Sha256(*b"\xf1\xac\x97\x02\xeb\x5f\xaf\x23\xca\x29\x1a\x4d\xc4\x6d\xed\xde\xee\x2a\x78\xcc\xda\xf0\xa4\x12\xbe\xd7\x71\x4c\xff\xfb\x1c\xc4"));
     }
 
+    #[test]
+    fn smoke_sha1() {
+        let mut h = Hmac::<Sha1>::new(b"hello");
+        h.update(b"world");
+        assert_eq!(h.finish(),
+                   HashOutput::Sha1(*b"\x8a\x3a\x84\xbc\xd0\xd0\x06\x5e\x97\xf1\x75\xd3\x70\x44\x7c\x7d\x02\xe0\x09\x73"));
+    }
+
+    #[test]
+    fn sha1_keys_without_collision_detection() {
+        // HMAC-SHA1 does not apply collision detection to the (secret) key,
+        // so these colliding keys give the same result as they would
+        // with plain SHA-1.
+        for key in [
+            &include_bytes!("../testdata/sha1-shattered-1.bin")[..],
+            &include_bytes!("../testdata/sha1-shattered-2.bin")[..],
+        ] {
+            let mut h = Hmac::<Sha1>::new(key);
+            h.update(b"world");
+            assert_eq!(h.finish(),
+                       HashOutput::Sha1(*b"\x1d\x66\x47\xb6\xc3\xc8\x08\x37\x95\x24\x5c\x4e\x2e\x51\xe8\xca\x7d\x2d\xc5\xaf"));
+        }
+    }
+
+    #[test]
+    fn smoke_blake2s() {
+        // as used in WireGuard
+        let mut h = Hmac::<Blake2s256>::new(b"hello");
+        h.update(b"world");
+        assert_eq!(h.finish(),
+                   HashOutput::Blake2s(Blake2Output::new(b"\xc6\x50\x82\xb4\xd7\x8b\x3b\x54\x11\x9e\xa3\x19\xef\x2a\x21\x41\x14\x44\xb6\x1d\x32\xc2\xe9\xa5\xc5\x6f\x3f\xd0\xea\x44\xb6\xb4").unwrap()));
+    }
+
+    #[test]
+    fn smoke_sha3() {
+        let mut h = Hmac::<Sha3_256>::new(b"hello");
+        h.update(b"world");
+        assert_eq!(h.finish(),
+                   HashOutput::Sha3_256(*b"\xb3\xb3\x75\x71\x9b\x8f\x26\xb3\x72\xe1\x7f\x41\xd2\x05\xfc\xd0\x67\x1c\x67\xbf\x9a\x32\x6a\x2b\xd4\x00\x08\xd4\x8d\xab\xc0\xa2"));
+
+        // SHA3-224 has a block size larger than the other hash functions
+        let mut h = Hmac::<Sha3_224>::new([b'k'; 200]);
+        h.update(b"world");
+        assert_eq!(h.finish(),
+                   HashOutput::Sha3_224(*b"\xc4\x16\x28\x41\x51\x04\x5c\x24\x44\x99\xff\x9a\x6a\x37\xf6\x1b\xa6\x69\x25\x59\x33\x3d\x6a\x66\x16\x9c\xb9\xa4"));
+    }
+
     #[test]
     fn cavp() {
         #[derive(Debug)]
         enum Kind {
             None,
+            Sha1,
+            Sha224,
             Sha256,
             Sha384,
             Sha512,
@@ -118,7 +178,8 @@ mod tests {
         impl CavpSink for Cavp {
             fn on_meta(&mut self, meta: &str) {
                 self.kind = match meta {
-                    "L=20" | "L=28" => Kind::None,
+                    "L=20" => Kind::Sha1,
+                    "L=28" => Kind::Sha224,
                     "L=32" => Kind::Sha256,
                     "L=48" => Kind::Sha384,
                     "L=64" => Kind::Sha512,
@@ -134,6 +195,37 @@ mod tests {
                     "Msg" => self.message = value.bytes(),
                     "Mac" => match self.kind {
                         Kind::None => {}
+                        Kind::Sha1 => {
+                            let mut h = Hmac::<Sha1>::new(&self.key);
+                            h.update(&self.message);
+                            let tag = h.finish();
+
+                            let wanted = value.bytes();
+
+                            match wanted.len() {
+                                20 => assert!(tag.ct_equal(&wanted)),
+                                16 => assert!(tag.truncated_ct_equal::<16>(&wanted)),
+                                12 => assert!(tag.truncated_ct_equal::<12>(&wanted)),
+                                10 => assert!(tag.truncated_ct_equal::<10>(&wanted)),
+                                len => todo!("unhandled tag len {len}"),
+                            }
+                        }
+                        Kind::Sha224 => {
+                            let mut h = Hmac::<Sha224>::new(&self.key);
+                            h.update(&self.message);
+                            let tag = h.finish();
+
+                            let wanted = value.bytes();
+
+                            match wanted.len() {
+                                28 => assert!(tag.ct_equal(&wanted)),
+                                24 => assert!(tag.truncated_ct_equal::<24>(&wanted)),
+                                20 => assert!(tag.truncated_ct_equal::<20>(&wanted)),
+                                16 => assert!(tag.truncated_ct_equal::<16>(&wanted)),
+                                14 => assert!(tag.truncated_ct_equal::<14>(&wanted)),
+                                len => todo!("unhandled tag len {len}"),
+                            }
+                        }
                         Kind::Sha256 => {
                             let mut h = Hmac::<Sha256>::new(&self.key);
                             h.update(&self.message);
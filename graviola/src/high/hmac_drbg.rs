@@ -1,23 +1,60 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
+//! HMAC_DRBG: a deterministic random bit generator built on HMAC.
+//!
+//! HMAC_DRBG is standardized in [SP800-90A](https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-90Ar1.pdf).
+
 use super::hash::{Hash, HashOutput};
 use super::hmac::Hmac;
+use crate::low::zeroise;
 use crate::mid::rng::RandomSource;
 use crate::Error;
 
 use core::marker::PhantomData;
 
-/// A limited implementation of SP800-90A -- enough for RFC6979 ECDSA.
-pub(crate) struct HmacDrbg<H: Hash> {
+/// An HMAC_DRBG instance, using hash function `H`.
+///
+/// This implements the HMAC_DRBG of SP800-90A section 10.1.2, including
+/// reseeding, additional input, reseed-interval enforcement and
+/// prediction resistance.
+///
+/// It is the caller's responsibility to supply entropy input of at
+/// least the security strength of `H` to [`HmacDrbg::new()`],
+/// [`HmacDrbg::reseed()`] and
+/// [`HmacDrbg::generate_with_prediction_resistance()`].
+/// [`HmacDrbg::new_from_source()`] and [`HmacDrbg::reseed_from_source()`]
+/// obtain 256 bits of entropy input from a [`RandomSource`].
+///
+/// The [`RandomSource`] implementation generates output with no
+/// additional input.  It returns [`Error::ReseedRequired`] once the
+/// reseed interval is exhausted.
+pub struct HmacDrbg<H: Hash> {
     k: HashOutput,
     v: HashOutput,
-    reseed_counter: usize,
+    reseed_counter: u64,
+    reseed_interval: u64,
     _h: PhantomData<H>,
 }
 
 impl<H: Hash> HmacDrbg<H> {
-    pub(crate) fn new(entropy_input: &[u8], nonce: &[u8], personalization_string: &[u8]) -> Self {
+    /// The largest number of bytes that may be requested from one call to
+    /// [`HmacDrbg::generate()`].
+    ///
+    /// This is 2<sup>19</sup> bits.
+    pub const MAX_REQUEST_BYTES: usize = 1 << 16;
+
+    /// The largest (and default) permitted reseed interval.
+    ///
+    /// This is the number of calls to [`HmacDrbg::generate()`] allowed
+    /// between reseeds.
+    pub const MAX_RESEED_INTERVAL: u64 = 1 << 48;
+
+    /// Instantiate an HMAC_DRBG.
+    ///
+    /// `entropy_input`, `nonce` and `personalization_string` are as
+    /// described in SP800-90A.  `personalization_string` may be empty.
+    pub fn new(entropy_input: &[u8], nonce: &[u8], personalization_string: &[u8]) -> Self {
         // 1. seed_material = entropy_input || nonce || personalization_string.
         let seed_material = &[entropy_input, nonce, personalization_string];
 
@@ -28,25 +65,145 @@ impl<H: Hash> HmacDrbg<H> {
         v.as_mut().fill(1u8);
 
         // 4. (Key, V) = HMAC_DRBG_Update (seed_material, Key, V).
-        // 5. reseed_counter = 1.
-        // (nb. `update()` increments `reseed_counter` to 1)
         let mut r = Self {
             k,
             v,
             reseed_counter: 0,
+            reseed_interval: Self::MAX_RESEED_INTERVAL,
             _h: PhantomData,
         };
         r.update(seed_material);
+
+        // 5. reseed_counter = 1.
+        r.reseed_counter = 1;
         r
     }
 
-    fn update(&mut self, additional_input: &[&[u8]]) {
+    /// Instantiate an HMAC_DRBG, taking the entropy input and nonce
+    /// from `source`.
+    ///
+    /// `personalization_string` may be empty.
+    pub fn new_from_source(
+        source: &mut dyn RandomSource,
+        personalization_string: &[u8],
+    ) -> Result<Self, Error> {
+        let mut seed = [0u8; ENTROPY_LEN + NONCE_LEN];
+        source.fill(&mut seed)?;
+        let (entropy_input, nonce) = seed.split_at(ENTROPY_LEN);
+        let r = Self::new(entropy_input, nonce, personalization_string);
+        zeroise(&mut seed);
+        Ok(r)
+    }
+
+    /// Reseed this HMAC_DRBG with fresh `entropy_input`.
+    ///
+    /// `additional_input` may be empty.
+    pub fn reseed(&mut self, entropy_input: &[u8], additional_input: &[u8]) {
+        // 1. seed_material = entropy_input || additional_input.
+        // 2. (Key, V) = HMAC_DRBG_Update (seed_material, Key, V).
+        self.update(&[entropy_input, additional_input]);
+
+        // 3. reseed_counter = 1.
+        self.reseed_counter = 1;
+    }
+
+    /// Reseed this HMAC_DRBG, taking the entropy input from `source`.
+    ///
+    /// `additional_input` may be empty.
+    pub fn reseed_from_source(
+        &mut self,
+        source: &mut dyn RandomSource,
+        additional_input: &[u8],
+    ) -> Result<(), Error> {
+        let mut entropy_input = [0u8; ENTROPY_LEN];
+        source.fill(&mut entropy_input)?;
+        self.reseed(&entropy_input, additional_input);
+        zeroise(&mut entropy_input);
+        Ok(())
+    }
+
+    /// Generate `out.len()` bytes of output, into `out`.
+    ///
+    /// `additional_input` may be empty.
+    ///
+    /// Returns [`Error::ReseedRequired`] if the reseed interval
+    /// is exhausted, or [`Error::WrongLength`] if `out` is longer
+    /// than [`HmacDrbg::MAX_REQUEST_BYTES`].
+    pub fn generate(&mut self, additional_input: &[u8], out: &mut [u8]) -> Result<(), Error> {
+        if out.len() > Self::MAX_REQUEST_BYTES {
+            return Err(Error::WrongLength);
+        }
+
+        // 1. If reseed_counter > reseed_interval, then
+        //    return an indication that a reseed is required.
+        if self.reseed_counter > self.reseed_interval {
+            return Err(Error::ReseedRequired);
+        }
+
+        // 2. If additional_input ≠ Null, then (Key, V) =
+        //    HMAC_DRBG_Update (additional_input, Key, V).
+        if !additional_input.is_empty() {
+            self.update(&[additional_input]);
+        }
+
+        // 3. - 5. this is a different formulation, but
+        // ends up with all `V` terms being written to `out`.
+        let hashlen = self.v.as_ref().len();
+        for chunk in out.chunks_mut(hashlen) {
+            self.generate_block();
+            chunk.copy_from_slice(&self.v.as_ref()[..chunk.len()]);
+        }
+
+        // 6. (Key, V) = HMAC_DRBG_Update (additional_input, Key, V).
+        self.update(&[additional_input]);
+
+        // 7. reseed_counter = reseed_counter + 1.
+        self.reseed_counter += 1;
+
+        Ok(())
+    }
+
+    /// Generate `out.len()` bytes of output, into `out`, with
+    /// prediction resistance.
+    ///
+    /// This reseeds with `entropy_input` and `additional_input`
+    /// before generating output, as described in SP800-90A
+    /// section 9.3.1.
+    pub fn generate_with_prediction_resistance(
+        &mut self,
+        entropy_input: &[u8],
+        additional_input: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), Error> {
+        if out.len() > Self::MAX_REQUEST_BYTES {
+            return Err(Error::WrongLength);
+        }
+
+        self.reseed(entropy_input, additional_input);
+        self.generate(&[], out)
+    }
+
+    /// Set the reseed interval.
+    ///
+    /// This is the number of calls to [`HmacDrbg::generate()`]
+    /// allowed between reseeds.  It must be between 1 and
+    /// [`HmacDrbg::MAX_RESEED_INTERVAL`] inclusive, otherwise
+    /// [`Error::OutOfRange`] is returned.
+    pub fn set_reseed_interval(&mut self, reseed_interval: u64) -> Result<(), Error> {
+        if !(1..=Self::MAX_RESEED_INTERVAL).contains(&reseed_interval) {
+            return Err(Error::OutOfRange);
+        }
+        self.reseed_interval = reseed_interval;
+        Ok(())
+    }
+
+    fn update(&mut self, provided_data: &[&[u8]]) {
         // 1. K = HMAC (K, V || 0x00 || provided_data).
         let mut ctx = Hmac::<H>::new(self.k.as_ref());
         ctx.update(self.v.as_ref());
         ctx.update([0u8]);
 
-        for item in additional_input {
+        for item in provided_data {
             ctx.update(item);
         }
 
@@ -57,10 +214,8 @@ impl<H: Hash> HmacDrbg<H> {
         ctx.update(self.v.as_ref());
         self.v = ctx.finish();
 
-        self.reseed_counter += 1;
-
         // 3. If (provided_data = Null), then return K and V.
-        if additional_input.is_empty() {
+        if provided_data.iter().all(|item| item.is_empty()) {
             return;
         }
 
@@ -69,7 +224,7 @@ impl<H: Hash> HmacDrbg<H> {
         ctx.update(self.v.as_ref());
         ctx.update([1u8]);
 
-        for item in additional_input {
+        for item in provided_data {
             ctx.update(item);
         }
 
@@ -91,36 +246,34 @@ impl<H: Hash> HmacDrbg<H> {
 
 impl<H: Hash> RandomSource for HmacDrbg<H> {
     fn fill(&mut self, out: &mut [u8]) -> Result<(), Error> {
-        let hashlen = self.v.as_ref().len();
-
-        // 1. If reseed_counter > reseed_interval, then
-        //    return an indication that a reseed is required.
-        // nb. in practice, does not happen in our uses
-        assert!(self.reseed_counter < 0x1_0000_0000_0000);
-
-        // 2. If additional_input ≠ Null, then (Key, V) =
-        //    HMAC_DRBG_Update (additional_input, Key, V).
-        // nb: we don't accept additional_input here.
-
-        // 3. - 5. this is a different formulation, but
-        // ends up with all `V` terms being written to `out`.
-        for chunk in out.chunks_mut(hashlen) {
-            self.generate_block();
-            chunk.copy_from_slice(&self.v.as_ref()[..chunk.len()]);
+        for chunk in out.chunks_mut(Self::MAX_REQUEST_BYTES) {
+            self.generate(&[], chunk)?;
         }
-
-        // 6. (Key, V) = HMAC_DRBG_Update (additional_input, Key, V).
-        // 7. reseed_counter = reseed_counter + 1.
-        self.update(&[]);
-
         Ok(())
     }
 }
 
-#[cfg(test)]
+impl<H: Hash> Drop for HmacDrbg<H> {
+    fn drop(&mut self) {
+        zeroise(self.k.as_mut());
+        zeroise(self.v.as_mut());
+    }
+}
+
+/// Entropy input length used by `*_from_source` functions.
+///
+/// This is sufficient for the 256-bit security strength
+/// of all supported hash functions.
+const ENTROPY_LEN: usize = 32;
+
+/// Nonce length used by `*_from_source` functions.
+const NONCE_LEN: usize = 16;
+
+#[cfg(all(test, feature = "sha1", feature = "sha256", feature = "sha512"))]
 mod tests {
     use super::*;
-    use crate::high::hash::Sha256;
+    use crate::high::hash::{Sha1, Sha224, Sha256, Sha384, Sha512};
+    use crate::test::*;
 
     #[test]
     fn rfc6979_example() {
@@ -138,4 +291,218 @@ mod tests {
         ctx.fill(&mut t2).unwrap();
         assert_eq!(&t2, b"\xC7\x0C\x78\x60\x8A\x3B\x5B\xE9\x28\x9B\xE9\x0E\xF6\xE8\x1A\x9E\x2C\x15\x16\xD5\x75\x1D\x2F\x75\xF5\x00\x33\xE4\x5F\x73\xBD\xEB");
     }
+
+    #[test]
+    fn nist_example() {
+        let mut ctx = HmacDrbg::<Sha256>::new(
+            b"\xca\x85\x19\x11\x34\x93\x84\xbf\xfe\x89\xde\x1c\xbd\xc4\x6e\x68\x31\xe4\x4d\x34\xa4\xfb\x93\x5e\xe2\x85\xdd\x14\xb7\x1a\x74\x88",
+            b"\x65\x9b\xa9\x6c\x60\x1d\xc6\x9f\xc9\x02\x94\x08\x05\xec\x0c\xa8",
+            &[],
+        );
+        let mut out = [0u8; 128];
+        ctx.generate(&[], &mut out).unwrap();
+        ctx.generate(&[], &mut out).unwrap();
+        assert_eq!(&out, b"\xe5\x28\xe9\xab\xf2\xde\xce\x54\xd4\x7c\x7e\x75\xe5\xfe\x30\x21\x49\xf8\x17\xea\x9f\xb4\xbe\xe6\xf4\x19\x96\x97\xd0\x4d\x5b\x89\xd5\x4f\xbb\x97\x8a\x15\xb5\xc4\x43\xc9\xec\x21\x03\x6d\x24\x60\xb6\xf7\x3e\xba\xd0\xdc\x2a\xba\x6e\x62\x4a\xbf\x07\x74\x5b\xc1\x07\x69\x4b\xb7\x54\x7b\xb0\x99\x5f\x70\xde\x25\xd6\xb2\x9e\x2d\x30\x11\xbb\x19\xd2\x76\x76\xc0\x71\x62\xc8\xb5\xcc\xde\x06\x68\x96\x1d\xf8\x68\x03\x48\x2c\xb3\x7e\xd6\xd5\xc0\xbb\x8d\x50\xcf\x1f\x50\xd4\x76\xaa\x04\x58\xbd\xab\xa8\x06\xf4\x8b\xe9\xdc\xb8");
+    }
+
+    #[test]
+    fn nist_example_additional_input() {
+        let mut ctx = HmacDrbg::<Sha256>::new(
+            b"\xd3\xcc\x4d\x1a\xcf\x3d\xde\x0c\x4b\xd2\x29\x0d\x26\x23\x37\x04\x2d\xc6\x32\x94\x82\x23\xd3\xa2\xea\xab\x87\xda\x44\x29\x5f\xbd",
+            b"\x01\x09\xb0\xe7\x29\xf4\x57\x32\x8a\xa1\x85\x69\xa9\x22\x49\x21",
+            &[],
+        );
+        let mut out = [0u8; 128];
+        ctx.generate(b"\x3c\x31\x18\x48\x18\x3c\x9a\x21\x2a\x26\xf2\x7f\x8c\x66\x47\xe4\x03\x75\xe4\x66\xa0\x85\x7c\xc3\x9c\x4e\x47\x57\x5d\x53\xf1\xf6", &mut out).unwrap();
+        ctx.generate(b"\xfc\xb9\xab\xd1\x9c\xcf\xbc\xce\xf8\x8c\x9c\x39\xbf\xb3\xdd\x7b\x1c\x12\x26\x6c\x98\x08\x99\x2e\x30\x5b\xc3\xcf\xf5\x66\xe4\xe4", &mut out).unwrap();
+        assert_eq!(&out, b"\x9c\x7b\x75\x8b\x21\x2c\xd0\xfc\xec\xd5\xda\xa4\x89\x82\x17\x12\xe3\xcd\xea\x44\x67\xb5\x60\xef\x5d\xdc\x24\xab\x47\x74\x9a\x1f\x1f\xfd\xbb\xb1\x18\xf4\xe6\x2f\xcf\xca\x33\x71\xb8\xfb\xfc\x5b\x06\x46\xb8\x3e\x06\xbf\xbb\xab\x5f\xac\x30\xea\x09\xea\x2b\xc7\x6f\x1e\xa5\x68\xc9\xbe\x04\x44\xb2\xcc\x90\x51\x7b\x20\xca\x82\x5f\x2d\x0e\xcc\xd8\x8e\x71\x75\x53\x8b\x85\xd9\x0a\xb3\x90\x18\x3c\xa6\x39\x55\x35\xd3\x44\x73\xaf\x6b\x5a\x5b\x88\xf5\xa5\x9e\xe7\x56\x15\x73\x33\x7e\xa8\x19\xda\x0d\xcc\x35\x73\xa2\x29\x74");
+    }
+
+    #[test]
+    fn reseed_interval() {
+        let mut ctx = HmacDrbg::<Sha256>::new(&[1; 32], &[2; 16], b"test");
+        assert_eq!(ctx.set_reseed_interval(0), Err(Error::OutOfRange));
+        assert_eq!(
+            ctx.set_reseed_interval(HmacDrbg::<Sha256>::MAX_RESEED_INTERVAL + 1),
+            Err(Error::OutOfRange)
+        );
+        ctx.set_reseed_interval(2).unwrap();
+
+        let mut out = [0u8; 16];
+        ctx.generate(&[], &mut out).unwrap();
+        ctx.fill(&mut out).unwrap();
+        assert_eq!(ctx.generate(&[], &mut out), Err(Error::ReseedRequired));
+        assert_eq!(ctx.fill(&mut out), Err(Error::ReseedRequired));
+
+        ctx.reseed(&[3; 32], &[]);
+        ctx.generate(&[], &mut out).unwrap();
+    }
+
+    #[test]
+    fn max_request() {
+        let mut ctx = HmacDrbg::<Sha256>::new(&[1; 32], &[2; 16], &[]);
+        let mut out = vec![0u8; HmacDrbg::<Sha256>::MAX_REQUEST_BYTES + 1];
+        assert_eq!(ctx.generate(&[], &mut out), Err(Error::WrongLength));
+        ctx.generate(&[], &mut out[1..]).unwrap();
+        // `fill` splits larger requests
+        ctx.fill(&mut out).unwrap();
+    }
+
+    #[test]
+    fn from_source() {
+        let mut source = crate::mid::rng::SliceRandomSource(&[0x11; 48 + 32]);
+        let mut ctx = HmacDrbg::<Sha256>::new_from_source(&mut source, b"pers").unwrap();
+        ctx.reseed_from_source(&mut source, b"add").unwrap();
+        assert_eq!(
+            ctx.reseed_from_source(&mut source, &[]),
+            Err(Error::RngFailed)
+        );
+
+        let mut expected = HmacDrbg::<Sha256>::new(&[0x11; 32], &[0x11; 16], b"pers");
+        expected.reseed(&[0x11; 32], b"add");
+
+        let (mut a, mut b) = ([0u8; 32], [0u8; 32]);
+        ctx.fill(&mut a).unwrap();
+        expected.fill(&mut b).unwrap();
+        assert_eq!(a, b);
+    }
+
+    /// Abstracts over `HmacDrbg<H>` for different `H`.
+    trait TestDrbg {
+        fn reseed(&mut self, entropy_input: &[u8], additional_input: &[u8]);
+        fn generate(
+            &mut self,
+            entropy_input_pr: Option<&[u8]>,
+            additional_input: &[u8],
+            out: &mut [u8],
+        );
+    }
+
+    impl<H: Hash> TestDrbg for HmacDrbg<H> {
+        fn reseed(&mut self, entropy_input: &[u8], additional_input: &[u8]) {
+            Self::reseed(self, entropy_input, additional_input);
+        }
+
+        fn generate(
+            &mut self,
+            entropy_input_pr: Option<&[u8]>,
+            additional_input: &[u8],
+            out: &mut [u8],
+        ) {
+            match entropy_input_pr {
+                Some(entropy_input) => self
+                    .generate_with_prediction_resistance(entropy_input, additional_input, out)
+                    .unwrap(),
+                None => Self::generate(self, additional_input, out).unwrap(),
+            }
+        }
+    }
+
+    #[test]
+    fn cavp() {
+        #[derive(Default)]
+        struct State {
+            hash: Option<&'static str>,
+            prediction_resistance: bool,
+            returned_bits_len: usize,
+            entropy_input: Vec<u8>,
+            nonce: Vec<u8>,
+            entropy_input_reseed: Vec<u8>,
+            additional_input: Vec<u8>,
+            drbg: Option<Box<dyn TestDrbg>>,
+            output: Vec<u8>,
+        }
+
+        impl State {
+            fn instantiate(&mut self, pers: &[u8]) {
+                let (e, n) = (&self.entropy_input[..], &self.nonce[..]);
+                self.drbg = match self.hash {
+                    Some("SHA-1") => Some(Box::new(HmacDrbg::<Sha1>::new(e, n, pers))),
+                    Some("SHA-224") => Some(Box::new(HmacDrbg::<Sha224>::new(e, n, pers))),
+                    Some("SHA-256") => Some(Box::new(HmacDrbg::<Sha256>::new(e, n, pers))),
+                    Some("SHA-384") => Some(Box::new(HmacDrbg::<Sha384>::new(e, n, pers))),
+                    Some("SHA-512") => Some(Box::new(HmacDrbg::<Sha512>::new(e, n, pers))),
+                    _ => None,
+                };
+            }
+        }
+
+        impl CavpSink for State {
+            fn on_meta(&mut self, meta: &str) {
+                match meta.split_once(" = ") {
+                    Some(("PredictionResistance", value)) => {
+                        self.prediction_resistance = value == "True"
+                    }
+                    Some(("ReturnedBitsLen", value)) => {
+                        self.returned_bits_len = value.parse().unwrap()
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.hash = match meta {
+                            "SHA-1" => Some("SHA-1"),
+                            "SHA-224" => Some("SHA-224"),
+                            "SHA-256" => Some("SHA-256"),
+                            "SHA-384" => Some("SHA-384"),
+                            "SHA-512" => Some("SHA-512"),
+                            _ => {
+                                println!("skipping unsupported {meta:?}");
+                                None
+                            }
+                        }
+                    }
+                }
+            }
+
+            fn on_value(&mut self, name: &str, value: Value<'_>) {
+                match name {
+                    "COUNT" => println!("  test {}", value.int()),
+                    "EntropyInput" => self.entropy_input = value.bytes(),
+                    "Nonce" => self.nonce = value.bytes(),
+                    "PersonalizationString" => self.instantiate(&value.bytes()),
+                    "EntropyInputReseed" => self.entropy_input_reseed = value.bytes(),
+                    "AdditionalInputReseed" => {
+                        if let Some(drbg) = self.drbg.as_mut() {
+                            drbg.reseed(&self.entropy_input_reseed, &value.bytes());
+                        }
+                    }
+                    "AdditionalInput" if self.prediction_resistance => {
+                        self.additional_input = value.bytes()
+                    }
+                    "AdditionalInput" => {
+                        if let Some(drbg) = self.drbg.as_mut() {
+                            self.output = vec![0u8; self.returned_bits_len / 8];
+                            drbg.generate(None, &value.bytes(), &mut self.output);
+                        }
+                    }
+                    "EntropyInputPR" => {
+                        if let Some(drbg) = self.drbg.as_mut() {
+                            self.output = vec![0u8; self.returned_bits_len / 8];
+                            drbg.generate(
+                                Some(&value.bytes()),
+                                &self.additional_input,
+                                &mut self.output,
+                            );
+                        }
+                    }
+                    "ReturnedBits" => {
+                        if self.drbg.take().is_some() {
+                            assert_eq!(self.output, value.bytes());
+                        }
+                    }
+                    _ => {
+                        todo!("unhandled value {name} = {value:?}");
+                    }
+                }
+            }
+        }
+
+        process_cavp(
+            "../thirdparty/cavp/drbg/pr_false/HMAC_DRBG.rsp",
+            &mut State::default(),
+        );
+        process_cavp(
+            "../thirdparty/cavp/drbg/pr_true/HMAC_DRBG.rsp",
+            &mut State::default(),
+        );
+    }
 }
@@ -0,0 +1,676 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! HPKE (Hybrid Public Key Encryption).
+//!
+//! HPKE is specified in [RFC9180](https://datatracker.ietf.org/doc/html/rfc9180).
+//! This implementation supports the base and PSK modes, with:
+//!
+//! - DHKEM(P-256, HKDF-SHA256), DHKEM(P-384, HKDF-SHA384) and DHKEM(X25519, HKDF-SHA256),
+//! - HKDF-SHA256, HKDF-SHA384 and HKDF-SHA512, and
+//! - AES-128-GCM, AES-256-GCM and ChaCha20Poly1305.
+
+use super::hash::{Hash, HashOutput, Sha256, Sha384, Sha512};
+use super::hmac::Hmac;
+use crate::low::{ct_equal, zeroise, Entry};
+use crate::mid::aes_gcm::AesGcm;
+use crate::mid::chacha20poly1305;
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
+use crate::mid::{p256, p384, x25519};
+use crate::Error;
+
+/// A key encapsulation mechanism.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kem {
+    /// DHKEM(P-256, HKDF-SHA256)
+    DhKemP256HkdfSha256,
+    /// DHKEM(P-384, HKDF-SHA384)
+    DhKemP384HkdfSha384,
+    /// DHKEM(X25519, HKDF-SHA256)
+    DhKemX25519HkdfSha256,
+}
+
+impl Kem {
+    /// The IANA-assigned identifier for this KEM.
+    pub fn id(&self) -> u16 {
+        match self {
+            Self::DhKemP256HkdfSha256 => 0x0010,
+            Self::DhKemP384HkdfSha384 => 0x0011,
+            Self::DhKemX25519HkdfSha256 => 0x0020,
+        }
+    }
+
+    /// Generate a new key pair using the default [`RandomSource`].
+    #[cfg(feature = "std")]
+    pub fn generate_key_pair(&self) -> Result<(PrivateKey, PublicKey), Error> {
+        self.generate_key_pair_with_rng(&mut DefaultRandom)
+    }
+
+    /// Generate a new key pair using `rng`.
+    ///
+    /// Fails only if `rng` fails.
+    pub fn generate_key_pair_with_rng(
+        &self,
+        rng: &mut dyn RandomSource,
+    ) -> Result<(PrivateKey, PublicKey), Error> {
+        let mut ikm = [0u8; MAX_PRIVATE_KEY_LEN];
+        let ikm = &mut ikm[..self.private_key_len()];
+        rng.fill(ikm)?;
+        let r = self.derive_key_pair(ikm);
+        zeroise(ikm);
+        r
+    }
+
+    /// Deterministically derive a key pair from `ikm`.
+    ///
+    /// `ikm` should have at least as much entropy as the private key.
+    ///
+    /// See [RFC9180 section 7.1.3](https://datatracker.ietf.org/doc/html/rfc9180#section-7.1.3).
+    pub fn derive_key_pair(&self, ikm: &[u8]) -> Result<(PrivateKey, PublicKey), Error> {
+        let _ = Entry::new_secret();
+        let suite_id = self.suite_id();
+        let kdf = self.kdf();
+        let dkp_prk = kdf.labeled_extract(&suite_id, b"", b"dkp_prk", ikm);
+
+        let mut private_key = PrivateKey {
+            buf: [0u8; MAX_PRIVATE_KEY_LEN],
+            len: self.private_key_len(),
+        };
+
+        match self {
+            Self::DhKemX25519HkdfSha256 => {
+                kdf.labeled_expand(
+                    &suite_id,
+                    dkp_prk.as_ref(),
+                    b"sk",
+                    &[],
+                    private_key.as_mut(),
+                )?;
+                let public_key = self.public_key(private_key.as_ref())?;
+                Ok((private_key, public_key))
+            }
+            Self::DhKemP256HkdfSha256 | Self::DhKemP384HkdfSha384 => {
+                // nb. the bitmask for these curves is 0xff, so is omitted.
+                for counter in 0..=255u8 {
+                    kdf.labeled_expand(
+                        &suite_id,
+                        dkp_prk.as_ref(),
+                        b"candidate",
+                        &[&[counter]],
+                        private_key.as_mut(),
+                    )?;
+                    if let Ok(public_key) = self.public_key(private_key.as_ref()) {
+                        return Ok((private_key, public_key));
+                    }
+                }
+
+                Err(Error::RngFailed)
+            }
+        }
+    }
+
+    fn encapsulate(
+        &self,
+        rng: &mut dyn RandomSource,
+        recipient: &[u8],
+    ) -> Result<(HashOutput, PublicKey), Error> {
+        let (private_key, encapsulated_key) = self.generate_key_pair_with_rng(rng)?;
+        let dh = self.diffie_hellman(private_key.as_ref(), recipient)?;
+        let shared_secret =
+            self.extract_and_expand(dh.as_ref(), encapsulated_key.as_ref(), recipient)?;
+        Ok((shared_secret, encapsulated_key))
+    }
+
+    fn decapsulate(
+        &self,
+        encapsulated_key: &[u8],
+        private_key: &[u8],
+    ) -> Result<HashOutput, Error> {
+        if private_key.len() != self.private_key_len() {
+            return Err(Error::WrongLength);
+        }
+        let dh = self.diffie_hellman(private_key, encapsulated_key)?;
+        let recipient = self.public_key(private_key)?;
+        self.extract_and_expand(dh.as_ref(), encapsulated_key, recipient.as_ref())
+    }
+
+    fn extract_and_expand(
+        &self,
+        dh: &[u8],
+        encapsulated_key: &[u8],
+        recipient: &[u8],
+    ) -> Result<HashOutput, Error> {
+        let suite_id = self.suite_id();
+        let kdf = self.kdf();
+        let eae_prk = kdf.labeled_extract(&suite_id, b"", b"eae_prk", dh);
+        let mut shared_secret = kdf.zeroed_output();
+        kdf.labeled_expand(
+            &suite_id,
+            eae_prk.as_ref(),
+            b"shared_secret",
+            &[encapsulated_key, recipient],
+            shared_secret.as_mut(),
+        )?;
+        Ok(shared_secret)
+    }
+
+    fn public_key(&self, private_key: &[u8]) -> Result<PublicKey, Error> {
+        match self {
+            Self::DhKemP256HkdfSha256 => {
+                let private_key = p256::PrivateKey::from_bytes(private_key)?;
+                Ok(PublicKey::new(&private_key.public_key_uncompressed()))
+            }
+            Self::DhKemP384HkdfSha384 => {
+                let private_key = p384::PrivateKey::from_bytes(private_key)?;
+                Ok(PublicKey::new(&private_key.public_key_uncompressed()))
+            }
+            Self::DhKemX25519HkdfSha256 => {
+                let private_key = x25519::PrivateKey::try_from_slice(private_key)
+                    .map_err(|_| Error::WrongLength)?;
+                Ok(PublicKey::new(&private_key.public_key().as_bytes()))
+            }
+        }
+    }
+
+    /// The result is secret and no larger than a private key, so shares that type.
+    fn diffie_hellman(&self, private_key: &[u8], public_key: &[u8]) -> Result<PrivateKey, Error> {
+        match self {
+            Self::DhKemP256HkdfSha256 => {
+                let private_key = p256::PrivateKey::from_bytes(private_key)?;
+                let public_key = p256::PublicKey::from_x962_uncompressed(public_key)?;
+                Ok(PrivateKey::new(&private_key.diffie_hellman(&public_key)?.0))
+            }
+            Self::DhKemP384HkdfSha384 => {
+                let private_key = p384::PrivateKey::from_bytes(private_key)?;
+                let public_key = p384::PublicKey::from_x962_uncompressed(public_key)?;
+                Ok(PrivateKey::new(&private_key.diffie_hellman(&public_key)?.0))
+            }
+            Self::DhKemX25519HkdfSha256 => {
+                let private_key = x25519::PrivateKey::try_from_slice(private_key)
+                    .map_err(|_| Error::WrongLength)?;
+                let public_key = x25519::PublicKey::try_from_slice(public_key)
+                    .map_err(|_| Error::WrongLength)?;
+                let shared_secret = private_key.diffie_hellman(&public_key);
+
+                // Reject small-order public keys: RFC9180 section 7.1.4.
+                match ct_equal(&shared_secret.0, &[0u8; 32]) {
+                    true => Err(Error::NotOnCurve),
+                    false => Ok(PrivateKey::new(&shared_secret.0)),
+                }
+            }
+        }
+    }
+
+    fn kdf(&self) -> Kdf {
+        match self {
+            Self::DhKemP256HkdfSha256 | Self::DhKemX25519HkdfSha256 => Kdf::HkdfSha256,
+            Self::DhKemP384HkdfSha384 => Kdf::HkdfSha384,
+        }
+    }
+
+    fn private_key_len(&self) -> usize {
+        match self {
+            Self::DhKemP256HkdfSha256 | Self::DhKemX25519HkdfSha256 => 32,
+            Self::DhKemP384HkdfSha384 => 48,
+        }
+    }
+
+    fn suite_id(&self) -> [u8; 5] {
+        let id = self.id().to_be_bytes();
+        [b'K', b'E', b'M', id[0], id[1]]
+    }
+}
+
+/// A key derivation function.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kdf {
+    /// HKDF-SHA256
+    HkdfSha256,
+    /// HKDF-SHA384
+    HkdfSha384,
+    /// HKDF-SHA512
+    HkdfSha512,
+}
+
+impl Kdf {
+    /// The IANA-assigned identifier for this KDF.
+    pub fn id(&self) -> u16 {
+        match self {
+            Self::HkdfSha256 => 0x0001,
+            Self::HkdfSha384 => 0x0002,
+            Self::HkdfSha512 => 0x0003,
+        }
+    }
+
+    fn labeled_extract(
+        &self,
+        suite_id: &[u8],
+        salt: &[u8],
+        label: &[u8],
+        ikm: &[u8],
+    ) -> HashOutput {
+        let ikm = [b"HPKE-v1".as_slice(), suite_id, label, ikm];
+        match self {
+            Self::HkdfSha256 => hkdf_extract::<Sha256>(salt, &ikm),
+            Self::HkdfSha384 => hkdf_extract::<Sha384>(salt, &ikm),
+            Self::HkdfSha512 => hkdf_extract::<Sha512>(salt, &ikm),
+        }
+    }
+
+    fn labeled_expand(
+        &self,
+        suite_id: &[u8],
+        prk: &[u8],
+        label: &[u8],
+        info: &[&[u8]],
+        out: &mut [u8],
+    ) -> Result<(), Error> {
+        let len = u16::try_from(out.len()).map_err(|_| Error::WrongLength)?;
+        if out.len() > 255 * self.zeroed_output().as_ref().len() {
+            return Err(Error::WrongLength);
+        }
+
+        // `info` has at most three parts (the key schedule context).
+        let len = len.to_be_bytes();
+        let mut labeled_info: [&[u8]; 7] = [&len, b"HPKE-v1", suite_id, label, &[], &[], &[]];
+        labeled_info[4..4 + info.len()].copy_from_slice(info);
+
+        match self {
+            Self::HkdfSha256 => hkdf_expand::<Sha256>(prk, &labeled_info, out),
+            Self::HkdfSha384 => hkdf_expand::<Sha384>(prk, &labeled_info, out),
+            Self::HkdfSha512 => hkdf_expand::<Sha512>(prk, &labeled_info, out),
+        }
+        Ok(())
+    }
+
+    fn zeroed_output(&self) -> HashOutput {
+        match self {
+            Self::HkdfSha256 => Sha256::zeroed_output(),
+            Self::HkdfSha384 => Sha384::zeroed_output(),
+            Self::HkdfSha512 => Sha512::zeroed_output(),
+        }
+    }
+}
+
+/// `HKDF-Extract` from [RFC5869](https://datatracker.ietf.org/doc/html/rfc5869).
+///
+/// An empty `salt` is equivalent to `HashLen` zeroes, because HMAC
+/// zero-pads its key.
+fn hkdf_extract<H: Hash>(salt: &[u8], ikm: &[&[u8]]) -> HashOutput {
+    let mut hmac = Hmac::<H>::new(salt);
+    for ikm in ikm {
+        hmac.update(ikm);
+    }
+    hmac.finish()
+}
+
+/// `HKDF-Expand` from [RFC5869](https://datatracker.ietf.org/doc/html/rfc5869).
+///
+/// `out` must be no longer than `255 * HashLen`.
+fn hkdf_expand<H: Hash>(prk: &[u8], info: &[&[u8]], out: &mut [u8]) {
+    let mut t = H::zeroed_output();
+    let mut t_len = 0;
+
+    for (i, chunk) in out.chunks_mut(t.as_ref().len()).enumerate() {
+        let mut hmac = Hmac::<H>::new(prk);
+        hmac.update(&t.as_ref()[..t_len]);
+        for info in info {
+            hmac.update(info);
+        }
+        hmac.update([i as u8 + 1]);
+        t = hmac.finish();
+        t_len = t.as_ref().len();
+        chunk.copy_from_slice(&t.as_ref()[..chunk.len()]);
+    }
+
+    zeroise(t.as_mut());
+}
+
+/// An authenticated encryption algorithm.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aead {
+    /// AES-128-GCM
+    Aes128Gcm,
+    /// AES-256-GCM
+    Aes256Gcm,
+    /// ChaCha20Poly1305
+    ChaCha20Poly1305,
+}
+
+impl Aead {
+    /// The IANA-assigned identifier for this AEAD.
+    pub fn id(&self) -> u16 {
+        match self {
+            Self::Aes128Gcm => 0x0001,
+            Self::Aes256Gcm => 0x0002,
+            Self::ChaCha20Poly1305 => 0x0003,
+        }
+    }
+
+    fn key_len(&self) -> usize {
+        match self {
+            Self::Aes128Gcm => 16,
+            Self::Aes256Gcm | Self::ChaCha20Poly1305 => 32,
+        }
+    }
+}
+
+/// An HPKE ciphersuite: a combination of KEM, KDF and AEAD.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Suite {
+    /// The key encapsulation mechanism.
+    pub kem: Kem,
+    /// The key derivation function.
+    pub kdf: Kdf,
+    /// The authenticated encryption algorithm.
+    pub aead: Aead,
+}
+
+impl Suite {
+    /// Set up an encryption context for sending to `recipient`, using the
+    /// default [`RandomSource`].
+    ///
+    /// See [`Suite::setup_sender_with_rng`].
+    #[cfg(feature = "std")]
+    pub fn setup_sender(
+        &self,
+        recipient: &[u8],
+        info: &[u8],
+        psk: Option<Psk<'_>>,
+    ) -> Result<(PublicKey, Context), Error> {
+        self.setup_sender_with_rng(&mut DefaultRandom, recipient, info, psk)
+    }
+
+    /// Set up an encryption context for sending to `recipient`, using `rng`.
+    ///
+    /// `recipient` is the recipient's serialized public key.  `info` is
+    /// application-supplied information.  If `psk` is supplied, this uses
+    /// the PSK mode; otherwise the base mode.
+    ///
+    /// The encapsulated key is returned, and must be sent to the recipient
+    /// alongside any ciphertexts.
+    ///
+    /// The ephemeral key is derived from `private_key_len` bytes from `rng`
+    /// using [`Kem::derive_key_pair`].
+    pub fn setup_sender_with_rng(
+        &self,
+        rng: &mut dyn RandomSource,
+        recipient: &[u8],
+        info: &[u8],
+        psk: Option<Psk<'_>>,
+    ) -> Result<(PublicKey, Context), Error> {
+        let _ = Entry::new_secret();
+        let (shared_secret, encapsulated_key) = self.kem.encapsulate(rng, recipient)?;
+        let context = self.key_schedule(shared_secret, info, psk)?;
+        Ok((encapsulated_key, context))
+    }
+
+    /// Set up a decryption context for receiving with `private_key`.
+    ///
+    /// `encapsulated_key` is the sender's encapsulated key.  `info` and `psk`
+    /// must match those used by the sender.
+    pub fn setup_receiver(
+        &self,
+        encapsulated_key: &[u8],
+        private_key: &[u8],
+        info: &[u8],
+        psk: Option<Psk<'_>>,
+    ) -> Result<Context, Error> {
+        let _ = Entry::new_secret();
+        let shared_secret = self.kem.decapsulate(encapsulated_key, private_key)?;
+        self.key_schedule(shared_secret, info, psk)
+    }
+
+    fn key_schedule(
+        &self,
+        mut shared_secret: HashOutput,
+        info: &[u8],
+        psk: Option<Psk<'_>>,
+    ) -> Result<Context, Error> {
+        let (mode, psk) = match psk {
+            Some(psk) if psk.key.is_empty() || psk.id.is_empty() => return Err(Error::WrongLength),
+            Some(psk) => (MODE_PSK, psk),
+            None => (MODE_BASE, Psk { key: &[], id: &[] }),
+        };
+
+        let suite_id = self.suite_id();
+        let kdf = self.kdf;
+        let psk_id_hash = kdf.labeled_extract(&suite_id, b"", b"psk_id_hash", psk.id);
+        let info_hash = kdf.labeled_extract(&suite_id, b"", b"info_hash", info);
+        let context = [&[mode], psk_id_hash.as_ref(), info_hash.as_ref()];
+
+        let mut secret = kdf.labeled_extract(&suite_id, shared_secret.as_ref(), b"secret", psk.key);
+        zeroise(shared_secret.as_mut());
+
+        let mut key = [0u8; 32];
+        let key = &mut key[..self.aead.key_len()];
+        kdf.labeled_expand(&suite_id, secret.as_ref(), b"key", &context, key)?;
+
+        let mut base_nonce = [0u8; 12];
+        kdf.labeled_expand(
+            &suite_id,
+            secret.as_ref(),
+            b"base_nonce",
+            &context,
+            &mut base_nonce,
+        )?;
+
+        let mut exporter_secret = kdf.zeroed_output();
+        kdf.labeled_expand(
+            &suite_id,
+            secret.as_ref(),
+            b"exp",
+            &context,
+            exporter_secret.as_mut(),
+        )?;
+        zeroise(secret.as_mut());
+
+        let key_obj = match self.aead {
+            Aead::Aes128Gcm | Aead::Aes256Gcm => ContextKey::AesGcm(AesGcm::new(key)),
+            Aead::ChaCha20Poly1305 => ContextKey::ChaCha20Poly1305(
+                chacha20poly1305::ChaCha20Poly1305::new(key.try_into().unwrap()),
+            ),
+        };
+        zeroise(key);
+
+        Ok(Context {
+            key: key_obj,
+            base_nonce,
+            seq: 0,
+            exporter_secret,
+            kdf,
+            suite_id,
+        })
+    }
+
+    fn suite_id(&self) -> [u8; 10] {
+        let kem = self.kem.id().to_be_bytes();
+        let kdf = self.kdf.id().to_be_bytes();
+        let aead = self.aead.id().to_be_bytes();
+        [
+            b'H', b'P', b'K', b'E', kem[0], kem[1], kdf[0], kdf[1], aead[0], aead[1],
+        ]
+    }
+}
+
+/// A pre-shared key, for use with the PSK mode.
+#[derive(Clone, Copy)]
+pub struct Psk<'a> {
+    /// The pre-shared key.  This must not be empty, and should have
+    /// at least 32 bytes of entropy.
+    pub key: &'a [u8],
+    /// An identifier for `key`.  This must not be empty.
+    pub id: &'a [u8],
+}
+
+/// An encryption context, for sending or receiving.
+///
+/// Each message sent or received uses a new nonce, so messages must be
+/// opened in the order they were sealed.
+pub struct Context {
+    key: ContextKey,
+    base_nonce: [u8; 12],
+    seq: u64,
+    exporter_secret: HashOutput,
+    kdf: Kdf,
+    suite_id: [u8; 10],
+}
+
+impl Context {
+    /// Encrypt the next message.
+    ///
+    /// On entry, `cipher_inout` contains the plaintext of the message.
+    /// `aad` is the additionally-authenticated data.  It may be empty.
+    ///
+    /// On exit, `cipher_inout` contains the ciphertext of the message,
+    /// and `tag_out` contains the authentication tag.  The ciphertext
+    /// is conventionally sent followed by the tag.
+    ///
+    /// Fails if the sequence number is exhausted.
+    pub fn seal(
+        &mut self,
+        aad: &[u8],
+        cipher_inout: &mut [u8],
+        tag_out: &mut [u8; 16],
+    ) -> Result<(), Error> {
+        let _ = Entry::new_secret();
+        let nonce = self.nonce()?;
+        match &self.key {
+            ContextKey::AesGcm(k) => k.encrypt(&nonce, aad, cipher_inout, tag_out),
+            ContextKey::ChaCha20Poly1305(k) => k.encrypt(&nonce, aad, cipher_inout, tag_out),
+        }
+        self.seq += 1;
+        Ok(())
+    }
+
+    /// Decrypt and verify the next message.
+    ///
+    /// On entry, `cipher_inout` contains the ciphertext of the message.
+    /// `aad` is the additionally-authenticated data.  It may be empty.
+    /// `tag` is the purported authentication tag.
+    ///
+    /// On success, `cipher_inout` contains the plaintext of the message,
+    /// and `Ok(())` is returned.  Otherwise, `Err(Error::DecryptFailed)` is
+    /// returned and `cipher_inout` is cleared; the next message is then
+    /// expected to be the same one.
+    pub fn open(&mut self, aad: &[u8], cipher_inout: &mut [u8], tag: &[u8]) -> Result<(), Error> {
+        let _ = Entry::new_secret();
+        let nonce = self.nonce()?;
+        match &self.key {
+            ContextKey::AesGcm(k) => k.decrypt(&nonce, aad, cipher_inout, tag),
+            ContextKey::ChaCha20Poly1305(k) => k.decrypt(&nonce, aad, cipher_inout, tag),
+        }?;
+        self.seq += 1;
+        Ok(())
+    }
+
+    /// Export a secret from this context.
+    ///
+    /// `exporter_context` distinguishes different uses of this function.
+    /// The secret is written to `out`, which must be no longer than
+    /// 255 times the KDF's output length.
+    pub fn export(&self, exporter_context: &[u8], out: &mut [u8]) -> Result<(), Error> {
+        let _ = Entry::new_secret();
+        self.kdf.labeled_expand(
+            &self.suite_id,
+            self.exporter_secret.as_ref(),
+            b"sec",
+            &[exporter_context],
+            out,
+        )
+    }
+
+    fn nonce(&self) -> Result<[u8; 12], Error> {
+        if self.seq == u64::MAX {
+            return Err(Error::OutOfRange);
+        }
+
+        let mut nonce = self.base_nonce;
+        for (n, s) in nonce[4..].iter_mut().zip(self.seq.to_be_bytes()) {
+            *n ^= s;
+        }
+        Ok(nonce)
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        zeroise(&mut self.base_nonce);
+        zeroise(self.exporter_secret.as_mut());
+    }
+}
+
+// Boxing the larger variant is not an option without alloc, and there is
+// only one of these per context.
+#[allow(clippy::large_enum_variant)]
+enum ContextKey {
+    AesGcm(AesGcm),
+    ChaCha20Poly1305(chacha20poly1305::ChaCha20Poly1305),
+}
+
+/// A serialized KEM public key, or encapsulated key.
+#[derive(Clone, Debug)]
+pub struct PublicKey {
+    buf: [u8; MAX_PUBLIC_KEY_LEN],
+    len: usize,
+}
+
+impl PublicKey {
+    fn new(bytes: &[u8]) -> Self {
+        let mut buf = [0u8; MAX_PUBLIC_KEY_LEN];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Self {
+            buf,
+            len: bytes.len(),
+        }
+    }
+}
+
+impl AsRef<[u8]> for PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// A serialized KEM private key.
+pub struct PrivateKey {
+    buf: [u8; MAX_PRIVATE_KEY_LEN],
+    len: usize,
+}
+
+impl PrivateKey {
+    fn new(bytes: &[u8]) -> Self {
+        let mut buf = [0u8; MAX_PRIVATE_KEY_LEN];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Self {
+            buf,
+            len: bytes.len(),
+        }
+    }
+}
+
+impl AsRef<[u8]> for PrivateKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl AsMut<[u8]> for PrivateKey {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[..self.len]
+    }
+}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        zeroise(&mut self.buf);
+    }
+}
+
+const MODE_BASE: u8 = 0x00;
+const MODE_PSK: u8 = 0x01;
+
+const MAX_PUBLIC_KEY_LEN: usize = 97;
+const MAX_PRIVATE_KEY_LEN: usize = 48;
@@ -0,0 +1,159 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! JSON Web Keys and JSON Web Signatures.
+//!
+//! See [RFC7517](https://datatracker.ietf.org/doc/html/rfc7517),
+//! [RFC7515](https://datatracker.ietf.org/doc/html/rfc7515) and
+//! [RFC7518](https://datatracker.ietf.org/doc/html/rfc7518).
+
+use super::base64::{self, Alphabet, Padding, Writer};
+use crate::error::Error;
+
+mod json;
+mod jwk;
+mod jws;
+
+pub use jwk::{Jwk, JwkSet, Key};
+pub use jws::{Algorithm, Jws, Signer, UnverifiedJws, Verifier};
+
+/// Decodes unpadded base64url `encoded` into `output`, returning the
+/// written prefix.
+///
+/// Whitespace and padding are not allowed.  See [`base64::decode()`].
+fn decode_base64url<'o>(encoded: &str, output: &'o mut [u8]) -> Result<&'o [u8], ()> {
+    match base64::decode(encoded.bytes(), Alphabet::UrlSafe, Padding::Omitted, output) {
+        Ok(len) => Ok(&output[..len]),
+        Err(_) => Err(()),
+    }
+}
+
+/// Returns the length of the data encoded by unpadded base64url `encoded`.
+fn decoded_len(encoded: &str) -> usize {
+    encoded.len() / 4 * 3 + encoded.len() % 4 * 3 / 4
+}
+
+/// Writes JSON text.
+impl Writer<'_> {
+    /// Writes the `"name":` part of an object member, preceded by
+    /// a comma if this isn't the first member.
+    fn name(&mut self, name: &str) -> Result<(), Error> {
+        if !matches!(self.written().last(), Some(b'{')) {
+            self.write(b",")?;
+        }
+        self.string(name)?;
+        self.write(b":")
+    }
+
+    /// Writes an object member with a string value.
+    fn string_member(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        self.name(name)?;
+        self.string(value)
+    }
+
+    /// Writes an object member with base64url-encoded `value`.
+    fn base64url_member(&mut self, name: &str, value: &[u8]) -> Result<(), Error> {
+        self.name(name)?;
+        self.write(b"\"")?;
+        self.base64url(value)?;
+        self.write(b"\"")
+    }
+
+    /// Writes `value` as a JSON string, escaping as required.
+    fn string(&mut self, value: &str) -> Result<(), Error> {
+        self.write(b"\"")?;
+        for c in value.bytes() {
+            match c {
+                b'"' | b'\\' => self.write(&[b'\\', c])?,
+                0x00..=0x1f => {
+                    let hex = b"0123456789abcdef";
+                    self.write(&[
+                        b'\\',
+                        b'u',
+                        b'0',
+                        b'0',
+                        hex[c as usize >> 4],
+                        hex[c as usize & 0xf],
+                    ])?
+                }
+                _ => self.write(&[c])?,
+            }
+        }
+        self.write(b"\"")
+    }
+
+    /// Writes unpadded base64url-encoded `data`.
+    fn base64url(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.base64(data, Alphabet::UrlSafe, Padding::Omitted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64url_roundtrip() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        let mut encoded = [0u8; 512];
+        let mut decoded = [0u8; 256];
+
+        for len in 0..data.len() {
+            let mut writer = Writer::new(&mut encoded);
+            let result = writer.base64url(&data[..len]);
+            let text = writer.finish(result).unwrap();
+            assert_eq!(text.len(), (len * 4 + 2) / 3);
+            assert!(!text.contains(['+', '/', '=']));
+            assert_eq!(decode_base64url(text, &mut decoded).unwrap(), &data[..len]);
+        }
+    }
+
+    #[test]
+    fn base64url_decoding() {
+        let mut output = [0u8; 8];
+        assert_eq!(decode_base64url("", &mut output).unwrap(), b"");
+        assert_eq!(decode_base64url("aGVsbG8", &mut output).unwrap(), b"hello");
+        assert_eq!(
+            decode_base64url("-_-_", &mut output).unwrap(),
+            b"\xfb\xff\xbf"
+        );
+
+        for bad in [
+            // padding
+            "aGVsbG8=", // standard alphabet
+            "+/+/",     // whitespace
+            "aGVs bG8", // impossible length
+            "aGVsb",    // non-zero trailing bits
+            "aGVsbG9", "aGVsbB",
+        ] {
+            assert_eq!(decode_base64url(bad, &mut output), Err(()), "{bad:?}");
+        }
+
+        assert_eq!(decode_base64url("aGVsbG8", &mut output[..4]), Err(()));
+        assert_eq!(
+            decode_base64url("aGVsbG8", &mut output[..5]).unwrap(),
+            b"hello"
+        );
+    }
+
+    #[test]
+    fn writer() {
+        let mut output = [0u8; 64];
+        let mut writer = Writer::new(&mut output);
+        let result = writer
+            .write(b"{")
+            .and_then(|()| writer.string_member("a", "q\"b\\\n\x01é"))
+            .and_then(|()| writer.base64url_member("b", b"hello"))
+            .and_then(|()| writer.write(b"}"));
+        assert_eq!(
+            writer.finish(result).unwrap(),
+            r#"{"a":"q\"b\\\u000a\u0001é","b":"aGVsbG8"}"#
+        );
+
+        let mut output = [0u8; 16];
+        let mut writer = Writer::new(&mut output);
+        let result = writer.string("a longer string than fits");
+        assert_eq!(writer.finish(result), Err(Error::WrongLength));
+        assert_eq!(output, [0u8; 16]);
+    }
+}
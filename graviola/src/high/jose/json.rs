@@ -0,0 +1,409 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! A minimal JSON parser, sufficient for JWKs and JWS headers.
+//!
+//! See [RFC8259](https://datatracker.ietf.org/doc/html/rfc8259).
+//!
+//! Nothing is allocated: values are validated but not converted.
+//! Strings are returned as the text between their quotes, and objects
+//! and arrays as their complete text, which is parsed again when
+//! iterating over their contents.
+
+/// A validated JSON object.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Object<'a> {
+    text: &'a str,
+}
+
+impl<'a> Object<'a> {
+    /// Parses `text`, which must contain exactly one JSON object.
+    ///
+    /// Whitespace is allowed before and after the object.
+    pub(crate) fn parse(text: &'a str) -> Result<Self, ()> {
+        match Parser::new(text).document()? {
+            Value::Object(object) => Ok(object),
+            _ => Err(()),
+        }
+    }
+
+    /// Returns the members of this object, in order.
+    ///
+    /// Duplicate names are not detected here.
+    pub(crate) fn members(&self) -> Members<'a> {
+        let mut parser = Parser::new(self.text);
+        parser.pos = 1;
+        Members(parser)
+    }
+}
+
+/// A validated JSON array.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Array<'a> {
+    text: &'a str,
+}
+
+impl<'a> Array<'a> {
+    /// Returns the elements of this array, in order.
+    pub(crate) fn elements(&self) -> Elements<'a> {
+        let mut parser = Parser::new(self.text);
+        parser.pos = 1;
+        Elements(parser)
+    }
+}
+
+/// A validated JSON value.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Value<'a> {
+    /// A string.  This is the text between the quotes, and may
+    /// contain escape sequences.
+    String(&'a str),
+    Object(Object<'a>),
+    Array(Array<'a>),
+    /// A number, `true`, `false` or `null`.
+    Other,
+}
+
+impl<'a> Value<'a> {
+    /// Returns the contents of a string without escape sequences.
+    ///
+    /// `None` is returned for other values, and for strings containing
+    /// escape sequences.  The strings this module is interested in
+    /// are names and base64url data, which never need escaping.
+    pub(crate) fn as_str(&self) -> Option<&'a str> {
+        match *self {
+            Self::String(s) if !s.contains('\\') => Some(s),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct Members<'a>(Parser<'a>);
+
+impl<'a> Iterator for Members<'a> {
+    type Item = (&'a str, Value<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // nb. the object was validated on construction, so errors
+        // are not possible here.
+        let p = &mut self.0;
+        p.skip_whitespace();
+        if p.eat(b',') {
+            p.skip_whitespace();
+        }
+        let name = p.string().ok()?;
+        p.skip_whitespace();
+        p.expect(b':').ok()?;
+        let value = p.value(0).ok()?;
+        Some((name, value))
+    }
+}
+
+pub(crate) struct Elements<'a>(Parser<'a>);
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = Value<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // nb. as for `Members`, the array was already validated.
+        let p = &mut self.0;
+        p.skip_whitespace();
+        if p.eat(b',') {
+            p.skip_whitespace();
+        }
+        match p.peek()? {
+            b']' => None,
+            _ => p.value(0).ok(),
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn document(&mut self) -> Result<Value<'a>, ()> {
+        let value = self.value(0)?;
+        self.skip_whitespace();
+        if self.pos == self.text.len() {
+            Ok(value)
+        } else {
+            Err(())
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value<'a>, ()> {
+        if depth > MAX_DEPTH {
+            return Err(());
+        }
+
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek().ok_or(())? {
+            b'"' => return self.string().map(Value::String),
+            b'{' => {
+                self.object(depth)?;
+                return Ok(Value::Object(Object {
+                    text: &self.text[start..self.pos],
+                }));
+            }
+            b'[' => {
+                self.array(depth)?;
+                return Ok(Value::Array(Array {
+                    text: &self.text[start..self.pos],
+                }));
+            }
+            b't' => self.literal("true")?,
+            b'f' => self.literal("false")?,
+            b'n' => self.literal("null")?,
+            b'-' | b'0'..=b'9' => self.number()?,
+            _ => return Err(()),
+        }
+        Ok(Value::Other)
+    }
+
+    fn object(&mut self, depth: usize) -> Result<(), ()> {
+        self.expect(b'{')?;
+        self.skip_whitespace();
+        if self.eat(b'}') {
+            return Ok(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            self.string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            self.value(depth + 1)?;
+            self.skip_whitespace();
+            match self.next().ok_or(())? {
+                b',' => continue,
+                b'}' => return Ok(()),
+                _ => return Err(()),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<(), ()> {
+        self.expect(b'[')?;
+        self.skip_whitespace();
+        if self.eat(b']') {
+            return Ok(());
+        }
+
+        loop {
+            self.value(depth + 1)?;
+            self.skip_whitespace();
+            match self.next().ok_or(())? {
+                b',' => continue,
+                b']' => return Ok(()),
+                _ => return Err(()),
+            }
+        }
+    }
+
+    /// Parses a string, returning the text between the quotes.
+    fn string(&mut self) -> Result<&'a str, ()> {
+        self.expect(b'"')?;
+        let start = self.pos;
+
+        loop {
+            match self.next().ok_or(())? {
+                b'"' => return Ok(&self.text[start..self.pos - 1]),
+                b'\\' => match self.next().ok_or(())? {
+                    b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => {}
+                    b'u' => {
+                        for _ in 0..4 {
+                            if !self.next().ok_or(())?.is_ascii_hexdigit() {
+                                return Err(());
+                            }
+                        }
+                    }
+                    _ => return Err(()),
+                },
+                0x00..=0x1f => return Err(()),
+                _ => {}
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<(), ()> {
+        self.eat(b'-');
+        if !self.eat(b'0') {
+            self.digits()?;
+        }
+        if self.eat(b'.') {
+            self.digits()?;
+        }
+        if self.eat(b'e') || self.eat(b'E') {
+            if !self.eat(b'+') {
+                self.eat(b'-');
+            }
+            self.digits()?;
+        }
+        Ok(())
+    }
+
+    /// Parses one or more decimal digits.
+    fn digits(&mut self) -> Result<(), ()> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        if self.pos > start {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn literal(&mut self, literal: &str) -> Result<(), ()> {
+        if !self.text[self.pos..].starts_with(literal) {
+            return Err(());
+        }
+        self.pos += literal.len();
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), ()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() != Some(c) {
+            return false;
+        }
+        self.pos += 1;
+        true
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+}
+
+/// The deepest nesting of objects and arrays accepted.
+const MAX_DEPTH: usize = 16;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid() {
+        for text in [
+            "{}",
+            " { } ",
+            r#"{"a":1}"#,
+            r#"{"a": -0.5e+10, "b": [true, false, null, {}, []], "c": {"d": "e"}}"#,
+            r#"{"esc": "\"\\\/\b\f\n\r\té", "utf8": "é"}"#,
+            "{\"a\":\r\n\t[ 1 , 2 ]\n}",
+        ] {
+            Object::parse(text).unwrap();
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let deep = "[".repeat(MAX_DEPTH + 1) + &"]".repeat(MAX_DEPTH + 1);
+        let deep = format!(r#"{{"a":{deep}}}"#);
+
+        for text in [
+            "",
+            "[]",
+            "\"a\"",
+            "{",
+            "}",
+            "{} {}",
+            "{}x",
+            r#"{"a"}"#,
+            r#"{"a":}"#,
+            r#"{"a":1,}"#,
+            r#"{,"a":1}"#,
+            r#"{"a":1 "b":2}"#,
+            r#"{a:1}"#,
+            r#"{'a':1}"#,
+            r#"{"a":[1,]}"#,
+            r#"{"a":[,1]}"#,
+            r#"{"a":01}"#,
+            r#"{"a":1.}"#,
+            r#"{"a":.5}"#,
+            r#"{"a":1e}"#,
+            r#"{"a":+1}"#,
+            r#"{"a":-}"#,
+            r#"{"a":tru}"#,
+            r#"{"a":nul}"#,
+            r#"{"a":"\x"}"#,
+            r#"{"a":"\u12"}"#,
+            r#"{"a":"\u12g4"}"#,
+            "{\"a\":\"\n\"}",
+            r#"{"a":"unterminated}"#,
+            &deep,
+        ] {
+            assert!(Object::parse(text).is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn members() {
+        let object = Object::parse(
+            r#" {"s": "v", "esc": "a\"b", "o": {"x": [1, {"y": 2}]}, "a": [ "p" , "q" ], "n": 12 } "#,
+        )
+        .unwrap();
+
+        let mut members = object.members();
+        let (name, value) = members.next().unwrap();
+        assert_eq!((name, value.as_str()), ("s", Some("v")));
+
+        let (name, value) = members.next().unwrap();
+        assert!(matches!(value, Value::String(r#"a\"b"#)));
+        assert_eq!((name, value.as_str()), ("esc", None));
+
+        let (name, value) = members.next().unwrap();
+        assert_eq!(name, "o");
+        let Value::Object(inner) = value else {
+            panic!("not an object: {value:?}");
+        };
+        assert_eq!(inner.text, r#"{"x": [1, {"y": 2}]}"#);
+        let (name, value) = inner.members().next().unwrap();
+        assert_eq!(name, "x");
+        assert!(matches!(value, Value::Array(_)));
+
+        let (name, value) = members.next().unwrap();
+        assert_eq!(name, "a");
+        let Value::Array(array) = value else {
+            panic!("not an array: {value:?}");
+        };
+        let elements: Vec<_> = array.elements().map(|e| e.as_str()).collect();
+        assert_eq!(elements, [Some("p"), Some("q")]);
+
+        let (name, value) = members.next().unwrap();
+        assert_eq!(name, "n");
+        assert!(matches!(value, Value::Other));
+
+        assert!(members.next().is_none());
+        assert!(Object::parse("{}").unwrap().members().next().is_none());
+    }
+}
@@ -0,0 +1,918 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! JSON Web Keys.
+//!
+//! See [RFC7517](https://datatracker.ietf.org/doc/html/rfc7517), and
+//! [RFC7518 section 6](https://datatracker.ietf.org/doc/html/rfc7518#section-6)
+//! and [RFC8037](https://datatracker.ietf.org/doc/html/rfc8037) for the
+//! key types.
+
+#[cfg(feature = "rsa")]
+use core::mem;
+
+use super::{decode_base64url, json, Writer};
+#[cfg(any(feature = "p256", feature = "p384"))]
+use crate::high::asn1::oid;
+#[cfg(feature = "rsa")]
+use crate::high::asn1::{self, pkix, Type};
+#[cfg(feature = "p256")]
+use crate::high::curve::P256;
+#[cfg(feature = "p384")]
+use crate::high::curve::P384;
+#[cfg(any(feature = "p256", feature = "p384"))]
+use crate::high::curve::{Curve, PrivateKey, PublicKey, Scalar, MAX_SCALAR_LEN};
+#[cfg(any(feature = "p256", feature = "p384"))]
+use crate::high::ecdsa;
+#[cfg(feature = "rsa")]
+use crate::high::rsa;
+use crate::low::{zeroise, Entry};
+#[cfg(feature = "x25519")]
+use crate::mid::x25519;
+#[cfg(feature = "rsa")]
+use crate::mid::{rsa_priv, rsa_pub};
+use crate::{Error, KeyFormatError};
+
+/// A JSON Web Key.
+///
+/// This is parsed from JSON by [`Self::parse()`], or found in a
+/// [`JwkSet`].  Parsing only checks the JSON syntax and the types of the
+/// members this library understands.  The key itself is decoded and
+/// checked by one of the `to_*` functions, which also check that `kty`
+/// (and `crv`, where relevant) match the requested key type.
+///
+/// Members not listed in the `to_*` functions are ignored, as RFC7517
+/// requires.  In particular, `use`, `key_ops` and `alg` are not
+/// enforced: they are available via [`Self::public_key_use()`] and
+/// [`Self::algorithm()`] for callers that wish to check them.
+///
+/// To encode a key as a JWK, see [`Key`].
+#[derive(Clone, Copy)]
+pub struct Jwk<'a> {
+    key_type: &'a str,
+    curve: Option<&'a str>,
+    key_id: Option<&'a str>,
+    algorithm: Option<&'a str>,
+    public_key_use: Option<&'a str>,
+
+    x: Option<&'a str>,
+    y: Option<&'a str>,
+    d: Option<&'a str>,
+
+    n: Option<&'a str>,
+    e: Option<&'a str>,
+    p: Option<&'a str>,
+    q: Option<&'a str>,
+    dp: Option<&'a str>,
+    dq: Option<&'a str>,
+    qi: Option<&'a str>,
+    other_primes: bool,
+}
+
+impl<'a> Jwk<'a> {
+    /// Parses a JSON Web Key.
+    ///
+    /// `json` must be a single JSON object with a string `kty` member.
+    /// Every understood member must be a string without escape sequences,
+    /// and must not be repeated.  Otherwise,
+    /// [`crate::KeyFormatError::InvalidJwk`] is returned.
+    pub fn parse(json: &'a str) -> Result<Self, Error> {
+        let _ = Entry::new_secret();
+        json::Object::parse(json)
+            .map_err(|()| KeyFormatError::InvalidJwk.into())
+            .and_then(Self::from_object)
+    }
+
+    fn from_object(object: json::Object<'a>) -> Result<Self, Error> {
+        let mut key_type = None;
+        let mut jwk = Self {
+            key_type: "",
+            curve: None,
+            key_id: None,
+            algorithm: None,
+            public_key_use: None,
+            x: None,
+            y: None,
+            d: None,
+            n: None,
+            e: None,
+            p: None,
+            q: None,
+            dp: None,
+            dq: None,
+            qi: None,
+            other_primes: false,
+        };
+
+        for (name, value) in object.members() {
+            let member = match name {
+                "kty" => &mut key_type,
+                "crv" => &mut jwk.curve,
+                "kid" => &mut jwk.key_id,
+                "alg" => &mut jwk.algorithm,
+                "use" => &mut jwk.public_key_use,
+                "x" => &mut jwk.x,
+                "y" => &mut jwk.y,
+                "d" => &mut jwk.d,
+                "n" => &mut jwk.n,
+                "e" => &mut jwk.e,
+                "p" => &mut jwk.p,
+                "q" => &mut jwk.q,
+                "dp" => &mut jwk.dp,
+                "dq" => &mut jwk.dq,
+                "qi" => &mut jwk.qi,
+                "oth" => {
+                    jwk.other_primes = true;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if member.is_some() {
+                return Err(KeyFormatError::InvalidJwk.into());
+            }
+            *member = Some(value.as_str().ok_or(KeyFormatError::InvalidJwk)?);
+        }
+
+        jwk.key_type = key_type.ok_or(KeyFormatError::InvalidJwk)?;
+        Ok(jwk)
+    }
+
+    /// Returns the key type (`kty`): for example, `RSA`, `EC` or `OKP`.
+    pub fn key_type(&self) -> &'a str {
+        self.key_type
+    }
+
+    /// Returns the key ID (`kid`), if present.
+    pub fn key_id(&self) -> Option<&'a str> {
+        self.key_id
+    }
+
+    /// Returns the algorithm (`alg`) the key is intended for, if present.
+    pub fn algorithm(&self) -> Option<&'a str> {
+        self.algorithm
+    }
+
+    /// Returns the intended use (`use`) of the key, if present: for
+    /// example, `sig` or `enc`.
+    pub fn public_key_use(&self) -> Option<&'a str> {
+        self.public_key_use
+    }
+
+    /// Returns true if this JWK contains a private key.
+    pub fn is_private(&self) -> bool {
+        self.d.is_some()
+    }
+
+    /// Decodes an RSA public key.
+    ///
+    /// `kty` must be `RSA`, and `n` and `e` are used.
+    #[cfg(feature = "rsa")]
+    pub fn to_rsa_verifying_key(&self) -> Result<rsa::VerifyingKey, Error> {
+        let _ = Entry::new_public();
+        self.check_key_type("RSA", None)?;
+
+        let mut parts = [0u8; rsa_pub::MAX_PARTS_LEN];
+        let mut parts = &mut parts[..];
+        let key = pkix::RSAPublicKey {
+            modulus: decode_integer(self.n, &mut parts)?,
+            publicExponent: decode_integer(self.e, &mut parts)?,
+        };
+
+        let mut der = [0u8; rsa::MAX_SPKI_LEN];
+        let len = key
+            .encode(&mut asn1::Encoder::new(&mut der))
+            .map_err(Error::Asn1Error)?;
+        rsa::VerifyingKey::from_pkcs1_der(&der[..len])
+    }
+
+    /// Decodes an RSA private key.
+    ///
+    /// `kty` must be `RSA`.  `n`, `e`, `d`, `p`, `q`, `dp`, `dq` and
+    /// `qi` are used, and are checked against each other as described in
+    /// [`rsa::SigningKey::from_pkcs1_der()`].  Keys with more than two
+    /// primes (that is, with an `oth` member) are not supported and
+    /// result in [`crate::KeyFormatError::InvalidJwk`].
+    #[cfg(feature = "rsa")]
+    pub fn to_rsa_signing_key(&self) -> Result<rsa::SigningKey, Error> {
+        let _ = Entry::new_secret();
+        self.check_key_type("RSA", None)?;
+
+        if self.other_primes {
+            return Err(KeyFormatError::InvalidJwk.into());
+        }
+
+        let mut parts = [0u8; rsa_priv::MAX_PARTS_LEN];
+        let mut der = [0u8; rsa::MAX_PKCS8_LEN];
+        let result = self
+            .encode_rsa_private_key(&mut parts, &mut der)
+            .and_then(rsa::SigningKey::from_pkcs1_der);
+        zeroise(&mut parts);
+        zeroise(&mut der);
+        result
+    }
+
+    #[cfg(feature = "rsa")]
+    fn encode_rsa_private_key<'d>(
+        &self,
+        mut parts: &mut [u8],
+        der: &'d mut [u8],
+    ) -> Result<&'d [u8], Error> {
+        let len = pkix::RSAPrivateKey {
+            version: pkix::Version::two_prime,
+            modulus: decode_integer(self.n, &mut parts)?,
+            publicExponent: decode_integer(self.e, &mut parts)?,
+            privateExponent: decode_integer(self.d, &mut parts)?,
+            prime1: decode_integer(self.p, &mut parts)?,
+            prime2: decode_integer(self.q, &mut parts)?,
+            exponent1: decode_integer(self.dp, &mut parts)?,
+            exponent2: decode_integer(self.dq, &mut parts)?,
+            coefficient: decode_integer(self.qi, &mut parts)?,
+        }
+        .encode(&mut asn1::Encoder::new(der))
+        .map_err(Error::Asn1Error)?;
+        Ok(&der[..len])
+    }
+
+    /// Decodes an ECDSA public key on curve `C`.
+    ///
+    /// `kty` must be `EC`, and `crv` must name `C` (`P-256` or `P-384`).
+    /// `x` and `y` are used, and must be the full length of a field element.
+    #[cfg(any(feature = "p256", feature = "p384"))]
+    pub fn to_ecdsa_verifying_key<C: Curve>(&self) -> Result<ecdsa::VerifyingKey<C>, Error> {
+        let _ = Entry::new_public();
+        self.check_key_type("EC", Some(curve_name::<C>()))?;
+
+        let mut point = [0u8; MAX_SCALAR_LEN * 2 + 1];
+        let point = self.decode_point::<C>(&mut point)?;
+        ecdsa::VerifyingKey::from_x962_uncompressed(point)
+    }
+
+    /// Decodes an ECDSA private key on curve `C`.
+    ///
+    /// As well as the members used by [`Self::to_ecdsa_verifying_key()`],
+    /// `d` is used.  It must be the full length of a scalar, and `x` and
+    /// `y` must be its public key, else
+    /// [`crate::KeyFormatError::MismatchedJwkPublicKey`] is returned.
+    #[cfg(any(feature = "p256", feature = "p384"))]
+    pub fn to_ecdsa_signing_key<C: Curve>(&self) -> Result<ecdsa::SigningKey<C>, Error> {
+        let _ = Entry::new_secret();
+        self.check_key_type("EC", Some(curve_name::<C>()))?;
+
+        let mut point = [0u8; MAX_SCALAR_LEN * 2 + 1];
+        let point = self.decode_point::<C>(&mut point)?;
+
+        let mut scalar = [0u8; MAX_SCALAR_LEN];
+        let result = decode_fixed(self.d, &mut scalar[..C::Scalar::LEN_BYTES])
+            .and_then(|()| C::PrivateKey::from_bytes(&scalar[..C::Scalar::LEN_BYTES]));
+        zeroise(&mut scalar);
+        let private_key = result?;
+
+        let mut public_key = [0u8; MAX_SCALAR_LEN * 2 + 1];
+        if private_key.public_key_encode_uncompressed(&mut public_key)? != point {
+            return Err(KeyFormatError::MismatchedJwkPublicKey.into());
+        }
+
+        Ok(ecdsa::SigningKey { private_key })
+    }
+
+    /// Decodes `x` and `y` into an X9.62 uncompressed point.
+    #[cfg(any(feature = "p256", feature = "p384"))]
+    fn decode_point<'p, C: Curve>(&self, point: &'p mut [u8]) -> Result<&'p [u8], Error> {
+        let len = C::Scalar::LEN_BYTES;
+        let point = &mut point[..len * 2 + 1];
+        point[0] = 0x04;
+        decode_fixed(self.x, &mut point[1..len + 1])?;
+        decode_fixed(self.y, &mut point[len + 1..])?;
+        Ok(point)
+    }
+
+    /// Decodes an X25519 public key.
+    ///
+    /// `kty` must be `OKP`, `crv` must be `X25519`, and `x` is used.
+    #[cfg(feature = "x25519")]
+    pub fn to_x25519_public_key(&self) -> Result<x25519::PublicKey, Error> {
+        let _ = Entry::new_public();
+        self.check_key_type("OKP", Some("X25519"))?;
+
+        let mut x = [0u8; 32];
+        decode_fixed(self.x, &mut x)?;
+        Ok(x25519::PublicKey::from_array(&x))
+    }
+
+    /// Decodes an X25519 private key.
+    ///
+    /// As well as `x`, `d` is used.  `x` must be its public key,
+    /// else [`crate::KeyFormatError::MismatchedJwkPublicKey`] is returned.
+    #[cfg(feature = "x25519")]
+    pub fn to_x25519_private_key(&self) -> Result<x25519::PrivateKey, Error> {
+        let _ = Entry::new_secret();
+        let public_key = self.to_x25519_public_key()?;
+
+        let mut d = [0u8; 32];
+        let result = decode_fixed(self.d, &mut d).map(|()| x25519::PrivateKey::from_array(&d));
+        zeroise(&mut d);
+        let private_key = result?;
+
+        if private_key.public_key().as_bytes() != public_key.as_bytes() {
+            return Err(KeyFormatError::MismatchedJwkPublicKey.into());
+        }
+        Ok(private_key)
+    }
+
+    fn check_key_type(&self, key_type: &str, curve: Option<&str>) -> Result<(), Error> {
+        if self.key_type != key_type || (curve.is_some() && self.curve != curve) {
+            return Err(KeyFormatError::MismatchedJwkKeyType.into());
+        }
+        Ok(())
+    }
+}
+
+/// A JSON Web Key Set.
+///
+/// This is a JSON object with a `keys` member, whose value is an array
+/// of JWKs.  See
+/// [RFC7517 section 5](https://datatracker.ietf.org/doc/html/rfc7517#section-5).
+#[derive(Clone, Copy)]
+pub struct JwkSet<'a> {
+    keys: json::Array<'a>,
+}
+
+impl<'a> JwkSet<'a> {
+    /// Parses a JSON Web Key Set.
+    ///
+    /// `json` must be a single JSON object, with exactly one `keys`
+    /// member that is an array.  Otherwise,
+    /// [`crate::KeyFormatError::InvalidJwk`] is returned.  The keys
+    /// themselves are parsed by [`Self::keys()`].
+    pub fn parse(json: &'a str) -> Result<Self, Error> {
+        let _ = Entry::new_secret();
+        let object = json::Object::parse(json).map_err(|()| KeyFormatError::InvalidJwk)?;
+
+        let mut keys = None;
+        for (name, value) in object.members() {
+            match (name, value) {
+                ("keys", json::Value::Array(array)) if keys.is_none() => keys = Some(array),
+                ("keys", _) => return Err(KeyFormatError::InvalidJwk.into()),
+                _ => {}
+            }
+        }
+
+        keys.map(|keys| Self { keys })
+            .ok_or(KeyFormatError::InvalidJwk.into())
+    }
+
+    /// Returns the keys in this set, in order.
+    ///
+    /// Each key is parsed as for [`Jwk::parse()`].  An error for one key
+    /// does not prevent the others being used.
+    pub fn keys(&self) -> impl Iterator<Item = Result<Jwk<'a>, Error>> + 'a {
+        self.keys.elements().map(|value| match value {
+            json::Value::Object(object) => Jwk::from_object(object),
+            _ => Err(KeyFormatError::InvalidJwk.into()),
+        })
+    }
+
+    /// Returns the first key in this set with the key ID (`kid`) `key_id`.
+    ///
+    /// Keys that fail to parse are skipped.
+    pub fn find(&self, key_id: &str) -> Option<Jwk<'a>> {
+        self.keys()
+            .filter_map(Result::ok)
+            .find(|jwk| jwk.key_id == Some(key_id))
+    }
+}
+
+/// A key to be encoded as a JSON Web Key.
+///
+/// Use [`Self::to_jwk()`] to encode it.
+#[derive(Clone, Copy)]
+pub enum Key<'a> {
+    /// An ECDSA P-256 public key.
+    #[cfg(feature = "p256")]
+    EcdsaP256Public(&'a ecdsa::VerifyingKey<P256>),
+
+    /// An ECDSA P-256 private key.
+    #[cfg(feature = "p256")]
+    EcdsaP256Private(&'a ecdsa::SigningKey<P256>),
+
+    /// An ECDSA P-384 public key.
+    #[cfg(feature = "p384")]
+    EcdsaP384Public(&'a ecdsa::VerifyingKey<P384>),
+
+    /// An ECDSA P-384 private key.
+    #[cfg(feature = "p384")]
+    EcdsaP384Private(&'a ecdsa::SigningKey<P384>),
+
+    /// An RSA public key.
+    #[cfg(feature = "rsa")]
+    RsaPublic(&'a rsa::VerifyingKey),
+
+    /// An RSA private key.
+    #[cfg(feature = "rsa")]
+    RsaPrivate(&'a rsa::SigningKey),
+
+    /// An X25519 public key.
+    #[cfg(feature = "x25519")]
+    X25519Public(&'a x25519::PublicKey),
+
+    /// An X25519 private key.
+    #[cfg(feature = "x25519")]
+    X25519Private(&'a x25519::PrivateKey),
+}
+
+impl Key<'_> {
+    /// Encodes this key as a JSON Web Key.
+    ///
+    /// The JWK contains `kty`, the members for the key type, and then
+    /// `kid` if `key_id` is given.  Private keys include their public key
+    /// members, and RSA private keys include the CRT members.  No
+    /// whitespace is included.
+    ///
+    /// The encoding is written to the front of `output`, and that prefix
+    /// is returned.  [`Error::WrongLength`] is returned if `output` is
+    /// too short.
+    pub fn to_jwk<'o>(&self, key_id: Option<&str>, output: &'o mut [u8]) -> Result<&'o str, Error> {
+        let _ = Entry::new_secret();
+        let mut writer = Writer::new(output);
+        let result = writer
+            .write(b"{")
+            .and_then(|()| self.write_members(&mut writer))
+            .and_then(|()| match key_id {
+                Some(key_id) => writer.string_member("kid", key_id),
+                None => Ok(()),
+            })
+            .and_then(|()| writer.write(b"}"));
+        writer.finish(result)
+    }
+
+    fn write_members(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::EcdsaP256Public(key) => write_ec_public::<P256>(&key.public_key, writer),
+            #[cfg(feature = "p256")]
+            Self::EcdsaP256Private(key) => write_ec_private::<P256>(&key.private_key, writer),
+            #[cfg(feature = "p384")]
+            Self::EcdsaP384Public(key) => write_ec_public::<P384>(&key.public_key, writer),
+            #[cfg(feature = "p384")]
+            Self::EcdsaP384Private(key) => write_ec_private::<P384>(&key.private_key, writer),
+            #[cfg(feature = "rsa")]
+            Self::RsaPublic(key) => {
+                let mut der = [0u8; rsa::MAX_SPKI_LEN];
+                let der = key.encode_pkcs1(&mut der)?;
+                let key = pkix::RSAPublicKey::from_bytes(der).map_err(Error::Asn1Error)?;
+                writer.string_member("kty", "RSA")?;
+                write_integer(writer, "n", &key.modulus)?;
+                write_integer(writer, "e", &key.publicExponent)
+            }
+            #[cfg(feature = "rsa")]
+            Self::RsaPrivate(key) => {
+                let mut der = [0u8; rsa::MAX_PKCS8_LEN];
+                let result = key
+                    .encode_pkcs1(&mut der)
+                    .and_then(|der| write_rsa_private(der, writer));
+                zeroise(&mut der);
+                result
+            }
+            #[cfg(feature = "x25519")]
+            Self::X25519Public(key) => {
+                writer.string_member("kty", "OKP")?;
+                writer.string_member("crv", "X25519")?;
+                writer.base64url_member("x", &key.as_bytes())
+            }
+            #[cfg(feature = "x25519")]
+            Self::X25519Private(key) => {
+                Key::X25519Public(&key.public_key()).write_members(writer)?;
+                let mut d = key.as_bytes();
+                let result = writer.base64url_member("d", &d);
+                zeroise(&mut d);
+                result
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "p256", feature = "p384"))]
+fn write_ec_public<C: Curve>(key: &C::PublicKey, writer: &mut Writer<'_>) -> Result<(), Error> {
+    let mut point = [0u8; MAX_SCALAR_LEN * 2 + 1];
+    let point = key.encode_uncompressed(&mut point)?;
+    let (x, y) = point[1..].split_at(C::Scalar::LEN_BYTES);
+
+    writer.string_member("kty", "EC")?;
+    writer.string_member("crv", curve_name::<C>())?;
+    writer.base64url_member("x", x)?;
+    writer.base64url_member("y", y)
+}
+
+#[cfg(any(feature = "p256", feature = "p384"))]
+fn write_ec_private<C: Curve>(key: &C::PrivateKey, writer: &mut Writer<'_>) -> Result<(), Error> {
+    let mut point = [0u8; MAX_SCALAR_LEN * 2 + 1];
+    let point = key.public_key_encode_uncompressed(&mut point)?;
+    write_ec_public::<C>(&C::PublicKey::from_x962_uncompressed(point)?, writer)?;
+
+    let mut d = [0u8; MAX_SCALAR_LEN];
+    let result = key
+        .encode(&mut d)
+        .and_then(|d| writer.base64url_member("d", d));
+    zeroise(&mut d);
+    result
+}
+
+#[cfg(feature = "rsa")]
+fn write_rsa_private(der: &[u8], writer: &mut Writer<'_>) -> Result<(), Error> {
+    let key = pkix::RSAPrivateKey::from_bytes(der).map_err(Error::Asn1Error)?;
+    writer.string_member("kty", "RSA")?;
+    for (name, value) in [
+        ("n", &key.modulus),
+        ("e", &key.publicExponent),
+        ("d", &key.privateExponent),
+        ("p", &key.prime1),
+        ("q", &key.prime2),
+        ("dp", &key.exponent1),
+        ("dq", &key.exponent2),
+        ("qi", &key.coefficient),
+    ] {
+        write_integer(writer, name, value)?;
+    }
+    Ok(())
+}
+
+/// Writes a non-negative `value` in the minimal number of bytes, as
+/// required for JWK's `Base64urlUInt`.
+#[cfg(feature = "rsa")]
+fn write_integer(
+    writer: &mut Writer<'_>,
+    name: &str,
+    value: &asn1::Integer<'_>,
+) -> Result<(), Error> {
+    let bytes = value.as_ref();
+    let bytes = match bytes {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        _ => bytes,
+    };
+    writer.base64url_member(name, bytes)
+}
+
+/// Decodes the `Base64urlUInt` `value` to the front of `parts`, and
+/// advances `parts`.
+///
+/// A leading zero byte is included, so the result is always a
+/// non-negative integer.
+#[cfg(feature = "rsa")]
+fn decode_integer<'p>(
+    value: Option<&str>,
+    parts: &mut &'p mut [u8],
+) -> Result<asn1::Integer<'p>, Error> {
+    let value = value.ok_or(KeyFormatError::InvalidJwk)?;
+    let buf = mem::take(parts);
+    let (zero, rest) = buf.split_first_mut().ok_or(KeyFormatError::InvalidJwk)?;
+    *zero = 0;
+    let len = decode_base64url(value, rest)
+        .map_err(|()| KeyFormatError::InvalidJwk)?
+        .len();
+    let (part, rest) = buf.split_at_mut(len + 1);
+    *parts = rest;
+    Ok(asn1::Integer::from_bytes(part))
+}
+
+/// Decodes base64url `value`, which must fill `output` exactly.
+#[cfg(any(feature = "p256", feature = "p384", feature = "x25519"))]
+fn decode_fixed(value: Option<&str>, output: &mut [u8]) -> Result<(), Error> {
+    let value = value.ok_or(KeyFormatError::InvalidJwk)?;
+    match decode_base64url(value, output).map(|decoded| decoded.len()) {
+        Ok(len) if len == output.len() => Ok(()),
+        _ => {
+            zeroise(output);
+            Err(KeyFormatError::InvalidJwk.into())
+        }
+    }
+}
+
+/// The JWK `crv` for the curve `C`.
+#[cfg(any(feature = "p256", feature = "p384"))]
+fn curve_name<C: Curve>() -> &'static str {
+    let oid = C::oid();
+    #[cfg(feature = "p256")]
+    if oid == oid::id_prime256v1 {
+        return "P-256";
+    }
+    #[cfg(feature = "p384")]
+    if oid == oid::secp384r1 {
+        return "P-384";
+    }
+    unreachable!("unexpected curve {oid:?}")
+}
+
+#[cfg(all(
+    test,
+    feature = "p256",
+    feature = "p384",
+    feature = "rsa",
+    feature = "x25519"
+))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ecdsa_p256() {
+        let json = include_str!("secp256r1.jwk.json");
+        let jwk = Jwk::parse(json).unwrap();
+        assert_eq!(jwk.key_type(), "EC");
+        assert!(jwk.is_private());
+
+        let key = jwk.to_ecdsa_signing_key::<P256>().unwrap();
+        let expected = ecdsa::SigningKey::<P256>::from_pkcs8_der(include_bytes!(
+            "../ecdsa/secp256r1.pkcs8.der"
+        ))
+        .unwrap();
+        check_same_pkcs8(&key, &expected);
+
+        let mut output = [0u8; 256];
+        assert_eq!(
+            Key::EcdsaP256Private(&key)
+                .to_jwk(None, &mut output)
+                .unwrap(),
+            json.trim_end()
+        );
+
+        let public_key = jwk.to_ecdsa_verifying_key::<P256>().unwrap();
+        assert_eq!(
+            Key::EcdsaP256Public(&public_key)
+                .to_jwk(Some("p256"), &mut output)
+                .unwrap(),
+            r#"{"kty":"EC","crv":"P-256","x":"IsvE8xTnPW7Z3Bxl28_gyfsbxfYmNztOcuSppwk6xVg","y":"NCZe91pv8-IiNK_xCa42uXn04ozVc18Z5dBSvillYrg","kid":"p256"}"#
+        );
+
+        assert_eq!(
+            jwk.to_ecdsa_verifying_key::<P384>().err(),
+            Some(KeyFormatError::MismatchedJwkKeyType.into())
+        );
+        assert_eq!(
+            jwk.to_rsa_verifying_key().err(),
+            Some(KeyFormatError::MismatchedJwkKeyType.into())
+        );
+    }
+
+    #[test]
+    fn ecdsa_p384() {
+        let json = include_str!("secp384r1.jwk.json");
+        let key = Jwk::parse(json)
+            .unwrap()
+            .to_ecdsa_signing_key::<P384>()
+            .unwrap();
+        let expected = ecdsa::SigningKey::<P384>::from_pkcs8_der(include_bytes!(
+            "../ecdsa/secp384r1.pkcs8.der"
+        ))
+        .unwrap();
+        check_same_pkcs8(&key, &expected);
+
+        let mut output = [0u8; 512];
+        assert_eq!(
+            Key::EcdsaP384Private(&key)
+                .to_jwk(None, &mut output)
+                .unwrap(),
+            json.trim_end()
+        );
+
+        let mut output = [0u8; 200];
+        assert_eq!(
+            Key::EcdsaP384Private(&key).to_jwk(None, &mut output),
+            Err(Error::WrongLength)
+        );
+        assert_eq!(output, [0u8; 200]);
+    }
+
+    #[test]
+    fn rsa() {
+        let json = include_str!("rsa2048.jwk.json");
+        let jwk = Jwk::parse(json).unwrap();
+        assert_eq!(jwk.key_type(), "RSA");
+
+        let key = jwk.to_rsa_signing_key().unwrap();
+        let expected =
+            rsa::SigningKey::from_pkcs1_der(include_bytes!("../rsa/rsa2048.der")).unwrap();
+        let mut pkcs8 = [0u8; 2048];
+        let mut expected_pkcs8 = [0u8; 2048];
+        assert_eq!(
+            key.to_pkcs8_der(&mut pkcs8).unwrap(),
+            expected.to_pkcs8_der(&mut expected_pkcs8).unwrap()
+        );
+
+        let mut output = [0u8; 2048];
+        assert_eq!(
+            Key::RsaPrivate(&key).to_jwk(None, &mut output).unwrap(),
+            json.trim_end()
+        );
+
+        let public_key = jwk.to_rsa_verifying_key().unwrap();
+        let public_json = Key::RsaPublic(&public_key)
+            .to_jwk(Some("rsa"), &mut output)
+            .unwrap();
+        assert!(public_json.starts_with(r#"{"kty":"RSA","n":"uD4GcFmnJH9XgZ9q"#));
+        assert!(public_json.ends_with(r#"","e":"AQAB","kid":"rsa"}"#));
+
+        let public_jwk = Jwk::parse(public_json).unwrap();
+        assert_eq!(public_jwk.key_id(), Some("rsa"));
+        assert!(!public_jwk.is_private());
+        public_jwk.to_rsa_verifying_key().unwrap();
+        assert_eq!(
+            public_jwk.to_rsa_signing_key().err(),
+            Some(KeyFormatError::InvalidJwk.into())
+        );
+    }
+
+    #[test]
+    fn x25519() {
+        // Alice's key from RFC7748 section 6.1
+        let json = r#"{
+            "kty": "OKP",
+            "crv": "X25519",
+            "x": "hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo",
+            "d": "dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo"
+        }"#;
+        let jwk = Jwk::parse(json).unwrap();
+        let private_key = jwk.to_x25519_private_key().unwrap();
+
+        let bob = Jwk::parse(
+            r#"{"kty":"OKP","crv":"X25519","x":"3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08"}"#,
+        )
+        .unwrap()
+        .to_x25519_public_key()
+        .unwrap();
+
+        let mut output = [0u8; 256];
+        assert_eq!(
+            Key::X25519Private(&private_key)
+                .to_jwk(None, &mut output)
+                .unwrap(),
+            r#"{"kty":"OKP","crv":"X25519","x":"hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo","d":"dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo"}"#
+        );
+        assert_eq!(
+            Key::X25519Public(&bob)
+                .to_jwk(Some("bob"), &mut output)
+                .unwrap(),
+            r#"{"kty":"OKP","crv":"X25519","x":"3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08","kid":"bob"}"#
+        );
+
+        let shared = private_key.diffie_hellman(&bob);
+        assert_eq!(
+            &shared.0[..],
+            &[
+                0x4a, 0x5d, 0x9d, 0x5b, 0xa4, 0xce, 0x2d, 0xe1, 0x72, 0x8e, 0x3b, 0xf4, 0x80, 0x35,
+                0x0f, 0x25, 0xe0, 0x7e, 0x21, 0xc9, 0x47, 0xd1, 0x9e, 0x33, 0x76, 0xf0, 0x9b, 0x3c,
+                0x1e, 0x16, 0x17, 0x42
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        const X: &str = "IsvE8xTnPW7Z3Bxl28_gyfsbxfYmNztOcuSppwk6xVg";
+        const Y: &str = "NCZe91pv8-IiNK_xCa42uXn04ozVc18Z5dBSvillYrg";
+        const D: &str = "xaXSrYxWyvlhJoQy3_VfZuTaC-tJJFh4cM2oNL9sZFY";
+
+        // failures during parsing
+        for json in [
+            "",
+            "[]",
+            "{}",
+            r#"{"kty":"EC""#,
+            r#"{"kty":1}"#,
+            r#"{"kty":"EC","kty":"EC"}"#,
+            r#"{"kty":"EC","x":["a"]}"#,
+        ] {
+            assert_eq!(
+                Jwk::parse(json).err(),
+                Some(KeyFormatError::InvalidJwk.into()),
+                "{json:?}"
+            );
+        }
+
+        // failures during decoding
+        for (json, error) in [
+            (
+                format!(r#"{{"kty":"EC","crv":"P-256","x":"{X}"}}"#),
+                Error::from(KeyFormatError::InvalidJwk),
+            ),
+            (
+                format!(r#"{{"kty":"EC","crv":"P-256","x":"{X}=","y":"{Y}","d":"{D}"}}"#),
+                KeyFormatError::InvalidJwk.into(),
+            ),
+            (
+                format!(
+                    r#"{{"kty":"EC","crv":"P-256","x":"{X}","y":"{}","d":"{D}"}}"#,
+                    &Y[1..]
+                ),
+                KeyFormatError::InvalidJwk.into(),
+            ),
+            (
+                format!(r#"{{"kty":"EC","crv":"P-256","x":"{X}","y":"{Y}"}}"#),
+                KeyFormatError::InvalidJwk.into(),
+            ),
+            (
+                format!(
+                    r#"{{"kty":"EC","crv":"P-256","x":"{X}","y":"{Y}","d":"A{}"}}"#,
+                    &D[1..]
+                ),
+                KeyFormatError::MismatchedJwkPublicKey.into(),
+            ),
+            (
+                format!(r#"{{"kty":"EC","crv":"P-256","x":"{X}","y":"{X}","d":"{D}"}}"#),
+                KeyFormatError::MismatchedJwkPublicKey.into(),
+            ),
+            (
+                format!(r#"{{"kty":"EC","crv":"P-384","x":"{X}","y":"{Y}","d":"{D}"}}"#),
+                KeyFormatError::MismatchedJwkKeyType.into(),
+            ),
+            (
+                format!(r#"{{"kty":"ec","crv":"P-256","x":"{X}","y":"{Y}","d":"{D}"}}"#),
+                KeyFormatError::MismatchedJwkKeyType.into(),
+            ),
+        ] {
+            let jwk = Jwk::parse(&json).unwrap();
+            assert_eq!(
+                jwk.to_ecdsa_signing_key::<P256>().err(),
+                Some(error),
+                "{json:?}"
+            );
+        }
+
+        let json = format!(r#"{{"kty":"EC","crv":"P-256","x":"{X}","y":"{X}"}}"#);
+        assert_eq!(
+            Jwk::parse(&json)
+                .unwrap()
+                .to_ecdsa_verifying_key::<P256>()
+                .err(),
+            Some(Error::NotOnCurve)
+        );
+
+        let json = include_str!("rsa2048.jwk.json").replace(r#""qi":"#, r#""oth":[],"qi":"#);
+        assert_eq!(
+            Jwk::parse(&json).unwrap().to_rsa_signing_key().err(),
+            Some(KeyFormatError::InvalidJwk.into())
+        );
+        let json = include_str!("rsa2048.jwk.json").replace(r#""e":"AQAB""#, r#""e":"AQAD""#);
+        assert!(Jwk::parse(&json).unwrap().to_rsa_signing_key().is_err());
+
+        let json = r#"{"kty":"OKP","crv":"X25519","x":"3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08","d":"dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo"}"#;
+        assert_eq!(
+            Jwk::parse(json).unwrap().to_x25519_private_key().err(),
+            Some(KeyFormatError::MismatchedJwkPublicKey.into())
+        );
+    }
+
+    #[test]
+    fn jwk_set() {
+        let json = format!(
+            r#"{{"keys": [
+                {{"kty":"oct","kid":"a","k":"AAAA"}},
+                {{"kty":"RSA","kid":"b","n":1}},
+                [],
+                {},
+                {{"kty":"EC","kid":"b"}}
+            ], "other": true}}"#,
+            include_str!("secp256r1.jwk.json").replace(r#""d":"#, r#""kid":"c","d":"#)
+        );
+        let set = JwkSet::parse(&json).unwrap();
+
+        let keys: Vec<_> = set
+            .keys()
+            .map(|jwk| jwk.map(|jwk| (jwk.key_type(), jwk.key_id())))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                Ok(("oct", Some("a"))),
+                Err(KeyFormatError::InvalidJwk.into()),
+                Err(KeyFormatError::InvalidJwk.into()),
+                Ok(("EC", Some("c"))),
+                Ok(("EC", Some("b"))),
+            ]
+        );
+
+        set.find("c")
+            .unwrap()
+            .to_ecdsa_signing_key::<P256>()
+            .unwrap();
+        assert_eq!(set.find("b").unwrap().key_type(), "EC");
+        assert!(set.find("d").is_none());
+
+        for json in ["{}", r#"{"keys":{}}"#, r#"{"keys":[],"keys":[]}"#, "[]"] {
+            assert_eq!(
+                JwkSet::parse(json).err(),
+                Some(KeyFormatError::InvalidJwk.into()),
+                "{json:?}"
+            );
+        }
+        assert_eq!(JwkSet::parse(r#"{"keys":[]}"#).unwrap().keys().count(), 0);
+    }
+
+    fn check_same_pkcs8<C: Curve>(key: &ecdsa::SigningKey<C>, expected: &ecdsa::SigningKey<C>) {
+        let mut pkcs8 = [0u8; 256];
+        let mut expected_pkcs8 = [0u8; 256];
+        assert_eq!(
+            key.to_pkcs8_der(&mut pkcs8).unwrap(),
+            expected.to_pkcs8_der(&mut expected_pkcs8).unwrap()
+        );
+    }
+}
@@ -0,0 +1,632 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! JSON Web Signatures, in the compact serialisation.
+//!
+//! See [RFC7515](https://datatracker.ietf.org/doc/html/rfc7515), and
+//! [RFC7518 section 3](https://datatracker.ietf.org/doc/html/rfc7518#section-3)
+//! for the algorithms.
+
+use super::{decode_base64url, decoded_len, json, Writer};
+#[cfg(feature = "p256")]
+use crate::high::curve::P256;
+#[cfg(feature = "p384")]
+use crate::high::curve::P384;
+#[cfg(any(feature = "p256", feature = "p384"))]
+use crate::high::ecdsa;
+#[cfg(any(feature = "p256", feature = "p384"))]
+use crate::high::hash;
+#[cfg(feature = "rsa")]
+use crate::high::rsa;
+use crate::low::Entry;
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
+use crate::{Error, JwsError};
+
+/// A JWS signature algorithm (`alg`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// `ES256`: ECDSA on P-256, with SHA-256.
+    #[cfg(feature = "p256")]
+    Es256,
+
+    /// `ES384`: ECDSA on P-384, with SHA-384.
+    #[cfg(feature = "p384")]
+    Es384,
+
+    /// `RS256`: RSASSA-PKCS1-v1_5 with SHA-256.
+    #[cfg(feature = "rsa")]
+    Rs256,
+
+    /// `PS256`: RSASSA-PSS with SHA-256, and a 32-byte salt.
+    #[cfg(feature = "rsa")]
+    Ps256,
+}
+
+impl Algorithm {
+    /// Returns the `alg` name of this algorithm.
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::Es256 => "ES256",
+            #[cfg(feature = "p384")]
+            Self::Es384 => "ES384",
+            #[cfg(feature = "rsa")]
+            Self::Rs256 => "RS256",
+            #[cfg(feature = "rsa")]
+            Self::Ps256 => "PS256",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            #[cfg(feature = "p256")]
+            "ES256" => Some(Self::Es256),
+            #[cfg(feature = "p384")]
+            "ES384" => Some(Self::Es384),
+            #[cfg(feature = "rsa")]
+            "RS256" => Some(Self::Rs256),
+            #[cfg(feature = "rsa")]
+            "PS256" => Some(Self::Ps256),
+            _ => None,
+        }
+    }
+}
+
+/// A private key, and the JWS algorithm to use with it.
+#[derive(Clone, Copy)]
+pub enum Signer<'a> {
+    /// `ES256`.
+    #[cfg(feature = "p256")]
+    Es256(&'a ecdsa::SigningKey<P256>),
+
+    /// `ES384`.
+    #[cfg(feature = "p384")]
+    Es384(&'a ecdsa::SigningKey<P384>),
+
+    /// `RS256`.
+    #[cfg(feature = "rsa")]
+    Rs256(&'a rsa::SigningKey),
+
+    /// `PS256`.
+    #[cfg(feature = "rsa")]
+    Ps256(&'a rsa::SigningKey),
+}
+
+impl Signer<'_> {
+    /// Returns the algorithm used by this signer.
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::Es256(_) => Algorithm::Es256,
+            #[cfg(feature = "p384")]
+            Self::Es384(_) => Algorithm::Es384,
+            #[cfg(feature = "rsa")]
+            Self::Rs256(_) => Algorithm::Rs256,
+            #[cfg(feature = "rsa")]
+            Self::Ps256(_) => Algorithm::Ps256,
+        }
+    }
+
+    /// Signs `message`, writing the signature into `signature`.
+    ///
+    /// ECDSA signatures are fixed-length `r || s`, as JWS requires.
+    fn sign<'s>(
+        &self,
+        rng: &mut dyn RandomSource,
+        message: &[u8],
+        signature: &'s mut [u8],
+    ) -> Result<&'s [u8], Error> {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::Es256(key) => key.sign_with_rng::<hash::Sha256>(rng, &[message], signature),
+            #[cfg(feature = "p384")]
+            Self::Es384(key) => key.sign_with_rng::<hash::Sha384>(rng, &[message], signature),
+            #[cfg(feature = "rsa")]
+            Self::Rs256(key) => key.sign_pkcs1_sha256(signature, message),
+            #[cfg(feature = "rsa")]
+            Self::Ps256(key) => key.sign_pss_sha256_with_rng(rng, signature, message),
+        }
+    }
+}
+
+/// A public key, and the JWS algorithm it must be used with.
+#[derive(Clone, Copy)]
+pub enum Verifier<'a> {
+    /// `ES256`.
+    #[cfg(feature = "p256")]
+    Es256(&'a ecdsa::VerifyingKey<P256>),
+
+    /// `ES384`.
+    #[cfg(feature = "p384")]
+    Es384(&'a ecdsa::VerifyingKey<P384>),
+
+    /// `RS256`.
+    #[cfg(feature = "rsa")]
+    Rs256(&'a rsa::VerifyingKey),
+
+    /// `PS256`.
+    #[cfg(feature = "rsa")]
+    Ps256(&'a rsa::VerifyingKey),
+}
+
+impl Verifier<'_> {
+    /// Returns the algorithm this verifier accepts.
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::Es256(_) => Algorithm::Es256,
+            #[cfg(feature = "p384")]
+            Self::Es384(_) => Algorithm::Es384,
+            #[cfg(feature = "rsa")]
+            Self::Rs256(_) => Algorithm::Rs256,
+            #[cfg(feature = "rsa")]
+            Self::Ps256(_) => Algorithm::Ps256,
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), Error> {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::Es256(key) => key
+                .verify::<hash::Sha256>(&[message], signature)
+                .map_err(|_| Error::BadSignature),
+            #[cfg(feature = "p384")]
+            Self::Es384(key) => key
+                .verify::<hash::Sha384>(&[message], signature)
+                .map_err(|_| Error::BadSignature),
+            #[cfg(feature = "rsa")]
+            Self::Rs256(key) => key.verify_pkcs1_sha256(signature, message),
+            #[cfg(feature = "rsa")]
+            Self::Ps256(key) => key.verify_pss_sha256(signature, message),
+        }
+    }
+}
+
+/// A JWS to be signed.
+///
+/// Fill in the fields of this structure, and then use [`Self::sign()`]
+/// to produce the compact serialisation.  The protected header contains
+/// `alg`, and then `kid` and `typ` if given.
+///
+/// A JSON Web Token ([RFC7519](https://datatracker.ietf.org/doc/html/rfc7519))
+/// is a JWS whose payload is a JSON claims set, typically with
+/// `media_type` of `JWT`.  Encoding the claims is left to the caller.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Jws<'a> {
+    /// The payload to be signed.
+    pub payload: &'a [u8],
+
+    /// The key ID (`kid`) header parameter.
+    pub key_id: Option<&'a str>,
+
+    /// The type (`typ`) header parameter: for example, `JWT`.
+    pub media_type: Option<&'a str>,
+}
+
+impl Jws<'_> {
+    /// Signs this JWS with `signer`, and encodes it in the compact
+    /// serialisation.
+    ///
+    /// Randomness (for ECDSA and RSASSA-PSS) comes from the default
+    /// [`RandomSource`].
+    ///
+    /// The encoding is written to the front of `output`, and that prefix
+    /// is returned.  [`Error::WrongLength`] is returned if `output` is
+    /// too short, or if the header is longer than 1024 bytes.
+    #[cfg(feature = "std")]
+    pub fn sign<'o>(&self, signer: Signer<'_>, output: &'o mut [u8]) -> Result<&'o str, Error> {
+        self.sign_with_rng(&mut DefaultRandom, signer, output)
+    }
+
+    /// Signs this JWS with `signer`, using randomness from `rng`.
+    ///
+    /// See [`Self::sign()`] for details of the other arguments.
+    pub fn sign_with_rng<'o>(
+        &self,
+        rng: &mut dyn RandomSource,
+        signer: Signer<'_>,
+        output: &'o mut [u8],
+    ) -> Result<&'o str, Error> {
+        let _ = Entry::new_secret();
+        let mut header = [0u8; MAX_HEADER_LEN];
+        let header = self.encode_header(signer.algorithm(), &mut header)?;
+
+        let mut signature = [0u8; MAX_SIGNATURE_LEN];
+        let mut writer = Writer::new(output);
+        let result = writer
+            .base64url(header.as_bytes())
+            .and_then(|()| writer.write(b"."))
+            .and_then(|()| writer.base64url(self.payload))
+            .and_then(|()| signer.sign(rng, writer.written(), &mut signature))
+            .and_then(|signature| {
+                writer.write(b".")?;
+                writer.base64url(signature)
+            });
+        writer.finish(result)
+    }
+
+    fn encode_header<'h>(
+        &self,
+        algorithm: Algorithm,
+        output: &'h mut [u8],
+    ) -> Result<&'h str, Error> {
+        let mut writer = Writer::new(output);
+        let result = writer
+            .write(b"{")
+            .and_then(|()| writer.string_member("alg", algorithm.name()))
+            .and_then(|()| match self.key_id {
+                Some(key_id) => writer.string_member("kid", key_id),
+                None => Ok(()),
+            })
+            .and_then(|()| match self.media_type {
+                Some(media_type) => writer.string_member("typ", media_type),
+                None => Ok(()),
+            })
+            .and_then(|()| writer.write(b"}"));
+        writer.finish(result)
+    }
+}
+
+/// A JWS in the compact serialisation, whose signature has not yet
+/// been verified.
+///
+/// [`Self::parse()`] checks the structure and protected header, so that
+/// [`Self::key_id()`] can be used to find the verification key.  Then
+/// [`Self::verify()`] checks the signature and returns the payload.
+pub struct UnverifiedJws<'a> {
+    signing_input: &'a str,
+    payload: &'a str,
+    signature: &'a str,
+    algorithm: Algorithm,
+    header: [u8; MAX_HEADER_LEN],
+    header_len: usize,
+}
+
+impl<'a> UnverifiedJws<'a> {
+    /// Parses a JWS in the compact serialisation.
+    ///
+    /// `compact` must have three base64url parts, separated by `.`.
+    /// The first is the protected header: this must be a JSON object of
+    /// at most 1024 bytes, with a supported `alg`.  The header members
+    /// `alg`, `kid` and `typ` must be strings without escape sequences,
+    /// and must not be repeated.
+    ///
+    /// A `crit` header member results in
+    /// [`crate::JwsError::UnsupportedCriticalHeader`], as no extensions
+    /// are supported.  Other errors in the structure or header result in
+    /// [`crate::JwsError::Malformed`].
+    pub fn parse(compact: &'a str) -> Result<Self, Error> {
+        let _ = Entry::new_public();
+        let (signing_input, signature) = compact.rsplit_once('.').ok_or(JwsError::Malformed)?;
+        let (encoded_header, payload) = signing_input.split_once('.').ok_or(JwsError::Malformed)?;
+        if payload.contains('.') {
+            return Err(JwsError::Malformed.into());
+        }
+
+        let mut header = [0u8; MAX_HEADER_LEN];
+        let header_len = decode_base64url(encoded_header, &mut header)
+            .map_err(|()| JwsError::Malformed)?
+            .len();
+        let text = core::str::from_utf8(&header[..header_len]).map_err(|_| JwsError::Malformed)?;
+        let object = json::Object::parse(text).map_err(|()| JwsError::Malformed)?;
+
+        let mut algorithm = None;
+        let mut key_id = None;
+        let mut media_type = None;
+        for (name, value) in object.members() {
+            let member = match name {
+                "alg" => &mut algorithm,
+                "kid" => &mut key_id,
+                "typ" => &mut media_type,
+                "crit" => return Err(JwsError::UnsupportedCriticalHeader.into()),
+                _ => continue,
+            };
+
+            if member.is_some() {
+                return Err(JwsError::Malformed.into());
+            }
+            *member = Some(value.as_str().ok_or(JwsError::Malformed)?);
+        }
+
+        let algorithm = algorithm.ok_or(JwsError::Malformed)?;
+        let algorithm = Algorithm::from_name(algorithm).ok_or(JwsError::UnsupportedAlgorithm)?;
+
+        Ok(Self {
+            signing_input,
+            payload,
+            signature,
+            algorithm,
+            header,
+            header_len,
+        })
+    }
+
+    /// Returns the algorithm (`alg`) named in the header.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Returns the key ID (`kid`) from the header, if present.
+    pub fn key_id(&self) -> Option<&str> {
+        self.header_member("kid")
+    }
+
+    /// Returns the type (`typ`) from the header, if present.
+    pub fn media_type(&self) -> Option<&str> {
+        self.header_member("typ")
+    }
+
+    /// Verifies the signature using `verifier`, and decodes the payload.
+    ///
+    /// The header's `alg` must match [`Verifier::algorithm()`], else
+    /// [`crate::JwsError::MismatchedAlgorithm`] is returned.  An invalid
+    /// signature results in [`Error::BadSignature`].
+    ///
+    /// The payload is written to the front of `payload`, and that prefix
+    /// is returned.  [`Error::WrongLength`] is returned if `payload` is
+    /// too short.
+    pub fn verify<'o>(
+        &self,
+        verifier: Verifier<'_>,
+        payload: &'o mut [u8],
+    ) -> Result<&'o [u8], Error> {
+        let _ = Entry::new_public();
+        if verifier.algorithm() != self.algorithm {
+            return Err(JwsError::MismatchedAlgorithm.into());
+        }
+
+        let mut signature = [0u8; MAX_SIGNATURE_LEN];
+        let signature =
+            decode_base64url(self.signature, &mut signature).map_err(|()| JwsError::Malformed)?;
+        verifier.verify(self.signing_input.as_bytes(), signature)?;
+
+        if payload.len() < decoded_len(self.payload) {
+            return Err(Error::WrongLength);
+        }
+        decode_base64url(self.payload, payload).map_err(|()| JwsError::Malformed.into())
+    }
+
+    /// Returns the string value of the header member `name`.
+    fn header_member(&self, name: &str) -> Option<&str> {
+        // nb. the header was validated by `parse()`.
+        let header = core::str::from_utf8(&self.header[..self.header_len]).ok()?;
+        json::Object::parse(header)
+            .ok()?
+            .members()
+            .find(|(member, _)| *member == name)?
+            .1
+            .as_str()
+    }
+}
+
+/// The largest protected header accepted or produced.
+const MAX_HEADER_LEN: usize = 1024;
+
+/// The largest signature accepted: an 8192-bit RSA signature.
+const MAX_SIGNATURE_LEN: usize = 1024;
+
+#[cfg(all(
+    test,
+    feature = "p256",
+    feature = "p384",
+    feature = "rsa",
+    feature = "std"
+))]
+mod tests {
+    use super::*;
+    use crate::high::jose::Jwk;
+
+    #[test]
+    fn rfc7515_example_a3() {
+        let key = Jwk::parse(
+            r#"{"kty":"EC","crv":"P-256",
+                "x":"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+                "y":"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"}"#,
+        )
+        .unwrap()
+        .to_ecdsa_verifying_key::<P256>()
+        .unwrap();
+
+        let jws = UnverifiedJws::parse(
+            "eyJhbGciOiJFUzI1NiJ9\
+             .eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ\
+             .DtEhU3ljbEg8L38VWAfUAqOyKAM6-Xx-F4GawxaepmXFCgfTjDxw5djxLa8ISlSApmWQxfKTUJqPP3-Kg6NU1Q",
+        )
+        .unwrap();
+        assert_eq!(jws.algorithm(), Algorithm::Es256);
+        assert_eq!(jws.key_id(), None);
+        assert_eq!(jws.media_type(), None);
+
+        let mut payload = [0u8; 128];
+        assert_eq!(
+            jws.verify(Verifier::Es256(&key), &mut payload).unwrap(),
+            b"{\"iss\":\"joe\",\r\n \"exp\":1300819380,\r\n \"http://example.com/is_root\":true}"
+        );
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let p256 = Jwk::parse(include_str!("secp256r1.jwk.json")).unwrap();
+        let p256_private = p256.to_ecdsa_signing_key::<P256>().unwrap();
+        let p256_public = p256.to_ecdsa_verifying_key::<P256>().unwrap();
+        let p384 = Jwk::parse(include_str!("secp384r1.jwk.json")).unwrap();
+        let p384_private = p384.to_ecdsa_signing_key::<P384>().unwrap();
+        let p384_public = p384.to_ecdsa_verifying_key::<P384>().unwrap();
+        let rsa_private =
+            rsa::SigningKey::from_pkcs1_der(include_bytes!("../rsa/rsa2048.der")).unwrap();
+        let rsa_public = rsa_private.public_key();
+
+        let cases = [
+            (
+                Signer::Es256(&p256_private),
+                Verifier::Es256(&p256_public),
+                "eyJhbGciOiJFUzI1NiIsImtpZCI6ImtleS0xIiwidHlwIjoiSldUIn0",
+            ),
+            (
+                Signer::Es384(&p384_private),
+                Verifier::Es384(&p384_public),
+                "eyJhbGciOiJFUzM4NCIsImtpZCI6ImtleS0xIiwidHlwIjoiSldUIn0",
+            ),
+            (
+                Signer::Rs256(&rsa_private),
+                Verifier::Rs256(&rsa_public),
+                "eyJhbGciOiJSUzI1NiIsImtpZCI6ImtleS0xIiwidHlwIjoiSldUIn0",
+            ),
+            (
+                Signer::Ps256(&rsa_private),
+                Verifier::Ps256(&rsa_public),
+                "eyJhbGciOiJQUzI1NiIsImtpZCI6ImtleS0xIiwidHlwIjoiSldUIn0",
+            ),
+        ];
+
+        let jws = Jws {
+            payload: br#"{"sub":"1234567890","admin":true}"#,
+            key_id: Some("key-1"),
+            media_type: Some("JWT"),
+        };
+
+        for (signer, verifier, header) in cases {
+            let mut output = [0u8; 1024];
+            let compact = jws.sign(signer, &mut output).unwrap();
+            assert!(compact.starts_with(header), "{compact}");
+
+            let unverified = UnverifiedJws::parse(compact).unwrap();
+            assert_eq!(unverified.algorithm(), signer.algorithm());
+            assert_eq!(unverified.key_id(), Some("key-1"));
+            assert_eq!(unverified.media_type(), Some("JWT"));
+
+            let mut payload = [0u8; 64];
+            assert_eq!(
+                unverified.verify(verifier, &mut payload).unwrap(),
+                jws.payload
+            );
+            assert_eq!(
+                unverified.verify(verifier, &mut payload[..jws.payload.len() - 1]),
+                Err(Error::WrongLength)
+            );
+
+            // any change to the signing input invalidates the signature
+            let mut other = [0u8; 1024];
+            let other = Jws {
+                payload: br#"{"sub":"1234567890","admin":false}"#,
+                ..jws
+            }
+            .sign(signer, &mut other)
+            .unwrap();
+            let (_, signature) = compact.rsplit_once('.').unwrap();
+            let (signing_input, _) = other.rsplit_once('.').unwrap();
+            let tampered = format!("{signing_input}.{signature}");
+            assert_eq!(
+                UnverifiedJws::parse(&tampered)
+                    .unwrap()
+                    .verify(verifier, &mut payload),
+                Err(Error::BadSignature)
+            );
+
+            for (_, other, _) in cases {
+                if other.algorithm() != verifier.algorithm() {
+                    assert_eq!(
+                        unverified.verify(other, &mut payload),
+                        Err(JwsError::MismatchedAlgorithm.into())
+                    );
+                }
+            }
+        }
+
+        let mut output = [0u8; 100];
+        assert_eq!(
+            jws.sign(Signer::Es256(&p256_private), &mut output),
+            Err(Error::WrongLength)
+        );
+        assert_eq!(output, [0u8; 100]);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut payload = [0u8; 64];
+        let key = Jwk::parse(include_str!("secp256r1.jwk.json"))
+            .unwrap()
+            .to_ecdsa_verifying_key::<P256>()
+            .unwrap();
+
+        let cases: [(&[u8], JwsError); 12] = [
+            (br#"{"alg":"none"}"#, JwsError::UnsupportedAlgorithm),
+            (br#"{"alg":"HS256"}"#, JwsError::UnsupportedAlgorithm),
+            (br#"{"alg":"es256"}"#, JwsError::UnsupportedAlgorithm),
+            (
+                br#"{"alg":"ES256","crit":["exp"],"exp":1}"#,
+                JwsError::UnsupportedCriticalHeader,
+            ),
+            (br#"{}"#, JwsError::Malformed),
+            (br#"{"alg":"ES256","alg":"ES256"}"#, JwsError::Malformed),
+            (br#"{"alg":"ES\u0032\u0035\u0036"}"#, JwsError::Malformed),
+            (br#"{"alg":["ES256"]}"#, JwsError::Malformed),
+            (br#"{"alg":"ES256","kid":1}"#, JwsError::Malformed),
+            (br#"{"alg":"ES256""#, JwsError::Malformed),
+            (br#"["ES256"]"#, JwsError::Malformed),
+            (b"{\"alg\":\"ES256\",\"kid\":\"\xff\"}", JwsError::Malformed),
+        ];
+        for (header, error) in cases {
+            let mut output = [0u8; 128];
+            let mut writer = Writer::new(&mut output);
+            let result = writer.base64url(header);
+            let compact = format!("{}.e30.AAAA", writer.finish(result).unwrap());
+            assert_eq!(
+                UnverifiedJws::parse(&compact).err(),
+                Some(error.into()),
+                "{:?}",
+                String::from_utf8_lossy(header)
+            );
+        }
+
+        let header = "eyJhbGciOiJFUzI1NiJ9";
+        for compact in [
+            String::new(),
+            header.to_string(),
+            format!("{header}.e30"),
+            format!("{header}.e30.AAAA.AAAA"),
+            format!("{header}=.e30.AAAA"),
+            format!("{header} .e30.AAAA"),
+            format!("{}.e30.AAAA", "e30".repeat(MAX_HEADER_LEN)),
+        ] {
+            assert_eq!(
+                UnverifiedJws::parse(&compact).err(),
+                Some(JwsError::Malformed.into()),
+                "{compact:?}"
+            );
+        }
+
+        // the payload and signature are only decoded by `verify()`,
+        // and the payload only once the signature is verified.
+        let private_key = Jwk::parse(include_str!("secp256r1.jwk.json"))
+            .unwrap()
+            .to_ecdsa_signing_key::<P256>()
+            .unwrap();
+        let signing_input = format!("{header}.e30=");
+        let mut signature = [0u8; 64];
+        let signature = Signer::Es256(&private_key)
+            .sign(&mut DefaultRandom, signing_input.as_bytes(), &mut signature)
+            .unwrap();
+        let mut output = [0u8; 128];
+        let mut writer = Writer::new(&mut output);
+        let result = writer.base64url(signature);
+        let signature = writer.finish(result).unwrap();
+
+        for compact in [
+            format!("{signing_input}.{signature}"),
+            format!("{header}.e30.AA+A"),
+            format!("{header}.e30.{}", "A".repeat(MAX_SIGNATURE_LEN * 2)),
+        ] {
+            assert_eq!(
+                UnverifiedJws::parse(&compact)
+                    .unwrap()
+                    .verify(Verifier::Es256(&key), &mut payload)
+                    .err(),
+                Some(JwsError::Malformed.into()),
+                "{compact:?}"
+            );
+        }
+    }
+}
@@ -3,12 +3,49 @@
 
 #![deny(unsafe_code)]
 
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
 pub(super) mod asn1;
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
+pub(super) mod base64;
+#[cfg(all(
+    feature = "x509",
+    any(feature = "p256", feature = "p384", feature = "rsa")
+))]
+pub(super) mod certificate;
+#[cfg(all(
+    feature = "x509",
+    any(feature = "p256", feature = "p384", feature = "rsa")
+))]
+pub(super) mod csr;
+#[cfg(feature = "aes")]
+pub(super) mod ctr_drbg;
+#[cfg(any(feature = "p256", feature = "p384"))]
 pub(super) mod curve;
+#[cfg(any(feature = "p256", feature = "p384"))]
 pub(super) mod ecdsa;
 pub(super) mod hash;
 pub mod hmac;
 pub(super) mod hmac_drbg;
+#[cfg(feature = "hpke")]
+pub(super) mod hpke;
+#[cfg(all(
+    feature = "jose",
+    any(feature = "p256", feature = "p384", feature = "rsa")
+))]
+pub(super) mod jose;
+#[cfg(all(
+    feature = "pbes2",
+    any(feature = "p256", feature = "p384", feature = "rsa")
+))]
+pub(super) mod pbes2;
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
+pub(super) mod pem;
+#[cfg(feature = "rsa")]
 pub(super) mod pkcs1;
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
 pub(super) mod pkcs8;
+#[cfg(feature = "rsa")]
 pub(super) mod rsa;
+#[cfg(feature = "rustcrypto")]
+pub(super) mod rustcrypto;
+pub(super) mod self_test;
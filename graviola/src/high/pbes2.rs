@@ -0,0 +1,506 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! PBES2 password-based encryption, as used by PKCS#8 `EncryptedPrivateKeyInfo`.
+//!
+//! PBES2 is standardized in [RFC8018](https://datatracker.ietf.org/doc/html/rfc8018#section-6.2).
+//!
+//! Only PBKDF2 with HMAC-SHA256 is supported for key derivation.  AES-128
+//! and AES-256 are supported for encryption, either in CBC mode with
+//! PKCS#7 padding, or GCM.  Encryption always uses AES-256-CBC, as that
+//! is the most widely supported combination.
+
+use super::asn1::{self, oid, pkix, Type};
+use super::hash::Sha256;
+use super::hmac::Hmac;
+use crate::error::{Error, KeyFormatError};
+use crate::low::{optimise_barrier_u8, zeroise, AesKey};
+use crate::mid::aes_gcm::AesGcm;
+use crate::mid::rng::RandomSource;
+
+/// Decrypts `ciphertext` with `password`, according to the PBES2 `algorithm`.
+///
+/// The plaintext is written to the front of `plaintext`, and that prefix
+/// is returned.  A wrong `password` results in [`Error::DecryptFailed`]
+/// (though with CBC mode, that may be instead detected by the caller, when
+/// decoding the plaintext.)
+pub(crate) fn decrypt<'a>(
+    algorithm: &pkix::AlgorithmIdentifier<'_>,
+    password: &[u8],
+    ciphertext: &[u8],
+    plaintext: &'a mut [u8],
+) -> Result<&'a [u8], Error> {
+    if algorithm.algorithm != oid::id_PBES2 {
+        return Err(KeyFormatError::UnsupportedPkcs8Encryption.into());
+    }
+
+    let params = match &algorithm.parameters {
+        Some(asn1::Any::Sequence(seq)) => seq
+            .decode::<pkix::Pbes2Params<'_>>()
+            .map_err(Error::Asn1Error)?,
+        _ => return Err(KeyFormatError::UnsupportedPkcs8Encryption.into()),
+    };
+
+    let scheme = Scheme::from_algorithm_identifier(&params.encryptionScheme)?;
+
+    let mut key = [0u8; 32];
+    let key = &mut key[..scheme.key_len()];
+    derive_key(&params.keyDerivationFunc, password, key)?;
+
+    let result = scheme.decrypt(key, ciphertext, plaintext);
+    zeroise(key);
+    result
+}
+
+/// Encrypts `plaintext` with `password`, using AES-256-CBC and a
+/// key derived with PBKDF2-HMAC-SHA256.
+///
+/// The salt and IV are generated using `rng`.  `iterations` is the
+/// PBKDF2 iteration count.
+///
+/// On entry, the plaintext is the first `plaintext_len` bytes of `inout`.
+/// It is encrypted in place, so `inout` must have space for up to 16
+/// bytes of padding after it.
+///
+/// The encoded `PBES2-params` are written to `params_out`.  The used
+/// prefixes of `params_out` and `inout` are returned.
+pub(crate) fn encrypt<'p, 'c>(
+    rng: &mut dyn RandomSource,
+    password: &[u8],
+    iterations: usize,
+    inout: &'c mut [u8],
+    plaintext_len: usize,
+    params_out: &'p mut [u8],
+) -> Result<(&'p [u8], &'c [u8]), Error> {
+    let mut salt = [0u8; 16];
+    rng.fill(&mut salt)?;
+    let mut iv = [0u8; 16];
+    rng.fill(&mut iv)?;
+
+    let mut key = [0u8; 32];
+    pbkdf2_hmac_sha256(password, &salt, iterations, &mut key);
+    let ciphertext = cbc_encrypt(&key, &iv, inout, plaintext_len);
+    zeroise(&mut key);
+    let ciphertext = ciphertext?;
+
+    let iterations = iterations.to_be_bytes();
+    let mut kdf_params = [0u8; 64];
+    let kdf_params_len = pkix::Pbkdf2Params {
+        salt: asn1::OctetString::new(&salt),
+        iterationCount: asn1::Integer::from_bytes(&iterations),
+        keyLength: None,
+        prf: Some(pkix::AlgorithmIdentifier {
+            algorithm: oid::id_hmacWithSHA256.clone(),
+            parameters: Some(asn1::Any::Null(asn1::Null)),
+        }),
+    }
+    .encode(&mut asn1::Encoder::new(&mut kdf_params))
+    .map_err(Error::Asn1Error)?;
+
+    let params = pkix::Pbes2Params {
+        keyDerivationFunc: pkix::AlgorithmIdentifier {
+            algorithm: oid::id_PBKDF2.clone(),
+            parameters: Some(asn1::Any::Sequence(asn1::Sequence::new(
+                &kdf_params[..kdf_params_len],
+            ))),
+        },
+        encryptionScheme: pkix::AlgorithmIdentifier {
+            algorithm: oid::aes256_CBC_PAD.clone(),
+            parameters: Some(asn1::Any::OctetString(asn1::OctetString::new(&iv))),
+        },
+    };
+    let params_len = params
+        .encode(&mut asn1::Encoder::new(params_out))
+        .map_err(Error::Asn1Error)?;
+
+    Ok((&params_out[..params_len], ciphertext))
+}
+
+/// The number of PBKDF2 iterations used for encryption.
+///
+/// This follows the current OWASP recommendation for PBKDF2-HMAC-SHA256.
+/// Unit tests use fewer, as they are typically unoptimised.
+#[cfg(not(test))]
+pub(crate) const ITERATIONS: usize = 600_000;
+#[cfg(test)]
+pub(crate) const ITERATIONS: usize = 2048;
+
+/// The largest PBKDF2 iteration count accepted for decryption.
+///
+/// This bounds the work done when loading an untrusted key encoding.
+const MAX_ITERATIONS: usize = 10_000_000;
+
+/// Derives `key` from `password` according to the PBKDF2 `algorithm`.
+fn derive_key(
+    algorithm: &pkix::AlgorithmIdentifier<'_>,
+    password: &[u8],
+    key: &mut [u8],
+) -> Result<(), Error> {
+    let params = match &algorithm.parameters {
+        Some(asn1::Any::Sequence(seq)) if algorithm.algorithm == oid::id_PBKDF2 => seq
+            .decode::<pkix::Pbkdf2Params<'_>>()
+            .map_err(Error::Asn1Error)?,
+        _ => return Err(KeyFormatError::UnsupportedPkcs8Encryption.into()),
+    };
+
+    let iterations = params.iterationCount.as_usize().map_err(Error::Asn1Error)?;
+    if iterations == 0 || iterations > MAX_ITERATIONS {
+        return Err(KeyFormatError::UnsupportedPkcs8Encryption.into());
+    }
+
+    if let Some(key_len) = params.keyLength {
+        if key_len.as_usize() != Ok(key.len()) {
+            return Err(KeyFormatError::UnsupportedPkcs8Encryption.into());
+        }
+    }
+
+    // nb. the DEFAULT prf is hmacWithSHA1, which we do not support.
+    match params.prf {
+        Some(prf)
+            if prf.algorithm == oid::id_hmacWithSHA256
+                && matches!(prf.parameters, None | Some(asn1::Any::Null(_))) => {}
+        _ => return Err(KeyFormatError::UnsupportedPkcs8Encryption.into()),
+    }
+
+    pbkdf2_hmac_sha256(password, params.salt.into_octets(), iterations, key);
+    Ok(())
+}
+
+/// PBKDF2, using HMAC-SHA256 as the PRF.
+///
+/// See [RFC8018 section 5.2](https://datatracker.ietf.org/doc/html/rfc8018#section-5.2).
+fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: usize, out: &mut [u8]) {
+    let prf = Hmac::<Sha256>::new(password);
+
+    for (i, block) in out.chunks_mut(32).enumerate() {
+        let mut ctx = prf.clone();
+        ctx.update(salt);
+        ctx.update((i as u32 + 1).to_be_bytes());
+        let mut u = ctx.finish();
+
+        let mut t = [0u8; 32];
+        t.copy_from_slice(u.as_ref());
+
+        for _ in 1..iterations {
+            let mut ctx = prf.clone();
+            ctx.update(u.as_ref());
+            u = ctx.finish();
+
+            for (t, u) in t.iter_mut().zip(u.as_ref()) {
+                *t ^= *u;
+            }
+        }
+
+        block.copy_from_slice(&t[..block.len()]);
+        zeroise(&mut t);
+    }
+}
+
+enum Scheme<'a> {
+    Cbc { key_len: usize, iv: &'a [u8] },
+    Gcm { key_len: usize, nonce: [u8; 12] },
+}
+
+impl<'a> Scheme<'a> {
+    fn from_algorithm_identifier(alg: &pkix::AlgorithmIdentifier<'a>) -> Result<Self, Error> {
+        let key_len = if alg.algorithm == oid::aes128_CBC_PAD || alg.algorithm == oid::aes128_GCM {
+            16
+        } else if alg.algorithm == oid::aes256_CBC_PAD || alg.algorithm == oid::aes256_GCM {
+            32
+        } else {
+            return Err(KeyFormatError::UnsupportedPkcs8Encryption.into());
+        };
+
+        match &alg.parameters {
+            Some(asn1::Any::OctetString(iv))
+                if alg.algorithm == oid::aes128_CBC_PAD || alg.algorithm == oid::aes256_CBC_PAD =>
+            {
+                let iv = iv.clone().into_octets();
+                if iv.len() != 16 {
+                    return Err(KeyFormatError::UnsupportedPkcs8Encryption.into());
+                }
+                Ok(Self::Cbc { key_len, iv })
+            }
+
+            Some(asn1::Any::Sequence(seq))
+                if alg.algorithm == oid::aes128_GCM || alg.algorithm == oid::aes256_GCM =>
+            {
+                let params = seq
+                    .decode::<pkix::GcmParameters<'_>>()
+                    .map_err(Error::Asn1Error)?;
+
+                // nb. the DEFAULT `aes-ICVlen` is 12, which we do not support.
+                let nonce = params
+                    .nonce
+                    .into_octets()
+                    .try_into()
+                    .map_err(|_| KeyFormatError::UnsupportedPkcs8Encryption)?;
+                match params.icvLen {
+                    Some(len) if len.as_usize() == Ok(GCM_TAG_LEN) => {}
+                    _ => return Err(KeyFormatError::UnsupportedPkcs8Encryption.into()),
+                }
+                Ok(Self::Gcm { key_len, nonce })
+            }
+
+            _ => Err(KeyFormatError::UnsupportedPkcs8Encryption.into()),
+        }
+    }
+
+    fn key_len(&self) -> usize {
+        match self {
+            Self::Cbc { key_len, .. } | Self::Gcm { key_len, .. } => *key_len,
+        }
+    }
+
+    fn decrypt<'p>(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        plaintext: &'p mut [u8],
+    ) -> Result<&'p [u8], Error> {
+        match self {
+            Self::Cbc { iv, .. } => cbc_decrypt(key, iv, ciphertext, plaintext),
+            Self::Gcm { nonce, .. } => {
+                // `encryptedData` is the ciphertext followed by the tag.
+                let tag_start = ciphertext
+                    .len()
+                    .checked_sub(GCM_TAG_LEN)
+                    .ok_or(Error::DecryptFailed)?;
+                let (ciphertext, tag) = ciphertext.split_at(tag_start);
+                let plaintext = plaintext
+                    .get_mut(..ciphertext.len())
+                    .ok_or(Error::WrongLength)?;
+                plaintext.copy_from_slice(ciphertext);
+                AesGcm::new(key).decrypt(nonce, &[], plaintext, tag)?;
+                Ok(plaintext)
+            }
+        }
+    }
+}
+
+const GCM_TAG_LEN: usize = 16;
+
+/// AES-CBC decryption, removing PKCS#7 padding.
+fn cbc_decrypt<'a>(
+    key: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+    plaintext: &'a mut [u8],
+) -> Result<&'a [u8], Error> {
+    if ciphertext.is_empty() || ciphertext.len() % 16 != 0 {
+        return Err(Error::DecryptFailed);
+    }
+
+    let plaintext = plaintext
+        .get_mut(..ciphertext.len())
+        .ok_or(Error::WrongLength)?;
+
+    let aes = AesKey::new(key);
+    let mut prev = iv;
+    for (c, p) in ciphertext
+        .chunks_exact(16)
+        .zip(plaintext.chunks_exact_mut(16))
+    {
+        p.copy_from_slice(c);
+        aes.decrypt_block(p);
+        for (p, v) in p.iter_mut().zip(prev) {
+            *p ^= *v;
+        }
+        prev = c;
+    }
+
+    // the padding is checked in constant time, so timing does not
+    // reveal whether a wrong key produced a plausible final block.
+    let pad = plaintext[plaintext.len() - 1];
+    // non-zero unless `pad` is in 1..=16
+    let mut diff = pad.wrapping_sub(1) & 0xf0;
+    for (i, b) in plaintext[plaintext.len() - 16..].iter().rev().enumerate() {
+        // 0xff for the final `pad` bytes, otherwise 0
+        let in_pad = 0u8.wrapping_sub(((i as u16).wrapping_sub(pad as u16) >> 15) as u8);
+        diff |= (*b ^ pad) & in_pad;
+    }
+
+    if optimise_barrier_u8(diff) != 0 {
+        zeroise(plaintext);
+        return Err(Error::DecryptFailed);
+    }
+
+    let len = plaintext.len() - pad as usize;
+    Ok(&plaintext[..len])
+}
+
+/// AES-CBC encryption in place, adding PKCS#7 padding.
+///
+/// The plaintext is the first `plaintext_len` bytes of `inout`.
+fn cbc_encrypt<'a>(
+    key: &[u8],
+    iv: &[u8; 16],
+    inout: &'a mut [u8],
+    plaintext_len: usize,
+) -> Result<&'a [u8], Error> {
+    let pad = 16 - plaintext_len % 16;
+    let ciphertext = inout
+        .get_mut(..plaintext_len + pad)
+        .ok_or(Error::WrongLength)?;
+    ciphertext[plaintext_len..].fill(pad as u8);
+
+    let aes = AesKey::new(key);
+    let mut prev = *iv;
+    for block in ciphertext.chunks_exact_mut(16) {
+        for (b, v) in block.iter_mut().zip(prev.iter()) {
+            *b ^= *v;
+        }
+        aes.encrypt_block(block);
+        prev.copy_from_slice(block);
+    }
+
+    Ok(ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mid::rng::SliceRandomSource;
+
+    #[test]
+    fn pbkdf2_test_vectors() {
+        // from RFC7914 section 11
+        let mut out = [0u8; 64];
+        pbkdf2_hmac_sha256(b"passwd", b"salt", 1, &mut out);
+        assert_eq!(
+            out,
+            *b"\x55\xac\x04\x6e\x56\xe3\x08\x9f\xec\x16\x91\xc2\x25\x44\xb6\x05\
+               \xf9\x41\x85\x21\x6d\xde\x04\x65\xe6\x8b\x9d\x57\xc2\x0d\xac\xbc\
+               \x49\xca\x9c\xcc\xf1\x79\xb6\x45\x99\x16\x64\xb3\x9d\x77\xef\x31\
+               \x7c\x71\xb8\x45\xb1\xe3\x0b\xd5\x09\x11\x20\x41\xd3\xa1\x97\x83"
+        );
+
+        pbkdf2_hmac_sha256(b"Password", b"NaCl", 80000, &mut out);
+        assert_eq!(
+            out,
+            *b"\x4d\xdc\xd8\xf6\x0b\x98\xbe\x21\x83\x0c\xee\x5e\xf2\x27\x01\xf9\
+               \x64\x1a\x44\x18\xd0\x4c\x04\x14\xae\xff\x08\x87\x6b\x34\xab\x56\
+               \xa1\xd4\x25\xa1\x22\x58\x33\x54\x9a\xdb\x84\x1b\x51\xc9\xb3\x17\
+               \x6a\x27\x2b\xde\xbb\xa1\xd0\x78\x47\x8f\x62\xb3\x97\xf3\x3c\x8d"
+        );
+    }
+
+    #[test]
+    fn cbc_roundtrip() {
+        let key = [0x11u8; 32];
+        let iv = [0x22u8; 16];
+
+        for len in 0..64 {
+            let mut buffer = [0x33u8; 80];
+            let ciphertext = cbc_encrypt(&key, &iv, &mut buffer, len).unwrap();
+            assert_eq!(ciphertext.len(), (len / 16 + 1) * 16);
+
+            let mut decrypted = [0u8; 80];
+            assert_eq!(
+                cbc_decrypt(&key, &iv, ciphertext, &mut decrypted).unwrap(),
+                &[0x33u8; 64][..len]
+            );
+        }
+    }
+
+    #[test]
+    fn cbc_bad_padding() {
+        let key = [0x11u8; 16];
+        let iv = [0x22u8; 16];
+        let aes = AesKey::new(&key);
+
+        for pad in [0u8, 17, 0xff] {
+            let mut block = [pad; 16];
+            for (b, v) in block.iter_mut().zip(iv.iter()) {
+                *b ^= *v;
+            }
+            aes.encrypt_block(&mut block);
+
+            let mut plaintext = [0u8; 16];
+            assert_eq!(
+                cbc_decrypt(&key, &iv, &block, &mut plaintext).unwrap_err(),
+                Error::DecryptFailed
+            );
+        }
+
+        // the final byte is valid, but an earlier padding byte is not
+        let mut block = [0x05u8; 16];
+        block[11] = 0x04;
+        for (b, v) in block.iter_mut().zip(iv.iter()) {
+            *b ^= *v;
+        }
+        aes.encrypt_block(&mut block);
+        let mut plaintext = [0u8; 16];
+        assert_eq!(
+            cbc_decrypt(&key, &iv, &block, &mut plaintext).unwrap_err(),
+            Error::DecryptFailed
+        );
+
+        let mut plaintext = [0u8; 32];
+        assert_eq!(
+            cbc_decrypt(&key, &iv, &[0u8; 15], &mut plaintext).unwrap_err(),
+            Error::DecryptFailed
+        );
+        assert_eq!(
+            cbc_decrypt(&key, &iv, &[], &mut plaintext).unwrap_err(),
+            Error::DecryptFailed
+        );
+        assert_eq!(
+            cbc_decrypt(&key, &iv, &[0u8; 48], &mut plaintext).unwrap_err(),
+            Error::WrongLength
+        );
+    }
+
+    #[test]
+    fn iteration_limit() {
+        let iterations = (MAX_ITERATIONS + 1).to_be_bytes();
+        let mut params = [0u8; 64];
+        let params_len = pkix::Pbkdf2Params {
+            salt: asn1::OctetString::new(b"salt"),
+            iterationCount: asn1::Integer::from_bytes(&iterations),
+            keyLength: None,
+            prf: Some(pkix::AlgorithmIdentifier {
+                algorithm: oid::id_hmacWithSHA256.clone(),
+                parameters: Some(asn1::Any::Null(asn1::Null)),
+            }),
+        }
+        .encode(&mut asn1::Encoder::new(&mut params))
+        .unwrap();
+
+        let algorithm = pkix::AlgorithmIdentifier {
+            algorithm: oid::id_PBKDF2.clone(),
+            parameters: Some(asn1::Any::Sequence(asn1::Sequence::new(
+                &params[..params_len],
+            ))),
+        };
+        assert_eq!(
+            derive_key(&algorithm, b"password", &mut [0u8; 32]).unwrap_err(),
+            KeyFormatError::UnsupportedPkcs8Encryption.into()
+        );
+    }
+
+    #[test]
+    fn encrypt_decrypt() {
+        let mut rng = SliceRandomSource(&[0x44; 32]);
+        let mut params = [0u8; 128];
+        let mut buffer = [0u8; 32];
+        buffer[..11].copy_from_slice(b"hello world");
+        let (params, ciphertext) =
+            encrypt(&mut rng, b"password", 1000, &mut buffer, 11, &mut params).unwrap();
+
+        let algorithm = pkix::AlgorithmIdentifier {
+            algorithm: oid::id_PBES2.clone(),
+            parameters: Some(asn1::Any::Sequence(asn1::Sequence::new(params))),
+        };
+
+        let mut plaintext = [0u8; 32];
+        assert_eq!(
+            decrypt(&algorithm, b"password", ciphertext, &mut plaintext).unwrap(),
+            b"hello world"
+        );
+        assert_eq!(
+            decrypt(&algorithm, b"wrong password", ciphertext, &mut plaintext).unwrap_err(),
+            Error::DecryptFailed
+        );
+    }
+}
@@ -56,6 +56,11 @@ pub(crate) fn encode_pkcs1_sig(out: &mut [u8], digest_info: &[u8], hash: &[u8])
     hash_out.copy_from_slice(hash);
 }
 
+#[cfg(feature = "sha1")]
+pub(crate) static DIGESTINFO_SHA1: &[u8] = &[
+    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+];
+
 pub(crate) static DIGESTINFO_SHA256: &[u8] = &[
     0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
     0x00, 0x04, 0x20,
@@ -73,7 +78,7 @@ pub(crate) static DIGESTINFO_SHA512: &[u8] = &[
 
 /// This is EMSA-PSS-ENCODE.
 ///
-/// `sLen` is fixed as `hLen`.
+/// `sLen` is `s_len`.
 /// `MGF` is `MGF1` with hash `H`.
 /// `out` is the modulus-length output buffer.
 /// `hash` is the message hash, made by the caller using `H`.
@@ -82,6 +87,7 @@ pub(crate) fn encode_pss_sig<H: Hash>(
     out: &mut [u8],
     rng: &mut dyn RandomSource,
     hash: &[u8],
+    s_len: usize,
 ) -> Result<(), Error> {
     // 1.  If the length of M is greater than the input limitation for the
     //     hash function (2^61 - 1 octets for SHA-1), output "message too
@@ -90,22 +96,26 @@ pub(crate) fn encode_pss_sig<H: Hash>(
     // (by caller)
 
     // 3.  If emLen < hLen + sLen + 2, output "encoding error" and stop.
-    let s_len = hash.len();
     let h_len = hash.len();
     let em_len = out.len();
-    assert!(em_len >= h_len + s_len + 2);
+    if em_len < h_len + 2 || em_len - h_len - 2 < s_len {
+        return Err(Error::OutOfRange);
+    }
+
+    // (the salt is generated in-place in DB, see step 8)
+    let (db, h_bc) = out.split_at_mut(em_len - h_len - 1);
+    let (ps, ps_sep_salt) = db.split_at_mut(em_len - s_len - h_len - 2);
+    let (ps_sep, salt) = ps_sep_salt.split_first_mut().unwrap();
 
     // 4.  Generate a random octet string salt of length sLen; if sLen = 0,
     //     then salt is the empty string.
-    let mut salt = H::zeroed_output();
-    assert_eq!(salt.as_ref().len(), s_len);
-    rng.fill(salt.as_mut())?;
+    rng.fill(salt)?;
 
     // 5.  Let
     //       M' = (0x)00 00 00 00 00 00 00 00 || mHash || salt;
     //     M' is an octet string of length 8 + hLen + sLen with eight
     //     initial zero octets.
-    let m_prime = [&[0u8; 8], hash, salt.as_ref()];
+    let m_prime = [&[0u8; 8], hash, &*salt];
 
     // 6.  Let H = Hash(M'), an octet string of length hLen.
     let mut ctx = H::new();
@@ -119,12 +129,8 @@ pub(crate) fn encode_pss_sig<H: Hash>(
     //     zero octets.  The length of PS may be 0.
     // 8.  Let DB = PS || 0x01 || salt; DB is an octet string of length
     //     emLen - hLen - 1.
-    let (db, h_bc) = out.split_at_mut(em_len - h_len - 1);
-    let (ps, ps_sep_salt) = db.split_at_mut(em_len - s_len - h_len - 2);
-    let (ps_sep, salt_out) = ps_sep_salt.split_first_mut().unwrap();
     ps.fill(0x00);
     *ps_sep = 0x01;
-    salt_out.copy_from_slice(salt.as_ref());
 
     // 9.  Let dbMask = MGF(H, emLen - hLen - 1).
     // 10. Let maskedDB = DB \xor dbMask.
@@ -145,14 +151,18 @@ pub(crate) fn encode_pss_sig<H: Hash>(
 
 /// This is EMSA-PSS-VERIFY.
 ///
-/// `sLen` is fixed as `hLen`, like `encode_pss_sig`.
+/// `sLen` is `s_len`.
 ///
 /// `MGF` is `MGF1` with hash `H`.
 ///
 /// `em` is the modulus-length input and temporary buffer.
 ///
 /// `m_hash` is the message hash, made by the caller using `H`.
-pub(crate) fn verify_pss_sig<H: Hash>(em: &mut [u8], m_hash: &[u8]) -> Result<(), Error> {
+pub(crate) fn verify_pss_sig<H: Hash>(
+    em: &mut [u8],
+    m_hash: &[u8],
+    s_len: usize,
+) -> Result<(), Error> {
     // 1.   If the length of M is greater than the input limitation for
     //      the hash function (2^61 - 1 octets for SHA-1), output
     //      "inconsistent" and stop.
@@ -162,8 +172,7 @@ pub(crate) fn verify_pss_sig<H: Hash>(em: &mut [u8], m_hash: &[u8]) -> Result<()
     // 3.   If emLen < hLen + sLen + 2, output "inconsistent" and stop.
     let em_len = em.len();
     let h_len = m_hash.len();
-    let s_len = h_len;
-    if em_len < h_len + s_len + 2 {
+    if em_len < h_len + 2 || em_len - h_len - 2 < s_len {
         return Err(Error::BadSignature);
     }
 
@@ -338,7 +347,7 @@ mod tests {
         );
         let hash = b"\xe3\xb0\xc4\x42\x98\xfc\x1c\x14\x9a\xfb\xf4\xc8\x99\x6f\xb9\x24\
                      \x27\xae\x41\xe4\x64\x9b\x93\x4c\xa4\x95\x99\x1b\x78\x52\xb8\x55";
-        encode_pss_sig::<hash::Sha256>(&mut buf, &mut seed, hash).unwrap();
+        encode_pss_sig::<hash::Sha256>(&mut buf, &mut seed, hash, 32).unwrap();
 
         assert_eq!(
             buf,
@@ -365,6 +374,6 @@ mod tests {
             ],
         );
 
-        verify_pss_sig::<hash::Sha256>(&mut buf, hash).unwrap();
+        verify_pss_sig::<hash::Sha256>(&mut buf, hash, 32).unwrap();
     }
 }
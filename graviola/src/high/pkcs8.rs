@@ -3,7 +3,13 @@
 
 use super::asn1::pkix;
 use super::asn1::{self, Integer, Type};
+#[cfg(feature = "pbes2")]
+use super::pbes2;
 use crate::error::{Error, KeyFormatError};
+#[cfg(feature = "pbes2")]
+use crate::low::Entry;
+#[cfg(feature = "pbes2")]
+use crate::mid::rng::RandomSource;
 
 /// Helper for decoding PKCS#8 key encodings.
 ///
@@ -15,11 +21,33 @@ use crate::error::{Error, KeyFormatError};
 ///
 /// Then returns the slice that covers the `privateKey` `OCTET STRING` body,
 /// that can be decoded by the caller as the correct asn1 type.
+#[cfg(any(feature = "p256", feature = "p384"))]
 pub(crate) fn decode_pkcs8<'a>(
     slice: &'a [u8],
     algorithm: &asn1::ObjectId,
     parameters: Option<asn1::Any<'_>>,
 ) -> Result<&'a [u8], Error> {
+    let (key_algorithm, private_key) = decode_pkcs8_any_algorithm(slice)?;
+
+    if key_algorithm.algorithm != *algorithm {
+        return Err(KeyFormatError::MismatchedPkcs8Algorithm.into());
+    }
+
+    if key_algorithm.parameters != parameters {
+        return Err(KeyFormatError::MismatchedPkcs8Parameters.into());
+    }
+
+    Ok(private_key)
+}
+
+/// Helper for decoding PKCS#8 key encodings, where the caller checks
+/// the key algorithm.
+///
+/// This is like `decode_pkcs8`, but returns the `privateKeyAlgorithm`
+/// alongside the `privateKey` body.  Only the version is checked.
+pub(crate) fn decode_pkcs8_any_algorithm(
+    slice: &[u8],
+) -> Result<(pkix::AlgorithmIdentifier<'_>, &[u8]), Error> {
     let pki =
         pkix::PrivateKeyInfo::parse(&mut asn1::Parser::new(slice)).map_err(Error::Asn1Error)?;
 
@@ -27,13 +55,98 @@ pub(crate) fn decode_pkcs8<'a>(
         return Err(KeyFormatError::UnsupportedPkcs8Version.into());
     }
 
-    if pki.privateKeyAlgorithm.algorithm != *algorithm {
-        return Err(KeyFormatError::MismatchedPkcs8Algorithm.into());
-    }
+    Ok((pki.privateKeyAlgorithm, pki.privateKey.into_octets()))
+}
 
-    if pki.privateKeyAlgorithm.parameters != parameters {
-        return Err(KeyFormatError::MismatchedPkcs8Parameters.into());
-    }
+/// Helper for encoding PKCS#8 key encodings.
+///
+/// This writes a `PrivateKeyInfo` with version 0, the given `algorithm`,
+/// and `private_key` as the `privateKey` body.  The encoding is written
+/// to the front of `output`, and that prefix is returned.
+pub(crate) fn encode_pkcs8<'a>(
+    algorithm: pkix::AlgorithmIdentifier<'_>,
+    private_key: &[u8],
+    output: &'a mut [u8],
+) -> Result<&'a [u8], Error> {
+    let pki = pkix::PrivateKeyInfo {
+        version: Integer::new(&[0]),
+        privateKeyAlgorithm: algorithm,
+        privateKey: asn1::OctetString::new(private_key),
+    };
+
+    let len = pki
+        .encode(&mut asn1::Encoder::new(output))
+        .map_err(Error::Asn1Error)?;
+    Ok(&output[..len])
+}
+
+/// Decrypts an encrypted PKCS#8 key, without decoding the key itself.
+///
+/// `slice` is an `EncryptedPrivateKeyInfo`, as defined in
+/// [RFC5208](https://datatracker.ietf.org/doc/html/rfc5208#section-6).
+/// It is decrypted with `password`, and the resulting (unencrypted)
+/// PKCS#8 `PrivateKeyInfo` is written to the front of `plaintext`.  That
+/// prefix is returned.  A `plaintext` as long as `slice` is sufficient.
+///
+/// This is useful when the key type is not known in advance: the
+/// result may be tried with each key type's `from_pkcs8_der()` function,
+/// without repeating the (deliberately slow) key derivation.  The
+/// plaintext is secret, and should be zeroised by the caller after use.
+///
+/// The supported encryption algorithms and errors are as for
+/// [`crate::signing::rsa::SigningKey::from_encrypted_pkcs8_der()`].
+#[cfg(feature = "pbes2")]
+pub fn decrypt_pkcs8<'a>(
+    slice: &[u8],
+    password: &[u8],
+    plaintext: &'a mut [u8],
+) -> Result<&'a [u8], Error> {
+    let _ = Entry::new_secret();
+    let epki = pkix::EncryptedPrivateKeyInfo::parse(&mut asn1::Parser::new(slice))
+        .map_err(Error::Asn1Error)?;
+
+    pbes2::decrypt(
+        &epki.encryptionAlgorithm,
+        password,
+        epki.encryptedData.into_octets(),
+        plaintext,
+    )
+}
+
+/// Helper for encrypting PKCS#8 key encodings.
+///
+/// On entry, the first `plaintext_len` bytes of `inout` are a `PrivateKeyInfo`
+/// encoding.  This is encrypted in place (so `inout` should have 16 bytes of
+/// space after it) with `password`, and then an `EncryptedPrivateKeyInfo`
+/// is written to the front of `output`.  That prefix is returned.
+#[cfg(feature = "pbes2")]
+pub(crate) fn encrypt_pkcs8<'a>(
+    rng: &mut dyn RandomSource,
+    password: &[u8],
+    inout: &mut [u8],
+    plaintext_len: usize,
+    output: &'a mut [u8],
+) -> Result<&'a [u8], Error> {
+    let mut params = [0u8; 128];
+    let (params, ciphertext) = pbes2::encrypt(
+        rng,
+        password,
+        pbes2::ITERATIONS,
+        inout,
+        plaintext_len,
+        &mut params,
+    )?;
+
+    let epki = pkix::EncryptedPrivateKeyInfo {
+        encryptionAlgorithm: pkix::AlgorithmIdentifier {
+            algorithm: asn1::oid::id_PBES2.clone(),
+            parameters: Some(asn1::Any::Sequence(asn1::Sequence::new(params))),
+        },
+        encryptedData: asn1::OctetString::new(ciphertext),
+    };
 
-    Ok(pki.privateKey.into_octets())
+    let len = epki
+        .encode(&mut asn1::Encoder::new(output))
+        .map_err(Error::Asn1Error)?;
+    Ok(&output[..len])
 }
@@ -1,12 +1,15 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
-use crate::high::asn1::{self, pkix, Type};
-use crate::high::hash::{self, Hash};
-use crate::high::{pkcs1, pkcs8};
-use crate::low::Entry;
+use crate::error::KeyFormatError;
+use crate::high::asn1::{self, oid, pkix, Type};
+use crate::high::hash::{self, Hash, SigningHash};
+use crate::high::{pem, pkcs1, pkcs8};
 use crate::low::PosInt;
-use crate::mid::rng::SystemRandom;
+use crate::low::{zeroise, Entry};
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
 use crate::mid::{rsa_priv, rsa_pub};
 use crate::Error;
 
@@ -15,7 +18,7 @@ use crate::Error;
 /// Keys supported by this library have public moduli between
 /// 2048- and 8192-bits.
 #[derive(Debug)]
-pub struct VerifyingKey(rsa_pub::RsaPublicKey);
+pub struct VerifyingKey(rsa_pub::RsaPublicKey, Restriction);
 
 impl VerifyingKey {
     /// Decodes an RSA public verification key from PKCS#1 DER format.
@@ -40,7 +43,112 @@ impl VerifyingKey {
 
         let pub_key = rsa_pub::RsaPublicKey::new(n, e)?;
 
-        Ok(Self(pub_key))
+        Ok(Self(pub_key, Restriction::None))
+    }
+
+    /// Decodes an RSA public verification key from X.509 `SubjectPublicKeyInfo`
+    /// DER format.
+    ///
+    /// This format is defined in
+    /// [RFC5280](https://datatracker.ietf.org/doc/html/rfc5280#section-4.1.2.7).
+    ///
+    /// `algorithm` inside this encoding may be `rsaEncryption`, or
+    /// `id-RSASSA-PSS` as described in
+    /// [RFC4055](https://datatracker.ietf.org/doc/html/rfc4055#section-1.2).
+    /// In the latter case, the key is restricted to RSASSA-PSS
+    /// and the parameters given: see [`Self::restriction()`].
+    pub fn from_spki_der(bytes: &[u8]) -> Result<Self, Error> {
+        let _ = Entry::new_public();
+        let spki = pkix::SubjectPublicKeyInfo::from_bytes(bytes).map_err(Error::Asn1Error)?;
+
+        let restriction = match Restriction::from_algorithm_identifier(&spki.algorithm) {
+            Err(Error::KeyFormatError(KeyFormatError::MismatchedPkcs8Algorithm)) => {
+                return Err(KeyFormatError::MismatchedSpkiAlgorithm.into());
+            }
+            Err(Error::KeyFormatError(KeyFormatError::MismatchedPkcs8Parameters)) => {
+                return Err(KeyFormatError::MismatchedSpkiParameters.into());
+            }
+            other => other?,
+        };
+
+        let Self(pub_key, _) = Self::from_pkcs1_der(spki.subjectPublicKey.into_octets())?;
+        Ok(Self(pub_key, restriction))
+    }
+
+    /// Decodes an RSA public verification key from PEM format.
+    ///
+    /// The first PEM section in `pem` is used.  A `PUBLIC KEY` section
+    /// is decoded as for [`Self::from_spki_der()`], and an `RSA PUBLIC KEY`
+    /// section as for [`Self::from_pkcs1_der()`].  Other labels result in
+    /// [`crate::KeyFormatError::MismatchedPemLabel`].
+    pub fn from_pem(pem: &str) -> Result<Self, Error> {
+        let _ = Entry::new_public();
+        let section = pem::Section::find(pem)?;
+        let mut der = [0u8; MAX_SPKI_LEN];
+        match section.label {
+            "PUBLIC KEY" => Self::from_spki_der(section.decode(&mut der)?),
+            "RSA PUBLIC KEY" => Self::from_pkcs1_der(section.decode(&mut der)?),
+            _ => Err(KeyFormatError::MismatchedPemLabel.into()),
+        }
+    }
+
+    /// Encodes this key in X.509 `SubjectPublicKeyInfo` DER format.
+    ///
+    /// The `algorithm` reflects [`Self::restriction()`]: it is
+    /// `rsaEncryption` for unrestricted keys, and `id-RSASSA-PSS`
+    /// otherwise.
+    ///
+    /// The encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    pub fn to_spki_der<'a>(&self, output: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let _ = Entry::new_public();
+        let mut params = [0u8; 128];
+        let algorithm = self.1.to_algorithm_identifier(&mut params)?;
+
+        let mut pkcs1 = [0u8; MAX_SPKI_LEN];
+        let pkcs1 = self.encode_pkcs1(&mut pkcs1)?;
+
+        let len = pkix::SubjectPublicKeyInfo {
+            algorithm,
+            subjectPublicKey: asn1::BitString::new(pkcs1),
+        }
+        .encode(&mut asn1::Encoder::new(output))
+        .map_err(Error::Asn1Error)?;
+        Ok(&output[..len])
+    }
+
+    /// Encodes this key in PEM format, with the label `PUBLIC KEY`.
+    ///
+    /// The key is encoded as for [`Self::to_spki_der()`].  The PEM
+    /// encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    pub fn to_pem<'a>(&self, output: &'a mut [u8]) -> Result<&'a str, Error> {
+        let _ = Entry::new_public();
+        let mut der = [0u8; MAX_SPKI_LEN];
+        let der = self.to_spki_der(&mut der)?;
+        pem::encode("PUBLIC KEY", der, output)
+    }
+
+    /// Returns how this key may be used.
+    pub fn restriction(&self) -> Restriction {
+        self.1
+    }
+
+    /// Verifies `signature`, using RSASSA-PKCS1-v1_5 with SHA-1.
+    ///
+    /// `message` is the (unhashed) signed message.  It is hashed
+    /// using SHA-1 by this function.
+    ///
+    /// This is provided only for verifying legacy signatures.  There
+    /// is intentionally no way to make such signatures.  A `message`
+    /// containing a known SHA-1 collision attack will fail to verify.
+    ///
+    /// [`Error::BadSignature`] is returned if the signature is invalid.
+    #[cfg(feature = "sha1")]
+    pub fn verify_pkcs1_sha1(&self, signature: &[u8], message: &[u8]) -> Result<(), Error> {
+        let _ = Entry::new_public();
+        let hash = hash::Sha1::hash(message);
+        self._verify_pkcs1(signature, pkcs1::DIGESTINFO_SHA1, hash.as_ref())
     }
 
     /// Verifies `signature`, using RSASSA-PKCS1-v1_5 with SHA-256.
@@ -79,12 +187,27 @@ impl VerifyingKey {
         self._verify_pkcs1(signature, pkcs1::DIGESTINFO_SHA512, hash.as_ref())
     }
 
+    /// Encodes this key in PKCS#1 `RSAPublicKey` DER format.
+    pub(crate) fn encode_pkcs1<'a>(&self, output: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let mut parts = [0u8; rsa_pub::MAX_PARTS_LEN];
+        let [n, e] = self.0.write_parts(&mut &mut parts[..])?;
+        let len = pkix::RSAPublicKey {
+            modulus: asn1::Integer::from_bytes(n),
+            publicExponent: asn1::Integer::from_bytes(e),
+        }
+        .encode(&mut asn1::Encoder::new(output))
+        .map_err(Error::Asn1Error)?;
+        Ok(&output[..len])
+    }
+
     fn _verify_pkcs1(
         &self,
         signature: &[u8],
         digest_info: &[u8],
         hash: &[u8],
     ) -> Result<(), Error> {
+        self.1.check_pkcs1()?;
+
         let c = PosInt::from_bytes(signature).map_err(|_| Error::BadSignature)?;
         let m = self.0.public_op(c).map_err(|_| Error::BadSignature)?;
 
@@ -116,7 +239,7 @@ impl VerifyingKey {
     /// (and earlier standards, including the original PKCS#1 standard).
     pub fn verify_pss_sha256(&self, signature: &[u8], message: &[u8]) -> Result<(), Error> {
         let _ = Entry::new_public();
-        self._verify_pss::<hash::Sha256>(signature, message)
+        self._verify_pss::<hash::Sha256>(PssParameters::new(PssHash::Sha256), signature, message)
     }
 
     /// Verifies `signature`, using RSASSA-PSS with SHA-384.
@@ -134,7 +257,7 @@ impl VerifyingKey {
     /// (and earlier standards, including the original PKCS#1 standard).
     pub fn verify_pss_sha384(&self, signature: &[u8], message: &[u8]) -> Result<(), Error> {
         let _ = Entry::new_public();
-        self._verify_pss::<hash::Sha384>(signature, message)
+        self._verify_pss::<hash::Sha384>(PssParameters::new(PssHash::Sha384), signature, message)
     }
 
     /// Verifies `signature`, using RSASSA-PSS with SHA-512.
@@ -152,10 +275,44 @@ impl VerifyingKey {
     /// (and earlier standards, including the original PKCS#1 standard).
     pub fn verify_pss_sha512(&self, signature: &[u8], message: &[u8]) -> Result<(), Error> {
         let _ = Entry::new_public();
-        self._verify_pss::<hash::Sha512>(signature, message)
+        self._verify_pss::<hash::Sha512>(PssParameters::new(PssHash::Sha512), signature, message)
+    }
+
+    /// Verifies `signature`, using RSASSA-PSS with the given `parameters`.
+    ///
+    /// This allows any `saltLength`, unlike [`Self::verify_pss_sha256()`] and
+    /// friends.
+    ///
+    /// `message` is the (unhashed) signed message.  It is hashed
+    /// using `parameters.hash` by this function.
+    ///
+    /// [`Error::BadSignature`] is returned if the signature is invalid.
+    ///
+    /// RSASSA-PSS is described in
+    /// [RFC8017](https://datatracker.ietf.org/doc/html/rfc8017#section-8.1)
+    /// (and earlier standards, including the original PKCS#1 standard).
+    pub fn verify_pss(
+        &self,
+        parameters: PssParameters,
+        signature: &[u8],
+        message: &[u8],
+    ) -> Result<(), Error> {
+        let _ = Entry::new_public();
+        match parameters.hash {
+            PssHash::Sha256 => self._verify_pss::<hash::Sha256>(parameters, signature, message),
+            PssHash::Sha384 => self._verify_pss::<hash::Sha384>(parameters, signature, message),
+            PssHash::Sha512 => self._verify_pss::<hash::Sha512>(parameters, signature, message),
+        }
     }
 
-    fn _verify_pss<H: Hash>(&self, signature: &[u8], message: &[u8]) -> Result<(), Error> {
+    fn _verify_pss<H: Hash>(
+        &self,
+        parameters: PssParameters,
+        signature: &[u8],
+        message: &[u8],
+    ) -> Result<(), Error> {
+        self.1.check_pss(parameters)?;
+
         let hash = H::hash(message);
 
         if signature.len() > self.0.modulus_len_bytes() {
@@ -167,7 +324,11 @@ impl VerifyingKey {
         let mut m_bytes = [0u8; rsa_pub::MAX_PUBLIC_MODULUS_BYTES];
         let m_bytes_len = m.to_bytes(&mut m_bytes)?.len();
 
-        pkcs1::verify_pss_sig::<H>(&mut m_bytes[..m_bytes_len], hash.as_ref())
+        pkcs1::verify_pss_sig::<H>(
+            &mut m_bytes[..m_bytes_len],
+            hash.as_ref(),
+            parameters.salt_len,
+        )
     }
 }
 
@@ -175,7 +336,7 @@ impl VerifyingKey {
 ///
 /// Keys supported by this library have public moduli between
 /// 2048- and 8192-bits.  Only two-prime RSA keys are supported.
-pub struct SigningKey(rsa_priv::RsaPrivateKey);
+pub struct SigningKey(rsa_priv::RsaPrivateKey, Restriction);
 
 impl SigningKey {
     /// Decodes an RSA signing key from PKCS#1 DER format.
@@ -183,6 +344,11 @@ impl SigningKey {
     /// This format is defined in
     /// [RFC8017](https://datatracker.ietf.org/doc/html/rfc8017#appendix-A.1.2)
     /// (and earlier standards, including the original PKCS#1 standard).
+    ///
+    /// The key parts are checked against each other: `modulus` must be
+    /// `prime1 * prime2`, and `privateExponent`, `exponent1` and `exponent2`
+    /// must be consistent with `publicExponent`.  Otherwise, a
+    /// [`crate::KeyFormatError`] is returned.
     pub fn from_pkcs1_der(bytes: &[u8]) -> Result<Self, Error> {
         let _ = Entry::new_secret();
         let decoded = pkix::RSAPrivateKey::from_bytes(bytes).map_err(Error::Asn1Error)?;
@@ -202,14 +368,15 @@ impl SigningKey {
             .map_err(Error::Asn1Error)?;
         let e = e.try_into().map_err(|_| Error::OutOfRange)?;
 
+        let d = PosInt::from_bytes(decoded.privateExponent.as_ref())?.into();
         let p = PosInt::from_bytes(decoded.prime1.as_ref())?.into();
         let q = PosInt::from_bytes(decoded.prime2.as_ref())?.into();
         let dp = PosInt::from_bytes(decoded.exponent1.as_ref())?.into();
         let dq = PosInt::from_bytes(decoded.exponent2.as_ref())?.into();
         let iqmp = PosInt::from_bytes(decoded.coefficient.as_ref())?.into();
 
-        let priv_key = rsa_priv::RsaPrivateKey::new(p, q, dp, dq, iqmp, n, e)?;
-        Ok(Self(priv_key))
+        let priv_key = rsa_priv::RsaPrivateKey::new(p, q, d, dp, dq, iqmp, n, e)?;
+        Ok(Self(priv_key, Restriction::None))
     }
 
     /// Decodes an RSA signing key from PKCS#8 DER format.
@@ -218,21 +385,145 @@ impl SigningKey {
     /// [RFC5208](https://datatracker.ietf.org/doc/html/rfc5208#section-5)
     /// (and earlier standards, including the original PKCS#8 standard).
     ///
-    /// `privateKeyAlgorithm` inside this encoding must be `rsaEncryption`.
+    /// `privateKeyAlgorithm` inside this encoding may be `rsaEncryption`,
+    /// or `id-RSASSA-PSS` as described in
+    /// [RFC4055](https://datatracker.ietf.org/doc/html/rfc4055#section-1.2).
+    /// In the latter case, the key is restricted to RSASSA-PSS
+    /// and the parameters given: see [`Self::restriction()`].
     pub fn from_pkcs8_der(bytes: &[u8]) -> Result<Self, Error> {
         let _ = Entry::new_secret();
-        pkcs8::decode_pkcs8(
-            bytes,
-            &asn1::oid::rsaEncryption,
-            Some(asn1::Any::Null(asn1::Null)),
-        )
-        .and_then(Self::from_pkcs1_der)
+        let (algorithm, private_key) = pkcs8::decode_pkcs8_any_algorithm(bytes)?;
+        let restriction = Restriction::from_algorithm_identifier(&algorithm)?;
+        let Self(priv_key, _) = Self::from_pkcs1_der(private_key)?;
+        Ok(Self(priv_key, restriction))
+    }
+
+    /// Decodes an RSA signing key from encrypted PKCS#8 DER format.
+    ///
+    /// This format is the `EncryptedPrivateKeyInfo` defined in
+    /// [RFC5208](https://datatracker.ietf.org/doc/html/rfc5208#section-6).
+    /// It is decrypted using `password`, and the result is decoded as
+    /// for [`Self::from_pkcs8_der()`].
+    ///
+    /// Only PBES2 encryption is supported, with PBKDF2-HMAC-SHA256 and
+    /// AES-128 or AES-256 in CBC or GCM mode.  Otherwise,
+    /// [`crate::KeyFormatError::UnsupportedPkcs8Encryption`] is returned.
+    /// A wrong `password` typically results in [`Error::DecryptFailed`].
+    #[cfg(feature = "pbes2")]
+    pub fn from_encrypted_pkcs8_der(bytes: &[u8], password: &[u8]) -> Result<Self, Error> {
+        let _ = Entry::new_secret();
+        let mut plaintext = [0u8; MAX_PKCS8_LEN];
+        let result =
+            pkcs8::decrypt_pkcs8(bytes, password, &mut plaintext).and_then(Self::from_pkcs8_der);
+        zeroise(&mut plaintext);
+        result
+    }
+
+    /// Encodes this key in PKCS#8 DER format.
+    ///
+    /// The `privateKeyAlgorithm` reflects [`Self::restriction()`]: it is
+    /// `rsaEncryption` for unrestricted keys, and `id-RSASSA-PSS`
+    /// otherwise.
+    ///
+    /// The encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    pub fn to_pkcs8_der<'a>(&self, output: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let _ = Entry::new_secret();
+        let mut params = [0u8; 128];
+        let algorithm = self.1.to_algorithm_identifier(&mut params)?;
+
+        let mut pkcs1 = [0u8; MAX_PKCS8_LEN];
+        let result = self
+            .encode_pkcs1(&mut pkcs1)
+            .and_then(|pkcs1| pkcs8::encode_pkcs8(algorithm, pkcs1, output));
+        zeroise(&mut pkcs1);
+        result
+    }
+
+    /// Encodes this key in encrypted PKCS#8 DER format.
+    ///
+    /// The key is encoded as for [`Self::to_pkcs8_der()`], and then
+    /// encrypted using `password`.  This uses PBES2, with PBKDF2-HMAC-SHA256
+    /// and AES-256-CBC.  The salt and IV are generated using the default
+    /// [`RandomSource`].
+    ///
+    /// The encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    #[cfg(all(feature = "pbes2", feature = "std"))]
+    pub fn to_encrypted_pkcs8_der<'a>(
+        &self,
+        password: &[u8],
+        output: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        self.to_encrypted_pkcs8_der_with_rng(&mut DefaultRandom, password, output)
+    }
+
+    /// Encodes this key in encrypted PKCS#8 DER format, using randomness
+    /// from `rng`.
+    ///
+    /// See [`Self::to_encrypted_pkcs8_der()`] for details of the other arguments.
+    #[cfg(feature = "pbes2")]
+    pub fn to_encrypted_pkcs8_der_with_rng<'a>(
+        &self,
+        rng: &mut dyn RandomSource,
+        password: &[u8],
+        output: &'a mut [u8],
+    ) -> Result<&'a [u8], Error> {
+        let _ = Entry::new_secret();
+        let mut plaintext = [0u8; MAX_PKCS8_LEN];
+        let result = self
+            .to_pkcs8_der(&mut plaintext)
+            .map(|pkcs8| pkcs8.len())
+            .and_then(|len| pkcs8::encrypt_pkcs8(rng, password, &mut plaintext, len, output));
+        zeroise(&mut plaintext);
+        result
+    }
+
+    /// Decodes an RSA signing key from PEM format.
+    ///
+    /// The first PEM section in `pem` is used.  A `PRIVATE KEY` section
+    /// is decoded as for [`Self::from_pkcs8_der()`], and an `RSA PRIVATE KEY`
+    /// section as for [`Self::from_pkcs1_der()`].  Other labels result in
+    /// [`crate::KeyFormatError::MismatchedPemLabel`].
+    pub fn from_pem(pem: &str) -> Result<Self, Error> {
+        let _ = Entry::new_secret();
+        let section = pem::Section::find(pem)?;
+        let mut der = [0u8; MAX_PKCS8_LEN];
+        let result = match section.label {
+            "PRIVATE KEY" => section.decode(&mut der).and_then(Self::from_pkcs8_der),
+            "RSA PRIVATE KEY" => section.decode(&mut der).and_then(Self::from_pkcs1_der),
+            _ => Err(KeyFormatError::MismatchedPemLabel.into()),
+        };
+        zeroise(&mut der);
+        result
+    }
+
+    /// Encodes this key in PEM format, with the label `PRIVATE KEY`.
+    ///
+    /// The key is encoded as for [`Self::to_pkcs8_der()`].  The PEM
+    /// encoding is written to the front of `output`, and that prefix
+    /// is returned.
+    pub fn to_pem<'a>(&self, output: &'a mut [u8]) -> Result<&'a str, Error> {
+        let _ = Entry::new_secret();
+        let mut der = [0u8; MAX_PKCS8_LEN];
+        let result = self
+            .to_pkcs8_der(&mut der)
+            .and_then(|der| pem::encode("PRIVATE KEY", der, output));
+        zeroise(&mut der);
+        result
     }
 
     /// Returns the matching public key.
+    ///
+    /// This has the same [`Restriction`] as this key.
     pub fn public_key(&self) -> VerifyingKey {
         let _ = Entry::new_public();
-        VerifyingKey(self.0.public_key())
+        VerifyingKey(self.0.public_key(), self.1)
+    }
+
+    /// Returns how this key may be used.
+    pub fn restriction(&self) -> Restriction {
+        self.1
     }
 
     /// Returns the public modulus length, in bytes.
@@ -310,13 +601,26 @@ impl SigningKey {
     /// RSASSA-PSS is described in
     /// [RFC8017](https://datatracker.ietf.org/doc/html/rfc8017#section-8.1)
     /// (and earlier standards, including the original PKCS#1 standard).
+    #[cfg(feature = "std")]
     pub fn sign_pss_sha256<'a>(
         &self,
         signature: &'a mut [u8],
         message: &[u8],
+    ) -> Result<&'a [u8], Error> {
+        self.sign_pss_sha256_with_rng(&mut DefaultRandom, signature, message)
+    }
+
+    /// Signs `message`, using RSASSA-PSS with SHA-256, and a salt from `rng`.
+    ///
+    /// See [`Self::sign_pss_sha256()`] for details of the other arguments.
+    pub fn sign_pss_sha256_with_rng<'a>(
+        &self,
+        rng: &mut dyn RandomSource,
+        signature: &'a mut [u8],
+        message: &[u8],
     ) -> Result<&'a [u8], Error> {
         let _ = Entry::new_secret();
-        self._sign_pss::<hash::Sha256>(signature, message)
+        self._sign_pss::<hash::Sha256>(PssParameters::new(PssHash::Sha256), rng, signature, message)
     }
 
     /// Signs `message`, using RSASSA-PSS with SHA-384.
@@ -331,13 +635,26 @@ impl SigningKey {
     /// RSASSA-PSS is described in
     /// [RFC8017](https://datatracker.ietf.org/doc/html/rfc8017#section-8.1)
     /// (and earlier standards, including the original PKCS#1 standard).
+    #[cfg(feature = "std")]
     pub fn sign_pss_sha384<'a>(
         &self,
         signature: &'a mut [u8],
         message: &[u8],
+    ) -> Result<&'a [u8], Error> {
+        self.sign_pss_sha384_with_rng(&mut DefaultRandom, signature, message)
+    }
+
+    /// Signs `message`, using RSASSA-PSS with SHA-384, and a salt from `rng`.
+    ///
+    /// See [`Self::sign_pss_sha384()`] for details of the other arguments.
+    pub fn sign_pss_sha384_with_rng<'a>(
+        &self,
+        rng: &mut dyn RandomSource,
+        signature: &'a mut [u8],
+        message: &[u8],
     ) -> Result<&'a [u8], Error> {
         let _ = Entry::new_secret();
-        self._sign_pss::<hash::Sha384>(signature, message)
+        self._sign_pss::<hash::Sha384>(PssParameters::new(PssHash::Sha384), rng, signature, message)
     }
 
     /// Signs `message`, using RSASSA-PSS with SHA-512.
@@ -352,13 +669,91 @@ impl SigningKey {
     /// RSASSA-PSS is described in
     /// [RFC8017](https://datatracker.ietf.org/doc/html/rfc8017#section-8.1)
     /// (and earlier standards, including the original PKCS#1 standard).
+    #[cfg(feature = "std")]
     pub fn sign_pss_sha512<'a>(
         &self,
         signature: &'a mut [u8],
         message: &[u8],
+    ) -> Result<&'a [u8], Error> {
+        self.sign_pss_sha512_with_rng(&mut DefaultRandom, signature, message)
+    }
+
+    /// Signs `message`, using RSASSA-PSS with SHA-512, and a salt from `rng`.
+    ///
+    /// See [`Self::sign_pss_sha512()`] for details of the other arguments.
+    pub fn sign_pss_sha512_with_rng<'a>(
+        &self,
+        rng: &mut dyn RandomSource,
+        signature: &'a mut [u8],
+        message: &[u8],
+    ) -> Result<&'a [u8], Error> {
+        let _ = Entry::new_secret();
+        self._sign_pss::<hash::Sha512>(PssParameters::new(PssHash::Sha512), rng, signature, message)
+    }
+
+    /// Signs `message`, using RSASSA-PSS with the given `parameters`.
+    ///
+    /// This allows any `saltLength`, unlike [`Self::sign_pss_sha256()`] and
+    /// friends.
+    ///
+    /// The signature is written to the front of `signature`, is
+    /// precisely [`Self::modulus_len_bytes()`] in length, and
+    /// then the written-to slice is returned.
+    ///
+    /// RSASSA-PSS is described in
+    /// [RFC8017](https://datatracker.ietf.org/doc/html/rfc8017#section-8.1)
+    /// (and earlier standards, including the original PKCS#1 standard).
+    #[cfg(feature = "std")]
+    pub fn sign_pss<'a>(
+        &self,
+        parameters: PssParameters,
+        signature: &'a mut [u8],
+        message: &[u8],
+    ) -> Result<&'a [u8], Error> {
+        self.sign_pss_with_rng(&mut DefaultRandom, parameters, signature, message)
+    }
+
+    /// Signs `message`, using RSASSA-PSS with the given `parameters`, and a salt from `rng`.
+    ///
+    /// See [`Self::sign_pss()`] for details of the other arguments.
+    pub fn sign_pss_with_rng<'a>(
+        &self,
+        rng: &mut dyn RandomSource,
+        parameters: PssParameters,
+        signature: &'a mut [u8],
+        message: &[u8],
     ) -> Result<&'a [u8], Error> {
         let _ = Entry::new_secret();
-        self._sign_pss::<hash::Sha512>(signature, message)
+        match parameters.hash {
+            PssHash::Sha256 => self._sign_pss::<hash::Sha256>(parameters, rng, signature, message),
+            PssHash::Sha384 => self._sign_pss::<hash::Sha384>(parameters, rng, signature, message),
+            PssHash::Sha512 => self._sign_pss::<hash::Sha512>(parameters, rng, signature, message),
+        }
+    }
+
+    /// Encodes this key in PKCS#1 `RSAPrivateKey` DER format.
+    pub(crate) fn encode_pkcs1<'a>(&self, output: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let mut parts = [0u8; rsa_priv::MAX_PARTS_LEN];
+        let result = self
+            .0
+            .write_parts(&mut parts)
+            .and_then(|[n, e, d, p, q, dp, dq, iqmp]| {
+                pkix::RSAPrivateKey {
+                    version: pkix::Version::two_prime,
+                    modulus: asn1::Integer::from_bytes(n),
+                    publicExponent: asn1::Integer::from_bytes(e),
+                    privateExponent: asn1::Integer::from_bytes(d),
+                    prime1: asn1::Integer::from_bytes(p),
+                    prime2: asn1::Integer::from_bytes(q),
+                    exponent1: asn1::Integer::from_bytes(dp),
+                    exponent2: asn1::Integer::from_bytes(dq),
+                    coefficient: asn1::Integer::from_bytes(iqmp),
+                }
+                .encode(&mut asn1::Encoder::new(output))
+                .map_err(Error::Asn1Error)
+            });
+        zeroise(&mut parts);
+        Ok(&output[..result?])
     }
 
     fn _sign_pkcs1<'a>(
@@ -367,6 +762,8 @@ impl SigningKey {
         digest_info: &[u8],
         hash: &[u8],
     ) -> Result<&'a [u8], Error> {
+        self.1.check_pkcs1()?;
+
         if signature.len() < self.0.modulus_len_bytes() {
             return Err(Error::OutOfRange);
         }
@@ -380,11 +777,15 @@ impl SigningKey {
         c.to_bytes(signature)
     }
 
-    fn _sign_pss<'a, H: Hash>(
+    fn _sign_pss<'a, H: SigningHash>(
         &self,
+        parameters: PssParameters,
+        rng: &mut dyn RandomSource,
         signature: &'a mut [u8],
         message: &[u8],
     ) -> Result<&'a [u8], Error> {
+        self.1.check_pss(parameters)?;
+
         if signature.len() < self.0.modulus_len_bytes() {
             return Err(Error::OutOfRange);
         }
@@ -394,16 +795,237 @@ impl SigningKey {
         let mut m = [0u8; rsa_pub::MAX_PUBLIC_MODULUS_BYTES];
         let m = &mut m[..self.0.modulus_len_bytes()];
 
-        pkcs1::encode_pss_sig::<H>(m, &mut SystemRandom, hash.as_ref())?;
+        pkcs1::encode_pss_sig::<H>(m, rng, hash.as_ref(), parameters.salt_len)?;
         let m = PosInt::from_bytes(m)?;
         let c = self.0.private_op(&m).map_err(|_| Error::BadSignature)?;
         c.to_bytes(signature)
     }
 }
 
+/// An upper bound on the length of PKCS#1 and PKCS#8 encodings of
+/// supported private keys, plus space for encryption padding.
+pub(crate) const MAX_PKCS8_LEN: usize = rsa_priv::MAX_PARTS_LEN + 256;
+
+/// An upper bound on the length of PKCS#1 and `SubjectPublicKeyInfo`
+/// encodings of supported public keys.
+pub(crate) const MAX_SPKI_LEN: usize = rsa_pub::MAX_PARTS_LEN + 256;
+
+/// How an RSA key may be used.
+///
+/// Keys that are encoded with the `id-RSASSA-PSS` algorithm identifier
+/// may only be used with RSASSA-PSS, and optionally only with specific
+/// parameters.  Using the key otherwise fails with [`Error::KeyRestricted`].
+///
+/// This is described in
+/// [RFC4055](https://datatracker.ietf.org/doc/html/rfc4055#section-3.1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Restriction {
+    /// The key may be used with any supported algorithm.
+    None,
+
+    /// The key may only be used with RSASSA-PSS, with any parameters.
+    Pss,
+
+    /// The key may only be used with RSASSA-PSS, with these parameters.
+    PssWith(PssParameters),
+}
+
+impl Restriction {
+    /// Errors are reported in terms of PKCS#8; callers may translate these.
+    fn from_algorithm_identifier(alg: &pkix::AlgorithmIdentifier<'_>) -> Result<Self, Error> {
+        if alg.algorithm == oid::rsaEncryption {
+            return match alg.parameters {
+                Some(asn1::Any::Null(_)) => Ok(Self::None),
+                _ => Err(KeyFormatError::MismatchedPkcs8Parameters.into()),
+            };
+        }
+
+        if alg.algorithm != oid::id_RSASSA_PSS {
+            return Err(KeyFormatError::MismatchedPkcs8Algorithm.into());
+        }
+
+        let params = match &alg.parameters {
+            None => return Ok(Self::Pss),
+            Some(asn1::Any::Sequence(seq)) => seq
+                .decode::<pkix::RsassaPssParams<'_>>()
+                .map_err(Error::Asn1Error)?,
+            Some(_) => return Err(KeyFormatError::MismatchedPkcs8Parameters.into()),
+        };
+
+        // nb. all the DEFAULT values name SHA-1, which we do not support.
+        let hash = params
+            .hashAlgorithm
+            .into_inner()
+            .ok_or(KeyFormatError::UnsupportedRsaPssParameters)?;
+        let hash = PssHash::from_algorithm_identifier(&hash)?;
+
+        let mgf = params
+            .maskGenAlgorithm
+            .into_inner()
+            .ok_or(KeyFormatError::UnsupportedRsaPssParameters)?;
+        let mgf_hash = match mgf.parameters {
+            Some(asn1::Any::Sequence(seq)) if mgf.algorithm == oid::id_mgf1 => seq
+                .decode::<pkix::AlgorithmIdentifier<'_>>()
+                .map_err(Error::Asn1Error)?,
+            _ => return Err(KeyFormatError::UnsupportedRsaPssParameters.into()),
+        };
+        if PssHash::from_algorithm_identifier(&mgf_hash)? != hash {
+            return Err(KeyFormatError::UnsupportedRsaPssParameters.into());
+        }
+
+        let salt_len = match params.saltLength.into_inner() {
+            None => 20,
+            Some(len) => len.as_usize().map_err(Error::Asn1Error)?,
+        };
+
+        match params.trailerField.into_inner() {
+            None => {}
+            Some(tf) if tf.as_usize() == Ok(1) => {}
+            Some(_) => return Err(KeyFormatError::UnsupportedRsaPssParameters.into()),
+        }
+
+        Ok(Self::PssWith(PssParameters { hash, salt_len }))
+    }
+
+    /// Returns the `AlgorithmIdentifier` that encodes this restriction.
+    ///
+    /// `buf` is used for encoding any `RSASSA-PSS-params`.
+    pub(crate) fn to_algorithm_identifier<'a>(
+        self,
+        buf: &'a mut [u8],
+    ) -> Result<pkix::AlgorithmIdentifier<'a>, Error> {
+        let parameters = match self {
+            Self::None => {
+                return Ok(pkix::AlgorithmIdentifier {
+                    algorithm: oid::rsaEncryption.clone(),
+                    parameters: Some(asn1::Any::Null(asn1::Null)),
+                });
+            }
+            Self::Pss => None,
+            Self::PssWith(params) => {
+                let hash = pkix::AlgorithmIdentifier {
+                    algorithm: params.hash.oid(),
+                    parameters: Some(asn1::Any::Null(asn1::Null)),
+                };
+                let mut mgf_hash = [0u8; 32];
+                let mgf_hash_len = hash
+                    .encode(&mut asn1::Encoder::new(&mut mgf_hash))
+                    .map_err(Error::Asn1Error)?;
+                let salt_len = params.salt_len.to_be_bytes();
+
+                let len = pkix::RsassaPssParams {
+                    hashAlgorithm: asn1::ContextConstructed::new(Some(hash)),
+                    maskGenAlgorithm: asn1::ContextConstructed::new(Some(
+                        pkix::AlgorithmIdentifier {
+                            algorithm: oid::id_mgf1.clone(),
+                            parameters: Some(asn1::Any::Sequence(asn1::Sequence::new(
+                                &mgf_hash[..mgf_hash_len],
+                            ))),
+                        },
+                    )),
+                    // nb. DER requires that DEFAULT values are omitted.
+                    saltLength: asn1::ContextConstructed::new(
+                        (params.salt_len != 20).then(|| asn1::Integer::from_bytes(&salt_len)),
+                    ),
+                    trailerField: asn1::ContextConstructed::new(None),
+                }
+                .encode(&mut asn1::Encoder::new(buf))
+                .map_err(Error::Asn1Error)?;
+
+                Some(asn1::Any::Sequence(asn1::Sequence::new(&buf[..len])))
+            }
+        };
+
+        Ok(pkix::AlgorithmIdentifier {
+            algorithm: oid::id_RSASSA_PSS.clone(),
+            parameters,
+        })
+    }
+
+    fn check_pkcs1(&self) -> Result<(), Error> {
+        match self {
+            Self::None => Ok(()),
+            Self::Pss | Self::PssWith(_) => Err(Error::KeyRestricted),
+        }
+    }
+
+    fn check_pss(&self, parameters: PssParameters) -> Result<(), Error> {
+        match self {
+            Self::PssWith(required) if *required != parameters => Err(Error::KeyRestricted),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parameters for RSASSA-PSS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PssParameters {
+    /// The hash function, used for both the message and MGF1.
+    pub hash: PssHash,
+
+    /// The `saltLength`, in bytes.
+    pub salt_len: usize,
+}
+
+impl PssParameters {
+    /// Returns parameters using `hash`, where `saltLength` is the
+    /// output length of `hash`.
+    ///
+    /// This is the most common choice, and the one used by TLS.
+    pub fn new(hash: PssHash) -> Self {
+        let salt_len = match hash {
+            PssHash::Sha256 => 32,
+            PssHash::Sha384 => 48,
+            PssHash::Sha512 => 64,
+        };
+        Self { hash, salt_len }
+    }
+}
+
+/// Hash functions supported for RSASSA-PSS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PssHash {
+    /// SHA-256.
+    Sha256,
+
+    /// SHA-384.
+    Sha384,
+
+    /// SHA-512.
+    Sha512,
+}
+
+impl PssHash {
+    fn oid(&self) -> asn1::ObjectId {
+        match self {
+            Self::Sha256 => oid::id_sha256.clone(),
+            Self::Sha384 => oid::id_sha384.clone(),
+            Self::Sha512 => oid::id_sha512.clone(),
+        }
+    }
+
+    fn from_algorithm_identifier(alg: &pkix::AlgorithmIdentifier<'_>) -> Result<Self, Error> {
+        // RFC4055 allows either absent or NULL parameters.
+        if !matches!(alg.parameters, None | Some(asn1::Any::Null(_))) {
+            return Err(KeyFormatError::UnsupportedRsaPssParameters.into());
+        }
+
+        if alg.algorithm == oid::id_sha256 {
+            Ok(Self::Sha256)
+        } else if alg.algorithm == oid::id_sha384 {
+            Ok(Self::Sha384)
+        } else if alg.algorithm == oid::id_sha512 {
+            Ok(Self::Sha512)
+        } else {
+            Err(KeyFormatError::UnsupportedRsaPssParameters.into())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mid::rng::SliceRandomSource;
 
     fn check_all_algs(buf: &mut [u8], private: &SigningKey, public: &VerifyingKey) {
         let sig = private.sign_pkcs1_sha256(buf, b"hello").unwrap();
@@ -415,14 +1037,458 @@ mod tests {
         let sig = private.sign_pkcs1_sha512(buf, b"hello").unwrap();
         public.verify_pkcs1_sha512(sig, b"hello").unwrap();
 
-        let sig = private.sign_pss_sha256(buf, b"hello").unwrap();
-        public.verify_pss_sha256(sig, b"hello").unwrap();
+        #[cfg(feature = "std")]
+        {
+            let sig = private.sign_pss_sha256(buf, b"hello").unwrap();
+            public.verify_pss_sha256(sig, b"hello").unwrap();
+
+            let sig = private.sign_pss_sha384(buf, b"hello").unwrap();
+            public.verify_pss_sha384(sig, b"hello").unwrap();
+
+            let sig = private.sign_pss_sha512(buf, b"hello").unwrap();
+            public.verify_pss_sha512(sig, b"hello").unwrap();
+        }
+    }
+
+    #[cfg(feature = "sha1")]
+    #[test]
+    fn verify_pkcs1_sha1() {
+        let public_key = SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa2048.der"))
+            .unwrap()
+            .public_key();
+
+        public_key
+            .verify_pkcs1_sha1(include_bytes!("rsa/rsa2048-pkcs1-sha1-hello.sig"), b"hello")
+            .unwrap();
+        public_key
+            .verify_pkcs1_sha1(include_bytes!("rsa/rsa2048-pkcs1-sha1-hello.sig"), b"jello")
+            .unwrap_err();
+
+        // a correct signature over half of a known collision
+        assert_eq!(
+            public_key
+                .verify_pkcs1_sha1(
+                    include_bytes!("rsa/rsa2048-pkcs1-sha1-shattered.sig"),
+                    include_bytes!("../testdata/sha1-shattered-2.bin")
+                )
+                .unwrap_err(),
+            Error::BadSignature
+        );
+    }
+
+    #[test]
+    fn sign_pss_with_rng() {
+        let private_key = SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa2048.der")).unwrap();
+        let public_key = private_key.public_key();
+        let salt = [0x5a; 64];
+
+        let mut sig = [0u8; 256];
+        let mut other = [0u8; 256];
+
+        // the salt is the only randomness, so fixing it means signatures are deterministic
+        private_key
+            .sign_pss_sha256_with_rng(&mut SliceRandomSource(&salt[..32]), &mut sig, b"hello")
+            .unwrap();
+        private_key
+            .sign_pss_sha256_with_rng(&mut SliceRandomSource(&salt[..32]), &mut other, b"hello")
+            .unwrap();
+        assert_eq!(sig, other);
+        public_key.verify_pss_sha256(&sig, b"hello").unwrap();
+
+        private_key
+            .sign_pss_sha384_with_rng(&mut SliceRandomSource(&salt[..48]), &mut sig, b"hello")
+            .unwrap();
+        public_key.verify_pss_sha384(&sig, b"hello").unwrap();
 
-        let sig = private.sign_pss_sha384(buf, b"hello").unwrap();
-        public.verify_pss_sha384(sig, b"hello").unwrap();
+        private_key
+            .sign_pss_sha512_with_rng(&mut SliceRandomSource(&salt), &mut sig, b"hello")
+            .unwrap();
+        public_key.verify_pss_sha512(&sig, b"hello").unwrap();
+
+        assert_eq!(
+            private_key
+                .sign_pss_sha256_with_rng(&mut SliceRandomSource(&[]), &mut sig, b"hello")
+                .unwrap_err(),
+            Error::RngFailed
+        );
+    }
+
+    #[test]
+    fn mismatched_key_parts() {
+        fn altered<'a>(
+            alter: impl FnOnce(&mut pkix::RSAPrivateKey<'a>),
+        ) -> Result<SigningKey, Error> {
+            let mut key =
+                pkix::RSAPrivateKey::from_bytes(include_bytes!("rsa/rsa2048.der")).unwrap();
+            alter(&mut key);
+            let mut buf = [0u8; 2048];
+            let len = key.encode(&mut asn1::Encoder::new(&mut buf)).unwrap();
+            SigningKey::from_pkcs1_der(&buf[..len])
+        }
+
+        // flips the second-lowest bit, so odd values stay odd
+        fn flip(int: &asn1::Integer<'_>) -> Vec<u8> {
+            let mut bytes = int.as_ref().to_vec();
+            *bytes.last_mut().unwrap() ^= 2;
+            bytes
+        }
+
+        let key = pkix::RSAPrivateKey::from_bytes(include_bytes!("rsa/rsa2048.der")).unwrap();
+        let n = flip(&key.modulus);
+        let d = flip(&key.privateExponent);
+        let dp = flip(&key.exponent1);
+        let dq = flip(&key.exponent2);
+
+        assert!(altered(|_| {}).is_ok());
+        assert_eq!(
+            altered(|k| k.modulus = asn1::Integer::new(&n)).err(),
+            Some(KeyFormatError::MismatchedRsaModulus.into())
+        );
+        assert_eq!(
+            altered(|k| k.privateExponent = asn1::Integer::new(&d)).err(),
+            Some(KeyFormatError::MismatchedRsaPrivateExponent.into())
+        );
+        assert_eq!(
+            altered(|k| k.exponent1 = asn1::Integer::new(&dp)).err(),
+            Some(KeyFormatError::MismatchedRsaCrtExponent.into())
+        );
+        assert_eq!(
+            altered(|k| k.exponent2 = asn1::Integer::new(&dq)).err(),
+            Some(KeyFormatError::MismatchedRsaCrtExponent.into())
+        );
+    }
+
+    #[test]
+    fn sign_pss_arbitrary_salt_len() {
+        let private_key = SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa2048.der")).unwrap();
+        let public_key = private_key.public_key();
+        let salt = [0x5a; 128];
+        let mut sig = [0u8; 256];
+
+        for salt_len in [0, 20, 128] {
+            let params = PssParameters {
+                hash: PssHash::Sha384,
+                salt_len,
+            };
+            private_key
+                .sign_pss_with_rng(&mut SliceRandomSource(&salt), params, &mut sig, b"hello")
+                .unwrap();
+            public_key.verify_pss(params, &sig, b"hello").unwrap();
+
+            let other = PssParameters {
+                salt_len: salt_len + 1,
+                ..params
+            };
+            assert_eq!(
+                public_key.verify_pss(other, &sig, b"hello").unwrap_err(),
+                Error::BadSignature
+            );
+        }
+
+        // emLen < hLen + sLen + 2
+        let params = PssParameters {
+            hash: PssHash::Sha512,
+            salt_len: 256 - 64 - 1,
+        };
+        assert_eq!(
+            private_key
+                .sign_pss_with_rng(&mut SliceRandomSource(&[]), params, &mut sig, b"hello")
+                .unwrap_err(),
+            Error::OutOfRange
+        );
+        assert_eq!(
+            public_key.verify_pss(params, &sig, b"hello").unwrap_err(),
+            Error::BadSignature
+        );
+    }
+
+    #[test]
+    fn pss_restricted_key() {
+        let private_key =
+            SigningKey::from_pkcs8_der(include_bytes!("rsa/rsa2048-pss-sha256.pkcs8.der")).unwrap();
+        let params = PssParameters::new(PssHash::Sha256);
+        assert_eq!(private_key.restriction(), Restriction::PssWith(params));
+
+        let public_key =
+            VerifyingKey::from_spki_der(include_bytes!("rsa/rsa2048-pss-sha256.spki.der")).unwrap();
+        assert_eq!(public_key.restriction(), Restriction::PssWith(params));
+        assert_eq!(
+            private_key.public_key().restriction(),
+            Restriction::PssWith(params)
+        );
+
+        let mut sig = [0u8; 256];
+        let salt = [0x5a; 64];
+        private_key
+            .sign_pss_sha256_with_rng(&mut SliceRandomSource(&salt), &mut sig, b"hello")
+            .unwrap();
+        public_key.verify_pss_sha256(&sig, b"hello").unwrap();
+        public_key.verify_pss(params, &sig, b"hello").unwrap();
+
+        assert_eq!(
+            private_key
+                .sign_pkcs1_sha256(&mut sig, b"hello")
+                .unwrap_err(),
+            Error::KeyRestricted
+        );
+        assert_eq!(
+            private_key
+                .sign_pss_sha512_with_rng(&mut SliceRandomSource(&salt), &mut sig, b"hello")
+                .unwrap_err(),
+            Error::KeyRestricted
+        );
+        assert_eq!(
+            public_key.verify_pkcs1_sha256(&sig, b"hello").unwrap_err(),
+            Error::KeyRestricted
+        );
+        assert_eq!(
+            public_key
+                .verify_pss(
+                    PssParameters {
+                        salt_len: 20,
+                        ..params
+                    },
+                    &sig,
+                    b"hello"
+                )
+                .unwrap_err(),
+            Error::KeyRestricted
+        );
+    }
+
+    #[test]
+    fn pss_restricted_key_with_salt_len() {
+        // the salt length is the DEFAULT, 20 bytes
+        let private_key =
+            SigningKey::from_pkcs8_der(include_bytes!("rsa/rsa2048-pss-sha512-salt20.pkcs8.der"))
+                .unwrap();
+        let params = PssParameters {
+            hash: PssHash::Sha512,
+            salt_len: 20,
+        };
+        assert_eq!(private_key.restriction(), Restriction::PssWith(params));
+
+        let public_key =
+            VerifyingKey::from_spki_der(include_bytes!("rsa/rsa2048-pss-sha512-salt20.spki.der"))
+                .unwrap();
+
+        // made by openssl
+        let sig = include_bytes!("rsa/rsa2048-pss-sha512-salt20-hello.sig");
+        public_key.verify_pss(params, sig, b"hello").unwrap();
+        assert_eq!(
+            public_key.verify_pss(params, sig, b"jello").unwrap_err(),
+            Error::BadSignature
+        );
+        assert_eq!(
+            public_key.verify_pss_sha512(sig, b"hello").unwrap_err(),
+            Error::KeyRestricted
+        );
+
+        let mut sig = [0u8; 256];
+        private_key
+            .sign_pss_with_rng(
+                &mut SliceRandomSource(&[0x5a; 20]),
+                params,
+                &mut sig,
+                b"hello",
+            )
+            .unwrap();
+        public_key.verify_pss(params, &sig, b"hello").unwrap();
+    }
+
+    #[test]
+    fn pss_unrestricted_key() {
+        let private_key =
+            SigningKey::from_pkcs8_der(include_bytes!("rsa/rsa2048-pss.pkcs8.der")).unwrap();
+        assert_eq!(private_key.restriction(), Restriction::Pss);
+        let public_key =
+            VerifyingKey::from_spki_der(include_bytes!("rsa/rsa2048-pss.spki.der")).unwrap();
+        assert_eq!(public_key.restriction(), Restriction::Pss);
+
+        let mut sig = [0u8; 256];
+        let salt = [0x5a; 64];
+        private_key
+            .sign_pss_sha384_with_rng(&mut SliceRandomSource(&salt), &mut sig, b"hello")
+            .unwrap();
+        public_key.verify_pss_sha384(&sig, b"hello").unwrap();
+
+        assert_eq!(
+            private_key
+                .sign_pkcs1_sha384(&mut sig, b"hello")
+                .unwrap_err(),
+            Error::KeyRestricted
+        );
+    }
+
+    #[test]
+    fn unsupported_pss_parameters() {
+        // MGF1 uses SHA-384, but the message hash is SHA-256
+        assert_eq!(
+            SigningKey::from_pkcs8_der(include_bytes!("rsa/rsa2048-pss-mismatched-mgf.pkcs8.der"))
+                .err(),
+            Some(KeyFormatError::UnsupportedRsaPssParameters.into())
+        );
+    }
+
+    #[test]
+    fn spki_algorithms() {
+        let public_key =
+            VerifyingKey::from_spki_der(include_bytes!("asn1/testdata/spki-rsa-2k.bin")).unwrap();
+        assert_eq!(public_key.restriction(), Restriction::None);
+
+        assert_eq!(
+            VerifyingKey::from_spki_der(include_bytes!("asn1/testdata/spki-ec-nistp256.bin"))
+                .unwrap_err(),
+            KeyFormatError::MismatchedSpkiAlgorithm.into()
+        );
+    }
+
+    #[test]
+    fn pkcs8_encoding() {
+        let mut buf = [0u8; 2048];
+        for der in [
+            &include_bytes!("rsa/rsa2048.pkcs8.der")[..],
+            &include_bytes!("rsa/rsa2048-pss.pkcs8.der")[..],
+            &include_bytes!("rsa/rsa2048-pss-sha256.pkcs8.der")[..],
+            &include_bytes!("rsa/rsa2048-pss-sha512-salt20.pkcs8.der")[..],
+        ] {
+            let key = SigningKey::from_pkcs8_der(der).unwrap();
+            let encoded = key.to_pkcs8_der(&mut buf).unwrap();
+            assert_eq!(
+                SigningKey::from_pkcs8_der(encoded).unwrap().restriction(),
+                key.restriction()
+            );
+            assert_eq!(encoded, der);
+        }
+
+        let key = SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa8192.der")).unwrap();
+        let mut buf = [0u8; 8192];
+        let encoded = key.to_pkcs8_der(&mut buf).unwrap();
+        let (_, pkcs1) = pkcs8::decode_pkcs8_any_algorithm(encoded).unwrap();
+        assert_eq!(pkcs1, include_bytes!("rsa/rsa8192.der"));
+    }
+
+    #[cfg(feature = "pbes2")]
+    #[test]
+    fn encrypted_pkcs8() {
+        // aes-256-cbc, by openssl
+        let key = SigningKey::from_encrypted_pkcs8_der(
+            include_bytes!("rsa/rsa2048.encrypted-pkcs8.der"),
+            b"hunter2",
+        )
+        .unwrap();
+        let mut buf = [0u8; 2048];
+        assert_eq!(
+            key.to_pkcs8_der(&mut buf).unwrap(),
+            include_bytes!("rsa/rsa2048.pkcs8.der")
+        );
+
+        assert_eq!(
+            SigningKey::from_encrypted_pkcs8_der(
+                include_bytes!("rsa/rsa2048.encrypted-pkcs8.der"),
+                b"hunter3",
+            )
+            .err(),
+            Some(Error::DecryptFailed)
+        );
+    }
+
+    #[cfg(feature = "pbes2")]
+    #[test]
+    fn encrypted_pkcs8_roundtrip() {
+        let key =
+            SigningKey::from_pkcs8_der(include_bytes!("rsa/rsa2048-pss-sha256.pkcs8.der")).unwrap();
+
+        let mut encrypted = [0u8; 2048];
+        let encrypted = key
+            .to_encrypted_pkcs8_der_with_rng(
+                &mut SliceRandomSource(&[0x11; 32]),
+                b"password",
+                &mut encrypted,
+            )
+            .unwrap();
+
+        let decrypted = SigningKey::from_encrypted_pkcs8_der(encrypted, b"password").unwrap();
+        assert_eq!(decrypted.restriction(), key.restriction());
+
+        let mut sig = [0u8; 256];
+        let params = PssParameters::new(PssHash::Sha256);
+        decrypted
+            .sign_pss_with_rng(
+                &mut SliceRandomSource(&[0x5a; 32]),
+                params,
+                &mut sig,
+                b"hello",
+            )
+            .unwrap();
+        key.public_key().verify_pss(params, &sig, b"hello").unwrap();
+    }
+
+    #[test]
+    fn pem_encoding() {
+        let mut buf = [0u8; 4096];
+        let mut expect = [0u8; 2048];
+
+        let pem = include_str!("rsa/rsa2048.pkcs8.pem");
+        let key = SigningKey::from_pem(pem).unwrap();
+        assert_eq!(key.to_pem(&mut buf).unwrap(), pem);
+
+        let key = SigningKey::from_pem(include_str!("rsa/rsa2048.pem")).unwrap();
+        assert_eq!(
+            key.to_pkcs8_der(&mut buf).unwrap(),
+            SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa2048.der"))
+                .unwrap()
+                .to_pkcs8_der(&mut expect)
+                .unwrap()
+        );
+
+        let public_key = VerifyingKey::from_pem(include_str!("rsa/rsa2048-public.pem")).unwrap();
+        assert_eq!(
+            public_key.to_spki_der(&mut buf).unwrap(),
+            key.public_key().to_spki_der(&mut expect).unwrap()
+        );
+
+        let pem = include_str!("rsa/rsa2048-pss-sha256.spki.pem");
+        let public_key = VerifyingKey::from_pem(pem).unwrap();
+        assert_eq!(
+            public_key.restriction(),
+            Restriction::PssWith(PssParameters::new(PssHash::Sha256))
+        );
+        assert_eq!(public_key.to_pem(&mut buf).unwrap(), pem);
+
+        // wrong labels
+        assert_eq!(
+            VerifyingKey::from_pem(include_str!("rsa/rsa2048.pem")).unwrap_err(),
+            KeyFormatError::MismatchedPemLabel.into()
+        );
+        assert_eq!(
+            SigningKey::from_pem(pem).err(),
+            Some(KeyFormatError::MismatchedPemLabel.into())
+        );
+    }
+
+    #[test]
+    fn spki_encoding() {
+        let mut buf = [0u8; 2048];
+        for der in [
+            &include_bytes!("rsa/rsa2048-pss.spki.der")[..],
+            &include_bytes!("rsa/rsa2048-pss-sha256.spki.der")[..],
+            &include_bytes!("rsa/rsa2048-pss-sha512-salt20.spki.der")[..],
+            &include_bytes!("asn1/testdata/spki-rsa-2k.bin")[..],
+        ] {
+            let key = VerifyingKey::from_spki_der(der).unwrap();
+            assert_eq!(key.to_spki_der(&mut buf).unwrap(), der);
+        }
 
-        let sig = private.sign_pss_sha512(buf, b"hello").unwrap();
-        public.verify_pss_sha512(sig, b"hello").unwrap();
+        let key = SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa8192.der")).unwrap();
+        let encoded = key.public_key().to_spki_der(&mut buf).unwrap();
+        assert_eq!(
+            VerifyingKey::from_spki_der(encoded)
+                .unwrap()
+                .to_spki_der(&mut [0u8; 2048])
+                .unwrap(),
+            encoded
+        );
     }
 
     #[test]
@@ -0,0 +1,735 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! Implementations of the RustCrypto traits for this crate's types.
+
+#[cfg(any(feature = "aes-gcm", feature = "chacha20poly1305"))]
+use aead::{AeadCore, AeadInPlace, Nonce, Tag};
+#[cfg(feature = "sha256")]
+use digest::consts::U28;
+#[cfg(any(feature = "chacha20poly1305", feature = "sha256", feature = "sha512"))]
+use digest::consts::U32;
+#[cfg(any(feature = "sha256", feature = "sha512"))]
+use digest::consts::U64;
+#[cfg(any(feature = "aes-gcm", feature = "chacha20poly1305"))]
+use digest::consts::{U0, U12, U16};
+#[cfg(feature = "sha512")]
+use digest::consts::{U128, U48};
+use digest::crypto_common::{BlockSizeUser, KeySizeUser};
+use digest::{FixedOutput, InvalidLength, Key, KeyInit, MacMarker, Output, OutputSizeUser, Update};
+#[cfg(any(feature = "sha256", feature = "sha512"))]
+use digest::{FixedOutputReset, HashMarker, Reset};
+use rand_core::CryptoRngCore;
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
+use signature::SignatureEncoding;
+#[cfg(any(
+    all(feature = "p256", feature = "sha256", feature = "std"),
+    all(feature = "p384", feature = "sha512", feature = "std"),
+    feature = "rsa"
+))]
+use signature::Signer;
+#[cfg(any(
+    all(feature = "p256", feature = "sha256"),
+    all(feature = "p384", feature = "sha512"),
+    feature = "rsa"
+))]
+use signature::{RandomizedSigner, Verifier};
+
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
+use core::fmt;
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
+use core::marker::PhantomData;
+
+#[cfg(all(feature = "p256", feature = "sha256"))]
+use super::curve::P256;
+#[cfg(all(feature = "p384", feature = "sha512"))]
+use super::curve::P384;
+#[cfg(any(feature = "p256", feature = "p384"))]
+use super::curve::{Curve, Scalar, MAX_SCALAR_LEN};
+#[cfg(any(
+    all(feature = "p256", feature = "sha256"),
+    all(feature = "p384", feature = "sha512")
+))]
+use super::ecdsa;
+use super::hash::Hash;
+#[cfg(any(all(feature = "p256", feature = "sha256"), feature = "rsa"))]
+use super::hash::Sha256;
+#[cfg(any(all(feature = "p384", feature = "sha512"), feature = "rsa"))]
+use super::hash::Sha384;
+#[cfg(feature = "rsa")]
+use super::hash::Sha512;
+use super::hmac::Hmac;
+#[cfg(feature = "rsa")]
+use super::rsa;
+#[cfg(feature = "aes-gcm")]
+use crate::mid::aes_gcm::AesGcm;
+#[cfg(feature = "chacha20poly1305")]
+use crate::mid::chacha20poly1305::ChaCha20Poly1305;
+use crate::mid::rng::RandomSource;
+#[cfg(feature = "rsa")]
+use crate::mid::rsa_pub::MAX_PUBLIC_MODULUS_BYTES;
+#[cfg(feature = "sha256")]
+use crate::mid::sha2::{Sha224Context, Sha256Context};
+#[cfg(feature = "sha512")]
+use crate::mid::sha2::{Sha384Context, Sha512Context, Sha512_256Context};
+use crate::Error;
+
+/// Any `rand_core` cryptographic RNG may be used as a [`RandomSource`].
+impl<R: CryptoRngCore + ?Sized> RandomSource for R {
+    fn fill(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        self.try_fill_bytes(out).map_err(|_| Error::RngFailed)
+    }
+}
+
+#[cfg(any(feature = "sha256", feature = "sha512"))]
+macro_rules! digest_impls {
+    ($context:ty, $output_size:ty, $block_size:ty) => {
+        impl HashMarker for $context {}
+
+        impl OutputSizeUser for $context {
+            type OutputSize = $output_size;
+        }
+
+        impl BlockSizeUser for $context {
+            type BlockSize = $block_size;
+        }
+
+        impl Default for $context {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl Update for $context {
+            fn update(&mut self, data: &[u8]) {
+                <$context>::update(self, data)
+            }
+        }
+
+        impl FixedOutput for $context {
+            fn finalize_into(self, out: &mut Output<Self>) {
+                out.copy_from_slice(&self.finish());
+            }
+        }
+
+        impl Reset for $context {
+            fn reset(&mut self) {
+                *self = Self::new();
+            }
+        }
+
+        impl FixedOutputReset for $context {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                out.copy_from_slice(&core::mem::take(self).finish());
+            }
+        }
+    };
+}
+
+#[cfg(feature = "sha256")]
+digest_impls!(Sha224Context, U28, U64);
+#[cfg(feature = "sha256")]
+digest_impls!(Sha256Context, U32, U64);
+#[cfg(feature = "sha512")]
+digest_impls!(Sha384Context, U48, U128);
+#[cfg(feature = "sha512")]
+digest_impls!(Sha512Context, U64, U128);
+#[cfg(feature = "sha512")]
+digest_impls!(Sha512_256Context, U32, U128);
+
+impl<H: Hash> MacMarker for Hmac<H> {}
+
+impl<H: Hash> OutputSizeUser for Hmac<H>
+where
+    H::Context: OutputSizeUser,
+{
+    type OutputSize = <H::Context as OutputSizeUser>::OutputSize;
+}
+
+impl<H: Hash> KeySizeUser for Hmac<H>
+where
+    H::Context: BlockSizeUser,
+{
+    type KeySize = <H::Context as BlockSizeUser>::BlockSize;
+}
+
+/// HMAC accepts keys of any length.
+impl<H: Hash> KeyInit for Hmac<H>
+where
+    H::Context: BlockSizeUser,
+{
+    fn new(key: &Key<Self>) -> Self {
+        Self::new(key)
+    }
+
+    fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        Ok(Self::new(key))
+    }
+}
+
+impl<H: Hash> Update for Hmac<H> {
+    fn update(&mut self, data: &[u8]) {
+        Self::update(self, data)
+    }
+}
+
+impl<H: Hash> FixedOutput for Hmac<H>
+where
+    H::Context: OutputSizeUser,
+{
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(self.finish().as_ref());
+    }
+}
+
+#[cfg(feature = "aes-gcm")]
+impl AeadCore for AesGcm {
+    type NonceSize = U12;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+/// `AesGcm` does not implement `KeyInit`, as its key length
+/// (AES-128 or AES-256) is not fixed.
+#[cfg(feature = "aes-gcm")]
+impl AeadInPlace for AesGcm {
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> aead::Result<Tag<Self>> {
+        let mut tag = [0u8; 16];
+        self.encrypt(&(*nonce).into(), associated_data, buffer, &mut tag);
+        Ok(tag.into())
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Self>,
+    ) -> aead::Result<()> {
+        self.decrypt(&(*nonce).into(), associated_data, buffer, tag)
+            .map_err(|_| aead::Error)
+    }
+}
+
+#[cfg(feature = "chacha20poly1305")]
+impl AeadCore for ChaCha20Poly1305 {
+    type NonceSize = U12;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+#[cfg(feature = "chacha20poly1305")]
+impl KeySizeUser for ChaCha20Poly1305 {
+    type KeySize = U32;
+}
+
+#[cfg(feature = "chacha20poly1305")]
+impl KeyInit for ChaCha20Poly1305 {
+    fn new(key: &Key<Self>) -> Self {
+        Self::new((*key).into())
+    }
+}
+
+#[cfg(feature = "chacha20poly1305")]
+impl AeadInPlace for ChaCha20Poly1305 {
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> aead::Result<Tag<Self>> {
+        let mut tag = [0u8; 16];
+        self.encrypt(&(*nonce).into(), associated_data, buffer, &mut tag);
+        Ok(tag.into())
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Self>,
+    ) -> aead::Result<()> {
+        self.decrypt(&(*nonce).into(), associated_data, buffer, tag)
+            .map_err(|_| aead::Error)
+    }
+}
+
+/// A fixed-length ECDSA signature on curve `C`, for use with the
+/// `signature` crate's traits.
+///
+/// The message is hashed with SHA-256 for P-256, and SHA-384 for P-384.
+/// Use [`ecdsa::SigningKey::sign()`] directly for other combinations.
+#[cfg(any(feature = "p256", feature = "p384"))]
+pub struct EcdsaSignature<C: Curve> {
+    bytes: [u8; MAX_SCALAR_LEN * 2],
+    _curve: PhantomData<fn() -> C>,
+}
+
+#[cfg(any(feature = "p256", feature = "p384"))]
+impl<C: Curve> EcdsaSignature<C> {
+    const LEN: usize = C::Scalar::LEN_BYTES * 2;
+
+    fn new(sign: impl FnOnce(&mut [u8]) -> Result<usize, Error>) -> Result<Self, signature::Error> {
+        let mut bytes = [0u8; MAX_SCALAR_LEN * 2];
+        match sign(&mut bytes[..Self::LEN]) {
+            Ok(len) if len == Self::LEN => Ok(Self {
+                bytes,
+                _curve: PhantomData,
+            }),
+            _ => Err(signature::Error::new()),
+        }
+    }
+}
+
+#[cfg(any(feature = "p256", feature = "p384"))]
+impl<C: Curve> AsRef<[u8]> for EcdsaSignature<C> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..Self::LEN]
+    }
+}
+
+#[cfg(any(feature = "p256", feature = "p384"))]
+impl<C: Curve> Clone for EcdsaSignature<C> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes,
+            _curve: PhantomData,
+        }
+    }
+}
+
+#[cfg(any(feature = "p256", feature = "p384"))]
+impl<C: Curve> fmt::Debug for EcdsaSignature<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EcdsaSignature")
+            .field(&self.as_ref())
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "p256", feature = "p384"))]
+impl<C: Curve> TryFrom<&[u8]> for EcdsaSignature<C> {
+    type Error = signature::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != Self::LEN {
+            return Err(signature::Error::new());
+        }
+
+        Self::new(|out| {
+            out.copy_from_slice(bytes);
+            Ok(out.len())
+        })
+    }
+}
+
+#[cfg(any(feature = "p256", feature = "p384"))]
+impl<C: Curve + 'static> SignatureEncoding for EcdsaSignature<C> {
+    type Repr = Self;
+}
+
+#[cfg(any(
+    all(feature = "p256", feature = "sha256"),
+    all(feature = "p384", feature = "sha512")
+))]
+macro_rules! ecdsa_impls {
+    ($curve:ty, $hash:ty) => {
+        #[cfg(feature = "std")]
+        impl Signer<EcdsaSignature<$curve>> for ecdsa::SigningKey<$curve> {
+            fn try_sign(&self, msg: &[u8]) -> Result<EcdsaSignature<$curve>, signature::Error> {
+                EcdsaSignature::new(|out| Ok(self.sign::<$hash>(&[msg], out)?.len()))
+            }
+        }
+
+        impl RandomizedSigner<EcdsaSignature<$curve>> for ecdsa::SigningKey<$curve> {
+            fn try_sign_with_rng(
+                &self,
+                rng: &mut impl CryptoRngCore,
+                msg: &[u8],
+            ) -> Result<EcdsaSignature<$curve>, signature::Error> {
+                EcdsaSignature::new(|out| Ok(self.sign_with_rng::<$hash>(rng, &[msg], out)?.len()))
+            }
+        }
+
+        impl Verifier<EcdsaSignature<$curve>> for ecdsa::VerifyingKey<$curve> {
+            fn verify(
+                &self,
+                msg: &[u8],
+                signature: &EcdsaSignature<$curve>,
+            ) -> Result<(), signature::Error> {
+                self.verify::<$hash>(&[msg], signature.as_ref())
+                    .map_err(|_| signature::Error::new())
+            }
+        }
+    };
+}
+
+#[cfg(all(feature = "p256", feature = "sha256"))]
+ecdsa_impls!(P256, Sha256);
+#[cfg(all(feature = "p384", feature = "sha512"))]
+ecdsa_impls!(P384, Sha384);
+
+#[cfg(feature = "rsa")]
+macro_rules! rsa_signature {
+    ($name:ident, $doc:literal) => {
+        #[doc = $doc]
+        ///
+        /// `H` is the hash function: one of [`Sha256`](crate::hashing::Sha256),
+        /// [`Sha384`](crate::hashing::Sha384) or [`Sha512`](crate::hashing::Sha512).
+        pub struct $name<H> {
+            bytes: [u8; MAX_PUBLIC_MODULUS_BYTES],
+            len: usize,
+            _hash: PhantomData<fn() -> H>,
+        }
+
+        impl<H> $name<H> {
+            fn new(
+                sign: impl FnOnce(&mut [u8]) -> Result<usize, Error>,
+            ) -> Result<Self, signature::Error> {
+                let mut bytes = [0u8; MAX_PUBLIC_MODULUS_BYTES];
+                let len = sign(&mut bytes).map_err(|_| signature::Error::new())?;
+                Ok(Self {
+                    bytes,
+                    len,
+                    _hash: PhantomData,
+                })
+            }
+        }
+
+        impl<H> AsRef<[u8]> for $name<H> {
+            fn as_ref(&self) -> &[u8] {
+                &self.bytes[..self.len]
+            }
+        }
+
+        impl<H> Clone for $name<H> {
+            fn clone(&self) -> Self {
+                Self {
+                    bytes: self.bytes,
+                    len: self.len,
+                    _hash: PhantomData,
+                }
+            }
+        }
+
+        impl<H> fmt::Debug for $name<H> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    .field(&self.as_ref())
+                    .finish()
+            }
+        }
+
+        impl<H> TryFrom<&[u8]> for $name<H> {
+            type Error = signature::Error;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                Self::new(|out| {
+                    out.get_mut(..bytes.len())
+                        .ok_or(Error::WrongLength)?
+                        .copy_from_slice(bytes);
+                    Ok(bytes.len())
+                })
+            }
+        }
+
+        impl<H: 'static> SignatureEncoding for $name<H> {
+            type Repr = Self;
+        }
+    };
+}
+
+#[cfg(feature = "rsa")]
+rsa_signature!(
+    RsaPkcs1Signature,
+    "An RSASSA-PKCS1-v1_5 signature using hash function `H`, for use with the `signature` crate's traits."
+);
+#[cfg(feature = "rsa")]
+rsa_signature!(
+    RsaPssSignature,
+    "An RSASSA-PSS signature using hash function `H`, for use with the `signature` crate's traits."
+);
+
+#[cfg(feature = "rsa")]
+macro_rules! rsa_impls {
+    (
+        $hash:ty,
+        $sign_pkcs1:ident,
+        $verify_pkcs1:ident,
+        $sign_pss:ident,
+        $sign_pss_with_rng:ident,
+        $verify_pss:ident
+    ) => {
+        impl Signer<RsaPkcs1Signature<$hash>> for rsa::SigningKey {
+            fn try_sign(&self, msg: &[u8]) -> Result<RsaPkcs1Signature<$hash>, signature::Error> {
+                RsaPkcs1Signature::new(|out| Ok(self.$sign_pkcs1(out, msg)?.len()))
+            }
+        }
+
+        impl Verifier<RsaPkcs1Signature<$hash>> for rsa::VerifyingKey {
+            fn verify(
+                &self,
+                msg: &[u8],
+                signature: &RsaPkcs1Signature<$hash>,
+            ) -> Result<(), signature::Error> {
+                self.$verify_pkcs1(signature.as_ref(), msg)
+                    .map_err(|_| signature::Error::new())
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl Signer<RsaPssSignature<$hash>> for rsa::SigningKey {
+            fn try_sign(&self, msg: &[u8]) -> Result<RsaPssSignature<$hash>, signature::Error> {
+                RsaPssSignature::new(|out| Ok(self.$sign_pss(out, msg)?.len()))
+            }
+        }
+
+        impl RandomizedSigner<RsaPssSignature<$hash>> for rsa::SigningKey {
+            fn try_sign_with_rng(
+                &self,
+                rng: &mut impl CryptoRngCore,
+                msg: &[u8],
+            ) -> Result<RsaPssSignature<$hash>, signature::Error> {
+                RsaPssSignature::new(|out| Ok(self.$sign_pss_with_rng(rng, out, msg)?.len()))
+            }
+        }
+
+        impl Verifier<RsaPssSignature<$hash>> for rsa::VerifyingKey {
+            fn verify(
+                &self,
+                msg: &[u8],
+                signature: &RsaPssSignature<$hash>,
+            ) -> Result<(), signature::Error> {
+                self.$verify_pss(signature.as_ref(), msg)
+                    .map_err(|_| signature::Error::new())
+            }
+        }
+    };
+}
+
+#[cfg(feature = "rsa")]
+rsa_impls!(
+    Sha256,
+    sign_pkcs1_sha256,
+    verify_pkcs1_sha256,
+    sign_pss_sha256,
+    sign_pss_sha256_with_rng,
+    verify_pss_sha256
+);
+#[cfg(feature = "rsa")]
+rsa_impls!(
+    Sha384,
+    sign_pkcs1_sha384,
+    verify_pkcs1_sha384,
+    sign_pss_sha384,
+    sign_pss_sha384_with_rng,
+    verify_pss_sha384
+);
+#[cfg(feature = "rsa")]
+rsa_impls!(
+    Sha512,
+    sign_pkcs1_sha512,
+    verify_pkcs1_sha512,
+    sign_pss_sha512,
+    sign_pss_sha512_with_rng,
+    verify_pss_sha512
+);
+
+#[cfg(all(
+    test,
+    feature = "aes-gcm",
+    feature = "chacha20poly1305",
+    feature = "p256",
+    feature = "p384",
+    feature = "rsa",
+    feature = "std"
+))]
+mod tests {
+    use super::*;
+    use crate::high::curve::PrivateKey;
+    use digest::{Digest, Mac};
+
+    #[test]
+    fn digest() {
+        let mut ctx = Sha256Context::default();
+        Digest::update(&mut ctx, b"hello");
+        assert_eq!(
+            ctx.finalize_reset().as_slice(),
+            Sha256::hash(b"hello").as_ref()
+        );
+        assert_eq!(
+            <Sha256Context as Digest>::digest(b"hello").as_slice(),
+            Sha256::hash(b"hello").as_ref()
+        );
+        assert_eq!(ctx.finalize().as_slice(), Sha256::hash(b"").as_ref());
+        assert_eq!(
+            Sha512_256Context::digest(b"hello").as_slice(),
+            crate::high::hash::Sha512_256::hash(b"hello").as_ref()
+        );
+    }
+
+    #[test]
+    fn mac() {
+        // RFC4231 test case 2
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(b"Jefe").unwrap();
+        Mac::update(&mut mac, b"what do ya want for nothing?");
+        mac.verify_slice(b"\x5b\xdc\xc1\x46\xbf\x60\x75\x4e\x6a\x04\x24\x26\x08\x95\x75\xc7\x5a\x00\x3f\x08\x9d\x27\x39\x83\x9d\xec\x58\xb9\x64\xec\x38\x43")
+            .unwrap();
+
+        let mac = <Hmac<Sha256> as Mac>::new_from_slice(b"Jefe").unwrap();
+        assert!(mac.verify_slice(&[0u8; 32]).is_err());
+    }
+
+    #[test]
+    fn aead() {
+        check_aead(&AesGcm::new(&[0x11; 16]), |n, a, c, t| {
+            AesGcm::new(&[0x11; 16]).encrypt(n, a, c, t)
+        });
+        check_aead(&AesGcm::new(&[0x22; 32]), |n, a, c, t| {
+            AesGcm::new(&[0x22; 32]).encrypt(n, a, c, t)
+        });
+        check_aead(
+            &<ChaCha20Poly1305 as KeyInit>::new(&[0x33; 32].into()),
+            |n, a, c, t| ChaCha20Poly1305::new([0x33; 32]).encrypt(n, a, c, t),
+        );
+    }
+
+    fn check_aead<A: AeadInPlace<NonceSize = U12, TagSize = U16>>(
+        aead: &A,
+        native_encrypt: impl Fn(&[u8; 12], &[u8], &mut [u8], &mut [u8; 16]),
+    ) {
+        let nonce = [0x44; 12];
+        let mut cipher = *b"hello world";
+        let tag = aead
+            .encrypt_in_place_detached(&nonce.into(), b"aad", &mut cipher)
+            .unwrap();
+
+        let mut native_cipher = *b"hello world";
+        let mut native_tag = [0u8; 16];
+        native_encrypt(&nonce, b"aad", &mut native_cipher, &mut native_tag);
+        assert_eq!(cipher, native_cipher);
+        assert_eq!(tag.as_slice(), &native_tag);
+
+        let mut plain = cipher;
+        aead.decrypt_in_place_detached(&nonce.into(), b"aad", &mut plain, &tag)
+            .unwrap();
+        assert_eq!(&plain, b"hello world");
+
+        let mut plain = cipher;
+        aead.decrypt_in_place_detached(&nonce.into(), b"aaa", &mut plain, &tag)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn ecdsa() {
+        // nb. the inherent `sign()` and `verify()` methods take precedence
+        // over the trait methods, so the traits are named here.
+        let sk =
+            ecdsa::SigningKey::<P256>::from_pkcs8_der(include_bytes!("ecdsa/secp256r1.pkcs8.der"))
+                .unwrap();
+        let mut public_key = [0u8; 65];
+        let vk = ecdsa::VerifyingKey::<P256>::from_x962_uncompressed(
+            sk.private_key
+                .public_key_encode_uncompressed(&mut public_key)
+                .unwrap(),
+        )
+        .unwrap();
+
+        let signature: EcdsaSignature<P256> = Signer::sign(&sk, b"hello");
+        assert_eq!(signature.to_bytes().as_ref().len(), 64);
+        Verifier::verify(&vk, b"hello", &signature).unwrap();
+        Verifier::verify(&vk, b"jello", &signature).unwrap_err();
+        vk.verify::<Sha256>(&[b"hello"], signature.as_ref())
+            .unwrap();
+
+        let signature: EcdsaSignature<P256> =
+            RandomizedSigner::sign_with_rng(&sk, &mut CountingRng(0), b"hello");
+        let signature = EcdsaSignature::<P256>::try_from(signature.as_ref()).unwrap();
+        Verifier::verify(&vk, b"hello", &signature).unwrap();
+        EcdsaSignature::<P256>::try_from(&signature.as_ref()[1..]).unwrap_err();
+
+        let sk =
+            ecdsa::SigningKey::<P384>::from_pkcs8_der(include_bytes!("ecdsa/secp384r1.pkcs8.der"))
+                .unwrap();
+        let mut public_key = [0u8; 97];
+        let vk = ecdsa::VerifyingKey::<P384>::from_x962_uncompressed(
+            sk.private_key
+                .public_key_encode_uncompressed(&mut public_key)
+                .unwrap(),
+        )
+        .unwrap();
+
+        let signature: EcdsaSignature<P384> =
+            RandomizedSigner::sign_with_rng(&sk, &mut CountingRng(0), b"hello");
+        assert_eq!(signature.as_ref().len(), 96);
+        Verifier::verify(&vk, b"hello", &signature).unwrap();
+        vk.verify::<Sha384>(&[b"hello"], signature.as_ref())
+            .unwrap();
+    }
+
+    #[test]
+    fn rsa() {
+        let sk = rsa::SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa2048.der")).unwrap();
+        let vk = sk.public_key();
+
+        let signature: RsaPkcs1Signature<Sha256> = sk.sign(b"hello");
+        assert_eq!(signature.as_ref().len(), 256);
+        vk.verify(b"hello", &signature).unwrap();
+        vk.verify(b"jello", &signature).unwrap_err();
+        vk.verify_pkcs1_sha256(signature.as_ref(), b"hello")
+            .unwrap();
+        // the scheme and hash function are part of the signature type
+        let wrong = RsaPkcs1Signature::<Sha512>::try_from(signature.as_ref()).unwrap();
+        vk.verify(b"hello", &wrong).unwrap_err();
+
+        let signature: RsaPssSignature<Sha384> = sk.sign(b"hello");
+        vk.verify(b"hello", &signature).unwrap();
+        let signature: RsaPssSignature<Sha512> = sk.sign_with_rng(&mut CountingRng(0), b"hello");
+        vk.verify(b"hello", &signature).unwrap();
+        let wrong = RsaPkcs1Signature::<Sha512>::try_from(signature.as_ref()).unwrap();
+        vk.verify(b"hello", &wrong).unwrap_err();
+    }
+
+    #[test]
+    fn rand_core_random_source() {
+        let mut out = [0u8; 4];
+        crate::mid::p256::PrivateKey::new_random_with_rng(&mut CountingRng(1)).unwrap();
+        (&mut CountingRng(1) as &mut dyn RandomSource)
+            .fill(&mut out)
+            .unwrap();
+        assert_eq!(out, [1, 2, 3, 4]);
+    }
+
+    /// A predictable RNG, for testing.
+    struct CountingRng(u8);
+
+    impl rand_core::RngCore for CountingRng {
+        fn next_u32(&mut self) -> u32 {
+            rand_core::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for b in dest {
+                *b = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl rand_core::CryptoRng for CountingRng {}
+}
@@ -0,0 +1,574 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! Known-answer and pairwise consistency self-tests.
+//!
+//! These run each algorithm once, through whichever implementation
+//! was selected for this CPU.  The inputs are long enough to reach the
+//! widest code paths: for example, 16 blocks at a time for AES-GCM,
+//! 8 blocks for ChaCha20 and 8 chunks for BLAKE3.
+//!
+//! Expected answers are from published test vectors, or were computed
+//! using other implementations.
+
+use crate::error::Error;
+
+/// Run all self-tests for the algorithms in this build.
+pub(crate) fn self_test() -> Result<(), Error> {
+    #[cfg_attr(
+        not(any(feature = "aes-gcm", feature = "chacha20poly1305")),
+        allow(unused_mut)
+    )]
+    let mut message = [0u8; MESSAGE_LEN];
+    for (i, m) in message.iter_mut().enumerate() {
+        *m = (i % 251) as u8;
+    }
+
+    #[cfg(feature = "sha1")]
+    run(SelfTestFailure::Sha1, hashes::sha1(&message))?;
+    #[cfg(feature = "sha256")]
+    run(SelfTestFailure::Sha256, hashes::sha256(&message))?;
+    #[cfg(feature = "sha512")]
+    run(SelfTestFailure::Sha512, hashes::sha512(&message))?;
+    #[cfg(feature = "sha3")]
+    run(SelfTestFailure::Sha3, hashes::sha3(&message))?;
+    #[cfg(feature = "blake2")]
+    run(SelfTestFailure::Blake2b, hashes::blake2b(&message))?;
+    #[cfg(feature = "blake2")]
+    run(SelfTestFailure::Blake2s, hashes::blake2s(&message))?;
+    #[cfg(feature = "blake3")]
+    run(SelfTestFailure::Blake3, hashes::blake3(&message))?;
+    #[cfg(feature = "sha256")]
+    run(SelfTestFailure::Hmac, hashes::hmac(&message))?;
+    #[cfg(feature = "sha256")]
+    run(SelfTestFailure::HmacDrbg, drbg::hmac_drbg())?;
+    #[cfg(feature = "aes")]
+    run(SelfTestFailure::CtrDrbg, drbg::ctr_drbg())?;
+    #[cfg(feature = "aes-gcm")]
+    run(SelfTestFailure::AesGcm, aead::aes_gcm(&mut message))?;
+    #[cfg(feature = "chacha20poly1305")]
+    run(
+        SelfTestFailure::ChaCha20Poly1305,
+        aead::chacha20poly1305(&mut message),
+    )?;
+    #[cfg(feature = "x25519")]
+    run(SelfTestFailure::X25519, kx::x25519())?;
+    #[cfg(feature = "p256")]
+    run(SelfTestFailure::P256, kx::p256())?;
+    #[cfg(feature = "p384")]
+    run(SelfTestFailure::P384, kx::p384())?;
+    #[cfg(feature = "ffdhe")]
+    run(SelfTestFailure::Ffdhe, kx::ffdhe())?;
+    #[cfg(all(feature = "p256", feature = "sha256"))]
+    run(SelfTestFailure::EcdsaP256, sign::ecdsa_p256())?;
+    #[cfg(all(feature = "p384", feature = "sha512"))]
+    run(SelfTestFailure::EcdsaP384, sign::ecdsa_p384())?;
+    #[cfg(feature = "rsa")]
+    run(SelfTestFailure::Rsa, sign::rsa())?;
+
+    Ok(())
+}
+
+/// Runs `self_test()` the first time the library is used.
+///
+/// This panics if any self-test fails, then and at every subsequent
+/// entry to the library.
+#[cfg(feature = "self-test-on-first-use")]
+pub(crate) fn on_first_use() {
+    use core::cell::Cell;
+    use std::sync::OnceLock;
+
+    static RESULT: OnceLock<Result<(), Error>> = OnceLock::new();
+
+    std::thread_local! {
+        static RUNNING: Cell<bool> = const { Cell::new(false) };
+    }
+
+    let result = match RESULT.get() {
+        Some(result) => result,
+        // the self-tests themselves use the library
+        None if RUNNING.with(Cell::get) => return,
+        None => RESULT.get_or_init(|| {
+            RUNNING.with(|r| r.set(true));
+            let result = self_test();
+            RUNNING.with(|r| r.set(false));
+            result
+        }),
+    };
+
+    if let Err(err) = result {
+        panic!("graviola self-test failed: {err:?}");
+    }
+}
+
+cfg_if::cfg_if! {
+    // every algorithm is tested with these.
+    if #[cfg(any(
+        feature = "aes",
+        feature = "blake2",
+        feature = "blake3",
+        feature = "chacha20poly1305",
+        feature = "p256",
+        feature = "p384",
+        feature = "sha1",
+        feature = "sha256",
+        feature = "sha3",
+        feature = "sha512",
+        feature = "x25519"
+    ))] {
+        use crate::error::SelfTestFailure;
+
+        fn run(algorithm: SelfTestFailure, outcome: Option<()>) -> Result<(), Error> {
+            outcome.ok_or(Error::SelfTestFailed(algorithm))
+        }
+
+        fn expect(condition: bool) -> Option<()> {
+            condition.then_some(())
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    // only the randomised algorithms need a `RandomSource`.
+    if #[cfg(any(
+        feature = "p256",
+        feature = "p384",
+        feature = "rsa",
+        feature = "x25519"
+    ))] {
+        use crate::mid::rng::RandomSource;
+
+        /// A predictable [`RandomSource`], so randomised algorithms are repeatable.
+        struct Counter(u8);
+
+        impl RandomSource for Counter {
+            fn fill(&mut self, out: &mut [u8]) -> Result<(), Error> {
+                for byte in out {
+                    *byte = self.0;
+                    self.0 = self.0.wrapping_add(1);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The test message is this many bytes of `i % 251`.
+///
+/// This is more than 8 BLAKE3 chunks.
+const MESSAGE_LEN: usize = 8193;
+
+/// Key, nonce and additional data (or personalization) used throughout.
+#[cfg(any(feature = "aes-gcm", feature = "chacha20poly1305"))]
+const KEY: &[u8; 32] = &[
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+];
+#[cfg(any(feature = "aes-gcm", feature = "chacha20poly1305"))]
+const NONCE: &[u8; 12] = &[
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+];
+#[cfg(any(
+    feature = "aes-gcm",
+    feature = "chacha20poly1305",
+    feature = "sha256",
+    all(feature = "p384", feature = "sha512")
+))]
+const AAD: &[u8] = b"graviola self-test";
+
+#[cfg(any(
+    feature = "blake2",
+    feature = "blake3",
+    feature = "sha1",
+    feature = "sha256",
+    feature = "sha3",
+    feature = "sha512"
+))]
+mod hashes {
+    use super::*;
+    use crate::high::hash::*;
+
+    fn check<H: Hash>(message: &[u8], expected: &[u8]) -> Option<()> {
+        // hash both one-shot and in pieces, to cover buffering.
+        let mut ctx = H::new();
+        for chunk in message.chunks(1000) {
+            ctx.update(chunk);
+        }
+        expect(ctx.finish().as_ref() == expected)?;
+        expect(H::hash(message).as_ref() == expected)
+    }
+
+    #[cfg(feature = "sha1")]
+    pub(super) fn sha1(message: &[u8]) -> Option<()> {
+        check::<Sha1>(
+            message,
+            b"\xc7\xfa\xc2\xa2\x75\x1a\xd1\x55\x28\x13\xf3\x66\xc3\xda\xef\x8e\xba\x51\x24\x36",
+        )
+    }
+
+    #[cfg(feature = "sha256")]
+    pub(super) fn sha256(message: &[u8]) -> Option<()> {
+        check::<Sha256>(message, b"\x7e\x36\x91\x79\x0c\xd6\x4b\x19\xd4\xed\xb1\xa8\x0e\x98\x82\x14\x51\x5a\xbe\xb5\x3a\xa0\xf3\x4f\xfb\xfe\x4b\x4b\xf4\x05\xd1\x20")
+    }
+
+    #[cfg(feature = "sha512")]
+    pub(super) fn sha512(message: &[u8]) -> Option<()> {
+        check::<Sha512>(message, b"\x82\x5d\x72\x24\x9c\x6a\xde\xff\x4a\xb0\x14\x90\xf1\xdc\xf1\x74\xbb\x18\x72\xba\xfe\x8d\xea\xe5\x1b\xb9\xf7\xea\xd4\x3b\xe0\x84\xcd\x69\x03\x06\xfe\x2b\x20\xfa\x7b\x79\x1b\xf3\x2e\xfe\x81\x4b\x8b\x06\x66\x7d\xf2\xeb\x62\x35\xff\x2c\x1b\xbb\xfb\x9a\x0e\x4a")
+    }
+
+    #[cfg(feature = "sha3")]
+    pub(super) fn sha3(message: &[u8]) -> Option<()> {
+        check::<Sha3_256>(message, b"\x91\xcb\xc1\x40\xc9\x56\x62\xbd\x52\xcf\xb1\x56\xb3\x6f\xe9\xee\xd5\x2f\x26\x4d\xd7\xba\xbd\xe2\x5c\xb5\xe8\x48\x37\x63\x3b\x88")
+    }
+
+    #[cfg(feature = "blake2")]
+    pub(super) fn blake2b(message: &[u8]) -> Option<()> {
+        check::<Blake2b512>(message, b"\x00\xf3\x82\xe5\x0a\xa0\x61\xd8\xe3\xea\xc0\xa7\xbe\xc8\x9c\x71\x1d\x2e\xc4\xc3\x15\xd8\x94\xfe\xf9\x2a\x8c\x71\xd7\x9b\x4f\x9b\x8b\x61\x82\xbd\x29\x65\xb2\x42\x8c\x12\x00\x1c\x07\x48\xef\xff\x0e\x7a\x96\x10\xce\xa3\x3f\x83\xa0\x55\xc6\x95\xd5\xab\x76\x7f")
+    }
+
+    #[cfg(feature = "blake2")]
+    pub(super) fn blake2s(message: &[u8]) -> Option<()> {
+        check::<Blake2s256>(message, b"\xf8\x0a\x41\x68\x16\x76\x18\x39\x95\xdf\x96\xab\xc3\xab\x16\x3d\x88\xa4\xff\xb2\x4b\xe4\x1a\xc8\xfd\x44\x88\xf6\xfd\xab\x02\x13")
+    }
+
+    #[cfg(feature = "blake3")]
+    pub(super) fn blake3(message: &[u8]) -> Option<()> {
+        check::<Blake3>(message, b"\xba\xb6\xc0\x9c\xb8\xce\x8c\xf4\x59\x26\x13\x98\xd2\xe7\xae\xf3\x57\x00\xbf\x48\x81\x16\xce\xb9\x4a\x36\xd0\xf5\xf1\xb7\xbc\x3b")
+    }
+
+    #[cfg(feature = "sha256")]
+    pub(super) fn hmac(message: &[u8]) -> Option<()> {
+        let mut ctx = crate::high::hmac::Hmac::<Sha256>::new(AAD);
+        ctx.update(message);
+        expect(ctx.finish().as_ref() == b"\x68\xfc\xd2\x95\xf0\xbe\x67\x0d\x82\xe1\x08\xb4\x57\x01\xfb\x84\xef\x19\x32\x80\x05\x24\x76\x16\xae\x32\xcc\x03\xef\x63\xb1\x06")
+    }
+}
+
+#[cfg(any(feature = "aes", feature = "sha256"))]
+mod drbg {
+    use super::*;
+
+    /// The example from RFC6979 A.1.
+    #[cfg(feature = "sha256")]
+    pub(super) fn hmac_drbg() -> Option<()> {
+        let mut ctx = crate::high::hmac_drbg::HmacDrbg::<crate::high::hash::Sha256>::new(
+            b"\x00\x9A\x4D\x67\x92\x29\x5A\x7F\x73\x0F\xC3\xF2\xB4\x9C\xBC\x0F\x62\xE8\x62\x27\x2F",
+            b"\x01\x79\x5E\xDF\x0D\x54\xDB\x76\x0F\x15\x6D\x0D\xAC\x04\xC0\x32\x2B\x3A\x20\x42\x24",
+            &[],
+        );
+        let mut out = [0u8; 32];
+        ctx.generate(&[], &mut out).ok()?;
+        expect(&out == b"\x93\x05\xA4\x6D\xE7\xFF\x8E\xB1\x07\x19\x4D\xEB\xD3\xFD\x48\xAA\x20\xD5\xE7\x65\x6C\xBE\x0E\xA6\x9D\x2A\x8D\x4E\x7C\x67\x31\x4A")
+    }
+
+    /// An example from the NIST CAVS vectors.
+    #[cfg(feature = "aes")]
+    pub(super) fn ctr_drbg() -> Option<()> {
+        let mut ctx = crate::high::ctr_drbg::CtrDrbgAes256::new(
+            b"\x2d\x4c\x9f\x46\xb9\x81\xc6\xa0\xb2\xb5\xd8\xc6\x93\x91\xe5\x69\xff\x13\x85\x14\x37\xeb\xc0\xfc\x00\xd6\x16\x34\x02\x52\xfe\xd5",
+            b"\x0b\xf8\x14\xb4\x11\xf6\x5e\xc4\x86\x6b\xe1\xab\xb5\x9d\x3c\x32",
+            &[],
+        );
+        ctx.reseed(b"\x93\x50\x0f\xae\x4f\xa3\x2b\x86\x03\x3b\x7a\x7b\xac\x9d\x37\xe7\x10\xdc\xc6\x7c\xa2\x66\xbc\x86\x07\xd6\x65\x93\x77\x66\xd2\x07", &[]);
+        let mut out = [0u8; 64];
+        ctx.generate(&[], &mut out).ok()?;
+        ctx.generate(&[], &mut out).ok()?;
+        expect(&out == b"\x32\x2d\xd2\x86\x70\xe7\x5c\x0e\xa6\x38\xf3\xcb\x68\xd6\xa9\xd6\xe5\x0d\xdf\xd0\x52\xb7\x72\xa7\xb1\xd7\x82\x63\xa7\xb8\x97\x8b\x67\x40\xc2\xb6\x5a\x95\x50\xc3\xa7\x63\x25\x86\x6f\xa9\x7e\x16\xd7\x40\x06\xbc\x96\xf2\x62\x49\xb9\xf0\xa9\x0d\x07\x6f\x08\xe5")
+    }
+}
+
+#[cfg(any(feature = "aes-gcm", feature = "chacha20poly1305"))]
+mod aead {
+    use super::*;
+
+    /// Encrypts `message` in place, checks the tag, then decrypts it.
+    ///
+    /// The tag covers every byte of ciphertext, so need not be compared
+    /// separately.
+    fn check(
+        message: &mut [u8],
+        encrypt: impl Fn(&mut [u8], &mut [u8; 16]),
+        decrypt: impl Fn(&mut [u8], &[u8]) -> Result<(), Error>,
+        expected_tag: &[u8; 16],
+    ) -> Option<()> {
+        let mut tag = [0u8; 16];
+        encrypt(message, &mut tag);
+        let tag_ok = &tag == expected_tag;
+        let decrypt_ok = decrypt(message, &tag).is_ok();
+        let plaintext_ok = message
+            .iter()
+            .enumerate()
+            .all(|(i, m)| *m == (i % 251) as u8);
+        expect(tag_ok && decrypt_ok && plaintext_ok)
+    }
+
+    #[cfg(feature = "aes-gcm")]
+    pub(super) fn aes_gcm(message: &mut [u8]) -> Option<()> {
+        use crate::mid::aes_gcm::AesGcm;
+
+        for (key, expected_tag) in [
+            (
+                &KEY[..16],
+                b"\xbc\x5c\xa5\xb2\x56\x4f\xc3\x29\xef\x21\x9f\x14\xc0\xf6\x45\xbe",
+            ),
+            (
+                &KEY[..],
+                b"\xbe\x4d\x2f\x68\x70\xd3\x6d\x64\xcf\x79\xcd\xf2\x52\xd8\x10\xc0",
+            ),
+        ] {
+            let key = AesGcm::new(key);
+            check(
+                message,
+                |inout, tag| key.encrypt(NONCE, AAD, inout, tag),
+                |inout, tag| key.decrypt(NONCE, AAD, inout, tag),
+                expected_tag,
+            )?;
+        }
+        Some(())
+    }
+
+    #[cfg(feature = "chacha20poly1305")]
+    pub(super) fn chacha20poly1305(message: &mut [u8]) -> Option<()> {
+        let key = crate::mid::chacha20poly1305::ChaCha20Poly1305::new(*KEY);
+        check(
+            message,
+            |inout, tag| key.encrypt(NONCE, AAD, inout, tag),
+            |inout, tag| key.decrypt(NONCE, AAD, inout, tag),
+            b"\xfe\x6d\x82\x11\xbc\xb5\xd8\x77\x22\x9a\x54\x4f\xd3\xeb\x39\x20",
+        )
+    }
+}
+
+#[cfg(any(
+    feature = "ffdhe",
+    feature = "p256",
+    feature = "p384",
+    feature = "x25519"
+))]
+mod kx {
+    use super::*;
+
+    // For each curve: the expected agreement between private keys
+    // `[1; LEN]` and `[2; LEN]`, computed in both directions, then
+    // the same for two freshly generated keys.
+
+    #[cfg(feature = "x25519")]
+    pub(super) fn x25519() -> Option<()> {
+        use crate::mid::x25519::PrivateKey;
+
+        let expected = b"\x2e\xd7\x6a\xb5\x49\xb1\xe7\x3c\x03\x1e\xb4\x9c\x94\x48\xf0\x79\x8a\xea\x81\xb6\x98\x27\x9a\x0c\x3d\xc3\xe4\x9f\xbf\xc4\xb9\x53";
+        let (a, b) = (
+            PrivateKey::from_array(&[1; 32]),
+            PrivateKey::from_array(&[2; 32]),
+        );
+        let (a_pub, b_pub) = (a.public_key(), b.public_key());
+        expect(&a.diffie_hellman(&b_pub).0 == expected)?;
+        expect(&b.diffie_hellman(&a_pub).0 == expected)?;
+
+        let mut rng = Counter(0);
+        let a = PrivateKey::new_random_with_rng(&mut rng).ok()?;
+        let b = PrivateKey::new_random_with_rng(&mut rng).ok()?;
+        let (a_pub, b_pub) = (a.public_key(), b.public_key());
+        expect(a.diffie_hellman(&b_pub).0 == b.diffie_hellman(&a_pub).0)
+    }
+
+    macro_rules! nist_curve {
+        ($name:ident, $feature:literal, $curve:ident, $len:literal, $expected:literal) => {
+            #[cfg(feature = $feature)]
+            pub(super) fn $name() -> Option<()> {
+                use crate::mid::$curve::{PrivateKey, PublicKey};
+
+                fn agree(a: PrivateKey, b: &PrivateKey) -> Option<[u8; $len]> {
+                    let b_pub =
+                        PublicKey::from_x962_uncompressed(&b.public_key_uncompressed()).ok()?;
+                    Some(a.diffie_hellman(&b_pub).ok()?.0)
+                }
+
+                let expected = $expected;
+                let a = || PrivateKey::from_bytes(&[1; $len]).ok();
+                let b = || PrivateKey::from_bytes(&[2; $len]).ok();
+                expect(&agree(a()?, &b()?)? == expected)?;
+                expect(&agree(b()?, &a()?)? == expected)?;
+
+                let mut rng = Counter(0);
+                let a = PrivateKey::new_random_with_rng(&mut rng).ok()?;
+                let b = PrivateKey::new_random_with_rng(&mut rng).ok()?;
+                let a_bytes = a.as_bytes();
+                let b_bytes = b.as_bytes();
+                let a_b = agree(a, &PrivateKey::from_bytes(&b_bytes).ok()?)?;
+                let b_a = agree(b, &PrivateKey::from_bytes(&a_bytes).ok()?)?;
+                expect(a_b == b_a)
+            }
+        };
+    }
+
+    /// Agrees between two ffdhe2048 keys generated from a `Counter`, and
+    /// checks the SHA-256 of the result.
+    #[cfg(feature = "ffdhe")]
+    pub(super) fn ffdhe() -> Option<()> {
+        use crate::high::hash::{Hash, Sha256};
+        use crate::mid::ffdhe::{Group, PrivateKey, PublicKey};
+
+        let mut rng = Counter(0);
+        let a = PrivateKey::new_random_with_rng(Group::Ffdhe2048, &mut rng).ok()?;
+        let b = PrivateKey::new_random_with_rng(Group::Ffdhe2048, &mut rng).ok()?;
+        let a_pub = PublicKey::from_bytes(Group::Ffdhe2048, a.public_key().as_bytes()).ok()?;
+        let b_pub = PublicKey::from_bytes(Group::Ffdhe2048, b.public_key().as_bytes()).ok()?;
+        let a_b = a.diffie_hellman(&b_pub).ok()?;
+        let b_a = b.diffie_hellman(&a_pub).ok()?;
+        expect(Sha256::hash(a_b.as_ref()).as_ref() == b"\xa2\x65\xdf\xb5\x09\x93\xaa\xc4\x8a\x45\xb3\xd5\xcc\x8f\x61\x3d\xb9\xdc\x24\x06\x2b\xb0\xe2\xec\xe3\xb9\xa8\xf7\xe6\x49\x4f\x8c")?;
+        expect(a_b.as_ref() == b_a.as_ref())
+    }
+
+    nist_curve!(
+        p256,
+        "p256",
+        p256,
+        32,
+        b"\xeb\x45\x9a\x6b\x30\xd7\x0e\xb4\xc0\xdd\xcd\x34\xea\x96\xed\x59\xe8\xf8\xd1\x84\x3c\x22\xdd\x89\x5a\xb6\x03\xaa\x13\xd9\xa8\x1d"
+    );
+    nist_curve!(
+        p384,
+        "p384",
+        p384,
+        48,
+        b"\x49\xca\x54\x24\x67\x3f\x10\x6f\xc1\x96\x8d\xa5\xde\xb5\x09\xbb\x51\xaf\x15\xf9\xa4\xff\xda\x8f\x0a\x41\xdd\x25\x28\xa0\x86\xe7\xa9\xf0\xf2\x4b\x9b\x2a\xc7\xf9\x05\x77\xc5\x36\x3c\xe9\xa4\x45"
+    );
+}
+
+#[cfg(any(
+    all(feature = "p256", feature = "sha256"),
+    all(feature = "p384", feature = "sha512"),
+    feature = "rsa"
+))]
+mod sign {
+    use super::*;
+    #[cfg(any(
+        all(feature = "p256", feature = "sha256"),
+        all(feature = "p384", feature = "sha512")
+    ))]
+    use crate::high::curve::{Curve, PrivateKey, PublicKey};
+    #[cfg(any(
+        all(feature = "p256", feature = "sha256"),
+        all(feature = "p384", feature = "sha512")
+    ))]
+    use crate::high::ecdsa::{SigningKey, VerifyingKey};
+    #[cfg(any(
+        all(feature = "p256", feature = "sha256"),
+        all(feature = "p384", feature = "sha512")
+    ))]
+    use crate::high::hash::SigningHash;
+
+    /// Signs a known message deterministically (as RFC6979), and checks
+    /// the result.  Then checks that the key and its public key agree,
+    /// for that signature and a randomised one.
+    #[cfg(any(
+        all(feature = "p256", feature = "sha256"),
+        all(feature = "p384", feature = "sha512")
+    ))]
+    fn ecdsa<C: Curve, H: SigningHash>(key: &SigningKey<C>, expected: &[u8]) -> Option<()> {
+        let mut signature = [0u8; 96];
+        let signature = key
+            .rfc6979_sign_with_random::<H>(&[AAD], &[], &mut signature)
+            .ok()?;
+        expect(signature == expected)?;
+        ecdsa_pairwise::<C, H>(key, signature)
+    }
+
+    #[cfg(any(
+        all(feature = "p256", feature = "sha256"),
+        all(feature = "p384", feature = "sha512")
+    ))]
+    fn ecdsa_pairwise<C: Curve, H: SigningHash>(
+        key: &SigningKey<C>,
+        signature: &[u8],
+    ) -> Option<()> {
+        let mut public_key = [0u8; 97];
+        let public_key = key
+            .private_key
+            .public_key_encode_uncompressed(&mut public_key)
+            .ok()?;
+        let verifier = VerifyingKey::<C> {
+            public_key: C::PublicKey::from_x962_uncompressed(public_key).ok()?,
+        };
+        verifier.verify::<H>(&[AAD], signature).ok()?;
+
+        let mut random_signature = [0u8; 96];
+        let random_signature = key
+            .sign_with_rng::<H>(&mut Counter(0), &[AAD], &mut random_signature)
+            .ok()?;
+        verifier.verify::<H>(&[AAD], random_signature).ok()?;
+
+        // and that verification can fail
+        expect(verifier.verify::<H>(&[b"other"], signature).is_err())
+    }
+
+    /// As `ecdsa()`, for a loaded key, then a generated one.
+    #[cfg(any(
+        all(feature = "p256", feature = "sha256"),
+        all(feature = "p384", feature = "sha512")
+    ))]
+    fn ecdsa_load_and_generate<C: Curve, H: SigningHash>(
+        pkcs8: &[u8],
+        expected: &[u8],
+    ) -> Option<()> {
+        ecdsa::<C, H>(&SigningKey::<C>::from_pkcs8_der(pkcs8).ok()?, expected)?;
+
+        let generated = SigningKey::<C> {
+            private_key: C::generate_random_key(&mut Counter(0)).ok()?,
+        };
+        let mut signature = [0u8; 96];
+        let signature = generated
+            .sign_with_rng::<H>(&mut Counter(1), &[AAD], &mut signature)
+            .ok()?;
+        ecdsa_pairwise::<C, H>(&generated, signature)
+    }
+
+    #[cfg(all(feature = "p256", feature = "sha256"))]
+    pub(super) fn ecdsa_p256() -> Option<()> {
+        ecdsa_load_and_generate::<crate::high::curve::P256, crate::high::hash::Sha256>(
+            include_bytes!("ecdsa/secp256r1.pkcs8.der"),
+            b"\xf2\xda\xe1\xc3\x13\x6a\x25\x82\xdb\xb7\x40\xda\x45\xa1\xcc\xfa\x6c\x17\x66\x10\x6c\xc9\x33\xaa\x43\xee\x9c\xe3\x8c\xe7\xbd\x06\x1d\x03\x99\x56\x77\x40\xbb\xb9\x41\x86\x95\xd9\x0b\x34\x47\xc3\x35\x19\x88\xe5\xd8\xad\x88\x8d\x2d\x6b\x62\x1b\xf1\x32\x9e\x52",
+        )
+    }
+
+    #[cfg(all(feature = "p384", feature = "sha512"))]
+    pub(super) fn ecdsa_p384() -> Option<()> {
+        ecdsa_load_and_generate::<crate::high::curve::P384, crate::high::hash::Sha384>(
+            include_bytes!("ecdsa/secp384r1.pkcs8.der"),
+            b"\x12\x67\x1c\xad\x4b\xb5\x8c\x94\x26\x03\x66\xc7\x20\x76\x48\x29\xa2\xfd\xb5\xdc\x31\xea\x2b\xdf\x3b\xee\xf1\xad\xe5\x54\xe1\xff\xa5\x6a\x6b\x7a\x70\x2a\x9b\x72\x75\xe7\xf4\x04\x9b\x87\xc6\xe1\xc0\x0a\x69\x9e\x5c\x91\xda\x40\x0e\xaf\x22\x24\x43\xc3\x68\x94\x50\x79\x37\xde\x40\x06\xf2\x12\x75\x5a\xd3\xf1\xe5\x71\xa5\xaf\x8f\x2e\x94\x7c\x60\x71\xe9\xf8\xda\xac\x63\xb8\xe2\x98\x90\x91",
+        )
+    }
+
+    /// Signs a known message with RSASSA-PKCS1-v1_5 and checks the (SHA-256
+    /// of the) result.  Then checks that the key and its public key agree,
+    /// for that signature and an RSASSA-PSS one.
+    #[cfg(feature = "rsa")]
+    pub(super) fn rsa() -> Option<()> {
+        use crate::high::hash::{Hash, Sha256};
+        use crate::high::rsa::SigningKey;
+
+        let key = SigningKey::from_pkcs1_der(include_bytes!("rsa/rsa2048.der")).ok()?;
+        let public_key = key.public_key();
+
+        let mut signature = [0u8; 256];
+        let signature = key.sign_pkcs1_sha256(&mut signature, AAD).ok()?;
+        expect(Sha256::hash(signature).as_ref() == b"\x19\x2d\x5f\x33\x1f\x33\x34\x08\xf7\xeb\x53\xe8\x13\x53\xea\x3a\xb5\x62\xd0\xc2\x57\x59\x96\x01\x97\x43\x81\x28\xe6\x06\x95\x7a")?;
+        public_key.verify_pkcs1_sha256(signature, AAD).ok()?;
+        expect(public_key.verify_pkcs1_sha256(signature, b"other").is_err())?;
+
+        let mut signature = [0u8; 256];
+        let signature = key
+            .sign_pss_sha256_with_rng(&mut Counter(0), &mut signature, AAD)
+            .ok()?;
+        public_key.verify_pss_sha256(signature, AAD).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn self_test() {
+        super::self_test().unwrap();
+    }
+}
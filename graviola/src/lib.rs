@@ -1,6 +1,7 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(
     clippy::new_without_default,
     clippy::result_unit_err,
@@ -49,7 +50,23 @@ mod error;
 mod test;
 
 // vvv Public API
-pub use error::Error;
+pub use error::{Error, JwsError, KeyFormatError, SelfTestFailure};
+
+/// Runs known-answer and pairwise consistency self-tests.
+///
+/// Every algorithm included in this build is tested, using the
+/// implementation selected for this CPU.  Signing and key agreement
+/// keys are tested after loading and after generation.
+///
+/// The first failure is returned as [`Error::SelfTestFailed`], naming
+/// the algorithm.
+///
+/// With the `self-test-on-first-use` crate feature, this runs
+/// automatically the first time the library is used, and the library
+/// panics if it fails.
+pub fn self_test() -> Result<(), Error> {
+    high::self_test::self_test()
+}
 
 /// Non-API documentation
 #[cfg(doc)]
@@ -59,6 +76,7 @@ pub mod doc {
 
 /// Key agreement algorithms.
 pub mod key_agreement {
+    #[cfg(feature = "x25519")]
     /// X25519 key agreement.
     ///
     /// See [RFC7748](https://datatracker.ietf.org/doc/html/rfc7748).
@@ -66,6 +84,7 @@ pub mod key_agreement {
         pub use crate::mid::x25519::{PrivateKey, PublicKey, SharedSecret};
     }
 
+    #[cfg(feature = "p256")]
     /// Elliptic curve Diffie-Hellman on P-256
     ///
     /// P-256 is also known as "NISTP256", "prime256v1", or "secp256r1".
@@ -75,6 +94,7 @@ pub mod key_agreement {
         pub use crate::mid::p256::{PrivateKey, PublicKey, SharedSecret};
     }
 
+    #[cfg(feature = "p384")]
     /// Elliptic curve Diffie-Hellman on P-384
     ///
     /// P-384 is also known as "NISTP384", or "secp384r1".
@@ -83,40 +103,182 @@ pub mod key_agreement {
     pub mod p384 {
         pub use crate::mid::p384::{PrivateKey, PublicKey, SharedSecret};
     }
+
+    #[cfg(feature = "ffdhe")]
+    /// Finite field Diffie-Hellman, using the RFC7919 groups.
+    ///
+    /// See [RFC7919](https://datatracker.ietf.org/doc/html/rfc7919).
+    pub mod ffdhe {
+        pub use crate::mid::ffdhe::{Group, PrivateKey, PublicKey, SharedSecret};
+    }
 }
 
 /// Public key signatures.
 pub mod signing {
+    #[cfg(feature = "rsa")]
     /// RSA signatures.
     pub mod rsa {
-        pub use crate::high::rsa::{SigningKey, VerifyingKey};
+        pub use crate::high::rsa::{PssHash, PssParameters, Restriction, SigningKey, VerifyingKey};
     }
 
+    #[cfg(any(feature = "p256", feature = "p384"))]
     /// ECDSA signatures.
     pub mod ecdsa {
-        pub use crate::high::curve::{Curve, P256, P384};
+        pub use crate::high::curve::Curve;
+        #[cfg(feature = "p256")]
+        pub use crate::high::curve::P256;
+        #[cfg(feature = "p384")]
+        pub use crate::high::curve::P384;
         pub use crate::high::ecdsa::{SigningKey, VerifyingKey};
     }
+
+    #[cfg(all(
+        feature = "pbes2",
+        any(feature = "p256", feature = "p384", feature = "rsa")
+    ))]
+    pub use crate::high::pkcs8::decrypt_pkcs8;
 }
 
 /// Cryptographic hash functions.
 pub mod hashing {
-    pub use super::high::hash::{Hash, HashContext, HashOutput, Sha256, Sha384, Sha512};
+    #[cfg(feature = "blake3")]
+    pub use super::high::hash::Blake3;
+    #[cfg(feature = "sha1")]
+    pub use super::high::hash::Sha1;
+    pub use super::high::hash::{Blake2Output, Hash, HashContext, HashOutput, SigningHash};
+    #[cfg(feature = "blake2")]
+    pub use super::high::hash::{Blake2b512, Blake2s256};
+    #[cfg(feature = "sha256")]
+    pub use super::high::hash::{Sha224, Sha256};
+    #[cfg(feature = "sha512")]
+    pub use super::high::hash::{Sha384, Sha512, Sha512_256};
+    #[cfg(feature = "sha3")]
+    pub use super::high::hash::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
     pub use super::high::hmac;
+    #[cfg(feature = "blake2")]
+    pub use super::mid::blake2;
+    #[cfg(feature = "blake3")]
+    pub use super::mid::blake3;
+    #[cfg(feature = "sha1")]
+    pub use super::mid::sha1;
+    #[cfg(any(feature = "sha256", feature = "sha512"))]
     pub use super::mid::sha2;
+    #[cfg(feature = "sha3")]
+    pub use super::mid::sha3;
 }
 
 /// Authenticated encryption.
 pub mod aead {
+    #[cfg(feature = "aes-gcm")]
     pub use super::mid::aes_gcm::AesGcm;
+    #[cfg(feature = "chacha20poly1305")]
     pub use super::mid::chacha20poly1305::ChaCha20Poly1305;
 }
 
+/// Hybrid public key encryption.
+///
+/// See [RFC9180](https://datatracker.ietf.org/doc/html/rfc9180).
+#[cfg(feature = "hpke")]
+pub mod hpke {
+    pub use crate::high::hpke::{Aead, Context, Kdf, Kem, PrivateKey, Psk, PublicKey, Suite};
+}
+
+/// QUIC header protection.
+///
+/// See [RFC9001 section 5.4](https://datatracker.ietf.org/doc/html/rfc9001#section-5.4).
+#[cfg(any(feature = "aes", feature = "chacha20poly1305"))]
+pub mod quic {
+    #[cfg(feature = "aes")]
+    pub use super::mid::quic::AesHeaderProtection;
+    #[cfg(feature = "chacha20poly1305")]
+    pub use super::mid::quic::ChaCha20HeaderProtection;
+}
+
 /// Cryptographic-quality random source
 pub mod random {
+    #[cfg(feature = "aes")]
+    pub use crate::high::ctr_drbg::CtrDrbgAes256;
+    pub use crate::high::hmac_drbg::HmacDrbg;
+    pub use crate::mid::rng::RandomSource;
+    #[cfg(feature = "std")]
+    pub use crate::mid::rng::{install_entropy_source, remove_entropy_source, SystemRandom};
+    #[cfg(feature = "std")]
+    pub use crate::mid::thread_rng::ThreadRandom;
+
     /// Fills the entirety of `out` with cryptographic-quality random bytes.
+    ///
+    /// This uses [`SystemRandom`], or [`ThreadRandom`] if the `thread-rng`
+    /// crate feature is enabled.
+    #[cfg(feature = "std")]
     pub fn fill(out: &mut [u8]) -> Result<(), super::Error> {
-        use crate::mid::rng::{RandomSource, SystemRandom};
-        SystemRandom.fill(out)
+        crate::mid::rng::DefaultRandom.fill(out)
     }
 }
+
+/// Interoperability with the [RustCrypto](https://github.com/RustCrypto) traits.
+///
+/// This is available with the `rustcrypto` crate feature, and implements:
+///
+/// - `digest::Digest` for the SHA-2 contexts in [`crate::hashing::sha2`],
+/// - `digest::Mac` for [`crate::hashing::hmac::Hmac`] with those hash functions,
+/// - `aead::AeadInPlace` for [`crate::aead::AesGcm`] and [`crate::aead::ChaCha20Poly1305`],
+/// - `signature::Signer`, `signature::RandomizedSigner` and `signature::Verifier`
+///   for the ECDSA and RSA keys in [`crate::signing`], using the signature types
+///   in this module, and
+/// - [`crate::random::RandomSource`] for any `rand_core::CryptoRngCore`.
+#[cfg(feature = "rustcrypto")]
+pub mod rustcrypto {
+    #[cfg(any(feature = "p256", feature = "p384"))]
+    pub use crate::high::rustcrypto::EcdsaSignature;
+    #[cfg(feature = "rsa")]
+    pub use crate::high::rustcrypto::{RsaPkcs1Signature, RsaPssSignature};
+}
+
+/// X.509 public key infrastructure.
+///
+/// This is available with the `x509` crate feature, and supports
+/// making:
+///
+/// - X.509 v3 certificates with a [`x509::Certificate`], which
+///   may be self-signed or issued by an [`x509::Issuer`], and
+/// - PKCS#10 certification requests with a [`x509::CertificationRequest`].
+///
+/// These are signed with a key from [`crate::signing`], chosen with
+/// [`x509::Signer`].
+#[cfg(all(
+    feature = "x509",
+    any(feature = "p256", feature = "p384", feature = "rsa")
+))]
+pub mod x509 {
+    pub use crate::high::asn1::pkix::{
+        BasicConstraints, ExtendedKeyUsage, KeyUsage, NameAttribute, SubjectAltName,
+    };
+    pub use crate::high::certificate::{Certificate, Issuer};
+    pub use crate::high::csr::{CertificationRequest, Signer};
+}
+
+/// JSON Object Signing and Encryption.
+///
+/// This supports:
+///
+/// - JSON Web Keys ([RFC7517](https://datatracker.ietf.org/doc/html/rfc7517)),
+///   parsed as a [`jose::Jwk`] or a [`jose::JwkSet`] and then decoded into
+///   a key from [`crate::signing`] or [`crate::key_agreement`], and
+///   encoded from those keys with [`jose::Key`], and
+/// - JSON Web Signatures ([RFC7515](https://datatracker.ietf.org/doc/html/rfc7515))
+///   in the compact serialisation, signed with a [`jose::Jws`] and
+///   verified with an [`jose::UnverifiedJws`].  The algorithms are
+///   `ES256`, `ES384`, `RS256` and `PS256`.
+///
+/// JSON Web Tokens ([RFC7519](https://datatracker.ietf.org/doc/html/rfc7519))
+/// are JWSs with a JSON payload, so can be signed and verified here;
+/// the claims are left to the caller.
+#[cfg(all(
+    feature = "jose",
+    any(feature = "p256", feature = "p384", feature = "rsa")
+))]
+pub mod jose {
+    pub use crate::high::jose::{
+        Algorithm, Jwk, JwkSet, Jws, Key, Signer, UnverifiedJws, Verifier,
+    };
+}
@@ -9,6 +9,7 @@
 // cf. the x86_64 version, on which this one is based.
 
 use crate::low;
+#[cfg(feature = "aes-gcm")]
 use crate::low::aarch64::cpu;
 use core::arch::aarch64::*;
 
@@ -39,11 +40,13 @@ impl AesKey {
         }
     }
 
+    #[cfg(feature = "aes-gcm")]
     pub(crate) fn ctr(&self, initial_counter: &[u8; 16], cipher_inout: &mut [u8]) {
         // SAFETY: this crate requires the `aes` & `neon` cpu features
         unsafe { self._ctr(initial_counter, cipher_inout) }
     }
 
+    #[cfg(feature = "aes-gcm")]
     #[target_feature(enable = "aes,neon")]
     unsafe fn _ctr(&self, initial_counter: &[u8; 16], cipher_inout: &mut [u8]) {
         // counter and inc are big endian, so must be vrev32q_u8'd before use
@@ -261,6 +264,51 @@ unsafe fn _sub_word(w: u32) -> u32 {
 
 const RCON: [u32; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
 
+// decryption is only needed for encrypted PKCS#8 private keys.
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        test,
+        all(
+            feature = "pbes2",
+            any(feature = "p256", feature = "p384", feature = "rsa")
+        )
+    ))] {
+        impl AesKey {
+            pub(crate) fn decrypt_block(&self, inout: &mut [u8]) {
+                debug_assert_eq!(inout.len(), 16);
+
+                let round_keys: &[uint8x16_t] = match self {
+                    Self::Aes128(a128) => &a128.round_keys,
+                    Self::Aes256(a256) => &a256.round_keys,
+                };
+
+                // SAFETY: this crate requires the `aes` cpu feature
+                unsafe { aes_decrypt_block(round_keys, inout) }
+            }
+        }
+
+        /// Decrypts one block.
+        ///
+        /// `aesd` is `InvSubBytes(InvShiftRows(block ^ key))`, and `InvMixColumns`
+        /// is linear, so each middle round is `aesd(aesimc(block), aesimc(key))`.
+        /// The decryption round keys are derived from the encryption ones
+        /// as we go, rather than stored.
+        #[target_feature(enable = "aes")]
+        unsafe fn aes_decrypt_block(round_keys: &[uint8x16_t], block_inout: &mut [u8]) {
+            let (first, rest) = round_keys.split_first().unwrap();
+            let (last, middle) = rest.split_last().unwrap();
+
+            let mut block = vld1q_u8(block_inout.as_ptr() as *const _);
+            block = vaesdq_u8(block, *last);
+            for rk in middle.iter().rev() {
+                block = vaesdq_u8(vaesimcq_u8(block), vaesimcq_u8(*rk));
+            }
+            block = veorq_u8(block, *first);
+            vst1q_u8(block_inout.as_mut_ptr() as *mut _, block);
+        }
+    }
+}
+
 #[target_feature(enable = "aes")]
 unsafe fn aes128_block(round_keys: &[uint8x16_t; 11], block_inout: &mut [u8]) {
     let block = vld1q_u8(block_inout.as_ptr() as *const _);
@@ -469,6 +517,7 @@ mod tests {
 
     fn to_u128(v: uint8x16_t) -> u128 {
         let mut buf = [0u8; 16];
+        // SAFETY: `buf` is 16 writable bytes; this crate requires the `neon` cpu feature
         unsafe {
             vst1q_u8(buf.as_mut_ptr(), v);
         }
@@ -552,6 +601,15 @@ mod tests {
                 0xc5, 0x5a
             ]
         );
+
+        AesKey::Aes128(context).decrypt_block(&mut block);
+        assert_eq!(
+            block,
+            [
+                0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+                0xee, 0xff,
+            ]
+        );
     }
 
     #[test]
@@ -573,5 +631,14 @@ mod tests {
                 0x60, 0x89
             ]
         );
+
+        AesKey::Aes256(context).decrypt_block(&mut block);
+        assert_eq!(
+            block,
+            [
+                0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+                0xee, 0xff,
+            ]
+        );
     }
 }
@@ -0,0 +1,185 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+//
+// NEON by-4 BLAKE3 chunk hashing.  Each vector lane holds the
+// state of a different chunk.
+
+use core::arch::aarch64::*;
+
+use crate::low::generic::blake3::{
+    blake3_hash_chunks as generic_hash_chunks, BLOCK_LEN, CHUNK_END, CHUNK_LEN, CHUNK_START, IV,
+    MSG_SCHEDULE,
+};
+
+/// See [`crate::low::generic::blake3::blake3_hash_chunks`].
+pub(crate) fn blake3_hash_chunks(
+    key: &[u32; 8],
+    counter: u64,
+    flags: u32,
+    input: &[u8],
+    cvs: &mut [[u32; 8]],
+) {
+    assert_eq!(input.len(), cvs.len() * CHUNK_LEN);
+
+    let mut input = input.chunks_exact(CHUNK_LEN * 4);
+    let mut cvs = cvs.chunks_exact_mut(4);
+    let mut counter = counter;
+
+    for (input4, cvs4) in input.by_ref().zip(cvs.by_ref()) {
+        // SAFETY: this crate requires the `neon` cpu feature
+        unsafe {
+            hash_4_chunks(key, counter, flags, input4, cvs4);
+        }
+        counter += 4;
+    }
+
+    generic_hash_chunks(key, counter, flags, input.remainder(), cvs.into_remainder());
+}
+
+macro_rules! rotate_right {
+    ($reg:expr, 16) => {
+        vreinterpretq_u32_u16(vrev32q_u16(vreinterpretq_u16_u32($reg)))
+    };
+    ($reg:expr, $rot:literal) => {
+        vsriq_n_u32(vshlq_n_u32($reg, 32 - $rot), $reg, $rot)
+    };
+}
+
+macro_rules! g {
+    ($v:ident, $a:literal, $b:literal, $c:literal, $d:literal, $x:expr, $y:expr) => {
+        $v[$a] = vaddq_u32(vaddq_u32($v[$a], $v[$b]), $x);
+        $v[$d] = rotate_right!(veorq_u32($v[$d], $v[$a]), 16);
+        $v[$c] = vaddq_u32($v[$c], $v[$d]);
+        $v[$b] = rotate_right!(veorq_u32($v[$b], $v[$c]), 12);
+        $v[$a] = vaddq_u32(vaddq_u32($v[$a], $v[$b]), $y);
+        $v[$d] = rotate_right!(veorq_u32($v[$d], $v[$a]), 8);
+        $v[$c] = vaddq_u32($v[$c], $v[$d]);
+        $v[$b] = rotate_right!(veorq_u32($v[$b], $v[$c]), 7);
+    };
+}
+
+/// Hashes 4 whole chunks in parallel.
+#[target_feature(enable = "neon")]
+unsafe fn hash_4_chunks(
+    key: &[u32; 8],
+    counter: u64,
+    flags: u32,
+    input: &[u8],
+    cvs_out: &mut [[u32; 8]],
+) {
+    let mut cv = [vdupq_n_u32(0); 8];
+    for (cv, k) in cv.iter_mut().zip(key.iter()) {
+        *cv = vdupq_n_u32(*k);
+    }
+
+    let counters = [0u64, 1, 2, 3].map(|i| counter + i);
+    let counter_lo = counters.map(|c| c as u32);
+    let counter_hi = counters.map(|c| (c >> 32) as u32);
+    let counter_lo = vld1q_u32(counter_lo.as_ptr());
+    let counter_hi = vld1q_u32(counter_hi.as_ptr());
+    let block_len = vdupq_n_u32(BLOCK_LEN as u32);
+
+    for block in 0..CHUNK_LEN / BLOCK_LEN {
+        let block_flags = flags
+            | if block == 0 { CHUNK_START } else { 0 }
+            | if block == CHUNK_LEN / BLOCK_LEN - 1 {
+                CHUNK_END
+            } else {
+                0
+            };
+
+        let m = load_transposed(input, block * BLOCK_LEN);
+
+        let mut v = [
+            cv[0],
+            cv[1],
+            cv[2],
+            cv[3],
+            cv[4],
+            cv[5],
+            cv[6],
+            cv[7],
+            vdupq_n_u32(IV[0]),
+            vdupq_n_u32(IV[1]),
+            vdupq_n_u32(IV[2]),
+            vdupq_n_u32(IV[3]),
+            counter_lo,
+            counter_hi,
+            block_len,
+            vdupq_n_u32(block_flags),
+        ];
+
+        for s in MSG_SCHEDULE {
+            g!(v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g!(v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g!(v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g!(v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g!(v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g!(v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g!(v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g!(v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            cv[i] = veorq_u32(v[i], v[i + 8]);
+        }
+    }
+
+    let lo = transpose4([cv[0], cv[1], cv[2], cv[3]]);
+    let hi = transpose4([cv[4], cv[5], cv[6], cv[7]]);
+    for (i, out) in cvs_out.iter_mut().enumerate() {
+        vst1q_u32(out[..4].as_mut_ptr(), lo[i]);
+        vst1q_u32(out[4..].as_mut_ptr(), hi[i]);
+    }
+}
+
+/// Loads the 64-byte block at `offset` in each of the 4 chunks
+/// of `input`, and returns it arranged so that item `i` holds
+/// message word `i` from each chunk.
+#[target_feature(enable = "neon")]
+unsafe fn load_transposed(input: &[u8], offset: usize) -> [uint32x4_t; 16] {
+    let mut rows = [[vdupq_n_u32(0); 4]; 4];
+
+    for (i, chunk) in input.chunks_exact(CHUNK_LEN).enumerate() {
+        let block = &chunk[offset..offset + BLOCK_LEN];
+        for (j, row) in rows.iter_mut().enumerate() {
+            row[i] = vreinterpretq_u32_u8(vld1q_u8(block[j * 16..].as_ptr()));
+        }
+    }
+
+    let mut m = [vdupq_n_u32(0); 16];
+    for (m, row) in m.chunks_exact_mut(4).zip(rows.iter()) {
+        m.copy_from_slice(&transpose4(*row));
+    }
+    m
+}
+
+/// Transposes a 4x4 matrix of 32-bit words.
+#[target_feature(enable = "neon")]
+unsafe fn transpose4(x: [uint32x4_t; 4]) -> [uint32x4_t; 4] {
+    // interleave 32-bit words
+    let ab_0 = vtrn1q_u32(x[0], x[1]);
+    let ab_1 = vtrn2q_u32(x[0], x[1]);
+    let cd_0 = vtrn1q_u32(x[2], x[3]);
+    let cd_1 = vtrn2q_u32(x[2], x[3]);
+
+    // then 64-bit words
+    [
+        vreinterpretq_u32_u64(vtrn1q_u64(
+            vreinterpretq_u64_u32(ab_0),
+            vreinterpretq_u64_u32(cd_0),
+        )),
+        vreinterpretq_u32_u64(vtrn1q_u64(
+            vreinterpretq_u64_u32(ab_1),
+            vreinterpretq_u64_u32(cd_1),
+        )),
+        vreinterpretq_u32_u64(vtrn2q_u64(
+            vreinterpretq_u64_u32(ab_0),
+            vreinterpretq_u64_u32(cd_0),
+        )),
+        vreinterpretq_u32_u64(vtrn2q_u64(
+            vreinterpretq_u64_u32(ab_1),
+            vreinterpretq_u64_u32(cd_1),
+        )),
+    ]
+}
@@ -1,12 +1,12 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
-use std::arch::is_aarch64_feature_detected;
-
+#[cfg(feature = "_entry")]
 pub(crate) fn enter_cpu_state() -> u32 {
     dit::maybe_enable()
 }
 
+#[cfg(feature = "_entry")]
 pub(crate) fn leave_cpu_state(old: u32) {
     dit::maybe_disable(old);
 }
@@ -44,28 +44,164 @@ pub(in crate::low) fn zero_bytes(ptr: *mut u8, len: usize) {
     }
 }
 
+#[cfg(feature = "_entry")]
 pub(crate) fn verify_cpu_features() {
     assert!(
-        is_aarch64_feature_detected!("neon"),
+        feature_detected!("neon"),
         "graviola requires neon CPU support"
     );
     assert!(
-        is_aarch64_feature_detected!("aes"),
+        feature_detected!("aes"),
         "graviola requires aes CPU support"
     );
     assert!(
-        is_aarch64_feature_detected!("pmull"),
+        feature_detected!("pmull"),
         "graviola requires pmull CPU support"
     );
     assert!(
-        is_aarch64_feature_detected!("sha2"),
+        feature_detected!("sha2"),
         "graviola requires sha2 CPU support"
     );
 }
 
+// only SHA-3 and SHA-512 have optional cpu features.
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "sha3", feature = "sha512"))] {
+        /// This macro interdicts `feature_detected` to
+        /// allow testability.
+        macro_rules! have_cpu_feature {
+            ("sha3") => {
+                crate::low::aarch64::cpu::test_toggle(
+                    "sha3",
+                    crate::low::aarch64::cpu::feature_detected!("sha3"),
+                )
+            };
+        }
+
+        pub(crate) use have_cpu_feature;
+
+        #[cfg(not(all(debug_assertions, feature = "std")))]
+        pub(crate) fn test_toggle(_id: &str, detected: bool) -> bool {
+            detected
+        }
+
+        #[cfg(all(debug_assertions, feature = "std"))]
+        pub(crate) fn test_toggle(id: &str, detected: bool) -> bool {
+            if std::env::var(format!("GRAVIOLA_CPU_DISABLE_{id}")).is_ok() {
+                println!("DEBUG: denying cpuid {id:?}");
+                false
+            } else {
+                detected
+            }
+        }
+    }
+}
+
+// cpu feature detection is needed by `verify_cpu_features()`, and
+// to select between implementations of SHA-3 and SHA-512.
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "_entry", feature = "sha3", feature = "sha512"))] {
+        #[cfg(feature = "std")]
+        pub(crate) use std::arch::is_aarch64_feature_detected as feature_detected;
+
+        /// A `no_std` replacement for `is_aarch64_feature_detected`.
+        #[cfg(not(feature = "std"))]
+        macro_rules! feature_detected {
+            ($feature:tt) => {
+                crate::low::aarch64::cpu::id_regs::detected(
+                    crate::low::aarch64::cpu::id_regs::feature_bit!($feature),
+                )
+            };
+        }
+
+        #[cfg(not(feature = "std"))]
+        pub(crate) use feature_detected;
+
+        #[cfg(not(feature = "std"))]
+        pub(crate) mod id_regs {
+            use core::sync::atomic::{AtomicU32, Ordering};
+
+            /// Maps a feature name (as used by `is_aarch64_feature_detected`) to its bit.
+            macro_rules! feature_bit {
+                ("neon") => {
+                    1 << 0
+                };
+                ("aes") => {
+                    1 << 1
+                };
+                ("pmull") => {
+                    1 << 2
+                };
+                ("sha2") => {
+                    1 << 3
+                };
+                ("sha3") => {
+                    1 << 4
+                };
+                ("dit") => {
+                    1 << 5
+                };
+            }
+
+            pub(crate) use feature_bit;
+
+            pub(crate) fn detected(feature: u32) -> bool {
+                let mut features = CACHE.load(Ordering::Relaxed);
+                if features == 0 {
+                    features = detect() | INITIALISED;
+                    CACHE.store(features, Ordering::Relaxed);
+                }
+                features & feature == feature
+            }
+
+            fn detect() -> u32 {
+                let (isar0, pfr0): (u64, u64);
+                // SAFETY: the ID registers are readable at EL1, and at EL0 on Linux
+                // (which emulates these reads).  They have no side effects.
+                unsafe {
+                    core::arch::asm!(
+                        "mrs {isar0}, ID_AA64ISAR0_EL1",
+                        "mrs {pfr0}, ID_AA64PFR0_EL1",
+                        isar0 = out(reg) isar0,
+                        pfr0 = out(reg) pfr0,
+                        options(nomem, nostack, preserves_flags),
+                    );
+                }
+
+                let field = |reg: u64, shift: u32| (reg >> shift) & 0xf;
+                let aes = field(isar0, 4);
+                let sha2 = field(isar0, 12);
+                let sha3 = field(isar0, 32);
+                // 0b1111 means not implemented
+                let advsimd = field(pfr0, 20);
+                let dit = field(pfr0, 48);
+
+                let mut features = 0;
+                let mut set = |feature: u32, present: bool| {
+                    if present {
+                        features |= feature;
+                    }
+                };
+                set(feature_bit!("neon"), advsimd != 0xf);
+                set(feature_bit!("aes"), aes >= 1);
+                set(feature_bit!("pmull"), aes >= 2);
+                set(feature_bit!("sha2"), sha2 >= 1);
+                // `is_aarch64_feature_detected!("sha3")` implies SHA512 too
+                set(feature_bit!("sha3"), sha3 >= 1 && sha2 >= 2);
+                set(feature_bit!("dit"), dit >= 1);
+                features
+            }
+
+            const INITIALISED: u32 = 1 << 31;
+            static CACHE: AtomicU32 = AtomicU32::new(0);
+        }
+    }
+}
+
+#[cfg(feature = "_entry")]
 mod dit {
     pub(super) fn maybe_enable() -> u32 {
-        if super::is_aarch64_feature_detected!("dit") {
+        if super::feature_detected!("dit") {
             // SAFETY: in this branch, we verified `dit` cpu feature is supported
             match unsafe { read() } {
                 0 => {
@@ -115,6 +251,7 @@ mod dit {
 }
 
 /// Read-only prefetch hint.
+#[cfg(any(feature = "sha1", feature = "sha256", feature = "sha512"))]
 pub(in crate::low) fn prefetch_ro<T>(ptr: *const T) {
     // SAFETY: inline assembly
     unsafe {
@@ -127,6 +264,7 @@ pub(in crate::low) fn prefetch_ro<T>(ptr: *const T) {
 }
 
 /// Read-write prefetch hint.
+#[cfg(feature = "aes-gcm")]
 pub(in crate::low) fn prefetch_rw<T>(ptr: *const T) {
     // SAFETY: inline assembly
     unsafe {
@@ -290,9 +290,11 @@ fn to_u128(u: uint64x2_t) -> u128 {
 }
 
 const GF128_POLY_HI: uint64x2_t =
+    // SAFETY: u128 and uint64x2_t have the same size and meaning of bits
     unsafe { mem::transmute(0xc2000000_00000000_c2000000_00000000u128) };
 
 const GF128_POLY_CARRY_MASK: uint64x2_t =
+    // SAFETY: u128 and uint64x2_t have the same size and meaning of bits
     unsafe { mem::transmute(0xc2000000_00000001_00000000_00000001u128) };
 
 #[cfg(test)]
@@ -0,0 +1,168 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+//
+// refs:
+// - https://documentation-service.arm.com/static/65fdad3c1bc22b03bca90781
+//   (sections C7.2.32 BCAX, C7.2.45 EOR3, C7.2.222 RAX1, C7.2.411 XAR)
+//
+// These instructions are available with FEAT_SHA3, which rustc and LLVM
+// call the `sha3` target feature.  The intrinsics for these are newer
+// than our MSRV, so we use inline assembly for them.
+//
+// Each lane of the state is held in the bottom half of a vector register.
+
+use crate::low::generic::keccak::RC;
+use core::arch::aarch64::*;
+
+pub(in crate::low) fn keccak_f1600(state: &mut [u64; 25]) {
+    // SAFETY: the parent caller checks for the `sha3` cpu feature; this
+    // crate requires the `neon` cpu feature.
+    unsafe { keccak(state) }
+}
+
+/// `ror($a ^ $b, $imm)`
+macro_rules! xar {
+    ($a:expr, $b:expr, $imm:literal) => {{
+        let mut r = $a;
+        // SAFETY: `xar` is defined only if `sha3` cpu feature is supported
+        core::arch::asm!(
+            concat!("xar {r:v}.2d, {r:v}.2d, {x:v}.2d, #", $imm),
+            r = inout(vreg) r,
+            x = in(vreg) $b,
+            options(pure, nomem, nostack),
+        );
+        r
+    }};
+}
+
+#[target_feature(enable = "neon,sha3")]
+unsafe fn keccak(state: &mut [u64; 25]) {
+    let mut a = [vdupq_n_u64(0); 25];
+    for (a, s) in a.iter_mut().zip(state.iter()) {
+        *a = vdupq_n_u64(*s);
+    }
+
+    for rc in RC {
+        // theta
+        let c0 = eor3(eor3(a[0], a[5], a[10]), a[15], a[20]);
+        let c1 = eor3(eor3(a[1], a[6], a[11]), a[16], a[21]);
+        let c2 = eor3(eor3(a[2], a[7], a[12]), a[17], a[22]);
+        let c3 = eor3(eor3(a[3], a[8], a[13]), a[18], a[23]);
+        let c4 = eor3(eor3(a[4], a[9], a[14]), a[19], a[24]);
+        let d0 = rax1(c4, c1);
+        let d1 = rax1(c0, c2);
+        let d2 = rax1(c1, c3);
+        let d3 = rax1(c2, c4);
+        let d4 = rax1(c3, c0);
+
+        // rho and pi
+        let b = [
+            xar!(a[0], d0, 0),
+            xar!(a[6], d1, 20),
+            xar!(a[12], d2, 21),
+            xar!(a[18], d3, 43),
+            xar!(a[24], d4, 50),
+            xar!(a[3], d3, 36),
+            xar!(a[9], d4, 44),
+            xar!(a[10], d0, 61),
+            xar!(a[16], d1, 19),
+            xar!(a[22], d2, 3),
+            xar!(a[1], d1, 63),
+            xar!(a[7], d2, 58),
+            xar!(a[13], d3, 39),
+            xar!(a[19], d4, 56),
+            xar!(a[20], d0, 46),
+            xar!(a[4], d4, 37),
+            xar!(a[5], d0, 28),
+            xar!(a[11], d1, 54),
+            xar!(a[17], d2, 49),
+            xar!(a[23], d3, 8),
+            xar!(a[2], d2, 2),
+            xar!(a[8], d3, 9),
+            xar!(a[14], d4, 25),
+            xar!(a[15], d0, 23),
+            xar!(a[21], d1, 62),
+        ];
+
+        // chi
+        a[0] = bcax(b[0], b[2], b[1]);
+        a[1] = bcax(b[1], b[3], b[2]);
+        a[2] = bcax(b[2], b[4], b[3]);
+        a[3] = bcax(b[3], b[0], b[4]);
+        a[4] = bcax(b[4], b[1], b[0]);
+        a[5] = bcax(b[5], b[7], b[6]);
+        a[6] = bcax(b[6], b[8], b[7]);
+        a[7] = bcax(b[7], b[9], b[8]);
+        a[8] = bcax(b[8], b[5], b[9]);
+        a[9] = bcax(b[9], b[6], b[5]);
+        a[10] = bcax(b[10], b[12], b[11]);
+        a[11] = bcax(b[11], b[13], b[12]);
+        a[12] = bcax(b[12], b[14], b[13]);
+        a[13] = bcax(b[13], b[10], b[14]);
+        a[14] = bcax(b[14], b[11], b[10]);
+        a[15] = bcax(b[15], b[17], b[16]);
+        a[16] = bcax(b[16], b[18], b[17]);
+        a[17] = bcax(b[17], b[19], b[18]);
+        a[18] = bcax(b[18], b[15], b[19]);
+        a[19] = bcax(b[19], b[16], b[15]);
+        a[20] = bcax(b[20], b[22], b[21]);
+        a[21] = bcax(b[21], b[23], b[22]);
+        a[22] = bcax(b[22], b[24], b[23]);
+        a[23] = bcax(b[23], b[20], b[24]);
+        a[24] = bcax(b[24], b[21], b[20]);
+
+        // iota
+        a[0] = veorq_u64(a[0], vdupq_n_u64(rc));
+    }
+
+    for (s, a) in state.iter_mut().zip(a.iter()) {
+        *s = vgetq_lane_u64(*a, 0);
+    }
+}
+
+/// `a ^ b ^ c`
+#[inline]
+#[target_feature(enable = "neon,sha3")]
+unsafe fn eor3(a: uint64x2_t, b: uint64x2_t, c: uint64x2_t) -> uint64x2_t {
+    let mut r = a;
+    // SAFETY: `eor3` is defined only if `sha3` cpu feature is supported
+    core::arch::asm!(
+        "eor3 {r:v}.16b, {r:v}.16b, {x:v}.16b, {y:v}.16b",
+        r = inout(vreg) r,
+        x = in(vreg) b,
+        y = in(vreg) c,
+        options(pure, nomem, nostack),
+    );
+    r
+}
+
+/// `a ^ rol(b, 1)`
+#[inline]
+#[target_feature(enable = "neon,sha3")]
+unsafe fn rax1(a: uint64x2_t, b: uint64x2_t) -> uint64x2_t {
+    let mut r = a;
+    // SAFETY: `rax1` is defined only if `sha3` cpu feature is supported
+    core::arch::asm!(
+        "rax1 {r:v}.2d, {r:v}.2d, {x:v}.2d",
+        r = inout(vreg) r,
+        x = in(vreg) b,
+        options(pure, nomem, nostack),
+    );
+    r
+}
+
+/// `a ^ (b & !c)`
+#[inline]
+#[target_feature(enable = "neon,sha3")]
+unsafe fn bcax(a: uint64x2_t, b: uint64x2_t, c: uint64x2_t) -> uint64x2_t {
+    let mut r = a;
+    // SAFETY: `bcax` is defined only if `sha3` cpu feature is supported
+    core::arch::asm!(
+        "bcax {r:v}.16b, {r:v}.16b, {x:v}.16b, {y:v}.16b",
+        r = inout(vreg) r,
+        x = in(vreg) b,
+        y = in(vreg) c,
+        options(pure, nomem, nostack),
+    );
+    r
+}
@@ -0,0 +1,13 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+use crate::low::aarch64;
+use crate::low::generic;
+
+pub(crate) fn keccak_f1600(state: &mut [u64; 25]) {
+    if aarch64::cpu::have_cpu_feature!("sha3") {
+        aarch64::keccak::keccak_f1600(state)
+    } else {
+        generic::keccak::keccak_f1600(state)
+    }
+}
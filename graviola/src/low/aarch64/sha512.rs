@@ -0,0 +1,264 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+//
+// refs:
+// - https://documentation-service.arm.com/static/65fdad3c1bc22b03bca90781
+//   (sections C7.2.277 to C7.2.280: SHA512H, SHA512H2, SHA512SU0, SHA512SU1)
+//
+// The `sha512*` instructions are available with FEAT_SHA512, which
+// rustc and LLVM call the `sha3` target feature.  The intrinsics for these
+// are newer than our MSRV, so we use inline assembly for the four
+// instructions and intrinsics for everything else.
+
+use crate::low::aarch64::cpu;
+use core::arch::aarch64::*;
+
+pub(in crate::low) fn sha512_compress_blocks(state: &mut [u64; 8], blocks: &[u8]) {
+    debug_assert!(blocks.len() % 128 == 0);
+    // SAFETY: the parent caller checks for the `sha3` cpu feature; this
+    // crate requires the `neon` cpu feature.
+    unsafe { sha512(state, blocks) }
+}
+
+macro_rules! k {
+    ($k:expr) => {
+        vld1q_u64(K.0.as_ptr().add($k))
+    };
+}
+
+/// Two rounds.
+///
+/// Each round pair rotates the roles of the four state registers,
+/// so `$ab` is the register currently holding `a` and `b`, etc.
+macro_rules! round {
+    ($msg:expr, $k:expr, $ab:ident, $cd:ident, $ef:ident, $gh:ident) => {
+        let t0 = vaddq_u64($msg, $k);
+        let t0 = vaddq_u64(vextq_u64(t0, t0, 1), $gh);
+        let t1 = sha512h(t0, vextq_u64($ef, $gh, 1), vextq_u64($cd, $ef, 1));
+        $gh = sha512h2(t1, $cd, $ab);
+        $cd = vaddq_u64($cd, t1);
+    };
+}
+
+/// Message schedule: computes the next two words of W into `$msg0`.
+macro_rules! schedule {
+    ($msg0:ident, $msg1:ident, $msg4:ident, $msg5:ident, $msg7:ident) => {
+        $msg0 = sha512su1(sha512su0($msg0, $msg1), $msg7, vextq_u64($msg4, $msg5, 1));
+    };
+}
+
+#[target_feature(enable = "neon,sha3")]
+unsafe fn sha512(state: &mut [u64; 8], blocks: &[u8]) {
+    let mut ab = vld1q_u64(state[0..2].as_ptr());
+    let mut cd = vld1q_u64(state[2..4].as_ptr());
+    let mut ef = vld1q_u64(state[4..6].as_ptr());
+    let mut gh = vld1q_u64(state[6..8].as_ptr());
+
+    for block in blocks.chunks_exact(128) {
+        let ab_prev = ab;
+        let cd_prev = cd;
+        let ef_prev = ef;
+        let gh_prev = gh;
+
+        // prefetch next block
+        cpu::prefetch_ro(block.as_ptr().add(128));
+
+        let mut msg0 = load_be(&block[0..]);
+        let mut msg1 = load_be(&block[16..]);
+        let mut msg2 = load_be(&block[32..]);
+        let mut msg3 = load_be(&block[48..]);
+        let mut msg4 = load_be(&block[64..]);
+        let mut msg5 = load_be(&block[80..]);
+        let mut msg6 = load_be(&block[96..]);
+        let mut msg7 = load_be(&block[112..]);
+
+        round!(msg0, k!(0), ab, cd, ef, gh);
+        round!(msg1, k!(2), gh, ab, cd, ef);
+        round!(msg2, k!(4), ef, gh, ab, cd);
+        round!(msg3, k!(6), cd, ef, gh, ab);
+        round!(msg4, k!(8), ab, cd, ef, gh);
+        round!(msg5, k!(10), gh, ab, cd, ef);
+        round!(msg6, k!(12), ef, gh, ab, cd);
+        round!(msg7, k!(14), cd, ef, gh, ab);
+
+        for t in (16..80).step_by(16) {
+            schedule!(msg0, msg1, msg4, msg5, msg7);
+            round!(msg0, k!(t), ab, cd, ef, gh);
+            schedule!(msg1, msg2, msg5, msg6, msg0);
+            round!(msg1, k!(t + 2), gh, ab, cd, ef);
+            schedule!(msg2, msg3, msg6, msg7, msg1);
+            round!(msg2, k!(t + 4), ef, gh, ab, cd);
+            schedule!(msg3, msg4, msg7, msg0, msg2);
+            round!(msg3, k!(t + 6), cd, ef, gh, ab);
+            schedule!(msg4, msg5, msg0, msg1, msg3);
+            round!(msg4, k!(t + 8), ab, cd, ef, gh);
+            schedule!(msg5, msg6, msg1, msg2, msg4);
+            round!(msg5, k!(t + 10), gh, ab, cd, ef);
+            schedule!(msg6, msg7, msg2, msg3, msg5);
+            round!(msg6, k!(t + 12), ef, gh, ab, cd);
+            schedule!(msg7, msg0, msg3, msg4, msg6);
+            round!(msg7, k!(t + 14), cd, ef, gh, ab);
+        }
+
+        ab = vaddq_u64(ab, ab_prev);
+        cd = vaddq_u64(cd, cd_prev);
+        ef = vaddq_u64(ef, ef_prev);
+        gh = vaddq_u64(gh, gh_prev);
+    }
+
+    vst1q_u64(state[0..2].as_mut_ptr(), ab);
+    vst1q_u64(state[2..4].as_mut_ptr(), cd);
+    vst1q_u64(state[4..6].as_mut_ptr(), ef);
+    vst1q_u64(state[6..8].as_mut_ptr(), gh);
+}
+
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn load_be(bytes: &[u8]) -> uint64x2_t {
+    vreinterpretq_u64_u8(vrev64q_u8(vld1q_u8(bytes.as_ptr())))
+}
+
+#[inline]
+#[target_feature(enable = "neon,sha3")]
+unsafe fn sha512h(hash: uint64x2_t, x: uint64x2_t, y: uint64x2_t) -> uint64x2_t {
+    let mut r = hash;
+    // SAFETY: `sha512h` is defined only if `sha3` cpu feature is supported
+    core::arch::asm!(
+        "sha512h {r:q}, {x:q}, {y:v}.2d",
+        r = inout(vreg) r,
+        x = in(vreg) x,
+        y = in(vreg) y,
+        options(pure, nomem, nostack),
+    );
+    r
+}
+
+#[inline]
+#[target_feature(enable = "neon,sha3")]
+unsafe fn sha512h2(hash: uint64x2_t, x: uint64x2_t, y: uint64x2_t) -> uint64x2_t {
+    let mut r = hash;
+    // SAFETY: `sha512h2` is defined only if `sha3` cpu feature is supported
+    core::arch::asm!(
+        "sha512h2 {r:q}, {x:q}, {y:v}.2d",
+        r = inout(vreg) r,
+        x = in(vreg) x,
+        y = in(vreg) y,
+        options(pure, nomem, nostack),
+    );
+    r
+}
+
+#[inline]
+#[target_feature(enable = "neon,sha3")]
+unsafe fn sha512su0(w0_1: uint64x2_t, w2_3: uint64x2_t) -> uint64x2_t {
+    let mut r = w0_1;
+    // SAFETY: `sha512su0` is defined only if `sha3` cpu feature is supported
+    core::arch::asm!(
+        "sha512su0 {r:v}.2d, {x:v}.2d",
+        r = inout(vreg) r,
+        x = in(vreg) w2_3,
+        options(pure, nomem, nostack),
+    );
+    r
+}
+
+#[inline]
+#[target_feature(enable = "neon,sha3")]
+unsafe fn sha512su1(s: uint64x2_t, w14_15: uint64x2_t, w9_10: uint64x2_t) -> uint64x2_t {
+    let mut r = s;
+    // SAFETY: `sha512su1` is defined only if `sha3` cpu feature is supported
+    core::arch::asm!(
+        "sha512su1 {r:v}.2d, {x:v}.2d, {y:v}.2d",
+        r = inout(vreg) r,
+        x = in(vreg) w14_15,
+        y = in(vreg) w9_10,
+        options(pure, nomem, nostack),
+    );
+    r
+}
+
+#[repr(align(16))]
+struct Aligned([u64; 80]);
+
+static K: Aligned = Aligned([
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+]);
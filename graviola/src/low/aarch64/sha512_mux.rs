@@ -0,0 +1,14 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+use crate::low::aarch64;
+use crate::low::generic;
+
+pub(crate) fn sha512_compress_blocks(state: &mut [u64; 8], blocks: &[u8]) {
+    // nb. FEAT_SHA512 is called `sha3` by rustc.
+    if aarch64::cpu::have_cpu_feature!("sha3") {
+        aarch64::sha512::sha512_compress_blocks(state, blocks)
+    } else {
+        generic::sha512::sha512_compress_blocks(state, blocks)
+    }
+}
@@ -16,8 +16,16 @@ impl Entry {
     /// Must be called at top-level crate entry points for public functions.
     ///
     /// Public functions have no secret data in their arguments or return values.
+    #[cfg(any(
+        feature = "p256",
+        feature = "p384",
+        feature = "rsa",
+        feature = "x25519"
+    ))]
     pub(crate) fn new_public() -> Self {
         verify_cpu_features();
+        #[cfg(feature = "self-test-on-first-use")]
+        crate::high::self_test::on_first_use();
 
         Self {
             secret: false,
@@ -31,6 +39,8 @@ impl Entry {
     /// (directly, or transitively).
     pub(crate) fn new_secret() -> Self {
         verify_cpu_features();
+        #[cfg(feature = "self-test-on-first-use")]
+        crate::high::self_test::on_first_use();
         let cpu_state = enter_cpu_state();
 
         Self {
@@ -0,0 +1,385 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+//
+// References:
+// - https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.197-upd1.pdf
+
+use crate::low::zeroise;
+
+/// A portable AES implementation, for targets without SIMD registers.
+///
+/// This does not use lookup tables: the S-box is computed as an
+/// inversion in GF(2^8) followed by the affine transform, for eight
+/// bytes at a time.  This makes it constant-time, but slow.
+pub(crate) struct AesKey {
+    round_keys: [[u8; 16]; 15],
+    rounds: usize,
+}
+
+impl AesKey {
+    /// Creates an AesKey.
+    ///
+    /// `key` must be 16 or 32 bytes in length (AES-192 not supported).
+    pub(crate) fn new(key: &[u8]) -> Self {
+        match key.len() {
+            16 | 32 => {}
+            24 => panic!("aes-192 not supported"),
+            _ => panic!("invalid aes key size"),
+        }
+
+        let nk = key.len() / 4;
+        let rounds = nk + 6;
+
+        let mut w = [[0u8; 4]; 60];
+        for (w, k) in w.iter_mut().zip(key.chunks_exact(4)) {
+            w.copy_from_slice(k);
+        }
+
+        let mut rcon = 0x01;
+        for i in nk..4 * (rounds + 1) {
+            let mut t = w[i - 1];
+            if i % nk == 0 {
+                t = sub_word([t[1], t[2], t[3], t[0]]);
+                t[0] ^= rcon;
+                rcon = xtime(rcon);
+            } else if nk > 6 && i % nk == 4 {
+                t = sub_word(t);
+            }
+
+            for j in 0..4 {
+                w[i][j] = w[i - nk][j] ^ t[j];
+            }
+        }
+
+        let mut round_keys = [[0u8; 16]; 15];
+        for (rk, w) in round_keys.iter_mut().zip(w.chunks_exact(4)) {
+            for (rk, w) in rk.chunks_exact_mut(4).zip(w) {
+                rk.copy_from_slice(w);
+            }
+        }
+        zeroise(&mut w);
+
+        Self { round_keys, rounds }
+    }
+
+    pub(crate) fn encrypt_block(&self, inout: &mut [u8]) {
+        debug_assert_eq!(inout.len(), 16);
+
+        let mut state = [0u8; 16];
+        state.copy_from_slice(inout);
+
+        add_round_key(&mut state, &self.round_keys[0]);
+        for rk in &self.round_keys[1..self.rounds] {
+            sub_bytes(&mut state);
+            shift_rows(&mut state);
+            mix_columns(&mut state);
+            add_round_key(&mut state, rk);
+        }
+        sub_bytes(&mut state);
+        shift_rows(&mut state);
+        add_round_key(&mut state, &self.round_keys[self.rounds]);
+
+        inout.copy_from_slice(&state);
+        zeroise(&mut state);
+    }
+
+    /// Encrypts or decrypts `cipher_inout` in CTR mode.
+    ///
+    /// The first block uses `initial_counter` incremented by one;
+    /// the counter is the final 32 bits, big-endian.
+    #[cfg(feature = "aes-gcm")]
+    pub(crate) fn ctr(&self, initial_counter: &[u8; 16], cipher_inout: &mut [u8]) {
+        let mut counter = *initial_counter;
+        let mut block = [0u8; 16];
+
+        for chunk in cipher_inout.chunks_mut(16) {
+            let ctr = u32::from_be_bytes(counter[12..].try_into().unwrap()).wrapping_add(1);
+            counter[12..].copy_from_slice(&ctr.to_be_bytes());
+
+            block = counter;
+            self.encrypt_block(&mut block);
+            for (c, k) in chunk.iter_mut().zip(block.iter()) {
+                *c ^= *k;
+            }
+        }
+
+        zeroise(&mut block);
+    }
+}
+
+impl Drop for AesKey {
+    fn drop(&mut self) {
+        zeroise(&mut self.round_keys);
+    }
+}
+
+// decryption is only needed for encrypted PKCS#8 private keys.
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        test,
+        all(
+            feature = "pbes2",
+            any(feature = "p256", feature = "p384", feature = "rsa")
+        )
+    ))] {
+        impl AesKey {
+            pub(crate) fn decrypt_block(&self, inout: &mut [u8]) {
+                debug_assert_eq!(inout.len(), 16);
+
+                let mut state = [0u8; 16];
+                state.copy_from_slice(inout);
+
+                add_round_key(&mut state, &self.round_keys[self.rounds]);
+                for rk in self.round_keys[1..self.rounds].iter().rev() {
+                    inv_shift_rows(&mut state);
+                    inv_sub_bytes(&mut state);
+                    add_round_key(&mut state, rk);
+                    inv_mix_columns(&mut state);
+                }
+                inv_shift_rows(&mut state);
+                inv_sub_bytes(&mut state);
+                add_round_key(&mut state, &self.round_keys[0]);
+
+                inout.copy_from_slice(&state);
+                zeroise(&mut state);
+            }
+        }
+
+        fn inv_sub_bytes(state: &mut [u8; 16]) {
+            for half in state.chunks_exact_mut(8) {
+                let x = u64::from_le_bytes(half.try_into().unwrap());
+                half.copy_from_slice(&inv_sbox_x8(x).to_le_bytes());
+            }
+        }
+
+        fn inv_shift_rows(state: &mut [u8; 16]) {
+            let old = *state;
+            for c in 0..4 {
+                for r in 0..4 {
+                    state[r + 4 * ((c + r) % 4)] = old[r + 4 * c];
+                }
+            }
+        }
+
+        /// `InvMixColumns` is `MixColumns` after multiplying each column
+        /// by `4x^2 + 5`.
+        fn inv_mix_columns(state: &mut [u8; 16]) {
+            for col in state.chunks_exact_mut(4) {
+                let u = xtime(xtime(col[0] ^ col[2]));
+                let v = xtime(xtime(col[1] ^ col[3]));
+                col[0] ^= u;
+                col[1] ^= v;
+                col[2] ^= u;
+                col[3] ^= v;
+            }
+            mix_columns(state);
+        }
+
+        /// The inverse AES S-box, applied to each byte of `x`.
+        fn inv_sbox_x8(x: u64) -> u64 {
+            let b = rotl_x8(x, 1) ^ rotl_x8(x, 3) ^ rotl_x8(x, 6) ^ splat(0x05);
+            gf_inv_x8(b)
+        }
+    }
+}
+
+fn add_round_key(state: &mut [u8; 16], rk: &[u8; 16]) {
+    for (s, k) in state.iter_mut().zip(rk.iter()) {
+        *s ^= *k;
+    }
+}
+
+fn sub_bytes(state: &mut [u8; 16]) {
+    for half in state.chunks_exact_mut(8) {
+        let x = u64::from_le_bytes(half.try_into().unwrap());
+        half.copy_from_slice(&sbox_x8(x).to_le_bytes());
+    }
+}
+
+fn sub_word(w: [u8; 4]) -> [u8; 4] {
+    let x = u32::from_le_bytes(w) as u64;
+    let y = sbox_x8(x) as u32;
+    y.to_le_bytes()
+}
+
+fn shift_rows(state: &mut [u8; 16]) {
+    let old = *state;
+    for c in 0..4 {
+        for r in 0..4 {
+            state[r + 4 * c] = old[r + 4 * ((c + r) % 4)];
+        }
+    }
+}
+
+fn mix_columns(state: &mut [u8; 16]) {
+    for col in state.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [col[0], col[1], col[2], col[3]];
+        let t = a0 ^ a1 ^ a2 ^ a3;
+        col[0] = a0 ^ t ^ xtime(a0 ^ a1);
+        col[1] = a1 ^ t ^ xtime(a1 ^ a2);
+        col[2] = a2 ^ t ^ xtime(a2 ^ a3);
+        col[3] = a3 ^ t ^ xtime(a3 ^ a0);
+    }
+}
+
+/// Multiplication by `x` in GF(2^8).
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ ((b >> 7) * 0x1b)
+}
+
+/// The AES S-box, applied to each byte of `x`.
+fn sbox_x8(x: u64) -> u64 {
+    let b = gf_inv_x8(x);
+    b ^ rotl_x8(b, 1) ^ rotl_x8(b, 2) ^ rotl_x8(b, 3) ^ rotl_x8(b, 4) ^ splat(0x63)
+}
+
+/// Inversion in GF(2^8), of each byte of `x`.
+fn gf_inv_x8(x: u64) -> u64 {
+    // x^254 is the multiplicative inverse of x (and maps 0 to 0)
+    let x2 = gf_mul_x8(x, x);
+    let x3 = gf_mul_x8(x2, x);
+    let x6 = gf_mul_x8(x3, x3);
+    let x7 = gf_mul_x8(x6, x);
+    let x14 = gf_mul_x8(x7, x7);
+    let x15 = gf_mul_x8(x14, x);
+    let x30 = gf_mul_x8(x15, x15);
+    let x31 = gf_mul_x8(x30, x);
+    let x62 = gf_mul_x8(x31, x31);
+    let x63 = gf_mul_x8(x62, x);
+    let x126 = gf_mul_x8(x63, x63);
+    let x127 = gf_mul_x8(x126, x);
+    gf_mul_x8(x127, x127)
+}
+
+/// Multiplication in GF(2^8), of each byte of `a` and `b`.
+fn gf_mul_x8(a: u64, b: u64) -> u64 {
+    let mut a = a;
+    let mut r = 0;
+    for i in 0..8 {
+        let mask = ((b >> i) & splat(0x01)) * 0xff;
+        r ^= a & mask;
+        a = xtime_x8(a);
+    }
+    r
+}
+
+/// Multiplication by `x` in GF(2^8), of each byte of `a`.
+fn xtime_x8(a: u64) -> u64 {
+    ((a & splat(0x7f)) << 1) ^ (((a >> 7) & splat(0x01)) * 0x1b)
+}
+
+/// Left-rotation of each byte of `a`.
+fn rotl_x8(a: u64, n: u32) -> u64 {
+    ((a << n) & splat(0xff << n)) | ((a >> (8 - n)) & splat(0xff >> (8 - n)))
+}
+
+const fn splat(b: u8) -> u64 {
+    b as u64 * 0x01010101_01010101
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sbox() {
+        // FIPS-197 table 4
+        let mut bytes = [0u8; 8];
+        for (i, expect) in [
+            (0x00, 0x63),
+            (0x01, 0x7c),
+            (0x53, 0xed),
+            (0x80, 0xcd),
+            (0xc9, 0xdd),
+            (0xff, 0x16),
+        ] {
+            bytes[0] = i;
+            bytes[7] = i;
+            let out = sbox_x8(u64::from_le_bytes(bytes)).to_le_bytes();
+            assert_eq!(out[0], expect);
+            assert_eq!(out[7], expect);
+
+            let inv = inv_sbox_x8(u64::from_le_bytes(out)).to_le_bytes();
+            assert_eq!(inv[0], i);
+            assert_eq!(inv[7], i);
+        }
+    }
+
+    // these test vectors from FIPS-197 appendix C.
+
+    #[test]
+    fn test_block_128() {
+        let key = AesKey::new(b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f");
+        let mut block = *b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff";
+        key.encrypt_block(&mut block);
+        assert_eq!(
+            &block,
+            b"\x69\xc4\xe0\xd8\x6a\x7b\x04\x30\xd8\xcd\xb7\x80\x70\xb4\xc5\x5a"
+        );
+        key.decrypt_block(&mut block);
+        assert_eq!(
+            &block,
+            b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff"
+        );
+    }
+
+    #[test]
+    fn test_block_256() {
+        let key = AesKey::new(
+            b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f\
+              \x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1a\x1b\x1c\x1d\x1e\x1f",
+        );
+        let mut block = *b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff";
+        key.encrypt_block(&mut block);
+        assert_eq!(
+            &block,
+            b"\x8e\xa2\xb7\xca\x51\x67\x45\xbf\xea\xfc\x49\x90\x4b\x49\x60\x89"
+        );
+        key.decrypt_block(&mut block);
+        assert_eq!(
+            &block,
+            b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff"
+        );
+    }
+
+    #[test]
+    fn pairwise() {
+        let mut block = [0u8; 16];
+        for key_len in [16, 32] {
+            let key_bytes = (0..key_len).map(|i| i as u8 ^ 0xa5).collect::<Vec<_>>();
+            let ours = AesKey::new(&key_bytes);
+            let theirs = crate::low::AesKey::new(&key_bytes);
+
+            for i in 0..64u8 {
+                block[usize::from(i % 16)] ^= i.wrapping_mul(0x35);
+                let mut expect = block;
+                theirs.encrypt_block(&mut expect);
+                ours.encrypt_block(&mut block);
+                assert_eq!(block, expect);
+
+                theirs.decrypt_block(&mut expect);
+                ours.decrypt_block(&mut block);
+                assert_eq!(block, expect);
+            }
+        }
+    }
+
+    #[cfg(feature = "aes-gcm")]
+    #[test]
+    fn ctr() {
+        let key = AesKey::new(&[0x11; 16]);
+        let counter = *b"\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\xff\xff\xff\xfe";
+        let mut data = [0u8; 40];
+        key.ctr(&counter, &mut data);
+
+        // counter wraps in its final 32 bits
+        let mut expect = [0u8; 48];
+        for (i, ctr) in [0xffff_ffffu32, 0, 1].into_iter().enumerate() {
+            let block = &mut expect[i * 16..(i + 1) * 16];
+            block[..12].copy_from_slice(&counter[..12]);
+            block[12..].copy_from_slice(&ctr.to_be_bytes());
+            key.encrypt_block(block);
+        }
+        assert_eq!(&data[..], &expect[..40]);
+    }
+}
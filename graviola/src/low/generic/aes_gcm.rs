@@ -0,0 +1,74 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! The model version of AES-GCM, without any stitching of
+//! the GHASH and AES computations.
+
+use super::aes::AesKey;
+use super::ghash::Ghash;
+
+pub(crate) fn encrypt(
+    key: &AesKey,
+    ghash: &mut Ghash<'_>,
+    initial_counter: &[u8; 16],
+    aad: &[u8],
+    cipher_inout: &mut [u8],
+) {
+    ghash.add(aad);
+    key.ctr(initial_counter, cipher_inout);
+    ghash.add(cipher_inout);
+}
+
+pub(crate) fn decrypt(
+    key: &AesKey,
+    ghash: &mut Ghash<'_>,
+    initial_counter: &[u8; 16],
+    aad: &[u8],
+    cipher_inout: &mut [u8],
+) {
+    ghash.add(aad);
+    ghash.add(cipher_inout);
+    key.ctr(initial_counter, cipher_inout);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::low::ghash as platform_ghash;
+
+    #[test]
+    fn pairwise() {
+        let key_bytes = [0x5a; 32];
+        let key = AesKey::new(&key_bytes);
+        let table = super::super::ghash::GhashTable::new(0x0123_4567_89ab_cdef);
+        let platform_key = crate::low::AesKey::new(&key_bytes);
+        let platform_table = platform_ghash::GhashTable::new(0x0123_4567_89ab_cdef);
+
+        let counter = [0x33; 16];
+        let aad = b"additional data";
+        let mut ours = [0u8; 300];
+        let mut theirs = [0u8; 300];
+
+        for len in [0, 1, 15, 16, 17, 128, 129, 300] {
+            ours.fill(0);
+            theirs.fill(0);
+
+            let mut ghash = Ghash::new(&table);
+            encrypt(&key, &mut ghash, &counter, aad, &mut ours[..len]);
+            let mut platform = platform_ghash::Ghash::new(&platform_table);
+            crate::low::aes_gcm::encrypt(
+                &platform_key,
+                &mut platform,
+                &counter,
+                aad,
+                &mut theirs[..len],
+            );
+            assert_eq!(ours, theirs);
+            assert_eq!(ghash.into_bytes(), platform.into_bytes());
+
+            let mut ghash = Ghash::new(&table);
+            decrypt(&key, &mut ghash, &counter, aad, &mut ours[..len]);
+            assert_eq!(ours, [0u8; 300]);
+        }
+    }
+}
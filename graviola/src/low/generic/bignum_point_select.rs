@@ -0,0 +1,76 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+/// Viewing table as rows of 8 words width, copy the 8 words at
+/// table[idx - 1] into z.  If `idx` is zero or larger than `height`,
+/// `z` is set to zero (ie, the affine point at infinity).
+#[cfg(feature = "p256")]
+pub(crate) fn bignum_aff_point_select_p256(z: &mut [u64; 8], table: &[u64], index: u8) {
+    select(z, table, index)
+}
+
+/// Viewing table as rows of 12 words width, copy the 12 words at
+/// table[idx - 1] into z.  If `idx` is zero or larger than `height`,
+/// `z` is set to zero (ie, a jacobian point at infinity).
+#[cfg(feature = "p256")]
+pub(crate) fn bignum_jac_point_select_p256(z: &mut [u64; 12], table: &[u64], index: u8) {
+    select(z, table, index)
+}
+
+/// Viewing table as rows of 18 words width, copy the 18 words at
+/// table[idx - 1] into z.  If `idx` is zero or larger than `height`,
+/// `z` is set to zero (ie, a jacobian point at infinity).
+#[cfg(feature = "p384")]
+pub(crate) fn bignum_jac_point_select_p384(z: &mut [u64; 18], table: &[u64], index: u8) {
+    select(z, table, index)
+}
+
+fn select<const W: usize>(z: &mut [u64; W], table: &[u64], index: u8) {
+    z.fill(0);
+
+    for (i, row) in table.chunks_exact(W).enumerate() {
+        // all-ones if i + 1 == index, else zero
+        let diff = (i as u64 + 1) ^ u64::from(index);
+        let mask = ((diff | diff.wrapping_neg()) >> 63).wrapping_sub(1);
+
+        for (z, r) in z.iter_mut().zip(row.iter()) {
+            *z |= *r & mask;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairwise() {
+        let table = (0..18 * 17).map(|i| i as u64 * 0x0101).collect::<Vec<_>>();
+
+        for index in 0..=18 {
+            #[cfg(feature = "p256")]
+            {
+                let mut ours = [0xff; 8];
+                let mut theirs = [0xff; 8];
+                bignum_aff_point_select_p256(&mut ours, &table[..8 * 16], index);
+                crate::low::bignum_aff_point_select_p256(&mut theirs, &table[..8 * 16], index);
+                assert_eq!(ours, theirs);
+
+                let mut ours = [0xff; 12];
+                let mut theirs = [0xff; 12];
+                bignum_jac_point_select_p256(&mut ours, &table[..12 * 16], index);
+                crate::low::bignum_jac_point_select_p256(&mut theirs, &table[..12 * 16], index);
+                assert_eq!(ours, theirs);
+            }
+
+            #[cfg(feature = "p384")]
+            {
+                let mut ours = [0xff; 18];
+                let mut theirs = [0xff; 18];
+                bignum_jac_point_select_p384(&mut ours, &table[..18 * 16], index);
+                crate::low::bignum_jac_point_select_p384(&mut theirs, &table[..18 * 16], index);
+                assert_eq!(ours, theirs);
+            }
+        }
+    }
+}
@@ -15,6 +15,7 @@ impl<const N: usize> Blockwise<N> {
         }
     }
 
+    #[cfg(any(feature = "sha1", feature = "sha256", feature = "sha512"))]
     pub(crate) const fn used(&self) -> usize {
         self.used
     }
@@ -41,6 +42,7 @@ impl<const N: usize> Blockwise<N> {
         }
     }
 
+    #[cfg(feature = "chacha20poly1305")]
     pub(crate) fn peek_remaining(&self) -> Option<&[u8]> {
         if self.used > 0 {
             Some(&self.buffer[..self.used])
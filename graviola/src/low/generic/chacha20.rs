@@ -39,6 +39,14 @@ impl ChaCha20 {
     }
 }
 
+impl Drop for ChaCha20 {
+    fn drop(&mut self) {
+        crate::low::zeroise(&mut self.key0);
+        crate::low::zeroise(&mut self.key1);
+        crate::low::zeroise(&mut self.nonce);
+    }
+}
+
 fn core(key0: &[u32; 4], key1: &[u32; 4], nonce: &[u32; 4], out: &mut [u8; 64]) {
     let [mut z0, mut z1, mut z2, mut z3] = SIGMA;
     let [mut z4, mut z5, mut z6, mut z7] = key0;
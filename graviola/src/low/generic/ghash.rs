@@ -3,8 +3,8 @@
 
 /// An extremely slow, by-the-book implementation.
 ///
-/// Useful as a test model for faster implementations.
-
+/// Useful as a test model for faster implementations, and
+/// used on targets without SIMD registers.
 pub(crate) struct GhashTable {
     h: u128,
 }
@@ -15,6 +15,12 @@ impl GhashTable {
     }
 }
 
+impl Drop for GhashTable {
+    fn drop(&mut self) {
+        crate::low::zeroise_value(&mut self.h);
+    }
+}
+
 pub(crate) struct Ghash<'a> {
     table: &'a GhashTable,
     current: u128,
@@ -0,0 +1,61 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+fn sha1_compress_block(state: &mut [u32; 5], block: &[u8]) {
+    let mut a = state[0];
+    let mut b = state[1];
+    let mut c = state[2];
+    let mut d = state[3];
+    let mut e = state[4];
+
+    // This is a 16-word window into the whole W array.
+    let mut w: [u32; 16] = [0; 16];
+
+    for t in 0..80 {
+        // W[t] = ROTL1(W[t - 3] ^ W[t - 8] ^ W[t - 14] ^ W[t - 16]),
+        // with indices reduced mod 16 into our window.
+        let w_t = if t < 16 {
+            let w_t = u32::from_be_bytes(block[t * 4..(t + 1) * 4].try_into().unwrap());
+            w[t] = w_t;
+            w_t
+        } else {
+            let w_t = (w[(t - 3) % 16] ^ w[(t - 8) % 16] ^ w[(t - 14) % 16] ^ w[(t - 16) % 16])
+                .rotate_left(1);
+            w[t % 16] = w_t;
+            w_t
+        };
+
+        let (f, k) = match t {
+            0..=19 => ((b & c) | (!b & d), 0x5a827999),
+            20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+            _ => (b ^ c ^ d, 0xca62c1d6),
+        };
+
+        let tmp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(w_t);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = tmp;
+    }
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+    state[4] = state[4].wrapping_add(e);
+}
+
+pub(crate) fn sha1_compress_blocks(state: &mut [u32; 5], blocks: &[u8]) {
+    debug_assert!(blocks.len() % 64 == 0);
+
+    for block in blocks.chunks_exact(64) {
+        sha1_compress_block(state, block);
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
 use crate::low::zero_bytes;
-use core::mem::{size_of, size_of_val};
+use core::mem::size_of_val;
 
 /// Writes zeroes over the whole of the `v` slice.
 pub(crate) fn zeroise<T: Zeroable>(v: &mut [T]) {
@@ -10,16 +10,20 @@ pub(crate) fn zeroise<T: Zeroable>(v: &mut [T]) {
 }
 
 /// Writes zeroes over the whole of the `v` value.
+#[cfg_attr(not(feature = "rsa"), allow(dead_code))]
 pub(crate) fn zeroise_value<T: Zeroable>(v: &mut T) {
-    zero_bytes(v as *mut T as *mut _, size_of::<T>());
+    zeroise(core::slice::from_mut(v));
 }
 
 /// Marker trait for types who have valid all-bits-zero values.
 pub(crate) trait Zeroable {}
 
 impl Zeroable for u8 {}
+impl Zeroable for u32 {}
 impl Zeroable for u64 {}
+impl Zeroable for u128 {}
 impl Zeroable for usize {}
+impl<T: Zeroable, const N: usize> Zeroable for [T; N] {}
 
 #[cfg(target_arch = "x86_64")]
 impl Zeroable for core::arch::x86_64::__m256i {}
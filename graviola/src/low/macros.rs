@@ -5,12 +5,14 @@
 /// and feeds them into concat!() to form a single string
 ///
 /// named after perl's q operator.
+#[allow(unused_macros)]
 macro_rules! Q {
     ($($e:expr)*) => {
         concat!($($e ,)*)
     };
 }
 
+#[allow(unused_imports)]
 pub(crate) use Q;
 
 /// Label macro, which just resolves to the id as a string,
@@ -19,6 +21,7 @@ pub(crate) use Q;
 /// See the warnings about labels in the rust guide, this
 /// means we use local labels without compromising so much
 /// on readability.
+#[allow(unused_macros)]
 macro_rules! Label {
     // declaration form
     ($name:literal, $id:literal) => {
@@ -36,6 +39,7 @@ macro_rules! Label {
     }
 }
 
+#[allow(unused_imports)]
 pub(crate) use Label;
 
 /// Plasters over the difference between ELF and Mach-O relocation
@@ -8,27 +8,84 @@ mod macros;
 pub mod inline_assembly_safety;
 
 mod generic {
+    #[cfg(all(
+        feature = "aes",
+        any(test, all(target_arch = "x86_64", not(target_feature = "sse2")))
+    ))]
+    pub(crate) mod aes;
+    #[cfg(all(
+        feature = "aes-gcm",
+        any(test, all(target_arch = "x86_64", not(target_feature = "sse2")))
+    ))]
+    pub(crate) mod aes_gcm;
+    #[cfg(all(
+        any(feature = "p256", feature = "p384"),
+        any(test, all(target_arch = "x86_64", not(target_feature = "sse2")))
+    ))]
+    pub(crate) mod bignum_point_select;
+    #[cfg(feature = "blake2")]
+    pub(super) mod blake2;
+    #[cfg(feature = "blake3")]
+    pub(crate) mod blake3;
+    #[cfg(any(
+        feature = "chacha20poly1305",
+        feature = "sha1",
+        feature = "sha256",
+        feature = "sha512"
+    ))]
     pub(super) mod blockwise;
-    #[cfg(target_arch = "aarch64")]
+    #[cfg(all(
+        any(feature = "chacha20poly1305", feature = "std"),
+        any(
+            target_arch = "aarch64",
+            all(target_arch = "x86_64", not(target_feature = "sse2"))
+        )
+    ))]
     pub(crate) mod chacha20;
     pub(super) mod ct_equal;
-    #[cfg(test)]
+    #[cfg(all(
+        feature = "aes-gcm",
+        any(test, all(target_arch = "x86_64", not(target_feature = "sse2")))
+    ))]
     pub(crate) mod ghash;
+    #[cfg(feature = "sha3")]
+    pub(super) mod keccak;
+    #[cfg(feature = "chacha20poly1305")]
     pub(crate) mod poly1305;
+    #[cfg(all(feature = "sha1", target_arch = "x86_64"))]
+    pub(super) mod sha1;
+    #[cfg(all(feature = "sha256", target_arch = "x86_64"))]
     pub(super) mod sha256;
+    #[cfg(feature = "sha512")]
     pub(super) mod sha512;
     pub(super) mod zeroise;
 }
 
+#[cfg(feature = "_entry")]
 mod entry;
+#[cfg(feature = "rsa")]
 mod posint;
+#[cfg(feature = "std")]
+mod wipe_on_fork;
 
+#[cfg(feature = "_entry")]
 pub(crate) use entry::Entry;
+#[cfg(feature = "blake2")]
+pub(crate) use generic::blake2::{blake2b_compress, blake2s_compress, BLAKE2B_IV, BLAKE2S_IV};
+#[cfg(feature = "blake3")]
+pub(crate) use generic::blake3;
+#[cfg(any(feature = "sha1", feature = "sha256", feature = "sha512"))]
 pub(crate) use generic::blockwise::Blockwise;
 pub(crate) use generic::ct_equal::ct_equal;
+#[cfg(feature = "chacha20poly1305")]
 pub(crate) use generic::poly1305;
-pub(crate) use generic::zeroise::{zeroise, zeroise_value};
+pub(crate) use generic::zeroise::zeroise;
+#[cfg_attr(not(feature = "rsa"), allow(unused_imports))]
+pub(crate) use generic::zeroise::zeroise_value;
+#[cfg(feature = "rsa")]
 pub(crate) use posint::{PosInt, SecretPosInt};
+#[cfg(feature = "std")]
+pub(crate) use wipe_on_fork::WipeOnFork;
 
 #[cfg(test)]
 mod tests;
@@ -37,122 +94,275 @@ cfg_if::cfg_if! {
     if #[cfg(target_arch = "x86_64")] {
         mod x86_64;
 
-        pub(in crate::low) use x86_64::cpu::{enter_cpu_state, zero_bytes, leave_cpu_state, verify_cpu_features};
-        pub(crate) use x86_64::chacha20;
-        pub(crate) use x86_64::aes::AesKey;
-        pub(crate) use x86_64::aes_gcm;
+        pub(in crate::low) use x86_64::cpu::zero_bytes;
+        #[cfg(feature = "_entry")]
+        pub(in crate::low) use x86_64::cpu::{enter_cpu_state, leave_cpu_state, verify_cpu_features};
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_add::bignum_add;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::bignum_add_p256::bignum_add_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::bignum_add_p384::bignum_add_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_bitsize::bignum_bitsize;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_cmp_lt::bignum_cmp_lt;
-        pub(crate) use x86_64::bignum_copy_row_from_table_mux::bignum_copy_row_from_table;
+        #[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
         pub(crate) use x86_64::bignum_demont::bignum_demont;
-        pub(crate) use x86_64::bignum_point_select_p256::{bignum_aff_point_select_p256, bignum_jac_point_select_p256};
-        pub(crate) use x86_64::bignum_point_select_p384::bignum_jac_point_select_p384;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::bignum_demont_p256::bignum_demont_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::bignum_demont_p384::bignum_demont_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_digitsize::bignum_digitsize;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_emontredc_8n::bignum_emontredc_8n;
+        #[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
         pub(crate) use x86_64::bignum_eq::bignum_eq;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::bignum_inv_p256::bignum_inv_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::bignum_inv_p384::bignum_inv_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_kmul_16_32::bignum_kmul_16_32;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_kmul_32_64::bignum_kmul_32_64;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_ksqr_16_32::bignum_ksqr_16_32;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_ksqr_32_64::bignum_ksqr_32_64;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::bignum_mod_n256::bignum_mod_n256;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::bignum_mod_n384::bignum_mod_n384;
+        #[cfg(any(feature = "p256", feature = "p384"))]
         pub(crate) use x86_64::bignum_modadd::bignum_modadd;
+        #[cfg(any(feature = "p256", feature = "p384"))]
         pub(crate) use x86_64::bignum_modinv::bignum_modinv;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_modsub::bignum_modsub;
+        #[cfg(any(feature = "rsa", all(test, any(feature = "p256", feature = "p384"))))]
         pub(crate) use x86_64::bignum_montifier::bignum_montifier;
+        #[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
         pub(crate) use x86_64::bignum_montmul::bignum_montmul;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::bignum_montmul_p256::bignum_montmul_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::bignum_montmul_p384::bignum_montmul_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_montredc::bignum_montredc;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_montsqr::bignum_montsqr;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::bignum_montsqr_p256::bignum_montsqr_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::bignum_montsqr_p384::bignum_montsqr_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_mul::bignum_mul;
+        #[cfg(any(feature = "p256", feature = "p384"))]
         pub(crate) use x86_64::bignum_mux::bignum_mux;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::bignum_neg_p256::bignum_neg_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::bignum_neg_p384::bignum_neg_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_negmodinv::bignum_negmodinv;
+        #[cfg(feature = "rsa")]
         pub(crate) use x86_64::bignum_optsub::bignum_optsub;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::bignum_tomont_p256::bignum_tomont_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::bignum_tomont_p384::bignum_tomont_p384;
+        #[cfg(feature = "x25519")]
         pub(crate) use x86_64::curve25519_x25519::curve25519_x25519;
+        #[cfg(all(feature = "x25519", not(feature = "runtime-tables")))]
         pub(crate) use x86_64::curve25519_x25519base::curve25519_x25519base;
-        pub(crate) use x86_64::ghash;
+        #[cfg(feature = "sha3")]
+        pub(crate) use generic::keccak::keccak_f1600;
         pub(crate) use x86_64::optimise_barrier::optimise_barrier_u8;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::p256_montjadd::p256_montjadd;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::p256_montjdouble::p256_montjdouble;
+        #[cfg(feature = "p256")]
         pub(crate) use x86_64::p256_montjmixadd::p256_montjmixadd;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::p384_montjadd::p384_montjadd;
+        #[cfg(feature = "p384")]
         pub(crate) use x86_64::p384_montjdouble::p384_montjdouble;
-        pub(crate) use x86_64::sha256_mux::sha256_compress_blocks;
-        pub(crate) use x86_64::sha512_mux::sha512_compress_blocks;
+
+        // targets without simd registers (eg. `x86_64-unknown-none`) use generic code
+        cfg_if::cfg_if! {
+            if #[cfg(target_feature = "sse2")] {
+                #[cfg(feature = "blake3")]
+                pub(crate) use x86_64::blake3::blake3_hash_chunks;
+                #[cfg(any(feature = "chacha20poly1305", feature = "std"))]
+                pub(crate) use x86_64::chacha20;
+                #[cfg(feature = "aes")]
+                pub(crate) use x86_64::aes::AesKey;
+                #[cfg(feature = "aes-gcm")]
+                pub(crate) use x86_64::aes_gcm;
+                #[cfg(feature = "rsa")]
+                pub(crate) use x86_64::bignum_copy_row_from_table_mux::bignum_copy_row_from_table;
+                #[cfg(feature = "p256")]
+                pub(crate) use x86_64::bignum_point_select_p256::{bignum_aff_point_select_p256, bignum_jac_point_select_p256};
+                #[cfg(feature = "p384")]
+                pub(crate) use x86_64::bignum_point_select_p384::bignum_jac_point_select_p384;
+                #[cfg(feature = "aes-gcm")]
+                pub(crate) use x86_64::ghash;
+                #[cfg(feature = "sha1")]
+                pub(crate) use x86_64::sha1_mux::sha1_compress_blocks;
+                #[cfg(feature = "sha256")]
+                pub(crate) use x86_64::sha256_mux::sha256_compress_blocks;
+                #[cfg(feature = "sha512")]
+                pub(crate) use x86_64::sha512_mux::sha512_compress_blocks;
+            } else {
+                #[cfg(feature = "aes")]
+                pub(crate) use generic::aes::AesKey;
+                #[cfg(feature = "aes-gcm")]
+                pub(crate) use generic::aes_gcm;
+                #[cfg(feature = "blake3")]
+                pub(crate) use generic::blake3::blake3_hash_chunks;
+                #[cfg(feature = "p256")]
+                pub(crate) use generic::bignum_point_select::{bignum_aff_point_select_p256, bignum_jac_point_select_p256};
+                #[cfg(feature = "p384")]
+                pub(crate) use generic::bignum_point_select::bignum_jac_point_select_p384;
+                #[cfg(any(feature = "chacha20poly1305", feature = "std"))]
+                pub(crate) use generic::chacha20;
+                #[cfg(feature = "aes-gcm")]
+                pub(crate) use generic::ghash;
+                #[cfg(feature = "sha1")]
+                pub(crate) use generic::sha1::sha1_compress_blocks;
+                #[cfg(feature = "sha256")]
+                pub(crate) use generic::sha256::sha256_compress_blocks;
+                #[cfg(feature = "sha512")]
+                pub(crate) use generic::sha512::sha512_compress_blocks;
+                #[cfg(feature = "rsa")]
+                pub(crate) use x86_64::bignum_copy_row_from_table::bignum_copy_row_from_table;
+            }
+        }
     } else if #[cfg(target_arch = "aarch64")] {
         mod aarch64;
 
-        pub(in crate::low) use aarch64::cpu::{enter_cpu_state, zero_bytes, leave_cpu_state, verify_cpu_features};
+        pub(in crate::low) use aarch64::cpu::zero_bytes;
+        #[cfg(feature = "_entry")]
+        pub(in crate::low) use aarch64::cpu::{enter_cpu_state, leave_cpu_state, verify_cpu_features};
+        #[cfg(feature = "aes")]
         pub(crate) use aarch64::aes::AesKey;
+        #[cfg(feature = "aes-gcm")]
         pub(crate) use aarch64::aes_gcm;
+        #[cfg(feature = "blake3")]
+        pub(crate) use aarch64::blake3::blake3_hash_chunks;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_add::bignum_add;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::bignum_add_p256::bignum_add_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::bignum_add_p384::bignum_add_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_bitsize::bignum_bitsize;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_cmp_lt::bignum_cmp_lt;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_copy_row_from_table_mux::bignum_copy_row_from_table;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::bignum_point_select_p256::{bignum_aff_point_select_p256, bignum_jac_point_select_p256};
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::bignum_point_select_p384::bignum_jac_point_select_p384;
+        #[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
         pub(crate) use aarch64::bignum_demont::bignum_demont;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::bignum_demont_p256::bignum_demont_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::bignum_demont_p384::bignum_demont_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_digitsize::bignum_digitsize;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_emontredc_8n::bignum_emontredc_8n;
+        #[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
         pub(crate) use aarch64::bignum_eq::bignum_eq;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::bignum_inv_p256::bignum_inv_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::bignum_inv_p384::bignum_inv_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_kmul_16_32::bignum_kmul_16_32;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_kmul_32_64::bignum_kmul_32_64;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_ksqr_16_32::bignum_ksqr_16_32;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_ksqr_32_64::bignum_ksqr_32_64;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::bignum_mod_n256::bignum_mod_n256;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::bignum_mod_n384::bignum_mod_n384;
+        #[cfg(any(feature = "p256", feature = "p384"))]
         pub(crate) use aarch64::bignum_modadd::bignum_modadd;
+        #[cfg(any(feature = "p256", feature = "p384"))]
         pub(crate) use aarch64::bignum_modinv::bignum_modinv;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_modsub::bignum_modsub;
+        #[cfg(any(feature = "rsa", all(test, any(feature = "p256", feature = "p384"))))]
         pub(crate) use aarch64::bignum_montifier::bignum_montifier;
+        #[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
         pub(crate) use aarch64::bignum_montmul::bignum_montmul;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::bignum_montmul_p256::bignum_montmul_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::bignum_montmul_p384::bignum_montmul_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_montredc::bignum_montredc;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_montsqr::bignum_montsqr;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::bignum_montsqr_p256::bignum_montsqr_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::bignum_montsqr_p384::bignum_montsqr_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_mul::bignum_mul;
+        #[cfg(any(feature = "p256", feature = "p384"))]
         pub(crate) use aarch64::bignum_mux::bignum_mux;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::bignum_neg_p256::bignum_neg_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::bignum_neg_p384::bignum_neg_p384;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_negmodinv::bignum_negmodinv;
+        #[cfg(feature = "rsa")]
         pub(crate) use aarch64::bignum_optsub::bignum_optsub;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::bignum_tomont_p256::bignum_tomont_p256;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::bignum_tomont_p384::bignum_tomont_p384;
+        #[cfg(feature = "x25519")]
         pub(crate) use aarch64::curve25519_x25519::curve25519_x25519;
+        #[cfg(all(feature = "x25519", not(feature = "runtime-tables")))]
         pub(crate) use aarch64::curve25519_x25519base::curve25519_x25519base;
+        #[cfg(feature = "aes-gcm")]
         pub(crate) use aarch64::ghash;
+        #[cfg(feature = "sha3")]
+        pub(crate) use aarch64::keccak_mux::keccak_f1600;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::p256_montjadd::p256_montjadd;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::p256_montjdouble::p256_montjdouble;
+        #[cfg(feature = "p256")]
         pub(crate) use aarch64::p256_montjmixadd::p256_montjmixadd;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::p384_montjadd::p384_montjadd;
+        #[cfg(feature = "p384")]
         pub(crate) use aarch64::p384_montjdouble::p384_montjdouble;
+        #[cfg(feature = "sha1")]
+        pub(crate) use aarch64::sha1::sha1_compress_blocks;
+        #[cfg(feature = "sha256")]
         pub(crate) use aarch64::sha256::sha256_compress_blocks;
+        #[cfg(feature = "sha512")]
+        pub(crate) use aarch64::sha512_mux::sha512_compress_blocks;
         pub(crate) use aarch64::optimise_barrier::optimise_barrier_u8;
 
+        #[cfg(any(feature = "chacha20poly1305", feature = "std"))]
         pub(crate) use generic::chacha20;
-        pub(crate) use generic::sha512::sha512_compress_blocks;
     } else {
         compile_error!("This crate only supports x86_64 or aarch64");
     }
@@ -103,6 +103,7 @@ impl<const N: usize> PosInt<N> {
         Ok(out)
     }
 
+    #[cfg(feature = "std")]
     #[allow(dead_code)]
     pub(crate) fn debug(&self, why: &str) {
         let mut bytes = [0u8; 512];
@@ -353,63 +354,72 @@ impl<const N: usize> PosInt<N> {
         // the window size also evenly divides our 64-bit word size, so there is no
         // need to have a degenerate case at the end of the loop
         //
-        // put this on the heap, so only n.used * 16 entries are needed (rather than
-        // N * 16, which is not possible to express with half-finished const generics)
-
-        let mut table = Vec::with_capacity(n.used * 16);
+        // entries are packed at a stride of n.used words, so only the first
+        // n.used * 16 words of the table are used.  (`[u64; N * 16]` is not
+        // possible to express with half-finished const generics.)
+        let mut table_storage = [[0u64; N]; 16];
+        // SAFETY: `[[u64; N]; 16]` has the same layout as `[u64; N * 16]`.
+        let table = unsafe {
+            core::slice::from_raw_parts_mut(table_storage.as_mut_ptr().cast::<u64>(), N * 16)
+        };
+        let mut table_len = 0;
+        let mut push = |words: &[u64]| {
+            table[table_len..table_len + words.len()].copy_from_slice(words);
+            table_len += words.len();
+        };
 
         // 0: identity in montgomery domain
-        table.extend_from_slice(accum.as_words());
+        push(accum.as_words());
 
         // 1: self
         let t1 = self.to_montgomery(n_montifier, n);
-        table.extend_from_slice(t1.as_words());
+        push(t1.as_words());
 
         // 2: self ^ 2
         let t2 = t1.mont_sqr(n, n_0);
-        table.extend_from_slice(t2.as_words());
+        push(t2.as_words());
 
         // 3: self ^ 2 * self
         let t3 = t2.mont_mul(&t1, n, n_0);
-        table.extend_from_slice(t3.as_words());
+        push(t3.as_words());
 
         // 4: self ^ 2 ^ 2
         let t4 = t2.mont_sqr(n, n_0);
-        table.extend_from_slice(t4.as_words());
+        push(t4.as_words());
 
         // (and so on)
         let t5 = t4.mont_mul(&t1, n, n_0);
-        table.extend_from_slice(t5.as_words());
+        push(t5.as_words());
 
         let t6 = t3.mont_sqr(n, n_0);
-        table.extend_from_slice(t6.as_words());
+        push(t6.as_words());
 
         let t7 = t6.mont_mul(&t1, n, n_0);
-        table.extend_from_slice(t7.as_words());
+        push(t7.as_words());
 
         let t8 = t4.mont_sqr(n, n_0);
-        table.extend_from_slice(t8.as_words());
+        push(t8.as_words());
 
         let t9 = t8.mont_mul(&t1, n, n_0);
-        table.extend_from_slice(t9.as_words());
+        push(t9.as_words());
 
         let t10 = t5.mont_sqr(n, n_0);
-        table.extend_from_slice(t10.as_words());
+        push(t10.as_words());
 
         let t11 = t10.mont_mul(&t1, n, n_0);
-        table.extend_from_slice(t11.as_words());
+        push(t11.as_words());
 
         let t12 = t6.mont_sqr(n, n_0);
-        table.extend_from_slice(t12.as_words());
+        push(t12.as_words());
 
         let t13 = t12.mont_mul(&t1, n, n_0);
-        table.extend_from_slice(t13.as_words());
+        push(t13.as_words());
 
         let t14 = t7.mont_sqr(n, n_0);
-        table.extend_from_slice(t14.as_words());
+        push(t14.as_words());
 
         let t15 = t14.mont_mul(&t1, n, n_0);
-        table.extend_from_slice(t15.as_words());
+        push(t15.as_words());
 
         let mut first = true;
         let mut wcount = 0;
@@ -439,7 +449,7 @@ impl<const N: usize> PosInt<N> {
                 // silent.
                 low::bignum_copy_row_from_table(
                     term.as_mut_words(),
-                    table.as_slice(),
+                    &table[..table_len],
                     16,
                     n.used as u64,
                     window,
@@ -454,7 +464,7 @@ impl<const N: usize> PosInt<N> {
             }
         }
 
-        low::zeroise(&mut table);
+        low::zeroise(table);
         accum.from_montgomery(n)
     }
 
@@ -621,7 +631,7 @@ mod tests {
     #[test]
     fn to_bytes() {
         let mut buf = [0xff; 8];
-        assert_eq!(PosInt::<2>::zero().to_bytes(&mut buf).unwrap(), &[]);
+        assert_eq!(PosInt::<2>::zero().to_bytes(&mut buf).unwrap(), &[0u8; 0]);
 
         let all_bits_set = PosInt::<2>::from_bytes(&[0xff; 16]).unwrap();
         assert_eq!(
@@ -1,6 +1,7 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
+#[cfg(any(feature = "p256", feature = "p384"))]
 fn bignum_mux_equiv(p: u64, x_if_p: &[u64], y_if_not_p: &[u64]) {
     let mut model_z = vec![0; x_if_p.len()];
     let mut real_z = vec![0; x_if_p.len()];
@@ -9,6 +10,7 @@ fn bignum_mux_equiv(p: u64, x_if_p: &[u64], y_if_not_p: &[u64]) {
     assert_eq!(model_z, real_z);
 }
 
+#[cfg(any(feature = "p256", feature = "p384"))]
 #[test]
 fn bignum_mux() {
     bignum_mux_equiv(0, &[0u64; 4], &[1u64; 4]);
@@ -30,6 +32,7 @@ fn zeroise_equiv(len: usize) {
     assert_eq!(expect, bytes);
 }
 
+#[cfg(any(feature = "p256", feature = "p384"))]
 mod model {
     pub(super) fn bignum_mux(p: u64, z: &mut [u64], x_if_p: &[u64], y_if_not_p: &[u64]) {
         if p > 0 {
@@ -0,0 +1,126 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+/// A flag which reads as clear in a child process after `fork()`.
+///
+/// On Linux (since 4.14) this is a page of memory marked with
+/// `MADV_WIPEONFORK`, so the kernel supplies a child process
+/// with a zeroed copy.  Elsewhere [`WipeOnFork::new()`] returns
+/// `None`.
+pub(crate) struct WipeOnFork {
+    #[cfg(target_os = "linux")]
+    page: core::ptr::NonNull<u8>,
+}
+
+#[cfg(target_os = "linux")]
+impl WipeOnFork {
+    /// Make a new flag, which is initially set.
+    ///
+    /// Returns `None` if `MADV_WIPEONFORK` is not supported.
+    pub(crate) fn new() -> Option<Self> {
+        // SAFETY: anonymous mapping, with no requested address.
+        let ptr = unsafe {
+            libc::mmap(
+                core::ptr::null_mut(),
+                PAGE_LEN,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+
+        if ptr == libc::MAP_FAILED {
+            return None;
+        }
+
+        let r = Self {
+            page: core::ptr::NonNull::new(ptr.cast())?,
+        };
+
+        // SAFETY: `ptr` is the valid mapping made above.
+        match unsafe { libc::madvise(ptr, PAGE_LEN, libc::MADV_WIPEONFORK) } {
+            0 => {
+                r.set();
+                Some(r)
+            }
+            // nb. `r` is dropped, unmapping the page.
+            _ => None,
+        }
+    }
+
+    /// Returns true if this process is a child which was forked
+    /// since the last call to [`WipeOnFork::set()`].
+    pub(crate) fn is_clear(&self) -> bool {
+        // SAFETY: `page` is a valid mapping for the lifetime of `self`.
+        unsafe { core::ptr::read_volatile(self.page.as_ptr()) == 0 }
+    }
+
+    /// Set the flag.
+    pub(crate) fn set(&self) {
+        // SAFETY: `page` is a valid mapping for the lifetime of `self`.
+        unsafe { core::ptr::write_volatile(self.page.as_ptr(), 1) }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for WipeOnFork {
+    fn drop(&mut self) {
+        // SAFETY: `page` is the mapping made in `new()`, and is no longer used.
+        unsafe {
+            libc::munmap(self.page.as_ptr().cast(), PAGE_LEN);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+const PAGE_LEN: usize = 4096;
+
+#[cfg(not(target_os = "linux"))]
+impl WipeOnFork {
+    pub(crate) fn new() -> Option<Self> {
+        None
+    }
+
+    pub(crate) fn is_clear(&self) -> bool {
+        unreachable!()
+    }
+
+    pub(crate) fn set(&self) {
+        unreachable!()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleared_in_child() {
+        let flag = WipeOnFork::new().expect("MADV_WIPEONFORK not supported");
+        assert!(!flag.is_clear());
+
+        // SAFETY: the child only reads memory and exits.
+        match unsafe { libc::fork() } {
+            0 => {
+                let status = match flag.is_clear() {
+                    true => 0,
+                    false => 1,
+                };
+                // SAFETY: the child must not run destructors or test harness code.
+                unsafe { libc::_exit(status) }
+            }
+            -1 => panic!("fork failed"),
+            child => {
+                let mut status = 0;
+                // SAFETY: `child` is our child process.
+                assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+                assert!(libc::WIFEXITED(status));
+                assert_eq!(libc::WEXITSTATUS(status), 0);
+            }
+        }
+
+        // not cleared in the parent
+        assert!(!flag.is_clear());
+    }
+}
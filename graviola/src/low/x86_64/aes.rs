@@ -36,6 +36,16 @@ impl AesKey {
         }
     }
 
+    /// Returns all the round keys, including the first and last.
+    #[cfg(any(test, feature = "aes-gcm"))]
+    pub(crate) fn round_keys_all(&self) -> &[__m128i] {
+        match self {
+            Self::Aes128(a128) => &a128.round_keys,
+            Self::Aes256(a256) => &a256.round_keys,
+        }
+    }
+
+    #[cfg(feature = "aes-gcm")]
     pub(crate) fn round_keys(&self) -> (__m128i, &[__m128i], __m128i) {
         match self {
             Self::Aes128(a128) => (
@@ -253,12 +263,51 @@ unsafe fn aes256_block(round_keys: &[__m128i; 15], block_inout: &mut [u8]) {
     _mm_storeu_si128(block_inout.as_mut_ptr() as *mut _, block);
 }
 
+// decryption is only needed for encrypted PKCS#8 private keys.
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        test,
+        all(
+            feature = "pbes2",
+            any(feature = "p256", feature = "p384", feature = "rsa")
+        )
+    ))] {
+        impl AesKey {
+            pub(crate) fn decrypt_block(&self, inout: &mut [u8]) {
+                debug_assert_eq!(inout.len(), 16);
+
+                // SAFETY: this crate requires the `aes` & `avx` cpu features
+                unsafe { aes_decrypt_block(self.round_keys_all(), inout) }
+            }
+        }
+
+        /// Decrypts one block, using the "equivalent inverse cipher" (FIPS-197 section 5.3.5).
+        ///
+        /// The decryption round keys are derived from the encryption ones
+        /// as we go, rather than stored.
+        #[target_feature(enable = "aes,avx")]
+        unsafe fn aes_decrypt_block(round_keys: &[__m128i], block_inout: &mut [u8]) {
+            let (first, rest) = round_keys.split_first().unwrap();
+            let (last, middle) = rest.split_last().unwrap();
+
+            let mut block = _mm_lddqu_si128(block_inout.as_ptr() as *const _);
+            block = _mm_xor_si128(block, *last);
+            for rk in middle.iter().rev() {
+                block = _mm_aesdec_si128(block, _mm_aesimc_si128(*rk));
+            }
+            block = _mm_aesdeclast_si128(block, *first);
+            _mm_storeu_si128(block_inout.as_mut_ptr() as *mut _, block);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn to_u128(v: __m128i) -> u128 {
         let mut u = 0;
+        // SAFETY: sizeof(u128) == sizeof(__m128i), all bits have same meaning
         unsafe {
             _mm_store_si128(&mut u as *mut u128 as *mut _, v);
         }
@@ -342,6 +391,15 @@ mod tests {
                 0xc5, 0x5a
             ]
         );
+
+        AesKey::Aes128(context).decrypt_block(&mut block);
+        assert_eq!(
+            block,
+            [
+                0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+                0xee, 0xff,
+            ]
+        );
     }
 
     #[test]
@@ -363,5 +421,14 @@ mod tests {
                 0x60, 0x89
             ]
         );
+
+        AesKey::Aes256(context).decrypt_block(&mut block);
+        assert_eq!(
+            block,
+            [
+                0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+                0xee, 0xff,
+            ]
+        );
     }
 }
@@ -7,6 +7,7 @@ use core::arch::x86_64::*;
 use core::mem;
 
 use super::aes::AesKey;
+use super::aes_gcm_vaes;
 use super::ghash::{self, Ghash};
 
 pub(crate) fn encrypt(
@@ -44,6 +45,10 @@ unsafe fn _cipher<const ENC: bool>(
     let (rk_first, rks, rk_last) = key.round_keys();
 
     let mut counter = Counter::new(initial_counter);
+
+    // use wider registers, where available
+    let cipher_inout = aes_gcm_vaes::cipher::<ENC>(key, ghash, &mut counter, cipher_inout);
+
     let mut by8_iter = cipher_inout.chunks_exact_mut(128);
 
     for blocks in by8_iter.by_ref() {
@@ -192,7 +197,7 @@ unsafe fn _cipher<const ENC: bool>(
 
 /// This stores the next counter value, in big endian.
 #[derive(Clone, Copy, Debug)]
-struct Counter(__m128i);
+pub(super) struct Counter(pub(super) __m128i);
 
 impl Counter {
     fn new(bytes: &[u8; 16]) -> Self {
@@ -212,10 +217,21 @@ impl Counter {
             _mm_shuffle_epi8(self.0, BYTESWAP_EPI64)
         }
     }
+
+    /// Advance the counter by `n` blocks, without producing them.
+    #[inline]
+    pub(super) fn skip(&mut self, n: usize) {
+        // SAFETY: this crate requires the `avx` feature
+        unsafe {
+            self.0 = _mm_add_epi32(self.0, _mm_set_epi32(0, n as i32, 0, 0));
+        }
+    }
 }
 
 // SAFETY: both u128 and __m128i have the same size and all bits mean the same thing
 const COUNTER_1: __m128i = unsafe { mem::transmute(1u128 << 64) };
+// SAFETY: both u128 and __m128i have the same size and all bits mean the same thing
 const BYTESWAP: __m128i = unsafe { mem::transmute(0x00010203_04050607_08090a0b_0c0d0e0fu128) };
 const BYTESWAP_EPI64: __m128i =
+    // SAFETY: both u128 and __m128i have the same size and all bits mean the same thing
     unsafe { mem::transmute(0x08090a0b_0c0d0e0f_00010203_04050607u128) };
@@ -0,0 +1,425 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+//
+//! AES-GCM using VAES and VPCLMULQDQ, on 256- or 512-bit registers.
+//!
+//! Refs.
+//! - <https://www.intel.com/content/www/us/en/content-details/812204/intel-advanced-vector-extensions-10-intel-avx10-architecture-specification.html>
+//! - <https://lore.kernel.org/linux-crypto/20240602222221.176625-1-ebiggers@kernel.org/>
+//!
+//! This processes four registers' worth of blocks at a time: 8 blocks for
+//! ymm, 16 blocks for zmm.  GHASH uses the same arithmetic as `ghash`, but
+//! uses schoolbook multiplication for the middle term (so we don't need
+//! the `powers_xor` table at each width).
+//!
+//! The VAES/VPCLMULQDQ intrinsics and the `avx512*` target features are
+//! newer than our MSRV, so this is written in inline assembly.
+//!
+//! Only vector registers 0-15 are used here.  That means `vzeroall` in
+//! `leave_cpu_state` clears any secret values left in these registers,
+//! including in the upper halves of zmm0-15.
+
+use core::arch::x86_64::*;
+
+use super::aes::AesKey;
+use super::aes_gcm::Counter;
+use super::ghash::Ghash;
+use crate::low::x86_64::cpu::have_cpu_feature;
+
+/// Process as much of `cipher_inout` as possible using wide registers.
+///
+/// This does nothing if the CPU does not support VAES and VPCLMULQDQ.
+///
+/// Returns the unprocessed remainder of `cipher_inout`.
+pub(super) fn cipher<'a, const ENC: bool>(
+    key: &AesKey,
+    ghash: &mut Ghash<'_>,
+    counter: &mut Counter,
+    cipher_inout: &'a mut [u8],
+) -> &'a mut [u8] {
+    if !(have_cpu_feature!("vaes") && have_cpu_feature!("vpclmulqdq")) {
+        return cipher_inout;
+    }
+
+    let by16 = have_cpu_feature!("avx512f") && have_cpu_feature!("avx512bw");
+    let chunk_len = if by16 { 256 } else { 128 };
+    let chunks = cipher_inout.len() / chunk_len;
+
+    if chunks == 0 {
+        return cipher_inout;
+    }
+
+    // SAFETY: we checked for the `vaes` and `vpclmulqdq` cpu features, and
+    // (for `cipher_zmm`) `avx512f` and `avx512bw`. this crate requires the
+    // `avx` cpu feature.  `cipher_inout` contains at least `chunks * chunk_len`
+    // bytes.
+    unsafe {
+        if by16 {
+            cipher_zmm::<ENC>(key, ghash, counter, cipher_inout.as_mut_ptr(), chunks);
+        } else {
+            cipher_ymm::<ENC>(key, ghash, counter, cipher_inout.as_mut_ptr(), chunks);
+        }
+    }
+
+    counter.skip(chunks * chunk_len / 16);
+    &mut cipher_inout[chunks * chunk_len..]
+}
+
+/// Register name `$n` at width `$w`.
+macro_rules! R {
+    ($w:tt, $n:literal) => {
+        concat!(stringify!($w), $n)
+    };
+}
+
+/// Register width in bytes.
+macro_rules! W {
+    (ymm) => {
+        "32"
+    };
+    (zmm) => {
+        "64"
+    };
+}
+
+macro_rules! VPXOR {
+    (ymm) => {
+        "vpxor"
+    };
+    (zmm) => {
+        "vpxord"
+    };
+}
+
+macro_rules! VMOVDQU {
+    (ymm) => {
+        "vmovdqu"
+    };
+    (zmm) => {
+        "vmovdqu64"
+    };
+}
+
+macro_rules! VBROADCAST {
+    (ymm) => {
+        "vbroadcasti128"
+    };
+    (zmm) => {
+        "vbroadcasti32x4"
+    };
+}
+
+/// Offset in `Constants` of the counter increment for one register
+/// (2 blocks for ymm, 4 blocks for zmm)
+macro_rules! INC_OFFSET {
+    (ymm) => {
+        "96"
+    };
+    (zmm) => {
+        "112"
+    };
+}
+
+/// XORs each 128-bit lane of registers 8, 9 and 10 into their bottom lane.
+macro_rules! FOLD_LANES {
+    (ymm) => {
+        Q!(
+            "vextracti128 xmm11, ymm8, 1\n"
+            "vpxor xmm8, xmm8, xmm11\n"
+            "vextracti128 xmm11, ymm9, 1\n"
+            "vpxor xmm9, xmm9, xmm11\n"
+            "vextracti128 xmm11, ymm10, 1\n"
+            "vpxor xmm10, xmm10, xmm11\n"
+        )
+    };
+    (zmm) => {
+        Q!(
+            "vextracti64x4 ymm11, zmm8, 1\n"
+            "vpxor ymm8, ymm8, ymm11\n"
+            "vextracti64x4 ymm11, zmm9, 1\n"
+            "vpxor ymm9, ymm9, ymm11\n"
+            "vextracti64x4 ymm11, zmm10, 1\n"
+            "vpxor ymm10, ymm10, ymm11\n"
+            FOLD_LANES!(ymm)
+        )
+    };
+}
+
+/// Multiplies register 4 (holding byte-reversed blocks) by the powers of H
+/// at `{tbl} + $j` registers, and accumulates into registers 8 (lo), 9 (mid)
+/// and 10 (hi).
+macro_rules! MUL_ACCUMULATE {
+    ($w:tt, $j:literal) => {
+        Q!(
+            "vpclmulqdq " R!($w, 11) ", " R!($w, 4) ", [{tbl} + " $j "*" W!($w) "], 0x00\n"
+            VPXOR!($w) " " R!($w, 8) ", " R!($w, 8) ", " R!($w, 11) "\n"
+            "vpclmulqdq " R!($w, 11) ", " R!($w, 4) ", [{tbl} + " $j "*" W!($w) "], 0x11\n"
+            VPXOR!($w) " " R!($w, 10) ", " R!($w, 10) ", " R!($w, 11) "\n"
+            "vpclmulqdq " R!($w, 11) ", " R!($w, 4) ", [{tbl} + " $j "*" W!($w) "], 0x01\n"
+            VPXOR!($w) " " R!($w, 9) ", " R!($w, 9) ", " R!($w, 11) "\n"
+            "vpclmulqdq " R!($w, 11) ", " R!($w, 4) ", [{tbl} + " $j "*" W!($w) "], 0x10\n"
+            VPXOR!($w) " " R!($w, 9) ", " R!($w, 9) ", " R!($w, 11) "\n"
+        )
+    };
+}
+
+/// Loads register `$j` of blocks from `{buf}` into register 4, and
+/// byte-reverses each block.
+macro_rules! LOAD_BLOCKS {
+    ($w:tt, $j:literal) => {
+        Q!(
+            VMOVDQU!($w) " " R!($w, 4) ", [{buf} + " $j "*" W!($w) "]\n"
+            "vpshufb " R!($w, 4) ", " R!($w, 4) ", " R!($w, 6) "\n"
+        )
+    };
+}
+
+/// GHASH over the four registers of blocks at `{buf}`.
+///
+/// The accumulator is in xmm14 (with the upper lanes zero).
+macro_rules! GHASH_X4 {
+    ($w:tt) => {
+        Q!(
+            LOAD_BLOCKS!($w, "0")
+            VPXOR!($w) " " R!($w, 4) ", " R!($w, 4) ", " R!($w, 14) "\n"
+            "vpclmulqdq " R!($w, 8) ", " R!($w, 4) ", [{tbl}], 0x00\n"
+            "vpclmulqdq " R!($w, 10) ", " R!($w, 4) ", [{tbl}], 0x11\n"
+            "vpclmulqdq " R!($w, 9) ", " R!($w, 4) ", [{tbl}], 0x01\n"
+            "vpclmulqdq " R!($w, 11) ", " R!($w, 4) ", [{tbl}], 0x10\n"
+            VPXOR!($w) " " R!($w, 9) ", " R!($w, 9) ", " R!($w, 11) "\n"
+            LOAD_BLOCKS!($w, "1")
+            MUL_ACCUMULATE!($w, "1")
+            LOAD_BLOCKS!($w, "2")
+            MUL_ACCUMULATE!($w, "2")
+            LOAD_BLOCKS!($w, "3")
+            MUL_ACCUMULATE!($w, "3")
+            FOLD_LANES!($w)
+            // reduction: cf. `ghash::reduce!`, but our middle term
+            // does not need the Karatsuba fixup
+            "vpshufd xmm11, xmm8, 0x4e\n"
+            "vpclmulqdq xmm8, xmm8, [{k} + 128], 0x00\n"
+            "vpxor xmm9, xmm9, xmm11\n"
+            "vpxor xmm9, xmm9, xmm8\n"
+            "vpshufd xmm11, xmm9, 0x4e\n"
+            "vpclmulqdq xmm9, xmm9, [{k} + 128], 0x00\n"
+            "vpxor xmm10, xmm10, xmm11\n"
+            "vpxor xmm14, xmm10, xmm9\n"
+        )
+    };
+}
+
+/// For decryption, GHASH happens on the input before it is overwritten.
+macro_rules! GHASH_BEFORE {
+    (true, $w:tt) => {
+        ""
+    };
+    (false, $w:tt) => {
+        GHASH_X4!($w)
+    };
+}
+
+/// For encryption, GHASH happens on the output.
+macro_rules! GHASH_AFTER {
+    (true, $w:tt) => {
+        GHASH_X4!($w)
+    };
+    (false, $w:tt) => {
+        ""
+    };
+}
+
+macro_rules! AES_X4 {
+    ($w:tt, $insn:expr) => {
+        Q!(
+            $insn " " R!($w, 0) ", " R!($w, 0) ", " R!($w, 15) "\n"
+            $insn " " R!($w, 1) ", " R!($w, 1) ", " R!($w, 15) "\n"
+            $insn " " R!($w, 2) ", " R!($w, 2) ", " R!($w, 15) "\n"
+            $insn " " R!($w, 3) ", " R!($w, 3) ", " R!($w, 15) "\n"
+        )
+    };
+}
+
+/// Encrypt/decrypt `$chunks` lots of four registers of blocks at `$buf`, in place.
+///
+/// Register usage:
+///
+/// - 0-3: counter blocks/keystream/output
+/// - 4: GHASH input
+/// - 5: `byteswap_epi64` constant
+/// - 6: `byteswap` constant
+/// - 8, 9, 10: GHASH lo, mid, hi product terms
+/// - 11: temporary
+/// - 12: next counter values (in `byteswap_epi64` form)
+/// - 13: counter increment
+/// - 14: GHASH accumulator
+/// - 15: current round key
+macro_rules! cipher_wide {
+    ($w:tt, $enc:tt, $round_keys:expr, $table:expr, $current:expr, $counter:expr, $buf:expr, $chunks:expr) => {
+        core::arch::asm!(
+            Q!(VBROADCAST!($w) " " R!($w, 5) ", [{k} + 80]"),
+            Q!(VBROADCAST!($w) " " R!($w, 6) ", [{k} + 64]"),
+            Q!(VBROADCAST!($w) " " R!($w, 13) ", [{k} + " INC_OFFSET!($w) "]"),
+            Q!(VBROADCAST!($w) " " R!($w, 12) ", [{ctr}]"),
+            Q!("vpaddd " R!($w, 12) ", " R!($w, 12) ", [{k}]"),
+            Q!("vmovdqu xmm14, [{acc}]"),
+
+            Q!("2:"),
+            GHASH_BEFORE!($enc, $w),
+
+            // make counter blocks
+            Q!("vpshufb " R!($w, 0) ", " R!($w, 12) ", " R!($w, 5)),
+            Q!("vpaddd " R!($w, 12) ", " R!($w, 12) ", " R!($w, 13)),
+            Q!("vpshufb " R!($w, 1) ", " R!($w, 12) ", " R!($w, 5)),
+            Q!("vpaddd " R!($w, 12) ", " R!($w, 12) ", " R!($w, 13)),
+            Q!("vpshufb " R!($w, 2) ", " R!($w, 12) ", " R!($w, 5)),
+            Q!("vpaddd " R!($w, 12) ", " R!($w, 12) ", " R!($w, 13)),
+            Q!("vpshufb " R!($w, 3) ", " R!($w, 12) ", " R!($w, 5)),
+            Q!("vpaddd " R!($w, 12) ", " R!($w, 12) ", " R!($w, 13)),
+
+            // encrypt them
+            Q!(VBROADCAST!($w) " " R!($w, 15) ", [{rk}]"),
+            AES_X4!($w, VPXOR!($w)),
+            Q!("lea {rkp}, [{rk} + 16]"),
+            Q!("mov {n}, {rounds}"),
+            Q!("3:"),
+            Q!(VBROADCAST!($w) " " R!($w, 15) ", [{rkp}]"),
+            AES_X4!($w, "vaesenc"),
+            Q!("add {rkp}, 16"),
+            Q!("dec {n}"),
+            Q!("jnz 3b"),
+            Q!(VBROADCAST!($w) " " R!($w, 15) ", [{rkp}]"),
+            AES_X4!($w, "vaesenclast"),
+
+            // xor with input, and write output
+            Q!(VPXOR!($w) " " R!($w, 0) ", " R!($w, 0) ", [{buf}]"),
+            Q!(VPXOR!($w) " " R!($w, 1) ", " R!($w, 1) ", [{buf} + 1*" W!($w) "]"),
+            Q!(VPXOR!($w) " " R!($w, 2) ", " R!($w, 2) ", [{buf} + 2*" W!($w) "]"),
+            Q!(VPXOR!($w) " " R!($w, 3) ", " R!($w, 3) ", [{buf} + 3*" W!($w) "]"),
+            Q!(VMOVDQU!($w) " [{buf}], " R!($w, 0)),
+            Q!(VMOVDQU!($w) " [{buf} + 1*" W!($w) "], " R!($w, 1)),
+            Q!(VMOVDQU!($w) " [{buf} + 2*" W!($w) "], " R!($w, 2)),
+            Q!(VMOVDQU!($w) " [{buf} + 3*" W!($w) "], " R!($w, 3)),
+
+            GHASH_AFTER!($enc, $w),
+
+            Q!("add {buf}, 4*" W!($w)),
+            Q!("dec {chunks}"),
+            Q!("jnz 2b"),
+
+            Q!("vmovdqu [{acc}], xmm14"),
+            Q!("vzeroupper"),
+
+            buf = inout(reg) $buf => _,
+            chunks = inout(reg) $chunks => _,
+            rk = in(reg) $round_keys.as_ptr(),
+            rounds = in(reg) $round_keys.len() - 2,
+            tbl = in(reg) $table.as_ptr(),
+            k = in(reg) &CONSTANTS,
+            ctr = in(reg) $counter,
+            acc = in(reg) $current,
+            rkp = out(reg) _,
+            n = out(reg) _,
+
+            // clobbers.  for zmm, these also cover the upper halves of
+            // zmm0-15: LLVM treats xmmN, ymmN and zmmN as one register.
+            // (naming zmm registers here would need the `avx512f` target
+            // feature, which is newer than our MSRV.)
+            out("ymm0") _,
+            out("ymm1") _,
+            out("ymm2") _,
+            out("ymm3") _,
+            out("ymm4") _,
+            out("ymm5") _,
+            out("ymm6") _,
+            out("ymm7") _,
+            out("ymm8") _,
+            out("ymm9") _,
+            out("ymm10") _,
+            out("ymm11") _,
+            out("ymm12") _,
+            out("ymm13") _,
+            out("ymm14") _,
+            out("ymm15") _,
+            options(nostack),
+        )
+    };
+}
+
+#[target_feature(enable = "avx,avx2")]
+unsafe fn cipher_ymm<const ENC: bool>(
+    key: &AesKey,
+    ghash: &mut Ghash<'_>,
+    counter: &Counter,
+    buf: *mut u8,
+    chunks: usize,
+) {
+    let round_keys = key.round_keys_all();
+    // H^8 .. H^1
+    let table = &ghash.table.powers_wide[8..];
+    let current: *mut __m128i = &mut ghash.current;
+    let counter: *const __m128i = &counter.0;
+
+    // SAFETY: caller ensures `vaes` and `vpclmulqdq` cpu features are available,
+    // and that there are `chunks * 128` bytes at `buf`.
+    if ENC {
+        cipher_wide!(ymm, true, round_keys, table, current, counter, buf, chunks);
+    } else {
+        cipher_wide!(ymm, false, round_keys, table, current, counter, buf, chunks);
+    }
+}
+
+/// As `cipher_ymm`, but with zmm registers.
+///
+/// This cannot enable the `avx512f` and `avx512bw` target features (they
+/// are newer than our MSRV), so must not contain any code that needs them
+/// other than the assembly in `cipher_wide`.
+#[target_feature(enable = "avx,avx2")]
+unsafe fn cipher_zmm<const ENC: bool>(
+    key: &AesKey,
+    ghash: &mut Ghash<'_>,
+    counter: &Counter,
+    buf: *mut u8,
+    chunks: usize,
+) {
+    let round_keys = key.round_keys_all();
+    // H^16 .. H^1
+    let table = &ghash.table.powers_wide[..];
+    let current: *mut __m128i = &mut ghash.current;
+    let counter: *const __m128i = &counter.0;
+
+    // SAFETY: caller ensures `vaes`, `vpclmulqdq`, `avx512f` and `avx512bw`
+    // cpu features are available, and that there are `chunks * 256` bytes at `buf`.
+    if ENC {
+        cipher_wide!(zmm, true, round_keys, table, current, counter, buf, chunks);
+    } else {
+        cipher_wide!(zmm, false, round_keys, table, current, counter, buf, chunks);
+    }
+}
+
+/// Constants used by `cipher_wide`.
+///
+/// The assembly refers to these by offset, so take care when changing this.
+#[repr(C, align(64))]
+struct Constants {
+    /// Offset 0: added to the (broadcast) counter to give each lane's counter.
+    lane_offsets: [u128; 4],
+    /// Offset 64: see `aes_gcm::BYTESWAP`.
+    byteswap: u128,
+    /// Offset 80: see `aes_gcm::BYTESWAP_EPI64`.
+    byteswap_epi64: u128,
+    /// Offset 96: counter increment for one ymm register (2 blocks).
+    inc_ymm: u128,
+    /// Offset 112: counter increment for one zmm register (4 blocks).
+    inc_zmm: u128,
+    /// Offset 128: see `ghash::GF128_POLY_HI`.
+    gf128_poly_hi: u128,
+}
+
+static CONSTANTS: Constants = Constants {
+    lane_offsets: [1 << 64, 2 << 64, 3 << 64, 4 << 64],
+    byteswap: 0x00010203_04050607_08090a0b_0c0d0e0f,
+    byteswap_epi64: 0x08090a0b_0c0d0e0f_00010203_04050607,
+    inc_ymm: 2 << 64,
+    inc_zmm: 4 << 64,
+    gf128_poly_hi: 0xc2000000_00000000,
+};
@@ -0,0 +1,227 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+//
+// AVX2 by-8 BLAKE3 chunk hashing.  Each vector lane holds the
+// state of a different chunk; this is the same word-sliced
+// approach as `core_8x` in our ChaCha20.
+
+use core::arch::x86_64::*;
+
+use crate::low::generic::blake3::{
+    blake3_hash_chunks as generic_hash_chunks, BLOCK_LEN, CHUNK_END, CHUNK_LEN, CHUNK_START, IV,
+    MSG_SCHEDULE,
+};
+
+/// See [`crate::low::generic::blake3::blake3_hash_chunks`].
+pub(crate) fn blake3_hash_chunks(
+    key: &[u32; 8],
+    counter: u64,
+    flags: u32,
+    input: &[u8],
+    cvs: &mut [[u32; 8]],
+) {
+    assert_eq!(input.len(), cvs.len() * CHUNK_LEN);
+
+    let mut input = input.chunks_exact(CHUNK_LEN * 8);
+    let mut cvs = cvs.chunks_exact_mut(8);
+    let mut counter = counter;
+
+    for (input8, cvs8) in input.by_ref().zip(cvs.by_ref()) {
+        // SAFETY: this crate requires the `avx2` and `ssse3` cpu features
+        unsafe {
+            hash_8_chunks(key, counter, flags, input8, cvs8);
+        }
+        counter += 8;
+    }
+
+    generic_hash_chunks(key, counter, flags, input.remainder(), cvs.into_remainder());
+}
+
+macro_rules! rotate_right {
+    ($reg:expr, 8) => {
+        // this is a byte shuffle rightwards, except little-endian
+        _mm256_shuffle_epi8(
+            $reg,
+            _mm256_set_epi8(
+                12, 15, 14, 13, 8, 11, 10, 9, 4, 7, 6, 5, 0, 3, 2, 1, 12, 15, 14, 13, 8, 11, 10, 9,
+                4, 7, 6, 5, 0, 3, 2, 1,
+            ),
+        )
+    };
+    ($reg:expr, 16) => {
+        // this is a two-byte shuffle rightwards, except little-endian
+        _mm256_shuffle_epi8(
+            $reg,
+            _mm256_set_epi8(
+                13, 12, 15, 14, 9, 8, 11, 10, 5, 4, 7, 6, 1, 0, 3, 2, 13, 12, 15, 14, 9, 8, 11, 10,
+                5, 4, 7, 6, 1, 0, 3, 2,
+            ),
+        )
+    };
+    ($reg:expr, $rot:literal) => {
+        _mm256_or_si256(
+            _mm256_srli_epi32($reg, $rot),
+            _mm256_slli_epi32($reg, 32 - $rot),
+        )
+    };
+}
+
+macro_rules! g {
+    ($v:ident, $a:literal, $b:literal, $c:literal, $d:literal, $x:expr, $y:expr) => {
+        $v[$a] = _mm256_add_epi32(_mm256_add_epi32($v[$a], $v[$b]), $x);
+        $v[$d] = rotate_right!(_mm256_xor_si256($v[$d], $v[$a]), 16);
+        $v[$c] = _mm256_add_epi32($v[$c], $v[$d]);
+        $v[$b] = rotate_right!(_mm256_xor_si256($v[$b], $v[$c]), 12);
+        $v[$a] = _mm256_add_epi32(_mm256_add_epi32($v[$a], $v[$b]), $y);
+        $v[$d] = rotate_right!(_mm256_xor_si256($v[$d], $v[$a]), 8);
+        $v[$c] = _mm256_add_epi32($v[$c], $v[$d]);
+        $v[$b] = rotate_right!(_mm256_xor_si256($v[$b], $v[$c]), 7);
+    };
+}
+
+/// Hashes 8 whole chunks in parallel.
+#[target_feature(enable = "ssse3,avx2")]
+unsafe fn hash_8_chunks(
+    key: &[u32; 8],
+    counter: u64,
+    flags: u32,
+    input: &[u8],
+    cvs_out: &mut [[u32; 8]],
+) {
+    let mut cv = [_mm256_setzero_si256(); 8];
+    for (cv, k) in cv.iter_mut().zip(key.iter()) {
+        *cv = _mm256_set1_epi32(*k as i32);
+    }
+
+    let counters = [0u64, 1, 2, 3, 4, 5, 6, 7].map(|i| counter + i);
+    let counter_lo = _mm256_setr_epi32(
+        counters[0] as i32,
+        counters[1] as i32,
+        counters[2] as i32,
+        counters[3] as i32,
+        counters[4] as i32,
+        counters[5] as i32,
+        counters[6] as i32,
+        counters[7] as i32,
+    );
+    let counter_hi = _mm256_setr_epi32(
+        (counters[0] >> 32) as i32,
+        (counters[1] >> 32) as i32,
+        (counters[2] >> 32) as i32,
+        (counters[3] >> 32) as i32,
+        (counters[4] >> 32) as i32,
+        (counters[5] >> 32) as i32,
+        (counters[6] >> 32) as i32,
+        (counters[7] >> 32) as i32,
+    );
+    let block_len = _mm256_set1_epi32(BLOCK_LEN as i32);
+
+    for block in 0..CHUNK_LEN / BLOCK_LEN {
+        let block_flags = flags
+            | if block == 0 { CHUNK_START } else { 0 }
+            | if block == CHUNK_LEN / BLOCK_LEN - 1 {
+                CHUNK_END
+            } else {
+                0
+            };
+
+        let m = load_transposed(input, block * BLOCK_LEN);
+
+        let mut v = [
+            cv[0],
+            cv[1],
+            cv[2],
+            cv[3],
+            cv[4],
+            cv[5],
+            cv[6],
+            cv[7],
+            _mm256_set1_epi32(IV[0] as i32),
+            _mm256_set1_epi32(IV[1] as i32),
+            _mm256_set1_epi32(IV[2] as i32),
+            _mm256_set1_epi32(IV[3] as i32),
+            counter_lo,
+            counter_hi,
+            block_len,
+            _mm256_set1_epi32(block_flags as i32),
+        ];
+
+        for s in MSG_SCHEDULE {
+            g!(v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g!(v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g!(v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g!(v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g!(v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g!(v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g!(v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g!(v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            cv[i] = _mm256_xor_si256(v[i], v[i + 8]);
+        }
+    }
+
+    let cv = transpose8(cv);
+    for (out, cv) in cvs_out.iter_mut().zip(cv.iter()) {
+        _mm256_storeu_si256(out.as_mut_ptr().cast(), *cv);
+    }
+}
+
+/// Loads the 64-byte block at `offset` in each of the 8 chunks
+/// of `input`, and returns it arranged so that item `i` holds
+/// message word `i` from each chunk.
+#[target_feature(enable = "avx2")]
+unsafe fn load_transposed(input: &[u8], offset: usize) -> [__m256i; 16] {
+    let mut lo = [_mm256_setzero_si256(); 8];
+    let mut hi = [_mm256_setzero_si256(); 8];
+
+    for (i, chunk) in input.chunks_exact(CHUNK_LEN).enumerate() {
+        let block = &chunk[offset..offset + BLOCK_LEN];
+        lo[i] = _mm256_loadu_si256(block[..32].as_ptr().cast());
+        hi[i] = _mm256_loadu_si256(block[32..].as_ptr().cast());
+    }
+
+    let lo = transpose8(lo);
+    let hi = transpose8(hi);
+    [
+        lo[0], lo[1], lo[2], lo[3], lo[4], lo[5], lo[6], lo[7], hi[0], hi[1], hi[2], hi[3], hi[4],
+        hi[5], hi[6], hi[7],
+    ]
+}
+
+/// Transposes an 8x8 matrix of 32-bit words.
+#[target_feature(enable = "avx2")]
+unsafe fn transpose8(x: [__m256i; 8]) -> [__m256i; 8] {
+    // interleave 32-bit words
+    let ab_lo = _mm256_unpacklo_epi32(x[0], x[1]);
+    let ab_hi = _mm256_unpackhi_epi32(x[0], x[1]);
+    let cd_lo = _mm256_unpacklo_epi32(x[2], x[3]);
+    let cd_hi = _mm256_unpackhi_epi32(x[2], x[3]);
+    let ef_lo = _mm256_unpacklo_epi32(x[4], x[5]);
+    let ef_hi = _mm256_unpackhi_epi32(x[4], x[5]);
+    let gh_lo = _mm256_unpacklo_epi32(x[6], x[7]);
+    let gh_hi = _mm256_unpackhi_epi32(x[6], x[7]);
+
+    // interleave 64-bit words
+    let abcd_0 = _mm256_unpacklo_epi64(ab_lo, cd_lo);
+    let abcd_1 = _mm256_unpackhi_epi64(ab_lo, cd_lo);
+    let abcd_2 = _mm256_unpacklo_epi64(ab_hi, cd_hi);
+    let abcd_3 = _mm256_unpackhi_epi64(ab_hi, cd_hi);
+    let efgh_0 = _mm256_unpacklo_epi64(ef_lo, gh_lo);
+    let efgh_1 = _mm256_unpackhi_epi64(ef_lo, gh_lo);
+    let efgh_2 = _mm256_unpacklo_epi64(ef_hi, gh_hi);
+    let efgh_3 = _mm256_unpackhi_epi64(ef_hi, gh_hi);
+
+    // and finally 128-bit lanes
+    [
+        _mm256_permute2x128_si256(abcd_0, efgh_0, 0x20),
+        _mm256_permute2x128_si256(abcd_1, efgh_1, 0x20),
+        _mm256_permute2x128_si256(abcd_2, efgh_2, 0x20),
+        _mm256_permute2x128_si256(abcd_3, efgh_3, 0x20),
+        _mm256_permute2x128_si256(abcd_0, efgh_0, 0x31),
+        _mm256_permute2x128_si256(abcd_1, efgh_1, 0x31),
+        _mm256_permute2x128_si256(abcd_2, efgh_2, 0x31),
+        _mm256_permute2x128_si256(abcd_3, efgh_3, 0x31),
+    ]
+}
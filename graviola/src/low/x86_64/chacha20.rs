@@ -34,6 +34,13 @@ impl ChaCha20 {
     }
 }
 
+impl Drop for ChaCha20 {
+    fn drop(&mut self) {
+        crate::low::zeroise_value(&mut self.z07);
+        crate::low::zeroise_value(&mut self.z8f);
+    }
+}
+
 macro_rules! rotate_left {
     ($reg:expr, 8) => {
         // this is a byte shuffle leftwards, except little-endian
@@ -1,6 +1,7 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
+#[cfg(feature = "_entry")]
 pub(crate) fn enter_cpu_state() -> u32 {
     // DOIT: "Data Operand Independent Timing" -- turning this on
     // is under kernel control, because MSRs are privileged.
@@ -8,6 +9,8 @@ pub(crate) fn enter_cpu_state() -> u32 {
     0
 }
 
+#[cfg(feature = "_entry")]
+#[cfg(target_feature = "sse2")]
 pub(crate) fn leave_cpu_state(_old: u32) {
     // zeroise simd registers
 
@@ -18,7 +21,7 @@ pub(crate) fn leave_cpu_state(_old: u32) {
             // clear z/y/xmm0-15
             "   vzeroall",
 
-            // TODO: add zmm16-31 here if/when we use AVX512
+            // nb. zmm16-31 are not used by this crate, so need not be cleared
             out("ymm0") _,
             out("ymm1") _,
             out("ymm2") _,
@@ -39,17 +42,26 @@ pub(crate) fn leave_cpu_state(_old: u32) {
     }
 }
 
+#[cfg(feature = "_entry")]
+#[cfg(not(target_feature = "sse2"))]
+pub(crate) fn leave_cpu_state(_old: u32) {
+    // no simd registers are available on this target, so
+    // there is nothing to zeroise.
+}
+
 /// Effectively memset(ptr, 0, len), but not visible to optimiser
 ///
 /// # Safety
 /// The caller must ensure that there are `len` bytes writable at `ptr`,
 /// and that the pointed-to object has a safe all-zeroes representation.
 /// (see `low::generic::zeroise` which expresses this within the type system).
+#[cfg(target_feature = "sse2")]
 pub(in crate::low) fn zero_bytes(ptr: *mut u8, len: usize) {
     // SAFETY: this crate requires the `avx` cpu feature
     unsafe { _zero_bytes(ptr, len) }
 }
 
+#[cfg(target_feature = "sse2")]
 #[target_feature(enable = "avx")]
 unsafe fn _zero_bytes(ptr: *mut u8, len: usize) {
     // SAFETY: writes to `len` bytes at `ptr`, which the caller guarantees
@@ -78,62 +90,283 @@ unsafe fn _zero_bytes(ptr: *mut u8, len: usize) {
     )
 }
 
-/// This macro interdicts is_x86_feature_detected to
-/// allow testability.
-macro_rules! have_cpu_feature {
-    ("aes") => {
-        crate::low::x86_64::cpu::test_toggle("aes", is_x86_feature_detected!("aes"))
-    };
-    ("pclmulqdq") => {
-        crate::low::x86_64::cpu::test_toggle("pclmulqdq", is_x86_feature_detected!("pclmulqdq"))
-    };
-    ("bmi1") => {
-        crate::low::x86_64::cpu::test_toggle("bmi1", is_x86_feature_detected!("bmi1"))
-    };
-    ("bmi2") => {
-        crate::low::x86_64::cpu::test_toggle("bmi2", is_x86_feature_detected!("bmi2"))
-    };
-    ("adx") => {
-        crate::low::x86_64::cpu::test_toggle("adx", is_x86_feature_detected!("adx"))
-    };
-    ("avx") => {
-        crate::low::x86_64::cpu::test_toggle("avx", is_x86_feature_detected!("avx"))
-    };
-    ("avx2") => {
-        crate::low::x86_64::cpu::test_toggle("avx2", is_x86_feature_detected!("avx2"))
-    };
-    ("sha") => {
-        crate::low::x86_64::cpu::test_toggle("sha", is_x86_feature_detected!("sha"))
-    };
+/// Effectively memset(ptr, 0, len), but not visible to optimiser
+///
+/// # Safety
+/// As above.
+#[cfg(not(target_feature = "sse2"))]
+pub(in crate::low) fn zero_bytes(ptr: *mut u8, len: usize) {
+    // SAFETY: writes to `len` bytes at `ptr`, which the caller guarantees
+    unsafe {
+        core::arch::asm!(
+            "       rep stosb",
+            inout("rcx") len => _,
+            inout("rdi") ptr => _,
+            in("al") 0u8,
+            options(nostack, preserves_flags),
+        )
+    }
 }
 
-pub(crate) use have_cpu_feature;
+// cpu feature detection is needed by `verify_cpu_features()`, and
+// to select between implementations of the hash functions.
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        feature = "_entry",
+        all(
+            target_feature = "sse2",
+            any(feature = "sha1", feature = "sha256", feature = "sha512")
+        )
+    ))] {
+        /// This macro interdicts `feature_detected` to
+        /// allow testability.
+        macro_rules! have_cpu_feature {
+            ("aes") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "aes",
+                    crate::low::x86_64::cpu::feature_detected!("aes"),
+                )
+            };
+            ("pclmulqdq") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "pclmulqdq",
+                    crate::low::x86_64::cpu::feature_detected!("pclmulqdq"),
+                )
+            };
+            ("bmi1") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "bmi1",
+                    crate::low::x86_64::cpu::feature_detected!("bmi1"),
+                )
+            };
+            ("bmi2") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "bmi2",
+                    crate::low::x86_64::cpu::feature_detected!("bmi2"),
+                )
+            };
+            ("adx") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "adx",
+                    crate::low::x86_64::cpu::feature_detected!("adx"),
+                )
+            };
+            ("avx") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "avx",
+                    crate::low::x86_64::cpu::feature_detected!("avx"),
+                )
+            };
+            ("avx2") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "avx2",
+                    crate::low::x86_64::cpu::feature_detected!("avx2"),
+                )
+            };
+            ("sha") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "sha",
+                    crate::low::x86_64::cpu::feature_detected!("sha"),
+                )
+            };
+            ("vaes") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "vaes",
+                    crate::low::x86_64::cpu::feature_detected!("vaes"),
+                )
+            };
+            ("vpclmulqdq") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "vpclmulqdq",
+                    crate::low::x86_64::cpu::feature_detected!("vpclmulqdq"),
+                )
+            };
+            ("avx512f") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "avx512f",
+                    crate::low::x86_64::cpu::feature_detected!("avx512f"),
+                )
+            };
+            ("avx512bw") => {
+                crate::low::x86_64::cpu::test_toggle(
+                    "avx512bw",
+                    crate::low::x86_64::cpu::feature_detected!("avx512bw"),
+                )
+            };
+        }
 
-#[cfg(not(debug_assertions))]
-pub(crate) fn test_toggle(_id: &str, detected: bool) -> bool {
-    detected
-}
+        #[allow(unused_imports)]
+        pub(crate) use have_cpu_feature;
+
+        #[cfg(feature = "std")]
+        pub(crate) use std::is_x86_feature_detected as feature_detected;
+
+        /// A `no_std` replacement for `is_x86_feature_detected`.
+        #[cfg(not(feature = "std"))]
+        macro_rules! feature_detected {
+            ($feature:tt) => {
+                crate::low::x86_64::cpu::cpuid::detected(crate::low::x86_64::cpu::cpuid::feature_bit!(
+                    $feature
+                ))
+            };
+        }
+
+        #[cfg(not(feature = "std"))]
+        pub(crate) use feature_detected;
+
+        #[cfg(not(all(debug_assertions, feature = "std")))]
+        pub(crate) fn test_toggle(_id: &str, detected: bool) -> bool {
+            detected
+        }
+
+        #[cfg(all(debug_assertions, feature = "std"))]
+        pub(crate) fn test_toggle(id: &str, detected: bool) -> bool {
+            if std::env::var(format!("GRAVIOLA_CPU_DISABLE_{id}")).is_ok() {
+                println!("DEBUG: denying cpuid {id:?}");
+                false
+            } else {
+                detected
+            }
+        }
+
+        #[cfg(not(feature = "std"))]
+        pub(crate) mod cpuid {
+            use core::arch::x86_64::{__cpuid, __cpuid_count, CpuidResult};
+            use core::sync::atomic::{AtomicU32, Ordering};
+
+            /// Maps a feature name (as used by `is_x86_feature_detected`) to its bit.
+            macro_rules! feature_bit {
+                ("aes") => {
+                    1 << 0
+                };
+                ("pclmulqdq") => {
+                    1 << 1
+                };
+                ("bmi1") => {
+                    1 << 2
+                };
+                ("bmi2") => {
+                    1 << 3
+                };
+                ("adx") => {
+                    1 << 4
+                };
+                ("avx") => {
+                    1 << 5
+                };
+                ("avx2") => {
+                    1 << 6
+                };
+                ("sha") => {
+                    1 << 7
+                };
+                ("vaes") => {
+                    1 << 8
+                };
+                ("vpclmulqdq") => {
+                    1 << 9
+                };
+                ("avx512f") => {
+                    1 << 10
+                };
+                ("avx512bw") => {
+                    1 << 11
+                };
+            }
+
+            pub(crate) use feature_bit;
+
+            pub(crate) fn detected(feature: u32) -> bool {
+                let mut features = CACHE.load(Ordering::Relaxed);
+                if features == 0 {
+                    features = detect() | INITIALISED;
+                    CACHE.store(features, Ordering::Relaxed);
+                }
+                features & feature == feature
+            }
+
+            fn detect() -> u32 {
+                // SAFETY: `cpuid` is available on all x86_64 processors.
+                #[allow(unused_unsafe)]
+                let (max_leaf, leaf1) = unsafe { (__cpuid(0).eax, __cpuid(1)) };
+                let leaf7 = match max_leaf >= 7 {
+                    // SAFETY: as above, and leaf 7 is supported.
+                    #[allow(unused_unsafe)]
+                    true => unsafe { __cpuid_count(7, 0) },
+                    false => CpuidResult {
+                        eax: 0,
+                        ebx: 0,
+                        ecx: 0,
+                        edx: 0,
+                    },
+                };
+
+                // AVX and AVX-512 register state must also be enabled by the OS.
+                let xcr0 = match bit(leaf1.ecx, 27) {
+                    // SAFETY: `xgetbv` is available if OSXSAVE is set.
+                    true => unsafe { xgetbv0() },
+                    false => 0,
+                };
+                let os_avx = xcr0 & 0b110 == 0b110;
+                let os_avx512 = os_avx && xcr0 & 0b1110_0000 == 0b1110_0000;
+
+                let mut features = 0;
+                let mut set = |feature: u32, present: bool| {
+                    if present {
+                        features |= feature;
+                    }
+                };
+                set(feature_bit!("aes"), bit(leaf1.ecx, 25));
+                set(feature_bit!("pclmulqdq"), bit(leaf1.ecx, 1));
+                set(feature_bit!("bmi1"), bit(leaf7.ebx, 3));
+                set(feature_bit!("bmi2"), bit(leaf7.ebx, 8));
+                set(feature_bit!("adx"), bit(leaf7.ebx, 19));
+                set(feature_bit!("avx"), os_avx && bit(leaf1.ecx, 28));
+                set(feature_bit!("avx2"), os_avx && bit(leaf7.ebx, 5));
+                set(feature_bit!("sha"), bit(leaf7.ebx, 29));
+                set(feature_bit!("vaes"), os_avx && bit(leaf7.ecx, 9));
+                set(feature_bit!("vpclmulqdq"), os_avx && bit(leaf7.ecx, 10));
+                set(feature_bit!("avx512f"), os_avx512 && bit(leaf7.ebx, 16));
+                set(feature_bit!("avx512bw"), os_avx512 && bit(leaf7.ebx, 30));
+                features
+            }
+
+            fn bit(reg: u32, bit: u32) -> bool {
+                reg & (1 << bit) != 0
+            }
+
+            unsafe fn xgetbv0() -> u64 {
+                let (lo, hi): (u32, u32);
+                // SAFETY: caller ensures `xgetbv` is available; it only writes `eax` and `edx`.
+                core::arch::asm!(
+                    "xgetbv",
+                    in("ecx") 0,
+                    out("eax") lo,
+                    out("edx") hi,
+                    options(nomem, nostack, preserves_flags),
+                );
+                ((hi as u64) << 32) | lo as u64
+            }
 
-#[cfg(debug_assertions)]
-pub(crate) fn test_toggle(id: &str, detected: bool) -> bool {
-    if std::env::var(format!("GRAVIOLA_CPU_DISABLE_{id}")).is_ok() {
-        println!("DEBUG: denying cpuid {id:?}");
-        false
-    } else {
-        detected
+            const INITIALISED: u32 = 1 << 31;
+            static CACHE: AtomicU32 = AtomicU32::new(0);
+        }
     }
 }
 
+#[cfg(feature = "_entry")]
 pub(crate) fn verify_cpu_features() {
     // these are the cpu features we require unconditionally.
     // this limits the library to x86_64 processors released after approx 2013.
 
     // mandatory feature requirements
     // our aes-gcm
+    #[cfg(target_feature = "sse2")]
     assert!(
         have_cpu_feature!("aes"),
         "graviola requires aes CPU support"
     );
+    #[cfg(target_feature = "sse2")]
     assert!(
         have_cpu_feature!("pclmulqdq"),
         "graviola requires pclmulqdq CPU support"
@@ -156,11 +389,13 @@ pub(crate) fn verify_cpu_features() {
     // -- therefore rely on the expectation that `adx` support is implied by
     // `bmi1` support.
 
-    // assorted intrinsic code
+    // assorted intrinsic code (not used on targets without simd registers)
+    #[cfg(target_feature = "sse2")]
     assert!(
-        is_x86_feature_detected!("avx"),
+        feature_detected!("avx"),
         "graviola requires avx CPU support"
     );
+    #[cfg(target_feature = "sse2")]
     assert!(
         have_cpu_feature!("avx2"),
         "graviola requires avx2 CPU support"
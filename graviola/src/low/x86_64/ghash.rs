@@ -20,29 +20,41 @@ pub(crate) struct GhashTable {
     ///
     /// This can be used directly in the middle Karatsuba term.
     powers_xor: [__m128i; 8],
+
+    /// H^16, H^15, ... H^1
+    ///
+    /// This is in descending order, so that it can be loaded
+    /// directly into wide registers.  See `aes_gcm_vaes`.
+    pub(crate) powers_wide: [__m128i; 16],
 }
 
 impl GhashTable {
     pub(crate) fn new(h: u128) -> Self {
         let mut powers = [zero(); 8];
         let mut powers_xor = powers;
+        let mut powers_wide = [zero(); 16];
         let h = u128_to_m128i(h);
 
         // SAFETY: this crate requires the `avx` cpu feature
         let h = unsafe { gf128_big_endian(h) };
-        powers[0] = h;
+        powers_wide[15] = h;
 
-        for i in 1..8 {
+        for i in (0..15).rev() {
             // SAFETY: this crate requires the `avx` and `pclmulqdq` cpu features
-            powers[i] = unsafe { _mul(powers[i - 1], h) };
+            powers_wide[i] = unsafe { _mul(powers_wide[i + 1], h) };
         }
 
         for i in 0..8 {
+            powers[i] = powers_wide[15 - i];
             // SAFETY: this crate requires the `avx` cpu feature
             powers_xor[i] = unsafe { xor_halves(powers[i]) };
         }
 
-        Self { powers, powers_xor }
+        Self {
+            powers,
+            powers_xor,
+            powers_wide,
+        }
     }
 }
 
@@ -50,6 +62,7 @@ impl Drop for GhashTable {
     fn drop(&mut self) {
         low::zeroise(&mut self.powers);
         low::zeroise(&mut self.powers_xor);
+        low::zeroise(&mut self.powers_wide);
     }
 }
 
@@ -246,6 +259,7 @@ fn u128_to_m128i(v: u128) -> __m128i {
     unsafe { mem::transmute(v) }
 }
 
+// SAFETY: both u128 and __m128i have the same size and all bits mean the same thing
 const BYTESWAP: __m128i = unsafe { mem::transmute(0x00010203_04050607_08090a0b_0c0d0e0fu128) };
 
 /// The high half of the ghash polynomial R, rotated left by one
@@ -253,10 +267,12 @@ const BYTESWAP: __m128i = unsafe { mem::transmute(0x00010203_04050607_08090a0b_0
 /// R is 0xe100..00u128
 ///
 /// We need this in a __m128i, but only the bottom 64-bits are used.
+// SAFETY: both u128 and __m128i have the same size and all bits mean the same thing
 const GF128_POLY_HI: __m128i = unsafe { mem::transmute(0xc2000000_00000000u128) };
 
 /// This is, again, R rotated left by one, but with a 2^64 term
 const GF128_POLY_CARRY_MASK: __m128i =
+    // SAFETY: both u128 and __m128i have the same size and all bits mean the same thing
     unsafe { mem::transmute(0xc2000000_00000001_00000000_00000001u128) };
 
 #[cfg(test)]
@@ -1,64 +1,139 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
+// Modules which use SIMD registers are not available on targets
+// without them (eg. `x86_64-unknown-none`), and are replaced by
+// their `low::generic` equivalents.  The s2n-bignum code only
+// uses general purpose registers, so is available everywhere.
+
+#[cfg(all(target_feature = "sse2", feature = "aes"))]
 pub(crate) mod aes;
+#[cfg(all(target_feature = "sse2", feature = "aes-gcm"))]
 pub(crate) mod aes_gcm;
+#[cfg(all(target_feature = "sse2", feature = "aes-gcm"))]
+mod aes_gcm_vaes;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_add;
+#[cfg(feature = "p256")]
 pub(crate) mod bignum_add_p256;
+#[cfg(feature = "p384")]
 pub(crate) mod bignum_add_p384;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_bitsize;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_cmp_lt;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_copy_row_from_table;
+#[cfg(all(target_feature = "sse2", feature = "rsa"))]
 pub(crate) mod bignum_copy_row_from_table_16_avx2;
+#[cfg(all(target_feature = "sse2", feature = "rsa"))]
 pub(crate) mod bignum_copy_row_from_table_8n_avx2;
+#[cfg(all(target_feature = "sse2", feature = "rsa"))]
 pub(crate) mod bignum_copy_row_from_table_mux;
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
 pub(crate) mod bignum_demont;
+#[cfg(feature = "p256")]
 pub(crate) mod bignum_demont_p256;
+#[cfg(feature = "p384")]
 pub(crate) mod bignum_demont_p384;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_digitsize;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_emontredc_8n;
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
 pub(crate) mod bignum_eq;
+#[cfg(feature = "p256")]
 pub(crate) mod bignum_inv_p256;
+#[cfg(feature = "p384")]
 pub(crate) mod bignum_inv_p384;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_kmul_16_32;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_kmul_32_64;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_ksqr_16_32;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_ksqr_32_64;
+#[cfg(feature = "p256")]
 pub(crate) mod bignum_mod_n256;
+#[cfg(feature = "p384")]
 pub(crate) mod bignum_mod_n384;
+#[cfg(any(feature = "p256", feature = "p384"))]
 pub(crate) mod bignum_modadd;
+#[cfg(any(feature = "p256", feature = "p384"))]
 pub(crate) mod bignum_modinv;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_modsub;
+#[cfg(any(feature = "rsa", all(test, any(feature = "p256", feature = "p384"))))]
 pub(crate) mod bignum_montifier;
+#[cfg(any(feature = "p256", feature = "p384", feature = "rsa"))]
 pub(crate) mod bignum_montmul;
+#[cfg(feature = "p256")]
 pub(crate) mod bignum_montmul_p256;
+#[cfg(feature = "p384")]
 pub(crate) mod bignum_montmul_p384;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_montredc;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_montsqr;
+#[cfg(feature = "p256")]
 pub(crate) mod bignum_montsqr_p256;
+#[cfg(feature = "p384")]
 pub(crate) mod bignum_montsqr_p384;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_mul;
+#[cfg(any(feature = "p256", feature = "p384"))]
 pub(crate) mod bignum_mux;
+#[cfg(feature = "p256")]
 pub(crate) mod bignum_neg_p256;
+#[cfg(feature = "p384")]
 pub(crate) mod bignum_neg_p384;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_negmodinv;
+#[cfg(feature = "rsa")]
 pub(crate) mod bignum_optsub;
+#[cfg(all(target_feature = "sse2", feature = "p256"))]
 pub(crate) mod bignum_point_select_p256;
+#[cfg(all(target_feature = "sse2", feature = "p384"))]
 pub(crate) mod bignum_point_select_p384;
+#[cfg(feature = "p256")]
 pub(crate) mod bignum_tomont_p256;
+#[cfg(feature = "p384")]
 pub(crate) mod bignum_tomont_p384;
+#[cfg(all(target_feature = "sse2", feature = "blake3"))]
+pub(crate) mod blake3;
+#[cfg(all(
+    target_feature = "sse2",
+    any(feature = "chacha20poly1305", feature = "std")
+))]
 pub(crate) mod chacha20;
 pub(crate) mod cpu;
+#[cfg(feature = "x25519")]
 pub(crate) mod curve25519_x25519;
+#[cfg(all(feature = "x25519", not(feature = "runtime-tables")))]
 pub(crate) mod curve25519_x25519base;
+#[cfg(all(target_feature = "sse2", feature = "aes-gcm"))]
 pub(crate) mod ghash;
 pub(crate) mod optimise_barrier;
+#[cfg(feature = "p256")]
 pub(crate) mod p256_montjadd;
+#[cfg(feature = "p256")]
 pub(crate) mod p256_montjdouble;
+#[cfg(feature = "p256")]
 pub(crate) mod p256_montjmixadd;
+#[cfg(feature = "p384")]
 pub(crate) mod p384_montjadd;
+#[cfg(feature = "p384")]
 pub(crate) mod p384_montjdouble;
+#[cfg(all(target_feature = "sse2", feature = "sha1"))]
+pub(crate) mod sha1;
+#[cfg(all(target_feature = "sse2", feature = "sha1"))]
+pub(crate) mod sha1_mux;
+#[cfg(all(target_feature = "sse2", feature = "sha256"))]
 pub(crate) mod sha256;
+#[cfg(all(target_feature = "sse2", feature = "sha256"))]
 pub(crate) mod sha256_mux;
+#[cfg(all(target_feature = "sse2", feature = "sha512"))]
 pub(crate) mod sha512;
+#[cfg(all(target_feature = "sse2", feature = "sha512"))]
 pub(crate) mod sha512_mux;
@@ -171,12 +171,41 @@ mod tests {
         let (cipher, tag) = expected.split_at(expected.len() - 16);
         let mut plain = cipher.to_vec();
 
-        t.decrypt(b"noncenonceno", b"aad", &mut plain, &tag)
-            .unwrap();
+        t.decrypt(b"noncenonceno", b"aad", &mut plain, tag).unwrap();
 
         assert_eq!(plain, &[b'p'; 4164]);
     }
 
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn many_lengths_test() {
+        // covers lengths either side of the various block-multiple
+        // boundaries, for AES-256
+        let t = AesGcm::new(&[b'K'; 32]);
+        let mut summary = crate::mid::sha2::Sha256Context::new();
+
+        for len in 0..=600 {
+            let mut cipher = vec![b'p'; len];
+            let mut tag = [0u8; 16];
+            t.encrypt(b"noncenonceno", b"aad", &mut cipher, &mut tag);
+            summary.update(&cipher);
+            summary.update(&tag);
+
+            t.decrypt(b"noncenonceno", b"aad", &mut cipher, &tag)
+                .unwrap();
+            assert_eq!(cipher, vec![b'p'; len]);
+        }
+
+        assert_eq!(
+            summary.finish(),
+            [
+                0xa4, 0xec, 0xda, 0x21, 0xb0, 0x37, 0xb3, 0x10, 0x09, 0x2b, 0xc6, 0x01, 0x42, 0x14,
+                0x2f, 0x4e, 0x44, 0x17, 0x98, 0xe6, 0x26, 0x21, 0xf8, 0x0c, 0xca, 0xf3, 0x2d, 0xe3,
+                0xb1, 0x46, 0x0d, 0xa0
+            ]
+        );
+    }
+
     #[test]
     fn cavp() {
         #[derive(Default)]
@@ -271,8 +300,10 @@ mod tests {
             &mut State::default(),
         );
 
-        let mut state = State::default();
-        state.encrypt = true;
+        let mut state = State {
+            encrypt: true,
+            ..Default::default()
+        };
         process_cavp("../thirdparty/cavp/gcm/gcmEncryptExtIV128.rsp", &mut state);
         process_cavp("../thirdparty/cavp/gcm/gcmEncryptExtIV256.rsp", &mut state);
     }
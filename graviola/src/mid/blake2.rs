@@ -0,0 +1,314 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! BLAKE2b and BLAKE2s.
+//!
+//! BLAKE2 is specified in [RFC7693](https://www.rfc-editor.org/rfc/rfc7693).
+//! Both keyed (MAC) mode and variable output lengths are supported.
+//! Salt and personalization parameters are not.
+
+use crate::low::{blake2b_compress, blake2s_compress, BLAKE2B_IV, BLAKE2S_IV};
+use crate::Error;
+
+macro_rules! blake2_context {
+    ($name:ident, $doc:literal, $word:ty, $counter:ty, $block_sz:literal, $max_output_len:literal, $iv:ident, $compress:ident) => {
+        #[doc = concat!("A context for incremental computation of ", $doc, ".")]
+        #[derive(Clone)]
+        pub struct $name {
+            h: [$word; 8],
+            buffer: [u8; $block_sz],
+            used: usize,
+            t: $counter,
+            output_len: usize,
+        }
+
+        impl $name {
+            #[doc = concat!("Start a new unkeyed ", $doc, " hash computation, with the maximum output length.")]
+            pub const fn new() -> Self {
+                Self::with_params(0, $max_output_len)
+            }
+
+            #[doc = concat!("Start a new ", $doc, " computation, in keyed mode.")]
+            ///
+            #[doc = concat!("`key` may be at most ", stringify!($max_output_len), " bytes, and may be empty for unkeyed hashing.")]
+            #[doc = concat!("`output_len` must be between 1 and ", stringify!($max_output_len), " bytes inclusive.")]
+            ///
+            /// The output length is an input to the computation: a shorter
+            /// output is not a prefix of a longer one.
+            pub fn new_keyed(key: &[u8], output_len: usize) -> Result<Self, Error> {
+                if key.len() > $max_output_len {
+                    return Err(Error::WrongLength);
+                }
+                if output_len == 0 || output_len > $max_output_len {
+                    return Err(Error::OutOfRange);
+                }
+
+                let mut ctx = Self::with_params(key.len(), output_len);
+                if !key.is_empty() {
+                    // the key is padded to a whole block
+                    ctx.buffer[..key.len()].copy_from_slice(key);
+                    ctx.used = $block_sz;
+                }
+                Ok(ctx)
+            }
+
+            const fn with_params(key_len: usize, output_len: usize) -> Self {
+                let mut h = $iv;
+                h[0] ^= 0x0101_0000 ^ ((key_len as $word) << 8) ^ (output_len as $word);
+                Self {
+                    h,
+                    buffer: [0u8; $block_sz],
+                    used: 0,
+                    t: 0,
+                    output_len,
+                }
+            }
+
+            /// Add `bytes` to the ongoing hash computation.
+            pub fn update(&mut self, mut bytes: &[u8]) {
+                while !bytes.is_empty() {
+                    // the final block is compressed differently, so
+                    // a full block is only processed once we know it is
+                    // not the last one.
+                    if self.used == $block_sz {
+                        self.t += $block_sz;
+                        $compress(&mut self.h, &self.buffer, self.t, false);
+                        self.used = 0;
+                    }
+
+                    if self.used == 0 && bytes.len() > $block_sz {
+                        let (block, rest) = bytes.split_at($block_sz);
+                        self.t += $block_sz;
+                        $compress(&mut self.h, block.try_into().unwrap(), self.t, false);
+                        bytes = rest;
+                        continue;
+                    }
+
+                    let take = core::cmp::min($block_sz - self.used, bytes.len());
+                    let (taken, rest) = bytes.split_at(take);
+                    self.buffer[self.used..self.used + take].copy_from_slice(taken);
+                    self.used += take;
+                    bytes = rest;
+                }
+            }
+
+            #[doc = concat!("Complete the ", $doc, " computation, writing the hash output to `out`.")]
+            ///
+            /// `out.len()` must equal the output length chosen when this
+            /// context was created, otherwise [`Error::WrongLength`] is
+            /// returned.
+            pub fn finish(mut self, out: &mut [u8]) -> Result<(), Error> {
+                if out.len() != self.output_len {
+                    return Err(Error::WrongLength);
+                }
+
+                self.t += self.used as $counter;
+                self.buffer[self.used..].fill(0);
+                $compress(&mut self.h, &self.buffer, self.t, true);
+
+                let word_sz = core::mem::size_of::<$word>();
+                for (out, h) in out.chunks_mut(word_sz).zip(self.h.iter()) {
+                    out.copy_from_slice(&h.to_le_bytes()[..out.len()]);
+                }
+                Ok(())
+            }
+
+            /// The output length of this computation, in bytes.
+            pub const fn output_len(&self) -> usize {
+                self.output_len
+            }
+
+            #[doc = concat!("The internal block size of ", $doc, ".")]
+            pub const BLOCK_SZ: usize = $block_sz;
+
+            #[doc = concat!("The maximum (and default) output length of ", $doc, ".")]
+            pub const MAX_OUTPUT_SZ: usize = $max_output_len;
+        }
+    };
+}
+
+blake2_context!(
+    Blake2bContext,
+    "BLAKE2b",
+    u64,
+    u128,
+    128,
+    64,
+    BLAKE2B_IV,
+    blake2b_compress
+);
+blake2_context!(
+    Blake2sContext,
+    "BLAKE2s",
+    u32,
+    u64,
+    64,
+    32,
+    BLAKE2S_IV,
+    blake2s_compress
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blake2_abc() {
+        // from RFC7693 appendices A and B
+        let mut ctx = Blake2bContext::new();
+        ctx.update(b"abc");
+        let mut out = [0u8; 64];
+        ctx.finish(&mut out).unwrap();
+        assert_eq!(&out,
+                   b"\xba\x80\xa5\x3f\x98\x1c\x4d\x0d\x6a\x27\x97\xb6\x9f\x12\xf6\xe9\x4c\x21\x2f\x14\x68\x5a\xc4\xb7\x4b\x12\xbb\x6f\xdb\xff\xa2\xd1\x7d\x87\xc5\x39\x2a\xab\x79\x2d\xc2\x52\xd5\xde\x45\x33\xcc\x95\x18\xd3\x8a\xa8\xdb\xf1\x92\x5a\xb9\x23\x86\xed\xd4\x00\x99\x23");
+
+        let mut ctx = Blake2sContext::new();
+        ctx.update(b"abc");
+        let mut out = [0u8; 32];
+        ctx.finish(&mut out).unwrap();
+        assert_eq!(&out,
+                   b"\x50\x8c\x5e\x8c\x32\x7c\x14\xe2\xe1\xa7\x2b\xa3\x4e\xeb\x45\x2f\x37\x45\x8b\x20\x9e\xd6\x3a\x29\x4d\x99\x9b\x4c\x86\x67\x59\x82");
+    }
+
+    #[test]
+    fn blake2b_all_lengths() {
+        let mut outer = Blake2bContext::new();
+
+        for len in 0..1024 {
+            let mut inner = Blake2bContext::new();
+
+            for _ in 0..len {
+                inner.update(&[len as u8]);
+            }
+
+            let mut out = [0u8; 64];
+            inner.finish(&mut out).unwrap();
+            outer.update(&out);
+        }
+
+        let mut out = [0u8; 64];
+        outer.finish(&mut out).unwrap();
+        assert_eq!(&out,
+                   b"\xde\x0f\x0f\x92\xf3\x6c\x9e\x87\xc4\x1b\x3f\xfc\xa7\x8e\xd5\xb6\xd1\xbc\xb0\x5f\x39\x18\xb3\xfa\x68\xb1\x93\x4a\xbe\x59\x01\x28\xd6\x38\x58\x93\x11\xf5\x8f\xcd\x98\x5f\x20\x00\x39\x24\xde\x60\xbb\x4a\x51\x29\xa4\x23\x37\x9b\x56\xf8\x12\x04\x8e\xe9\xbb\xca");
+    }
+
+    #[test]
+    fn blake2s_all_lengths() {
+        let mut outer = Blake2sContext::new();
+
+        for len in 0..1024 {
+            let mut inner = Blake2sContext::new();
+
+            for _ in 0..len {
+                inner.update(&[len as u8]);
+            }
+
+            let mut out = [0u8; 32];
+            inner.finish(&mut out).unwrap();
+            outer.update(&out);
+        }
+
+        let mut out = [0u8; 32];
+        outer.finish(&mut out).unwrap();
+        assert_eq!(&out,
+                   b"\xb5\x12\xbb\x84\x29\x33\xa8\x96\x8a\xe7\x49\xab\x87\xea\x1a\x4e\x1e\x1f\x2d\x7b\xaf\x43\x1a\x12\x6f\x05\x98\xea\x57\x04\xde\xb5");
+    }
+
+    #[test]
+    fn keyed() {
+        // these are the keyed KATs from the BLAKE2 reference
+        // implementation, with all message lengths up to 255 hashed
+        // together.
+        let key = (0..64).collect::<Vec<u8>>();
+        let message = (0..255).collect::<Vec<u8>>();
+
+        let mut outer = Blake2bContext::new();
+        for len in 0..256 {
+            let mut inner = Blake2bContext::new_keyed(&key, 64).unwrap();
+            inner.update(&message[..len]);
+            let mut out = [0u8; 64];
+            inner.finish(&mut out).unwrap();
+            outer.update(&out);
+        }
+        let mut out = [0u8; 64];
+        outer.finish(&mut out).unwrap();
+        assert_eq!(&out,
+                   b"\x79\xa5\x1f\x89\x1c\x56\x67\x9c\x1f\xff\x4c\xff\x93\xa7\xf1\xed\x29\x22\xd6\x68\x56\x0b\x88\x0f\x2d\xeb\xec\xf6\x30\xd3\xcd\xaf\xf5\x9d\x3b\xb3\x80\xd1\x8c\x3c\x24\xd9\x1e\x21\x4c\x20\x08\x5e\x6b\x4b\x08\x1a\xdc\xcb\x8f\xc3\x02\x5a\x96\xe5\x20\xeb\x7c\x34");
+
+        let mut outer = Blake2sContext::new();
+        for len in 0..256 {
+            let mut inner = Blake2sContext::new_keyed(&key[..32], 32).unwrap();
+            inner.update(&message[..len]);
+            let mut out = [0u8; 32];
+            inner.finish(&mut out).unwrap();
+            outer.update(&out);
+        }
+        let mut out = [0u8; 32];
+        outer.finish(&mut out).unwrap();
+        assert_eq!(&out,
+                   b"\x25\x82\xf7\xb1\x8a\x91\xf6\x6e\xf2\x2a\xa9\x45\x53\x84\xad\xa6\x9b\x25\x90\x30\x6a\x64\x70\xee\x85\x88\xdf\x75\xee\x98\x2f\x02");
+
+        // empty message: only the key block is compressed
+        let mut out = [0u8; 64];
+        Blake2bContext::new_keyed(&[b'k'; 64], 64)
+            .unwrap()
+            .finish(&mut out)
+            .unwrap();
+        assert_eq!(&out,
+                   b"\x5c\xae\xfa\x98\x18\x31\x54\x14\xd0\xd7\x63\x30\x0f\xc0\x8c\x4c\x7c\x43\x4a\x25\x9b\x75\xc5\xed\x81\x4e\x97\xc3\x13\xab\x1b\x15\x72\x76\xe5\x45\x69\x67\x79\x4c\x81\x0c\xdb\xd0\xb5\xc5\x26\x65\x03\x06\x7a\x4a\x49\x9d\x29\x61\x56\x93\xe0\x63\xb1\xb6\xaf\xb9");
+    }
+
+    #[test]
+    fn output_lengths() {
+        let mut ctx = Blake2bContext::new_keyed(b"", 32).unwrap();
+        assert_eq!(ctx.output_len(), 32);
+        ctx.update(b"abc");
+        let mut out = [0u8; 32];
+        ctx.finish(&mut out).unwrap();
+        assert_eq!(&out,
+                   b"\xbd\xdd\x81\x3c\x63\x42\x39\x72\x31\x71\xef\x3f\xee\x98\x57\x9b\x94\x96\x4e\x3b\xb1\xcb\x3e\x42\x72\x62\xc8\xc0\x68\xd5\x23\x19");
+
+        let mut ctx = Blake2sContext::new_keyed(b"key", 20).unwrap();
+        ctx.update(b"abc");
+        let mut out = [0u8; 20];
+        ctx.finish(&mut out).unwrap();
+        assert_eq!(
+            &out,
+            b"\x4e\xd6\x99\xf7\xe7\x1c\xe7\x43\x40\xba\x20\x2a\x9c\x37\xc4\xcf\x97\x72\xaa\x3a"
+        );
+    }
+
+    #[test]
+    fn invalid_params() {
+        assert_eq!(
+            Blake2bContext::new_keyed(&[0u8; 65], 64).err(),
+            Some(Error::WrongLength)
+        );
+        assert_eq!(
+            Blake2bContext::new_keyed(b"", 0).err(),
+            Some(Error::OutOfRange)
+        );
+        assert_eq!(
+            Blake2bContext::new_keyed(b"", 65).err(),
+            Some(Error::OutOfRange)
+        );
+        assert_eq!(
+            Blake2sContext::new_keyed(&[0u8; 33], 32).err(),
+            Some(Error::WrongLength)
+        );
+        assert_eq!(
+            Blake2sContext::new_keyed(b"", 33).err(),
+            Some(Error::OutOfRange)
+        );
+        assert_eq!(
+            Blake2bContext::new_keyed(b"", 32)
+                .unwrap()
+                .finish(&mut [0u8; 64]),
+            Err(Error::WrongLength)
+        );
+        assert_eq!(
+            Blake2sContext::new().finish(&mut [0u8; 16]),
+            Err(Error::WrongLength)
+        );
+    }
+}
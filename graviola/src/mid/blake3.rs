@@ -0,0 +1,471 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! BLAKE3.
+//!
+//! BLAKE3 is specified in [the BLAKE3 paper](https://github.com/BLAKE3-team/BLAKE3-specs/blob/master/blake3.pdf).
+//! This supports the hash, keyed hash and key derivation modes,
+//! and extendable output.
+
+use crate::low::blake3::{
+    blake3_compress, BLOCK_LEN, CHUNK_END, CHUNK_LEN, CHUNK_START, DERIVE_KEY_CONTEXT,
+    DERIVE_KEY_MATERIAL, IV, KEYED_HASH, PARENT, ROOT,
+};
+use crate::low::blake3_hash_chunks;
+
+/// A context for incremental computation of BLAKE3.
+#[derive(Clone)]
+pub struct Blake3Context {
+    key: [u32; 8],
+    flags: u32,
+    chunk: ChunkState,
+    /// Chaining values of completed subtrees.
+    ///
+    /// 54 entries is enough for 2^64 bytes of input.
+    cv_stack: [[u32; 8]; 54],
+    cv_stack_len: usize,
+}
+
+impl Blake3Context {
+    /// Start a new BLAKE3 hash computation.
+    pub const fn new() -> Self {
+        Self::with_key_and_flags(IV, 0)
+    }
+
+    /// Start a new BLAKE3 computation in keyed hash mode.
+    ///
+    /// This is a MAC, or a PRF.
+    pub fn new_keyed(key: &[u8; 32]) -> Self {
+        Self::with_key_and_flags(words_from_le_bytes(key), KEYED_HASH)
+    }
+
+    /// Start a new BLAKE3 computation in key derivation mode.
+    ///
+    /// `context` should be a hardcoded, globally unique and
+    /// application-specific string.  The input key material is
+    /// then provided with [`Blake3Context::update()`], and the derived
+    /// key is the output.
+    pub fn new_derive_key(context: &str) -> Self {
+        let mut context_hasher = Self::with_key_and_flags(IV, DERIVE_KEY_CONTEXT);
+        context_hasher.update(context.as_bytes());
+        let context_key = context_hasher.finish();
+        Self::with_key_and_flags(words_from_le_bytes(&context_key), DERIVE_KEY_MATERIAL)
+    }
+
+    const fn with_key_and_flags(key: [u32; 8], flags: u32) -> Self {
+        Self {
+            key,
+            flags,
+            chunk: ChunkState::new(key, 0, flags),
+            cv_stack: [[0u32; 8]; 54],
+            cv_stack_len: 0,
+        }
+    }
+
+    /// Add `bytes` to the ongoing hash computation.
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            // the final chunk is compressed differently (it may be the root),
+            // so a full chunk is only finished once we know it is not the last one.
+            if self.chunk.len() == CHUNK_LEN {
+                let cv = self.chunk.output().chaining_value();
+                let total_chunks = self.chunk.counter + 1;
+                self.add_chunk_chaining_value(cv, total_chunks);
+                self.chunk = ChunkState::new(self.key, total_chunks, self.flags);
+            }
+
+            // hash whole chunks directly from the input, in parallel where possible.
+            if self.chunk.len() == 0 && bytes.len() > CHUNK_LEN {
+                let count = core::cmp::min((bytes.len() - 1) / CHUNK_LEN, MAX_PARALLEL_CHUNKS);
+                let (chunks, rest) = bytes.split_at(count * CHUNK_LEN);
+
+                let mut cvs = [[0u32; 8]; MAX_PARALLEL_CHUNKS];
+                let counter = self.chunk.counter;
+                blake3_hash_chunks(&self.key, counter, self.flags, chunks, &mut cvs[..count]);
+
+                for (i, cv) in cvs[..count].iter().enumerate() {
+                    self.add_chunk_chaining_value(*cv, counter + i as u64 + 1);
+                }
+                self.chunk = ChunkState::new(self.key, counter + count as u64, self.flags);
+                bytes = rest;
+                continue;
+            }
+
+            let take = core::cmp::min(CHUNK_LEN - self.chunk.len(), bytes.len());
+            let (taken, rest) = bytes.split_at(take);
+            self.chunk.update(taken);
+            bytes = rest;
+        }
+    }
+
+    /// Complete the BLAKE3 computation, returning the hash output.
+    pub fn finish(self) -> [u8; 32] {
+        let mut r = [0u8; 32];
+        self.finish_xof().read(&mut r);
+        r
+    }
+
+    /// Complete the input, and return a reader for an arbitrary-length output.
+    ///
+    /// The first 32 bytes of output are the same as [`Blake3Context::finish()`].
+    pub fn finish_xof(self) -> XofReader {
+        let mut output = self.chunk.output();
+        for cv in self.cv_stack[..self.cv_stack_len].iter().rev() {
+            output = Output::parent(cv, &output.chaining_value(), &self.key, self.flags);
+        }
+
+        XofReader {
+            output,
+            block: [0u8; 64],
+            counter: 0,
+            pos: BLOCK_LEN,
+        }
+    }
+
+    /// Add a completed chunk's chaining value, merging completed
+    /// subtrees.
+    ///
+    /// The number of trailing zero bits in `total_chunks` is the
+    /// number of subtrees that `cv` completes.
+    fn add_chunk_chaining_value(&mut self, mut cv: [u32; 8], mut total_chunks: u64) {
+        while total_chunks & 1 == 0 {
+            self.cv_stack_len -= 1;
+            cv = Output::parent(
+                &self.cv_stack[self.cv_stack_len],
+                &cv,
+                &self.key,
+                self.flags,
+            )
+            .chaining_value();
+            total_chunks >>= 1;
+        }
+        self.cv_stack[self.cv_stack_len] = cv;
+        self.cv_stack_len += 1;
+    }
+
+    /// The internal block size of BLAKE3.
+    pub const BLOCK_SZ: usize = BLOCK_LEN;
+}
+
+/// Output from BLAKE3 in extendable-output mode.
+///
+/// This yields an unbounded stream of output bytes.
+#[derive(Clone)]
+pub struct XofReader {
+    output: Output,
+    block: [u8; 64],
+    counter: u64,
+    pos: usize,
+}
+
+impl XofReader {
+    /// Fill `out` with the next `out.len()` bytes of output.
+    pub fn read(&mut self, out: &mut [u8]) {
+        for o in out.iter_mut() {
+            if self.pos == BLOCK_LEN {
+                self.block = self.output.root_output_block(self.counter);
+                self.counter += 1;
+                self.pos = 0;
+            }
+            *o = self.block[self.pos];
+            self.pos += 1;
+        }
+    }
+}
+
+/// The inputs to a compression, from which the chaining value
+/// or root output can be produced.
+#[derive(Clone)]
+struct Output {
+    input_cv: [u32; 8],
+    block: [u8; BLOCK_LEN],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn parent(left: &[u32; 8], right: &[u32; 8], key: &[u32; 8], flags: u32) -> Self {
+        let mut block = [0u8; BLOCK_LEN];
+        for (bytes, word) in block
+            .chunks_exact_mut(4)
+            .zip(left.iter().chain(right.iter()))
+        {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+
+        Self {
+            input_cv: *key,
+            block,
+            counter: 0,
+            block_len: BLOCK_LEN as u32,
+            flags: PARENT | flags,
+        }
+    }
+
+    fn chaining_value(&self) -> [u32; 8] {
+        let out = blake3_compress(
+            &self.input_cv,
+            &self.block,
+            self.counter,
+            self.block_len,
+            self.flags,
+        );
+        out[..8].try_into().unwrap()
+    }
+
+    fn root_output_block(&self, counter: u64) -> [u8; BLOCK_LEN] {
+        let out = blake3_compress(
+            &self.input_cv,
+            &self.block,
+            counter,
+            self.block_len,
+            self.flags | ROOT,
+        );
+
+        let mut r = [0u8; BLOCK_LEN];
+        for (bytes, word) in r.chunks_exact_mut(4).zip(out.iter()) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        r
+    }
+}
+
+/// The state of a partially-hashed chunk.
+#[derive(Clone)]
+struct ChunkState {
+    cv: [u32; 8],
+    counter: u64,
+    block: [u8; BLOCK_LEN],
+    block_len: usize,
+    blocks_compressed: usize,
+    flags: u32,
+}
+
+impl ChunkState {
+    const fn new(key: [u32; 8], counter: u64, flags: u32) -> Self {
+        Self {
+            cv: key,
+            counter,
+            block: [0u8; BLOCK_LEN],
+            block_len: 0,
+            blocks_compressed: 0,
+            flags,
+        }
+    }
+
+    fn len(&self) -> usize {
+        BLOCK_LEN * self.blocks_compressed + self.block_len
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+
+    /// Add `bytes` to the chunk.  The chunk must not overflow.
+    fn update(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            if self.block_len == BLOCK_LEN {
+                let out = blake3_compress(
+                    &self.cv,
+                    &self.block,
+                    self.counter,
+                    BLOCK_LEN as u32,
+                    self.flags | self.start_flag(),
+                );
+                self.cv.copy_from_slice(&out[..8]);
+                self.blocks_compressed += 1;
+                self.block = [0u8; BLOCK_LEN];
+                self.block_len = 0;
+            }
+
+            let take = core::cmp::min(BLOCK_LEN - self.block_len, bytes.len());
+            let (taken, rest) = bytes.split_at(take);
+            self.block[self.block_len..self.block_len + take].copy_from_slice(taken);
+            self.block_len += take;
+            bytes = rest;
+        }
+    }
+
+    fn output(&self) -> Output {
+        Output {
+            input_cv: self.cv,
+            block: self.block,
+            counter: self.counter,
+            block_len: self.block_len as u32,
+            flags: self.flags | self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+fn words_from_le_bytes(bytes: &[u8; 32]) -> [u32; 8] {
+    let mut r = [0u32; 8];
+    for (w, bytes) in r.iter_mut().zip(bytes.chunks_exact(4)) {
+        *w = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    r
+}
+
+/// Upper bound on the number of chunks hashed at once by
+/// `blake3_hash_chunks`.
+const MAX_PARALLEL_CHUNKS: usize = 16;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blake3_abc() {
+        let mut ctx = Blake3Context::new();
+        ctx.update(b"abc");
+        assert_eq!(&ctx.finish(),
+                   b"\x64\x37\xb3\xac\x38\x46\x51\x33\xff\xb6\x3b\x75\x27\x3a\x8d\xb5\x48\xc5\x58\x46\x5d\x79\xdb\x03\xfd\x35\x9c\x6c\xd5\xbd\x9d\x85");
+
+        assert_eq!(&Blake3Context::new().finish(),
+                   b"\xaf\x13\x49\xb9\xf5\xf9\xa1\xa6\xa0\x40\x4d\xea\x36\xdc\xc9\x49\x9b\xcb\x25\xc9\xad\xc1\x12\xb7\xcc\x9a\x93\xca\xe4\x1f\x32\x62");
+    }
+
+    #[test]
+    fn blake3_all_lengths() {
+        let mut outer = Blake3Context::new();
+
+        for len in 0..1024 {
+            let mut inner = Blake3Context::new();
+
+            for _ in 0..len {
+                inner.update(&[len as u8]);
+            }
+
+            outer.update(&inner.finish());
+        }
+
+        assert_eq!(&outer.finish(),
+                   b"\x9c\x8e\xc8\x81\x23\xac\x5d\x64\x9e\x4c\x15\xa6\x5b\x52\x8f\x0b\x55\x5b\xcd\x8b\xf9\x2a\xfd\x05\x79\xb8\x56\x06\xb8\x8a\xb9\xa3");
+    }
+
+    #[test]
+    fn test_vectors() {
+        // a selection of the official BLAKE3 test vectors
+        // (`test_vectors/test_vectors.json` in the reference implementation),
+        // whose lengths cross chunk boundaries.
+        //
+        // each is (input length, hash, keyed_hash, derive_key), with 131-byte
+        // extended outputs.
+        const KEY: &[u8; 32] = b"whats the Elvish word for friend";
+        const CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+        const VECTORS: &[(usize, &str, &str, &str)] = &[
+            (
+                0,
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262e00f03e7b69af26b7faaf09fcd333050338ddfe085b8cc869ca98b206c08243a26f5487789e8f660afe6c99ef9e0c52b92e7393024a80459cf91f476f9ffdbda7001c22e159b402631f277ca96f2defdf1078282314e763699a31c5363165421cce14d",
+                "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26b18171a2f22a4b94822c701f107153dba24918c4bae4d2945c20ece13387627d3b73cbf97b797d5e59948c7ef788f54372df45e45e4293c7dc18c1d41144a9758be58960856be1eabbe22c2653190de560ca3b2ac4aa692a9210694254c371e851bc8f",
+                "2cc39783c223154fea8dfb7c1b1660f2ac2dcbd1c1de8277b0b0dd39b7e50d7d905630c8be290dfcf3e6842f13bddd573c098c3f17361f1f206b8cad9d088aa4a3f746752c6b0ce6a83b0da81d59649257cdf8eb3e9f7d4998e41021fac119deefb896224ac99f860011f73609e6e0e4540f93b273e56547dfd3aa1a035ba6689d89a0",
+            ),
+            (
+                1,
+                "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213c3a6cb8bf623e20cdb535f8d1a5ffb86342d9c0b64aca3bce1d31f60adfa137b358ad4d79f97b47c3d5e79f179df87a3b9776ef8325f8329886ba42f07fb138bb502f4081cbcec3195c5871e6c23e2cc97d3c69a613eba131e5f1351f3f1da786545e5",
+                "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b6568c0490609413006fbd428eb3fd14e7756d90f73a4725fad147f7bf70fd61c4e0cf7074885e92b0e3f125978b4154986d4fb202a3f331a3fb6cf349a3a70e49990f98fe4289761c8602c4e6ab1138d31d3b62218078b2f3ba9a88e1d08d0dd4cea11",
+                "b3e2e340a117a499c6cf2398a19ee0d29cca2bb7404c73063382693bf66cb06c5827b91bf889b6b97c5477f535361caefca0b5d8c4746441c57617111933158950670f9aa8a05d791daae10ac683cbef8faf897c84e6114a59d2173c3f417023a35d6983f2c7dfa57e7fc559ad751dbfb9ffab39c2ef8c4aafebc9ae973a64f0c76551",
+            ),
+            (
+                1023,
+                "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11a182d27a591b05592b15607500e1e8dd56bc6c7fc063715b7a1d737df5bad3339c56778957d870eb9717b57ea3d9fb68d1b55127bba6a906a4a24bbd5acb2d123a37b28f9e9a81bbaae360d58f85e5fc9d75f7c370a0cc09b6522d9c8d822f2f28f485",
+                "c951ecdf03288d0fcc96ee3413563d8a6d3589547f2c2fb36d9786470f1b9d6e890316d2e6d8b8c25b0a5b2180f94fb1a158ef508c3cde45e2966bd796a696d3e13efd86259d756387d9becf5c8bf1ce2192b87025152907b6d8cc33d17826d8b7b9bc97e38c3c85108ef09f013e01c229c20a83d9e8efac5b37470da28575fd755a10",
+                "74a16c1c3d44368a86e1ca6df64be6a2f64cce8f09220787450722d85725dea59c413264404661e9e4d955409dfe4ad3aa487871bcd454ed12abfe2c2b1eb7757588cf6cb18d2eccad49e018c0d0fec323bec82bf1644c6325717d13ea712e6840d3e6e730d35553f59eff5377a9c350bcc1556694b924b858f329c44ee64b884ef00d",
+            ),
+            (
+                1024,
+                "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af71cf8107265ecdaf8505b95d8fcec83a98a6a96ea5109d2c179c47a387ffbb404756f6eeae7883b446b70ebb144527c2075ab8ab204c0086bb22b7c93d465efc57f8d917f0b385c6df265e77003b85102967486ed57db5c5ca170ba441427ed9afa684e",
+                "75c46f6f3d9eb4f55ecaaee480db732e6c2105546f1e675003687c31719c7ba4a78bc838c72852d4f49c864acb7adafe2478e824afe51c8919d06168414c265f298a8094b1ad813a9b8614acabac321f24ce61c5a5346eb519520d38ecc43e89b5000236df0597243e4d2493fd626730e2ba17ac4d8824d09d1a4a8f57b8227778e2de",
+                "7356cd7720d5b66b6d0697eb3177d9f8d73a4a5c5e968896eb6a6896843027066c23b601d3ddfb391e90d5c8eccdef4ae2a264bce9e612ba15e2bc9d654af1481b2e75dbabe615974f1070bba84d56853265a34330b4766f8e75edd1f4a1650476c10802f22b64bd3919d246ba20a17558bc51c199efdec67e80a227251808d8ce5bad",
+            ),
+            (
+                1025,
+                "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444f4c4a22b4b399155358a994e52bf255de60035742ec71bd08ac275a1b51cc6bfe332b0ef84b409108cda080e6269ed4b3e2c3f7d722aa4cdc98d16deb554e5627be8f955c98e1d5f9565a9194cad0c4285f93700062d9595adb992ae68ff12800ab67a",
+                "357dc55de0c7e382c900fd6e320acc04146be01db6a8ce7210b7189bd664ea69362396b77fdc0d2634a552970843722066c3c15902ae5097e00ff53f1e116f1cd5352720113a837ab2452cafbde4d54085d9cf5d21ca613071551b25d52e69d6c81123872b6f19cd3bc1333edf0c52b94de23ba772cf82636cff4542540a7738d5b930",
+                "effaa245f065fbf82ac186839a249707c3bddf6d3fdda22d1b95a3c970379bcb5d31013a167509e9066273ab6e2123bc835b408b067d88f96addb550d96b6852dad38e320b9d940f86db74d398c770f462118b35d2724efa13da97194491d96dd37c3c09cbef665953f2ee85ec83d88b88d11547a6f911c8217cca46defa2751e7f3ad",
+            ),
+            (
+                2048,
+                "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a9a60bf80001410ec9eea6698cd537939fad4749edd484cb541aced55cd9bf54764d063f23f6f1e32e12958ba5cfeb1bf618ad094266d4fc3c968c2088f677454c288c67ba0dba337b9d91c7e1ba586dc9a5bc2d5e90c14f53a8863ac75655461cea8f9",
+                "879cf1fa2ea0e79126cb1063617a05b6ad9d0b696d0d757cf053439f60a99dd10173b961cd574288194b23ece278c330fbb8585485e74967f31352a8183aa782b2b22f26cdcadb61eed1a5bc144b8198fbb0c13abbf8e3192c145d0a5c21633b0ef86054f42809df823389ee40811a5910dcbd1018af31c3b43aa55201ed4edaac74fe",
+                "7b2945cb4fef70885cc5d78a87bf6f6207dd901ff239201351ffac04e1088a23e2c11a1ebffcea4d80447867b61badb1383d842d4e79645d48dd82ccba290769caa7af8eaa1bd78a2a5e6e94fbdab78d9c7b74e894879f6a515257ccf6f95056f4e25390f24f6b35ffbb74b766202569b1d797f2d4bd9d17524c720107f985f4ddc583",
+            ),
+            (
+                2049,
+                "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b687952256303096de31d71d74103403822a2e0bc1eb193e7aecc9643a76b7bbc0c9f9c52e8783aae98764ca468962b5c2ec92f0c74eb5448d519713e09413719431c802f948dd5d90425a4ecdadece9eb178d80f26efccae630734dff63340285adec2aed3b51073ad3",
+                "9f29700902f7c86e514ddc4df1e3049f258b2472b6dd5267f61bf13983b78dd5f9a88abfefdfa1e00b418971f2b39c64ca621e8eb37fceac57fd0c8fc8e117d43b81447be22d5d8186f8f5919ba6bcc6846bd7d50726c06d245672c2ad4f61702c646499ee1173daa061ffe15bf45a631e2946d616a4c345822f1151284712f76b2b0e",
+                "2ea477c5515cc3dd606512ee72bb3e0e758cfae7232826f35fb98ca1bcbdf27316d8e9e79081a80b046b60f6a263616f33ca464bd78d79fa18200d06c7fc9bffd808cc4755277a7d5e09da0f29ed150f6537ea9bed946227ff184cc66a72a5f8c1e4bd8b04e81cf40fe6dc4427ad5678311a61f4ffc39d195589bdbc670f63ae70f4b6",
+            ),
+            (
+                3073,
+                "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd39a27ae3b79d68d89da9bf25bc27139ae65a324918a5f9b7828181e52cf373c84f35b639b7fccbb985b6f2fa56aea0c18f531203497b8bbd3a07ceb5926f1cab74d14bd66486d9a91eba99059a98bd1cd25876b2af5a76c3e9eed554ed72ea952b603bf",
+                "68dede9bef00ba89e43f31a6825f4cf433389fedae75c04ee9f0cf16a427c95a96d6da3fe985054d3478865be9a092250839a697bbda74e279e8a9e69f0025e4cfddd6cfb434b1cd9543aaf97c635d1b451a4386041e4bb100f5e45407cbbc24fa53ea2de3536ccb329e4eb9466ec37093a42cf62b82903c696a93a50b702c80f3c3c5",
+                "72613c9ec9ff7e40f8f5c173784c532ad852e827dba2bf85b2ab4b76f7079081576288e552647a9d86481c2cae75c2dd4e7c5195fb9ada1ef50e9c5098c249d743929191441301c69e1f48505a4305ec1778450ee48b8e69dc23a25960fe33070ea549119599760a8a2d28aeca06b8c5e9ba58bc19e11fe57b6ee98aa44b2a8e6b14a5",
+            ),
+            (
+                8193,
+                "bab6c09cb8ce8cf459261398d2e7aef35700bf488116ceb94a36d0f5f1b7bc3bb2282aa69be089359ea1154b9a9286c4a56af4de975a9aa4a5c497654914d279bea60bb6d2cf7225a2fa0ff5ef56bbe4b149f3ed15860f78b4e2ad04e158e375c1e0c0b551cd7dfc82f1b155c11b6b3ed51ec9edb30d133653bb5709d1dbd55f4e1ff6",
+                "954a2a75420c8d6547e3ba5b98d963e6fa6491addc8c023189cc519821b4a1f5f03228648fd983aef045c2fa8290934b0866b615f585149587dda2299039965328835a2b18f1d63b7e300fc76ff260b571839fe44876a4eae66cbac8c67694411ed7e09df51068a22c6e67d6d3dd2cca8ff12e3275384006c80f4db68023f24eebba57",
+                "af1e0346e389b17c23200270a64aa4e1ead98c61695d917de7d5b00491c9b0f12f20a01d6d622edf3de026a4db4e4526225debb93c1237934d71c7340bb5916158cbdafe9ac3225476b6ab57a12357db3abbad7a26c6e66290e44034fb08a20a8d0ec264f309994d2810c49cfba6989d7abb095897459f5425adb48aba07c5fb3c83c0",
+            ),
+            (
+                102400,
+                "bc3e3d41a1146b069abffad3c0d44860cf664390afce4d9661f7902e7943e085e01c59dab908c04c3342b816941a26d69c2605ebee5ec5291cc55e15b76146e6745f0601156c3596cb75065a9c57f35585a52e1ac70f69131c23d611ce11ee4ab1ec2c009012d236648e77be9295dd0426f29b764d65de58eb7d01dd42248204f45f8e",
+                "1c35d1a5811083fd7119f5d5d1ba027b4d01c0c6c49fb6ff2cf75393ea5db4a7f9dbdd3e1d81dcbca3ba241bb18760f207710b751846faaeb9dff8262710999a59b2aa1aca298a032d94eacfadf1aa192418eb54808db23b56e34213266aa08499a16b354f018fc4967d05f8b9d2ad87a7278337be9693fc638a3bfdbe314574ee6fc4",
+                "4652cff7a3f385a6103b5c260fc1593e13c778dbe608efb092fe7ee69df6e9c6d83a3e041bc3a48df2879f4a0a3ed40e7c961c73eff740f3117a0504c2dff4786d44fb17f1549eb0ba585e40ec29bf7732f0b7e286ff8acddc4cb1e23b87ff5d824a986458dcc6a04ac83969b80637562953df51ed1a7e90a7926924d2763778be8560",
+            ),
+        ];
+
+        for (len, hash, keyed_hash, derive_key) in VECTORS {
+            println!("input length {len}");
+            let input = (0..*len).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+
+            for (mut ctx, expected) in [
+                (Blake3Context::new(), hash),
+                (Blake3Context::new_keyed(KEY), keyed_hash),
+                (Blake3Context::new_derive_key(CONTEXT), derive_key),
+            ] {
+                let expected = hex::decode(expected).unwrap();
+                ctx.update(&input);
+                assert_eq!(&ctx.clone().finish()[..], &expected[..32]);
+
+                let mut out = [0u8; 131];
+                ctx.finish_xof().read(&mut out);
+                assert_eq!(&out[..], &expected[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_input() {
+        // irregular pieces, which mix the per-chunk and parallel
+        // paths in `update`
+        let input = (0..102400).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        for piece in [1, 63, 64, 1000, 1024, 3000, 8191, 20000] {
+            let mut ctx = Blake3Context::new();
+            for chunk in input.chunks(piece) {
+                ctx.update(chunk);
+            }
+            assert_eq!(&ctx.finish(),
+                       b"\xbc\x3e\x3d\x41\xa1\x14\x6b\x06\x9a\xbf\xfa\xd3\xc0\xd4\x48\x60\xcf\x66\x43\x90\xaf\xce\x4d\x96\x61\xf7\x90\x2e\x79\x43\xe0\x85");
+        }
+    }
+
+    #[test]
+    fn incremental_output() {
+        let mut ctx = Blake3Context::new();
+        ctx.update(b"abc");
+        let mut reader = ctx.finish_xof();
+
+        let mut out = [0u8; 131];
+        for chunk in out.chunks_mut(33) {
+            reader.read(chunk);
+        }
+        assert_eq!(&out[96..],
+                   b"\x83\x10\x5f\x01\x33\x84\xf2\xf1\x1d\x38\x87\x9c\x98\x5d\x47\x00\x38\x04\xb9\x05\xf0\xc3\x89\x75\xe2\x8d\x36\x80\x4b\xb6\x0d\x8c\x30\x36\x53");
+    }
+}
@@ -0,0 +1,346 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+use crate::error::Error;
+use crate::low;
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
+
+mod primes;
+
+/// A finite field Diffie-Hellman group from RFC7919.
+///
+/// All groups use the generator 2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Group {
+    /// The 2048-bit group `ffdhe2048`.
+    Ffdhe2048,
+    /// The 3072-bit group `ffdhe3072`.
+    Ffdhe3072,
+    /// The 4096-bit group `ffdhe4096`.
+    Ffdhe4096,
+    /// The 6144-bit group `ffdhe6144`.
+    Ffdhe6144,
+    /// The 8192-bit group `ffdhe8192`.
+    Ffdhe8192,
+}
+
+impl Group {
+    /// The length of the prime `p`, in bytes.
+    ///
+    /// Public keys and shared secrets are always this long.
+    pub fn prime_len(&self) -> usize {
+        self.prime().len()
+    }
+
+    fn prime(&self) -> &'static [u8] {
+        match self {
+            Self::Ffdhe2048 => &primes::FFDHE2048,
+            Self::Ffdhe3072 => &primes::FFDHE3072,
+            Self::Ffdhe4096 => &primes::FFDHE4096,
+            Self::Ffdhe6144 => &primes::FFDHE6144,
+            Self::Ffdhe8192 => &primes::FFDHE8192,
+        }
+    }
+
+    /// The length of private exponents, in bytes.
+    ///
+    /// RFC7919 appendix A gives the estimated strength of each group.
+    /// Private exponents are at least twice that length, rounded up to
+    /// a whole number of words.  That is much shorter than `q`, which
+    /// makes key agreement considerably faster.
+    fn exponent_len(&self) -> usize {
+        match self {
+            Self::Ffdhe2048 => 32,
+            Self::Ffdhe3072 => 40,
+            Self::Ffdhe4096 => 48,
+            Self::Ffdhe6144 => 48,
+            Self::Ffdhe8192 => 56,
+        }
+    }
+
+    fn modulus(&self) -> Modulus {
+        let p = FfdhePosInt::from_bytes(self.prime()).unwrap();
+        let montifier = p.montifier();
+        let p0 = p.mont_neg_inverse();
+        Modulus { p, montifier, p0 }
+    }
+}
+
+struct Modulus {
+    p: FfdhePosInt,
+    montifier: FfdhePosInt,
+    p0: u64,
+}
+
+impl Modulus {
+    fn exp(&self, base: &FfdhePosInt, exponent: &FfdhePosInt) -> FfdhePosInt {
+        base.mont_exp(exponent, &self.p, &self.montifier, self.p0)
+    }
+}
+
+/// A finite field Diffie-Hellman private key.
+pub struct PrivateKey {
+    group: Group,
+    x: low::SecretPosInt<MAX_WORDS>,
+}
+
+impl PrivateKey {
+    /// Generate a new key in `group` using the default [`RandomSource`].
+    ///
+    /// Fails only if the random source fails.
+    #[cfg(feature = "std")]
+    pub fn new_random(group: Group) -> Result<Self, Error> {
+        Self::new_random_with_rng(group, &mut DefaultRandom)
+    }
+
+    /// Generate a new key in `group` using `rng`.
+    ///
+    /// Fails only if `rng` fails.
+    pub fn new_random_with_rng(group: Group, rng: &mut dyn RandomSource) -> Result<Self, Error> {
+        let _ = low::Entry::new_secret();
+        let mut bytes = [0u8; MAX_EXPONENT_LEN];
+        let bytes = &mut bytes[..group.exponent_len()];
+        rng.fill(bytes)?;
+
+        // setting the top bit means `x` is non-zero, and has a fixed
+        // width.
+        bytes[0] |= 0x80;
+        let x = FfdhePosInt::from_bytes(bytes).map(low::SecretPosInt::from);
+        low::zeroise(bytes);
+
+        Ok(Self { group, x: x? })
+    }
+
+    /// The group of this key.
+    pub fn group(&self) -> Group {
+        self.group
+    }
+
+    /// Compute the associated public key.
+    pub fn public_key(&self) -> PublicKey {
+        let _ = low::Entry::new_secret();
+        let modulus = self.group.modulus();
+        let mut g = FfdhePosInt::from_bytes(&[GENERATOR]).unwrap();
+        g.expand(&modulus.p);
+        PublicKey::from_posint(self.group, &modulus.exp(&g, &self.x))
+    }
+
+    /// Do the Diffie-Hellman operation.
+    ///
+    /// `peer` is the peer's public key, and must be in the same group
+    /// as this key.  Returns a shared secret, which is as long as the
+    /// group's prime (ie, left-padded with zeroes).
+    pub fn diffie_hellman(self, peer: &PublicKey) -> Result<SharedSecret, Error> {
+        let _ = low::Entry::new_secret();
+        if peer.group != self.group {
+            return Err(Error::OutOfRange);
+        }
+
+        let modulus = self.group.modulus();
+        let mut y = FfdhePosInt::from_bytes(peer.as_bytes())?;
+        y.expand(&modulus.p);
+        let z = low::SecretPosInt::from(modulus.exp(&y, &self.x));
+
+        let mut secret = SharedSecret {
+            buf: [0u8; MAX_PRIME_LEN],
+            len: self.group.prime_len(),
+        };
+        z.to_bytes(&mut secret.buf)?;
+        Ok(secret)
+    }
+}
+
+/// A finite field Diffie-Hellman public key.
+#[derive(Clone)]
+pub struct PublicKey {
+    group: Group,
+    buf: [u8; MAX_PRIME_LEN],
+}
+
+impl PublicKey {
+    /// Decode a public key in `group` from big-endian `bytes`.
+    ///
+    /// `bytes` may be shorter than the group's prime, but not longer.
+    ///
+    /// As required by RFC7919 section 5.1, this checks that
+    /// `1 < y < p - 1`.  Since `p` is a safe prime, that excludes the
+    /// only small subgroup.
+    pub fn from_bytes(group: Group, bytes: &[u8]) -> Result<Self, Error> {
+        let _ = low::Entry::new_public();
+        if bytes.len() > group.prime_len() {
+            return Err(Error::WrongLength);
+        }
+
+        let mut y = FfdhePosInt::from_bytes(bytes)?;
+        let one = FfdhePosInt::from_bytes(&[1]).unwrap();
+
+        // p is odd, so p - 1 is p with the bottom bit cleared.
+        let mut p_minus_1 = [0u8; MAX_PRIME_LEN];
+        let p_minus_1 = &mut p_minus_1[..group.prime_len()];
+        p_minus_1.copy_from_slice(group.prime());
+        p_minus_1[group.prime_len() - 1] &= !1;
+        let p_minus_1 = FfdhePosInt::from_bytes(p_minus_1).unwrap();
+
+        if !one.less_than(&y) || !y.less_than(&p_minus_1) {
+            return Err(Error::OutOfRange);
+        }
+
+        y.expand(&p_minus_1);
+        Ok(Self::from_posint(group, &y))
+    }
+
+    /// `y` must be as wide as the group's prime.
+    fn from_posint(group: Group, y: &FfdhePosInt) -> Self {
+        let mut buf = [0u8; MAX_PRIME_LEN];
+        y.to_bytes(&mut buf[..group.prime_len()]).unwrap();
+        Self { group, buf }
+    }
+
+    /// The group of this key.
+    pub fn group(&self) -> Group {
+        self.group
+    }
+
+    /// The encoding of this public key: big-endian, and as long as
+    /// the group's prime.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.group.prime_len()]
+    }
+}
+
+/// A shared secret resulting from a finite field Diffie-Hellman operation.
+///
+/// This is big-endian, and as long as the group's prime.
+pub struct SharedSecret {
+    buf: [u8; MAX_PRIME_LEN],
+    len: usize,
+}
+
+impl AsRef<[u8]> for SharedSecret {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        low::zeroise(&mut self.buf);
+    }
+}
+
+const GENERATOR: u8 = 2;
+const MAX_PRIME_LEN: usize = 8192 / 8;
+const MAX_WORDS: usize = MAX_PRIME_LEN / 8;
+const MAX_EXPONENT_LEN: usize = 56;
+
+type FfdhePosInt = low::PosInt<MAX_WORDS>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mid::rng::SliceRandomSource;
+
+    const ALL_GROUPS: [Group; 5] = [
+        Group::Ffdhe2048,
+        Group::Ffdhe3072,
+        Group::Ffdhe4096,
+        Group::Ffdhe6144,
+        Group::Ffdhe8192,
+    ];
+
+    fn key(group: Group, byte: u8) -> PrivateKey {
+        PrivateKey::new_random_with_rng(group, &mut SliceRandomSource(&[byte; MAX_EXPONENT_LEN]))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_vector() {
+        // computed with python: x = 0x9111..11, then pow(2, x, p), and
+        // similarly for the peer with 0xa222..22.
+        let a =
+            PrivateKey::new_random_with_rng(Group::Ffdhe2048, &mut SliceRandomSource(&[0x11; 32]))
+                .unwrap();
+        let b =
+            PrivateKey::new_random_with_rng(Group::Ffdhe2048, &mut SliceRandomSource(&[0x22; 32]))
+                .unwrap();
+        assert_eq!(a.public_key().as_bytes(), YA);
+        let z = a.diffie_hellman(&b.public_key()).unwrap();
+        assert_eq!(z.as_ref(), Z);
+    }
+
+    const YA: &[u8] = b"\xb5\x26\x22\xc1\xb4\x3a\x40\x15\x72\x2b\xba\x98\x17\x82\x23\xa4\x17\x3c\x7b\x3c\x39\xff\x21\xce\x28\xce\xb9\x97\x7d\x59\x4f\x7c\x49\x14\xe5\xb7\xcf\x56\x16\x12\xd6\x95\xab\xa6\xcd\xf3\x32\x5a\x28\xec\x8f\x2d\x9a\x82\x20\xfd\x0d\x49\xbb\x68\xa7\xf1\x74\xc8\xcc\xc0\xc3\x0a\xce\x8d\x35\x6f\xee\x62\x23\xc5\xfc\xba\xaf\x1d\xa3\x16\x81\xde\x8e\x30\xab\x5b\x0d\x91\xbc\x9a\x2a\x27\x09\x80\xba\x6e\xdd\x99\x97\x24\x01\x32\x4f\xbb\x9c\xd3\x8f\xb3\x91\x27\x10\x81\x1c\xb5\x40\x3a\xab\x74\xd4\x20\x37\xe6\x25\xd9\x20\xe9\xe1\x9e\x58\xf2\x1a\x86\x45\x0b\x45\x87\x06\x60\x99\x4f\xec\xac\xe7\x11\x9e\xb0\x2f\x10\xc1\xe3\xed\x46\xc9\xb5\x19\xe8\x78\x7e\x3c\x8a\x8d\x8e\x90\x56\x62\x23\xa6\x0c\xbb\xc7\xa8\x9c\xe4\x1c\xf1\xde\x0c\x14\x3e\xce\x6a\x8f\x5b\xab\xda\x3f\xa7\x7e\xa9\xe0\x11\x08\x3d\x56\xc5\x4f\xe7\x0a\x21\x38\x7b\xf9\x03\x73\x25\x07\x3c\x25\xd7\xeb\xa4\x8f\x4d\x9f\x24\x9a\xfc\x07\xe8\xb5\x15\xe2\x3c\x7a\x22\x0c\x8e\x7e\x5f\x2f\x47\xe1\xed\x01\x76\xf8\x8c\x5a\xcc\x5f\x9b\xcb\x43\x5c\x21\x52\xbd\x62\x00\x27\x6e\x08\xbe\x2e";
+    const Z: &[u8] = b"\xa9\x70\xff\xeb\x09\xb4\x16\x69\xf5\x74\xdd\x42\xc3\x82\x98\x55\x6f\x35\x4e\xbf\x5b\x82\x44\xe5\x5d\x4f\x47\xa0\x8e\x21\x80\xbe\x25\xb8\x27\x47\x6e\x33\x2d\x19\x19\xdf\x7c\xea\xe0\xf1\xc0\xd9\x17\x2f\x32\x28\x40\x25\x91\x9e\xe4\xe9\x05\xd2\x13\x31\x27\x3a\x58\x5f\x84\x82\xd6\xb8\xc4\x2f\x56\x59\x1d\xc6\x1f\x75\xd4\xfa\x32\xbb\x07\x8c\xdc\x5c\x79\x95\x5c\xd4\x87\x35\x1e\xef\xb5\x82\xef\xee\xe9\xac\xc3\x15\xd6\x1d\xb8\x05\x98\xfa\xeb\xcc\x47\x39\xf9\x63\x7c\x4a\xd3\xb5\x0b\x50\xf7\x90\x8d\x94\xf9\xad\x14\xad\x06\x70\x55\xcb\xf2\xef\xe0\x3e\xdf\x9c\x99\x52\x35\x1d\xb5\xa5\x0e\xfe\xf5\x4b\x0f\xde\xa3\x4d\x10\xa4\x49\xcf\xac\x59\x57\x2a\xcb\xf7\x19\x5c\xb1\xaa\x05\x4c\xbe\x1e\xee\xe9\xaa\x18\x00\x66\x13\x7b\xaa\x71\xb2\x48\xf3\x3e\xa1\x97\x29\x33\x74\x59\x04\xb9\x33\xdc\xba\x4d\x2e\x3e\xf1\x25\x75\xac\x4f\x77\xcc\x60\x8b\x86\x9b\xfe\x06\xed\x5a\x23\x6c\x98\xe6\x33\x1b\x92\x15\xde\x50\x9b\xdd\xe2\x61\x55\x83\xaf\x74\x73\x42\x94\x1b\xf6\x8c\xf3\xb3\xfc\x4c\x53\x34\xae\x7f\x03\xab\x99\xdd\xbf\x2d\x47\x4c\x58\xb7\x11";
+
+    #[test]
+    fn pairwise() {
+        for group in ALL_GROUPS {
+            let a = key(group, 0x33);
+            let b = key(group, 0x44);
+            let a_pub = PublicKey::from_bytes(group, a.public_key().as_bytes()).unwrap();
+            let b_pub = PublicKey::from_bytes(group, b.public_key().as_bytes()).unwrap();
+            let ab = a.diffie_hellman(&b_pub).unwrap();
+            let ba = b.diffie_hellman(&a_pub).unwrap();
+            assert_eq!(ab.as_ref().len(), group.prime_len());
+            assert_eq!(ab.as_ref(), ba.as_ref());
+        }
+    }
+
+    #[test]
+    fn public_key_range() {
+        for group in ALL_GROUPS {
+            let len = group.prime_len();
+            let mut p_minus_1 = group.prime().to_vec();
+            p_minus_1[len - 1] &= !1;
+            let mut p_minus_2 = group.prime().to_vec();
+            p_minus_2[len - 1] -= 2;
+
+            assert_eq!(
+                PublicKey::from_bytes(group, &[]).err(),
+                Some(Error::OutOfRange)
+            );
+            assert_eq!(
+                PublicKey::from_bytes(group, &[0]).err(),
+                Some(Error::OutOfRange)
+            );
+            assert_eq!(
+                PublicKey::from_bytes(group, &[1]).err(),
+                Some(Error::OutOfRange)
+            );
+            assert_eq!(
+                PublicKey::from_bytes(group, &p_minus_1).err(),
+                Some(Error::OutOfRange)
+            );
+            assert_eq!(
+                PublicKey::from_bytes(group, group.prime()).err(),
+                Some(Error::OutOfRange)
+            );
+            assert_eq!(
+                PublicKey::from_bytes(group, &vec![0xff; len]).err(),
+                Some(Error::OutOfRange)
+            );
+            assert_eq!(
+                PublicKey::from_bytes(group, &vec![0x01; len + 1]).err(),
+                Some(Error::WrongLength)
+            );
+
+            // the extremes of the allowed range
+            let two = PublicKey::from_bytes(group, &[2]).unwrap();
+            assert_eq!(two.as_bytes().len(), len);
+            assert_eq!(two.as_bytes()[len - 1], 2);
+            assert!(two.as_bytes()[..len - 1].iter().all(|b| *b == 0));
+            PublicKey::from_bytes(group, &p_minus_2).unwrap();
+        }
+    }
+
+    #[test]
+    fn mismatched_groups() {
+        let a = key(Group::Ffdhe2048, 0x33);
+        let b = key(Group::Ffdhe3072, 0x44);
+        assert_eq!(
+            a.diffie_hellman(&b.public_key()).err(),
+            Some(Error::OutOfRange)
+        );
+    }
+}
@@ -3,13 +3,39 @@
 
 #![deny(unsafe_code)]
 
+#[cfg(feature = "aes-gcm")]
 pub(super) mod aes_gcm;
+#[cfg(feature = "blake2")]
+pub mod blake2;
+#[cfg(feature = "blake3")]
+pub mod blake3;
+#[cfg(feature = "chacha20poly1305")]
 pub(super) mod chacha20poly1305;
+#[cfg(feature = "ffdhe")]
+pub(super) mod ffdhe;
+#[cfg(feature = "p256")]
 pub(super) mod p256;
+#[cfg(feature = "p384")]
 pub(super) mod p384;
+#[cfg(any(feature = "aes", feature = "chacha20poly1305"))]
+pub(super) mod quic;
 pub(super) mod rng;
+#[cfg(feature = "rsa")]
 pub(super) mod rsa_priv;
+#[cfg(feature = "rsa")]
 pub(super) mod rsa_pub;
+#[cfg(feature = "sha1")]
+pub mod sha1;
+#[cfg(any(feature = "sha256", feature = "sha512"))]
 pub mod sha2;
+// SHA-1 alone needs only the padding.
+#[cfg(all(feature = "sha1", not(any(feature = "sha256", feature = "sha512"))))]
+mod sha2;
+#[cfg(feature = "sha3")]
+pub mod sha3;
+#[cfg(feature = "std")]
+pub(super) mod thread_rng;
+#[cfg(any(feature = "p256", feature = "p384", feature = "x25519"))]
 pub(super) mod util;
+#[cfg(feature = "x25519")]
 pub(super) mod x25519;
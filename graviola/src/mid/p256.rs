@@ -3,11 +3,14 @@
 
 use super::util;
 use crate::low;
-use crate::mid::rng::{RandomSource, SystemRandom};
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
 use crate::Error;
 
 use core::fmt;
 
+#[cfg(not(feature = "runtime-tables"))]
 mod precomp;
 
 /// A P-256 public key.
@@ -96,9 +99,26 @@ impl PrivateKey {
         Scalar::from_bytes_checked(bytes).map(|scalar| Self { scalar })
     }
 
-    /// Generate a new random private key using the system RNG.
+    /// Generate a new random private key using the default [`RandomSource`].
+    #[cfg(feature = "std")]
     pub fn new_random() -> Result<Self, Error> {
-        Self::generate(&mut SystemRandom)
+        Self::new_random_with_rng(&mut DefaultRandom)
+    }
+
+    /// Generate a new random private key using `rng`.
+    ///
+    /// Fails only if `rng` fails.
+    pub fn new_random_with_rng(rng: &mut dyn RandomSource) -> Result<Self, Error> {
+        let _ = low::Entry::new_secret();
+        for _ in 0..64 {
+            let mut r = [0u8; 32];
+            rng.fill(&mut r)?;
+            if let Ok(p) = Self::from_bytes(&r) {
+                return Ok(p);
+            }
+        }
+
+        Err(Error::RngFailed)
     }
 
     /// Return a fixed-length encoding of this private key's value.
@@ -145,19 +165,6 @@ impl PrivateKey {
         }
     }
 
-    pub(crate) fn generate(rng: &mut dyn RandomSource) -> Result<Self, Error> {
-        let _ = low::Entry::new_secret();
-        for _ in 0..64 {
-            let mut r = [0u8; 32];
-            rng.fill(&mut r)?;
-            if let Ok(p) = Self::from_bytes(&r) {
-                return Ok(p);
-            }
-        }
-
-        Err(Error::RngFailed)
-    }
-
     pub(crate) fn raw_ecdsa_sign(&self, k: &Self, e: &Scalar, r: &Scalar) -> Scalar {
         // this is (e + r * d) / k
         let lhs_mont = self
@@ -318,6 +325,37 @@ impl AffineMontPoint {
         r
     }
 
+    /// Precomputes wNAF form (with 𝑤=6) for the point `self`.
+    ///
+    /// This produces the same table as `public_precomp_wnaf_7_slow`, but
+    /// is fast enough to use at runtime.
+    #[cfg(any(test, feature = "runtime-tables"))]
+    fn public_precomp_wnaf_7(&self) -> Box<AffineMontPointTableW7> {
+        let mut table: Box<AffineMontPointTableW7> =
+            vec![[0u64; 512]; 37].into_boxed_slice().try_into().unwrap();
+
+        let mut first = JacobianMontPoint::from_affine(self);
+
+        for row in table.iter_mut() {
+            // nb. `p256_montjadd` does not handle doubling, so 2 * first is
+            // computed separately
+            let double = first.double();
+            let mut point = first;
+
+            for (i, out) in row.chunks_exact_mut(8).enumerate() {
+                out.copy_from_slice(&point.as_affine().xy);
+                point = match i {
+                    0 => double,
+                    _ => point.add(&first),
+                };
+            }
+
+            first.double_inplace_n(7);
+        }
+
+        table
+    }
+
     fn public_precomp_wnaf_5(&self) -> JacobianMontPointTableW5 {
         let mut r = [JacobianMontPoint::zero(); 16];
 
@@ -410,7 +448,7 @@ impl JacobianMontPoint {
     }
 
     fn base_multiply(scalar: &Scalar) -> Self {
-        Self::multiply_wnaf_7(scalar, &precomp::CURVE_GENERATOR_PRECOMP_WNAF_7)
+        Self::multiply_wnaf_7(scalar, generator_precomp_wnaf_7())
     }
 
     fn multiply_wnaf_7(scalar: &Scalar, precomp: &AffineMontPointTableW7) -> Self {
@@ -427,13 +465,11 @@ impl JacobianMontPoint {
             digit,
         ));
 
-        let mut index = 1;
-        for (digit, sign) in terms {
+        for (index, (digit, sign)) in (1..).zip(terms) {
             let mut tmp = AffineMontPoint::lookup_w7(&precomp[index], digit);
             tmp.maybe_negate_y(sign);
 
             result.add_inplace_affine(&tmp);
-            index += 1;
         }
 
         result
@@ -670,7 +706,7 @@ impl Scalar {
 
     #[cfg(test)]
     fn small_u64(v: u64) -> Self {
-        Scalar([v, 0, 0, 0])
+        Self([v, 0, 0, 0])
     }
 
     /// Private test for zero
@@ -920,6 +956,18 @@ impl Iterator for BoothRecodeW5 {
 /// Each 'row' builds in 7 successive doublings.
 type AffineMontPointTableW7 = [[u64; 512]; 37];
 
+#[cfg(not(feature = "runtime-tables"))]
+fn generator_precomp_wnaf_7() -> &'static AffineMontPointTableW7 {
+    &precomp::CURVE_GENERATOR_PRECOMP_WNAF_7
+}
+
+/// The table is computed at first use, rather than being included in the binary.
+#[cfg(feature = "runtime-tables")]
+fn generator_precomp_wnaf_7() -> &'static AffineMontPointTableW7 {
+    static TABLE: std::sync::OnceLock<Box<AffineMontPointTableW7>> = std::sync::OnceLock::new();
+    TABLE.get_or_init(|| CURVE_GENERATOR.public_precomp_wnaf_7())
+}
+
 type JacobianMontPointTableW5 = [u64; 192];
 
 const CURVE_A_MONT: FieldElement = FieldElement([
@@ -957,24 +1005,25 @@ const CURVE_ORDER_MM: [u64; 4] = [
     0x66e12d94f3d95620,
 ];
 
+#[cfg(any(test, feature = "runtime-tables"))]
+const CURVE_GENERATOR: AffineMontPoint = AffineMontPoint {
+    xy: [
+        0x79e7_30d4_18a9_143c,
+        0x75ba_95fc_5fed_b601,
+        0x79fb_732b_7762_2510,
+        0x1890_5f76_a537_55c6,
+        0xddf2_5357_ce95_560a,
+        0x8b4a_b8e4_ba19_e45c,
+        0xd2e8_8688_dd21_f325,
+        0x8571_ff18_2588_5d85,
+    ],
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use core::mem::size_of_val;
 
-    const CURVE_GENERATOR: AffineMontPoint = AffineMontPoint {
-        xy: [
-            0x79e7_30d4_18a9_143c,
-            0x75ba_95fc_5fed_b601,
-            0x79fb_732b_7762_2510,
-            0x1890_5f76_a537_55c6,
-            0xddf2_5357_ce95_560a,
-            0x8b4a_b8e4_ba19_e45c,
-            0xd2e8_8688_dd21_f325,
-            0x8571_ff18_2588_5d85,
-        ],
-    };
-
     #[test]
     fn generator_on_curve() {
         println!("{CURVE_GENERATOR:x?}");
@@ -990,6 +1039,22 @@ mod tests {
         println!("fmt {:x?}", r.as_bytes_uncompressed());
     }
 
+    #[test]
+    fn new_random_with_rng() {
+        // the first candidate exceeds `n`, so is rejected
+        let mut rng = crate::mid::rng::SliceRandomSource(&[0xff; 64]);
+        assert_eq!(
+            PrivateKey::new_random_with_rng(&mut rng).unwrap_err(),
+            Error::RngFailed
+        );
+
+        let mut random = [0xff; 64];
+        random[32..].fill(0x11);
+        let mut rng = crate::mid::rng::SliceRandomSource(&random);
+        let key = PrivateKey::new_random_with_rng(&mut rng).unwrap();
+        assert_eq!(key.as_bytes(), [0x11; 32]);
+    }
+
     #[test]
     fn point_double() {
         let mut p = JacobianMontPoint::from_affine(&CURVE_GENERATOR);
@@ -1105,6 +1170,21 @@ mod tests {
         println!("montify n = {:016x?}", Scalar::montifier().0);
     }
 
+    #[test]
+    fn runtime_precomp_wnaf_7() {
+        let fast = CURVE_GENERATOR.public_precomp_wnaf_7();
+        let slow = CURVE_GENERATOR.public_precomp_wnaf_7_slow();
+
+        for (fast_row, slow_row) in fast.iter().zip(slow.iter()) {
+            for (fast, slow) in fast_row.chunks_exact(8).zip(slow_row.iter()) {
+                assert_eq!(fast, &slow.xy);
+            }
+        }
+
+        #[cfg(not(feature = "runtime-tables"))]
+        assert_eq!(&*fast, &precomp::CURVE_GENERATOR_PRECOMP_WNAF_7);
+    }
+
     #[test]
     fn base_point_precomp_wnaf_7() {
         let precomp = CURVE_GENERATOR.public_precomp_wnaf_7_slow();
@@ -1112,19 +1192,19 @@ mod tests {
         println!(
             "pub(super) static CURVE_GENERATOR_PRECOMP_WNAF_7: super::AffineMontPointTableW7 = ["
         );
-        for w in 0..37 {
+        for (w, window) in precomp.iter().enumerate() {
             println!("    // 1G..64G << {}", w * 7);
             println!("    [");
-            for p in 0..64 {
-                for j in 0..8 {
-                    println!("            0x{:016x}, ", precomp[w][p].xy[j]);
+            for point in window {
+                for xy in point.xy {
+                    println!("            0x{:016x}, ", xy);
                 }
             }
             println!("    ],");
         }
         println!("];");
 
-        println!("");
+        println!();
         println!("table size is {} bytes", size_of_val(&precomp));
     }
 }
@@ -3,7 +3,9 @@
 
 use super::util;
 use crate::low;
-use crate::mid::rng::{RandomSource, SystemRandom};
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
 use crate::Error;
 
 use core::fmt;
@@ -96,9 +98,26 @@ impl PrivateKey {
         Scalar::from_bytes_checked(bytes).map(|scalar| Self { scalar })
     }
 
-    /// Generate a new random private key using the system RNG.
+    /// Generate a new random private key using the default [`RandomSource`].
+    #[cfg(feature = "std")]
     pub fn new_random() -> Result<Self, Error> {
-        Self::generate(&mut SystemRandom)
+        Self::new_random_with_rng(&mut DefaultRandom)
+    }
+
+    /// Generate a new random private key using `rng`.
+    ///
+    /// Fails only if `rng` fails.
+    pub fn new_random_with_rng(rng: &mut dyn RandomSource) -> Result<Self, Error> {
+        let _ = low::Entry::new_secret();
+        for _ in 0..64 {
+            let mut r = [0u8; 48];
+            rng.fill(&mut r)?;
+            if let Ok(p) = Self::from_bytes(&r) {
+                return Ok(p);
+            }
+        }
+
+        Err(Error::RngFailed)
     }
 
     /// Return a fixed-length encoding of this private key's value.
@@ -144,19 +163,6 @@ impl PrivateKey {
         }
     }
 
-    pub(crate) fn generate(rng: &mut dyn RandomSource) -> Result<Self, Error> {
-        let _ = low::Entry::new_secret();
-        for _ in 0..64 {
-            let mut r = [0u8; 48];
-            rng.fill(&mut r)?;
-            if let Ok(p) = Self::from_bytes(&r) {
-                return Ok(p);
-            }
-        }
-
-        Err(Error::RngFailed)
-    }
-
     pub(crate) fn raw_ecdsa_sign(&self, k: &Self, e: &Scalar, r: &Scalar) -> Scalar {
         // this is (e + r * d) / k
         let lhs_mont = self
@@ -596,7 +602,7 @@ impl Scalar {
 
     #[cfg(test)]
     fn small_u64(v: u64) -> Self {
-        Scalar([v, 0, 0, 0, 0, 0])
+        Self([v, 0, 0, 0, 0, 0])
     }
 
     /// Private test for zero
@@ -995,17 +1001,15 @@ mod tests {
         println!(
             "pub(super) static CURVE_GENERATOR_PRECOMP_WNAF_5: super::JacobianMontPointTableW5 = ["
         );
-        let mut i = 1;
-        for point in precomp.chunks_exact(18) {
+        for (i, point) in (1..).zip(precomp.chunks_exact(18)) {
             println!("// {i}G");
             for p in point {
                 println!("            0x{:016x}, ", p);
             }
-            i += 1;
         }
         println!("];");
 
-        println!("");
+        println!();
         println!("table size is {} bytes", size_of_val(&precomp));
     }
 }
@@ -0,0 +1,114 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+#[cfg(feature = "chacha20poly1305")]
+use crate::low::chacha20::ChaCha20;
+#[cfg(feature = "aes")]
+use crate::low::AesKey;
+use crate::low::{zeroise, Entry};
+
+/// A QUIC header protection key using AES.
+///
+/// See [RFC9001 section 5.4.3](https://datatracker.ietf.org/doc/html/rfc9001#section-5.4.3).
+#[cfg(feature = "aes")]
+pub struct AesHeaderProtection {
+    key: AesKey,
+}
+
+#[cfg(feature = "aes")]
+impl AesHeaderProtection {
+    /// Create a new `AesHeaderProtection` object.
+    ///
+    /// `key` must be 16 or 32 bytes, corresponding
+    /// to AES-128 or AES-256.  This function panics otherwise.
+    pub fn new(key: &[u8]) -> Self {
+        let _ = Entry::new_secret();
+        Self {
+            key: AesKey::new(key),
+        }
+    }
+
+    /// Computes the header protection mask for the given `sample`.
+    ///
+    /// This is the first five bytes of the AES encryption of `sample`.
+    pub fn mask(&self, sample: &[u8; 16]) -> [u8; 5] {
+        let _ = Entry::new_secret();
+        let mut block = *sample;
+        self.key.encrypt_block(&mut block);
+        let mut mask = [0u8; 5];
+        mask.copy_from_slice(&block[..5]);
+        zeroise(&mut block);
+        mask
+    }
+}
+
+/// A QUIC header protection key using ChaCha20.
+///
+/// See [RFC9001 section 5.4.4](https://datatracker.ietf.org/doc/html/rfc9001#section-5.4.4).
+#[cfg(feature = "chacha20poly1305")]
+pub struct ChaCha20HeaderProtection {
+    key: [u8; 32],
+}
+
+#[cfg(feature = "chacha20poly1305")]
+impl ChaCha20HeaderProtection {
+    /// Create a new `ChaCha20HeaderProtection` from 32 bytes of key material.
+    pub fn new(key: [u8; 32]) -> Self {
+        Self { key }
+    }
+
+    /// Computes the header protection mask for the given `sample`.
+    ///
+    /// The first four bytes of `sample` are the little-endian block
+    /// counter, and the remaining twelve bytes are the nonce.  The mask
+    /// is the first five bytes of the ChaCha20 keystream.
+    pub fn mask(&self, sample: &[u8; 16]) -> [u8; 5] {
+        let _ = Entry::new_secret();
+        let mut mask = [0u8; 5];
+        ChaCha20::new(&self.key, sample).cipher(&mut mask);
+        mask
+    }
+}
+
+#[cfg(feature = "chacha20poly1305")]
+impl Drop for ChaCha20HeaderProtection {
+    fn drop(&mut self) {
+        zeroise(&mut self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "aes")]
+    #[test]
+    fn aes_test_vector() {
+        // from RFC9001 appendix A.2
+        let k = AesHeaderProtection::new(&[
+            0x9f, 0x50, 0x44, 0x9e, 0x04, 0xa0, 0xe8, 0x10, 0x28, 0x3a, 0x1e, 0x99, 0x33, 0xad,
+            0xed, 0xd2,
+        ]);
+        let mask = k.mask(&[
+            0xd1, 0xb1, 0xc9, 0x8d, 0xd7, 0x68, 0x9f, 0xb8, 0xec, 0x11, 0xd2, 0x42, 0xb1, 0x23,
+            0xdc, 0x9b,
+        ]);
+        assert_eq!(mask, [0x43, 0x7b, 0x9a, 0xec, 0x36]);
+    }
+
+    #[cfg(feature = "chacha20poly1305")]
+    #[test]
+    fn chacha20_test_vector() {
+        // from RFC9001 appendix A.5
+        let k = ChaCha20HeaderProtection::new([
+            0x25, 0xa2, 0x82, 0xb9, 0xe8, 0x2f, 0x06, 0xf2, 0x1f, 0x48, 0x89, 0x17, 0xa4, 0xfc,
+            0x8f, 0x1b, 0x73, 0x57, 0x36, 0x85, 0x60, 0x85, 0x97, 0xd0, 0xef, 0xcb, 0x07, 0x6b,
+            0x0a, 0xb7, 0xa7, 0xa4,
+        ]);
+        let mask = k.mask(&[
+            0x5e, 0x5c, 0xd5, 0x5c, 0x41, 0xf6, 0x90, 0x80, 0x57, 0x5d, 0x79, 0x99, 0xc2, 0x5a,
+            0x5b, 0xfb,
+        ]);
+        assert_eq!(mask, [0xae, 0xfe, 0xfe, 0x7d, 0x03]);
+    }
+}
@@ -1,28 +1,114 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
 use crate::Error;
 
 /// The library's external and internal trait for all
 /// random consumption.
+///
+/// Every API that consumes randomness has a variant (named
+/// `*_with_rng`) taking a `&mut dyn RandomSource`.  Other APIs
+/// use [`SystemRandom`], or [`ThreadRandom`] if the `thread-rng`
+/// crate feature is enabled.  Those other APIs are only available
+/// with the `std` crate feature.
+///
+/// [`ThreadRandom`]: crate::random::ThreadRandom
 pub trait RandomSource {
+    /// Fills the entirety of `out` with random bytes.
+    ///
+    /// Implementations must return an error rather than
+    /// partially filling `out`.
     fn fill(&mut self, out: &mut [u8]) -> Result<(), Error>;
 }
 
-/// Random generation from the system entropy source via
-/// the `getrandom` crate.
-pub(crate) struct SystemRandom;
+/// The default source of random bytes.
+///
+/// This is the entropy source installed with [`install_entropy_source()`],
+/// if any.  Otherwise it is the system entropy source, via the `getrandom`
+/// crate.
+#[cfg(feature = "std")]
+pub struct SystemRandom;
+
+#[cfg(feature = "thread-rng")]
+pub(crate) use super::thread_rng::ThreadRandom as DefaultRandom;
+#[cfg(all(feature = "std", not(feature = "thread-rng")))]
+pub(crate) use SystemRandom as DefaultRandom;
 
+#[cfg(feature = "std")]
 impl RandomSource for SystemRandom {
     fn fill(&mut self, out: &mut [u8]) -> Result<(), Error> {
-        getrandom::getrandom(out).map_err(|_| Error::RngFailed)
+        if !entropy_source_installed() {
+            return getrandom::getrandom(out).map_err(|_| Error::RngFailed);
+        }
+
+        // nb. the source may have been removed since the check above.
+        let mut installed = ENTROPY_SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+        match installed.as_mut() {
+            Some(source) => source.fill(out),
+            None => getrandom::getrandom(out).map_err(|_| Error::RngFailed),
+        }
     }
 }
 
+/// Installs a process-wide entropy source, replacing `getrandom`.
+///
+/// After this call, all randomness used by this crate (other than
+/// that passed explicitly to `*_with_rng` functions) comes from
+/// `source`.  This is intended for deterministic testing, and for
+/// environments where `getrandom` is unavailable.
+///
+/// While a source is installed, [`ThreadRandom`] passes requests
+/// directly to it.
+///
+/// [`ThreadRandom`]: crate::random::ThreadRandom
+///
+/// The previously-installed source is returned, if any.
+///
+/// `source` must not itself use [`SystemRandom`] (directly or via
+/// any other API of this crate): that will deadlock.
+#[cfg(feature = "std")]
+pub fn install_entropy_source(
+    source: Box<dyn RandomSource + Send>,
+) -> Option<Box<dyn RandomSource + Send>> {
+    let mut installed = ENTROPY_SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+    ENTROPY_SOURCE_INSTALLED.store(true, Ordering::SeqCst);
+    installed.replace(source)
+}
+
+/// Removes any entropy source installed by [`install_entropy_source()`].
+///
+/// [`SystemRandom`] reverts to using `getrandom`.  The
+/// previously-installed source is returned, if any.
+#[cfg(feature = "std")]
+pub fn remove_entropy_source() -> Option<Box<dyn RandomSource + Send>> {
+    let mut installed = ENTROPY_SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+    ENTROPY_SOURCE_INSTALLED.store(false, Ordering::SeqCst);
+    installed.take()
+}
+
+/// Returns true if an entropy source is installed.
+///
+/// This avoids taking the lock on `ENTROPY_SOURCE`.
+#[cfg(feature = "std")]
+pub(crate) fn entropy_source_installed() -> bool {
+    ENTROPY_SOURCE_INSTALLED.load(Ordering::SeqCst)
+}
+
+#[cfg(feature = "std")]
+static ENTROPY_SOURCE: Mutex<Option<Box<dyn RandomSource + Send>>> = Mutex::new(None);
+#[cfg(feature = "std")]
+static ENTROPY_SOURCE_INSTALLED: AtomicBool = AtomicBool::new(false);
+
 /// Random generation from a slice.
 ///
 /// Returns an error once exhausted.  Intended only for testing.
 #[cfg(test)]
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
 pub(crate) struct SliceRandomSource<'a>(pub &'a [u8]);
 
 #[cfg(test)]
@@ -1,17 +1,19 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
-use super::rsa_pub::RsaPublicKey;
-use crate::error::Error;
+use super::rsa_pub::{write_part, RsaPublicKey};
+use crate::error::{Error, KeyFormatError};
 use crate::low;
 
 pub(crate) struct RsaPrivateKey {
     public: RsaPublicKey,
 
+    d: RsaSecretPosIntModN,
     p: RsaPosIntModP,
     q: RsaPosIntModP,
     dp: RsaPosIntModP,
     dq: RsaPosIntModP,
+    iqmp: RsaPosIntModP,
 
     iqmp_mont: RsaPosIntModP,
     p_montifier: RsaPosIntModP,
@@ -24,6 +26,7 @@ impl RsaPrivateKey {
     pub(crate) fn new(
         p: RsaPosIntModP,
         q: RsaPosIntModP,
+        d: RsaSecretPosIntModN,
         dp: RsaPosIntModP,
         dq: RsaPosIntModP,
         iqmp: RsaPosIntModP,
@@ -42,17 +45,38 @@ impl RsaPrivateKey {
 
         let public = RsaPublicKey::new(n, e)?;
         let p_montifier: RsaPosIntModP = p.montifier().into();
-        let q_montifier = q.montifier().into();
+        let q_montifier: RsaPosIntModP = q.montifier().into();
         let iqmp_mont = iqmp.to_montgomery(&p_montifier, &p).into();
         let p0 = p.mont_neg_inverse();
         let q0 = q.mont_neg_inverse();
 
+        // check the key parts are consistent, so a mismatched key is
+        // rejected here rather than when it is used.
+        let pq: RsaPosIntModN = low::PosInt::mul(&p, &q);
+        if !pq.equals(&public.n) {
+            return Err(KeyFormatError::MismatchedRsaModulus.into());
+        }
+
+        let n_montifier = public.n.montifier();
+        let n0 = public.n.mont_neg_inverse();
+        if !is_inverse_exponent(e, &d, &public.n, &n_montifier, n0)? {
+            return Err(KeyFormatError::MismatchedRsaPrivateExponent.into());
+        }
+
+        if !is_inverse_exponent(e, &dp, &p, &p_montifier, p0)?
+            || !is_inverse_exponent(e, &dq, &q, &q_montifier, q0)?
+        {
+            return Err(KeyFormatError::MismatchedRsaCrtExponent.into());
+        }
+
         Ok(Self {
             public,
+            d,
             p,
             q,
             dp,
             dq,
+            iqmp,
             iqmp_mont,
             p_montifier,
             q_montifier,
@@ -69,6 +93,25 @@ impl RsaPrivateKey {
         self.public.modulus_len_bytes()
     }
 
+    /// Writes the key parts to `out`, for encoding as a PKCS#1 `RSAPrivateKey`.
+    ///
+    /// The parts are returned in the order of that structure: `n`, `e`, `d`,
+    /// `p`, `q`, `dp`, `dq` and `iqmp`.  Each is big-endian, with a leading
+    /// zero byte so it may be interpreted as a two's complement integer.
+    pub(crate) fn write_parts<'a>(&self, mut out: &'a mut [u8]) -> Result<[&'a [u8]; 8], Error> {
+        let [n, e] = self.public.write_parts(&mut out)?;
+        Ok([
+            n,
+            e,
+            write_part(&self.d, &mut out)?,
+            write_part(&self.p, &mut out)?,
+            write_part(&self.q, &mut out)?,
+            write_part(&self.dp, &mut out)?,
+            write_part(&self.dq, &mut out)?,
+            write_part(&self.iqmp, &mut out)?,
+        ])
+    }
+
     /// returns c ^ d mod n
     ///
     /// (albeit via CRT)
@@ -117,6 +160,27 @@ impl RsaPrivateKey {
     }
 }
 
+/// Returns whether `x ^ (e * d) == x (mod n)`, for a fixed `x`.
+///
+/// This holds for all `x` if `d` is the inverse of `e` modulo
+/// the order of the multiplicative group mod `n`.
+fn is_inverse_exponent<const N: usize>(
+    e: u32,
+    d: &low::PosInt<N>,
+    n: &low::PosInt<N>,
+    n_montifier: &low::PosInt<N>,
+    n0: u64,
+) -> Result<bool, Error> {
+    let e = low::PosInt::from_bytes(&e.to_be_bytes())?;
+    let mut x = low::PosInt::from_bytes(&[2])?;
+    x.expand(n);
+
+    let y = x
+        .mont_exp(&e, n, n_montifier, n0)
+        .mont_exp(d, n, n_montifier, n0);
+    Ok(y.equals(&x))
+}
+
 impl Drop for RsaPrivateKey {
     fn drop(&mut self) {
         low::zeroise_value(&mut self.p0);
@@ -128,8 +192,16 @@ const MAX_PRIVATE_MODULUS_BITS: usize = 4096;
 const MAX_PRIVATE_MODULUS_WORDS: usize = MAX_PRIVATE_MODULUS_BITS / 64;
 pub(crate) const MAX_PRIVATE_MODULUS_BYTES: usize = MAX_PRIVATE_MODULUS_BITS / 8;
 
+/// The largest output of [`RsaPrivateKey::write_parts()`].
+///
+/// `n` and `d` may be twice the length of the primes, and `e` is a `u32`
+/// written as one word.  Each part has a leading zero byte.
+pub(crate) const MAX_PARTS_LEN: usize =
+    2 * (MAX_PRIVATE_MODULUS_BYTES * 2 + 1) + (8 + 1) + 5 * (MAX_PRIVATE_MODULUS_BYTES + 1);
+
 const MIN_PRIVATE_MODULUS_BITS: usize = 1024;
 const MIN_PRIVATE_MODULUS_BYTES: usize = MIN_PRIVATE_MODULUS_BITS / 8;
 
 type RsaPosIntModP = low::SecretPosInt<MAX_PRIVATE_MODULUS_WORDS>;
 type RsaPosIntModN = low::PosInt<{ MAX_PRIVATE_MODULUS_WORDS * 2 }>;
+type RsaSecretPosIntModN = low::SecretPosInt<{ MAX_PRIVATE_MODULUS_WORDS * 2 }>;
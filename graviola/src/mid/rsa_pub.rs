@@ -1,13 +1,15 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
+use core::mem;
+
 use crate::error::Error;
 use crate::low;
 
 #[derive(Clone, Debug)]
 pub(crate) struct RsaPublicKey {
     pub(crate) n: RsaPosInt,
-    e: u32,
+    pub(crate) e: u32,
 
     montifier: RsaPosInt,
     one: RsaPosInt,
@@ -42,6 +44,16 @@ impl RsaPublicKey {
         })
     }
 
+    /// Writes the key parts to `out`, for encoding as a PKCS#1 `RSAPublicKey`.
+    ///
+    /// The parts are `n` and `e`, with the same format as
+    /// [`super::rsa_priv::RsaPrivateKey::write_parts()`].  `out` is advanced
+    /// past the written parts.
+    pub(crate) fn write_parts<'a>(&self, out: &mut &'a mut [u8]) -> Result<[&'a [u8]; 2], Error> {
+        let e = low::PosInt::<1>::from_bytes(&self.e.to_be_bytes())?;
+        Ok([write_part(&self.n, out)?, write_part(&e, out)?])
+    }
+
     pub(crate) fn modulus_len_bytes(&self) -> usize {
         self.n.len_bytes()
     }
@@ -86,6 +98,23 @@ const MAX_PUBLIC_MODULUS_BITS: usize = 8192;
 const MAX_PUBLIC_MODULUS_WORDS: usize = MAX_PUBLIC_MODULUS_BITS / 64;
 pub(crate) const MAX_PUBLIC_MODULUS_BYTES: usize = MAX_PUBLIC_MODULUS_BITS / 8;
 
+/// Writes a zero byte then `value` to the front of `out`, and advances `out`.
+pub(crate) fn write_part<'a, const N: usize>(
+    value: &low::PosInt<N>,
+    out: &mut &'a mut [u8],
+) -> Result<&'a [u8], Error> {
+    let buf = mem::take(out);
+    let (zero, rest) = buf.split_first_mut().ok_or(Error::OutOfRange)?;
+    *zero = 0;
+    let len = value.to_bytes(rest)?.len() + 1;
+    let (part, rest) = buf.split_at_mut(len);
+    *out = rest;
+    Ok(part)
+}
+
+/// The largest output of [`RsaPublicKey::write_parts()`].
+pub(crate) const MAX_PARTS_LEN: usize = (MAX_PUBLIC_MODULUS_BYTES + 1) + (8 + 1);
+
 const MIN_PUBLIC_MODULUS_BITS: usize = 2048;
 const MIN_PUBLIC_MODULUS_BYTES: usize = MIN_PUBLIC_MODULUS_BITS / 8;
 
@@ -0,0 +1,327 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! SHA-1, with collision detection.
+//!
+//! SHA-1 is described in [FIPS180](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+//! It is broken: practical collisions are known.  It is provided here
+//! only so that legacy signatures and HMAC-SHA1 tags can be verified.
+//!
+//! Every block is checked for the disturbance vectors used by known
+//! collision attacks (including SHAttered and SHA-mbles) using the
+//! method of Marc Stevens and Dan Shumow, as described in
+//! ["Speeding up detection of SHA-1 collision attacks using unavoidable attack conditions"](https://marc-stevens.nl/research/papers/C13-S.pdf).
+//! If a block is found to be one half of a collision, the output is
+//! changed so that it no longer collides (this is the same "safe hash"
+//! behaviour as `sha1collisiondetection` and git), and
+//! [`Sha1Context::collision_detected`] returns true.
+//!
+//! Detection branches on the data being hashed, so it is disabled when
+//! SHA-1 is used with HMAC: there the input includes the secret key,
+//! and HMAC does not rely on collision resistance.
+
+use super::sha2::MD_PADDING;
+use crate::low::Blockwise;
+
+mod ubc_check;
+
+/// A context for incremental computation of SHA-1.
+#[derive(Clone)]
+pub struct Sha1Context {
+    h: [u32; 5],
+    blockwise: Blockwise<64>,
+    nblocks: usize,
+    detect_collisions: bool,
+    collision: bool,
+}
+
+impl Sha1Context {
+    /// Start a new SHA-1 hash computation.
+    pub const fn new() -> Self {
+        Self {
+            h: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0],
+            blockwise: Blockwise::new(),
+            nblocks: 0,
+            detect_collisions: true,
+            collision: false,
+        }
+    }
+
+    /// Start a new SHA-1 hash computation, without collision detection.
+    ///
+    /// This is for hashing secret data, where the timing of
+    /// detection would leak information about it.
+    pub(crate) const fn new_without_collision_detection() -> Self {
+        let mut ctx = Self::new();
+        ctx.detect_collisions = false;
+        ctx
+    }
+
+    /// Add `bytes` to the ongoing hash computation.
+    pub fn update(&mut self, bytes: &[u8]) {
+        let bytes = self.blockwise.add_leading(bytes);
+
+        if let Some(block) = self.blockwise.take() {
+            self.update_blocks(&block);
+        }
+
+        let (whole_blocks, remainder) = {
+            let whole_len = bytes.len() - (bytes.len() & (Self::BLOCK_SZ - 1));
+            (&bytes[..whole_len], &bytes[whole_len..])
+        };
+
+        self.update_blocks(whole_blocks);
+
+        self.blockwise.add_trailing(remainder);
+    }
+
+    /// Returns true if any block so far was found to be part of
+    /// a collision attack.
+    ///
+    /// In that case, the output of [`Sha1Context::finish`] will not
+    /// be the true SHA-1 value of the input.
+    pub fn collision_detected(&self) -> bool {
+        self.collision
+    }
+
+    /// Complete the SHA-1 computation, returning the hash output.
+    pub fn finish(mut self) -> [u8; 20] {
+        let bytes = self
+            .nblocks
+            .checked_mul(Self::BLOCK_SZ)
+            .and_then(|bytes| bytes.checked_add(self.blockwise.used()))
+            .unwrap();
+
+        let bits = bytes
+            .checked_mul(8)
+            .expect("excess data processed by hash function");
+
+        let padding_len = Self::BLOCK_SZ - ((bytes + 8) % Self::BLOCK_SZ);
+        self.update(&MD_PADDING[..padding_len]);
+        self.update(&(bits as u64).to_be_bytes());
+        debug_assert_eq!(self.blockwise.used(), 0);
+
+        let mut r = [0u8; 20];
+        for (out, state) in r.chunks_exact_mut(4).zip(self.h.iter()) {
+            out.copy_from_slice(&state.to_be_bytes());
+        }
+        r
+    }
+
+    fn update_blocks(&mut self, blocks: &[u8]) {
+        debug_assert!(blocks.len() % Self::BLOCK_SZ == 0);
+        if self.detect_collisions {
+            for block in blocks.chunks_exact(Self::BLOCK_SZ) {
+                self.update_block_with_detection(block);
+            }
+        } else {
+            crate::low::sha1_compress_blocks(&mut self.h, blocks);
+        }
+        self.nblocks = self.nblocks.saturating_add(blocks.len() / Self::BLOCK_SZ);
+    }
+
+    fn update_block_with_detection(&mut self, block: &[u8]) {
+        let w = expand(block);
+        let ihv_in = self.h;
+        crate::low::sha1_compress_blocks(&mut self.h, block);
+
+        let mask = ubc_check::ubc_check(&w);
+        if mask != 0 && self.detect_collision(&ihv_in, &w, mask) {
+            self.collision = true;
+            // "safe hash": make the output differ from the colliding
+            // message by compressing the block twice more.
+            crate::low::sha1_compress_blocks(&mut self.h, block);
+            crate::low::sha1_compress_blocks(&mut self.h, block);
+        }
+    }
+
+    /// Check whether the block with expanded message `w` (which took
+    /// `ihv_in` to `self.h`) is half of a near-collision for any
+    /// disturbance vector in `mask`.
+    ///
+    /// This is done by recompressing from the intermediate state at each
+    /// disturbance vector's test step, using the message with the
+    /// vector's differences applied: backwards to find the chaining
+    /// value the other block must have had, and forwards to find the
+    /// output it would have given.
+    fn detect_collision(&self, ihv_in: &[u32; 5], w: &[u32; 80], mask: u32) -> bool {
+        // the states before steps 58 and 65
+        let mut state = *ihv_in;
+        for (t, w) in w.iter().enumerate().take(58) {
+            step(&mut state, t, *w);
+        }
+        let state_58 = state;
+        for (t, w) in w.iter().enumerate().take(65).skip(58) {
+            step(&mut state, t, *w);
+        }
+        let state_65 = state;
+
+        for (i, dv) in ubc_check::SHA1_DVS.iter().enumerate() {
+            if mask & (1 << i) == 0 {
+                continue;
+            }
+
+            let mut w2 = [0u32; 80];
+            for ((w2, w), dm) in w2.iter_mut().zip(w.iter()).zip(dv.dm.iter()) {
+                *w2 = w ^ dm;
+            }
+
+            let start = match dv.test_step {
+                58 => state_58,
+                _ => state_65,
+            };
+
+            let mut ihv_in2 = start;
+            for t in (0..dv.test_step).rev() {
+                unstep(&mut ihv_in2, t, w2[t]);
+            }
+
+            let mut ihv_out2 = start;
+            for (t, w2) in w2.iter().enumerate().skip(dv.test_step) {
+                step(&mut ihv_out2, t, *w2);
+            }
+            for (out, input) in ihv_out2.iter_mut().zip(ihv_in2.iter()) {
+                *out = out.wrapping_add(*input);
+            }
+
+            if ihv_out2 == self.h {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// The internal block size of SHA-1.
+    pub const BLOCK_SZ: usize = 64;
+}
+
+/// Compute the full message expansion of `block`.
+fn expand(block: &[u8]) -> [u32; 80] {
+    let mut w = [0u32; 80];
+    for (w, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    for t in 16..80 {
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+    w
+}
+
+fn f_and_k(t: usize, b: u32, c: u32, d: u32) -> u32 {
+    match t {
+        0..=19 => ((b & c) | (!b & d)).wrapping_add(0x5a827999),
+        20..=39 => (b ^ c ^ d).wrapping_add(0x6ed9eba1),
+        40..=59 => ((b & c) | (b & d) | (c & d)).wrapping_add(0x8f1bbcdc),
+        _ => (b ^ c ^ d).wrapping_add(0xca62c1d6),
+    }
+}
+
+/// Step `t` of the SHA-1 compression function, on `state` (which holds
+/// `[a, b, c, d, e]` before step `t`).
+fn step(state: &mut [u32; 5], t: usize, w: u32) {
+    let [a, b, c, d, e] = *state;
+    let tmp = a
+        .rotate_left(5)
+        .wrapping_add(f_and_k(t, b, c, d))
+        .wrapping_add(e)
+        .wrapping_add(w);
+    *state = [tmp, a, b.rotate_left(30), c, d];
+}
+
+/// The inverse of `step`: takes `state` after step `t` to the state
+/// before it.
+fn unstep(state: &mut [u32; 5], t: usize, w: u32) {
+    let [tmp, a, b, c, d] = *state;
+    let b = b.rotate_right(30);
+    let e = tmp
+        .wrapping_sub(a.rotate_left(5))
+        .wrapping_sub(f_and_k(t, b, c, d))
+        .wrapping_sub(w);
+    *state = [a, b, c, d, e];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hello() {
+        let mut ctx = Sha1Context::new();
+        ctx.update(b"hello");
+        assert_eq!(
+            &ctx.finish(),
+            b"\xaa\xf4\xc6\x1d\xdc\xc5\xe8\xa2\xda\xbe\xde\x0f\x3b\x48\x2c\xd9\xae\xa9\x43\x4d"
+        );
+    }
+
+    #[test]
+    fn sha1_all_lengths() {
+        let mut outer = Sha1Context::new();
+        for len in 0..1024 {
+            let mut inner = Sha1Context::new();
+            inner.update(&b"a".repeat(len));
+            outer.update(&inner.finish());
+        }
+        assert_eq!(
+            &outer.finish(),
+            b"\x7f\x05\xe3\x2c\x57\xdb\x35\xec\x21\x3f\x03\x3f\x3e\x2b\x00\xf0\xb2\x92\x82\x30"
+        );
+    }
+
+    #[test]
+    fn shattered() {
+        // the first 320 bytes of the SHAttered PDFs collide under SHA-1
+        let mut ctx = Sha1Context::new();
+        ctx.update(include_bytes!("../testdata/sha1-shattered-1.bin"));
+        assert!(ctx.collision_detected());
+        assert_eq!(
+            &ctx.finish(),
+            b"\x71\x17\xb3\xcb\x92\x25\xaa\xf0\xd8\xef\x1a\x40\xe4\x93\x95\x7b\x0b\xf8\x69\x3d"
+        );
+
+        let mut ctx = Sha1Context::new();
+        ctx.update(include_bytes!("../testdata/sha1-shattered-2.bin"));
+        assert!(ctx.collision_detected());
+        assert_eq!(
+            &ctx.finish(),
+            b"\x29\xf3\x8a\xe9\xfd\x98\xe2\x93\x11\x20\xfa\x0b\xf2\x13\xe0\x24\x25\x0d\x3f\x6a"
+        );
+    }
+
+    #[test]
+    fn shattered_without_collision_detection() {
+        for input in [
+            &include_bytes!("../testdata/sha1-shattered-1.bin")[..],
+            &include_bytes!("../testdata/sha1-shattered-2.bin")[..],
+        ] {
+            let mut ctx = Sha1Context::new_without_collision_detection();
+            ctx.update(input);
+            assert!(!ctx.collision_detected());
+            assert_eq!(
+                &ctx.finish(),
+                b"\xf9\x2d\x74\xe3\x87\x45\x87\xaa\xf4\x43\xd1\xdb\x96\x1d\x4e\x26\xdd\xe1\x3e\x9c"
+            );
+        }
+    }
+
+    #[test]
+    fn sha_mbles() {
+        // chosen-prefix collision from <https://sha-mbles.github.io/>
+        let mut ctx = Sha1Context::new();
+        ctx.update(include_bytes!("../testdata/sha1-sha-mbles-1.bin"));
+        assert!(ctx.collision_detected());
+        assert_eq!(
+            &ctx.finish(),
+            b"\x4f\x3d\x9b\xe4\xa4\x72\xc4\xda\xe8\x3c\x63\x14\xaa\x6c\x36\xa0\x64\xc1\xfd\x14"
+        );
+
+        let mut ctx = Sha1Context::new();
+        ctx.update(include_bytes!("../testdata/sha1-sha-mbles-2.bin"));
+        assert!(ctx.collision_detected());
+        assert_eq!(
+            &ctx.finish(),
+            b"\x9e\xd5\xd7\x7a\x4f\x48\xbe\x1d\xbf\x3e\x9e\x15\x65\x07\x33\xeb\x85\x08\x97\xf2"
+        );
+    }
+}
@@ -6,9 +6,11 @@
 //! This is SHA256, SHA384, and SHA512.
 //! These are all described in [FIPS180](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
 
+#[cfg(any(feature = "sha256", feature = "sha512"))]
 use crate::low::Blockwise;
 
 /// A context for incremental computation of SHA256.
+#[cfg(feature = "sha256")]
 #[derive(Clone)]
 pub struct Sha256Context {
     h: [u32; 8],
@@ -16,6 +18,7 @@ pub struct Sha256Context {
     nblocks: usize,
 }
 
+#[cfg(feature = "sha256")]
 impl Sha256Context {
     /// Start a new SHA256 hash computation.
     pub const fn new() -> Self {
@@ -83,12 +86,50 @@ impl Sha256Context {
     pub const BLOCK_SZ: usize = 64;
 }
 
+/// A context for incremental computation of SHA224.
+#[cfg(feature = "sha256")]
+#[derive(Clone)]
+pub struct Sha224Context {
+    inner: Sha256Context,
+}
+
+#[cfg(feature = "sha256")]
+impl Sha224Context {
+    /// Start a new SHA224 hash computation.
+    pub const fn new() -> Self {
+        Self {
+            inner: Sha256Context {
+                h: [
+                    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511,
+                    0x64f98fa7, 0xbefa4fa4,
+                ],
+                blockwise: Blockwise::new(),
+                nblocks: 0,
+            },
+        }
+    }
+
+    /// Add `bytes` to the ongoing hash computation.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.inner.update(bytes)
+    }
+
+    /// Complete the SHA224 computation, returning the hash output.
+    pub fn finish(self) -> [u8; 28] {
+        let inner = self.inner.finish();
+        // SAFETY: 28 is less than 32.
+        inner[..28].try_into().unwrap()
+    }
+}
+
 /// A context for incremental computation of SHA384.
+#[cfg(feature = "sha512")]
 #[derive(Clone)]
 pub struct Sha384Context {
     inner: Sha512Context,
 }
 
+#[cfg(feature = "sha512")]
 impl Sha384Context {
     /// Start a new SHA384 hash computation.
     pub const fn new() -> Self {
@@ -123,7 +164,53 @@ impl Sha384Context {
     }
 }
 
+/// A context for incremental computation of SHA512/256.
+///
+/// This is SHA512 with a different initial state, truncated to 256 bits.
+/// It is _not_ the same as truncating the output of SHA512.
+#[cfg(feature = "sha512")]
+#[derive(Clone)]
+pub struct Sha512_256Context {
+    inner: Sha512Context,
+}
+
+#[cfg(feature = "sha512")]
+impl Sha512_256Context {
+    /// Start a new SHA512/256 hash computation.
+    pub const fn new() -> Self {
+        Self {
+            inner: Sha512Context {
+                h: [
+                    0x22312194fc2bf72c,
+                    0x9f555fa3c84c64c2,
+                    0x2393b86b6f53b151,
+                    0x963877195940eabd,
+                    0x96283ee2a88effe3,
+                    0xbe5e1e2553863992,
+                    0x2b0199fc2c85b8aa,
+                    0x0eb72ddc81c52ca2,
+                ],
+                blockwise: Blockwise::new(),
+                nblocks: 0,
+            },
+        }
+    }
+
+    /// Add `bytes` to the ongoing hash computation.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.inner.update(bytes)
+    }
+
+    /// Complete the SHA512/256 computation, returning the hash output.
+    pub fn finish(self) -> [u8; 32] {
+        let inner = self.inner.finish();
+        // SAFETY: 32 is less than 64.
+        inner[..32].try_into().unwrap()
+    }
+}
+
 /// A context for incremental computation of SHA512.
+#[cfg(feature = "sha512")]
 #[derive(Clone)]
 pub struct Sha512Context {
     h: [u64; 8],
@@ -131,6 +218,7 @@ pub struct Sha512Context {
     nblocks: usize,
 }
 
+#[cfg(feature = "sha512")]
 impl Sha512Context {
     /// Start a new SHA512 hash computation.
     pub const fn new() -> Self {
@@ -204,7 +292,7 @@ impl Sha512Context {
     pub const BLOCK_SZ: usize = 128;
 }
 
-static MD_PADDING: [u8; 128] = [
+pub(super) static MD_PADDING: [u8; 128] = [
     0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -215,10 +303,11 @@ static MD_PADDING: [u8; 128] = [
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
 ];
 
-#[cfg(test)]
+#[cfg(all(test, any(feature = "sha256", feature = "sha512")))]
 mod tests {
     use super::*;
 
+    #[cfg(all(feature = "sha256", feature = "sha512"))]
     #[test]
     fn hello() {
         let mut ctx = Sha256Context::new();
@@ -226,12 +315,23 @@ mod tests {
         assert_eq!(&ctx.finish(),
                    b"\x2c\xf2\x4d\xba\x5f\xb0\xa3\x0e\x26\xe8\x3b\x2a\xc5\xb9\xe2\x9e\x1b\x16\x1e\x5c\x1f\xa7\x42\x5e\x73\x04\x33\x62\x93\x8b\x98\x24");
 
+        let mut ctx = Sha224Context::new();
+        ctx.update(b"hello");
+        assert_eq!(&ctx.finish(),
+                   b"\xea\x09\xae\x9c\xc6\x76\x8c\x50\xfc\xee\x90\x3e\xd0\x54\x55\x6e\x5b\xfc\x83\x47\x90\x7f\x12\x59\x8a\xa2\x41\x93");
+
+        let mut ctx = Sha512_256Context::new();
+        ctx.update(b"hello");
+        assert_eq!(&ctx.finish(),
+                   b"\xe3\x0d\x87\xcf\xa2\xa7\x5d\xb5\x45\xea\xc4\xd6\x1b\xaf\x97\x03\x66\xa8\x35\x7c\x7f\x72\xfa\x95\xb5\x2d\x0a\xcc\xb6\x98\xf1\x3a");
+
         let mut ctx = Sha512Context::new();
         ctx.update(b"hello");
         assert_eq!(&ctx.finish(),
                    b"\x9b\x71\xd2\x24\xbd\x62\xf3\x78\x5d\x96\xd4\x6a\xd3\xea\x3d\x73\x31\x9b\xfb\xc2\x89\x0c\xaa\xda\xe2\xdf\xf7\x25\x19\x67\x3c\xa7\x23\x23\xc3\xd9\x9b\xa5\xc1\x1d\x7c\x7a\xcc\x6e\x14\xb8\xc5\xda\x0c\x46\x63\x47\x5c\x2e\x5c\x3a\xde\xf4\x6f\x73\xbc\xde\xc0\x43");
     }
 
+    #[cfg(feature = "sha512")]
     #[test]
     fn sha512_long() {
         let mut data = Vec::with_capacity(1024);
@@ -251,6 +351,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "sha256")]
     #[test]
     fn sha256_all_lengths() {
         // see cifra `vector_length` and associated
@@ -269,6 +370,7 @@ mod tests {
         assert_eq!(&outer.finish(),
                    b"\x55\x7b\xfd\xd5\xef\xda\xfd\x63\x06\x5e\xb7\x98\x87\xde\x86\xdb\x54\xc3\xfe\xdf\x7b\xcc\xcb\x97\x08\xfa\x87\xf0\x11\x87\x61\xdc");
     }
+    #[cfg(feature = "sha512")]
     #[test]
     fn sha512_all_lengths() {
         let mut outer = Sha512Context::new();
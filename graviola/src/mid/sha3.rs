@@ -0,0 +1,527 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+//! SHA-3, SHAKE, cSHAKE and KMAC.
+//!
+//! SHA-3 and SHAKE are standardized in [FIPS202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+//! cSHAKE and KMAC are standardized in [SP800-185](https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf).
+
+use crate::low::{ct_equal, keccak_f1600};
+use crate::Error;
+
+/// The Keccak sponge construction, over Keccak-f\[1600\].
+#[derive(Clone)]
+struct Sponge {
+    state: [u64; 25],
+    /// Rate in bytes.
+    rate: usize,
+    /// Position within the current block, in bytes.
+    pos: usize,
+}
+
+impl Sponge {
+    const fn new(rate: usize) -> Self {
+        Self {
+            state: [0u64; 25],
+            rate,
+            pos: 0,
+        }
+    }
+
+    fn absorb(&mut self, mut bytes: &[u8]) {
+        // complete any partial block
+        while self.pos != 0 && !bytes.is_empty() {
+            self.xor_byte(bytes[0]);
+            bytes = &bytes[1..];
+        }
+
+        // whole blocks
+        while bytes.len() >= self.rate {
+            let (block, rest) = bytes.split_at(self.rate);
+            for (lane, chunk) in self.state.iter_mut().zip(block.chunks_exact(8)) {
+                *lane ^= u64::from_le_bytes(chunk.try_into().unwrap());
+            }
+            keccak_f1600(&mut self.state);
+            bytes = rest;
+        }
+
+        for b in bytes {
+            self.xor_byte(*b);
+        }
+    }
+
+    /// Absorbs zeroes until the input is a multiple of the rate.
+    fn pad_zero(&mut self) {
+        if self.pos != 0 {
+            keccak_f1600(&mut self.state);
+            self.pos = 0;
+        }
+    }
+
+    /// Adds domain separation bits and padding, and starts squeezing.
+    fn finish(mut self, domain: u8) -> Self {
+        self.state[self.pos / 8] ^= (domain as u64) << (8 * (self.pos % 8));
+        self.state[(self.rate - 1) / 8] ^= 0x80 << 56;
+        keccak_f1600(&mut self.state);
+        self.pos = 0;
+        self
+    }
+
+    fn squeeze(&mut self, out: &mut [u8]) {
+        for o in out.iter_mut() {
+            if self.pos == self.rate {
+                keccak_f1600(&mut self.state);
+                self.pos = 0;
+            }
+            *o = (self.state[self.pos / 8] >> (8 * (self.pos % 8))) as u8;
+            self.pos += 1;
+        }
+    }
+
+    fn xor_byte(&mut self, b: u8) {
+        self.state[self.pos / 8] ^= (b as u64) << (8 * (self.pos % 8));
+        self.pos += 1;
+        if self.pos == self.rate {
+            keccak_f1600(&mut self.state);
+            self.pos = 0;
+        }
+    }
+
+    /// `left_encode(value)` from SP800-185.
+    fn left_encode(&mut self, value: usize) {
+        let bytes = (value as u64).to_be_bytes();
+        let skip = (value as u64).leading_zeros() as usize / 8;
+        let skip = core::cmp::min(skip, 7);
+        self.absorb(&[(8 - skip) as u8]);
+        self.absorb(&bytes[skip..]);
+    }
+
+    /// `right_encode(value)` from SP800-185.
+    fn right_encode(&mut self, value: usize) {
+        let bytes = (value as u64).to_be_bytes();
+        let skip = (value as u64).leading_zeros() as usize / 8;
+        let skip = core::cmp::min(skip, 7);
+        self.absorb(&bytes[skip..]);
+        self.absorb(&[(8 - skip) as u8]);
+    }
+
+    /// `encode_string(bytes)` from SP800-185.
+    fn encode_string(&mut self, bytes: &[u8]) {
+        self.left_encode(bytes.len() * 8);
+        self.absorb(bytes);
+    }
+}
+
+const DOMAIN_SHA3: u8 = 0x06;
+const DOMAIN_SHAKE: u8 = 0x1f;
+const DOMAIN_CSHAKE: u8 = 0x04;
+
+macro_rules! sha3_context {
+    ($name:ident, $doc:literal, $output_len:literal) => {
+        #[doc = concat!("A context for incremental computation of ", $doc, ".")]
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Sponge,
+        }
+
+        impl $name {
+            #[doc = concat!("Start a new ", $doc, " hash computation.")]
+            pub const fn new() -> Self {
+                Self {
+                    sponge: Sponge::new(Self::BLOCK_SZ),
+                }
+            }
+
+            /// Add `bytes` to the ongoing hash computation.
+            pub fn update(&mut self, bytes: &[u8]) {
+                self.sponge.absorb(bytes);
+            }
+
+            #[doc = concat!("Complete the ", $doc, " computation, returning the hash output.")]
+            pub fn finish(self) -> [u8; $output_len] {
+                let mut r = [0u8; $output_len];
+                self.sponge.finish(DOMAIN_SHA3).squeeze(&mut r);
+                r
+            }
+
+            #[doc = concat!("The internal block size (rate) of ", $doc, ".")]
+            pub const BLOCK_SZ: usize = 200 - 2 * $output_len;
+        }
+    };
+}
+
+sha3_context!(Sha3_224Context, "SHA3-224", 28);
+sha3_context!(Sha3_256Context, "SHA3-256", 32);
+sha3_context!(Sha3_384Context, "SHA3-384", 48);
+sha3_context!(Sha3_512Context, "SHA3-512", 64);
+
+/// Output from an extendable-output function (XOF).
+///
+/// This yields an unbounded stream of output bytes.
+#[derive(Clone)]
+pub struct XofReader {
+    sponge: Sponge,
+}
+
+impl XofReader {
+    /// Fill `out` with the next `out.len()` bytes of output.
+    pub fn read(&mut self, out: &mut [u8]) {
+        self.sponge.squeeze(out);
+    }
+}
+
+macro_rules! shake_context {
+    ($name:ident, $doc:literal, $security:literal) => {
+        #[doc = concat!("A context for incremental computation of ", $doc, ".")]
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Sponge,
+        }
+
+        impl $name {
+            #[doc = concat!("Start a new ", $doc, " computation.")]
+            pub const fn new() -> Self {
+                Self {
+                    sponge: Sponge::new(Self::BLOCK_SZ),
+                }
+            }
+
+            /// Add `bytes` to the ongoing computation.
+            pub fn update(&mut self, bytes: &[u8]) {
+                self.sponge.absorb(bytes);
+            }
+
+            /// Complete the input, and return a reader for the output.
+            pub fn finish(self) -> XofReader {
+                XofReader {
+                    sponge: self.sponge.finish(DOMAIN_SHAKE),
+                }
+            }
+
+            #[doc = concat!("The internal block size (rate) of ", $doc, ".")]
+            pub const BLOCK_SZ: usize = 200 - $security / 4;
+        }
+    };
+}
+
+shake_context!(Shake128Context, "SHAKE128", 128);
+shake_context!(Shake256Context, "SHAKE256", 256);
+
+macro_rules! cshake_context {
+    ($name:ident, $doc:literal, $security:literal) => {
+        #[doc = concat!("A context for incremental computation of ", $doc, ".")]
+        ///
+        /// If both the function name and customization string are empty,
+        /// this is equivalent to the corresponding SHAKE function.
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Sponge,
+            domain: u8,
+        }
+
+        impl $name {
+            #[doc = concat!("Start a new ", $doc, " computation.")]
+            ///
+            /// `function_name` is the NIST-defined function name `N`.  Callers
+            /// should generally pass an empty slice here.
+            ///
+            /// `customization` is the user-defined customization string `S`.
+            pub fn new(function_name: &[u8], customization: &[u8]) -> Self {
+                let mut sponge = Sponge::new(Self::BLOCK_SZ);
+
+                if function_name.is_empty() && customization.is_empty() {
+                    return Self {
+                        sponge,
+                        domain: DOMAIN_SHAKE,
+                    };
+                }
+
+                sponge.left_encode(Self::BLOCK_SZ);
+                sponge.encode_string(function_name);
+                sponge.encode_string(customization);
+                sponge.pad_zero();
+
+                Self {
+                    sponge,
+                    domain: DOMAIN_CSHAKE,
+                }
+            }
+
+            /// Add `bytes` to the ongoing computation.
+            pub fn update(&mut self, bytes: &[u8]) {
+                self.sponge.absorb(bytes);
+            }
+
+            /// Complete the input, and return a reader for the output.
+            pub fn finish(self) -> XofReader {
+                XofReader {
+                    sponge: self.sponge.finish(self.domain),
+                }
+            }
+
+            #[doc = concat!("The internal block size (rate) of ", $doc, ".")]
+            pub const BLOCK_SZ: usize = 200 - $security / 4;
+        }
+    };
+}
+
+cshake_context!(CShake128Context, "cSHAKE128", 128);
+cshake_context!(CShake256Context, "cSHAKE256", 256);
+
+macro_rules! kmac {
+    ($name:ident, $cshake:ident, $doc:literal) => {
+        #[doc = concat!("An in-progress ", $doc, " computation.")]
+        #[derive(Clone)]
+        pub struct $name {
+            inner: $cshake,
+        }
+
+        impl $name {
+            #[doc = concat!("Create a new ", $doc, " computation, with the given key.")]
+            ///
+            /// `customization` is the user-defined customization string `S`.
+            /// It may be empty.
+            pub fn new(key: &[u8], customization: &[u8]) -> Self {
+                let mut inner = $cshake::new(b"KMAC", customization);
+                inner.sponge.left_encode($cshake::BLOCK_SZ);
+                inner.sponge.encode_string(key);
+                inner.sponge.pad_zero();
+                Self { inner }
+            }
+
+            /// Add data to be authenticated.
+            pub fn update(&mut self, bytes: &[u8]) {
+                self.inner.update(bytes);
+            }
+
+            /// Complete the computation, filling `out` with the tag.
+            ///
+            /// The length of `out` is an input to the computation: a shorter
+            /// output is not a prefix of a longer one.
+            pub fn finish(mut self, out: &mut [u8]) {
+                self.inner.sponge.right_encode(out.len() * 8);
+                self.inner.finish().read(out);
+            }
+
+            /// Complete the computation, returning a reader for an arbitrary-length
+            /// output.
+            ///
+            /// This is the "KMACXOF" variant.
+            pub fn finish_xof(mut self) -> XofReader {
+                self.inner.sponge.right_encode(0);
+                self.inner.finish()
+            }
+
+            /// Complete the computation and compare the result against `expected_tag`.
+            ///
+            /// This is done in constant-time.  The length of `expected_tag` determines
+            /// the length of the computed tag.  Tags shorter than 32 bits (the minimum
+            /// recommended by SP800-185 section 8.4.2) or longer than 64 bytes are
+            /// rejected.
+            pub fn verify(self, expected_tag: &[u8]) -> Result<(), Error> {
+                if expected_tag.len() < KMAC_MIN_TAG_LEN {
+                    return Err(Error::BadSignature);
+                }
+
+                let mut tag = [0u8; 64];
+                let tag = tag
+                    .get_mut(..expected_tag.len())
+                    .ok_or(Error::BadSignature)?;
+                self.finish(tag);
+                match ct_equal(tag, expected_tag) {
+                    true => Ok(()),
+                    false => Err(Error::BadSignature),
+                }
+            }
+        }
+    };
+}
+
+kmac!(Kmac128, CShake128Context, "KMAC128");
+kmac!(Kmac256, CShake256Context, "KMAC256");
+
+/// The shortest tag accepted by `verify()`, in bytes.
+const KMAC_MIN_TAG_LEN: usize = 4;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha3_abc() {
+        let mut ctx = Sha3_224Context::new();
+        ctx.update(b"abc");
+        assert_eq!(&ctx.finish(),
+                   b"\xe6\x42\x82\x4c\x3f\x8c\xf2\x4a\xd0\x92\x34\xee\x7d\x3c\x76\x6f\xc9\xa3\xa5\x16\x8d\x0c\x94\xad\x73\xb4\x6f\xdf");
+
+        let mut ctx = Sha3_256Context::new();
+        ctx.update(b"abc");
+        assert_eq!(&ctx.finish(),
+                   b"\x3a\x98\x5d\xa7\x4f\xe2\x25\xb2\x04\x5c\x17\x2d\x6b\xd3\x90\xbd\x85\x5f\x08\x6e\x3e\x9d\x52\x5b\x46\xbf\xe2\x45\x11\x43\x15\x32");
+
+        let mut ctx = Sha3_384Context::new();
+        ctx.update(b"abc");
+        assert_eq!(&ctx.finish(),
+                   b"\xec\x01\x49\x82\x88\x51\x6f\xc9\x26\x45\x9f\x58\xe2\xc6\xad\x8d\xf9\xb4\x73\xcb\x0f\xc0\x8c\x25\x96\xda\x7c\xf0\xe4\x9b\xe4\xb2\x98\xd8\x8c\xea\x92\x7a\xc7\xf5\x39\xf1\xed\xf2\x28\x37\x6d\x25");
+
+        let mut ctx = Sha3_512Context::new();
+        ctx.update(b"abc");
+        assert_eq!(&ctx.finish(),
+                   b"\xb7\x51\x85\x0b\x1a\x57\x16\x8a\x56\x93\xcd\x92\x4b\x6b\x09\x6e\x08\xf6\x21\x82\x74\x44\xf7\x0d\x88\x4f\x5d\x02\x40\xd2\x71\x2e\x10\xe1\x16\xe9\x19\x2a\xf3\xc9\x1a\x7e\xc5\x76\x47\xe3\x93\x40\x57\x34\x0b\x4c\xf4\x08\xd5\xa5\x65\x92\xf8\x27\x4e\xec\x53\xf0");
+    }
+
+    #[test]
+    fn sha3_all_lengths() {
+        let mut outer = Sha3_256Context::new();
+
+        for len in 0..1024 {
+            let mut inner = Sha3_256Context::new();
+
+            for _ in 0..len {
+                inner.update(&[len as u8]);
+            }
+
+            outer.update(&inner.finish());
+        }
+
+        assert_eq!(&outer.finish(),
+                   b"\xf7\xed\xf7\x2b\x34\x8c\xb4\xab\x5e\xe7\x4f\x6c\xae\xaf\x11\xad\xe2\x2f\x04\x65\x84\x8e\x5c\xaa\x14\x38\x7f\xd4\xeb\xdb\x9d\x70");
+    }
+
+    #[test]
+    fn shake() {
+        let mut out = [0u8; 32];
+        Shake128Context::new().finish().read(&mut out);
+        assert_eq!(&out,
+                   b"\x7f\x9c\x2b\xa4\xe8\x8f\x82\x7d\x61\x60\x45\x50\x76\x05\x85\x3e\xd7\x3b\x80\x93\xf6\xef\xbc\x88\xeb\x1a\x6e\xac\xfa\x66\xef\x26");
+
+        let mut out = [0u8; 64];
+        Shake256Context::new().finish().read(&mut out);
+        assert_eq!(&out,
+                   b"\x46\xb9\xdd\x2b\x0b\xa8\x8d\x13\x23\x3b\x3f\xeb\x74\x3e\xeb\x24\x3f\xcd\x52\xea\x62\xb8\x1b\x82\xb5\x0c\x27\x64\x6e\xd5\x76\x2f\xd7\x5d\xc4\xdd\xd8\xc0\xf2\x00\xcb\x05\x01\x9d\x67\xb5\x92\xf6\xfc\x82\x1c\x49\x47\x9a\xb4\x86\x40\x29\x2e\xac\xb3\xb7\xc4\xbe");
+    }
+
+    #[test]
+    fn shake_incremental_output() {
+        let message = (0..1000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let mut ctx = Shake128Context::new();
+        for chunk in message.chunks(7) {
+            ctx.update(chunk);
+        }
+        let mut reader = ctx.finish();
+
+        // read output in irregular pieces, spanning several blocks
+        let mut out = [0u8; 400];
+        for chunk in out.chunks_mut(33) {
+            reader.read(chunk);
+        }
+        assert_eq!(&out[368..],
+                   b"\x6a\x16\xfc\xa5\xb5\xf0\x17\xaf\x0f\x6c\x51\x85\x20\x1f\x72\x98\x82\x7d\xcb\x89\x6d\x70\x7f\xd7\xba\xf0\xca\xa8\x7c\x4a\x56\xe1");
+    }
+
+    #[test]
+    fn cshake() {
+        // from SP800-185 examples
+        let mut ctx = CShake128Context::new(b"", b"Email Signature");
+        ctx.update(b"\x00\x01\x02\x03");
+        let mut out = [0u8; 32];
+        ctx.finish().read(&mut out);
+        assert_eq!(&out,
+                   b"\xc1\xc3\x69\x25\xb6\x40\x9a\x04\xf1\xb5\x04\xfc\xbc\xa9\xd8\x2b\x40\x17\x27\x7c\xb5\xed\x2b\x20\x65\xfc\x1d\x38\x14\xd5\xaa\xf5");
+
+        let mut ctx = CShake256Context::new(b"", b"Email Signature");
+        ctx.update(b"\x00\x01\x02\x03");
+        let mut out = [0u8; 64];
+        ctx.finish().read(&mut out);
+        assert_eq!(&out,
+                   b"\xd0\x08\x82\x8e\x2b\x80\xac\x9d\x22\x18\xff\xee\x1d\x07\x0c\x48\xb8\xe4\xc8\x7b\xff\x32\xc9\x69\x9d\x5b\x68\x96\xee\xe0\xed\xd1\x64\x02\x0e\x2b\xe0\x56\x08\x58\xd9\xc0\x0c\x03\x7e\x34\xa9\x69\x37\xc5\x61\xa7\x4c\x41\x2b\xb4\xc7\x46\x46\x95\x27\x28\x1c\x8c");
+    }
+
+    #[test]
+    fn cshake_is_shake_without_customization() {
+        let mut a = [0u8; 100];
+        let mut ctx = CShake256Context::new(b"", b"");
+        ctx.update(b"hello");
+        ctx.finish().read(&mut a);
+
+        let mut b = [0u8; 100];
+        let mut ctx = Shake256Context::new();
+        ctx.update(b"hello");
+        ctx.finish().read(&mut b);
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn kmac() {
+        // from SP800-185 examples
+        let key: Vec<u8> = (0x40..0x60).collect();
+
+        let mut ctx = Kmac128::new(&key, b"");
+        ctx.update(b"\x00\x01\x02\x03");
+        let mut out = [0u8; 32];
+        ctx.finish(&mut out);
+        assert_eq!(&out,
+                   b"\xe5\x78\x0b\x0d\x3e\xa6\xf7\xd3\xa4\x29\xc5\x70\x6a\xa4\x3a\x00\xfa\xdb\xd7\xd4\x96\x28\x83\x9e\x31\x87\x24\x3f\x45\x6e\xe1\x4e");
+
+        let mut ctx = Kmac128::new(&key, b"My Tagged Application");
+        ctx.update(b"\x00\x01\x02\x03");
+        ctx.verify(b"\x3b\x1f\xba\x96\x3c\xd8\xb0\xb5\x9e\x8c\x1a\x6d\x71\x88\x8b\x71\x43\x65\x1a\xf8\xba\x0a\x70\x70\xc0\x97\x9e\x28\x11\x32\x4a\xa5")
+            .unwrap();
+
+        let mut ctx = Kmac256::new(&key, b"My Tagged Application");
+        ctx.update(b"\x00\x01\x02\x03");
+        let mut out = [0u8; 64];
+        ctx.finish(&mut out);
+        assert_eq!(&out,
+                   b"\x20\xc5\x70\xc3\x13\x46\xf7\x03\xc9\xac\x36\xc6\x1c\x03\xcb\x64\xc3\x97\x0d\x0c\xfc\x78\x7e\x9b\x79\x59\x9d\x27\x3a\x68\xd2\xf7\xf6\x9d\x4c\xc3\xde\x9d\x10\x4a\x35\x16\x89\xf2\x7c\xf6\xf5\x95\x1f\x01\x03\xf3\x3f\x4f\x24\x87\x10\x24\xd9\xc2\x77\x73\xa8\xdd");
+
+        let mut ctx = Kmac256::new(&key, b"My Tagged Application");
+        ctx.update(b"\x00\x01\x02\x03");
+        assert_eq!(ctx.verify(&out[..32]), Err(Error::BadSignature));
+    }
+
+    #[test]
+    fn kmac_verify_short_tags() {
+        let key: Vec<u8> = (0x40..0x60).collect();
+
+        for len in 0..=4 {
+            let mut tag = [0u8; 4];
+            let mut ctx = Kmac128::new(&key, b"");
+            ctx.update(b"message");
+            ctx.clone().finish(&mut tag[..len]);
+
+            let result = ctx.verify(&tag[..len]);
+            if len < KMAC_MIN_TAG_LEN {
+                assert_eq!(result, Err(Error::BadSignature), "len {len}");
+            } else {
+                result.unwrap();
+            }
+        }
+
+        assert_eq!(
+            Kmac256::new(&key, b"").verify(&[0u8; 65]),
+            Err(Error::BadSignature)
+        );
+    }
+
+    #[test]
+    fn kmac_xof() {
+        // from SP800-185 examples
+        let key: Vec<u8> = (0x40..0x60).collect();
+
+        let mut ctx = Kmac128::new(&key, b"");
+        ctx.update(b"\x00\x01\x02\x03");
+        let mut out = [0u8; 32];
+        ctx.finish_xof().read(&mut out);
+        assert_eq!(&out,
+                   b"\xcd\x83\x74\x0b\xbd\x92\xcc\xc8\xcf\x03\x2b\x14\x81\xa0\xf4\x46\x0e\x7c\xa9\xdd\x12\xb0\x8a\x0c\x40\x31\x17\x8b\xac\xd6\xec\x35");
+
+        let mut ctx = Kmac256::new(&key, b"My Tagged Application");
+        ctx.update(b"\x00\x01\x02\x03");
+        let mut out = [0u8; 64];
+        ctx.finish_xof().read(&mut out);
+        assert_eq!(&out,
+                   b"\x17\x55\x13\x3f\x15\x34\x75\x2a\xad\x07\x48\xf2\xc7\x06\xfb\x5c\x78\x45\x12\xca\xb8\x35\xcd\x15\x67\x6b\x16\xc0\xc6\x64\x7f\xa9\x6f\xaa\x7a\xf6\x34\xa0\xbf\x8f\xf6\xdf\x39\x37\x4f\xa0\x0f\xad\x9a\x39\xe3\x22\xa7\xc9\x20\x65\xa6\x4e\xb1\xfb\x08\x01\xeb\x2b");
+    }
+}
@@ -0,0 +1,259 @@
+// Written for Graviola by Joe Birr-Pixton, 2024.
+// SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
+
+use core::cell::RefCell;
+
+use super::rng::{entropy_source_installed, RandomSource, SystemRandom};
+use crate::low::chacha20::ChaCha20;
+use crate::low::{zeroise, Entry, WipeOnFork};
+use crate::Error;
+
+/// A fast, thread-local source of random bytes.
+///
+/// Each thread has a ChaCha20-based generator, seeded from
+/// [`SystemRandom`].  This avoids a system call for each request.
+///
+/// The generator uses "fast-key-erasure": the key used to produce
+/// output is overwritten before that output is returned, and output
+/// is erased from the generator's buffer once returned.  See
+/// <https://blog.cr.yp.to/20170723-random.html>.
+///
+/// The generator is reseeded from [`SystemRandom`]:
+///
+/// - after producing 64KiB of output, and
+/// - in a child process, after `fork()`.  On Linux this is detected
+///   using `MADV_WIPEONFORK`; elsewhere (or on kernels before 4.14)
+///   by checking the process ID on each request.
+///
+/// If an entropy source is installed with
+/// [`install_entropy_source()`](crate::random::install_entropy_source),
+/// requests are passed directly to it instead.
+pub struct ThreadRandom;
+
+impl RandomSource for ThreadRandom {
+    fn fill(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        let _ = Entry::new_secret();
+
+        if entropy_source_installed() {
+            return SystemRandom.fill(out);
+        }
+
+        STATE
+            .try_with(|state| state.borrow_mut().fill(&mut SystemRandom, out))
+            // this thread's generator has been destroyed
+            .unwrap_or_else(|_| SystemRandom.fill(out))
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<FastKeyErasure> = RefCell::new(FastKeyErasure::new());
+}
+
+struct FastKeyErasure {
+    key: [u8; KEY_LEN],
+    buffer: [u8; BUFFER_LEN],
+    /// Bytes at the start of `buffer` which are already used (and erased).
+    used: usize,
+    /// Bytes which may be produced before a reseed.  Zero if unseeded.
+    budget: usize,
+    fork: ForkDetector,
+}
+
+impl FastKeyErasure {
+    fn new() -> Self {
+        Self {
+            key: [0u8; KEY_LEN],
+            buffer: [0u8; BUFFER_LEN],
+            used: BUFFER_LEN,
+            budget: 0,
+            fork: ForkDetector::new(),
+        }
+    }
+
+    fn fill(&mut self, source: &mut dyn RandomSource, out: &mut [u8]) -> Result<(), Error> {
+        if self.budget < out.len() || self.fork.forked() {
+            self.reseed(source)?;
+        }
+        self.budget = self.budget.saturating_sub(out.len());
+
+        // large requests are written directly
+        if out.len() > BUFFER_LEN - KEY_LEN {
+            let mut chacha = ChaCha20::new(&self.key, &NONCE);
+            self.key.fill(0);
+            chacha.cipher(&mut self.key);
+            out.fill(0);
+            chacha.cipher(out);
+            return Ok(());
+        }
+
+        let mut out = out;
+        while !out.is_empty() {
+            if self.used == BUFFER_LEN {
+                self.refill();
+            }
+
+            let available = &mut self.buffer[self.used..];
+            let take = out.len().min(available.len());
+            let (chunk, rest) = out.split_at_mut(take);
+            chunk.copy_from_slice(&available[..take]);
+            zeroise(&mut available[..take]);
+            self.used += take;
+            out = rest;
+        }
+
+        Ok(())
+    }
+
+    fn refill(&mut self) {
+        let mut chacha = ChaCha20::new(&self.key, &NONCE);
+        self.buffer.fill(0);
+        chacha.cipher(&mut self.buffer);
+
+        let (key, _) = self.buffer.split_at_mut(KEY_LEN);
+        self.key.copy_from_slice(key);
+        zeroise(key);
+        self.used = KEY_LEN;
+    }
+
+    fn reseed(&mut self, source: &mut dyn RandomSource) -> Result<(), Error> {
+        // discard any buffered output
+        zeroise(&mut self.buffer);
+        self.used = BUFFER_LEN;
+        self.budget = 0;
+
+        source.fill(&mut self.key)?;
+        self.budget = RESEED_BYTES;
+        self.fork.reset();
+        Ok(())
+    }
+}
+
+impl Drop for FastKeyErasure {
+    fn drop(&mut self) {
+        zeroise(&mut self.key);
+        zeroise(&mut self.buffer);
+    }
+}
+
+struct ForkDetector {
+    wipe_on_fork: Option<WipeOnFork>,
+    pid: u32,
+}
+
+impl ForkDetector {
+    fn new() -> Self {
+        Self {
+            wipe_on_fork: WipeOnFork::new(),
+            pid: std::process::id(),
+        }
+    }
+
+    /// Returns true if we might be in a child process since the last `reset()`.
+    fn forked(&self) -> bool {
+        match &self.wipe_on_fork {
+            Some(wipe_on_fork) => wipe_on_fork.is_clear(),
+            None => std::process::id() != self.pid,
+        }
+    }
+
+    fn reset(&mut self) {
+        match &self.wipe_on_fork {
+            Some(wipe_on_fork) => wipe_on_fork.set(),
+            None => self.pid = std::process::id(),
+        }
+    }
+}
+
+const KEY_LEN: usize = 32;
+const BUFFER_LEN: usize = 1024;
+const RESEED_BYTES: usize = 64 * 1024;
+
+/// Each key is used once, so the nonce can be fixed.
+const NONCE: [u8; 16] = [0u8; 16];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mid::rng::SliceRandomSource;
+
+    #[test]
+    fn smoke() {
+        let mut a = [0u8; 32];
+        let mut b = [0u8; 32];
+        ThreadRandom.fill(&mut a).unwrap();
+        ThreadRandom.fill(&mut b).unwrap();
+        assert_ne!(a, b);
+
+        let mut large = vec![0u8; 4096];
+        ThreadRandom.fill(&mut large).unwrap();
+        assert_ne!(&large[..32], &[0u8; 32]);
+    }
+
+    #[test]
+    fn known_answer() {
+        let mut rng = FastKeyErasure::new();
+        let mut source = SliceRandomSource(&[0u8; KEY_LEN]);
+
+        // RFC8439 section 2.4.2: first ChaCha20 block for an all-zero key and nonce.
+        // The first 32 bytes become the next key.
+        let mut out = [0u8; 8];
+        rng.fill(&mut source, &mut out).unwrap();
+        assert_eq!(&out, b"\xda\x41\x59\x7c\x51\x57\x48\x8d");
+        rng.fill(&mut source, &mut out).unwrap();
+        assert_eq!(&out, b"\x77\x24\xe0\x3f\xb8\xd8\x4a\x37");
+        assert!(rng.buffer[..KEY_LEN + 16].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn all_lengths() {
+        let mut rng = FastKeyErasure::new();
+        let mut source = SliceRandomSource(&[0x11; KEY_LEN * 16]);
+
+        for len in 0..=BUFFER_LEN + 64 {
+            let mut out = vec![0u8; len];
+            rng.fill(&mut source, &mut out).unwrap();
+            if len >= 16 {
+                assert_ne!(&out[len - 16..], &[0u8; 16]);
+            }
+        }
+    }
+
+    #[test]
+    fn reseeds_after_budget() {
+        let seeds = [0x22u8; KEY_LEN * 2];
+        let mut source = SliceRandomSource(&seeds);
+        let mut rng = FastKeyErasure::new();
+
+        let mut out = vec![0u8; RESEED_BYTES / 2];
+        rng.fill(&mut source, &mut out).unwrap();
+        rng.fill(&mut source, &mut out).unwrap();
+        assert_eq!(source.0.len(), KEY_LEN);
+
+        // budget exhausted: takes second seed
+        rng.fill(&mut source, &mut [0u8; 1]).unwrap();
+        assert!(source.0.is_empty());
+
+        rng.budget = 0;
+        assert_eq!(rng.fill(&mut source, &mut [0u8; 1]), Err(Error::RngFailed));
+    }
+
+    #[test]
+    fn reseeds_after_fork() {
+        let seeds = [0x33u8; KEY_LEN * 2];
+        let mut source = SliceRandomSource(&seeds);
+        let mut rng = FastKeyErasure::new();
+
+        rng.fill(&mut source, &mut [0u8; 1]).unwrap();
+        rng.fill(&mut source, &mut [0u8; 1]).unwrap();
+        assert_eq!(source.0.len(), KEY_LEN);
+
+        // simulate being a child process, using the process ID fallback
+        assert!(!rng.fork.forked());
+        rng.fork.wipe_on_fork = None;
+        rng.fork.pid = rng.fork.pid.wrapping_add(1);
+        assert!(rng.fork.forked());
+
+        rng.fill(&mut source, &mut [0u8; 1]).unwrap();
+        assert!(source.0.is_empty());
+    }
+}
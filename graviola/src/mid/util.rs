@@ -4,6 +4,7 @@
 // Once const generics is completed this should be able to be
 // done better that way.
 
+#[cfg(feature = "x25519")]
 macro_rules! little_endian {
     ([u64; $N:literal], $fn_array_to:ident, $fn_slice_to:ident, $fn_to_bytes:ident) => {
         pub(crate) fn $fn_array_to(b: &[u8; $N * 8]) -> [u64; $N] {
@@ -29,6 +30,7 @@ macro_rules! little_endian {
     };
 }
 
+#[cfg(feature = "x25519")]
 little_endian!(
     [u64; 4],
     little_endian_to_u64x4,
@@ -36,6 +38,7 @@ little_endian!(
     u64x4_to_little_endian
 );
 
+#[cfg(any(feature = "p256", feature = "p384"))]
 macro_rules! big_endian {
     ([u64; $N:literal], $fn_array_to:ident, $fn_slice_to:ident, $fn_slice_any_size_to:ident, $fn_to_bytes:ident) => {
         pub(crate) fn $fn_array_to(b: &[u8; $N * 8]) -> [u64; $N] {
@@ -95,6 +98,7 @@ macro_rules! big_endian {
     };
 }
 
+#[cfg(feature = "p256")]
 big_endian!(
     [u64; 4],
     big_endian_to_u64x4,
@@ -102,6 +106,7 @@ big_endian!(
     big_endian_slice_any_size_to_u64x4,
     u64x4_to_big_endian
 );
+#[cfg(feature = "p384")]
 big_endian!(
     [u64; 6],
     big_endian_to_u64x6,
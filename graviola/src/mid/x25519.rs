@@ -3,7 +3,9 @@
 
 use super::util;
 use crate::low;
-use crate::mid::rng::{RandomSource, SystemRandom};
+#[cfg(feature = "std")]
+use crate::mid::rng::DefaultRandom;
+use crate::mid::rng::RandomSource;
 
 /// An X25519 private key.
 pub struct PrivateKey([u64; 4]);
@@ -29,13 +31,21 @@ impl PrivateKey {
         util::u64x4_to_little_endian(&self.0)
     }
 
-    /// Generate a new key using the system random number generator.
+    /// Generate a new key using the default [`RandomSource`].
     ///
     /// Fails only if the random source fails.
+    #[cfg(feature = "std")]
     pub fn new_random() -> Result<Self, crate::Error> {
+        Self::new_random_with_rng(&mut DefaultRandom)
+    }
+
+    /// Generate a new key using `rng`.
+    ///
+    /// Fails only if `rng` fails.
+    pub fn new_random_with_rng(rng: &mut dyn RandomSource) -> Result<Self, crate::Error> {
         let _ = low::Entry::new_secret();
         let mut r = [0u8; 32];
-        SystemRandom.fill(&mut r)?;
+        rng.fill(&mut r)?;
         Ok(Self::from_array(&r))
     }
 
@@ -43,7 +53,10 @@ impl PrivateKey {
     pub fn public_key(&self) -> PublicKey {
         let _ = low::Entry::new_secret();
         let mut res = [0u64; 4];
+        #[cfg(not(feature = "runtime-tables"))]
         low::curve25519_x25519base(&mut res, &self.0);
+        #[cfg(feature = "runtime-tables")]
+        low::curve25519_x25519(&mut res, &self.0, &BASE_POINT);
         PublicKey(res)
     }
 
@@ -98,9 +111,28 @@ impl Drop for SharedSecret {
     }
 }
 
+/// The base point, u = 9.
+#[cfg(feature = "runtime-tables")]
+const BASE_POINT: [u64; 4] = [9, 0, 0, 0];
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mid::rng::SliceRandomSource;
+
+    #[test]
+    fn new_random_with_rng() {
+        let key = PrivateKey::new_random_with_rng(&mut SliceRandomSource(&[0x42; 32])).unwrap();
+        assert_eq!(
+            key.as_bytes(),
+            PrivateKey::from_array(&[0x42; 32]).as_bytes()
+        );
+
+        assert_eq!(
+            PrivateKey::new_random_with_rng(&mut SliceRandomSource(&[0x42; 31])).err(),
+            Some(crate::Error::RngFailed)
+        );
+    }
 
     #[test]
     fn rfc7748_1() {
@@ -154,7 +186,7 @@ mod tests {
 
         if option_env!("SLOW_TESTS").is_some() {
             // After 1,000,000 iterations: 7c3911e0ab2586fd864497297e575e6f3bc601c0883c30df5f4dd2d24f665424
-            for _ in 1000..1000_000 {
+            for _ in 1_000..1_000_000 {
                 let new_u = PublicKey::from_array(&k.as_bytes());
                 let res = k.diffie_hellman(&u);
                 u = new_u;
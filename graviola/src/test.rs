@@ -1,6 +1,9 @@
 // Written for Graviola by Joe Birr-Pixton, 2024.
 // SPDX-License-Identifier: Apache-2.0 OR ISC OR MIT-0
 
+// CAVP tests exist for AES, but not for every algorithm.
+#![cfg_attr(not(feature = "aes"), allow(dead_code))]
+
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
@@ -34,6 +37,7 @@ impl Value<'_> {
         self.0.parse::<u64>().unwrap()
     }
 
+    #[cfg_attr(not(all(feature = "p256", feature = "p384")), allow(dead_code))]
     pub(crate) fn str(&self) -> &str {
         self.0
     }
@@ -0,0 +1,55 @@
+//! Tests for the process-wide entropy source hook.
+//!
+//! This is a separate test binary, because installing an entropy
+//! source affects every test running in the same process.
+
+use graviola::key_agreement::x25519;
+use graviola::random::{self, RandomSource};
+use graviola::Error;
+
+/// A trivially-predictable source, for testing.
+struct Counter(u8);
+
+impl RandomSource for Counter {
+    fn fill(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        for b in out.iter_mut() {
+            *b = self.0;
+            self.0 = self.0.wrapping_add(1);
+        }
+        Ok(())
+    }
+}
+
+struct Broken;
+
+impl RandomSource for Broken {
+    fn fill(&mut self, _out: &mut [u8]) -> Result<(), Error> {
+        Err(Error::RngFailed)
+    }
+}
+
+#[test]
+fn install_and_remove() {
+    assert!(random::install_entropy_source(Box::new(Counter(0))).is_none());
+
+    let mut out = [0u8; 4];
+    random::fill(&mut out).unwrap();
+    assert_eq!(out, [0, 1, 2, 3]);
+
+    // other APIs also use the installed source
+    let key = x25519::PrivateKey::new_random().unwrap();
+    let expected = x25519::PrivateKey::from_array(&core::array::from_fn(|i| i as u8 + 4));
+    assert_eq!(key.as_bytes(), expected.as_bytes());
+
+    // failures are propagated
+    assert!(random::install_entropy_source(Box::new(Broken)).is_some());
+    assert_eq!(random::fill(&mut out), Err(Error::RngFailed));
+    assert!(x25519::PrivateKey::new_random().is_err());
+
+    // and removing it reverts to the system source
+    assert!(random::remove_entropy_source().is_some());
+    assert!(random::remove_entropy_source().is_none());
+    let mut out = [0u8; 32];
+    random::fill(&mut out).unwrap();
+    assert_ne!(out, [0u8; 32]);
+}
@@ -0,0 +1,260 @@
+use serde::Deserialize;
+use std::fs::File;
+
+use graviola::hpke::{Aead, Kdf, Kem, Psk, Suite};
+use graviola::random::RandomSource;
+use graviola::Error;
+
+/// An HPKE test vector, in the format of the RFC9180 test vectors
+/// (<https://github.com/cfrg/draft-irtf-cfrg-hpke/blob/master/test-vectors.json>).
+#[derive(Deserialize, Debug)]
+struct TestVector {
+    mode: u8,
+    kem_id: u16,
+    kdf_id: u16,
+    aead_id: u16,
+    #[serde(with = "hex::serde")]
+    info: Vec<u8>,
+    #[serde(rename = "ikmR", with = "hex::serde")]
+    ikm_r: Vec<u8>,
+    #[serde(rename = "ikmE", with = "hex::serde")]
+    ikm_e: Vec<u8>,
+    #[serde(rename = "skRm", with = "hex::serde")]
+    sk_rm: Vec<u8>,
+    #[serde(rename = "pkRm", with = "hex::serde")]
+    pk_rm: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    enc: Vec<u8>,
+    #[serde(default, with = "hex::serde")]
+    psk: Vec<u8>,
+    #[serde(default, with = "hex::serde")]
+    psk_id: Vec<u8>,
+    encryptions: Vec<Encryption>,
+    exports: Vec<Export>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Encryption {
+    #[serde(with = "hex::serde")]
+    aad: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    ct: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pt: Vec<u8>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Export {
+    #[serde(with = "hex::serde")]
+    exporter_context: Vec<u8>,
+    #[serde(rename = "L")]
+    len: usize,
+    #[serde(with = "hex::serde")]
+    exported_value: Vec<u8>,
+}
+
+impl TestVector {
+    fn suite(&self) -> Option<Suite> {
+        let kem = match self.kem_id {
+            0x0010 => Kem::DhKemP256HkdfSha256,
+            0x0011 => Kem::DhKemP384HkdfSha384,
+            0x0020 => Kem::DhKemX25519HkdfSha256,
+            _ => return None,
+        };
+        let kdf = match self.kdf_id {
+            0x0001 => Kdf::HkdfSha256,
+            0x0002 => Kdf::HkdfSha384,
+            0x0003 => Kdf::HkdfSha512,
+            _ => return None,
+        };
+        let aead = match self.aead_id {
+            0x0001 => Aead::Aes128Gcm,
+            0x0002 => Aead::Aes256Gcm,
+            0x0003 => Aead::ChaCha20Poly1305,
+            _ => return None,
+        };
+        Some(Suite { kem, kdf, aead })
+    }
+
+    fn psk(&self) -> Option<Psk<'_>> {
+        match self.mode {
+            0 => None,
+            1 => Some(Psk {
+                key: &self.psk,
+                id: &self.psk_id,
+            }),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Supplies the test vector's `ikmE` as the sender's randomness.
+struct FixedRandom<'a>(&'a [u8]);
+
+impl RandomSource for FixedRandom<'_> {
+    fn fill(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        if out.len() != self.0.len() {
+            return Err(Error::RngFailed);
+        }
+        out.copy_from_slice(self.0);
+        Ok(())
+    }
+}
+
+fn run_test_vectors(path: &str) {
+    let vectors: Vec<TestVector> = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+    let mut tested = 0;
+
+    for v in vectors {
+        // base and PSK modes only
+        if v.mode > 1 {
+            continue;
+        }
+        let Some(suite) = v.suite() else {
+            continue;
+        };
+        println!("testing {suite:?} mode {}", v.mode);
+
+        let (sk_r, pk_r) = suite.kem.derive_key_pair(&v.ikm_r).unwrap();
+        assert_eq!(sk_r.as_ref(), &v.sk_rm);
+        assert_eq!(pk_r.as_ref(), &v.pk_rm);
+
+        let (enc, mut sender) = suite
+            .setup_sender_with_rng(&mut FixedRandom(&v.ikm_e), &v.pk_rm, &v.info, v.psk())
+            .unwrap();
+        assert_eq!(enc.as_ref(), &v.enc);
+
+        let mut receiver = suite
+            .setup_receiver(&v.enc, &v.sk_rm, &v.info, v.psk())
+            .unwrap();
+
+        for e in &v.encryptions {
+            let mut buf = e.pt.clone();
+            let mut tag = [0u8; 16];
+            sender.seal(&e.aad, &mut buf, &mut tag).unwrap();
+            buf.extend_from_slice(&tag);
+            assert_eq!(buf, e.ct);
+
+            let (ct, tag) = buf.split_at_mut(e.pt.len());
+            receiver.open(&e.aad, ct, tag).unwrap();
+            assert_eq!(ct, &e.pt);
+        }
+
+        for x in &v.exports {
+            let mut out = vec![0u8; x.len];
+            sender.export(&x.exporter_context, &mut out).unwrap();
+            assert_eq!(out, x.exported_value);
+            receiver.export(&x.exporter_context, &mut out).unwrap();
+            assert_eq!(out, x.exported_value);
+        }
+
+        tested += 1;
+    }
+
+    assert_ne!(tested, 0, "all tests were skipped");
+}
+
+#[test]
+fn rfc9180_test_vectors() {
+    // The subset of RFC9180 appendix A for supported suites and modes,
+    // with the first few encryptions of each.
+    run_test_vectors("tests/hpke/rfc9180-vectors.json");
+}
+
+#[test]
+fn additional_test_vectors() {
+    // RFC9180 has no vectors for DHKEM(P-384), and covers few KDF/AEAD
+    // combinations.  These were computed with an independent implementation.
+    run_test_vectors("tests/hpke/additional-vectors.json");
+}
+
+#[test]
+fn open_failures() {
+    let suite = Suite {
+        kem: Kem::DhKemX25519HkdfSha256,
+        kdf: Kdf::HkdfSha256,
+        aead: Aead::ChaCha20Poly1305,
+    };
+    let (sk_r, pk_r) = suite.kem.generate_key_pair().unwrap();
+    let (enc, mut sender) = suite.setup_sender(pk_r.as_ref(), b"info", None).unwrap();
+
+    let mut msg = *b"hello";
+    let mut tag = [0u8; 16];
+    sender.seal(b"aad", &mut msg, &mut tag).unwrap();
+
+    // wrong info
+    let mut receiver = suite
+        .setup_receiver(enc.as_ref(), sk_r.as_ref(), b"other", None)
+        .unwrap();
+    let mut buf = msg;
+    assert_eq!(
+        receiver.open(b"aad", &mut buf, &tag),
+        Err(Error::DecryptFailed)
+    );
+
+    // wrong aad, then the right one: a failure does not advance the sequence
+    let mut receiver = suite
+        .setup_receiver(enc.as_ref(), sk_r.as_ref(), b"info", None)
+        .unwrap();
+    let mut buf = msg;
+    assert_eq!(
+        receiver.open(b"other", &mut buf, &tag),
+        Err(Error::DecryptFailed)
+    );
+    let mut buf = msg;
+    receiver.open(b"aad", &mut buf, &tag).unwrap();
+    assert_eq!(&buf, b"hello");
+
+    // PSK mode when the sender used base mode
+    let psk = Psk {
+        key: &[0x11; 32],
+        id: b"id",
+    };
+    let mut receiver = suite
+        .setup_receiver(enc.as_ref(), sk_r.as_ref(), b"info", Some(psk))
+        .unwrap();
+    let mut buf = msg;
+    assert_eq!(
+        receiver.open(b"aad", &mut buf, &tag),
+        Err(Error::DecryptFailed)
+    );
+
+    // empty PSK parts
+    let empty = Psk {
+        key: &[],
+        id: b"id",
+    };
+    assert!(suite
+        .setup_receiver(enc.as_ref(), sk_r.as_ref(), b"info", Some(empty))
+        .is_err());
+
+    // bad key lengths
+    assert!(suite
+        .setup_receiver(&enc.as_ref()[1..], sk_r.as_ref(), b"info", None)
+        .is_err());
+    assert!(suite
+        .setup_receiver(enc.as_ref(), &sk_r.as_ref()[1..], b"info", None)
+        .is_err());
+    assert!(suite.setup_sender(&[], b"info", None).is_err());
+
+    // small-order X25519 public key
+    assert_eq!(
+        suite.setup_sender(&[0u8; 32], b"info", None).err(),
+        Some(Error::NotOnCurve)
+    );
+}
+
+#[test]
+fn nist_curve_public_keys_are_checked() {
+    for kem in [Kem::DhKemP256HkdfSha256, Kem::DhKemP384HkdfSha384] {
+        let suite = Suite {
+            kem,
+            kdf: Kdf::HkdfSha256,
+            aead: Aead::Aes128Gcm,
+        };
+        let (_, pk_r) = kem.generate_key_pair().unwrap();
+        let mut bad = pk_r.as_ref().to_vec();
+        *bad.last_mut().unwrap() ^= 1;
+        assert!(suite.setup_sender(&bad, b"info", None).is_err());
+    }
+}
@@ -440,7 +440,7 @@ fn test_ecdh_x25519() {
 
             let private = x25519::PrivateKey::try_from_slice(&test.private).unwrap();
             let result = x25519::PublicKey::try_from_slice(&test.public)
-                .and_then(|pubkey| Ok(private.diffie_hellman(&pubkey)));
+                .map(|pubkey| private.diffie_hellman(&pubkey));
             match (test.result, &result) {
                 (ExpectedResult::Valid | ExpectedResult::Acceptable, Ok(shared)) => {
                     assert_eq!(&shared.0[..], &test.shared)
@@ -570,7 +570,7 @@ fn test_rsa_pkcs1_verify() {
         "rsa_signature_8192_sha384_test.json",
         "rsa_signature_8192_sha512_test.json",
     ] {
-        let data_file = File::open(&format!("../thirdparty/wycheproof/testvectors_v1/{file}"))
+        let data_file = File::open(format!("../thirdparty/wycheproof/testvectors_v1/{file}"))
             .expect("failed to open data file");
         println!("file: {data_file:?}");
 
@@ -617,7 +617,7 @@ fn test_rsa_pss_verify() {
         "rsa_pss_4096_sha512_mgf1_64_test.json",
         "rsa_pss_misc_test.json",
     ] {
-        let data_file = File::open(&format!("../thirdparty/wycheproof/testvectors_v1/{file}"))
+        let data_file = File::open(format!("../thirdparty/wycheproof/testvectors_v1/{file}"))
             .expect("failed to open data file");
         println!("file: {data_file:?}");
 
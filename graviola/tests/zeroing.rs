@@ -19,10 +19,12 @@ use core::ptr;
 #[test]
 fn rsa() {
     use graviola::signing::rsa;
-    let rsa_priv =
-        rsa::SigningKey::from_pkcs1_der(include_bytes!("../src/high/rsa/rsa8192.der")).unwrap();
-    let pub_key_size = size_of::<rsa::VerifyingKey>();
-    check_zeroed_on_drop_bounded(Box::pin(rsa_priv), Bounds::SkipPrefix(pub_key_size));
+    let der = include_bytes!("../src/high/rsa/rsa8192.der");
+    let rsa_priv = rsa::SigningKey::from_pkcs1_der(der).unwrap();
+    // the public key is not secret, so rather than assume anything about
+    // the layout of the key, look for the private components directly.
+    let secrets = pkcs1_private_integers(der);
+    check_secrets_gone_on_drop(Box::pin(rsa_priv), &secrets);
 }
 
 #[test]
@@ -84,10 +86,6 @@ fn chacha20_poly1305() {
 }
 
 fn check_zeroed_on_drop<T>(value: Pin<Box<T>>) {
-    check_zeroed_on_drop_bounded(value, Bounds::All)
-}
-
-fn check_zeroed_on_drop_bounded<T>(value: Pin<Box<T>>, bounds: Bounds) {
     let ptr = value.deref() as *const T as *const u8;
     let len = size_of::<T>();
     assert_ne!(len, 0);
@@ -96,7 +94,7 @@ fn check_zeroed_on_drop_bounded<T>(value: Pin<Box<T>>, bounds: Bounds) {
     drop(value);
     let after_drop = read_into_vec(ptr, len);
 
-    for i in bounds.start()..bounds.end(len) {
+    for i in HEAP_FREELIST_ZONE.0..len - HEAP_FREELIST_ZONE.1 {
         if after_drop[i] != 0x00 {
             println!("before_drop: {before_drop:02x?}");
             println!("after_drop: {after_drop:02x?}");
@@ -108,36 +106,101 @@ fn check_zeroed_on_drop_bounded<T>(value: Pin<Box<T>>, bounds: Bounds) {
     }
 }
 
-fn read_into_vec(ptr: *const u8, len: usize) -> Vec<u8> {
-    let mut out = Vec::with_capacity(len);
+/// Checks that no word of any of `secrets` (big-endian integers) is
+/// left in the memory of `value` after it is dropped.
+///
+/// Large integers are stored as arrays of native-endian `u64` words,
+/// least significant first.
+fn check_secrets_gone_on_drop<T>(value: Pin<Box<T>>, secrets: &[&[u8]]) {
+    let ptr = value.deref() as *const T as *const u8;
+    let len = size_of::<T>();
+    assert_ne!(len, 0);
+    println!("this value is {len} bytes in length");
+    let before_drop = read_into_vec(ptr, len);
+    drop(value);
+    let after_drop = read_into_vec(ptr, len);
 
-    for i in 0..len {
-        // Safety: none
-        let byte = unsafe { ptr::read_volatile(ptr.add(i)) };
-        out.push(byte);
+    for (n, secret) in secrets.iter().enumerate() {
+        let words = integer_words(secret);
+        let in_memory = words
+            .iter()
+            .flat_map(|w| w.to_ne_bytes())
+            .collect::<Vec<u8>>();
+        // otherwise this test is not checking anything
+        assert!(
+            contains(&before_drop, &in_memory),
+            "secret {n} not found in value before drop"
+        );
+
+        for (i, word) in words.iter().enumerate() {
+            if *word != 0 && contains(&after_drop, &word.to_ne_bytes()) {
+                println!("before_drop: {before_drop:02x?}");
+                println!("after_drop: {after_drop:02x?}");
+                panic!("word {i} of secret {n} was not cleared after drop");
+            }
+        }
     }
-    out
 }
 
-enum Bounds {
-    All,
-    SkipPrefix(usize),
+fn integer_words(big_endian: &[u8]) -> Vec<u64> {
+    let start = big_endian
+        .iter()
+        .position(|b| *b != 0)
+        .unwrap_or(big_endian.len());
+    big_endian[start..]
+        .rchunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold(0u64, |word, byte| word << 8 | *byte as u64)
+        })
+        .collect()
 }
 
-impl Bounds {
-    fn start(&self) -> usize {
-        match self {
-            Bounds::All => HEAP_FREELIST_ZONE.0,
-            Bounds::SkipPrefix(prefix) => {
-                assert!(HEAP_FREELIST_ZONE.0 <= *prefix);
-                *prefix
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// Returns the private integers (`d`, `p`, `q`, `dp`, `dq`, `qinv`) from
+/// a PKCS#1 `RSAPrivateKey`.
+fn pkcs1_private_integers(der: &[u8]) -> Vec<&[u8]> {
+    fn take(der: &[u8], tag: u8) -> (&[u8], &[u8]) {
+        assert_eq!(der[0], tag);
+        let (len, header) = match der[1] {
+            short @ 0..=0x7f => (short as usize, 2),
+            long => {
+                let octets = (long & 0x7f) as usize;
+                let len = der[2..2 + octets]
+                    .iter()
+                    .fold(0usize, |len, byte| len << 8 | *byte as usize);
+                (len, 2 + octets)
             }
-        }
+        };
+        (&der[header..header + len], &der[header + len..])
     }
 
-    fn end(&self, len: usize) -> usize {
-        len - HEAP_FREELIST_ZONE.1
+    let (mut body, _) = take(der, 0x30);
+    let mut integers = vec![];
+    while !body.is_empty() {
+        let (integer, rest) = take(body, 0x02);
+        integers.push(integer);
+        body = rest;
     }
+
+    // version, n, e, then the private parts
+    assert_eq!(integers.len(), 9);
+    integers.split_off(3)
+}
+
+fn read_into_vec(ptr: *const u8, len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len);
+
+    for i in 0..len {
+        // Safety: none
+        let byte = unsafe { ptr::read_volatile(ptr.add(i)) };
+        out.push(byte);
+    }
+    out
 }
 
 /// Bytes written by the heap to (probably) keep the freed chunk
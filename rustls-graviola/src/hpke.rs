@@ -0,0 +1,293 @@
+use core::fmt;
+
+use rustls::crypto::hpke::{
+    EncapsulatedSecret, Hpke, HpkeOpener, HpkePrivateKey, HpkePublicKey, HpkeSealer, HpkeSuite,
+};
+use rustls::internal::msgs::enums::{HpkeAead, HpkeKdf, HpkeKem};
+use rustls::internal::msgs::handshake::HpkeSymmetricCipherSuite;
+
+use graviola::hpke::{Aead, Context, Kdf, Kem, Suite};
+
+/// All supported HPKE suites.
+///
+/// These are usable for Encrypted Client Hello.  Each KEM is paired
+/// with its own KDF, and each supported AEAD.
+pub static ALL_SUPPORTED_SUITES: &[&dyn Hpke] = &[
+    DH_KEM_X25519_HKDF_SHA256_AES_128,
+    DH_KEM_X25519_HKDF_SHA256_AES_256,
+    DH_KEM_X25519_HKDF_SHA256_CHACHA20_POLY1305,
+    DH_KEM_P256_HKDF_SHA256_AES_128,
+    DH_KEM_P256_HKDF_SHA256_AES_256,
+    DH_KEM_P256_HKDF_SHA256_CHACHA20_POLY1305,
+    DH_KEM_P384_HKDF_SHA384_AES_128,
+    DH_KEM_P384_HKDF_SHA384_AES_256,
+    DH_KEM_P384_HKDF_SHA384_CHACHA20_POLY1305,
+];
+
+/// DHKEM(X25519, HKDF-SHA256) with HKDF-SHA256 and AES-128-GCM.
+pub static DH_KEM_X25519_HKDF_SHA256_AES_128: &HpkeGraviola =
+    &HpkeGraviola::new(Kem::DhKemX25519HkdfSha256, Aead::Aes128Gcm);
+
+/// DHKEM(X25519, HKDF-SHA256) with HKDF-SHA256 and AES-256-GCM.
+pub static DH_KEM_X25519_HKDF_SHA256_AES_256: &HpkeGraviola =
+    &HpkeGraviola::new(Kem::DhKemX25519HkdfSha256, Aead::Aes256Gcm);
+
+/// DHKEM(X25519, HKDF-SHA256) with HKDF-SHA256 and ChaCha20Poly1305.
+pub static DH_KEM_X25519_HKDF_SHA256_CHACHA20_POLY1305: &HpkeGraviola =
+    &HpkeGraviola::new(Kem::DhKemX25519HkdfSha256, Aead::ChaCha20Poly1305);
+
+/// DHKEM(P-256, HKDF-SHA256) with HKDF-SHA256 and AES-128-GCM.
+pub static DH_KEM_P256_HKDF_SHA256_AES_128: &HpkeGraviola =
+    &HpkeGraviola::new(Kem::DhKemP256HkdfSha256, Aead::Aes128Gcm);
+
+/// DHKEM(P-256, HKDF-SHA256) with HKDF-SHA256 and AES-256-GCM.
+pub static DH_KEM_P256_HKDF_SHA256_AES_256: &HpkeGraviola =
+    &HpkeGraviola::new(Kem::DhKemP256HkdfSha256, Aead::Aes256Gcm);
+
+/// DHKEM(P-256, HKDF-SHA256) with HKDF-SHA256 and ChaCha20Poly1305.
+pub static DH_KEM_P256_HKDF_SHA256_CHACHA20_POLY1305: &HpkeGraviola =
+    &HpkeGraviola::new(Kem::DhKemP256HkdfSha256, Aead::ChaCha20Poly1305);
+
+/// DHKEM(P-384, HKDF-SHA384) with HKDF-SHA384 and AES-128-GCM.
+pub static DH_KEM_P384_HKDF_SHA384_AES_128: &HpkeGraviola =
+    &HpkeGraviola::new(Kem::DhKemP384HkdfSha384, Aead::Aes128Gcm);
+
+/// DHKEM(P-384, HKDF-SHA384) with HKDF-SHA384 and AES-256-GCM.
+pub static DH_KEM_P384_HKDF_SHA384_AES_256: &HpkeGraviola =
+    &HpkeGraviola::new(Kem::DhKemP384HkdfSha384, Aead::Aes256Gcm);
+
+/// DHKEM(P-384, HKDF-SHA384) with HKDF-SHA384 and ChaCha20Poly1305.
+pub static DH_KEM_P384_HKDF_SHA384_CHACHA20_POLY1305: &HpkeGraviola =
+    &HpkeGraviola::new(Kem::DhKemP384HkdfSha384, Aead::ChaCha20Poly1305);
+
+/// An HPKE suite implemented using Graviola.
+///
+/// Only the base mode is offered by the rustls [`Hpke`] trait.
+#[derive(Debug)]
+pub struct HpkeGraviola {
+    suite: Suite,
+    rustls_suite: HpkeSuite,
+}
+
+impl HpkeGraviola {
+    const fn new(kem: Kem, aead: Aead) -> Self {
+        let (kdf, kem_id, kdf_id) = match kem {
+            Kem::DhKemP256HkdfSha256 => (
+                Kdf::HkdfSha256,
+                HpkeKem::DHKEM_P256_HKDF_SHA256,
+                HpkeKdf::HKDF_SHA256,
+            ),
+            Kem::DhKemP384HkdfSha384 => (
+                Kdf::HkdfSha384,
+                HpkeKem::DHKEM_P384_HKDF_SHA384,
+                HpkeKdf::HKDF_SHA384,
+            ),
+            Kem::DhKemX25519HkdfSha256 => (
+                Kdf::HkdfSha256,
+                HpkeKem::DHKEM_X25519_HKDF_SHA256,
+                HpkeKdf::HKDF_SHA256,
+            ),
+        };
+        let aead_id = match aead {
+            Aead::Aes128Gcm => HpkeAead::AES_128_GCM,
+            Aead::Aes256Gcm => HpkeAead::AES_256_GCM,
+            Aead::ChaCha20Poly1305 => HpkeAead::CHACHA20_POLY_1305,
+        };
+
+        Self {
+            suite: Suite { kem, kdf, aead },
+            rustls_suite: HpkeSuite {
+                kem: kem_id,
+                sym: HpkeSymmetricCipherSuite { kdf_id, aead_id },
+            },
+        }
+    }
+}
+
+impl Hpke for HpkeGraviola {
+    fn seal(
+        &self,
+        info: &[u8],
+        aad: &[u8],
+        plaintext: &[u8],
+        pub_key: &HpkePublicKey,
+    ) -> Result<(EncapsulatedSecret, Vec<u8>), rustls::Error> {
+        let (enc, mut sealer) = self.setup_sealer(info, pub_key)?;
+        Ok((enc, sealer.seal(aad, plaintext)?))
+    }
+
+    fn setup_sealer(
+        &self,
+        info: &[u8],
+        pub_key: &HpkePublicKey,
+    ) -> Result<(EncapsulatedSecret, Box<dyn HpkeSealer + 'static>), rustls::Error> {
+        let (enc, context) = self
+            .suite
+            .setup_sender(&pub_key.0, info, None)
+            .map_err(|err| rustls::Error::General(format!("HPKE setup failed: {err:?}")))?;
+        Ok((
+            EncapsulatedSecret(enc.as_ref().to_vec()),
+            Box::new(Sealer(context)),
+        ))
+    }
+
+    fn open(
+        &self,
+        enc: &EncapsulatedSecret,
+        info: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+        secret_key: &HpkePrivateKey,
+    ) -> Result<Vec<u8>, rustls::Error> {
+        self.setup_opener(enc, info, secret_key)?
+            .open(aad, ciphertext)
+    }
+
+    fn setup_opener(
+        &self,
+        enc: &EncapsulatedSecret,
+        info: &[u8],
+        secret_key: &HpkePrivateKey,
+    ) -> Result<Box<dyn HpkeOpener + 'static>, rustls::Error> {
+        let context = self
+            .suite
+            .setup_receiver(&enc.0, secret_key.secret_bytes(), info, None)
+            .map_err(|err| rustls::Error::General(format!("HPKE setup failed: {err:?}")))?;
+        Ok(Box::new(Opener(context)))
+    }
+
+    fn generate_key_pair(&self) -> Result<(HpkePublicKey, HpkePrivateKey), rustls::Error> {
+        let (private_key, public_key) = self
+            .suite
+            .kem
+            .generate_key_pair()
+            .map_err(|_| rustls::Error::from(rustls::crypto::GetRandomFailed))?;
+        Ok((
+            HpkePublicKey(public_key.as_ref().to_vec()),
+            HpkePrivateKey::from(private_key.as_ref().to_vec()),
+        ))
+    }
+
+    fn suite(&self) -> HpkeSuite {
+        self.rustls_suite
+    }
+}
+
+struct Sealer(Context);
+
+impl HpkeSealer for Sealer {
+    fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, rustls::Error> {
+        let mut ciphertext = Vec::with_capacity(plaintext.len() + TAG_LEN);
+        ciphertext.extend_from_slice(plaintext);
+
+        let mut tag = [0u8; TAG_LEN];
+        self.0
+            .seal(aad, &mut ciphertext, &mut tag)
+            .map_err(|err| rustls::Error::General(format!("HPKE seal failed: {err:?}")))?;
+        ciphertext.extend_from_slice(&tag);
+        Ok(ciphertext)
+    }
+}
+
+impl fmt::Debug for Sealer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Note: we deliberately omit the context from the debug output.
+        f.debug_struct("Sealer").finish_non_exhaustive()
+    }
+}
+
+struct Opener(Context);
+
+impl HpkeOpener for Opener {
+    fn open(&mut self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, rustls::Error> {
+        if ciphertext.len() < TAG_LEN {
+            return Err(rustls::Error::DecryptError);
+        }
+
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - TAG_LEN);
+        let mut plain = ciphertext.to_vec();
+        self.0
+            .open(aad, &mut plain, tag)
+            .map_err(|_| rustls::Error::DecryptError)?;
+        Ok(plain)
+    }
+}
+
+impl fmt::Debug for Opener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Note: we deliberately omit the context from the debug output.
+        f.debug_struct("Opener").finish_non_exhaustive()
+    }
+}
+
+const TAG_LEN: usize = 16;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_test() {
+        for suite in ALL_SUPPORTED_SUITES {
+            let _ = format!("{suite:?}");
+
+            let (pk, sk) = suite.generate_key_pair().unwrap();
+            let info = b"Ode on a Grecian Urn";
+            let aad = &[0xc0, 0xff, 0xee];
+
+            let (enc, mut sealer) = suite.setup_sealer(info, &pk).unwrap();
+            assert_eq!(format!("{sealer:?}"), "Sealer { .. }");
+            assert!(suite.setup_sealer(info, &HpkePublicKey(vec![])).is_err());
+
+            let ct1 = sealer.seal(aad, b"hello").unwrap();
+            let ct2 = sealer.seal(aad, b"world").unwrap();
+            assert_eq!(ct1.len(), 5 + TAG_LEN);
+            assert_ne!(ct1, ct2);
+
+            let mut opener = suite.setup_opener(&enc, info, &sk).unwrap();
+            assert_eq!(format!("{opener:?}"), "Opener { .. }");
+            assert_eq!(opener.open(aad, &ct1).unwrap(), b"hello");
+            assert_eq!(
+                opener.open(aad, &ct1).unwrap_err(),
+                rustls::Error::DecryptError
+            );
+            assert_eq!(opener.open(aad, &ct2).unwrap(), b"world");
+
+            // single-shot API
+            let (enc, ct) = suite.seal(info, aad, b"hello", &pk).unwrap();
+            assert_eq!(suite.open(&enc, info, aad, &ct, &sk).unwrap(), b"hello");
+            assert_eq!(
+                suite.open(&enc, info, b"", &ct, &sk).unwrap_err(),
+                rustls::Error::DecryptError
+            );
+            assert_eq!(
+                suite
+                    .open(&enc, info, aad, &ct[..TAG_LEN - 1], &sk)
+                    .unwrap_err(),
+                rustls::Error::DecryptError
+            );
+        }
+    }
+
+    #[test]
+    fn suite_ids() {
+        for suite in ALL_SUPPORTED_SUITES {
+            let s = suite.suite();
+            let (pk, _) = suite.generate_key_pair().unwrap();
+            let expected_len = match s.kem {
+                HpkeKem::DHKEM_X25519_HKDF_SHA256 => 32,
+                HpkeKem::DHKEM_P256_HKDF_SHA256 => 65,
+                HpkeKem::DHKEM_P384_HKDF_SHA384 => 97,
+                _ => unreachable!(),
+            };
+            assert_eq!(pk.0.len(), expected_len);
+        }
+        assert_eq!(
+            DH_KEM_P384_HKDF_SHA384_AES_256.suite().sym,
+            HpkeSymmetricCipherSuite {
+                kdf_id: HpkeKdf::HKDF_SHA384,
+                aead_id: HpkeAead::AES_256_GCM,
+            }
+        );
+    }
+}
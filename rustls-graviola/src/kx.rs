@@ -1,8 +1,8 @@
 use crypto::SupportedKxGroup;
 use rustls::crypto;
-use rustls::ffdhe_groups::FfdheGroup;
+use rustls::ffdhe_groups::{self, FfdheGroup};
 
-use graviola::key_agreement::{p256, p384, x25519};
+use graviola::key_agreement::{ffdhe, p256, p384, x25519};
 
 /// All key exchange algorithms, in order of preference.
 pub const ALL_KX_GROUPS: &[&dyn SupportedKxGroup] = &[
@@ -11,6 +11,30 @@ pub const ALL_KX_GROUPS: &[&dyn SupportedKxGroup] = &[
     &P384 as &dyn SupportedKxGroup,
 ];
 
+/// The RFC7919 finite field key exchange groups, in order of preference.
+///
+/// These are not included in [`ALL_KX_GROUPS`].  They are only useful
+/// for TLS1.2 peers that support only the `TLS_DHE_RSA_WITH_*` cipher
+/// suites in [`crate::suites::DHE_CIPHER_SUITES`].  Add both to a
+/// [`rustls::crypto::CryptoProvider`] to use them:
+///
+/// ```rust
+/// let mut provider = rustls_graviola::default_provider();
+/// provider
+///     .cipher_suites
+///     .extend(rustls_graviola::suites::DHE_CIPHER_SUITES);
+/// provider
+///     .kx_groups
+///     .extend(rustls_graviola::kx::FFDHE_KX_GROUPS);
+/// ```
+pub const FFDHE_KX_GROUPS: &[&dyn SupportedKxGroup] = &[
+    &FFDHE2048 as &dyn SupportedKxGroup,
+    &FFDHE3072 as &dyn SupportedKxGroup,
+    &FFDHE4096 as &dyn SupportedKxGroup,
+    &FFDHE6144 as &dyn SupportedKxGroup,
+    &FFDHE8192 as &dyn SupportedKxGroup,
+];
+
 /// Key exchange using X25519.
 #[derive(Debug)]
 pub struct X25519;
@@ -173,3 +197,148 @@ impl crypto::ActiveKeyExchange for ActiveP384 {
         P384.name()
     }
 }
+
+macro_rules! ffdhe_group {
+    ($name:ident, $group:ident, $doc:literal) => {
+        #[doc = $doc]
+        #[derive(Debug)]
+        pub struct $name;
+
+        impl SupportedKxGroup for $name {
+            fn start(&self) -> Result<Box<dyn crypto::ActiveKeyExchange>, rustls::Error> {
+                let priv_key = ffdhe::PrivateKey::new_random(ffdhe::Group::$group)
+                    .map_err(|_| rustls::Error::from(crypto::GetRandomFailed))?;
+                let pub_key = priv_key.public_key();
+
+                Ok(Box::new(ActiveFfdhe {
+                    priv_key,
+                    pub_key,
+                    ffdhe_group: ffdhe_groups::$name,
+                    name: rustls::NamedGroup::$name,
+                }))
+            }
+
+            fn ffdhe_group(&self) -> Option<FfdheGroup<'static>> {
+                Some(ffdhe_groups::$name)
+            }
+
+            fn name(&self) -> rustls::NamedGroup {
+                rustls::NamedGroup::$name
+            }
+        }
+    };
+}
+
+ffdhe_group!(
+    FFDHE2048,
+    Ffdhe2048,
+    "Finite field key exchange using the RFC7919 `ffdhe2048` group."
+);
+ffdhe_group!(
+    FFDHE3072,
+    Ffdhe3072,
+    "Finite field key exchange using the RFC7919 `ffdhe3072` group."
+);
+ffdhe_group!(
+    FFDHE4096,
+    Ffdhe4096,
+    "Finite field key exchange using the RFC7919 `ffdhe4096` group."
+);
+ffdhe_group!(
+    FFDHE6144,
+    Ffdhe6144,
+    "Finite field key exchange using the RFC7919 `ffdhe6144` group."
+);
+ffdhe_group!(
+    FFDHE8192,
+    Ffdhe8192,
+    "Finite field key exchange using the RFC7919 `ffdhe8192` group."
+);
+
+struct ActiveFfdhe {
+    priv_key: ffdhe::PrivateKey,
+    pub_key: ffdhe::PublicKey,
+    ffdhe_group: FfdheGroup<'static>,
+    name: rustls::NamedGroup,
+}
+
+impl crypto::ActiveKeyExchange for ActiveFfdhe {
+    /// This returns the shared secret padded to the length of the prime,
+    /// as TLS1.3 requires.  `complete_for_tls_version()` strips that
+    /// padding for TLS1.2.
+    fn complete(self: Box<Self>, peer: &[u8]) -> Result<crypto::SharedSecret, rustls::Error> {
+        let their_pub = ffdhe::PublicKey::from_bytes(self.priv_key.group(), peer)
+            .map_err(|_| rustls::Error::from(rustls::PeerMisbehaved::InvalidKeyShare))?;
+        let shared_secret = self
+            .priv_key
+            .diffie_hellman(&their_pub)
+            .map_err(|_| rustls::Error::from(rustls::PeerMisbehaved::InvalidKeyShare))?;
+        Ok(crypto::SharedSecret::from(shared_secret.as_ref()))
+    }
+
+    fn pub_key(&self) -> &[u8] {
+        self.pub_key.as_bytes()
+    }
+
+    fn ffdhe_group(&self) -> Option<FfdheGroup<'static>> {
+        Some(self.ffdhe_group)
+    }
+
+    fn group(&self) -> rustls::NamedGroup {
+        self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ffdhe_agreement() {
+        for group in FFDHE_KX_GROUPS {
+            let p_len = group.ffdhe_group().unwrap().p.len();
+            let a = group.start().unwrap();
+            let b = group.start().unwrap();
+            assert_eq!(a.group(), group.name());
+            assert_eq!(a.ffdhe_group(), group.ffdhe_group());
+            assert_eq!(a.pub_key().len(), p_len);
+            assert_eq!(b.pub_key().len(), p_len);
+
+            let a_pub = a.pub_key().to_vec();
+            let b_pub = b.pub_key().to_vec();
+            let ab = a
+                .complete_for_tls_version(&b_pub, &rustls::version::TLS12)
+                .unwrap();
+            let ba = b.complete(&a_pub).unwrap();
+            assert_eq!(ba.secret_bytes().len(), p_len);
+
+            // TLS1.2 strips leading zeroes from the TLS1.3 encoding
+            let first_nonzero = ba.secret_bytes().iter().position(|b| *b != 0).unwrap();
+            assert_eq!(ab.secret_bytes(), &ba.secret_bytes()[first_nonzero..]);
+        }
+    }
+
+    #[test]
+    fn ffdhe_peer_range_checks() {
+        for group in FFDHE_KX_GROUPS {
+            let p = group.ffdhe_group().unwrap().p;
+            let mut p_minus_1 = p.to_vec();
+            *p_minus_1.last_mut().unwrap() -= 1;
+            let mut p_minus_2 = p.to_vec();
+            *p_minus_2.last_mut().unwrap() -= 2;
+
+            for bad in [&[][..], &[0], &[1], &p_minus_1, p] {
+                assert_eq!(
+                    group.start().unwrap().complete(bad).err(),
+                    Some(rustls::Error::PeerMisbehaved(
+                        rustls::PeerMisbehaved::InvalidKeyShare
+                    ))
+                );
+            }
+
+            // accepting p - 2 but not p - 1 means our prime is the same as rustls's
+            group.start().unwrap().complete(&[2]).unwrap();
+            group.start().unwrap().complete(&p_minus_2).unwrap();
+        }
+    }
+}
@@ -12,14 +12,33 @@
 //! ```
 //!
 //! And then use rustls as normal.
+//!
+//! To load password-protected (encrypted PKCS#8) private keys, see
+//! [`EncryptedKeyProvider`].
+//!
+//! # Crate features
+//!
+//! - `thread-rng`: use [`graviola::random::ThreadRandom`] for all
+//!   randomness, instead of making a system call for each request.
+//!   This covers [`CryptoProvider::secure_random`], key shares and
+//!   signatures.
+
+use std::sync::Arc;
 
 use rustls::crypto::CryptoProvider;
+use rustls::server::ProducesTickets;
 
 mod aead;
 mod hash;
 mod hmac;
+mod quic;
 mod sign;
-mod verify;
+mod ticketer;
+
+pub use sign::EncryptedKeyProvider;
+
+/// Supported signature verification algorithms.
+pub mod verify;
 
 /// Supported key exchange algorithms.
 pub mod kx;
@@ -27,6 +46,9 @@ pub mod kx;
 /// Supported cipher suites.
 pub mod suites;
 
+/// Supported HPKE suites, for Encrypted Client Hello.
+pub mod hpke;
+
 /// This is a rustls [`CryptoProvider`] using cryptography from Graviola.
 ///
 /// This provides the same algorithms as the rustls *ring*-based
@@ -41,6 +63,21 @@ pub fn default_provider() -> CryptoProvider {
     }
 }
 
+/// Makes the recommended session ticket encrypter for rustls servers.
+///
+/// Tickets are encrypted with ChaCha20Poly1305, using a random nonce
+/// for each ticket.  Each ticket is prefixed with a random name
+/// identifying its key.
+///
+/// The key is replaced with a new random key every six hours.  The
+/// previous key is kept to decrypt tickets for one further period, so
+/// tickets have a 12 hour life.
+///
+/// Use it by setting [`rustls::ServerConfig::ticketer`].
+pub fn ticketer() -> Result<Arc<dyn ProducesTickets>, rustls::Error> {
+    ticketer::new()
+}
+
 #[derive(Debug)]
 struct RngProvider;
 
@@ -0,0 +1,463 @@
+use rustls::crypto::cipher::{AeadKey, Iv, Nonce};
+use rustls::quic;
+
+use graviola::aead::{AesGcm, ChaCha20Poly1305};
+use graviola::quic::{AesHeaderProtection, ChaCha20HeaderProtection};
+
+const TAG_LEN: usize = 16;
+
+pub struct QuicAesGcm(pub usize);
+
+impl quic::Algorithm for QuicAesGcm {
+    fn packet_key(&self, key: AeadKey, iv: Iv) -> Box<dyn quic::PacketKey> {
+        Box::new(PacketKey {
+            aead: AesGcm::new(key.as_ref()),
+            iv,
+            // <https://datatracker.ietf.org/doc/html/rfc9001#section-6.6>
+            confidentiality_limit: 1 << 23,
+            integrity_limit: 1 << 52,
+        })
+    }
+
+    fn header_protection_key(&self, key: AeadKey) -> Box<dyn quic::HeaderProtectionKey> {
+        Box::new(HeaderProtectionKey(AesHeaderProtection::new(key.as_ref())))
+    }
+
+    fn aead_key_len(&self) -> usize {
+        self.0
+    }
+}
+
+pub struct QuicChacha20Poly1305;
+
+impl quic::Algorithm for QuicChacha20Poly1305 {
+    fn packet_key(&self, key: AeadKey, iv: Iv) -> Box<dyn quic::PacketKey> {
+        Box::new(PacketKey {
+            aead: ChaCha20Poly1305::new(key.as_ref().try_into().unwrap()),
+            iv,
+            // <https://datatracker.ietf.org/doc/html/rfc9001#section-6.6>
+            confidentiality_limit: u64::MAX,
+            integrity_limit: 1 << 36,
+        })
+    }
+
+    fn header_protection_key(&self, key: AeadKey) -> Box<dyn quic::HeaderProtectionKey> {
+        Box::new(HeaderProtectionKey(ChaCha20HeaderProtection::new(
+            key.as_ref().try_into().unwrap(),
+        )))
+    }
+
+    fn aead_key_len(&self) -> usize {
+        32
+    }
+}
+
+/// The common shape of graviola's AEAD types.
+trait Aead: Send + Sync {
+    fn encrypt(
+        &self,
+        nonce: &[u8; 12],
+        aad: &[u8],
+        cipher_inout: &mut [u8],
+        tag_out: &mut [u8; 16],
+    );
+
+    fn decrypt(
+        &self,
+        nonce: &[u8; 12],
+        aad: &[u8],
+        cipher_inout: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), graviola::Error>;
+}
+
+impl Aead for AesGcm {
+    fn encrypt(
+        &self,
+        nonce: &[u8; 12],
+        aad: &[u8],
+        cipher_inout: &mut [u8],
+        tag_out: &mut [u8; 16],
+    ) {
+        self.encrypt(nonce, aad, cipher_inout, tag_out)
+    }
+
+    fn decrypt(
+        &self,
+        nonce: &[u8; 12],
+        aad: &[u8],
+        cipher_inout: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), graviola::Error> {
+        self.decrypt(nonce, aad, cipher_inout, tag)
+    }
+}
+
+impl Aead for ChaCha20Poly1305 {
+    fn encrypt(
+        &self,
+        nonce: &[u8; 12],
+        aad: &[u8],
+        cipher_inout: &mut [u8],
+        tag_out: &mut [u8; 16],
+    ) {
+        self.encrypt(nonce, aad, cipher_inout, tag_out)
+    }
+
+    fn decrypt(
+        &self,
+        nonce: &[u8; 12],
+        aad: &[u8],
+        cipher_inout: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), graviola::Error> {
+        self.decrypt(nonce, aad, cipher_inout, tag)
+    }
+}
+
+struct PacketKey<A: Aead> {
+    aead: A,
+    iv: Iv,
+    confidentiality_limit: u64,
+    integrity_limit: u64,
+}
+
+impl<A: Aead> quic::PacketKey for PacketKey<A> {
+    fn encrypt_in_place(
+        &self,
+        packet_number: u64,
+        header: &[u8],
+        payload: &mut [u8],
+    ) -> Result<quic::Tag, rustls::Error> {
+        let nonce = Nonce::new(&self.iv, packet_number);
+        let mut tag = [0u8; TAG_LEN];
+        self.aead.encrypt(&nonce.0, header, payload, &mut tag);
+        Ok(quic::Tag::from(&tag[..]))
+    }
+
+    fn decrypt_in_place<'a>(
+        &self,
+        packet_number: u64,
+        header: &[u8],
+        payload: &'a mut [u8],
+    ) -> Result<&'a [u8], rustls::Error> {
+        if payload.len() < TAG_LEN {
+            return Err(rustls::Error::DecryptError);
+        }
+        let nonce = Nonce::new(&self.iv, packet_number);
+        let cipher_len = payload.len() - TAG_LEN;
+        let (cipher, tag) = payload.split_at_mut(cipher_len);
+
+        self.aead
+            .decrypt(&nonce.0, header, cipher, tag)
+            .map_err(|_| rustls::Error::DecryptError)?;
+
+        Ok(&payload[..cipher_len])
+    }
+
+    fn tag_len(&self) -> usize {
+        TAG_LEN
+    }
+
+    fn confidentiality_limit(&self) -> u64 {
+        self.confidentiality_limit
+    }
+
+    fn integrity_limit(&self) -> u64 {
+        self.integrity_limit
+    }
+}
+
+/// The common shape of graviola's QUIC header protection types.
+trait HeaderMask: Send + Sync {
+    fn mask(&self, sample: &[u8; 16]) -> [u8; 5];
+}
+
+impl HeaderMask for AesHeaderProtection {
+    fn mask(&self, sample: &[u8; 16]) -> [u8; 5] {
+        self.mask(sample)
+    }
+}
+
+impl HeaderMask for ChaCha20HeaderProtection {
+    fn mask(&self, sample: &[u8; 16]) -> [u8; 5] {
+        self.mask(sample)
+    }
+}
+
+struct HeaderProtectionKey<M: HeaderMask>(M);
+
+impl<M: HeaderMask> HeaderProtectionKey<M> {
+    fn xor_in_place(
+        &self,
+        sample: &[u8],
+        first: &mut u8,
+        packet_number: &mut [u8],
+        masked: bool,
+    ) -> Result<(), rustls::Error> {
+        // This implements "Header Protection Application" from
+        // <https://datatracker.ietf.org/doc/html/rfc9001#section-5.4.1>
+        let sample = sample
+            .try_into()
+            .map_err(|_| rustls::Error::General("sample of invalid length".into()))?;
+        let mask = self.0.mask(sample);
+        let (first_mask, pn_mask) = mask.split_first().unwrap();
+
+        if packet_number.len() > pn_mask.len() {
+            return Err(rustls::Error::General("packet number too long".into()));
+        }
+
+        const LONG_HEADER_FORM: u8 = 0x80;
+        let bits = match *first & LONG_HEADER_FORM == LONG_HEADER_FORM {
+            true => 0x0f,  // Long header: 4 bits masked
+            false => 0x1f, // Short header: 5 bits masked
+        };
+
+        // The packet number length is in the unprotected first byte.
+        let first_plain = match masked {
+            true => *first ^ (first_mask & bits),
+            false => *first,
+        };
+        let pn_len = (first_plain & 0x03) as usize + 1;
+
+        *first ^= first_mask & bits;
+        for (dst, m) in packet_number.iter_mut().zip(pn_mask).take(pn_len) {
+            *dst ^= m;
+        }
+
+        Ok(())
+    }
+}
+
+impl<M: HeaderMask> quic::HeaderProtectionKey for HeaderProtectionKey<M> {
+    fn encrypt_in_place(
+        &self,
+        sample: &[u8],
+        first: &mut u8,
+        packet_number: &mut [u8],
+    ) -> Result<(), rustls::Error> {
+        self.xor_in_place(sample, first, packet_number, false)
+    }
+
+    fn decrypt_in_place(
+        &self,
+        sample: &[u8],
+        first: &mut u8,
+        packet_number: &mut [u8],
+    ) -> Result<(), rustls::Error> {
+        self.xor_in_place(sample, first, packet_number, true)
+    }
+
+    fn sample_len(&self) -> usize {
+        16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustls::quic::{Algorithm, Keys, Version};
+    use rustls::{Side, SupportedCipherSuite};
+
+    use super::*;
+    use crate::suites;
+
+    fn tls13_aes_128_gcm_sha256() -> &'static rustls::Tls13CipherSuite {
+        match suites::TLS13_AES_128_GCM_SHA256 {
+            SupportedCipherSuite::Tls13(suite) => suite,
+            _ => unreachable!(),
+        }
+    }
+
+    const INITIAL_DCID: &[u8] = &[0x83, 0x94, 0xc8, 0xf0, 0x3e, 0x51, 0x57, 0x08];
+
+    #[test]
+    fn client_initial() {
+        // from RFC9001 appendix A.2
+        let suite = tls13_aes_128_gcm_sha256();
+        let client = Keys::initial(
+            Version::V1,
+            suite,
+            suite.quic.unwrap(),
+            INITIAL_DCID,
+            Side::Client,
+        );
+
+        let mut header = [
+            0xc3, 0x00, 0x00, 0x00, 0x01, 0x08, 0x83, 0x94, 0xc8, 0xf0, 0x3e, 0x51, 0x57, 0x08,
+            0x00, 0x00, 0x44, 0x9e, 0x00, 0x00, 0x00, 0x02,
+        ];
+
+        // The sample is the first 16 bytes of ciphertext, which depend only
+        // on the first 16 bytes of the (1162 byte) payload.
+        let mut payload = [
+            0x06, 0x00, 0x40, 0xf1, 0x01, 0x00, 0x00, 0xed, 0x03, 0x03, 0xeb, 0xf8, 0xfa, 0x56,
+            0xf1, 0x29,
+        ];
+        client
+            .local
+            .packet
+            .encrypt_in_place(2, &header, &mut payload)
+            .unwrap();
+        let sample = [
+            0xd1, 0xb1, 0xc9, 0x8d, 0xd7, 0x68, 0x9f, 0xb8, 0xec, 0x11, 0xd2, 0x42, 0xb1, 0x23,
+            0xdc, 0x9b,
+        ];
+        assert_eq!(payload, sample);
+
+        let (first, rest) = header.split_at_mut(1);
+        client
+            .local
+            .header
+            .encrypt_in_place(&sample, &mut first[0], &mut rest[17..])
+            .unwrap();
+        assert_eq!(
+            header,
+            [
+                0xc0, 0x00, 0x00, 0x00, 0x01, 0x08, 0x83, 0x94, 0xc8, 0xf0, 0x3e, 0x51, 0x57, 0x08,
+                0x00, 0x00, 0x44, 0x9e, 0x7b, 0x9a, 0xec, 0x34,
+            ]
+        );
+    }
+
+    #[test]
+    fn server_initial() {
+        // from RFC9001 appendix A.3
+        let suite = tls13_aes_128_gcm_sha256();
+        let server = Keys::initial(
+            Version::V1,
+            suite,
+            suite.quic.unwrap(),
+            INITIAL_DCID,
+            Side::Server,
+        );
+        let plain_payload = [
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x06, 0x00, 0x40, 0x5a, 0x02, 0x00, 0x00, 0x56, 0x03,
+            0x03, 0xee, 0xfc, 0xe7, 0xf7, 0xb3, 0x7b, 0xa1, 0xd1, 0x63, 0x2e, 0x96, 0x67, 0x78,
+            0x25, 0xdd, 0xf7, 0x39, 0x88, 0xcf, 0xc7, 0x98, 0x25, 0xdf, 0x56, 0x6d, 0xc5, 0x43,
+            0x0b, 0x9a, 0x04, 0x5a, 0x12, 0x00, 0x13, 0x01, 0x00, 0x00, 0x2e, 0x00, 0x33, 0x00,
+            0x24, 0x00, 0x1d, 0x00, 0x20, 0x9d, 0x3c, 0x94, 0x0d, 0x89, 0x69, 0x0b, 0x84, 0xd0,
+            0x8a, 0x60, 0x99, 0x3c, 0x14, 0x4e, 0xca, 0x68, 0x4d, 0x10, 0x81, 0x28, 0x7c, 0x83,
+            0x4d, 0x53, 0x11, 0xbc, 0xf3, 0x2b, 0xb9, 0xda, 0x1a, 0x00, 0x2b, 0x00, 0x02, 0x03,
+            0x04,
+        ];
+        let plain_header = [
+            0xc1, 0x00, 0x00, 0x00, 0x01, 0x00, 0x08, 0xf0, 0x67, 0xa5, 0x50, 0x2a, 0x42, 0x62,
+            0xb5, 0x00, 0x40, 0x75, 0x00, 0x01,
+        ];
+
+        let mut header = plain_header;
+        let mut payload = plain_payload;
+        let tag = server
+            .local
+            .packet
+            .encrypt_in_place(1, &header, &mut payload)
+            .unwrap();
+        let (first, rest) = header.split_at_mut(1);
+        server
+            .local
+            .header
+            .encrypt_in_place(&payload[2..18], &mut first[0], &mut rest[17..])
+            .unwrap();
+
+        let mut packet = header.to_vec();
+        packet.extend(payload);
+        packet.extend(tag.as_ref());
+        let expected_packet = [
+            0xcf, 0x00, 0x00, 0x00, 0x01, 0x00, 0x08, 0xf0, 0x67, 0xa5, 0x50, 0x2a, 0x42, 0x62,
+            0xb5, 0x00, 0x40, 0x75, 0xc0, 0xd9, 0x5a, 0x48, 0x2c, 0xd0, 0x99, 0x1c, 0xd2, 0x5b,
+            0x0a, 0xac, 0x40, 0x6a, 0x58, 0x16, 0xb6, 0x39, 0x41, 0x00, 0xf3, 0x7a, 0x1c, 0x69,
+            0x79, 0x75, 0x54, 0x78, 0x0b, 0xb3, 0x8c, 0xc5, 0xa9, 0x9f, 0x5e, 0xde, 0x4c, 0xf7,
+            0x3c, 0x3e, 0xc2, 0x49, 0x3a, 0x18, 0x39, 0xb3, 0xdb, 0xcb, 0xa3, 0xf6, 0xea, 0x46,
+            0xc5, 0xb7, 0x68, 0x4d, 0xf3, 0x54, 0x8e, 0x7d, 0xde, 0xb9, 0xc3, 0xbf, 0x9c, 0x73,
+            0xcc, 0x3f, 0x3b, 0xde, 0xd7, 0x4b, 0x56, 0x2b, 0xfb, 0x19, 0xfb, 0x84, 0x02, 0x2f,
+            0x8e, 0xf4, 0xcd, 0xd9, 0x37, 0x95, 0xd7, 0x7d, 0x06, 0xed, 0xbb, 0x7a, 0xaf, 0x2f,
+            0x58, 0x89, 0x18, 0x50, 0xab, 0xbd, 0xca, 0x3d, 0x20, 0x39, 0x8c, 0x27, 0x64, 0x56,
+            0xcb, 0xc4, 0x21, 0x58, 0x40, 0x7d, 0xd0, 0x74, 0xee,
+        ];
+        assert_eq!(packet, expected_packet);
+
+        // and the client can remove it
+        let client = Keys::initial(
+            Version::V1,
+            suite,
+            suite.quic.unwrap(),
+            INITIAL_DCID,
+            Side::Client,
+        );
+        let (header, payload) = packet.split_at_mut(plain_header.len());
+        let (first, rest) = header.split_at_mut(1);
+        client
+            .remote
+            .header
+            .decrypt_in_place(&payload[2..18], &mut first[0], &mut rest[17..])
+            .unwrap();
+        assert_eq!(header, plain_header);
+        let plain = client
+            .remote
+            .packet
+            .decrypt_in_place(1, header, payload)
+            .unwrap();
+        assert_eq!(plain, plain_payload);
+    }
+
+    #[test]
+    fn chacha20_poly1305_short_header() {
+        // from RFC9001 appendix A.5
+        let key = AeadKey::from([
+            0xc6, 0xd9, 0x8f, 0xf3, 0x44, 0x1c, 0x3f, 0xe1, 0xb2, 0x18, 0x20, 0x94, 0xf6, 0x9c,
+            0xaa, 0x2e, 0xd4, 0xb7, 0x16, 0xb6, 0x54, 0x88, 0x96, 0x0a, 0x7a, 0x98, 0x49, 0x79,
+            0xfb, 0x23, 0xe1, 0xc8,
+        ]);
+        let iv = Iv::new([
+            0xe0, 0x45, 0x9b, 0x34, 0x74, 0xbd, 0xd0, 0xe4, 0x4a, 0x41, 0xc1, 0x44,
+        ]);
+        let hp = AeadKey::from([
+            0x25, 0xa2, 0x82, 0xb9, 0xe8, 0x2f, 0x06, 0xf2, 0x1f, 0x48, 0x89, 0x17, 0xa4, 0xfc,
+            0x8f, 0x1b, 0x73, 0x57, 0x36, 0x85, 0x60, 0x85, 0x97, 0xd0, 0xef, 0xcb, 0x07, 0x6b,
+            0x0a, 0xb7, 0xa7, 0xa4,
+        ]);
+        let packet = QuicChacha20Poly1305.packet_key(key, iv);
+        let hpk = QuicChacha20Poly1305.header_protection_key(hp);
+
+        const PN: u64 = 654360564;
+        const PLAIN: &[u8] = &[0x42, 0x00, 0xbf, 0xf4, 0x01];
+
+        let mut buf = PLAIN.to_vec();
+        let (header, payload) = buf.split_at_mut(4);
+        let tag = packet.encrypt_in_place(PN, header, payload).unwrap();
+        buf.extend(tag.as_ref());
+
+        let (header, sample) = buf.split_at_mut(5);
+        let (first, rest) = header.split_at_mut(1);
+        hpk.encrypt_in_place(&sample[..hpk.sample_len()], &mut first[0], rest)
+            .unwrap();
+        assert_eq!(
+            buf,
+            [
+                0x4c, 0xfe, 0x41, 0x89, 0x65, 0x5e, 0x5c, 0xd5, 0x5c, 0x41, 0xf6, 0x90, 0x80, 0x57,
+                0x5d, 0x79, 0x99, 0xc2, 0x5a, 0x5b, 0xfb,
+            ]
+        );
+
+        let (header, sample) = buf.split_at_mut(5);
+        let (first, rest) = header.split_at_mut(1);
+        hpk.decrypt_in_place(&sample[..hpk.sample_len()], &mut first[0], rest)
+            .unwrap();
+        let (header, payload) = buf.split_at_mut(4);
+        let plain = packet.decrypt_in_place(PN, header, payload).unwrap();
+        assert_eq!(plain, &PLAIN[4..]);
+    }
+
+    #[test]
+    fn invalid_header_protection_inputs() {
+        let hpk = QuicAesGcm(16).header_protection_key(AeadKey::from([0u8; 32]));
+        let mut first = 0x40;
+        assert!(hpk
+            .encrypt_in_place(&[0u8; 15], &mut first, &mut [0u8; 4])
+            .is_err());
+        assert!(hpk
+            .encrypt_in_place(&[0u8; 16], &mut first, &mut [0u8; 5])
+            .is_err());
+        assert_eq!(first, 0x40);
+    }
+}
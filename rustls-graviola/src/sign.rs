@@ -3,7 +3,7 @@ use rustls::{pki_types, sign, SignatureScheme};
 use std::sync::Arc;
 
 use graviola::hashing;
-use graviola::signing::{ecdsa, rsa};
+use graviola::signing::{self, ecdsa, rsa};
 
 #[derive(Debug)]
 pub(super) struct Provider;
@@ -14,7 +14,7 @@ impl rustls::crypto::KeyProvider for Provider {
         key_der: pki_types::PrivateKeyDer<'static>,
     ) -> Result<Arc<dyn sign::SigningKey>, rustls::Error> {
         match key_der {
-            pki_types::PrivateKeyDer::Pkcs8(p8) => load_pkcs8(p8),
+            pki_types::PrivateKeyDer::Pkcs8(p8) => load_pkcs8(p8.secret_pkcs8_der()),
             pki_types::PrivateKeyDer::Pkcs1(p1) => load_pkcs1(p1),
             pki_types::PrivateKeyDer::Sec1(sec1) => load_sec1(sec1),
             _ => Err(rustls::Error::General("unhandled private key".to_string())),
@@ -22,20 +22,103 @@ impl rustls::crypto::KeyProvider for Provider {
     }
 }
 
-fn load_pkcs8(
-    key_der: pki_types::PrivatePkcs8KeyDer<'static>,
+/// A rustls [`KeyProvider`] that also accepts encrypted PKCS#8 private keys.
+///
+/// Unencrypted keys are loaded as for [`crate::default_provider()`].
+/// Otherwise, a [`PrivateKeyDer::Pkcs8`] is treated as an encrypted
+/// PKCS#8 `EncryptedPrivateKeyInfo`, and decrypted using the password
+/// returned by the callback given to [`EncryptedKeyProvider::new()`].
+///
+/// See [`graviola::signing::rsa::SigningKey::from_encrypted_pkcs8_der()`]
+/// for the supported encryption algorithms.
+///
+/// Use it like:
+///
+/// ```rust
+/// use rustls::crypto::CryptoProvider;
+/// use rustls_graviola::EncryptedKeyProvider;
+///
+/// let key_provider = EncryptedKeyProvider::new(|| Ok(b"hunter2".to_vec()));
+/// let provider = CryptoProvider {
+///     key_provider: Box::leak(Box::new(key_provider)),
+///     ..rustls_graviola::default_provider()
+/// };
+/// ```
+///
+/// [`KeyProvider`]: rustls::crypto::KeyProvider
+/// [`PrivateKeyDer::Pkcs8`]: pki_types::PrivateKeyDer::Pkcs8
+pub struct EncryptedKeyProvider {
+    password: Box<PasswordCallback>,
+}
+
+type PasswordCallback = dyn Fn() -> Result<Vec<u8>, rustls::Error> + Send + Sync;
+
+impl EncryptedKeyProvider {
+    /// Make a new `EncryptedKeyProvider`.
+    ///
+    /// `password` is called each time an encrypted key is loaded.
+    pub fn new(
+        password: impl Fn() -> Result<Vec<u8>, rustls::Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            password: Box::new(password),
+        }
+    }
+}
+
+impl rustls::crypto::KeyProvider for EncryptedKeyProvider {
+    fn load_private_key(
+        &self,
+        key_der: pki_types::PrivateKeyDer<'static>,
+    ) -> Result<Arc<dyn sign::SigningKey>, rustls::Error> {
+        match key_der {
+            pki_types::PrivateKeyDer::Pkcs8(p8) => match load_pkcs8(p8.secret_pkcs8_der()) {
+                Ok(key) => Ok(key),
+                Err(_) => {
+                    let mut password = (self.password)()?;
+                    let result = load_encrypted_pkcs8(p8.secret_pkcs8_der(), &password);
+                    password.fill(0);
+                    result
+                }
+            },
+            other => Provider.load_private_key(other),
+        }
+    }
+}
+
+impl fmt::Debug for EncryptedKeyProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("EncryptedKeyProvider")
+            .finish_non_exhaustive()
+    }
+}
+
+fn load_encrypted_pkcs8(
+    key_der: &[u8],
+    password: &[u8],
 ) -> Result<Arc<dyn sign::SigningKey>, rustls::Error> {
-    if let Ok(rsa) = rsa::SigningKey::from_pkcs8_der(key_der.secret_pkcs8_der()) {
+    // the plaintext is never longer than the ciphertext.
+    let mut plaintext = vec![0u8; key_der.len()];
+    let result = match signing::decrypt_pkcs8(key_der, password, &mut plaintext) {
+        Ok(key_der) => load_pkcs8(key_der),
+        Err(err) => Err(rustls::Error::General(format!(
+            "cannot decrypt pkcs8 key: {err:?}"
+        ))),
+    };
+    plaintext.fill(0);
+    result
+}
+
+fn load_pkcs8(key_der: &[u8]) -> Result<Arc<dyn sign::SigningKey>, rustls::Error> {
+    if let Ok(rsa) = rsa::SigningKey::from_pkcs8_der(key_der) {
         return Ok(Arc::new(Rsa(Arc::new(rsa))));
     }
 
-    if let Ok(ecp256) = ecdsa::SigningKey::<ecdsa::P256>::from_pkcs8_der(key_der.secret_pkcs8_der())
-    {
+    if let Ok(ecp256) = ecdsa::SigningKey::<ecdsa::P256>::from_pkcs8_der(key_der) {
         return Ok(Arc::new(EcdsaP256(Arc::new(ecp256))));
     }
 
-    if let Ok(ecp384) = ecdsa::SigningKey::<ecdsa::P384>::from_pkcs8_der(key_der.secret_pkcs8_der())
-    {
+    if let Ok(ecp384) = ecdsa::SigningKey::<ecdsa::P384>::from_pkcs8_der(key_der) {
         return Ok(Arc::new(EcdsaP384(Arc::new(ecp384))));
     }
 
@@ -73,40 +156,18 @@ impl sign::SigningKey for Rsa {
     fn choose_scheme(
         &self,
         schemes: &[SignatureScheme],
-    ) -> Option<Box<(dyn sign::Signer + 'static)>> {
-        if schemes.contains(&SignatureScheme::RSA_PSS_SHA512) {
-            Some(Box::new(RsaSigner {
-                key: Arc::clone(&self.0),
-                scheme: SignatureScheme::RSA_PSS_SHA512,
-            }))
-        } else if schemes.contains(&SignatureScheme::RSA_PSS_SHA384) {
-            Some(Box::new(RsaSigner {
-                key: Arc::clone(&self.0),
-                scheme: SignatureScheme::RSA_PSS_SHA384,
-            }))
-        } else if schemes.contains(&SignatureScheme::RSA_PSS_SHA256) {
-            Some(Box::new(RsaSigner {
-                key: Arc::clone(&self.0),
-                scheme: SignatureScheme::RSA_PSS_SHA256,
-            }))
-        } else if schemes.contains(&SignatureScheme::RSA_PKCS1_SHA512) {
-            Some(Box::new(RsaSigner {
-                key: Arc::clone(&self.0),
-                scheme: SignatureScheme::RSA_PKCS1_SHA512,
-            }))
-        } else if schemes.contains(&SignatureScheme::RSA_PKCS1_SHA384) {
-            Some(Box::new(RsaSigner {
-                key: Arc::clone(&self.0),
-                scheme: SignatureScheme::RSA_PKCS1_SHA384,
-            }))
-        } else if schemes.contains(&SignatureScheme::RSA_PKCS1_SHA256) {
-            Some(Box::new(RsaSigner {
-                key: Arc::clone(&self.0),
-                scheme: SignatureScheme::RSA_PKCS1_SHA256,
-            }))
-        } else {
-            None
-        }
+    ) -> Option<Box<dyn sign::Signer + 'static>> {
+        let restriction = self.0.restriction();
+
+        RSA_SCHEMES
+            .iter()
+            .find(|scheme| schemes.contains(scheme) && rsa_scheme_allowed(**scheme, restriction))
+            .map(|scheme| {
+                Box::new(RsaSigner {
+                    key: Arc::clone(&self.0),
+                    scheme: *scheme,
+                }) as Box<dyn sign::Signer>
+            })
     }
 
     fn algorithm(&self) -> rustls::SignatureAlgorithm {
@@ -120,6 +181,36 @@ impl fmt::Debug for Rsa {
     }
 }
 
+/// In order of preference.
+static RSA_SCHEMES: &[SignatureScheme] = &[
+    SignatureScheme::RSA_PSS_SHA512,
+    SignatureScheme::RSA_PSS_SHA384,
+    SignatureScheme::RSA_PSS_SHA256,
+    SignatureScheme::RSA_PKCS1_SHA512,
+    SignatureScheme::RSA_PKCS1_SHA384,
+    SignatureScheme::RSA_PKCS1_SHA256,
+];
+
+/// Keys from `id-RSASSA-PSS` encodings may only be used for PSS, and
+/// perhaps only with one hash function and salt length.
+///
+/// TLS's `rsa_pss_pss_*` schemes are not supported by rustls, so
+/// such keys are offered for the `rsa_pss_rsae_*` schemes.  These
+/// have the same signature format.
+fn rsa_scheme_allowed(scheme: SignatureScheme, restriction: rsa::Restriction) -> bool {
+    let hash = match scheme {
+        SignatureScheme::RSA_PSS_SHA256 => rsa::PssHash::Sha256,
+        SignatureScheme::RSA_PSS_SHA384 => rsa::PssHash::Sha384,
+        SignatureScheme::RSA_PSS_SHA512 => rsa::PssHash::Sha512,
+        _ => return restriction == rsa::Restriction::None,
+    };
+
+    match restriction {
+        rsa::Restriction::None | rsa::Restriction::Pss => true,
+        rsa::Restriction::PssWith(params) => params == rsa::PssParameters::new(hash),
+    }
+}
+
 struct RsaSigner {
     key: Arc<rsa::SigningKey>,
     scheme: SignatureScheme,
@@ -159,7 +250,7 @@ impl sign::SigningKey for EcdsaP256 {
     fn choose_scheme(
         &self,
         schemes: &[SignatureScheme],
-    ) -> Option<Box<(dyn sign::Signer + 'static)>> {
+    ) -> Option<Box<dyn sign::Signer + 'static>> {
         if schemes.contains(&SignatureScheme::ECDSA_NISTP256_SHA256) {
             Some(Box::new(Self(self.0.clone())))
         } else {
@@ -200,7 +291,7 @@ impl sign::SigningKey for EcdsaP384 {
     fn choose_scheme(
         &self,
         schemes: &[SignatureScheme],
-    ) -> Option<Box<(dyn sign::Signer + 'static)>> {
+    ) -> Option<Box<dyn sign::Signer + 'static>> {
         if schemes.contains(&SignatureScheme::ECDSA_NISTP384_SHA384) {
             Some(Box::new(Self(self.0.clone())))
         } else {
@@ -234,3 +325,112 @@ impl fmt::Debug for EcdsaP384 {
         f.debug_struct("EcdsaP384").finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::crypto::KeyProvider;
+
+    fn load(key_der: &'static [u8]) -> Arc<dyn sign::SigningKey> {
+        Provider
+            .load_private_key(pki_types::PrivateKeyDer::Pkcs8(key_der.into()))
+            .unwrap()
+    }
+
+    fn offered(key_der: &'static [u8], schemes: &[SignatureScheme]) -> Option<SignatureScheme> {
+        load(key_der)
+            .choose_scheme(schemes)
+            .map(|signer| signer.scheme())
+    }
+
+    #[test]
+    fn pss_restricted_keys() {
+        let all = RSA_SCHEMES;
+
+        assert_eq!(
+            offered(
+                include_bytes!("../../graviola/src/high/rsa/rsa2048.pkcs8.der"),
+                &[SignatureScheme::RSA_PKCS1_SHA256]
+            ),
+            Some(SignatureScheme::RSA_PKCS1_SHA256)
+        );
+
+        let pss = include_bytes!("../../graviola/src/high/rsa/rsa2048-pss.pkcs8.der");
+        assert_eq!(offered(pss, all), Some(SignatureScheme::RSA_PSS_SHA512));
+        assert_eq!(offered(pss, &[SignatureScheme::RSA_PKCS1_SHA256]), None);
+
+        let pss_sha256 = include_bytes!("../../graviola/src/high/rsa/rsa2048-pss-sha256.pkcs8.der");
+        assert_eq!(
+            offered(pss_sha256, all),
+            Some(SignatureScheme::RSA_PSS_SHA256)
+        );
+        assert_eq!(
+            offered(pss_sha256, &[SignatureScheme::RSA_PSS_SHA384]),
+            None
+        );
+
+        // salt length is not the hash length, so usable for no TLS schemes
+        let pss_salt20 =
+            include_bytes!("../../graviola/src/high/rsa/rsa2048-pss-sha512-salt20.pkcs8.der");
+        assert_eq!(offered(pss_salt20, all), None);
+    }
+
+    #[test]
+    fn pss_restricted_signature() {
+        let key = load(include_bytes!(
+            "../../graviola/src/high/rsa/rsa2048-pss-sha256.pkcs8.der"
+        ));
+        let signer = key.choose_scheme(RSA_SCHEMES).unwrap();
+        let sig = signer.sign(b"hello").unwrap();
+
+        let public_key = rsa::VerifyingKey::from_spki_der(include_bytes!(
+            "../../graviola/src/high/rsa/rsa2048-pss-sha256.spki.der"
+        ))
+        .unwrap();
+        public_key.verify_pss_sha256(&sig, b"hello").unwrap();
+    }
+
+    #[test]
+    fn encrypted_keys() {
+        let provider = EncryptedKeyProvider::new(|| Ok(b"hunter2".to_vec()));
+
+        for key_der in [
+            &include_bytes!("../../graviola/src/high/rsa/rsa2048.encrypted-pkcs8.der")[..],
+            &include_bytes!("../../graviola/src/high/ecdsa/secp256r1.encrypted-pkcs8.der")[..],
+            &include_bytes!(
+                "../../graviola/src/high/ecdsa/secp384r1-aes256gcm.encrypted-pkcs8.der"
+            )[..],
+            // unencrypted keys work too
+            &include_bytes!("../../graviola/src/high/rsa/rsa2048.pkcs8.der")[..],
+        ] {
+            let key = provider
+                .load_private_key(pki_types::PrivateKeyDer::Pkcs8(key_der.into()))
+                .unwrap();
+            key.choose_scheme(&[
+                SignatureScheme::ECDSA_NISTP256_SHA256,
+                SignatureScheme::ECDSA_NISTP384_SHA384,
+                SignatureScheme::RSA_PSS_SHA256,
+            ])
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn encrypted_key_errors() {
+        let key_der = include_bytes!("../../graviola/src/high/ecdsa/secp256r1.encrypted-pkcs8.der");
+
+        let provider = EncryptedKeyProvider::new(|| Ok(b"hunter3".to_vec()));
+        assert!(provider
+            .load_private_key(pki_types::PrivateKeyDer::Pkcs8(key_der[..].into()))
+            .is_err());
+
+        let provider =
+            EncryptedKeyProvider::new(|| Err(rustls::Error::General("no password".into())));
+        assert_eq!(
+            provider
+                .load_private_key(pki_types::PrivateKeyDer::Pkcs8(key_der[..].into()))
+                .err(),
+            Some(rustls::Error::General("no password".into()))
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::{aead, hash, hmac};
+use super::{aead, hash, hmac, quic};
 
 use rustls::crypto::tls12::PrfUsingHmac;
 use rustls::crypto::tls13::HkdfUsingHmac;
@@ -20,6 +20,16 @@ pub static ALL_CIPHER_SUITES: &[SupportedCipherSuite] = &[
     TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
 ];
 
+/// The TLS1.2 finite field Diffie-Hellman cipher suites, in priority order.
+///
+/// These are not included in [`ALL_CIPHER_SUITES`].  They need one of
+/// the key exchange groups in [`crate::kx::FFDHE_KX_GROUPS`].
+pub static DHE_CIPHER_SUITES: &[SupportedCipherSuite] = &[
+    TLS_DHE_RSA_WITH_AES_256_GCM_SHA384,
+    TLS_DHE_RSA_WITH_AES_128_GCM_SHA256,
+    TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+];
+
 /// The TLS1.3 `TLS_AES_256_GCM_SHA384` cipher suite.
 pub static TLS13_AES_256_GCM_SHA384: SupportedCipherSuite =
     SupportedCipherSuite::Tls13(&Tls13CipherSuite {
@@ -30,7 +40,7 @@ pub static TLS13_AES_256_GCM_SHA384: SupportedCipherSuite =
         },
         hkdf_provider: &HkdfUsingHmac(&hmac::Sha384Hmac),
         aead_alg: &aead::TlsAesGcm(32),
-        quic: None,
+        quic: Some(&quic::QuicAesGcm(32)),
     });
 
 /// The TLS1.3 `TLS_AES_128_GCM_SHA256` cipher suite.
@@ -43,7 +53,7 @@ pub static TLS13_AES_128_GCM_SHA256: SupportedCipherSuite =
         },
         hkdf_provider: &HkdfUsingHmac(&hmac::Sha256Hmac),
         aead_alg: &aead::TlsAesGcm(16),
-        quic: None,
+        quic: Some(&quic::QuicAesGcm(16)),
     });
 
 /// The TLS1.3 `TLS_CHACHA20_POLY1305_SHA256` cipher suite.
@@ -56,7 +66,7 @@ pub static TLS13_CHACHA20_POLY1305_SHA256: SupportedCipherSuite =
         },
         hkdf_provider: &HkdfUsingHmac(&hmac::Sha256Hmac),
         aead_alg: &aead::Chacha20Poly1305,
-        quic: None,
+        quic: Some(&quic::QuicChacha20Poly1305),
     });
 
 /// The TLS1.2 `TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256` cipher suite.
@@ -143,6 +153,48 @@ pub static TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384: SupportedCipherSuite =
         prf_provider: &PrfUsingHmac(&hmac::Sha384Hmac),
     });
 
+/// The TLS1.2 `TLS_DHE_RSA_WITH_AES_256_GCM_SHA384` cipher suite.
+pub static TLS_DHE_RSA_WITH_AES_256_GCM_SHA384: SupportedCipherSuite =
+    SupportedCipherSuite::Tls12(&Tls12CipherSuite {
+        common: CipherSuiteCommon {
+            suite: CipherSuite::TLS_DHE_RSA_WITH_AES_256_GCM_SHA384,
+            hash_provider: &hash::Sha384,
+            confidentiality_limit: 1 << 24,
+        },
+        kx: KeyExchangeAlgorithm::DHE,
+        sign: TLS12_RSA_SCHEMES,
+        aead_alg: &aead::TlsAesGcm(32),
+        prf_provider: &PrfUsingHmac(&hmac::Sha384Hmac),
+    });
+
+/// The TLS1.2 `TLS_DHE_RSA_WITH_AES_128_GCM_SHA256` cipher suite.
+pub static TLS_DHE_RSA_WITH_AES_128_GCM_SHA256: SupportedCipherSuite =
+    SupportedCipherSuite::Tls12(&Tls12CipherSuite {
+        common: CipherSuiteCommon {
+            suite: CipherSuite::TLS_DHE_RSA_WITH_AES_128_GCM_SHA256,
+            hash_provider: &hash::Sha256,
+            confidentiality_limit: 1 << 24,
+        },
+        kx: KeyExchangeAlgorithm::DHE,
+        sign: TLS12_RSA_SCHEMES,
+        aead_alg: &aead::TlsAesGcm(16),
+        prf_provider: &PrfUsingHmac(&hmac::Sha256Hmac),
+    });
+
+/// The TLS1.2 `TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256` cipher suite.
+pub static TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256: SupportedCipherSuite =
+    SupportedCipherSuite::Tls12(&Tls12CipherSuite {
+        common: CipherSuiteCommon {
+            suite: CipherSuite::TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+            hash_provider: &hash::Sha256,
+            confidentiality_limit: u64::MAX,
+        },
+        kx: KeyExchangeAlgorithm::DHE,
+        sign: TLS12_RSA_SCHEMES,
+        aead_alg: &aead::Chacha20Poly1305,
+        prf_provider: &PrfUsingHmac(&hmac::Sha256Hmac),
+    });
+
 static TLS12_ECDSA_SCHEMES: &[SignatureScheme] = &[
     SignatureScheme::ECDSA_NISTP521_SHA512,
     SignatureScheme::ECDSA_NISTP384_SHA384,
@@ -0,0 +1,191 @@
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use rustls::crypto::GetRandomFailed;
+use rustls::server::ProducesTickets;
+use rustls::TicketSwitcher;
+
+use graviola::aead::ChaCha20Poly1305;
+
+/// Makes the recommended ticketer, which rotates between randomly
+/// generated keys every six hours.  Tickets are accepted by the
+/// previous key for one further rotation, so have a 12 hour life.
+pub(crate) fn new() -> Result<Arc<dyn ProducesTickets>, rustls::Error> {
+    Ok(Arc::new(TicketSwitcher::new(
+        6 * 60 * 60,
+        make_ticket_generator,
+    )?))
+}
+
+fn make_ticket_generator() -> Result<Box<dyn ProducesTickets>, GetRandomFailed> {
+    Ok(Box::new(AeadTicketer::new()?))
+}
+
+/// A `ProducesTickets` implementation using ChaCha20Poly1305 with
+/// a random key.  It does not enforce any lifetime constraint.
+struct AeadTicketer {
+    key: ChaCha20Poly1305,
+    key_name: [u8; KEY_NAME_LEN],
+    lifetime: u32,
+
+    /// The largest ciphertext produced by `encrypt`, used to early-reject
+    /// `decrypt` queries that are too long.  That frustrates partitioning
+    /// oracle attacks: see <https://eprint.iacr.org/2020/1491.pdf>.
+    maximum_ciphertext_len: AtomicUsize,
+}
+
+impl AeadTicketer {
+    fn new() -> Result<Self, GetRandomFailed> {
+        let mut key = [0u8; 32];
+        graviola::random::fill(&mut key).map_err(|_| GetRandomFailed)?;
+
+        let mut key_name = [0u8; KEY_NAME_LEN];
+        graviola::random::fill(&mut key_name).map_err(|_| GetRandomFailed)?;
+
+        Ok(Self {
+            key: ChaCha20Poly1305::new(key),
+            key_name,
+            lifetime: 60 * 60 * 12,
+            maximum_ciphertext_len: AtomicUsize::new(0),
+        })
+    }
+}
+
+impl ProducesTickets for AeadTicketer {
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn lifetime(&self) -> u32 {
+        self.lifetime
+    }
+
+    fn encrypt(&self, message: &[u8]) -> Option<Vec<u8>> {
+        // Random nonce, because a counter is a privacy leak.
+        let mut nonce = [0u8; NONCE_LEN];
+        graviola::random::fill(&mut nonce).ok()?;
+
+        // ciphertext structure is:
+        // key_name: [u8; 16]
+        // nonce: [u8; 12]
+        // message: [u8, _]
+        // tag: [u8; 16]
+        let mut ciphertext = Vec::with_capacity(KEY_NAME_LEN + NONCE_LEN + message.len() + TAG_LEN);
+        ciphertext.extend_from_slice(&self.key_name);
+        ciphertext.extend_from_slice(&nonce);
+        ciphertext.extend_from_slice(message);
+
+        let mut tag = [0u8; TAG_LEN];
+        self.key.encrypt(
+            &nonce,
+            &self.key_name,
+            &mut ciphertext[KEY_NAME_LEN + NONCE_LEN..],
+            &mut tag,
+        );
+        ciphertext.extend_from_slice(&tag);
+
+        self.maximum_ciphertext_len
+            .fetch_max(ciphertext.len(), Ordering::SeqCst);
+        Some(ciphertext)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Option<Vec<u8>> {
+        if ciphertext.len() > self.maximum_ciphertext_len.load(Ordering::SeqCst)
+            || ciphertext.len() < KEY_NAME_LEN + NONCE_LEN + TAG_LEN
+        {
+            return None;
+        }
+
+        let (key_name, rest) = ciphertext.split_at(KEY_NAME_LEN);
+        let (nonce, rest) = rest.split_at(NONCE_LEN);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LEN);
+
+        // This quickly rejects tickets from another ticketer (see
+        // `TicketSwitcher`).  The key name is not secret, and is
+        // authenticated anyway as the additional data.
+        if key_name != self.key_name {
+            return None;
+        }
+
+        let mut plain = ciphertext.to_vec();
+        self.key
+            .decrypt(nonce.try_into().unwrap(), key_name, &mut plain, tag)
+            .ok()?;
+        Some(plain)
+    }
+}
+
+impl fmt::Debug for AeadTicketer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Note: we deliberately omit the key from the debug output.
+        f.debug_struct("AeadTicketer")
+            .field("lifetime", &self.lifetime)
+            .finish_non_exhaustive()
+    }
+}
+
+const KEY_NAME_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairwise() {
+        let t = new().unwrap();
+        assert!(t.enabled());
+        assert_eq!(t.lifetime(), 12 * 60 * 60);
+        let cipher = t.encrypt(b"hello world").unwrap();
+        assert_eq!(cipher.len(), KEY_NAME_LEN + NONCE_LEN + 11 + TAG_LEN);
+        assert_eq!(t.decrypt(&cipher).unwrap(), b"hello world");
+
+        // nonces are random
+        assert_ne!(t.encrypt(b"hello world").unwrap(), cipher);
+    }
+
+    #[test]
+    fn refuses_decrypt_before_encrypt() {
+        let t = new().unwrap();
+        assert_eq!(t.decrypt(b"hello"), None);
+        assert_eq!(t.decrypt(&[0u8; 64]), None);
+    }
+
+    #[test]
+    fn refuses_decrypt_larger_than_largest_encryption() {
+        let t = new().unwrap();
+        let mut cipher = t.encrypt(b"hello world").unwrap();
+        cipher.push(0);
+        assert_eq!(t.decrypt(&cipher), None);
+    }
+
+    #[test]
+    fn refuses_altered_tickets() {
+        let t = new().unwrap();
+        let cipher = t.encrypt(b"hello world").unwrap();
+        for i in 0..cipher.len() {
+            let mut altered = cipher.clone();
+            altered[i] ^= 1;
+            assert_eq!(t.decrypt(&altered), None);
+        }
+        assert_eq!(t.decrypt(&cipher[..cipher.len() - 1]), None);
+    }
+
+    #[test]
+    fn refuses_other_ticketers_tickets() {
+        let a = new().unwrap();
+        let b = new().unwrap();
+        let cipher = a.encrypt(b"hello world").unwrap();
+        b.encrypt(b"hello world").unwrap();
+        assert_eq!(b.decrypt(&cipher), None);
+    }
+
+    #[test]
+    fn ticketer_is_debug() {
+        let t = make_ticket_generator().unwrap();
+        assert_eq!(format!("{t:?}"), "AeadTicketer { lifetime: 43200, .. }");
+        assert_eq!(t.lifetime(), 43200);
+    }
+}
@@ -6,7 +6,10 @@ use webpki::alg_id;
 use graviola::hashing;
 use graviola::signing::{ecdsa, rsa};
 
-pub(crate) static ALGORITHMS: WebPkiSupportedAlgorithms = WebPkiSupportedAlgorithms {
+/// The signature verification algorithms used by [`crate::default_provider`].
+///
+/// These do not include any using SHA-1.
+pub static ALGORITHMS: WebPkiSupportedAlgorithms = WebPkiSupportedAlgorithms {
     all: &[
         ECDSA_P256_SHA512,
         ECDSA_P256_SHA384,
@@ -39,6 +42,75 @@ pub(crate) static ALGORITHMS: WebPkiSupportedAlgorithms = WebPkiSupportedAlgorit
     ],
 };
 
+/// [`ALGORITHMS`], plus RSASSA-PKCS1-v1_5 and ECDSA with SHA-1.
+///
+/// These are only suitable for verifying legacy certificates and signatures.
+/// Use like:
+///
+/// ```rust
+/// let provider = rustls::crypto::CryptoProvider {
+///     signature_verification_algorithms: rustls_graviola::verify::ALGORITHMS_WITH_SHA1,
+///     ..rustls_graviola::default_provider()
+/// };
+/// ```
+///
+/// Certificates or messages that contain a known SHA-1 collision attack
+/// will not verify.
+pub static ALGORITHMS_WITH_SHA1: WebPkiSupportedAlgorithms = WebPkiSupportedAlgorithms {
+    all: &[
+        ECDSA_P256_SHA512,
+        ECDSA_P256_SHA384,
+        ECDSA_P256_SHA256,
+        ECDSA_P256_SHA1,
+        ECDSA_P384_SHA512,
+        ECDSA_P384_SHA384,
+        ECDSA_P384_SHA256,
+        ECDSA_P384_SHA1,
+        RSA_PSS_SHA512,
+        RSA_PSS_SHA384,
+        RSA_PSS_SHA256,
+        RSA_PKCS1_SHA512,
+        RSA_PKCS1_SHA384,
+        RSA_PKCS1_SHA256,
+        RSA_PKCS1_SHA1,
+    ],
+    mapping: &[
+        (
+            SignatureScheme::ECDSA_NISTP256_SHA256,
+            &[ECDSA_P256_SHA256, ECDSA_P384_SHA256],
+        ),
+        (
+            SignatureScheme::ECDSA_NISTP384_SHA384,
+            &[ECDSA_P384_SHA384, ECDSA_P256_SHA384],
+        ),
+        (
+            SignatureScheme::ECDSA_SHA1_Legacy,
+            &[ECDSA_P256_SHA1, ECDSA_P384_SHA1],
+        ),
+        (SignatureScheme::RSA_PSS_SHA512, &[RSA_PSS_SHA512]),
+        (SignatureScheme::RSA_PSS_SHA384, &[RSA_PSS_SHA384]),
+        (SignatureScheme::RSA_PSS_SHA256, &[RSA_PSS_SHA256]),
+        (SignatureScheme::RSA_PKCS1_SHA512, &[RSA_PKCS1_SHA512]),
+        (SignatureScheme::RSA_PKCS1_SHA384, &[RSA_PKCS1_SHA384]),
+        (SignatureScheme::RSA_PKCS1_SHA256, &[RSA_PKCS1_SHA256]),
+        (SignatureScheme::RSA_PKCS1_SHA1, &[RSA_PKCS1_SHA1]),
+    ],
+};
+
+/// AlgorithmIdentifier for `ecdsa-with-SHA1`.
+const ECDSA_SHA1_ALG_ID: AlgorithmIdentifier =
+    AlgorithmIdentifier::from_slice(&[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x01]);
+
+/// AlgorithmIdentifier for `sha1WithRSAEncryption`.
+const RSA_PKCS1_SHA1_ALG_ID: AlgorithmIdentifier = AlgorithmIdentifier::from_slice(&[
+    0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x05, 0x05, 0x00,
+]);
+
+static ECDSA_P256_SHA1: &dyn SignatureVerificationAlgorithm = &EcdsaP256Verify {
+    signature_alg_id: ECDSA_SHA1_ALG_ID,
+    verify: |key, signature, message| key.verify_asn1::<hashing::Sha1>(&[message], signature),
+};
+
 static ECDSA_P256_SHA256: &dyn SignatureVerificationAlgorithm = &EcdsaP256Verify {
     signature_alg_id: alg_id::ECDSA_SHA256,
     verify: |key, signature, message| key.verify_asn1::<hashing::Sha256>(&[message], signature),
@@ -54,6 +126,11 @@ static ECDSA_P256_SHA512: &dyn SignatureVerificationAlgorithm = &EcdsaP256Verify
     verify: |key, signature, message| key.verify_asn1::<hashing::Sha512>(&[message], signature),
 };
 
+static ECDSA_P384_SHA1: &dyn SignatureVerificationAlgorithm = &EcdsaP384Verify {
+    signature_alg_id: ECDSA_SHA1_ALG_ID,
+    verify: |key, signature, message| key.verify_asn1::<hashing::Sha1>(&[message], signature),
+};
+
 static ECDSA_P384_SHA256: &dyn SignatureVerificationAlgorithm = &EcdsaP384Verify {
     signature_alg_id: alg_id::ECDSA_SHA256,
     verify: |key, signature, message| key.verify_asn1::<hashing::Sha256>(&[message], signature),
@@ -84,6 +161,11 @@ static RSA_PSS_SHA512: &dyn SignatureVerificationAlgorithm = &RsaVerify {
     verify: |key, signature, message| key.verify_pss_sha512(signature, message),
 };
 
+static RSA_PKCS1_SHA1: &dyn SignatureVerificationAlgorithm = &RsaVerify {
+    signature_alg_id: RSA_PKCS1_SHA1_ALG_ID,
+    verify: |key, signature, message| key.verify_pkcs1_sha1(signature, message),
+};
+
 static RSA_PKCS1_SHA256: &dyn SignatureVerificationAlgorithm = &RsaVerify {
     signature_alg_id: alg_id::RSA_PKCS1_SHA256,
     verify: |key, signature, message| key.verify_pkcs1_sha256(signature, message),
@@ -186,3 +268,175 @@ impl SignatureVerificationAlgorithm for EcdsaP384Verify {
             .map_err(|_| InvalidSignature)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use graviola::random::SystemRandom;
+    use graviola::x509::{
+        BasicConstraints, Certificate, ExtendedKeyUsage, Issuer, KeyUsage, NameAttribute, Signer,
+        SubjectAltName,
+    };
+    use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+
+    use super::*;
+
+    #[test]
+    fn issued_certificates_verify() {
+        let rsa_key = rsa::SigningKey::from_pkcs1_der(include_bytes!(
+            "../../graviola/src/high/rsa/rsa2048.der"
+        ))
+        .unwrap();
+        let p256_key = ecdsa::SigningKey::<ecdsa::P256> {
+            private_key: <ecdsa::P256 as ecdsa::Curve>::generate_random_key(&mut SystemRandom)
+                .unwrap(),
+        };
+        let p384_key = ecdsa::SigningKey::<ecdsa::P384> {
+            private_key: <ecdsa::P384 as ecdsa::Curve>::generate_random_key(&mut SystemRandom)
+                .unwrap(),
+        };
+
+        let mut p256_spki = [0u8; 128];
+        let p256_spki = ecdsa::VerifyingKey::<ecdsa::P256>::from_x962_uncompressed(
+            &p256_key.private_key.public_key_uncompressed(),
+        )
+        .unwrap()
+        .to_spki_der(&mut p256_spki)
+        .unwrap();
+        let mut p384_spki = [0u8; 128];
+        let p384_spki = ecdsa::VerifyingKey::<ecdsa::P384>::from_x962_uncompressed(
+            &p384_key.private_key.public_key_uncompressed(),
+        )
+        .unwrap()
+        .to_spki_der(&mut p384_spki)
+        .unwrap();
+
+        for (root_signer, intermediate, end_entity) in [
+            (
+                Signer::RsaPssSha256(&rsa_key),
+                (p384_spki, Signer::EcdsaP384Sha384(&p384_key)),
+                p256_spki,
+            ),
+            (
+                Signer::RsaPkcs1Sha256(&rsa_key),
+                (p256_spki, Signer::EcdsaP256Sha256(&p256_key)),
+                p384_spki,
+            ),
+            (
+                Signer::EcdsaP256Sha256(&p256_key),
+                (p384_spki, Signer::EcdsaP384Sha384(&p384_key)),
+                p256_spki,
+            ),
+        ] {
+            let root_name = [
+                NameAttribute::Country("GB"),
+                NameAttribute::Organization("Graviola"),
+                NameAttribute::CommonName("Graviola Test Root"),
+            ];
+            let mut root = [0u8; 2048];
+            let root = Certificate {
+                serial_number: &[0x01; 16],
+                not_before: NOT_BEFORE,
+                not_after: NOT_AFTER,
+                subject: &root_name,
+                basic_constraints: Some(BasicConstraints::Ca { path_len: None }),
+                key_usage: Some(KeyUsage::KEY_CERT_SIGN | KeyUsage::CRL_SIGN),
+                ..Default::default()
+            }
+            .self_sign(root_signer, &mut root)
+            .unwrap();
+
+            let (intermediate_spki, intermediate_signer) = intermediate;
+            let intermediate_name = [NameAttribute::CommonName("Graviola Test Intermediate")];
+            let mut intermediate = [0u8; 2048];
+            let intermediate = Certificate {
+                serial_number: &[0x02; 16],
+                not_before: NOT_BEFORE,
+                not_after: NOT_AFTER,
+                subject: &intermediate_name,
+                basic_constraints: Some(BasicConstraints::Ca { path_len: Some(0) }),
+                key_usage: Some(KeyUsage::KEY_CERT_SIGN),
+                ..Default::default()
+            }
+            .sign(
+                intermediate_spki,
+                &Issuer {
+                    name: &root_name,
+                    signer: root_signer,
+                },
+                &mut intermediate,
+            )
+            .unwrap();
+
+            let mut end_entity_cert = [0u8; 2048];
+            let end_entity_cert = Certificate {
+                serial_number: &[0x03; 16],
+                not_before: NOT_BEFORE,
+                not_after: NOT_AFTER,
+                subject_alt_names: &[
+                    SubjectAltName::DnsName("localhost"),
+                    SubjectAltName::IpAddress(&[127, 0, 0, 1]),
+                ],
+                basic_constraints: Some(BasicConstraints::EndEntity),
+                key_usage: Some(KeyUsage::DIGITAL_SIGNATURE),
+                extended_key_usage: &[ExtendedKeyUsage::ServerAuth],
+                ..Default::default()
+            }
+            .sign(
+                end_entity,
+                &Issuer {
+                    name: &intermediate_name,
+                    signer: intermediate_signer,
+                },
+                &mut end_entity_cert,
+            )
+            .unwrap();
+
+            let root = CertificateDer::from(root);
+            let anchors = [webpki::anchor_from_trusted_cert(&root).unwrap()];
+            let intermediates = [CertificateDer::from(intermediate)];
+            let end_entity = CertificateDer::from(end_entity_cert);
+            let end_entity = webpki::EndEntityCert::try_from(&end_entity).unwrap();
+
+            let verify = |secs, usage| {
+                end_entity
+                    .verify_for_usage(
+                        ALGORITHMS.all,
+                        &anchors,
+                        &intermediates,
+                        UnixTime::since_unix_epoch(Duration::from_secs(secs)),
+                        usage,
+                        None,
+                        None,
+                    )
+                    .map(|_| ())
+            };
+
+            verify(NOT_BEFORE + 1, webpki::KeyUsage::server_auth()).unwrap();
+            assert_eq!(
+                verify(NOT_AFTER + 1, webpki::KeyUsage::server_auth()),
+                Err(webpki::Error::CertExpired)
+            );
+            assert_eq!(
+                verify(NOT_BEFORE + 1, webpki::KeyUsage::client_auth()),
+                Err(webpki::Error::RequiredEkuNotFound)
+            );
+
+            for name in ["localhost", "127.0.0.1"] {
+                end_entity
+                    .verify_is_valid_for_subject_name(&ServerName::try_from(name).unwrap())
+                    .unwrap();
+            }
+            assert_eq!(
+                end_entity.verify_is_valid_for_subject_name(
+                    &ServerName::try_from("example.com").unwrap()
+                ),
+                Err(webpki::Error::CertNotValidForName)
+            );
+        }
+    }
+
+    const NOT_BEFORE: u64 = 1_700_000_000;
+    const NOT_AFTER: u64 = NOT_BEFORE + 86_400;
+}